@@ -23,6 +23,9 @@ import android.util.Log;
 
 import com.android.uwb.resources.R;
 
+import java.util.ArrayList;
+import java.util.List;
+
 /**
  * This class allows getting all configurable flags from DeviceConfig.
  */
@@ -36,6 +39,11 @@ public class DeviceConfigFacade {
     public static final int DEFAULT_RANGING_RESULT_LOG_INTERVAL_MS = 5_000;
     private static final int MS_IN_HOUR = 60 * 60 * 1000;
     public static final int DEFAULT_BUG_REPORT_MIN_INTERVAL_MS = 24 * MS_IN_HOUR;
+    // Values must match BackpressurePolicy of the native stack.
+    public static final int NOTIFICATION_BACKPRESSURE_POLICY_UNBOUNDED = 0;
+    public static final int NOTIFICATION_BACKPRESSURE_POLICY_DROP_OLDEST = 1;
+    public static final int NOTIFICATION_BACKPRESSURE_POLICY_DROP_NEWEST = 2;
+    public static final int DEFAULT_NOTIFICATION_QUEUE_CAPACITY = 64;
     private static final String TAG = "DeviceConfigFacadeUwb";
 
     public enum PoseSourceType {
@@ -102,6 +110,23 @@ public class DeviceConfigFacade {
     private boolean mPersistentCacheUseForCountryCodeEnabled;
     private boolean mHwIdleTurnOffEnabled;
     private boolean mIsAntennaModeConfigSupported;
+    // Flag to coordinate the initiator sessions with the concurrent responder sessions.
+    private boolean mDualRoleCoordinationEnabled;
+    private int mLinkQualityThresholdPercent;
+    // Flags gating the rollout of features of the native stack.
+    private boolean mNativeControleeBatchingEnabled;
+    private boolean mNativeProximityFastPathEnabled;
+    private boolean mNativeAntennaPairTaggingEnabled;
+    private boolean mNativeRangingAdvisorEnabled;
+    private boolean mNativeMetricsExporterEnabled;
+    private boolean mNativeLatencyTracingEnabled;
+    private boolean mNativeErrorTriggeredUciLogEnabled;
+    private int mNotificationBackpressurePolicy;
+    private int mNotificationQueueCapacity;
+    private boolean mRegulatoryAutoSuspendEnabled;
+    private PeerVendorClassifier mPeerVendorClassifier;
+    private int mSessionHealthCheckIntervalMs;
+    private int[] mUciInjectionGidAllowlist;
 
     public DeviceConfigFacade(Handler handler, Context context) {
         mContext = context;
@@ -321,6 +346,45 @@ public class DeviceConfigFacade {
                 mContext.getResources().getBoolean(R.bool.is_antenna_mode_config_supported)
         );
 
+        mDualRoleCoordinationEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "dual_role_coordination_enabled", false);
+        mLinkQualityThresholdPercent = DeviceConfig.getInt(DeviceConfig.NAMESPACE_UWB,
+                "link_quality_threshold_percent", 0);
+        mNativeControleeBatchingEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "native_controlee_batching_enabled", false);
+        mNativeProximityFastPathEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "native_proximity_fast_path_enabled", false);
+        mNativeAntennaPairTaggingEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "native_antenna_pair_tagging_enabled", false);
+        mNativeRangingAdvisorEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "native_ranging_advisor_enabled", false);
+        mNativeMetricsExporterEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "native_metrics_exporter_enabled", false);
+        mNativeLatencyTracingEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "native_latency_tracing_enabled", false);
+        mNativeErrorTriggeredUciLogEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "native_error_triggered_uci_log_enabled", false);
+        mNotificationBackpressurePolicy = DeviceConfig.getInt(DeviceConfig.NAMESPACE_UWB,
+                "notification_backpressure_policy", NOTIFICATION_BACKPRESSURE_POLICY_UNBOUNDED);
+        if (mNotificationBackpressurePolicy < NOTIFICATION_BACKPRESSURE_POLICY_UNBOUNDED
+                || mNotificationBackpressurePolicy > NOTIFICATION_BACKPRESSURE_POLICY_DROP_NEWEST) {
+            Log.w(TAG, "Unknown notification backpressure policy "
+                    + mNotificationBackpressurePolicy + ", notifications are not queued");
+            mNotificationBackpressurePolicy = NOTIFICATION_BACKPRESSURE_POLICY_UNBOUNDED;
+        }
+        mNotificationQueueCapacity = DeviceConfig.getInt(DeviceConfig.NAMESPACE_UWB,
+                "notification_queue_capacity", DEFAULT_NOTIFICATION_QUEUE_CAPACITY);
+        mRegulatoryAutoSuspendEnabled = DeviceConfig.getBoolean(DeviceConfig.NAMESPACE_UWB,
+                "regulatory_auto_suspend_enabled", false);
+        mPeerVendorClassifier = PeerVendorClassifier.fromConfig(
+                DeviceConfig.getString(DeviceConfig.NAMESPACE_UWB, "peer_vendor_oui_table", ""),
+                DeviceConfig.getString(DeviceConfig.NAMESPACE_UWB,
+                        "peer_vendor_aoa_azimuth_sign_flip", ""));
+        mSessionHealthCheckIntervalMs = DeviceConfig.getInt(DeviceConfig.NAMESPACE_UWB,
+                "session_health_check_interval_ms", 0);
+        mUciInjectionGidAllowlist = parseGidList(DeviceConfig.getString(
+                DeviceConfig.NAMESPACE_UWB, "uci_injection_gid_allowlist", ""));
+
         // A little parsing and cleanup:
         mFrontAzimuthRadiansPerSecond = (float) Math.toRadians(frontAzimuthDegreesPerSecond);
         mBackAzimuthRadiansPerSecond = (float) Math.toRadians(backAzimuthDegreesPerSecond);
@@ -630,4 +694,137 @@ public class DeviceConfigFacade {
      * Returns whether antenna mode configuration is supported or not.
      */
     public boolean isAntennaModeConfigSupported() { return mIsAntennaModeConfigSupported; }
+
+    /**
+     * Returns whether the initiator sessions are offset past the ranging rounds of the concurrent
+     * responder sessions, with the conflicts between the two roles reported.
+     */
+    public boolean isDualRoleCoordinationEnabled() {
+        return mDualRoleCoordinationEnabled;
+    }
+
+    /**
+     * Gets the ranging success rate of a controlee under which its link is reported as flaky, in
+     * percent. 0 disables the reports.
+     */
+    public int getLinkQualityThresholdPercent() {
+        return mLinkQualityThresholdPercent;
+    }
+
+    /**
+     * Returns whether the native stack splits large controlee lists into batches.
+     */
+    public boolean isNativeControleeBatchingEnabled() {
+        return mNativeControleeBatchingEnabled;
+    }
+
+    /**
+     * Returns whether the native stack reports only the near/far edges of the proximity-only
+     * sessions.
+     */
+    public boolean isNativeProximityFastPathEnabled() {
+        return mNativeProximityFastPathEnabled;
+    }
+
+    /**
+     * Returns whether the native stack tags the two-way measurements with the antenna pair
+     * reported by the UWBS.
+     */
+    public boolean isNativeAntennaPairTaggingEnabled() {
+        return mNativeAntennaPairTaggingEnabled;
+    }
+
+    /**
+     * Returns whether the native stack suggests longer ranging intervals to the sessions missing
+     * rounds or starved of data credits.
+     */
+    public boolean isNativeRangingAdvisorEnabled() {
+        return mNativeRangingAdvisorEnabled;
+    }
+
+    /**
+     * Returns whether the native stack may serve the metrics of the chips in the Prometheus text
+     * format over a local TCP port.
+     */
+    public boolean isNativeMetricsExporterEnabled() {
+        return mNativeMetricsExporterEnabled;
+    }
+
+    /**
+     * Returns whether the native stack writes ATrace markers at the stages the notifications go
+     * through, from the HAL read to the return of the Java callback.
+     */
+    public boolean isNativeLatencyTracingEnabled() {
+        return mNativeLatencyTracingEnabled;
+    }
+
+    /**
+     * Returns whether the native stack holds the UCI packets while the UCI log is disabled, and
+     * writes them to the log file from the first command failure or protocol error, for a bounded
+     * duration.
+     */
+    public boolean isNativeErrorTriggeredUciLogEnabled() {
+        return mNativeErrorTriggeredUciLogEnabled;
+    }
+
+    /**
+     * Gets the policy applied by the native stack when its notification queue is full, one of
+     * NOTIFICATION_BACKPRESSURE_POLICY_*.
+     */
+    public int getNotificationBackpressurePolicy() {
+        return mNotificationBackpressurePolicy;
+    }
+
+    /**
+     * Gets the maximum number of notifications queued by the native stack.
+     */
+    public int getNotificationQueueCapacity() {
+        return mNotificationQueueCapacity;
+    }
+
+    /**
+     * Returns whether the active sessions ranging on a channel which a new country code no longer
+     * allows are stopped, instead of only being reported.
+     */
+    public boolean isRegulatoryAutoSuspendEnabled() {
+        return mRegulatoryAutoSuspendEnabled;
+    }
+
+    /**
+     * Gets the classifier of the peers by vendor, from the OUI table of the device config.
+     */
+    public PeerVendorClassifier getPeerVendorClassifier() {
+        return mPeerVendorClassifier;
+    }
+
+    /**
+     * Gets the interval between two comparisons of the sessions tracked by the native stack with
+     * the UWBS's view of them, 0 if disabled.
+     */
+    public int getSessionHealthCheckIntervalMs() {
+        return mSessionHealthCheckIntervalMs;
+    }
+
+    /**
+     * Gets the GIDs of the raw UCI messages which may be injected on production builds.
+     */
+    public int[] getUciInjectionGidAllowlist() {
+        return mUciInjectionGidAllowlist;
+    }
+
+    /** Parses a comma-separated list of GIDs in hex, e.g. "9,e", skipping the malformed ones. */
+    private static int[] parseGidList(String gids) {
+        List<Integer> parsedGids = new ArrayList<>();
+        for (String gid : gids.split(",")) {
+            if (gid.trim().isEmpty()) {
+                continue;
+            }
+            try {
+                parsedGids.add(Integer.parseInt(gid.trim(), 16));
+            } catch (NumberFormatException e) {
+                Log.w(TAG, "Skipping malformed GID: " + gid);
+            }
+        }
+        return parsedGids.stream().mapToInt(Integer::intValue).toArray();
+    }
 }
@@ -0,0 +1,185 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb;
+
+import android.annotation.NonNull;
+
+import com.android.internal.annotations.VisibleForTesting;
+
+import com.google.uwb.support.fira.FiraOpenSessionParams;
+import com.google.uwb.support.fira.FiraParams;
+
+import java.util.ArrayList;
+import java.util.List;
+
+/**
+ * Coordinates the FiRa sessions in which the device is the initiator with the ones in which it is
+ * the responder, which share the air time of the chip.
+ *
+ * <p>The blocks of a new initiator session are offset past the ranging rounds of the responder
+ * sessions, whose timing is set by their remote controller. The combined air time of the two
+ * roles is validated, and the conflicts found are reported as warnings.
+ */
+public class DualRoleScheduler {
+    /** The sessions combined use more air time than available. */
+    public static final int CONFLICT_AIRTIME_EXCEEDED = 1;
+    /**
+     * The block intervals of two sessions of different roles are not multiples of each other, so
+     * their ranging rounds drift into each other.
+     */
+    public static final int CONFLICT_INTERVAL_MISMATCH = 2;
+
+    /** No other session is involved in the conflict. */
+    public static final long NO_SESSION_ID = -1;
+
+    private static final int RSTU_PER_MS = 1200;
+    /** Share of the air time the sessions may use combined, in percent. */
+    @VisibleForTesting
+    static final int MAX_AIRTIME_PERCENT = 90;
+
+    /** Air time of a FiRa session. */
+    public static class SessionTiming {
+        public final long sessionId;
+        public final boolean isInitiator;
+        /** Duration of the ranging round, in microseconds. */
+        public final long roundDurationUs;
+        /** Interval between the ranging rounds, including the block stride, in microseconds. */
+        public final long blockIntervalUs;
+
+        public SessionTiming(long sessionId, boolean isInitiator, long roundDurationUs,
+                long blockIntervalUs) {
+            this.sessionId = sessionId;
+            this.isInitiator = isInitiator;
+            this.roundDurationUs = roundDurationUs;
+            this.blockIntervalUs = blockIntervalUs;
+        }
+
+        /** Gets the air time of the session from its parameters. */
+        public static SessionTiming fromParams(long sessionId,
+                @NonNull FiraOpenSessionParams params) {
+            long roundDurationUs = (long) params.getSlotDurationRstu()
+                    * params.getSlotsPerRangingRound() * 1000 / RSTU_PER_MS;
+            long blockIntervalUs = (long) params.getRangingIntervalMs()
+                    * (params.getBlockStrideLength() + 1) * 1000;
+            return new SessionTiming(sessionId,
+                    params.getDeviceRole() == FiraParams.RANGING_DEVICE_ROLE_INITIATOR,
+                    roundDurationUs, blockIntervalUs);
+        }
+    }
+
+    /** A conflict between the sessions of the two roles. */
+    public static class Conflict {
+        public final int type;
+        public final long sessionId;
+        public final long otherSessionId;
+
+        public Conflict(int type, long sessionId, long otherSessionId) {
+            this.type = type;
+            this.sessionId = sessionId;
+            this.otherSessionId = otherSessionId;
+        }
+
+        @Override
+        public boolean equals(Object o) {
+            if (!(o instanceof Conflict)) {
+                return false;
+            }
+            Conflict other = (Conflict) o;
+            return type == other.type && sessionId == other.sessionId
+                    && otherSessionId == other.otherSessionId;
+        }
+
+        @Override
+        public int hashCode() {
+            return Long.hashCode(sessionId) * 31 + Long.hashCode(otherSessionId) * 7 + type;
+        }
+
+        @Override
+        public String toString() {
+            return "Conflict: type=" + type + ", sessionId=" + sessionId
+                    + ", otherSessionId=" + otherSessionId;
+        }
+    }
+
+    /** How a new session is scheduled next to the sessions of the other role. */
+    public static class Plan {
+        /** Offset added to the initiation time of the new session, in milliseconds. */
+        public final long initiationTimeOffsetMs;
+        public final List<Conflict> conflicts;
+
+        public Plan(long initiationTimeOffsetMs, List<Conflict> conflicts) {
+            this.initiationTimeOffsetMs = initiationTimeOffsetMs;
+            this.conflicts = conflicts;
+        }
+    }
+
+    private DualRoleScheduler() {}
+
+    /**
+     * Plans a new session next to the sessions already ranging on the same chip.
+     *
+     * @param newSession : the session about to be configured
+     * @param activeSessions : the other FiRa sessions ranging on the chip
+     */
+    public static Plan plan(@NonNull SessionTiming newSession,
+            @NonNull List<SessionTiming> activeSessions) {
+        List<Conflict> conflicts = new ArrayList<>();
+        long otherRoleDurationUs = 0;
+        boolean hasOtherRole = false;
+        for (SessionTiming session : activeSessions) {
+            if (session.isInitiator == newSession.isInitiator) {
+                continue;
+            }
+            hasOtherRole = true;
+            otherRoleDurationUs += session.roundDurationUs;
+            if (!areIntervalsAligned(newSession.blockIntervalUs, session.blockIntervalUs)) {
+                conflicts.add(new Conflict(CONFLICT_INTERVAL_MISMATCH, newSession.sessionId,
+                        session.sessionId));
+            }
+        }
+        if (!hasOtherRole) {
+            return new Plan(0, conflicts);
+        }
+
+        // Air time used per second, in microseconds.
+        long airtimeUs = getAirtimeUsPerSecond(newSession);
+        for (SessionTiming session : activeSessions) {
+            airtimeUs += getAirtimeUsPerSecond(session);
+        }
+        if (airtimeUs * 100 > 1_000_000L * MAX_AIRTIME_PERCENT) {
+            conflicts.add(new Conflict(CONFLICT_AIRTIME_EXCEEDED, newSession.sessionId,
+                    NO_SESSION_ID));
+        }
+
+        // Only the initiator sessions are scheduled by this device.
+        long offsetMs = newSession.isInitiator ? (otherRoleDurationUs + 999) / 1000 : 0;
+        return new Plan(offsetMs, conflicts);
+    }
+
+    private static boolean areIntervalsAligned(long intervalUs, long otherIntervalUs) {
+        if (intervalUs <= 0 || otherIntervalUs <= 0) {
+            return true;
+        }
+        return Math.max(intervalUs, otherIntervalUs) % Math.min(intervalUs, otherIntervalUs) == 0;
+    }
+
+    private static long getAirtimeUsPerSecond(SessionTiming session) {
+        if (session.blockIntervalUs <= 0) {
+            return 0;
+        }
+        return session.roundDurationUs * 1_000_000L / session.blockIntervalUs;
+    }
+}
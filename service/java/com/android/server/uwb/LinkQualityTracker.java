@@ -0,0 +1,187 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb;
+
+import android.annotation.NonNull;
+import android.os.PersistableBundle;
+
+import com.android.internal.annotations.VisibleForTesting;
+
+/**
+ * Aggregates the two-way measurements of a controlee over the last ranging rounds into a link
+ * quality report, so that flaky peers can be spotted before the session fails.
+ */
+public class LinkQualityTracker {
+    /** Number of ranging rounds the link quality is computed over. */
+    @VisibleForTesting
+    static final int WINDOW_SIZE = 20;
+
+    /** Metadata keys of the link quality, added to the measurement crossing the threshold. */
+    public static final String KEY_SUCCESS_RATE_PERCENT = "link_quality_success_rate_percent";
+    public static final String KEY_AVERAGE_RSSI_DBM = "link_quality_average_rssi_dbm";
+    public static final String KEY_FOM_TREND = "link_quality_fom_trend";
+    public static final String KEY_BELOW_THRESHOLD = "link_quality_below_threshold";
+
+    /** Value of the RSSI when it was not reported. */
+    public static final int RSSI_UNKNOWN = 0;
+
+    /** Link quality of a controlee over the last ranging rounds. */
+    public static class LinkQuality {
+        /** Number of ranging rounds the link quality is computed over. */
+        public final int roundCount;
+        /** Share of the rounds whose measurement succeeded, in percent. */
+        public final int successRatePercent;
+        /** Average RSSI of the rounds reporting it, or RSSI_UNKNOWN. */
+        public final int averageRssiDbm;
+        /**
+         * Average FOM of the newer half of the successful rounds minus the one of the older
+         * half. Negative when the measurements degrade.
+         */
+        public final int fomTrend;
+
+        public LinkQuality(int roundCount, int successRatePercent, int averageRssiDbm,
+                int fomTrend) {
+            this.roundCount = roundCount;
+            this.successRatePercent = successRatePercent;
+            this.averageRssiDbm = averageRssiDbm;
+            this.fomTrend = fomTrend;
+        }
+
+        /** Gets the link quality as ranging measurement metadata. */
+        public PersistableBundle toBundle(boolean belowThreshold) {
+            PersistableBundle bundle = new PersistableBundle();
+            bundle.putInt(KEY_SUCCESS_RATE_PERCENT, successRatePercent);
+            bundle.putInt(KEY_AVERAGE_RSSI_DBM, averageRssiDbm);
+            bundle.putInt(KEY_FOM_TREND, fomTrend);
+            bundle.putBoolean(KEY_BELOW_THRESHOLD, belowThreshold);
+            return bundle;
+        }
+
+        @Override
+        public String toString() {
+            return "LinkQuality: roundCount=" + roundCount
+                    + ", successRatePercent=" + successRatePercent
+                    + ", averageRssiDbm=" + averageRssiDbm
+                    + ", fomTrend=" + fomTrend;
+        }
+    }
+
+    private final int mThresholdPercent;
+    private final boolean[] mSuccess = new boolean[WINDOW_SIZE];
+    private final int[] mRssiDbm = new int[WINDOW_SIZE];
+    private final int[] mFom = new int[WINDOW_SIZE];
+    private int mNext;
+    private int mCount;
+    private boolean mBelowThreshold;
+
+    /**
+     * Creates a new LinkQualityTracker.
+     *
+     * @param thresholdPercent : success rate under which the link is reported as flaky, or 0 to
+     *                         not report the threshold crossings
+     */
+    public LinkQualityTracker(int thresholdPercent) {
+        mThresholdPercent = thresholdPercent;
+    }
+
+    /**
+     * Adds the measurement of a ranging round.
+     *
+     * @param success : whether the measurement succeeded
+     * @param rssiDbm : RSSI of the measurement, or RSSI_UNKNOWN
+     * @param fom : figure of merit of the measurement, from 0 to 100
+     * @return true if the success rate crossed the threshold, in either direction
+     */
+    public synchronized boolean onRound(boolean success, int rssiDbm, int fom) {
+        mSuccess[mNext] = success;
+        mRssiDbm[mNext] = rssiDbm;
+        mFom[mNext] = fom;
+        mNext = (mNext + 1) % WINDOW_SIZE;
+        mCount = Math.min(mCount + 1, WINDOW_SIZE);
+
+        // Only judge the link once the window is filled.
+        if (mThresholdPercent <= 0 || mCount < WINDOW_SIZE) {
+            return false;
+        }
+        boolean belowThreshold = getSuccessRatePercent() < mThresholdPercent;
+        if (belowThreshold == mBelowThreshold) {
+            return false;
+        }
+        mBelowThreshold = belowThreshold;
+        return true;
+    }
+
+    /** Returns whether the success rate is under the threshold. */
+    public synchronized boolean isBelowThreshold() {
+        return mBelowThreshold;
+    }
+
+    /** Gets the link quality over the last ranging rounds. */
+    @NonNull
+    public synchronized LinkQuality getLinkQuality() {
+        int rssiSum = 0;
+        int rssiCount = 0;
+        for (int i = 0; i < mCount; i++) {
+            int index = getIndex(i);
+            if (mRssiDbm[index] != RSSI_UNKNOWN) {
+                rssiSum += mRssiDbm[index];
+                rssiCount++;
+            }
+        }
+        return new LinkQuality(mCount, getSuccessRatePercent(),
+                rssiCount == 0 ? RSSI_UNKNOWN : rssiSum / rssiCount, getFomTrend());
+    }
+
+    private int getSuccessRatePercent() {
+        if (mCount == 0) {
+            return 0;
+        }
+        int successCount = 0;
+        for (int i = 0; i < mCount; i++) {
+            if (mSuccess[getIndex(i)]) {
+                successCount++;
+            }
+        }
+        return successCount * 100 / mCount;
+    }
+
+    private int getFomTrend() {
+        int[] foms = new int[mCount];
+        int fomCount = 0;
+        for (int i = 0; i < mCount; i++) {
+            int index = getIndex(i);
+            if (mSuccess[index]) {
+                foms[fomCount++] = mFom[index];
+            }
+        }
+        if (fomCount < 2) {
+            return 0;
+        }
+        int half = fomCount / 2;
+        int olderSum = 0;
+        int newerSum = 0;
+        for (int i = 0; i < half; i++) {
+            olderSum += foms[i];
+            newerSum += foms[fomCount - half + i];
+        }
+        return (newerSum - olderSum) / half;
+    }
+
+    /** Gets the index of the i-th oldest round of the window. */
+    private int getIndex(int i) {
+        return (mNext - mCount + i + WINDOW_SIZE) % WINDOW_SIZE;
+    }
+}
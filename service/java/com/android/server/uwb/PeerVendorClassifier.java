@@ -0,0 +1,108 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb;
+
+import android.annotation.NonNull;
+import android.annotation.Nullable;
+import android.text.TextUtils;
+import android.util.Log;
+import android.uwb.UwbAddress;
+
+import java.util.HashMap;
+import java.util.HashSet;
+import java.util.Map;
+import java.util.Set;
+
+/**
+ * Classifies the peers by vendor from the OUI of their extended MAC address, so that the interop
+ * workarounds of a vendor, e.g. an inverted AoA azimuth, are applied to its devices only.
+ *
+ * <p>The OUI is the first three octets of the address, as it is reported to the apps. The peers
+ * using short MAC addresses cannot be classified.
+ */
+public class PeerVendorClassifier {
+    private static final String TAG = "PeerVendorClassifier";
+
+    /** Metadata key of the vendor of the peer, added to its measurements. */
+    public static final String KEY_PEER_VENDOR = "peer_vendor";
+
+    private static final int OUI_LENGTH = 3;
+
+    private final Map<Integer, String> mOuiToVendor;
+    private final Set<String> mAoaAzimuthSignFlipVendors;
+
+    public PeerVendorClassifier(@NonNull Map<Integer, String> ouiToVendor,
+            @NonNull Set<String> aoaAzimuthSignFlipVendors) {
+        mOuiToVendor = Map.copyOf(ouiToVendor);
+        mAoaAzimuthSignFlipVendors = Set.copyOf(aoaAzimuthSignFlipVendors);
+    }
+
+    /**
+     * Creates a classifier from its device config.
+     *
+     * @param ouiTable comma-separated list of {@code <OUI in hex>:<vendor>} entries, e.g.
+     *                 {@code "0025DF:vendor1,70B3D5:vendor2"}. Malformed entries are skipped.
+     * @param aoaAzimuthSignFlipVendors comma-separated list of the vendors whose devices report
+     *                                  the AoA azimuth with an inverted sign
+     */
+    @NonNull
+    public static PeerVendorClassifier fromConfig(@Nullable String ouiTable,
+            @Nullable String aoaAzimuthSignFlipVendors) {
+        Map<Integer, String> ouiToVendor = new HashMap<>();
+        if (!TextUtils.isEmpty(ouiTable)) {
+            for (String entry : ouiTable.split(",")) {
+                String[] ouiAndVendor = entry.trim().split(":");
+                if (ouiAndVendor.length != 2 || ouiAndVendor[0].length() != 2 * OUI_LENGTH
+                        || ouiAndVendor[1].isEmpty()) {
+                    Log.w(TAG, "Skipping malformed OUI table entry: " + entry);
+                    continue;
+                }
+                try {
+                    ouiToVendor.put(Integer.parseInt(ouiAndVendor[0], 16), ouiAndVendor[1]);
+                } catch (NumberFormatException e) {
+                    Log.w(TAG, "Skipping malformed OUI table entry: " + entry);
+                }
+            }
+        }
+        Set<String> signFlipVendors = new HashSet<>();
+        if (!TextUtils.isEmpty(aoaAzimuthSignFlipVendors)) {
+            for (String vendor : aoaAzimuthSignFlipVendors.split(",")) {
+                if (!vendor.trim().isEmpty()) {
+                    signFlipVendors.add(vendor.trim());
+                }
+            }
+        }
+        return new PeerVendorClassifier(ouiToVendor, signFlipVendors);
+    }
+
+    /**
+     * Gets the vendor of the peer, or null if its address is short or its OUI is unknown.
+     */
+    @Nullable
+    public String classify(@NonNull UwbAddress address) {
+        if (address.size() != UwbAddress.EXTENDED_ADDRESS_BYTE_LENGTH) {
+            return null;
+        }
+        byte[] bytes = address.toBytes();
+        int oui = ((bytes[0] & 0xFF) << 16) | ((bytes[1] & 0xFF) << 8) | (bytes[2] & 0xFF);
+        return mOuiToVendor.get(oui);
+    }
+
+    /** Returns whether the devices of the vendor report the AoA azimuth with an inverted sign. */
+    public boolean hasAoaAzimuthSignFlip(@Nullable String vendor) {
+        return vendor != null && mAoaAzimuthSignFlipVendors.contains(vendor);
+    }
+}
@@ -0,0 +1,80 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb;
+
+import android.annotation.NonNull;
+import android.annotation.Nullable;
+
+import java.util.List;
+
+/**
+ * The active sessions of a chip ranging on a channel which the regulation of a new country code
+ * no longer allows, found when the country code is set in the UWBS.
+ */
+public class RegulatoryImpact {
+    private final String mChipId;
+    @Nullable private final String mCountryCode;
+    private final List<Integer> mAllowedChannels;
+    private final List<Integer> mAffectedSessionIds;
+    private final boolean mSuspended;
+
+    public RegulatoryImpact(@NonNull String chipId, @Nullable String countryCode,
+            @NonNull List<Integer> allowedChannels, @NonNull List<Integer> affectedSessionIds,
+            boolean suspended) {
+        mChipId = chipId;
+        mCountryCode = countryCode;
+        mAllowedChannels = List.copyOf(allowedChannels);
+        mAffectedSessionIds = List.copyOf(affectedSessionIds);
+        mSuspended = suspended;
+    }
+
+    @NonNull
+    public String getChipId() {
+        return mChipId;
+    }
+
+    @Nullable
+    public String getCountryCode() {
+        return mCountryCode;
+    }
+
+    /** Gets the channels supported by the chip under the new country code. */
+    @NonNull
+    public List<Integer> getAllowedChannels() {
+        return mAllowedChannels;
+    }
+
+    /** Gets the IDs of the active sessions ranging on a channel no longer allowed. */
+    @NonNull
+    public List<Integer> getAffectedSessionIds() {
+        return mAffectedSessionIds;
+    }
+
+    /** Returns whether the affected sessions were stopped. */
+    public boolean isSuspended() {
+        return mSuspended;
+    }
+
+    @Override
+    public String toString() {
+        return "RegulatoryImpact { chipId = " + mChipId
+                + ", countryCode = " + mCountryCode
+                + ", allowedChannels = " + mAllowedChannels
+                + ", affectedSessionIds = " + mAffectedSessionIds
+                + ", suspended = " + mSuspended
+                + " }";
+    }
+}
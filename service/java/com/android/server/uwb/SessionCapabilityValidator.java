@@ -0,0 +1,128 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb;
+
+import android.annotation.NonNull;
+import android.annotation.Nullable;
+
+import com.google.uwb.support.fira.FiraOpenSessionParams;
+import com.google.uwb.support.fira.FiraParams;
+import com.google.uwb.support.fira.FiraParams.MultiNodeCapabilityFlag;
+import com.google.uwb.support.fira.FiraParams.StsCapabilityFlag;
+import com.google.uwb.support.fira.FiraSpecificationParams;
+import com.google.uwb.support.generic.GenericSpecificationParams;
+
+import java.util.EnumSet;
+import java.util.List;
+
+/**
+ * Checks the session parameters against the capabilities of the chip before the session is
+ * initialized, so that a feature the chip lacks is reported with a descriptive reason rather than
+ * an opaque UCI status.
+ *
+ * <p>The capabilities the chip did not report are not checked.
+ */
+public final class SessionCapabilityValidator {
+    private SessionCapabilityValidator() {}
+
+    /**
+     * Returns why the chip cannot run the FiRa session, or null if it can, or if its
+     * capabilities are unknown.
+     */
+    @Nullable
+    public static String validateFiraSession(@NonNull FiraOpenSessionParams params,
+            @Nullable GenericSpecificationParams specificationParams) {
+        if (specificationParams == null
+                || specificationParams.getFiraSpecificationParams() == null) {
+            return null;
+        }
+        FiraSpecificationParams firaSpecificationParams =
+                specificationParams.getFiraSpecificationParams();
+
+        List<Integer> supportedChannels = firaSpecificationParams.getSupportedChannels();
+        if (supportedChannels != null && !supportedChannels.isEmpty()
+                && !supportedChannels.contains(params.getChannelNumber())) {
+            return "Channel " + params.getChannelNumber() + " is not supported, supported"
+                    + " channels: " + supportedChannels;
+        }
+        if (params.getMacAddressMode() != FiraParams.MAC_ADDRESS_MODE_2_BYTES
+                && !firaSpecificationParams.hasExtendedMacAddressSupport()) {
+            return "Extended MAC addresses are not supported";
+        }
+        MultiNodeCapabilityFlag multiNodeFlag = getMultiNodeCapabilityFlag(
+                params.getMultiNodeMode());
+        if (!isSupported(firaSpecificationParams.getMultiNodeCapabilities(), multiNodeFlag)) {
+            return "Multi node mode " + params.getMultiNodeMode() + " is not supported";
+        }
+        StsCapabilityFlag stsFlag = getStsCapabilityFlag(params.getStsConfig());
+        if (!isSupported(firaSpecificationParams.getStsCapabilities(), stsFlag)) {
+            return "STS config " + params.getStsConfig() + " is not supported";
+        }
+        return null;
+    }
+
+    /**
+     * Returns why the chip cannot run a radar session, or null if it can, or if its capabilities
+     * are unknown.
+     */
+    @Nullable
+    public static String validateRadarSession(
+            @Nullable GenericSpecificationParams specificationParams) {
+        if (specificationParams != null
+                && specificationParams.getRadarSpecificationParams() == null) {
+            return "Radar is not supported";
+        }
+        return null;
+    }
+
+    private static <E extends Enum<E>> boolean isSupported(@Nullable EnumSet<E> capabilities,
+            @Nullable E flag) {
+        return flag == null || capabilities == null || capabilities.isEmpty()
+                || capabilities.contains(flag);
+    }
+
+    @Nullable
+    private static MultiNodeCapabilityFlag getMultiNodeCapabilityFlag(int multiNodeMode) {
+        switch (multiNodeMode) {
+            case FiraParams.MULTI_NODE_MODE_UNICAST:
+                return MultiNodeCapabilityFlag.HAS_UNICAST_SUPPORT;
+            case FiraParams.MULTI_NODE_MODE_ONE_TO_MANY:
+                return MultiNodeCapabilityFlag.HAS_ONE_TO_MANY_SUPPORT;
+            case FiraParams.MULTI_NODE_MODE_MANY_TO_MANY:
+                return MultiNodeCapabilityFlag.HAS_MANY_TO_MANY_SUPPORT;
+            default:
+                return null;
+        }
+    }
+
+    @Nullable
+    private static StsCapabilityFlag getStsCapabilityFlag(int stsConfig) {
+        switch (stsConfig) {
+            case FiraParams.STS_CONFIG_STATIC:
+                return StsCapabilityFlag.HAS_STATIC_STS_SUPPORT;
+            case FiraParams.STS_CONFIG_DYNAMIC:
+                return StsCapabilityFlag.HAS_DYNAMIC_STS_SUPPORT;
+            case FiraParams.STS_CONFIG_DYNAMIC_FOR_CONTROLEE_INDIVIDUAL_KEY:
+                return StsCapabilityFlag.HAS_DYNAMIC_STS_INDIVIDUAL_CONTROLEE_KEY_SUPPORT;
+            case FiraParams.STS_CONFIG_PROVISIONED:
+                return StsCapabilityFlag.HAS_PROVISIONED_STS_SUPPORT;
+            case FiraParams.STS_CONFIG_PROVISIONED_FOR_CONTROLEE_INDIVIDUAL_KEY:
+                return StsCapabilityFlag.HAS_PROVISIONED_STS_INDIVIDUAL_CONTROLEE_KEY_SUPPORT;
+            default:
+                return null;
+        }
+    }
+}
@@ -57,7 +57,7 @@ public class UwbConfigurationManager {
             return status;
         }
 
-        tlvBuffer = encoder.getTlvBuffer(params, protocolVersion);
+        tlvBuffer = encoder.getTlvBuffer(params, protocolVersion, chipId);
 
         if (tlvBuffer.getNoOfParams() != 0) {
             byte[] tlvByteArray = tlvBuffer.getByteArray();
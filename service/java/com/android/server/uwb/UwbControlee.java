@@ -35,6 +35,10 @@ public class UwbControlee implements AutoCloseable {
     private final UwbAddress mUwbAddress;
     private final UwbInjector mUwbInjector;
     private final UwbFilterEngine mEngine;
+    private final LinkQualityTracker mLinkQualityTracker;
+    /** Vendor of the controlee, from the OUI of its extended address, or null if unknown. */
+    @Nullable private String mPeerVendor;
+    private boolean mHasAoaAzimuthSignFlip;
     /** Error value to use when the engine produces a result that wasn't in the original reading. */
     private static final double DEFAULT_ERROR_DISTANCE = 0.0;
     private long mLastMeasurementInstant;
@@ -52,13 +56,24 @@ public class UwbControlee implements AutoCloseable {
         mUwbAddress = uwbAddress;
         mEngine = engine;
         mUwbInjector = uwbInjector;
+        int linkQualityThresholdPercent = 0;
         if (mUwbInjector != null
                 && mUwbInjector.getDeviceConfigFacade() != null) {
             // Injector or deviceConfigFacade might be null during tests and this is fine.
             mPredictionTimeoutMilli = mUwbInjector
                     .getDeviceConfigFacade()
                     .getPredictionTimeoutSeconds() * SEC_TO_MILLI;
+            linkQualityThresholdPercent = mUwbInjector
+                    .getDeviceConfigFacade()
+                    .getLinkQualityThresholdPercent();
+            PeerVendorClassifier classifier =
+                    mUwbInjector.getDeviceConfigFacade().getPeerVendorClassifier();
+            if (classifier != null) {
+                mPeerVendor = classifier.classify(uwbAddress);
+                mHasAoaAzimuthSignFlip = classifier.hasAoaAzimuthSignFlip(mPeerVendor);
+            }
         }
+        mLinkQualityTracker = new LinkQualityTracker(linkQualityThresholdPercent);
     }
 
     /**
@@ -70,6 +85,29 @@ public class UwbControlee implements AutoCloseable {
         return mUwbAddress;
     }
 
+    /**
+     * Gets the link quality of the controlee over the last ranging rounds.
+     */
+    public LinkQualityTracker getLinkQualityTracker() {
+        return mLinkQualityTracker;
+    }
+
+    /**
+     * Gets the vendor of the controlee, or null if it could not be classified.
+     */
+    @Nullable
+    public String getPeerVendor() {
+        return mPeerVendor;
+    }
+
+    /**
+     * Returns whether the vendor of the controlee is known to report the AoA azimuth with an
+     * inverted sign.
+     */
+    public boolean hasAoaAzimuthSignFlip() {
+        return mHasAoaAzimuthSignFlip;
+    }
+
     /** Shuts down any controlee-specific work. */
     @Override
     public void close() {
@@ -16,6 +16,7 @@
 
 package com.android.server.uwb;
 
+import static android.Manifest.permission.ACCESS_FINE_LOCATION;
 import static android.Manifest.permission.UWB_RANGING;
 import static android.permission.PermissionManager.PERMISSION_GRANTED;
 
@@ -34,6 +35,7 @@ import android.database.ContentObserver;
 import android.location.Geocoder;
 import android.net.Uri;
 import android.os.Binder;
+import android.os.Build;
 import android.os.Handler;
 import android.os.HandlerThread;
 import android.os.Looper;
@@ -65,6 +67,7 @@ import com.android.server.uwb.correction.primers.FovPrimer;
 import com.android.server.uwb.data.ServiceProfileData;
 import com.android.server.uwb.jni.NativeUwbManager;
 import com.android.server.uwb.multchip.UwbMultichipData;
+import com.android.server.uwb.params.VendorAppConfigRegistry;
 import com.android.server.uwb.pm.ProfileManager;
 import com.android.uwb.flags.FeatureFlags;
 
@@ -88,6 +91,15 @@ public class UwbInjector {
     private static final String APEX_NAME = "com.android.uwb";
     private static final String VENDOR_SERVICE_NAME = "uwb_vendor";
     private static final String BOOT_DEFAULT_UWB_COUNTRY_CODE = "ro.boot.uwbcountrycode";
+    private static final String FIRMWARE_LOG_GID = "ro.vendor.uwb.firmware_log.gid";
+    private static final String FIRMWARE_LOG_OID = "ro.vendor.uwb.firmware_log.oid";
+    private static final String FIRMWARE_LOG_FILE_ENABLED = "persist.uwb.firmware_log.file";
+    private static final String FIRMWARE_LOG_STREAM_ENABLED = "persist.uwb.firmware_log.stream";
+    private static final String CCC_VENDOR_GID = "ro.vendor.uwb.vendor_gid.ccc";
+    private static final String RADAR_VENDOR_GID = "ro.vendor.uwb.vendor_gid.radar";
+    private static final String DIAGNOSTICS_VENDOR_GID = "ro.vendor.uwb.vendor_gid.diagnostics";
+    private static final String SET_CALIBRATION_VENDOR_OID =
+            "ro.vendor.uwb.vendor_oid.set_calibration";
 
     /**
      * The path where the Uwb apex is mounted.
@@ -114,6 +126,7 @@ public class UwbInjector {
     private final UwbMultichipData mUwbMultichipData;
     private final SystemBuildProperties mSystemBuildProperties;
     private final UwbDiagnostics mUwbDiagnostics;
+    private final VendorAppConfigRegistry mVendorAppConfigRegistry;
     private IPoseSource mDefaultPoseSource;
     private final ReentrantLock mPoseLock = new ReentrantLock();
     private int mPoseSourceRefCount = 0;
@@ -145,6 +158,7 @@ public class UwbInjector {
                 new UwbCountryCode(mContext, mNativeUwbManager, new Handler(mLooper), this);
         mUwbMetrics = new UwbMetrics(this);
         mDeviceConfigFacade = new DeviceConfigFacade(new Handler(mLooper), mContext);
+        mVendorAppConfigRegistry = new VendorAppConfigRegistry();
         UwbConfigurationManager uwbConfigurationManager =
                 new UwbConfigurationManager(mNativeUwbManager, this);
         UwbSessionNotificationManager uwbSessionNotificationManager =
@@ -227,6 +241,13 @@ public class UwbInjector {
         return mUwbDiagnostics;
     }
 
+    /**
+     * Vendor app configs registered by OEM code, appended to the FiRa session configuration.
+     */
+    public VendorAppConfigRegistry getVendorAppConfigRegistry() {
+        return mVendorAppConfigRegistry;
+    }
+
     public UwbSessionManager getUwbSessionManager() {
         return mUwbSessionManager;
     }
@@ -283,6 +304,16 @@ public class UwbInjector {
         return permissionCheckResult == PERMISSION_GRANTED;
     }
 
+    /**
+     * Returns true if every app of the attribution source chain holds the ACCESS_FINE_LOCATION
+     * permission, which the precise range data of their session require.
+     */
+    public boolean checkFineLocationPermission(@NonNull AttributionSource attributionSource) {
+        int permissionCheckResult = mPermissionManager.checkPermissionForPreflight(
+                ACCESS_FINE_LOCATION, attributionSource);
+        return permissionCheckResult == PERMISSION_GRANTED;
+    }
+
     /** Indicate permission manager that the ranging session is done or stopped. */
     public void finishUwbRangingPermissionForDataDelivery(
             @NonNull AttributionSource attributionSource) {
@@ -403,6 +434,75 @@ public class UwbInjector {
         return isValidCountryCode(country) ? country.toUpperCase(Locale.US) : null;
     }
 
+    /**
+     * GID of the vendor notification carrying the firmware logs, stored in system property
+     *
+     * @return GID if available, -1 otherwise.
+     */
+    public int getFirmwareLogGid() {
+        return SystemProperties.getInt(FIRMWARE_LOG_GID, -1);
+    }
+
+    /**
+     * OID of the vendor notification carrying the firmware logs, stored in system property
+     *
+     * @return OID if available, -1 otherwise.
+     */
+    public int getFirmwareLogOid() {
+        return SystemProperties.getInt(FIRMWARE_LOG_OID, -1);
+    }
+
+    /**
+     * Whether the firmware logs are written to the firmware log file.
+     */
+    public boolean isFirmwareLogFileEnabled() {
+        return SystemProperties.getBoolean(FIRMWARE_LOG_FILE_ENABLED, false);
+    }
+
+    /**
+     * Whether the firmware logs are streamed to the framework, only on debuggable builds.
+     */
+    public boolean isFirmwareLogStreamEnabled() {
+        return Build.isDebuggable()
+                && SystemProperties.getBoolean(FIRMWARE_LOG_STREAM_ENABLED, false);
+    }
+
+    /**
+     * GID of the vendor notifications of the CCC stack, stored in system property
+     *
+     * @return GID if available, -1 otherwise.
+     */
+    public int getCccVendorGid() {
+        return SystemProperties.getInt(CCC_VENDOR_GID, -1);
+    }
+
+    /**
+     * GID of the vendor notifications of the radar, stored in system property
+     *
+     * @return GID if available, -1 otherwise.
+     */
+    public int getRadarVendorGid() {
+        return SystemProperties.getInt(RADAR_VENDOR_GID, -1);
+    }
+
+    /**
+     * GID of the OEM diagnostics vendor notifications, stored in system property
+     *
+     * @return GID if available, -1 otherwise.
+     */
+    public int getDiagnosticsVendorGid() {
+        return SystemProperties.getInt(DIAGNOSTICS_VENDOR_GID, -1);
+    }
+
+    /**
+     * OID of the ANDROID_SET_CALIBRATION vendor command, stored in system property
+     *
+     * @return OID if the chip supports the command, -1 otherwise.
+     */
+    public int getSetCalibrationVendorOid() {
+        return SystemProperties.getInt(SET_CALIBRATION_VENDOR_OID, -1);
+    }
+
     /**
      * Helper method creating a context based on the app's uid (to deal with multi user scenarios)
      */
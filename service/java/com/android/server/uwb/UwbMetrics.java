@@ -26,6 +26,7 @@ import com.android.server.uwb.data.UwbOwrAoaMeasurement;
 import com.android.server.uwb.data.UwbRangingData;
 import com.android.server.uwb.data.UwbTwoWayMeasurement;
 import com.android.server.uwb.data.UwbUciConstants;
+import com.android.server.uwb.info.UwbParseErrorStats;
 import com.android.server.uwb.proto.UwbStatsLog;
 
 import com.google.common.collect.ImmutableSet;
@@ -40,6 +41,8 @@ import java.io.PrintWriter;
 import java.util.ArrayDeque;
 import java.util.Calendar;
 import java.util.Deque;
+import java.util.Map;
+import java.util.TreeMap;
 
 /**
  * A class to collect and report UWB metrics.
@@ -724,6 +727,7 @@ public class UwbMetrics {
     private boolean mFirstDeviceInitFailure = false;
     private int mNumDeviceStatusError = 0;
     private int mNumUciGenericError = 0;
+    private final Map<String, Long> mParseErrorCounts = new TreeMap<>();
 
     /**
      * Increment the count of device initialization success
@@ -763,6 +767,20 @@ public class UwbMetrics {
                 UwbStatsLog.UWB_DEVICE_ERROR_REPORTED__TYPE__UCI_GENERIC_ERROR);
     }
 
+    /**
+     * Accumulate the notification conversion failures fetched from the native stack
+     */
+    public synchronized void addParseErrorStats(UwbParseErrorStats[] parseErrorStats) {
+        if (parseErrorStats == null) {
+            return;
+        }
+        synchronized (mLock) {
+            for (UwbParseErrorStats stats : parseErrorStats) {
+                mParseErrorCounts.merge(stats.getFamily(), stats.getCount(), Long::sum);
+            }
+        }
+    }
+
     /**
      * Dump the UWB logs
      */
@@ -792,6 +810,10 @@ public class UwbMetrics {
             pw.println("mFirstDeviceInitFailure = " + mFirstDeviceInitFailure);
             pw.println("mNumDeviceStatusError = " + mNumDeviceStatusError);
             pw.println("mNumUciGenericError = " + mNumUciGenericError);
+            pw.println("-- Native parse error count --");
+            for (Map.Entry<String, Long> entry : mParseErrorCounts.entrySet()) {
+                pw.println(entry.getKey() + " = " + entry.getValue());
+            }
             pw.println("---- Dump of UwbMetrics ----");
         }
     }
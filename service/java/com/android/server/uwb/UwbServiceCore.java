@@ -56,7 +56,9 @@ import com.android.internal.annotations.VisibleForTesting;
 import com.android.server.uwb.data.UwbDeviceInfoResponse;
 import com.android.server.uwb.data.UwbUciConstants;
 import com.android.server.uwb.data.UwbVendorUciResponse;
+import com.android.server.uwb.info.UwbAbortedDataTransfers;
 import com.android.server.uwb.info.UwbPowerStats;
+import com.android.server.uwb.info.UwbSessionMismatch;
 import com.android.server.uwb.jni.INativeUwbManager;
 import com.android.server.uwb.jni.NativeUwbManager;
 
@@ -85,11 +87,13 @@ import com.google.uwb.support.radar.RadarParams;
 import java.io.FileDescriptor;
 import java.io.PrintWriter;
 import java.util.HashMap;
+import java.util.List;
 import java.util.Map;
 import java.util.NoSuchElementException;
 import java.util.Objects;
 import java.util.Optional;
 import java.util.Set;
+import java.util.concurrent.ConcurrentHashMap;
 import java.util.concurrent.ExecutionException;
 import java.util.concurrent.FutureTask;
 import java.util.concurrent.TimeoutException;
@@ -143,10 +147,24 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
     private  IUwbVendorUciCallback mCallBack = null;
     private IUwbOemExtensionCallback mOemExtensionCallback = null;
     private final Handler mHandler;
-    private GenericSpecificationParams mCachedSpecificationParams;
-    private boolean mNeedCachedSpecParamsUpdate = true;
+    /** The capabilities of each chip, parsed from CORE_GET_CAPS_INFO. */
+    private final Map<String, GenericSpecificationParams> mChipIdToSpecificationParamsMap =
+            new ConcurrentHashMap<>();
     private boolean mSetEnabled = false;
     private final Set<InitializationFailureListener> mListeners = new ArraySet<>();
+    /** Chips whose firmware is being updated, see {@link #enterMaintenanceMode(String)}. */
+    private final Set<String> mMaintenanceChipIds = new ArraySet<>();
+    /** Chips whose HAL is proxied over TCP, see {@link #startUciProxy(String, int)}. */
+    private final Set<String> mUciProxyChipIds = new ArraySet<>();
+    /** Sessions affected by the last country code set in each chip. */
+    private final Map<String, RegulatoryImpact> mChipIdToRegulatoryImpactMap = new HashMap<>();
+    /** Session mismatches found by the last health check of each chip. */
+    private final Map<String, List<UwbSessionMismatch>> mChipIdToSessionMismatchesMap =
+            new HashMap<>();
+    private final Runnable mSessionHealthCheck = this::checkSessionHealth;
+    /** Data transfers aborted by the last shutdown of the service, on each chip. */
+    private final Map<String, List<UwbAbortedDataTransfers>> mChipIdToAbortedDataTransfersMap =
+            new HashMap<>();
 
     /**
      * Wrapper class to hold {@link AttributionSource} and override it's equals
@@ -314,6 +332,16 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
         void onFailure();
     }
 
+    /**
+     * Thrown when a command is rejected because the chip is busy with a firmware update, until
+     * {@link #exitMaintenanceMode(String)} is called.
+     */
+    public static class BusyException extends IllegalStateException {
+        public BusyException(String message) {
+            super(message);
+        }
+    }
+
     public void addInitializationFailureListener(@NonNull InitializationFailureListener listener) {
         mListeners.add(listener);
     }
@@ -346,6 +374,237 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
         return getAdapterState() != AdapterStateCallback.STATE_DISABLED;
     }
 
+    /** Returns whether the chip is in maintenance mode. */
+    public synchronized boolean isInMaintenanceMode(String chipId) {
+        return mMaintenanceChipIds.contains(chipId);
+    }
+
+    /** Rejects a session the chip cannot run, before the session is initialized. */
+    private static void checkSessionCapabilities(@Nullable String unsupportedReason,
+            String chipId) {
+        if (unsupportedReason != null) {
+            Log.e(TAG, "openRanging: " + unsupportedReason + " by chip " + chipId);
+            throw new IllegalArgumentException(unsupportedReason + " by chip " + chipId);
+        }
+    }
+
+    private void checkNotInMaintenanceMode(String chipId) {
+        if (isInMaintenanceMode(chipId)) {
+            throw new BusyException("Uwb chip " + chipId + " is in maintenance mode");
+        }
+    }
+
+    /**
+     * Puts a chip in maintenance mode, for a vendor-driven firmware update through
+     * {@link #sendVendorUciMessage}. The sessions of the chip are stopped and closed, the new
+     * sessions are rejected with a {@link BusyException}, and the device states notified by the
+     * chip no longer restart the stack or change the adapter state.
+     *
+     * @return false if UWB is not enabled or the chip is unknown
+     */
+    public boolean enterMaintenanceMode(String chipId) {
+        if (!isUwbEnabled() || !mUwbInjector.getMultichipData().getChipIds().contains(chipId)) {
+            Log.e(TAG, "enterMaintenanceMode: Uwb is not enabled or unknown chip " + chipId);
+            return false;
+        }
+        synchronized (this) {
+            if (!mMaintenanceChipIds.add(chipId)) {
+                return true;
+            }
+        }
+        Log.i(TAG, "Entering maintenance mode for chip " + chipId);
+        mSessionManager.deinitAllSession(chipId);
+        return true;
+    }
+
+    /**
+     * Takes a chip out of maintenance mode once its device info and capabilities are retrieved
+     * again, as the firmware update may have changed them.
+     *
+     * @return false if the chip is left in maintenance mode, as it could not be revalidated
+     */
+    public boolean exitMaintenanceMode(String chipId) {
+        if (!isInMaintenanceMode(chipId)) {
+            return true;
+        }
+        UwbDeviceInfoResponse deviceInfo = mNativeUwbManager.getDeviceInfo(chipId);
+        if (deviceInfo == null || deviceInfo.mStatusCode != UwbUciConstants.STATUS_CODE_OK) {
+            Log.e(TAG, "exitMaintenanceMode: failed to get the device info of chip " + chipId);
+            return false;
+        }
+        synchronized (this) {
+            mChipIdToDeviceInfoResponseMap.put(chipId, deviceInfo);
+            mMaintenanceChipIds.remove(chipId);
+            mChipIdToSpecificationParamsMap.remove(chipId);
+        }
+        if (getSpecificationInfo(chipId).isEmpty()) {
+            Log.e(TAG, "exitMaintenanceMode: failed to get the capabilities of chip " + chipId);
+            synchronized (this) {
+                mMaintenanceChipIds.add(chipId);
+            }
+            return false;
+        }
+        Log.i(TAG, "Exited maintenance mode for chip " + chipId);
+        mUwbTask.computeAndNotifyAdapterStateChange(
+                getReasonFromDeviceState(UwbUciConstants.DEVICE_STATE_READY),
+                mUwbCountryCode.getCountryCode(),
+                mUwbCountryCode.getCountryCodeStatus());
+        return true;
+    }
+
+    /**
+     * Proxies the HAL of a chip over a local TCP port, letting a host-side UWB stack drive the chip
+     * during development. Only possible while UWB is disabled, as the stack then does not use the
+     * HAL. The proxy is stopped when UWB is enabled again.
+     *
+     * @return false if UWB is enabled, the chip is unknown, or the proxy could not be started
+     */
+    public boolean startUciProxy(String chipId, int port) {
+        if (isUwbEnabledInternal()
+                || !mUwbInjector.getMultichipData().getChipIds().contains(chipId)) {
+            Log.e(TAG, "startUciProxy: Uwb is enabled or unknown chip " + chipId);
+            return false;
+        }
+        synchronized (this) {
+            if (mUciProxyChipIds.contains(chipId)) {
+                return true;
+            }
+            if (!mNativeUwbManager.startUciProxy(port, chipId)) {
+                return false;
+            }
+            mUciProxyChipIds.add(chipId);
+        }
+        Log.i(TAG, "Proxying the HAL of chip " + chipId + " on port " + port);
+        return true;
+    }
+
+    /** Stops proxying the HAL of a chip, see {@link #startUciProxy(String, int)}. */
+    public synchronized boolean stopUciProxy(String chipId) {
+        if (!mUciProxyChipIds.contains(chipId)) {
+            return true;
+        }
+        if (!mNativeUwbManager.stopUciProxy(chipId)) {
+            return false;
+        }
+        mUciProxyChipIds.remove(chipId);
+        Log.i(TAG, "Stopped proxying the HAL of chip " + chipId);
+        return true;
+    }
+
+    /**
+     * Serves the metrics of all the chips in the Prometheus text format over a local TCP port, for
+     * the lab soak tests. Only possible while UWB is enabled, the exporter being stopped with the
+     * stack. Requires the native_metrics_exporter_enabled flag.
+     *
+     * @return false if UWB is disabled, or the exporter could not be started
+     */
+    public boolean startMetricsExporter(int port) {
+        if (!isUwbEnabledInternal()) {
+            Log.e(TAG, "startMetricsExporter: Uwb is disabled");
+            return false;
+        }
+        if (!mNativeUwbManager.startMetricsExporter(port)) {
+            return false;
+        }
+        Log.i(TAG, "Exporting the metrics on port " + port);
+        return true;
+    }
+
+    /** Stops serving the metrics, see {@link #startMetricsExporter(int)}. */
+    public boolean stopMetricsExporter() {
+        return mNativeUwbManager.stopMetricsExporter();
+    }
+
+    private synchronized void stopAllUciProxies() {
+        for (String chipId : List.copyOf(mUciProxyChipIds)) {
+            stopUciProxy(chipId);
+        }
+    }
+
+    private void scheduleSessionHealthCheck() {
+        mHandler.removeCallbacks(mSessionHealthCheck);
+        int intervalMs = mUwbInjector.getDeviceConfigFacade().getSessionHealthCheckIntervalMs();
+        if (intervalMs > 0) {
+            mHandler.postDelayed(mSessionHealthCheck, intervalMs);
+        }
+    }
+
+    private synchronized void cancelSessionHealthCheck() {
+        mHandler.removeCallbacks(mSessionHealthCheck);
+        mChipIdToSessionMismatchesMap.clear();
+    }
+
+    /**
+     * Compares the sessions tracked by the native stack of each chip with the UWBS's view of
+     * them. A mismatch found by two consecutive checks is not a transition in flight, so it is
+     * reported as a device error.
+     */
+    @VisibleForTesting
+    synchronized void checkSessionHealth() {
+        if (!isUwbEnabled()) {
+            return;
+        }
+        for (String chipId : mUwbInjector.getMultichipData().getChipIds()) {
+            UwbSessionMismatch[] mismatches = mNativeUwbManager.checkSessions(chipId);
+            if (mismatches == null) {
+                Log.w(TAG, "Failed to check the sessions of chip " + chipId);
+                continue;
+            }
+            List<UwbSessionMismatch> previousMismatches =
+                    mChipIdToSessionMismatchesMap.getOrDefault(chipId, List.of());
+            boolean confirmed = false;
+            for (UwbSessionMismatch mismatch : mismatches) {
+                if (previousMismatches.contains(mismatch)) {
+                    Log.e(TAG, "Session bookkeeping mismatch on chip " + chipId + ": "
+                            + mismatch);
+                    confirmed = true;
+                }
+            }
+            mChipIdToSessionMismatchesMap.put(chipId, List.of(mismatches));
+            if (confirmed) {
+                takBugReportAfterDeviceError("UWB Bugreport: session bookkeeping mismatch");
+            }
+        }
+        scheduleSessionHealthCheck();
+    }
+
+    /**
+     * Writes the data transfers in flight on each chip before the service is shut down, so that
+     * they are reported as aborted once restarted instead of being lost silently.
+     */
+    public synchronized void persistDataTransfers() {
+        if (!isUwbEnabled()) {
+            return;
+        }
+        for (String chipId : mUwbInjector.getMultichipData().getChipIds()) {
+            if (!mNativeUwbManager.persistDataTransfers(chipId)) {
+                Log.w(TAG, "Failed to persist the data transfers of chip " + chipId);
+            }
+        }
+    }
+
+    /**
+     * Reports the data transfers of each session aborted by the last shutdown of the service.
+     */
+    private synchronized void reportAbortedDataTransfers() {
+        for (String chipId : mUwbInjector.getMultichipData().getChipIds()) {
+            UwbAbortedDataTransfers[] abortedTransfers =
+                    mNativeUwbManager.fetchAbortedDataTransfers(chipId);
+            if (abortedTransfers == null) {
+                Log.w(TAG, "Failed to fetch the aborted data transfers of chip " + chipId);
+                continue;
+            }
+            if (abortedTransfers.length == 0) {
+                continue;
+            }
+            for (UwbAbortedDataTransfers aborted : abortedTransfers) {
+                Log.w(TAG, "Data transfers aborted by the shutdown on chip " + chipId + ": "
+                        + aborted);
+            }
+            mChipIdToAbortedDataTransfersMap.put(chipId, List.of(abortedTransfers));
+        }
+    }
+
     private boolean isUwbEnabledInternal() {
         synchronized (UwbServiceCore.this) {
             return getInternalAdapterState() != AdapterStateCallback.STATE_DISABLED;
@@ -397,6 +656,14 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
                 return;
             }
 
+            // The chip reboots during a firmware update, which must not fight the live stack.
+            if (isInMaintenanceMode(chipId)) {
+                Log.i(TAG, "Chip " + chipId + " is in maintenance mode, device state "
+                        + getDeviceStateString(deviceState) + " is not handled");
+                oemExtensionDeviceStatusUpdate(deviceState, chipId);
+                return;
+            }
+
             if ((byte) deviceState == UwbUciConstants.DEVICE_STATE_ERROR) {
                 Log.wtf(TAG, "Error device status received. Restarting...");
                 mUwbMetrics.incrementDeviceStatusErrorCount();
@@ -498,6 +765,29 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
         mUwbMetrics.incrementUciGenericErrorCount();
     }
 
+    @Override
+    public void onNotificationOverflow(long droppedCount, String chipId) {
+        Log.w(TAG, "onNotificationOverflow: " + droppedCount + " notifications dropped on chip "
+                + chipId);
+    }
+
+    @Override
+    public void onProtocolError(String stage, String notification, long suppressedCount,
+            String chipId) {
+        Log.e(TAG, "onProtocolError: failed to convert " + stage + " notification on chip "
+                + chipId + " (" + suppressedCount + " suppressed since last report): "
+                + notification);
+        // Collect the counts off the JNI callback thread.
+        mHandler.post(() -> mUwbMetrics.addParseErrorStats(
+                mNativeUwbManager.fetchAndClearParseErrorStats(chipId)));
+    }
+
+    @Override
+    public void onUciRetryStorm(int gid, int oid, int retryCount, String chipId) {
+        Log.e(TAG, "onUciRetryStorm: " + retryCount + " retries of GID " + gid + " OID " + oid
+                + " on chip " + chipId + ", commands throttled");
+    }
+
     @Override
     public void onCountryCodeChanged(int setCountryCodeStatus, @Nullable String countryCode) {
         Log.i(TAG, "Received onCountryCodeChanged() with countryCode = " + countryCode);
@@ -512,7 +802,53 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
                 countryCode,
                 Optional.of(setCountryCodeStatus));
         Log.d(TAG, "Resetting cached specifications");
-        mNeedCachedSpecParamsUpdate = true;
+        mChipIdToSpecificationParamsMap.clear();
+
+        if (setCountryCodeStatus == STATUS_CODE_OK && isUwbEnabled()) {
+            for (String chipId : mUwbInjector.getMultichipData().getChipIds()) {
+                updateRegulatoryImpact(chipId, countryCode);
+            }
+        }
+    }
+
+    /**
+     * Finds the active sessions of the chip ranging on a channel which the new country code no
+     * longer allows, from the channels the chip now supports, and stops them if configured to.
+     */
+    private void updateRegulatoryImpact(String chipId, @Nullable String countryCode) {
+        mChipIdToRegulatoryImpactMap.remove(chipId);
+        if (isInMaintenanceMode(chipId) || !mSessionManager.hasActiveSession(chipId)) {
+            return;
+        }
+        Pair<Integer, GenericSpecificationParams> specificationParams =
+                mConfigurationManager.getCapsInfo(
+                        GenericParams.PROTOCOL_NAME, GenericSpecificationParams.class, chipId,
+                        mSessionManager.getUwbsFiraProtocolVersion(chipId));
+        if (specificationParams.first != UwbUciConstants.STATUS_CODE_OK
+                || specificationParams.second == null
+                || specificationParams.second.getFiraSpecificationParams() == null) {
+            Log.e(TAG, "Failed to retrieve the channels allowed in " + countryCode
+                    + " for chip " + chipId);
+            return;
+        }
+        List<Integer> allowedChannels =
+                specificationParams.second.getFiraSpecificationParams().getSupportedChannels();
+        RegulatoryImpact regulatoryImpact = mSessionManager.onRegulatoryChannelsChanged(chipId,
+                countryCode, allowedChannels,
+                mUwbInjector.getDeviceConfigFacade().isRegulatoryAutoSuspendEnabled());
+        if (!regulatoryImpact.getAffectedSessionIds().isEmpty()) {
+            Log.w(TAG, "Sessions ranging on a disallowed channel: " + regulatoryImpact);
+        }
+        mChipIdToRegulatoryImpactMap.put(chipId, regulatoryImpact);
+    }
+
+    /**
+     * Gets the sessions affected by the last country code set in the chip, or null if no session
+     * of the chip was active then.
+     */
+    @Nullable
+    public RegulatoryImpact getRegulatoryImpact(String chipId) {
+        return mChipIdToRegulatoryImpactMap.get(chipId);
     }
 
     public void registerAdapterStateCallbacks(IUwbAdapterStateCallbacks adapterStateCallbacks)
@@ -559,16 +895,18 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
     }
 
     /**
-     * Get cached specification params
+     * Get cached specification params of the chip, or null if they cannot be retrieved.
      */
+    @Nullable
     public GenericSpecificationParams getCachedSpecificationParams(String chipId) {
-        if (mCachedSpecificationParams != null && !mNeedCachedSpecParamsUpdate) {
-            return mCachedSpecificationParams;
+        GenericSpecificationParams specificationParams =
+                mChipIdToSpecificationParamsMap.get(chipId);
+        if (specificationParams != null) {
+            return specificationParams;
         }
         // If nothing in cache, populate it.
         getSpecificationInfo(chipId);
-        mNeedCachedSpecParamsUpdate = false;
-        return mCachedSpecificationParams;
+        return mChipIdToSpecificationParamsMap.get(chipId);
     }
 
     /**
@@ -586,6 +924,7 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
         if (!isUwbEnabled()) {
             throw new IllegalStateException("Uwb is not enabled");
         }
+        checkNotInMaintenanceMode(chipId);
         Trace.beginSection("UWB#getSpecificationInfo");
         // TODO(b/211445008): Consolidate to a single uwb thread.
         Pair<Integer, GenericSpecificationParams> specificationParams =
@@ -593,7 +932,8 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
                         GenericParams.PROTOCOL_NAME, GenericSpecificationParams.class, chipId,
                         mSessionManager.getUwbsFiraProtocolVersion(chipId));
         Trace.endSection();
-        if (specificationParams.first != UwbUciConstants.STATUS_CODE_OK
+        if (specificationParams == null
+                || specificationParams.first != UwbUciConstants.STATUS_CODE_OK
                 || specificationParams.second == null)  {
             Log.e(TAG, "Failed to retrieve specification params");
             return new PersistableBundle();
@@ -604,12 +944,12 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
                             specificationParams.second.getFiraSpecificationParams())
                             .setBackgroundRangingSupport(mUwbInjector.getDeviceConfigFacade()
                                     .isBackgroundRangingEnabled())
-                            .setUciVersionSupported(getCachedDeviceInfoResponse(
-                                    mUwbInjector.getMultichipData().getDefaultChipId()).mUciVersion)
+                            .setUciVersionSupported(
+                                    getCachedDeviceInfoResponse(chipId).mUciVersion)
                             .build();
             specificationParams.second.setFiraSpecificationParams(firaSpecificationParams);
         }
-        mCachedSpecificationParams = specificationParams.second;
+        mChipIdToSpecificationParamsMap.put(chipId, specificationParams.second);
         return specificationParams.second.toBundle();
     }
 
@@ -640,6 +980,7 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
         if (!isUwbEnabled()) {
             throw new IllegalStateException("Uwb is not enabled");
         }
+        checkNotInMaintenanceMode(chipId);
         int sessionId = 0;
         int sessionType = 0;
 
@@ -667,6 +1008,8 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
                 builder.setDiagramsFrameReportsFieldsFlags(mDiagramsFrameReportsFieldsFlags);
             }
             FiraOpenSessionParams firaOpenSessionParams = builder.build();
+            checkSessionCapabilities(SessionCapabilityValidator.validateFiraSession(
+                    firaOpenSessionParams, getCachedSpecificationParams(chipId)), chipId);
             sessionId = firaOpenSessionParams.getSessionId();
             sessionType = firaOpenSessionParams.getSessionType();
             mSessionManager.initSession(attributionSource, sessionHandle, sessionId,
@@ -690,6 +1033,8 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
         } else if (RadarParams.isCorrectProtocol(params)) {
             RadarOpenSessionParams radarOpenSessionParams =
                     RadarOpenSessionParams.fromBundle(params);
+            checkSessionCapabilities(SessionCapabilityValidator.validateRadarSession(
+                    getCachedSpecificationParams(chipId)), chipId);
             sessionId = radarOpenSessionParams.getSessionId();
             sessionType = radarOpenSessionParams.getSessionType();
             mSessionManager.initSession(attributionSource, sessionHandle, sessionId,
@@ -1025,6 +1370,24 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
         return status;
     }
 
+    /**
+     * Injects a raw UCI message for vendor debugging, bypassing the typed builders of the native
+     * stack. Production builds only allow the GIDs of the device config allowlist.
+     *
+     * @return the response of the UWBS, or null if UWB is not enabled
+     */
+    @Nullable
+    public synchronized UwbVendorUciResponse injectUciMessage(int mt, int gid, int oid,
+            byte[] payload, String chipId) {
+        if (!isUwbEnabledInternal()) {
+            Log.e(TAG, "injectUciMessage: UWB is not enabled");
+            return null;
+        }
+        Log.i(TAG, "Injecting UCI message MT " + mt + " GID " + gid + " OID " + oid + " on chip "
+                + chipId);
+        return mNativeUwbManager.injectUciMessage(mt, gid, oid, payload, chipId);
+    }
+
     public void rangingRoundsUpdateDtTag(SessionHandle sessionHandle,
             PersistableBundle params) throws RemoteException {
         if (!isUwbEnabled()) {
@@ -1142,6 +1505,8 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
                     mUwbWakeLock.acquire();
                 }
                 try {
+                    // The stack takes the HAL back from the host-side stacks.
+                    stopAllUciProxies();
                     Map<String, UwbDeviceInfoResponse> result = mNativeUwbManager.doInitialize();
                     if (result == null) {
                         Log.e(TAG, "Error enabling UWB");
@@ -1171,6 +1536,8 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
                             Log.d(TAG, "enabling chip " + chipId);
                             updateDeviceState(UwbUciConstants.DEVICE_STATE_READY, chipId);
                         }
+                        scheduleSessionHealthCheck();
+                        reportAbortedDataTransfers();
 
                         // Set country code on every enable (example: for the scenario when the
                         // country code was determined/changed while the UWB stack was disabled).
@@ -1207,6 +1574,7 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
 
             try {
                 Log.i(TAG, "Deinitialization start ...");
+                cancelSessionHealthCheck();
                 synchronized (mUwbWakeLock) {
                     mUwbWakeLock.acquire();
                 }
@@ -1371,6 +1739,11 @@ public class UwbServiceCore implements INativeUwbManager.DeviceNotification,
         pw.println("mUwbClientHwState = " + mUwbClientHwState);
         pw.println("mLastAdapterStateChangedReason = " + mLastAdapterStateChangedReason);
         pw.println("mLastAdapterStateNotification = " + mLastAdapterStateNotification);
+        pw.println("mMaintenanceChipIds = " + mMaintenanceChipIds);
+        pw.println("mUciProxyChipIds = " + mUciProxyChipIds);
+        pw.println("mChipIdToRegulatoryImpactMap = " + mChipIdToRegulatoryImpactMap);
+        pw.println("mChipIdToSessionMismatchesMap = " + mChipIdToSessionMismatchesMap);
+        pw.println("mChipIdToAbortedDataTransfersMap = " + mChipIdToAbortedDataTransfersMap);
         pw.println("---- Dump of UwbServiceCore ----");
     }
 
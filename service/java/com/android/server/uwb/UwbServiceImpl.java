@@ -48,6 +48,10 @@ import android.uwb.UwbAddress;
 import com.android.internal.annotations.VisibleForTesting;
 import com.android.modules.utils.build.SdkLevel;
 import com.android.server.uwb.data.UwbUciConstants;
+import com.android.server.uwb.info.UwbHalTransaction;
+import com.android.server.uwb.info.UwbSessionRecord;
+import com.android.server.uwb.info.UwbSessionTokenMapping;
+import com.android.server.uwb.info.UwbUciCommandStats;
 
 import com.google.uwb.support.generic.GenericSpecificationParams;
 import com.google.uwb.support.multichip.ChipInfoParams;
@@ -120,6 +124,7 @@ public class UwbServiceImpl extends IUwbAdapter.Stub {
         registerSatelliteModeReceiver();
         mUwbUserRestricted = isUwbUserRestricted();
         registerUserRestrictionsReceiver();
+        registerShutdownReceiver();
     }
 
     /**
@@ -164,7 +169,96 @@ public class UwbServiceImpl extends IUwbAdapter.Stub {
         pw.println();
         if (isUwbEnabled()) {
             dumpPowerStats(fd, pw, args);
+            pw.println();
+            dumpNativeSessions(fd, pw, args);
+            pw.println();
+            dumpSessionTokens(fd, pw, args);
+            pw.println();
+            dumpUciMetrics(fd, pw, args);
+            pw.println();
+            dumpHalTrace(fd, pw, args);
         }
+        pw.println();
+        dumpNativeFeatureFlags(fd, pw, args);
+    }
+
+    private void dumpNativeSessions(FileDescriptor fd, PrintWriter pw, String[] args) {
+        pw.println("---- NativeSessions ----");
+        for (String chipId : mUwbInjector.getMultichipData().getChipIds()) {
+            pw.println("Chip " + chipId + ":");
+            UwbSessionRecord[] sessions =
+                    mUwbInjector.getNativeUwbManager().listSessions(chipId);
+            if (sessions == null) {
+                pw.println("Failed to list the native sessions");
+                continue;
+            }
+            for (UwbSessionRecord session : sessions) {
+                pw.println(session);
+            }
+        }
+        pw.println("---- NativeSessions ----");
+    }
+
+    private void dumpSessionTokens(FileDescriptor fd, PrintWriter pw, String[] args) {
+        pw.println("---- SessionTokens ----");
+        for (String chipId : mUwbInjector.getMultichipData().getChipIds()) {
+            pw.println("Chip " + chipId + ":");
+            UwbSessionTokenMapping[] mappings =
+                    mUwbInjector.getNativeUwbManager().getSessionTokenMappings(chipId);
+            UwbSessionTokenMapping[] remaps =
+                    mUwbInjector.getNativeUwbManager().getSessionTokenRemaps(chipId);
+            if (mappings == null || remaps == null) {
+                pw.println("Failed to get the session tokens");
+                continue;
+            }
+            for (UwbSessionTokenMapping mapping : mappings) {
+                pw.println(mapping);
+            }
+            pw.println("Remaps:");
+            for (UwbSessionTokenMapping remap : remaps) {
+                pw.println(remap);
+            }
+        }
+        pw.println("---- SessionTokens ----");
+    }
+
+    private void dumpUciMetrics(FileDescriptor fd, PrintWriter pw, String[] args) {
+        pw.println("---- UciMetrics ----");
+        for (String chipId : mUwbInjector.getMultichipData().getChipIds()) {
+            pw.println("Chip " + chipId + ":");
+            UwbUciCommandStats[] stats = mUwbInjector.getNativeUwbManager().getUciMetrics(chipId);
+            if (stats == null) {
+                pw.println("Failed to get the UCI metrics");
+                continue;
+            }
+            for (UwbUciCommandStats commandStats : stats) {
+                pw.println(commandStats);
+            }
+        }
+        pw.println("---- UciMetrics ----");
+    }
+
+    private void dumpHalTrace(FileDescriptor fd, PrintWriter pw, String[] args) {
+        pw.println("---- HalTrace ----");
+        for (String chipId : mUwbInjector.getMultichipData().getChipIds()) {
+            pw.println("Chip " + chipId + ":");
+            UwbHalTransaction[] transactions =
+                    mUwbInjector.getNativeUwbManager().getHalTrace(chipId);
+            if (transactions == null) {
+                pw.println("Failed to get the HAL trace");
+                continue;
+            }
+            for (UwbHalTransaction transaction : transactions) {
+                pw.println(transaction);
+            }
+        }
+        pw.println("---- HalTrace ----");
+    }
+
+    private void dumpNativeFeatureFlags(FileDescriptor fd, PrintWriter pw, String[] args) {
+        pw.println("---- NativeFeatureFlags ----");
+        pw.println(mUwbInjector.getNativeUwbManager().getNativeFeatureFlagsDump());
+        pw.println("---- NativeFeatureFlags ----");
     }
 
     private void dumpPowerStats(FileDescriptor fd, PrintWriter pw, String[] args) {
@@ -690,6 +784,20 @@ public class UwbServiceImpl extends IUwbAdapter.Stub {
                 mUwbServiceCore.getHandler());
     }
 
+    private void registerShutdownReceiver() {
+        mContext.registerReceiver(
+                new BroadcastReceiver() {
+                    @Override
+                    public void onReceive(Context context, Intent intent) {
+                        Log.i(TAG, "Shutdown detected");
+                        mUwbServiceCore.persistDataTransfers();
+                    }
+                },
+                new IntentFilter(Intent.ACTION_SHUTDOWN),
+                null,
+                mUwbServiceCore.getHandler());
+    }
+
     private void handleAirplaneOrSatelliteModeEvent() {
         try {
             mUwbServiceCore.setEnabled(isUwbEnabled());
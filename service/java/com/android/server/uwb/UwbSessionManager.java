@@ -148,6 +148,12 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
     private static final int UWB_HUS_CONTROLLER_PHASE_LIST_SHORT_MAC_ADDRESS_SIZE = 11;
     private static final int UWB_HUS_CONTROLLER_PHASE_LIST_EXTENDED_MAC_ADDRESS_SIZE = 17;
     private static final int UWB_HUS_CONTROLEE_PHASE_LIST_SIZE = 5;
+    /** Distance step of the range data delivered to the apps without precise location. */
+    @VisibleForTesting
+    static final int COARSE_LOCATION_DISTANCE_STEP_CM = 50;
+
+    /** Key of the time a data packet was received from the UWBS, in nanos since boot. */
+    public static final String KEY_DATA_RECEIVED_AT_NANOS = "data_received_at_nanos";
 
     @VisibleForTesting
     public static final int SESSION_OPEN_RANGING = 1;
@@ -177,6 +183,9 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
     final ConcurrentHashMap<SessionHandle, UwbSession> mSessionTable = new ConcurrentHashMap();
     // Used for storing recently closed sessions for debugging purposes.
     final LruList<UwbSession> mDbgRecentlyClosedSessions = new LruList<>(5);
+    // Used for storing the recent conflicts between initiator and responder sessions.
+    final LruList<DualRoleScheduler.Conflict> mDbgDualRoleConflicts = new LruList<>(10);
+    final LruList<String> mDbgSessionTransitionViolations = new LruList<>(10);
     final ConcurrentHashMap<Integer, List<UwbSession>> mNonPrivilegedUidToFiraSessionsTable =
             new ConcurrentHashMap();
     final ConcurrentHashMap<Integer, Integer> mSessionTokenMap = new ConcurrentHashMap<>();
@@ -266,9 +275,10 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             }
         } else if (rangingData.getRangingMeasuresType()
                 == UwbUciConstants.RANGING_MEASUREMENT_TYPE_OWR_AOA) {
-            UwbOwrAoaMeasurement measure = rangingData.getRangingOwrAoaMeasure();
-            if (measure.getRangingStatus() == UwbUciConstants.STATUS_CODE_OK) {
-                return false;
+            for (UwbOwrAoaMeasurement measure : rangingData.getRangingOwrAoaMeasures()) {
+                if (measure.getRangingStatus() == UwbUciConstants.STATUS_CODE_OK) {
+                    return false;
+                }
             }
         } else if (rangingData.getRangingMeasuresType()
                 == UwbUciConstants.RANGING_MEASUREMENT_TYPE_DL_TDOA) {
@@ -310,11 +320,15 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         }
     }
 
+    private boolean isRangingErrorStreakTimerEnabled(@NonNull UwbSession uwbSession) {
+        return mUwbInjector.getDeviceConfigFacade().isRangingErrorStreakTimerEnabled()
+                && uwbSession.mRangingErrorStreakTimeoutMs
+                != UwbSession.RANGING_RESULT_ERROR_NO_TIMEOUT;
+    }
+
     private void handleRangingResultErrorStreakTimers(@NonNull UwbRangingData rangingData,
                                                       @NonNull UwbSession uwbSession) {
-        if (!mUwbInjector.getDeviceConfigFacade().isRangingErrorStreakTimerEnabled()
-                || uwbSession.mRangingErrorStreakTimeoutMs
-                == UwbSession.RANGING_RESULT_ERROR_NO_TIMEOUT) {
+        if (!isRangingErrorStreakTimerEnabled(uwbSession)) {
             return;
         }
 
@@ -344,16 +358,31 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         Trace.endSection();
     }
 
+    @Override
+    public void onRangeKeepAlive(long sessionId, long sequenceNumber) {
+        UwbSession uwbSession = getUwbSession((int) sessionId);
+        if (uwbSession == null) {
+            Log.i(TAG, "onRangeKeepAlive: unknown sessionId = " + sessionId);
+            return;
+        }
+        // A round without any measurement counts as a failed one, as did the empty range data
+        // it replaces. Nothing is reported to the application.
+        if (isRangingErrorStreakTimerEnabled(uwbSession)) {
+            uwbSession.startRangingResultErrorStreakTimerIfNotSet();
+        }
+    }
+
     /* Notification of received data over UWB to Application*/
     @Override
-    public void onDataReceived(
-            long sessionId, int status, long sequenceNum, byte[] address, byte[] data) {
+    public void onDataReceived(long sessionId, int status, long sequenceNum, byte[] address,
+            byte[] data, long receivedAtNanos) {
         Log.d(TAG, "onDataReceived(): Received data packet - "
                 + "Address: " + UwbUtil.toHexString(address)
                 + ", Data: " + UwbUtil.toHexString(data)
                 + ", sessionId: " + sessionId
                 + ", status: " + status
-                + ", sequenceNum: " + sequenceNum);
+                + ", sequenceNum: " + sequenceNum
+                + ", receivedAtNanos: " + receivedAtNanos);
 
         UwbSession uwbSession = getUwbSession((int) sessionId);
         if (uwbSession == null) {
@@ -379,7 +408,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         // received SESSION_INFO_NTF indicate this Observer device is pointing to an Advertiser.
         if (uwbSession.getRangingRoundUsage() != ROUND_USAGE_OWR_AOA_MEASUREMENT) {
             mSessionNotificationManager.onDataReceived(
-                    uwbSession, uwbAddress, new PersistableBundle(), data);
+                    uwbSession, uwbAddress, createReceivedDataParameters(receivedAtNanos), data);
             return;
         }
 
@@ -389,10 +418,17 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         info.sequenceNum = sequenceNum;
         info.address = longAddress;
         info.payload = data;
+        info.receivedAtNanos = receivedAtNanos;
 
         uwbSession.addReceivedDataInfo(info);
     }
 
+    private static PersistableBundle createReceivedDataParameters(long receivedAtNanos) {
+        PersistableBundle parameters = new PersistableBundle();
+        parameters.putLong(KEY_DATA_RECEIVED_AT_NANOS, receivedAtNanos);
+        return parameters;
+    }
+
     /* Notification of data send status */
     @Override
     public void onDataSendStatus(
@@ -474,6 +510,126 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         }
     }
 
+    @Override
+    public void onOwrAoaAdvertiserLost(long sessionId, byte[] macAddress) {
+        Log.d(TAG, "onOwrAoaAdvertiserLost: sessionId: " + sessionId
+                + ", macAddress: " + UwbUtil.toHexString(macAddress));
+        UwbSession uwbSession = getUwbSession((int) sessionId);
+        if (uwbSession == null) {
+            Log.i(TAG, "onOwrAoaAdvertiserLost: unknown sessionId = " + sessionId);
+            return;
+        }
+        mAdvertiseManager.removeAdvertiseTarget(macAddressByteArrayToLong(macAddress));
+    }
+
+    @Override
+    public void onProximityEvent(long sessionId, boolean isNear, int distanceCm,
+            byte[] macAddress) {
+        Log.d(TAG, "onProximityEvent: sessionId: " + sessionId + ", isNear: " + isNear
+                + ", distanceCm: " + distanceCm);
+        UwbSession uwbSession = getUwbSession((int) sessionId);
+        if (uwbSession == null) {
+            Log.i(TAG, "onProximityEvent: unknown sessionId = " + sessionId);
+            return;
+        }
+        mSessionNotificationManager.onProximityEvent(uwbSession, distanceCm, macAddress);
+    }
+
+    @Override
+    public void onRangingIntervalSuggestion(long sessionId, int rangingDurationMs,
+            int blockStride) {
+        Log.i(TAG, "onRangingIntervalSuggestion: sessionId: " + sessionId
+                + ", rangingDurationMs: " + rangingDurationMs + ", blockStride: " + blockStride);
+        UwbSession uwbSession = getUwbSession((int) sessionId);
+        if (uwbSession == null) {
+            Log.i(TAG, "onRangingIntervalSuggestion: unknown sessionId = " + sessionId);
+            return;
+        }
+        uwbSession.setRangingIntervalSuggestion(rangingDurationMs, blockStride);
+    }
+
+    @Override
+    public void onSessionStateTransitionViolation(long sessionId, int previousState,
+            int sessionState, int reasonCode, int violation) {
+        String violationInfo = "sessionId: " + sessionId + ", previousState: " + previousState
+                + ", sessionState: " + sessionState + ", reasonCode: " + reasonCode
+                + ", violation: " + violation;
+        Log.w(TAG, "onSessionStateTransitionViolation: " + violationInfo);
+        mDbgSessionTransitionViolations.add(violationInfo);
+    }
+
+    /**
+     * Offsets the initiation time of a new FiRa session past the ranging rounds of the sessions of
+     * the other role ranging on the same chip, and reports the conflicts between the two roles.
+     */
+    private void coordinateDualRole(UwbSession uwbSession) {
+        if (!mUwbInjector.getDeviceConfigFacade().isDualRoleCoordinationEnabled()
+                || !(uwbSession.getParams() instanceof FiraOpenSessionParams)) {
+            return;
+        }
+        List<DualRoleScheduler.SessionTiming> activeSessions = new ArrayList<>();
+        for (UwbSession session : mSessionTable.values()) {
+            if (session == uwbSession
+                    || !session.getChipId().equals(uwbSession.getChipId())
+                    || session.getSessionState() != UwbUciConstants.UWB_SESSION_STATE_ACTIVE
+                    || !(session.getParams() instanceof FiraOpenSessionParams)) {
+                continue;
+            }
+            activeSessions.add(DualRoleScheduler.SessionTiming.fromParams(
+                    session.getSessionId(), (FiraOpenSessionParams) session.getParams()));
+        }
+        DualRoleScheduler.Plan plan = DualRoleScheduler.plan(
+                DualRoleScheduler.SessionTiming.fromParams(uwbSession.getSessionId(),
+                        (FiraOpenSessionParams) uwbSession.getParams()),
+                activeSessions);
+        if (plan.initiationTimeOffsetMs > 0) {
+            Log.i(TAG, "Dual role: initiation time of session " + uwbSession.getSessionId()
+                    + " offset by " + plan.initiationTimeOffsetMs + " ms");
+            uwbSession.addInitiationTimeOffset(plan.initiationTimeOffsetMs);
+        }
+        for (DualRoleScheduler.Conflict conflict : plan.conflicts) {
+            Log.w(TAG, "Dual role " + conflict);
+            mDbgDualRoleConflicts.add(conflict);
+        }
+    }
+
+    /**
+     * Lets the native stack report only the near/far edges of a FiRa session configured for
+     * proximity edge notifications, as the app does not receive anything else for it.
+     */
+    private void registerProximityOnlySession(UwbSession uwbSession) {
+        if (!mUwbInjector.getDeviceConfigFacade().isNativeProximityFastPathEnabled()
+                || !(uwbSession.getParams() instanceof FiraOpenSessionParams)) {
+            return;
+        }
+        FiraOpenSessionParams params = (FiraOpenSessionParams) uwbSession.getParams();
+        if (params.getRangeDataNtfConfig()
+                != FiraParams.RANGE_DATA_NTF_CONFIG_ENABLE_PROXIMITY_EDGE_TRIG) {
+            return;
+        }
+        if (!mNativeUwbManager.setProximityOnlySession(uwbSession.getSessionId(), true,
+                params.getRangeDataNtfProximityNear(), params.getRangeDataNtfProximityFar(),
+                uwbSession.getChipId())) {
+            Log.w(TAG, "Failed to register proximity-only session " + uwbSession.getSessionId());
+        }
+    }
+
+    /**
+     * Lets the native stack coarsen the range data of a session whose non-privileged app may not
+     * locate its peers precisely, so that the precise values never reach the app.
+     */
+    private void applyLocationPrivacy(UwbSession uwbSession) {
+        if (!uwbSession.hasNonPrivilegedApp()
+                || mUwbInjector.checkFineLocationPermission(uwbSession.getAttributionSource())) {
+            return;
+        }
+        if (!mNativeUwbManager.setCoarseLocation(uwbSession.getSessionId(), true,
+                COARSE_LOCATION_DISTANCE_STEP_CM, uwbSession.getChipId())) {
+            Log.w(TAG, "Failed to set the coarse location privacy of session "
+                    + uwbSession.getSessionId());
+        }
+    }
+
     /** Updates pose information if the session is using an ApplicationPoseSource */
     public void updatePose(SessionHandle sessionHandle, PersistableBundle params) {
         int sessionId = getSessionId(sessionHandle);
@@ -494,6 +650,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         public long sequenceNum;
         public long address;
         public byte[] payload;
+        public long receivedAtNanos;
     }
 
     @Override
@@ -549,6 +706,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                     // If session was stopped explicitly, then the onStopped() is sent from
                     // stopRanging method.
                     if (reasonCode != REASON_STATE_CHANGE_WITH_SESSION_MANAGEMENT_COMMANDS) {
+                        notifyPendingDataSendCancelled(uwbSession);
                         mSessionNotificationManager.onRangingStoppedWithUciReasonCode(
                                 uwbSession, reasonCode);
                         mUwbMetrics.longRangingStopEvent(uwbSession);
@@ -576,6 +734,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                     .setAppPackageName(appPackageName)
                     .setSessiontoken(sessionToken)
                     .setProtocolName(uwbSession.getProtocolName())
+                    .setHostStopReason(uwbSession.getHostStopReason())
                     .build()
                     .toBundle();
             try {
@@ -587,6 +746,21 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         }
     }
 
+    /**
+     * Reports the sends for which no terminal DATA_TRANSFER_STATUS_NTF was received as cancelled,
+     * so that every send is accounted for before the session is reported stopped.
+     */
+    private void notifyPendingDataSendCancelled(UwbSession uwbSession) {
+        for (Map.Entry<Long, SendDataInfo> entry : uwbSession.drainSendDataInfo().entrySet()) {
+            Log.d(TAG, "notifyPendingDataSendCancelled(): sessionId = "
+                    + uwbSession.getSessionId() + ", sequenceNum = " + entry.getKey());
+            mSessionNotificationManager.onDataSendFailed(
+                    uwbSession, entry.getValue().remoteDeviceAddress,
+                    UwbUciConstants.STATUS_CODE_DATA_TRANSFER_CANCELLED,
+                    entry.getValue().params);
+        }
+    }
+
     private int setAppConfigurations(UwbSession uwbSession) {
         if (uwbSession.getProtocolName().equals(FiraParams.PROTOCOL_NAME)
                         && uwbSession.getParams() instanceof FiraOpenSessionParams) {
@@ -660,6 +834,15 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             return;
         }
 
+        if (!isPinnedFiraVersionSupported(uwbSession)) {
+            rangingCallbacks.onRangingOpenFailed(sessionHandle, RangingChangeReason.BAD_PARAMETERS,
+                    UwbSessionNotificationHelper.convertUciStatusToParam(protocolName,
+                            UwbUciConstants.STATUS_CODE_INVALID_PARAM));
+            mUwbMetrics.logRangingInitEvent(uwbSession,
+                    UwbUciConstants.STATUS_CODE_INVALID_PARAM);
+            return;
+        }
+
         boolean maxSessionsExceeded = false;
         // TODO: getCccSessionCount and getFiraSessionCount should be chip specific
         if (protocolName.equals(AliroParams.PROTOCOL_NAME)
@@ -710,6 +893,56 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         return;
     }
 
+    /**
+     * Checks that the FiRa PHY/MAC versions the session is pinned to, if any, are within the
+     * ranges supported by the UWBS.
+     */
+    private boolean isPinnedFiraVersionSupported(UwbSession uwbSession) {
+        if (!(uwbSession.getParams() instanceof FiraOpenSessionParams)) {
+            return true;
+        }
+        FiraOpenSessionParams params = (FiraOpenSessionParams) uwbSession.getParams();
+        FiraProtocolVersion pinnedPhyVersion = params.getPinnedPhyVersion();
+        FiraProtocolVersion pinnedMacVersion = params.getPinnedMacVersion();
+        if (pinnedPhyVersion == null && pinnedMacVersion == null) {
+            return true;
+        }
+        GenericSpecificationParams specificationParams = mUwbInjector.getUwbServiceCore()
+                .getCachedSpecificationParams(uwbSession.getChipId());
+        if (specificationParams == null
+                || specificationParams.getFiraSpecificationParams() == null) {
+            Log.e(TAG, "Cannot pin the FiRa version without the specification params");
+            return false;
+        }
+        FiraSpecificationParams firaSpecificationParams =
+                specificationParams.getFiraSpecificationParams();
+        if (pinnedPhyVersion != null && !isVersionInRange(pinnedPhyVersion,
+                firaSpecificationParams.getMinPhyVersionSupported(),
+                firaSpecificationParams.getMaxPhyVersionSupported())) {
+            Log.e(TAG, "Pinned FiRa PHY version " + pinnedPhyVersion + " is not supported");
+            return false;
+        }
+        if (pinnedMacVersion != null && !isVersionInRange(pinnedMacVersion,
+                firaSpecificationParams.getMinMacVersionSupported(),
+                firaSpecificationParams.getMaxMacVersionSupported())) {
+            Log.e(TAG, "Pinned FiRa MAC version " + pinnedMacVersion + " is not supported");
+            return false;
+        }
+        return true;
+    }
+
+    private static boolean isVersionInRange(FiraProtocolVersion version,
+            FiraProtocolVersion min, FiraProtocolVersion max) {
+        return compareVersions(version, min) >= 0 && compareVersions(version, max) <= 0;
+    }
+
+    private static int compareVersions(FiraProtocolVersion a, FiraProtocolVersion b) {
+        if (a.getMajor() != b.getMajor()) {
+            return Integer.compare(a.getMajor(), b.getMajor());
+        }
+        return Integer.compare(a.getMinor(), b.getMinor());
+    }
+
     private boolean tryMakeSpaceForFiraSession(int priorityThreshold) {
         Optional<UwbSession> lowestPrioritySession = getSessionWithLowestPriorityByProtocol(
                 FiraParams.PROTOCOL_NAME);
@@ -815,7 +1048,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
     }
 
     private synchronized void stopRangingInternal(SessionHandle sessionHandle,
-            boolean triggeredBySystemPolicy) {
+            int hostStopReason) {
         if (!isExistedSession(sessionHandle)) {
             Log.i(TAG, "Not initialized session ID");
             return;
@@ -828,7 +1061,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         UwbSession uwbSession = getUwbSession(sessionId);
         int currentSessionState = getCurrentSessionState(sessionId);
         if (currentSessionState == UwbUciConstants.UWB_SESSION_STATE_ACTIVE) {
-            mEventTask.execute(SESSION_STOP_RANGING, uwbSession, triggeredBySystemPolicy ? 1 : 0);
+            mEventTask.execute(SESSION_STOP_RANGING, uwbSession, hostStopReason);
         } else if (currentSessionState == UwbUciConstants.UWB_SESSION_STATE_IDLE) {
             Log.i(TAG, "session is already idle state");
             mSessionNotificationManager.onRangingStopped(uwbSession,
@@ -842,7 +1075,47 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
     }
 
     public synchronized void stopRanging(SessionHandle sessionHandle) {
-        stopRangingInternal(sessionHandle, false /* triggeredBySystemPolicy */);
+        stopRangingInternal(sessionHandle, SessionStatus.HOST_STOP_REASON_LOCAL_API);
+    }
+
+    /**
+     * Stops a session for a platform policy, e.g. thermal mitigation. The app is told that the
+     * session was stopped by the system policy, and the OEM extension gets {@code hostStopReason}
+     * along with the session status.
+     *
+     * @param hostStopReason one of the SessionStatus.HOST_STOP_REASON_* constants.
+     */
+    public synchronized void stopRangingForSystemPolicy(SessionHandle sessionHandle,
+            int hostStopReason) {
+        stopRangingInternal(sessionHandle, hostStopReason);
+    }
+
+    /**
+     * Finds the active sessions of the chip ranging on a channel missing from
+     * {@code allowedChannels}, e.g. after the country code changed, and stops them if
+     * {@code autoSuspend} is set. The apps of the stopped sessions are told that the regulation
+     * stopped them.
+     */
+    public synchronized RegulatoryImpact onRegulatoryChannelsChanged(String chipId,
+            @Nullable String countryCode, List<Integer> allowedChannels, boolean autoSuspend) {
+        List<Integer> affectedSessionIds = new ArrayList<>();
+        for (UwbSession uwbSession : mSessionTable.values()) {
+            if (!chipId.equals(uwbSession.getChipId())
+                    || uwbSession.getSessionState() != UwbUciConstants.UWB_SESSION_STATE_ACTIVE) {
+                continue;
+            }
+            Integer channel = uwbSession.getChannel();
+            if (channel == null || allowedChannels.contains(channel)) {
+                continue;
+            }
+            affectedSessionIds.add(uwbSession.getSessionId());
+            if (autoSuspend) {
+                stopRangingInternal(uwbSession.getSessionHandle(),
+                        SessionStatus.HOST_STOP_REASON_REGULATORY);
+            }
+        }
+        return new RegulatoryImpact(chipId, countryCode, allowedChannels, affectedSessionIds,
+                autoSuspend && !affectedSessionIds.isEmpty());
     }
 
     /**
@@ -858,6 +1131,27 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                 .orElse(null);
     }
 
+    /**
+     * Get the link quality of a controlee of the given UWB session over its last ranging rounds.
+     * This API returns {@code null} when the UWB session or the controlee is not found.
+     */
+    @Nullable
+    public LinkQualityTracker.LinkQuality getLinkQuality(SessionHandle sessionHandle,
+            UwbAddress controleeAddress) {
+        UwbSession uwbSession = getUwbSession(sessionHandle);
+        if (uwbSession == null) {
+            return null;
+        }
+        if (uwbSession.mControlees == null) {
+            return null;
+        }
+        UwbControlee controlee = uwbSession.getControlee(controleeAddress);
+        if (controlee == null) {
+            return null;
+        }
+        return controlee.getLinkQualityTracker().getLinkQuality();
+    }
+
     /**
      * Get the UwbSession corresponding to the given UWB SessionHandle. This API returns
      * {@code null} when the UWB session is not found.
@@ -897,8 +1191,14 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             return;
         }
 
+        for (UwbOwrAoaMeasurement uwbOwrAoaMeasurement : rangingData.getRangingOwrAoaMeasures()) {
+            processOwrAoaMeasurement(rangingData, uwbOwrAoaMeasurement, uwbSession);
+        }
+    }
+
+    private void processOwrAoaMeasurement(UwbRangingData rangingData,
+            UwbOwrAoaMeasurement uwbOwrAoaMeasurement, UwbSession uwbSession) {
         // Record the OWR Aoa Measurement from the RANGE_DATA_NTF.
-        UwbOwrAoaMeasurement uwbOwrAoaMeasurement = rangingData.getRangingOwrAoaMeasure();
         mAdvertiseManager.updateAdvertiseTarget(uwbOwrAoaMeasurement);
 
         byte[] macAddressBytes = getValidMacAddressFromOwrAoaMeasurement(
@@ -941,8 +1241,8 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             }
 
             receivedDataInfoList.stream().forEach(r ->
-                    mSessionNotificationManager.onDataReceived(
-                            uwbSession, uwbAddress, new PersistableBundle(), r.payload));
+                    mSessionNotificationManager.onDataReceived(uwbSession, uwbAddress,
+                            createReceivedDataParameters(r.receivedAtNanos), r.payload));
             mUwbMetrics.logDataToUpperLayer(uwbSession, receivedDataInfoList.size());
             mAdvertiseManager.removeAdvertiseTarget(macAddress);
         }
@@ -994,6 +1294,24 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         // mNativeUwbManager.deviceReset(UwbUciConstants.UWBS_RESET);
     }
 
+    /**
+     * Stops and deinitializes the sessions of a chip in the UWBS, e.g. before its firmware is
+     * updated, and closes them.
+     */
+    public synchronized void deinitAllSession(String chipId) {
+        Log.d(TAG, "deinitAllSession(): chipId=" + chipId);
+        for (UwbSession uwbSession : mSessionTable.values()) {
+            if (!chipId.equals(uwbSession.getChipId())) {
+                continue;
+            }
+            if (uwbSession.getSessionState() == UwbUciConstants.UWB_SESSION_STATE_ACTIVE) {
+                mNativeUwbManager.stopRanging(uwbSession.getSessionId(), chipId);
+            }
+            mNativeUwbManager.deInitSession(uwbSession.getSessionId(), chipId);
+            handleOnDeInit(uwbSession);
+        }
+    }
+
     public synchronized void handleOnDeInit(UwbSession uwbSession) {
         if (!isExistedSession(uwbSession.getSessionHandle())) {
             Log.i(TAG, "onDeinit - Ignoring already deleted session "
@@ -1025,6 +1343,13 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         return UwbUciConstants.UWB_SESSION_STATE_ERROR;
     }
 
+    /** Returns whether a session of the chip is ranging. */
+    public boolean hasActiveSession(String chipId) {
+        return mSessionTable.values().stream().anyMatch(
+                s -> chipId.equals(s.getChipId())
+                        && s.getSessionState() == UwbUciConstants.UWB_SESSION_STATE_ACTIVE);
+    }
+
     public int getSessionCount() {
         return mSessionTable.size();
     }
@@ -1794,8 +2119,8 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
 
                 case SESSION_STOP_RANGING: {
                     UwbSession uwbSession = (UwbSession) msg.obj;
-                    boolean triggeredBySystemPolicy = msg.arg1 == 1;
-                    handleStopRanging(uwbSession, triggeredBySystemPolicy);
+                    int hostStopReason = msg.arg1;
+                    handleStopRanging(uwbSession, hostStopReason);
                     break;
                 }
 
@@ -1899,6 +2224,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                             status = UwbUciConstants.STATUS_CODE_FAILED;
                             if (uwbSession.getSessionState()
                                     == UwbUciConstants.UWB_SESSION_STATE_INIT) {
+                                coordinateDualRole(uwbSession);
                                 uwbSession.setNeedsQueryUwbsTimestamp(
                                         null /* cccRangingStartParams */);
                                 uwbSession.setAbsoluteInitiationTimeIfNeeded();
@@ -1913,6 +2239,8 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                                 if (uwbSession.getSessionState()
                                         == UwbUciConstants.UWB_SESSION_STATE_IDLE) {
                                     mSessionNotificationManager.onRangingOpened(uwbSession);
+                                    registerProximityOnlySession(uwbSession);
+                                    applyLocationPrivacy(uwbSession);
                                     status = UwbUciConstants.STATUS_CODE_OK;
                                 } else {
                                     status = UwbUciConstants.STATUS_CODE_FAILED;
@@ -1954,6 +2282,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                     () -> {
                         int status = UwbUciConstants.STATUS_CODE_FAILED;
                         synchronized (uwbSession.getWaitObj()) {
+                            uwbSession.setHostStopReason(SessionStatus.HOST_STOP_REASON_NONE);
                             uwbSession.setAbsoluteInitiationTimeIfNeeded();
                             if (uwbSession.getNeedsAppConfigUpdate()) {
                                 uwbSession.resetNeedsAppConfigUpdate();
@@ -2053,7 +2382,7 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             Trace.endSection();
         }
 
-        private void handleStopRanging(UwbSession uwbSession, boolean triggeredBySystemPolicy) {
+        private void handleStopRanging(UwbSession uwbSession, int hostStopReason) {
             Trace.beginSection("UWB#handleStopRanging");
             // TODO(b/211445008): Consolidate to a single uwb thread.
             FutureTask<Integer> stopRangingTask = new FutureTask<>(
@@ -2061,12 +2390,16 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                         int status = UwbUciConstants.STATUS_CODE_FAILED;
                         synchronized (uwbSession.getWaitObj()) {
                             uwbSession.setOperationType(SESSION_STOP_RANGING);
+                            // Set before the stop, for the session status notification to carry it.
+                            uwbSession.setHostStopReason(hostStopReason);
                             status = mNativeUwbManager.stopRanging(uwbSession.getSessionId(),
                                     uwbSession.getChipId());
                             if (status != UwbUciConstants.STATUS_CODE_OK) {
                                 if (uwbSession.getSessionState()
                                         == UwbUciConstants.UWB_SESSION_STATE_IDLE) {
-                                    handleStopRangingParams(uwbSession, true /*systemPolicy*/);
+                                    notifyPendingDataSendCancelled(uwbSession);
+                                    handleStopRangingParams(uwbSession,
+                                            RangingChangeReason.SYSTEM_POLICY);
                                     return UwbUciConstants.STATUS_CODE_OK;
                                 }
                                 mSessionNotificationManager.onRangingStopFailed(uwbSession, status);
@@ -2075,7 +2408,9 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                             uwbSession.getWaitObj().blockingWait();
                             if (uwbSession.getSessionState()
                                     == UwbUciConstants.UWB_SESSION_STATE_IDLE) {
-                                handleStopRangingParams(uwbSession, triggeredBySystemPolicy);
+                                notifyPendingDataSendCancelled(uwbSession);
+                                handleStopRangingParams(uwbSession,
+                                        getRangingChangeReason(hostStopReason));
                             } else {
                                 status = UwbUciConstants.STATUS_CODE_FAILED;
                                 mSessionNotificationManager.onRangingStopFailed(uwbSession,
@@ -2113,8 +2448,19 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             Trace.endSection();
         }
 
-        private void handleStopRangingParams(UwbSession uwbSession,
-                boolean triggeredBySystemPolicy) {
+        /** Gets the reason passed to the app for a session stopped by the host. */
+        private int getRangingChangeReason(int hostStopReason) {
+            switch (hostStopReason) {
+                case SessionStatus.HOST_STOP_REASON_LOCAL_API:
+                    return RangingChangeReason.LOCAL_API;
+                case SessionStatus.HOST_STOP_REASON_REGULATORY:
+                    return RangingChangeReason.SYSTEM_REGULATION;
+                default:
+                    return RangingChangeReason.SYSTEM_POLICY;
+            }
+        }
+
+        private void handleStopRangingParams(UwbSession uwbSession, int apiReasonCode) {
             PersistableBundle rangingStoppedParamsBundle = new PersistableBundle();
 
             // For ALIRO sessions, retrieve the app configs
@@ -2153,9 +2499,6 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                 rangingStoppedParamsBundle = statusAndParams.second.toBundle();
             }
 
-            int apiReasonCode = triggeredBySystemPolicy
-                    ? RangingChangeReason.SYSTEM_POLICY
-                    : RangingChangeReason.LOCAL_API;
             mSessionNotificationManager.onRangingStoppedWithApiReasonCode(
                     uwbSession, apiReasonCode, rangingStoppedParamsBundle);
         }
@@ -2676,6 +3019,11 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
 
         // reasonCode from the last received SESSION_STATUS_NTF for this session.
         private int mLastSessionStatusNtfReasonCode = -1;
+        // Why the host last stopped the session, reset when ranging starts.
+        private int mHostStopReason = SessionStatus.HOST_STOP_REASON_NONE;
+        // The last ranging adjustment suggested by the native stack, -1 if none.
+        private int mSuggestedRangingDurationMs = -1;
+        private int mSuggestedBlockStride = -1;
 
         // Keeps track of all controlees in the session.
         public Map<UwbAddress, UwbControlee> mControlees;
@@ -2924,6 +3272,21 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             return mSendDataInfoMap.get(sequenceNumber);
         }
 
+        /**
+         * Remove and return all the SendDataInfo still stored in the current UWB Session, ordered
+         * by sequence number.
+         */
+        public SortedMap<Long, SendDataInfo> drainSendDataInfo() {
+            SortedMap<Long, SendDataInfo> pending = new TreeMap<>();
+            for (Long sequenceNumber : mSendDataInfoMap.keySet()) {
+                SendDataInfo sendDataInfo = mSendDataInfoMap.remove(sequenceNumber);
+                if (sendDataInfo != null) {
+                    pending.put(sequenceNumber, sendDataInfo);
+                }
+            }
+            return pending;
+        }
+
         /**
          * Adds a Controlee to the session. This should only be called to reflect
          *  the state of the native UWB interface.
@@ -2997,6 +3360,19 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             return this.mParams;
         }
 
+        /** Gets the channel the session ranges on, or null if its protocol has no channel. */
+        @Nullable
+        public Integer getChannel() {
+            if (mParams instanceof FiraOpenSessionParams) {
+                return ((FiraOpenSessionParams) mParams).getChannelNumber();
+            } else if (mParams instanceof CccOpenRangingParams) {
+                return ((CccOpenRangingParams) mParams).getChannel();
+            } else if (mParams instanceof AliroOpenRangingParams) {
+                return ((AliroOpenRangingParams) mParams).getChannel();
+            }
+            return null;
+        }
+
         public int getDataRepetitionCount() {
             return mDataRepetitionCount;
         }
@@ -3396,7 +3772,8 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                     Log.w(TAG, "Continuous errors or no ranging results detected for "
                             + mRangingErrorStreakTimeoutMs + " ms."
                             + " Stopping session");
-                    stopRangingInternal(mSessionHandle, true /* triggeredBySystemPolicy */);
+                    stopRangingInternal(mSessionHandle,
+                            SessionStatus.HOST_STOP_REASON_ERROR_STREAK);
                 };
                 Log.v(TAG, "Starting error timer for "
                         + mRangingErrorStreakTimeoutMs + " ms.");
@@ -3451,7 +3828,8 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                         + address + " for " + mRangingErrorStreakTimeoutMs + " ms.");
                 if (mControlees.size() == 1) {
                     Log.w(TAG, "No active controlees, stopping session");
-                    stopRangingInternal(mSessionHandle, true /* triggeredBySystemPolicy */);
+                    stopRangingInternal(mSessionHandle,
+                            SessionStatus.HOST_STOP_REASON_ERROR_STREAK);
                 } else {
                     removeControleeDueToErrorStreakTimeout(address);
                 }
@@ -3494,7 +3872,8 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                 mNonPrivilegedBgAppTimerListener = () -> {
                     Log.w(TAG, "Non-privileged app in background for longer than timeout - "
                             + " Stopping session");
-                    stopRangingInternal(mSessionHandle, true /* triggeredBySystemPolicy */);
+                    stopRangingInternal(mSessionHandle,
+                            SessionStatus.HOST_STOP_REASON_BACKGROUND_APP);
                 };
                 mAlarmManager.setExact(AlarmManager.ELAPSED_REALTIME_WAKEUP,
                         mUwbInjector.getElapsedSinceBootMillis()
@@ -3538,6 +3917,13 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             FiraRangingReconfigureParams reconfigureParams = builder.build();
             reconfigureInternal(mSessionHandle, reconfigureParams,
                     Reconfiguration.Reason.FG_STATE_CHANGE);
+            // The range data notifications still in flight until the reconfiguration is applied
+            // are dropped in the native stack.
+            if (!mNativeUwbManager.setSessionInfoSuppressed(getSessionId(),
+                    !mHasNonPrivilegedFgAppOrService, getChipId())) {
+                Log.w(TAG, "Failed to set the session info suppression of session "
+                        + getSessionId());
+            }
 
             if (!mUwbInjector.getDeviceConfigFacade().isBackgroundRangingEnabled()) {
                 Log.d(TAG, "reconfigureFiraSessionOnFgStateChange - System policy disallows for "
@@ -3572,6 +3958,37 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
             mLastSessionStatusNtfReasonCode = lastSessionStatusNtfReasonCode;
         }
 
+        public int getHostStopReason() {
+            return mHostStopReason;
+        }
+
+        public void setHostStopReason(int hostStopReason) {
+            mHostStopReason = hostStopReason;
+        }
+
+        public int getSuggestedRangingDurationMs() {
+            return mSuggestedRangingDurationMs;
+        }
+
+        public int getSuggestedBlockStride() {
+            return mSuggestedBlockStride;
+        }
+
+        public void setRangingIntervalSuggestion(int rangingDurationMs, int blockStride) {
+            mSuggestedRangingDurationMs = rangingDurationMs;
+            mSuggestedBlockStride = blockStride;
+        }
+
+        /** Delays the first ranging block of a FiRa session, relative to its start. */
+        public void addInitiationTimeOffset(long offsetMs) {
+            if (mParams instanceof FiraOpenSessionParams) {
+                FiraOpenSessionParams firaParams = (FiraOpenSessionParams) mParams;
+                this.mParams = firaParams.toBuilder()
+                        .setInitiationTime(firaParams.getInitiationTime() + offsetMs)
+                        .build();
+            }
+        }
+
         /** Creates a filter engine based on the device configuration. */
         public UwbFilterEngine createFilterEngine() {
             if (mParams instanceof FiraOpenSessionParams) {
@@ -3650,6 +4067,8 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                     + ", Protocol: " + getProtocolName()
                     + ", State: " + getSessionState()
                     + ", Data Send Sequence Number: " + mDataSndSequenceNumber
+                    + ", Suggested Ranging Duration Ms: " + mSuggestedRangingDurationMs
+                    + ", Suggested Block Stride: " + mSuggestedBlockStride
                     + ", Params: " + getParams()
                     + ", AttributionSource: " + getAttributionSource()
                     + " }";
@@ -3680,6 +4099,14 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
         pw.println("Active sessions: ");
         for (UwbSession uwbSession : mSessionTable.values()) {
             pw.println(uwbSession);
+            if (uwbSession.mControlees == null) {
+                continue;
+            }
+            for (UwbControlee controlee : uwbSession.getControleeList()) {
+                pw.println("  " + controlee.getUwbAddress() + ": "
+                        + controlee.getLinkQualityTracker().getLinkQuality()
+                        + ", peerVendor = " + controlee.getPeerVendor());
+            }
         }
         pw.println("Recently closed sessions: ");
         for (UwbSession uwbSession: mDbgRecentlyClosedSessions.getEntries()) {
@@ -3695,6 +4122,14 @@ public class UwbSessionManager implements INativeUwbManager.SessionNotification,
                         .flatMap(Collection::stream)
                         .collect(Collectors.toList());
         pw.println("Non Privileged Fira Session Ids: " + nonPrivilegedSessionIds);
+        pw.println("Recent dual role conflicts: ");
+        for (DualRoleScheduler.Conflict conflict : mDbgDualRoleConflicts.getEntries()) {
+            pw.println(conflict);
+        }
+        pw.println("Recent session state transition violations: ");
+        for (String violationInfo : mDbgSessionTransitionViolations.getEntries()) {
+            pw.println(violationInfo);
+        }
         pw.println("---- Dump of UwbSessionManager ----");
     }
 
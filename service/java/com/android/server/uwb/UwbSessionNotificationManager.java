@@ -62,6 +62,8 @@ import java.util.Objects;
 
 public class UwbSessionNotificationManager {
     private static final String TAG = "UwbSessionNotiManager";
+    /** Key of the sweeps dropped by the native delivery budget, added to the radar data. */
+    public static final String KEY_RADAR_DROPPED_SWEEP_COUNT = "dropped_sweep_count";
     private final UwbInjector mUwbInjector;
 
     public UwbSessionNotificationManager(@NonNull UwbInjector uwbInjector) {
@@ -121,6 +123,40 @@ public class UwbSessionNotificationManager {
         }
     }
 
+    /**
+     * Notifies the near/far edge of a proximity-only session as a ranging result holding only the
+     * distance to the peer.
+     */
+    public void onProximityEvent(UwbSession uwbSession, int distanceCm, byte[] macAddress) {
+        SessionHandle sessionHandle = uwbSession.getSessionHandle();
+        IUwbRangingCallbacks uwbRangingCallbacks = uwbSession.getIUwbRangingCallbacks();
+        if (uwbSession.isDataDeliveryPermissionCheckNeeded()) {
+            boolean permissionGranted = mUwbInjector.checkUwbRangingPermissionForStartDataDelivery(
+                    uwbSession.getAttributionSource(), "uwb ranging result");
+            if (!permissionGranted) {
+                Log.e(TAG, "Not delivering proximity event because of permission denial"
+                        + sessionHandle);
+                return;
+            }
+            uwbSession.setDataDeliveryPermissionCheckNeeded(false);
+        }
+        RangingMeasurement rangingMeasurement = buildRangingMeasurement(
+                macAddress, RangingMeasurement.RANGING_STATUS_SUCCESS,
+                mUwbInjector.getElapsedSinceBootNanos(), RangingMeasurement.LOS_UNDETERMINED)
+                .setDistanceMeasurement(buildDistanceMeasurement(distanceCm))
+                .build();
+        RangingReport rangingReport = new RangingReport.Builder()
+                .addMeasurement(rangingMeasurement)
+                .build();
+        try {
+            uwbRangingCallbacks.onRangingResult(sessionHandle, rangingReport);
+            Log.i(TAG, "IUwbRangingCallbacks - onRangingResult (proximity event)");
+        } catch (Exception e) {
+            Log.e(TAG, "IUwbRangingCallbacks - onRangingResult : Failed");
+            e.printStackTrace();
+        }
+    }
+
     public void onRangingOpened(UwbSession uwbSession) {
         SessionHandle sessionHandle = uwbSession.getSessionHandle();
         IUwbRangingCallbacks uwbRangingCallbacks = uwbSession.getIUwbRangingCallbacks();
@@ -626,6 +662,7 @@ public class UwbSessionNotificationManager {
             uwbSession.setDataDeliveryPermissionCheckNeeded(false);
         }
         PersistableBundle radarDataBundle = getRadarData(radarData).toBundle();
+        radarDataBundle.putInt(KEY_RADAR_DROPPED_SWEEP_COUNT, radarData.droppedSweepCount);
         try {
             // TODO: Add radar specific @SystemApi
             // Temporary workaround to avoid adding a new @SystemApi for the short-term.
@@ -729,6 +766,8 @@ public class UwbSessionNotificationManager {
             UwbTwoWayMeasurement[] uwbTwoWayMeasurement = rangingData.getRangingTwoWayMeasures();
             for (int i = 0; i < rangingData.getNoOfRangingMeasures(); ++i) {
                 int rangingStatus = uwbTwoWayMeasurement[i].convertStatusCode();
+                UwbAddress addr = getComputedMacAddress(uwbTwoWayMeasurement[i].getMacAddress());
+                UwbControlee controlee = uwbSession.getControlee(addr);
 
                 RangingMeasurement.Builder rangingMeasurementBuilder = buildRangingMeasurement(
                         uwbTwoWayMeasurement[i].getMacAddress(), rangingStatus,
@@ -744,10 +783,14 @@ public class UwbSessionNotificationManager {
                             buildDistanceMeasurement(uwbTwoWayMeasurement[i].getDistance()));
 
                     // Aoa measurement is optional based on configuration.
+                    float aoaAzimuth = uwbTwoWayMeasurement[i].getAoaAzimuth();
+                    if (controlee != null && controlee.hasAoaAzimuthSignFlip()) {
+                        aoaAzimuth = -aoaAzimuth;
+                    }
                     AngleOfArrivalMeasurement angleOfArrivalMeasurement =
                             computeAngleOfArrivalMeasurement(
                                     isAoaAzimuthEnabled, isAoaElevationEnabled,
-                                    uwbTwoWayMeasurement[i].getAoaAzimuth(),
+                                    aoaAzimuth,
                                     uwbTwoWayMeasurement[i].getAoaAzimuthFom(),
                                     uwbTwoWayMeasurement[i].getAoaElevation(),
                                     uwbTwoWayMeasurement[i].getAoaElevationFom());
@@ -770,12 +813,23 @@ public class UwbSessionNotificationManager {
                     }
                 }
 
-                // TODO: No ranging measurement metadata defined, added for future usage
+                // The link quality is only reported when it crosses the threshold.
                 PersistableBundle rangingMeasurementMetadata = new PersistableBundle();
+                if (controlee != null) {
+                    LinkQualityTracker tracker = controlee.getLinkQualityTracker();
+                    if (tracker.onRound(uwbTwoWayMeasurement[i].isStatusCodeOk(),
+                            rssi < 0 ? rssi : LinkQualityTracker.RSSI_UNKNOWN,
+                            uwbTwoWayMeasurement[i].getAoaAzimuthFom())) {
+                        rangingMeasurementMetadata = tracker.getLinkQuality().toBundle(
+                                tracker.isBelowThreshold());
+                    }
+                    if (controlee.getPeerVendor() != null) {
+                        rangingMeasurementMetadata.putString(PeerVendorClassifier.KEY_PEER_VENDOR,
+                                controlee.getPeerVendor());
+                    }
+                }
                 rangingMeasurementBuilder.setRangingMeasurementMetadata(rangingMeasurementMetadata);
 
-                UwbAddress addr = getComputedMacAddress(uwbTwoWayMeasurement[i].getMacAddress());
-                UwbControlee controlee = uwbSession.getControlee(addr);
                 if (controlee != null) {
                     controlee.filterMeasurement(rangingMeasurementBuilder);
                 }
@@ -786,29 +840,32 @@ public class UwbSessionNotificationManager {
             rangingReportBuilder.addMeasurements(rangingMeasurements);
         } else if (rangingData.getRangingMeasuresType()
                 == UwbUciConstants.RANGING_MEASUREMENT_TYPE_OWR_AOA) {
-            UwbOwrAoaMeasurement uwbOwrAoaMeasurement = rangingData.getRangingOwrAoaMeasure();
-
-            int rangingStatus = uwbOwrAoaMeasurement.getRangingStatus();
-            RangingMeasurement.Builder rangingMeasurementBuilder = buildRangingMeasurement(
-                    uwbOwrAoaMeasurement.getMacAddress(), rangingStatus, elapsedRealtimeNanos,
-                    uwbOwrAoaMeasurement.getNLoS());
-
-            if (rangingStatus == FiraParams.STATUS_CODE_OK) {
-                // AngleOfArrivalMeasurement
-                AngleOfArrivalMeasurement angleOfArrivalMeasurement =
-                        computeAngleOfArrivalMeasurement(
-                                isAoaAzimuthEnabled, isAoaElevationEnabled,
-                                uwbOwrAoaMeasurement.getAoaAzimuth(),
-                                uwbOwrAoaMeasurement.getAoaAzimuthFom(),
-                                uwbOwrAoaMeasurement.getAoaElevation(),
-                                uwbOwrAoaMeasurement.getAoaElevationFom());
-                if (angleOfArrivalMeasurement != null) {
-                    rangingMeasurementBuilder.setAngleOfArrivalMeasurement(
-                            angleOfArrivalMeasurement);
+            List<RangingMeasurement> rangingMeasurements = new ArrayList<>();
+            for (UwbOwrAoaMeasurement uwbOwrAoaMeasurement
+                    : rangingData.getRangingOwrAoaMeasures()) {
+                int rangingStatus = uwbOwrAoaMeasurement.getRangingStatus();
+                RangingMeasurement.Builder rangingMeasurementBuilder = buildRangingMeasurement(
+                        uwbOwrAoaMeasurement.getMacAddress(), rangingStatus, elapsedRealtimeNanos,
+                        uwbOwrAoaMeasurement.getNLoS());
+
+                if (rangingStatus == FiraParams.STATUS_CODE_OK) {
+                    // AngleOfArrivalMeasurement
+                    AngleOfArrivalMeasurement angleOfArrivalMeasurement =
+                            computeAngleOfArrivalMeasurement(
+                                    isAoaAzimuthEnabled, isAoaElevationEnabled,
+                                    uwbOwrAoaMeasurement.getAoaAzimuth(),
+                                    uwbOwrAoaMeasurement.getAoaAzimuthFom(),
+                                    uwbOwrAoaMeasurement.getAoaElevation(),
+                                    uwbOwrAoaMeasurement.getAoaElevationFom());
+                    if (angleOfArrivalMeasurement != null) {
+                        rangingMeasurementBuilder.setAngleOfArrivalMeasurement(
+                                angleOfArrivalMeasurement);
+                    }
                 }
-            }
 
-            rangingReportBuilder.addMeasurement(rangingMeasurementBuilder.build());
+                rangingMeasurements.add(rangingMeasurementBuilder.build());
+            }
+            rangingReportBuilder.addMeasurements(rangingMeasurements);
         } else if (rangingData.getRangingMeasuresType()
                 == UwbUciConstants.RANGING_MEASUREMENT_TYPE_DL_TDOA) {
             List<RangingMeasurement> rangingMeasurements = new ArrayList<>();
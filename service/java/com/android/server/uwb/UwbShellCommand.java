@@ -79,6 +79,7 @@ import android.content.AttributionSource;
 import android.content.Context;
 import android.content.pm.PackageManager;
 import android.os.Binder;
+import android.os.Build;
 import android.os.Handler;
 import android.os.Looper;
 import android.os.PersistableBundle;
@@ -97,7 +98,10 @@ import androidx.annotation.Nullable;
 
 import com.android.internal.annotations.VisibleForTesting;
 import com.android.modules.utils.BasicShellCommandHandler;
+import com.android.server.uwb.data.UwbUciConstants;
+import com.android.server.uwb.data.UwbVendorUciResponse;
 import com.android.server.uwb.jni.NativeUwbManager;
+import com.android.server.uwb.params.TestStsMaterial;
 import com.android.server.uwb.util.ArrayUtils;
 
 import com.google.common.io.BaseEncoding;
@@ -636,6 +640,13 @@ public class UwbShellCommand extends BasicShellCommandHandler {
                     throw new IllegalArgumentException("vendorId expecting 2 bytes");
                 }
             }
+            if (option.equals("-T")) {
+                // Overrides -g and -v given before it.
+                TestStsMaterial stsMaterial =
+                        TestStsMaterial.fromSeed(Long.parseLong(getNextArgRequired()));
+                builder.setVendorId(stsMaterial.getVendorId());
+                builder.setStaticStsIV(stsMaterial.getStaticStsIV());
+            }
             if (option.equals("-h")) {
                 int slotDurationRstu = Integer.parseInt(getNextArgRequired());
                 builder.setSlotDurationRstu(slotDurationRstu);
@@ -1270,6 +1281,15 @@ public class UwbShellCommand extends BasicShellCommandHandler {
                                 + " instead");
                         return -1;
                     }
+                    String option = getNextOption();
+                    if (option != null && option.equals("-c")) {
+                        String chipId = getNextArgRequired();
+                        if (!mNativeUwbManager.setLogMode(logMode, chipId)) {
+                            pw.println("Failed to set log mode for chip " + chipId);
+                            return -1;
+                        }
+                        return 0;
+                    }
                     mUciLogModeStore.storeMode(logMode);
                     if (!mNativeUwbManager.setLogMode(logMode)) {
                         pw.println("Failed to set log mode. " + logMode
@@ -1281,6 +1301,189 @@ public class UwbShellCommand extends BasicShellCommandHandler {
                 case "get-log-mode":
                     pw.println("UWB Log Mode = " + mUciLogModeStore.getMode());
                     return 0;
+                case "set-uci-capture": {
+                    int mode;
+                    String modeStr = getNextArgRequired();
+                    switch (modeStr) {
+                        case "disabled":
+                            mode = NativeUwbManager.UCI_CAPTURE_MODE_DISABLED;
+                            break;
+                        case "all":
+                            mode = NativeUwbManager.UCI_CAPTURE_MODE_ALL;
+                            break;
+                        case "filtered":
+                            mode = NativeUwbManager.UCI_CAPTURE_MODE_FILTERED;
+                            break;
+                        default:
+                            pw.println("Invalid argument: UCI capture mode must be one of the"
+                                    + " following: disabled, all, or filtered. But got " + modeStr
+                                    + " instead");
+                            return -1;
+                    }
+                    List<Integer> sessionTokens = new ArrayList<>();
+                    List<Integer> gids = new ArrayList<>();
+                    String option = getNextOption();
+                    while (option != null) {
+                        if (option.equals("-s")) {
+                            sessionTokens.add(Integer.parseUnsignedInt(getNextArgRequired()));
+                        }
+                        if (option.equals("-g")) {
+                            gids.add(Integer.parseInt(getNextArgRequired()));
+                        }
+                        option = getNextOption();
+                    }
+                    if (!mNativeUwbManager.setUciCapture(mode,
+                            sessionTokens.stream().mapToInt(Integer::intValue).toArray(),
+                            gids.stream().mapToInt(Integer::intValue).toArray(),
+                            mUwbService.getDefaultChipId())) {
+                        pw.println("Failed to set UCI capture");
+                        return -1;
+                    }
+                    return 0;
+                }
+                case "simulate-controlee-departure": {
+                    if (!Build.isDebuggable()) {
+                        pw.println("The controlee departure simulation is only available on"
+                                + " debuggable builds");
+                        return -1;
+                    }
+                    int sessionId = Integer.parseInt(getNextArgRequired());
+                    byte[] macAddress =
+                            BaseEncoding.base16().decode(getNextArgRequired().toUpperCase());
+                    boolean departed = getNextArgRequiredTrueOrFalse("departed", "returned");
+                    String chipId = mUwbService.getDefaultChipId();
+                    String option = getNextOption();
+                    if (option != null && option.equals("-c")) {
+                        chipId = getNextArgRequired();
+                    }
+                    if (!mNativeUwbManager.simulateControleeDeparture(sessionId, macAddress,
+                            departed, chipId)) {
+                        pw.println("Failed to simulate the controlee departure, the session must"
+                                + " be initialized and the MAC address 2 or 8 bytes long");
+                        return -1;
+                    }
+                    return 0;
+                }
+                case "start-uci-recording": {
+                    if (!Build.isDebuggable()) {
+                        pw.println("The UCI recording is only available on debuggable builds");
+                        return -1;
+                    }
+                    String chipId = mUwbService.getDefaultChipId();
+                    String option = getNextOption();
+                    if (option != null && option.equals("-c")) {
+                        chipId = getNextArgRequired();
+                    }
+                    if (!mNativeUwbManager.startUciRecording(chipId)) {
+                        pw.println("Failed to start the UCI recording of chip " + chipId
+                                + ", UWB must be enabled");
+                        return -1;
+                    }
+                    pw.println("The recording starts when UWB is next enabled");
+                    return 0;
+                }
+                case "stop-uci-recording": {
+                    String chipId = mUwbService.getDefaultChipId();
+                    String option = getNextOption();
+                    if (option != null && option.equals("-c")) {
+                        chipId = getNextArgRequired();
+                    }
+                    String path = mNativeUwbManager.stopUciRecording(chipId);
+                    if (path == null) {
+                        pw.println("Failed to write the UCI recording of chip " + chipId);
+                        return -1;
+                    }
+                    pw.println("UCI recording written to " + path);
+                    return 0;
+                }
+                case "enter-maintenance-mode":
+                case "exit-maintenance-mode": {
+                    String chipId = mUwbService.getDefaultChipId();
+                    String option = getNextOption();
+                    if (option != null && option.equals("-c")) {
+                        chipId = getNextArgRequired();
+                    }
+                    boolean success = cmd.equals("enter-maintenance-mode")
+                            ? mUwbServiceCore.enterMaintenanceMode(chipId)
+                            : mUwbServiceCore.exitMaintenanceMode(chipId);
+                    if (!success) {
+                        pw.println("Failed to " + cmd.replace('-', ' ') + " for chip " + chipId);
+                        return -1;
+                    }
+                    return 0;
+                }
+                case "start-uci-proxy": {
+                    if (!Build.isDebuggable()) {
+                        pw.println("The UCI proxy is only available on debuggable builds");
+                        return -1;
+                    }
+                    int port = Integer.parseInt(getNextArgRequired());
+                    String chipId = mUwbService.getDefaultChipId();
+                    String option = getNextOption();
+                    if (option != null && option.equals("-c")) {
+                        chipId = getNextArgRequired();
+                    }
+                    if (!mUwbServiceCore.startUciProxy(chipId, port)) {
+                        pw.println("Failed to start the UCI proxy of chip " + chipId
+                                + ", UWB must be disabled");
+                        return -1;
+                    }
+                    return 0;
+                }
+                case "start-metrics-exporter": {
+                    if (!Build.isDebuggable()) {
+                        pw.println("The metrics exporter is only available on debuggable builds");
+                        return -1;
+                    }
+                    int port = Integer.parseInt(getNextArgRequired());
+                    if (!mUwbServiceCore.startMetricsExporter(port)) {
+                        pw.println("Failed to start the metrics exporter, UWB must be enabled"
+                                + " and the native_metrics_exporter_enabled flag set");
+                        return -1;
+                    }
+                    return 0;
+                }
+                case "stop-metrics-exporter": {
+                    if (!mUwbServiceCore.stopMetricsExporter()) {
+                        pw.println("Failed to stop the metrics exporter");
+                        return -1;
+                    }
+                    return 0;
+                }
+                case "inject-uci-message": {
+                    int mt = Integer.parseInt(getNextArgRequired());
+                    int gid = Integer.parseInt(getNextArgRequired(), 16);
+                    int oid = Integer.parseInt(getNextArgRequired(), 16);
+                    byte[] payload =
+                            BaseEncoding.base16().decode(getNextArgRequired().toUpperCase());
+                    String chipId = mUwbService.getDefaultChipId();
+                    String option = getNextOption();
+                    if (option != null && option.equals("-c")) {
+                        chipId = getNextArgRequired();
+                    }
+                    UwbVendorUciResponse response =
+                            mUwbServiceCore.injectUciMessage(mt, gid, oid, payload, chipId);
+                    if (response == null) {
+                        pw.println("Failed to inject the UCI message, UWB must be enabled");
+                        return -1;
+                    }
+                    pw.println("status=" + response.status + " gid=" + response.gid + " oid="
+                            + response.oid + " payload="
+                            + BaseEncoding.base16().encode(response.payload));
+                    return response.status == UwbUciConstants.STATUS_CODE_OK ? 0 : -1;
+                }
+                case "stop-uci-proxy": {
+                    String chipId = mUwbService.getDefaultChipId();
+                    String option = getNextOption();
+                    if (option != null && option.equals("-c")) {
+                        chipId = getNextArgRequired();
+                    }
+                    if (!mUwbServiceCore.stopUciProxy(chipId)) {
+                        pw.println("Failed to stop the UCI proxy of chip " + chipId);
+                        return -1;
+                    }
+                    return 0;
+                }
                 case "status":
                     printStatus(pw);
                     return 0;
@@ -1498,6 +1701,8 @@ public class UwbShellCommand extends BasicShellCommandHandler {
                 + " [-f <tof,azimuth,elevation,aoa-fom>(result-report-config)"
                 + " [-g <staticStsIV>(staticStsIV 6-bytes)"
                 + " [-v <staticStsVendorId>(staticStsVendorId 2-bytes)"
+                + " [-T <seed>](staticStsVendorId and staticStsIV derived from seed,"
+                + " debuggable builds only)"
                 + " [-w enabled|disabled](has-result-report-phase)"
                 + " [-y enabled|disabled](hopping-mode, default = disabled)"
                 + " [-p <preamble-code-index>](preamble-code-index, default = 10)"
@@ -1596,8 +1801,48 @@ public class UwbShellCommand extends BasicShellCommandHandler {
         pw.println("    Sets country code to <two-letter code> or left for normal value");
         pw.println("  get-power-stats");
         pw.println("    Get power stats");
-        pw.println("  set-log-mode disabled|filtered|unfiltered");
-        pw.println("    Sets the log mode for UCI packet capturing");
+        pw.println("  set-log-mode disabled|filtered|unfiltered [-c <chip id>]");
+        pw.println("    Sets the log mode for UCI packet capturing. With -c, only sets it for the"
+                + " chip until the next UWB restart. Session keys and STS IVs are always"
+                + " redacted, the filtered mode also redacts the vendor ID.");
+        pw.println("  set-uci-capture disabled|all|filtered [-s <session token>] [-g <gid>]");
+        pw.println("    Sets which UCI packets are captured, on top of the log mode. In filtered"
+                + " mode, only the packets of the given session tokens and GIDs are captured."
+                + " -s and -g may be repeated");
+        pw.println("  simulate-controlee-departure <session id> <mac address in hex>"
+                + " departed|returned [-c <chip id>]");
+        pw.println("    Omits the measurements of the controlee from the range data of the session"
+                + " while departed, to test the handling of the peer loss. The MAC address bytes"
+                + " are in the order of the ranging reports (debuggable builds only)");
+        pw.println("  start-uci-recording [-c <chip id>]");
+        pw.println("    Records the UCI packets exchanged with the HAL of the chip, unredacted, from"
+                + " the next time UWB is enabled, for their replay in the native unit tests."
+                + " Discards the previous recording (debuggable builds only)");
+        pw.println("  stop-uci-recording [-c <chip id>]");
+        pw.println("    Stops recording the UCI packets of the chip, and writes the recording to a"
+                + " file");
+        pw.println("  enter-maintenance-mode [-c <chip id>]");
+        pw.println("    Closes the sessions of the chip and rejects the new ones, for a firmware"
+                + " update through vendor commands. Device states are not handled meanwhile");
+        pw.println("  exit-maintenance-mode [-c <chip id>]");
+        pw.println("    Revalidates the device info and capabilities of the chip, then accepts"
+                + " the new sessions again");
+        pw.println("  start-uci-proxy <port> [-c <chip id>]");
+        pw.println("    Proxies the HAL of the chip on a local TCP port for a host-side UWB stack,"
+                + " reachable through adb forward. UWB must be disabled, enabling it stops the"
+                + " proxy (debuggable builds only)");
+        pw.println("  stop-uci-proxy [-c <chip id>]");
+        pw.println("    Stops proxying the HAL of the chip");
+        pw.println("  start-metrics-exporter <port>");
+        pw.println("    Serves the metrics of the chips in the Prometheus text format on a local TCP"
+                + " port at /metrics, reachable through adb forward. UWB must be enabled,"
+                + " disabling it stops the exporter (debuggable builds only)");
+        pw.println("  stop-metrics-exporter");
+        pw.println("    Stops serving the metrics of the chips");
+        pw.println("  inject-uci-message <mt> <gid in hex> <oid in hex> <payload in hex>"
+                + " [-c <chip id>]");
+        pw.println("    Sends a raw UCI message to the chip and prints its response. Production"
+                + " builds only allow the GIDs of the uci_injection_gid_allowlist flag");
     }
 
     @Override
@@ -15,6 +15,8 @@
  */
 package com.android.server.uwb.data;
 
+import com.google.uwb.support.fira.FiraProtocolVersion;
+
 import java.util.Arrays;
 import java.util.Objects;
 
@@ -44,6 +46,20 @@ public class UwbDeviceInfoResponse {
         this.mVendorSpecInfo = vendorSpecInfo;
     }
 
+    /**
+     * Gets the FiRa MAC version implemented by the UWBS.
+     */
+    public FiraProtocolVersion getMacVersion() {
+        return FiraProtocolVersion.fromLEShort((short) mMacVersion);
+    }
+
+    /**
+     * Gets the FiRa PHY version implemented by the UWBS.
+     */
+    public FiraProtocolVersion getPhyVersion() {
+        return FiraProtocolVersion.fromLEShort((short) mPhyVersion);
+    }
+
     @Override
     public boolean equals(Object o) {
         if (this == o) return true;
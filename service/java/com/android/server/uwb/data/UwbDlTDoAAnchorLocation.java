@@ -0,0 +1,89 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.data;
+
+/** DT-Anchor location of a DL-TDoA measurement, as decoded by the native stack. */
+public class UwbDlTDoAAnchorLocation {
+    /** No location was included, or it could not be decoded. */
+    public static final int TYPE_NONE = 0;
+    /** WGS-84 coordinates. */
+    public static final int TYPE_WGS84 = 1;
+    /** Coordinates relative to the reference anchor. */
+    public static final int TYPE_RELATIVE = 2;
+
+    public int mType;
+    public double mCoordinate1;
+    public double mCoordinate2;
+    public double mCoordinate3;
+
+    public UwbDlTDoAAnchorLocation(int type, double coordinate1, double coordinate2,
+            double coordinate3) {
+        mType = type;
+        mCoordinate1 = coordinate1;
+        mCoordinate2 = coordinate2;
+        mCoordinate3 = coordinate3;
+    }
+
+    public int getType() {
+        return mType;
+    }
+
+    /** Latitude in degrees, for {@link #TYPE_WGS84}. */
+    public double getLatitude() {
+        return mCoordinate1;
+    }
+
+    /** Longitude in degrees, for {@link #TYPE_WGS84}. */
+    public double getLongitude() {
+        return mCoordinate2;
+    }
+
+    /** Altitude in meters, for {@link #TYPE_WGS84}. */
+    public double getAltitude() {
+        return mCoordinate3;
+    }
+
+    /** X in meters, for {@link #TYPE_RELATIVE}. */
+    public double getX() {
+        return mCoordinate1;
+    }
+
+    /** Y in meters, for {@link #TYPE_RELATIVE}. */
+    public double getY() {
+        return mCoordinate2;
+    }
+
+    /** Z in meters, for {@link #TYPE_RELATIVE}. */
+    public double getZ() {
+        return mCoordinate3;
+    }
+
+    @Override
+    public String toString() {
+        switch (mType) {
+            case TYPE_WGS84:
+                return "UwbDlTDoAAnchorLocation{Latitude=" + mCoordinate1
+                        + ", Longitude=" + mCoordinate2
+                        + ", Altitude=" + mCoordinate3 + '}';
+            case TYPE_RELATIVE:
+                return "UwbDlTDoAAnchorLocation{X=" + mCoordinate1
+                        + ", Y=" + mCoordinate2
+                        + ", Z=" + mCoordinate3 + '}';
+            default:
+                return "UwbDlTDoAAnchorLocation{None}";
+        }
+    }
+}
@@ -42,12 +42,27 @@ public class UwbDlTDoAMeasurement {
     public int mInitiatorResponderTof;
     public byte[] mAnchorLocation;
     public byte[] mActiveRangingRounds;
+    public UwbDlTDoAAnchorLocation mDecodedAnchorLocation;
 
     public UwbDlTDoAMeasurement(byte[] macAddress, int status, int messageType, int messageControl,
             int blockIndex, int roundIndex, int nLoS, int aoaAzimuth, int aoaAzimuthFom,
             int aoaElevation, int aoaElevationFom, int rssi, long txTimestamp, long rxTimestamp,
             int anchorCfo, int cfo, long initiatorReplyTime, long responderReplyTime,
             int initiatorResponderTof, byte[] anchorLocation, byte[] activeRangingRounds) {
+        this(macAddress, status, messageType, messageControl, blockIndex, roundIndex, nLoS,
+                aoaAzimuth, aoaAzimuthFom, aoaElevation, aoaElevationFom, rssi, txTimestamp,
+                rxTimestamp, anchorCfo, cfo, initiatorReplyTime, responderReplyTime,
+                initiatorResponderTof, anchorLocation, activeRangingRounds,
+                UwbDlTDoAAnchorLocation.TYPE_NONE, 0, 0, 0);
+    }
+
+    public UwbDlTDoAMeasurement(byte[] macAddress, int status, int messageType, int messageControl,
+            int blockIndex, int roundIndex, int nLoS, int aoaAzimuth, int aoaAzimuthFom,
+            int aoaElevation, int aoaElevationFom, int rssi, long txTimestamp, long rxTimestamp,
+            int anchorCfo, int cfo, long initiatorReplyTime, long responderReplyTime,
+            int initiatorResponderTof, byte[] anchorLocation, byte[] activeRangingRounds,
+            int anchorLocationType, double anchorCoordinate1, double anchorCoordinate2,
+            double anchorCoordinate3) {
         mMacAddress = macAddress;
         mStatus = status;
         mMessageType = messageType;
@@ -69,6 +84,10 @@ public class UwbDlTDoAMeasurement {
         mInitiatorResponderTof = initiatorResponderTof;
         mAnchorLocation = anchorLocation;
         mActiveRangingRounds = activeRangingRounds;
+        mDecodedAnchorLocation = anchorLocationType == UwbDlTDoAAnchorLocation.TYPE_NONE
+                ? null
+                : new UwbDlTDoAAnchorLocation(anchorLocationType, anchorCoordinate1,
+                        anchorCoordinate2, anchorCoordinate3);
     }
 
     public byte[] getMacAddress() {
@@ -155,6 +174,11 @@ public class UwbDlTDoAMeasurement {
         return mActiveRangingRounds;
     }
 
+    /** Returns the decoded anchor location, or null if it was not included or not decodable. */
+    public UwbDlTDoAAnchorLocation getDecodedAnchorLocation() {
+        return mDecodedAnchorLocation;
+    }
+
     private float toFloatFromQ9_7_Format(int value) {
         return UwbUtil.convertQFormatToFloat(UwbUtil.twos_compliment(value, 16),
                 9, 7);
@@ -189,6 +213,7 @@ public class UwbDlTDoAMeasurement {
                 ", InitiatorResponderTof=" + mInitiatorResponderTof +
                 ", AnchorLocation=" + Arrays.toString(mAnchorLocation) +
                 ", ActiveRangingRounds=" + Arrays.toString(mActiveRangingRounds) +
+                ", DecodedAnchorLocation=" + mDecodedAnchorLocation +
                 '}';
     }
 }
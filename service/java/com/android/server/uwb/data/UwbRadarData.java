@@ -28,6 +28,8 @@ public class UwbRadarData {
     public int bitsPerSample;
     public int sweepOffset;
     public UwbRadarSweepData[] radarSweepData;
+    /** Sweeps dropped by the delivery budget of the session since the previous radar data. */
+    public int droppedSweepCount;
 
     public UwbRadarData(
             long sessionId,
@@ -37,6 +39,19 @@ public class UwbRadarData {
             int bitsPerSample,
             int sweepOffset,
             UwbRadarSweepData[] radarSweepData) {
+        this(sessionId, statusCode, radarDataType, samplesPerSweep, bitsPerSample, sweepOffset,
+                radarSweepData, 0);
+    }
+
+    public UwbRadarData(
+            long sessionId,
+            int statusCode,
+            int radarDataType,
+            int samplesPerSweep,
+            int bitsPerSample,
+            int sweepOffset,
+            UwbRadarSweepData[] radarSweepData,
+            int droppedSweepCount) {
         this.sessionId = sessionId;
         this.statusCode = statusCode;
         this.radarDataType = radarDataType;
@@ -44,6 +59,7 @@ public class UwbRadarData {
         this.bitsPerSample = bitsPerSample;
         this.sweepOffset = sweepOffset;
         this.radarSweepData = radarSweepData;
+        this.droppedSweepCount = droppedSweepCount;
     }
 
     @Override
@@ -27,7 +27,7 @@ public class UwbRangingData {
     public int mNoOfRangingMeasures;
     public UwbTwoWayMeasurement[] mRangingTwoWayMeasures;
     public byte[] mRawNtfData;
-    public UwbOwrAoaMeasurement mRangingOwrAoaMeasure;
+    public UwbOwrAoaMeasurement[] mRangingOwrAoaMeasures;
     public UwbDlTDoAMeasurement[] mUwbDlTDoAMeasurements;
 
     public UwbRangingData(long seqCounter, long sessionId, int rcrIndication,
@@ -49,6 +49,15 @@ public class UwbRangingData {
             long currRangingInterval, int rangingMeasuresType, int macAddressMode,
             int noOfRangingMeasures, UwbOwrAoaMeasurement rangingOwrAoaMeasure,
             byte[] rawNtfData) {
+        this(seqCounter, sessionId, rcrIndication, currRangingInterval, rangingMeasuresType,
+                macAddressMode, noOfRangingMeasures,
+                new UwbOwrAoaMeasurement[] {rangingOwrAoaMeasure}, rawNtfData);
+    }
+
+    public UwbRangingData(long seqCounter, long sessionId, int rcrIndication,
+            long currRangingInterval, int rangingMeasuresType, int macAddressMode,
+            int noOfRangingMeasures, UwbOwrAoaMeasurement[] rangingOwrAoaMeasures,
+            byte[] rawNtfData) {
         this.mSeqCounter = seqCounter;
         this.mSessionId = sessionId;
         this.mRcrIndication = rcrIndication;
@@ -56,7 +65,7 @@ public class UwbRangingData {
         this.mRangingMeasuresType = rangingMeasuresType;
         this.mMacAddressMode = macAddressMode;
         this.mNoOfRangingMeasures = noOfRangingMeasures;
-        this.mRangingOwrAoaMeasure = rangingOwrAoaMeasure;
+        this.mRangingOwrAoaMeasures = rangingOwrAoaMeasures;
         this.mRawNtfData = rawNtfData;
     }
 
@@ -111,8 +120,16 @@ public class UwbRangingData {
         return mRawNtfData;
     }
 
+    /**
+     * Gets the first OWR AoA measurement of the notification, or null if there is none.
+     */
     public UwbOwrAoaMeasurement getRangingOwrAoaMeasure() {
-        return mRangingOwrAoaMeasure;
+        return (mRangingOwrAoaMeasures == null || mRangingOwrAoaMeasures.length == 0)
+                ? null : mRangingOwrAoaMeasures[0];
+    }
+
+    public UwbOwrAoaMeasurement[] getRangingOwrAoaMeasures() {
+        return mRangingOwrAoaMeasures;
     }
 
     public UwbDlTDoAMeasurement[] getUwbDlTDoAMeasurements() {
@@ -141,7 +158,7 @@ public class UwbRangingData {
                     + ", RangingMeasuresType = " + mRangingMeasuresType
                     + ", MacAddressMode = " + mMacAddressMode
                     + ", NoOfRangingMeasures = " + mNoOfRangingMeasures
-                    + ", RangingOwrAoaMeasure = " + mRangingOwrAoaMeasure.toString()
+                    + ", RangingOwrAoaMeasures = " + Arrays.toString(mRangingOwrAoaMeasures)
                     + ", RawNotificationData = " + Arrays.toString(mRawNtfData)
                     + '}';
         } else if (mRangingMeasuresType == UwbUciConstants.RANGING_MEASUREMENT_TYPE_DL_TDOA) {
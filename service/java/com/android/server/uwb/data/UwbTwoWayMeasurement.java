@@ -20,6 +20,9 @@ import android.uwb.RangingMeasurement;
 import com.android.server.uwb.util.UwbUtil;
 
 public class UwbTwoWayMeasurement {
+    /** The UWBS did not report which antenna pair produced the measurement. */
+    public static final int ANTENNA_PAIR_ID_UNKNOWN = -1;
+
     public byte[] mMacAddress;
     public int mStatus;
     public int mNLoS;
@@ -34,11 +37,25 @@ public class UwbTwoWayMeasurement {
     public int mAoaDestElevationFom;
     public int mSlotIndex;
     public int mRssi;
+    public float mRssiDbm;
+    public int mRssiRaw;
+    public int mAntennaPairId;
 
     public UwbTwoWayMeasurement(byte[] macAddress, int status, int nLoS, int distance,
             int aoaAzimuth, int aoaAzimuthFom, int aoaElevation,
             int aoaElevationFom, int aoaDestAzimuth, int aoaDestAzimuthFom,
-            int aoaDestElevation, int aoaDestElevationFom, int slotIndex, int rssiHalfDbmAbs) {
+            int aoaDestElevation, int aoaDestElevationFom, int slotIndex, float rssiDbm,
+            int rssiRaw) {
+        this(macAddress, status, nLoS, distance, aoaAzimuth, aoaAzimuthFom, aoaElevation,
+                aoaElevationFom, aoaDestAzimuth, aoaDestAzimuthFom, aoaDestElevation,
+                aoaDestElevationFom, slotIndex, rssiDbm, rssiRaw, ANTENNA_PAIR_ID_UNKNOWN);
+    }
+
+    public UwbTwoWayMeasurement(byte[] macAddress, int status, int nLoS, int distance,
+            int aoaAzimuth, int aoaAzimuthFom, int aoaElevation,
+            int aoaElevationFom, int aoaDestAzimuth, int aoaDestAzimuthFom,
+            int aoaDestElevation, int aoaDestElevationFom, int slotIndex, float rssiDbm,
+            int rssiRaw, int antennaPairId) {
 
         this.mMacAddress = macAddress;
         this.mStatus = status;
@@ -56,12 +73,14 @@ public class UwbTwoWayMeasurement {
         this.mAoaDestElevationFom = aoaDestElevationFom;
         this.mSlotIndex = slotIndex;
         /*
-         * According to FiRa UCI Generic Technical Specification v2.0.0,
-         * decode the rssi value in dBm format where the abs value was encoded in FP Q7.1 format.
-         * Just need to divide this number by two and take the negative value.
+         * The rssi value is already decoded to dBm by the native stack, from the abs value
+         * encoded in FP Q7.1 format (FiRa UCI Generic Technical Specification v2.0.0).
          * If the reported RSSI is lower than RSSI_MIN, set it to RSSI_MIN to avoid exceptions.
          */
-        this.mRssi = Math.max(-rssiHalfDbmAbs / 2, RangingMeasurement.RSSI_MIN);
+        this.mRssi = Math.max((int) rssiDbm, RangingMeasurement.RSSI_MIN);
+        this.mRssiDbm = rssiDbm;
+        this.mRssiRaw = rssiRaw;
+        this.mAntennaPairId = antennaPairId;
     }
 
     public byte[] getMacAddress() {
@@ -119,6 +138,28 @@ public class UwbTwoWayMeasurement {
         return mRssi;
     }
 
+    /**
+     * Gets the RSSI in dBm, with the 0.5 dBm resolution reported by the UWBS.
+     */
+    public float getRssiDbm() {
+        return mRssiDbm;
+    }
+
+    /**
+     * Gets the RSSI as reported by the UWBS, the abs value in dBm encoded in FP Q7.1 format.
+     */
+    public int getRssiRaw() {
+        return mRssiRaw;
+    }
+
+    /**
+     * Gets the vendor defined ID of the antenna pair which produced the AoA, or
+     * {@link #ANTENNA_PAIR_ID_UNKNOWN} if the UWBS did not report it.
+     */
+    public int getAntennaPairId() {
+        return mAntennaPairId;
+    }
+
     public boolean isStatusCodeOk() {
         return mStatus == UwbUciConstants.STATUS_CODE_OK
                 || mStatus == UwbUciConstants.STATUS_CODE_OK_NEGATIVE_DISTANCE_REPORT;
@@ -160,6 +201,9 @@ public class UwbTwoWayMeasurement {
                 + ", AoaDestElevationFom = " + mAoaDestElevationFom
                 + ", SlotIndex = 0x" + UwbUtil.toHexString(mSlotIndex)
                 + ", RSSI = " + mRssi
+                + ", RssiDbm = " + mRssiDbm
+                + ", RssiRaw = " + mRssiRaw
+                + ", AntennaPairId = " + mAntennaPairId
                 + '}';
     }
 }
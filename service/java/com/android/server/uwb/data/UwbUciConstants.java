@@ -260,6 +260,8 @@ public class UwbUciConstants {
             FiraParams.STATUS_CODE_DATA_TRANSFER_NTF_OK;
     public static final int STATUS_CODE_DATA_TRANSFER_ERROR_DATA_TRANSFER =
             FiraParams.STATUS_CODE_DATA_TRANSFER_NTF_ERROR_DATA_TRANSFER;
+    public static final int STATUS_CODE_DATA_TRANSFER_CANCELLED =
+            FiraParams.STATUS_CODE_DATA_TRANSFER_NTF_CANCELLED;
 
     /**
      * Table TBD: Status codes in the DATA_TRANSFER_PHASE_CONFIGURATION_NTF
@@ -0,0 +1,74 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+import java.util.Arrays;
+
+/**
+ * The data transfers of a session in flight when the service was shut down, and thus aborted.
+ */
+public class UwbAbortedDataTransfers {
+    private final int mSessionId;
+    private final int mTransferCount;
+    private final int mRemainingFragments;
+    private final long[] mDestinations;
+
+    public UwbAbortedDataTransfers(int sessionId, int transferCount, int remainingFragments,
+            long[] destinations) {
+        mSessionId = sessionId;
+        mTransferCount = transferCount;
+        mRemainingFragments = remainingFragments;
+        mDestinations = destinations;
+    }
+
+    /**
+     * get the session id
+     */
+    public int getSessionId() {
+        return mSessionId;
+    }
+
+    /**
+     * get the number of data packets sent and not yet acknowledged by the UWBS
+     */
+    public int getTransferCount() {
+        return mTransferCount;
+    }
+
+    /**
+     * get the number of fragments of the data packets not yet passed to the UWBS
+     */
+    public int getRemainingFragments() {
+        return mRemainingFragments;
+    }
+
+    /**
+     * get the destination addresses of the data packets, as little endian longs
+     */
+    public long[] getDestinations() {
+        return mDestinations;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbAbortedDataTransfers: session_id=").append(mSessionId)
+                .append(" transfer_count=").append(mTransferCount)
+                .append(" remaining_fragments=").append(mRemainingFragments)
+                .append(" destinations=").append(Arrays.toString(mDestinations));
+        return sb.toString();
+    }
+}
@@ -0,0 +1,114 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+/**
+ * A UCI command passed to the HAL by the native stack, and what followed it. The durations not
+ * measured are -1.
+ */
+public class UwbHalTransaction {
+    private final int mCorrelationId;
+    private final int mGid;
+    private final int mOid;
+    private final long mAgeUs;
+    private final long mHalSendUs;
+    private final long mResponseLatencyUs;
+    private final int mNotificationCount;
+    private final long mFirstNotificationLatencyUs;
+
+    public UwbHalTransaction(int correlationId, int gid, int oid, long ageUs, long halSendUs,
+            long responseLatencyUs, int notificationCount, long firstNotificationLatencyUs) {
+        mCorrelationId = correlationId;
+        mGid = gid;
+        mOid = oid;
+        mAgeUs = ageUs;
+        mHalSendUs = halSendUs;
+        mResponseLatencyUs = responseLatencyUs;
+        mNotificationCount = notificationCount;
+        mFirstNotificationLatencyUs = firstNotificationLatencyUs;
+    }
+
+    /**
+     * get the correlation id of the command, which tags its response and notifications in the
+     * native logs
+     */
+    public int getCorrelationId() {
+        return mCorrelationId;
+    }
+
+    /**
+     * get the group identifier of the command
+     */
+    public int getGid() {
+        return mGid;
+    }
+
+    /**
+     * get the opcode identifier of the command
+     */
+    public int getOid() {
+        return mOid;
+    }
+
+    /**
+     * get the time elapsed since the command was passed to the HAL in micros
+     */
+    public long getAgeUs() {
+        return mAgeUs;
+    }
+
+    /**
+     * get the time the HAL took to accept the command in micros, -1 if it failed to
+     */
+    public long getHalSendUs() {
+        return mHalSendUs;
+    }
+
+    /**
+     * get the time the response took to come back from the HAL in micros, -1 if it did not
+     */
+    public long getResponseLatencyUs() {
+        return mResponseLatencyUs;
+    }
+
+    /**
+     * get the number of notifications received from the HAL until the next command
+     */
+    public int getNotificationCount() {
+        return mNotificationCount;
+    }
+
+    /**
+     * get the time the first of these notifications took to come back in micros, -1 if none
+     */
+    public long getFirstNotificationLatencyUs() {
+        return mFirstNotificationLatencyUs;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbHalTransaction: correlation_id=").append(mCorrelationId)
+                .append(" gid=").append(mGid)
+                .append(" oid=").append(mOid)
+                .append(" age_us=").append(mAgeUs)
+                .append(" hal_send_us=").append(mHalSendUs)
+                .append(" response_latency_us=").append(mResponseLatencyUs)
+                .append(" notification_count=").append(mNotificationCount)
+                .append(" first_notification_latency_us=").append(mFirstNotificationLatencyUs);
+        return sb.toString();
+    }
+}
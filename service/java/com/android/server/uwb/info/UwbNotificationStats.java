@@ -0,0 +1,96 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+import java.util.Arrays;
+
+/**
+ * Notification dispatch statistics of one callback type, as collected by the native stack.
+ * All values should never decrease after the creation of the native dispatcher.
+ */
+public class UwbNotificationStats {
+    public static final int CALLBACK_TYPE_RANGING = 0;
+    public static final int CALLBACK_TYPE_RADAR = 1;
+    public static final int CALLBACK_TYPE_VENDOR = 2;
+    public static final int CALLBACK_TYPE_DATA_RCV = 3;
+
+    /**
+     * Upper bounds (exclusive) of the latency histogram buckets in micros. The last histogram
+     * bucket counts the dispatches slower than the last bound.
+     */
+    public static final long[] LATENCY_BUCKET_BOUNDS_US = {100, 500, 1000, 5000, 10000, 50000};
+
+    private final int mCallbackType;
+    private final long mCount;
+    private final long mFailureCount;
+    private final long mMaxLatencyUs;
+    private final long[] mLatencyHistogram;
+
+    public UwbNotificationStats(int callbackType, long count, long failureCount,
+            long maxLatencyUs, long[] latencyHistogram) {
+        mCallbackType = callbackType;
+        mCount = count;
+        mFailureCount = failureCount;
+        mMaxLatencyUs = maxLatencyUs;
+        mLatencyHistogram = latencyHistogram;
+    }
+
+    /**
+     * get the callback type, one of CALLBACK_TYPE_*
+     */
+    public int getCallbackType() {
+        return mCallbackType;
+    }
+
+    /**
+     * get the total number of dispatched notifications
+     */
+    public long getCount() {
+        return mCount;
+    }
+
+    /**
+     * get the number of notifications which failed to be delivered
+     */
+    public long getFailureCount() {
+        return mFailureCount;
+    }
+
+    /**
+     * get the slowest dispatch latency in micros
+     */
+    public long getMaxLatencyUs() {
+        return mMaxLatencyUs;
+    }
+
+    /**
+     * get the dispatch latency histogram, see {@link #LATENCY_BUCKET_BOUNDS_US}
+     */
+    public long[] getLatencyHistogram() {
+        return mLatencyHistogram;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbNotificationStats: callback_type=").append(mCallbackType)
+                .append(" count=").append(mCount)
+                .append(" failure_count=").append(mFailureCount)
+                .append(" max_latency_us=").append(mMaxLatencyUs)
+                .append(" latency_histogram=").append(Arrays.toString(mLatencyHistogram));
+        return sb.toString();
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+import com.android.server.uwb.util.UwbUtil;
+
+/**
+ * State of an advertiser heard by an OWR AoA observer session, as tracked by the native stack.
+ */
+public class UwbOwrAoaAdvertiserInfo {
+    private final byte[] mMacAddress;
+    private final int mLastBlockIndex;
+    private final int mLastFrameSequenceNumber;
+    private final long mFrameCount;
+    private final long mMissedFrameCount;
+    private final long mOutOfOrderCount;
+    private final long mMsSinceLastSeen;
+
+    public UwbOwrAoaAdvertiserInfo(byte[] macAddress, int lastBlockIndex,
+            int lastFrameSequenceNumber, long frameCount, long missedFrameCount,
+            long outOfOrderCount, long msSinceLastSeen) {
+        mMacAddress = macAddress;
+        mLastBlockIndex = lastBlockIndex;
+        mLastFrameSequenceNumber = lastFrameSequenceNumber;
+        mFrameCount = frameCount;
+        mMissedFrameCount = missedFrameCount;
+        mOutOfOrderCount = outOfOrderCount;
+        mMsSinceLastSeen = msSinceLastSeen;
+    }
+
+    /**
+     * get the MAC address of the advertiser
+     */
+    public byte[] getMacAddress() {
+        return mMacAddress;
+    }
+
+    /**
+     * get the block index of the last measurement
+     */
+    public int getLastBlockIndex() {
+        return mLastBlockIndex;
+    }
+
+    /**
+     * get the frame sequence number of the last measurement
+     */
+    public int getLastFrameSequenceNumber() {
+        return mLastFrameSequenceNumber;
+    }
+
+    /**
+     * get the number of measurements received from the advertiser
+     */
+    public long getFrameCount() {
+        return mFrameCount;
+    }
+
+    /**
+     * get the number of frames skipped according to the frame sequence numbers
+     */
+    public long getMissedFrameCount() {
+        return mMissedFrameCount;
+    }
+
+    /**
+     * get the number of frames received repeated or out of order
+     */
+    public long getOutOfOrderCount() {
+        return mOutOfOrderCount;
+    }
+
+    /**
+     * get the time elapsed since the last measurement in millis
+     */
+    public long getMsSinceLastSeen() {
+        return mMsSinceLastSeen;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbOwrAoaAdvertiserInfo: mac_address=")
+                .append(UwbUtil.toHexString(mMacAddress))
+                .append(" last_block_index=").append(mLastBlockIndex)
+                .append(" last_frame_sequence_number=").append(mLastFrameSequenceNumber)
+                .append(" frame_count=").append(mFrameCount)
+                .append(" missed_frame_count=").append(mMissedFrameCount)
+                .append(" out_of_order_count=").append(mOutOfOrderCount)
+                .append(" ms_since_last_seen=").append(mMsSinceLastSeen);
+        return sb.toString();
+    }
+}
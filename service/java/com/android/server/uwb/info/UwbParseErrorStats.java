@@ -0,0 +1,52 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+/**
+ * Number of notifications of one packet family which the native stack failed to convert, since
+ * the previous fetch.
+ */
+public class UwbParseErrorStats {
+    private final String mFamily;
+    private final long mCount;
+
+    public UwbParseErrorStats(String family, long count) {
+        mFamily = family;
+        mCount = count;
+    }
+
+    /**
+     * get the packet family, e.g. "Core" or "SessionInfo"
+     */
+    public String getFamily() {
+        return mFamily;
+    }
+
+    /**
+     * get the number of conversion failures
+     */
+    public long getCount() {
+        return mCount;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbParseErrorStats: family=").append(mFamily)
+                .append(" count=").append(mCount);
+        return sb.toString();
+    }
+}
@@ -0,0 +1,76 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+import com.android.server.uwb.util.UwbUtil;
+
+/**
+ * Signature of a segment of the measurement stream of an attested session, as returned by the
+ * platform attestor.
+ */
+public class UwbRangingAttestation {
+    private final int mSessionId;
+    private final int mSequenceNumber;
+    private final int mMeasurementCount;
+    private final byte[] mSignature;
+
+    public UwbRangingAttestation(int sessionId, int sequenceNumber, int measurementCount,
+            byte[] signature) {
+        mSessionId = sessionId;
+        mSequenceNumber = sequenceNumber;
+        mMeasurementCount = measurementCount;
+        mSignature = signature;
+    }
+
+    /**
+     * get the session id
+     */
+    public int getSessionId() {
+        return mSessionId;
+    }
+
+    /**
+     * get the position of the segment in the measurement stream, a gap reveals a segment which
+     * could not be signed
+     */
+    public int getSequenceNumber() {
+        return mSequenceNumber;
+    }
+
+    /**
+     * get the number of SESSION_INFO_NTFs in the segment
+     */
+    public int getMeasurementCount() {
+        return mMeasurementCount;
+    }
+
+    /**
+     * get the signature of the segment
+     */
+    public byte[] getSignature() {
+        return mSignature;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbRangingAttestation: session_id=").append(mSessionId)
+                .append(" sequence_number=").append(mSequenceNumber)
+                .append(" measurement_count=").append(mMeasurementCount)
+                .append(" signature=").append(UwbUtil.toHexString(mSignature));
+        return sb.toString();
+    }
+}
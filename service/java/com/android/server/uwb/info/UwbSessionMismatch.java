@@ -0,0 +1,99 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+import java.util.Objects;
+
+/**
+ * A difference between the sessions tracked by the native stack and the UWBS's view of them, as
+ * queried by SESSION_GET_COUNT and SESSION_GET_STATE.
+ */
+public class UwbSessionMismatch {
+    /** The UWBS counts another number of sessions than the host tracks. */
+    public static final int TYPE_COUNT = 0;
+    /** The UWBS does not know a session tracked by the host. */
+    public static final int TYPE_UNKNOWN_SESSION = 1;
+    /** The UWBS reports another state than the last one it notified for the session. */
+    public static final int TYPE_STATE = 2;
+
+    private final int mType;
+    private final int mSessionId;
+    private final int mHostValue;
+    private final int mUwbsValue;
+
+    public UwbSessionMismatch(int type, int sessionId, int hostValue, int uwbsValue) {
+        mType = type;
+        mSessionId = sessionId;
+        mHostValue = hostValue;
+        mUwbsValue = uwbsValue;
+    }
+
+    /**
+     * get the type of the mismatch
+     */
+    public int getType() {
+        return mType;
+    }
+
+    /**
+     * get the session id, 0 for {@link #TYPE_COUNT}
+     */
+    public int getSessionId() {
+        return mSessionId;
+    }
+
+    /**
+     * get the session count or state tracked by the host, 0 for {@link #TYPE_UNKNOWN_SESSION}
+     */
+    public int getHostValue() {
+        return mHostValue;
+    }
+
+    /**
+     * get the session count or state reported by the UWBS, 0 for {@link #TYPE_UNKNOWN_SESSION}
+     */
+    public int getUwbsValue() {
+        return mUwbsValue;
+    }
+
+    @Override
+    public boolean equals(Object o) {
+        if (this == o) {
+            return true;
+        }
+        if (!(o instanceof UwbSessionMismatch)) {
+            return false;
+        }
+        UwbSessionMismatch that = (UwbSessionMismatch) o;
+        return mType == that.mType && mSessionId == that.mSessionId
+                && mHostValue == that.mHostValue && mUwbsValue == that.mUwbsValue;
+    }
+
+    @Override
+    public int hashCode() {
+        return Objects.hash(mType, mSessionId, mHostValue, mUwbsValue);
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbSessionMismatch: type=").append(mType)
+                .append(" session_id=").append(mSessionId)
+                .append(" host_value=").append(mHostValue)
+                .append(" uwbs_value=").append(mUwbsValue);
+        return sb.toString();
+    }
+}
@@ -0,0 +1,116 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+/**
+ * A session as tracked by the native stack, from its initialization until its deinitialization.
+ */
+public class UwbSessionRecord {
+    /** Session state value used until the UWBS notifies the first state of the session. */
+    public static final int SESSION_STATE_UNKNOWN = -1;
+
+    private final int mSessionId;
+    private final int mSessionType;
+    private final int mSessionState;
+    private final int mControleeCount;
+    private final long mMsSinceLastActivity;
+    private final long mDataPacketCount;
+    private final long mMeanDataIntervalUs;
+    private final long mDataJitterUs;
+
+    public UwbSessionRecord(int sessionId, int sessionType, int sessionState,
+            int controleeCount, long msSinceLastActivity, long dataPacketCount,
+            long meanDataIntervalUs, long dataJitterUs) {
+        mSessionId = sessionId;
+        mSessionType = sessionType;
+        mSessionState = sessionState;
+        mControleeCount = controleeCount;
+        mMsSinceLastActivity = msSinceLastActivity;
+        mDataPacketCount = dataPacketCount;
+        mMeanDataIntervalUs = meanDataIntervalUs;
+        mDataJitterUs = dataJitterUs;
+    }
+
+    /**
+     * get the session id
+     */
+    public int getSessionId() {
+        return mSessionId;
+    }
+
+    /**
+     * get the session type, as passed to the SESSION_INIT_CMD
+     */
+    public int getSessionType() {
+        return mSessionType;
+    }
+
+    /**
+     * get the last session state notified by the UWBS, or {@link #SESSION_STATE_UNKNOWN}
+     */
+    public int getSessionState() {
+        return mSessionState;
+    }
+
+    /**
+     * get the number of controlees added by successful multicast list updates
+     */
+    public int getControleeCount() {
+        return mControleeCount;
+    }
+
+    /**
+     * get the time in millis since the last command or notification of the session
+     */
+    public long getMsSinceLastActivity() {
+        return mMsSinceLastActivity;
+    }
+
+    /**
+     * get the number of data packets received by the session
+     */
+    public long getDataPacketCount() {
+        return mDataPacketCount;
+    }
+
+    /**
+     * get the mean interval in micros between two data packets received by the session
+     */
+    public long getMeanDataIntervalUs() {
+        return mMeanDataIntervalUs;
+    }
+
+    /**
+     * get the smoothed inter-arrival jitter in micros of the data packets received by the session
+     */
+    public long getDataJitterUs() {
+        return mDataJitterUs;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbSessionRecord: session_id=").append(mSessionId)
+                .append(" session_type=").append(mSessionType)
+                .append(" session_state=").append(mSessionState)
+                .append(" controlee_count=").append(mControleeCount)
+                .append(" ms_since_last_activity=").append(mMsSinceLastActivity)
+                .append(" data_packet_count=").append(mDataPacketCount)
+                .append(" mean_data_interval_us=").append(mMeanDataIntervalUs)
+                .append(" data_jitter_us=").append(mDataJitterUs);
+        return sb.toString();
+    }
+}
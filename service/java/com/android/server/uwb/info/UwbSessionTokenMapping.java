@@ -0,0 +1,84 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+/**
+ * A session token mapped to a session id by the UWBS, as learned by the native stack from the
+ * SESSION_STATUS_NTF.
+ */
+public class UwbSessionTokenMapping {
+    private final int mSessionToken;
+    private final int mSessionId;
+    private final boolean mRemapped;
+    private final int mPreviousSessionId;
+    private final long mMsSinceMapped;
+
+    public UwbSessionTokenMapping(int sessionToken, int sessionId, boolean remapped,
+            int previousSessionId, long msSinceMapped) {
+        mSessionToken = sessionToken;
+        mSessionId = sessionId;
+        mRemapped = remapped;
+        mPreviousSessionId = previousSessionId;
+        mMsSinceMapped = msSinceMapped;
+    }
+
+    /**
+     * get the session token
+     */
+    public int getSessionToken() {
+        return mSessionToken;
+    }
+
+    /**
+     * get the session id the token is mapped to
+     */
+    public int getSessionId() {
+        return mSessionId;
+    }
+
+    /**
+     * whether the token was mapped to another session before, i.e. reused by the UWBS
+     */
+    public boolean isRemapped() {
+        return mRemapped;
+    }
+
+    /**
+     * get the session id the token was mapped to before, only valid if {@link #isRemapped()}
+     */
+    public int getPreviousSessionId() {
+        return mPreviousSessionId;
+    }
+
+    /**
+     * get the time in millis since the token was mapped to the session
+     */
+    public long getMsSinceMapped() {
+        return mMsSinceMapped;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbSessionTokenMapping: session_token=").append(mSessionToken)
+                .append(" session_id=").append(mSessionId);
+        if (mRemapped) {
+            sb.append(" previous_session_id=").append(mPreviousSessionId);
+        }
+        sb.append(" ms_since_mapped=").append(mMsSinceMapped);
+        return sb.toString();
+    }
+}
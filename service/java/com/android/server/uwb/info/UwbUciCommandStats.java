@@ -0,0 +1,124 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+/**
+ * Round-trip metrics of the UCI commands of one GID/OID, as collected by the native stack.
+ * All values should never decrease after the creation of the native dispatcher.
+ */
+public class UwbUciCommandStats {
+    private final int mGid;
+    private final int mOid;
+    private final long mCommandCount;
+    private final long mResponseCount;
+    private final long mRetryCount;
+    private final long mTimeoutCount;
+    private final long mTotalLatencyUs;
+    private final long mMaxLatencyUs;
+    private final long mStormCount;
+
+    public UwbUciCommandStats(int gid, int oid, long commandCount, long responseCount,
+            long retryCount, long timeoutCount, long totalLatencyUs, long maxLatencyUs,
+            long stormCount) {
+        mGid = gid;
+        mOid = oid;
+        mCommandCount = commandCount;
+        mResponseCount = responseCount;
+        mRetryCount = retryCount;
+        mTimeoutCount = timeoutCount;
+        mTotalLatencyUs = totalLatencyUs;
+        mMaxLatencyUs = maxLatencyUs;
+        mStormCount = stormCount;
+    }
+
+    /**
+     * get the group identifier of the commands
+     */
+    public int getGid() {
+        return mGid;
+    }
+
+    /**
+     * get the opcode identifier of the commands
+     */
+    public int getOid() {
+        return mOid;
+    }
+
+    /**
+     * get the number of commands sent, retransmissions excluded
+     */
+    public long getCommandCount() {
+        return mCommandCount;
+    }
+
+    /**
+     * get the number of commands which got a response
+     */
+    public long getResponseCount() {
+        return mResponseCount;
+    }
+
+    /**
+     * get the number of retransmissions
+     */
+    public long getRetryCount() {
+        return mRetryCount;
+    }
+
+    /**
+     * get the number of attempts which got no response
+     */
+    public long getTimeoutCount() {
+        return mTimeoutCount;
+    }
+
+    /**
+     * get the sum of the round-trip times of the answered commands in micros
+     */
+    public long getTotalLatencyUs() {
+        return mTotalLatencyUs;
+    }
+
+    /**
+     * get the slowest round-trip time in micros
+     */
+    public long getMaxLatencyUs() {
+        return mMaxLatencyUs;
+    }
+
+    /**
+     * get the number of UCI_STATUS_COMMAND_RETRY storms which throttled the commands
+     */
+    public long getStormCount() {
+        return mStormCount;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbUciCommandStats: gid=").append(mGid)
+                .append(" oid=").append(mOid)
+                .append(" command_count=").append(mCommandCount)
+                .append(" response_count=").append(mResponseCount)
+                .append(" retry_count=").append(mRetryCount)
+                .append(" timeout_count=").append(mTimeoutCount)
+                .append(" total_latency_us=").append(mTotalLatencyUs)
+                .append(" max_latency_us=").append(mMaxLatencyUs)
+                .append(" storm_count=").append(mStormCount);
+        return sb.toString();
+    }
+}
@@ -37,6 +37,15 @@ public interface INativeUwbManager {
          */
         void onRangeDataNotificationReceived(UwbRangingData rangingData);
 
+        /**
+         * Interface for receiving the Ranging Data Notifications without any measurement, in place
+         * of an empty {@link UwbRangingData}
+         *
+         * @param sessionId      : Session ID
+         * @param sequenceNumber : sequence number of the Ranging Data Notification
+         */
+        void onRangeKeepAlive(long sessionId, long sequenceNumber);
+
         /**
          * Interface for receiving Session Status Notification
          *
@@ -64,11 +73,12 @@ public interface INativeUwbManager {
          * @param sequenceNum : Sequence Number
          * @param address     : Address of remote address
          * @param data        : Data received from remote address
+         * @param receivedAtNanos : Time the data was received from the UWBS, in nanos since boot
          */
         // TODO(b/261762781): Change the type of sessionID & sequenceNum parameters to int (to match
         // their 4-octet size in the UCI spec).
-        void onDataReceived(
-                long sessionID, int status, long sequenceNum, byte[] address, byte[] data);
+        void onDataReceived(long sessionID, int status, long sequenceNum, byte[] address,
+                byte[] data, long receivedAtNanos);
 
         /**
          * Interface for receiving the data transfer status, corresponding to a Data packet
@@ -97,6 +107,52 @@ public interface INativeUwbManager {
          */
         void onDataTransferPhaseConfigNotificationReceived(long sessionId,
                 int dataTransferPhaseConfigStatus);
+
+        /**
+         * Interface for receiving the loss of an OWR AoA advertiser, which was not heard from
+         * for the lost advertiser timeout
+         *
+         * @param sessionId  : Session ID
+         * @param macAddress : MAC address of the advertiser
+         */
+        void onOwrAoaAdvertiserLost(long sessionId, byte[] macAddress);
+
+        /**
+         * Interface for receiving the near/far edges of a proximity-only session, which are
+         * reported instead of its range data.
+         *
+         * @param sessionId : Session ID
+         * @param isNear : true if the peer became near, false if it became far
+         * @param distanceCm : distance to the peer which triggered the edge, in centimeters
+         * @param macAddress : MAC address of the peer
+         */
+        void onProximityEvent(long sessionId, boolean isNear, int distanceCm, byte[] macAddress);
+
+        /**
+         * Interface for receiving the ranging adjustments suggested by the native stack for a
+         * session missing ranging rounds or starved of data credits. The suggestions are
+         * advisory, they are not applied to the session.
+         *
+         * @param sessionId : Session ID
+         * @param rangingDurationMs : suggested RANGING_DURATION, in milliseconds
+         * @param blockStride : suggested BLOCK_STRIDE_LENGTH
+         */
+        void onRangingIntervalSuggestion(long sessionId, int rangingDurationMs, int blockStride);
+
+        /**
+         * Interface for receiving the session state transitions found by the native stack to
+         * break the UCI session state machine. The session status notification is still
+         * delivered, this is only a diagnostic.
+         *
+         * @param sessionId     : Session ID
+         * @param previousState : state of the session before the transition
+         * @param sessionState  : state notified by the UWBS
+         * @param reasonCode    : reason code notified with the state
+         * @param violation     : 1 if the transition is illegal, 2 if the reason code
+         *                        contradicts the new state
+         */
+        void onSessionStateTransitionViolation(long sessionId, int previousState,
+                int sessionState, int reasonCode, int violation);
     }
 
     interface DeviceNotification {
@@ -116,6 +172,39 @@ public interface INativeUwbManager {
          * @param chipId : identifier of UWB chip for multi-HAL devices
          */
         void onCoreGenericErrorNotificationReceived(int status, String chipId);
+
+        /**
+         * Interface for receiving the number of notifications dropped by the native stack
+         * because the notification queue was full.
+         *
+         * @param droppedCount : number of notifications dropped since the previous report
+         * @param chipId       : identifier of UWB chip for multi-HAL devices
+         */
+        void onNotificationOverflow(long droppedCount, String chipId);
+
+        /**
+         * Interface for receiving the notifications which the native stack failed to convert to
+         * Java objects, and so could not deliver.
+         *
+         * @param stage           : the notification type whose conversion failed
+         * @param notification    : the raw notification payload in hex when available, the
+         *                          notification parsed by the native stack otherwise
+         * @param suppressedCount : number of failures not reported since the previous report
+         * @param chipId          : identifier of UWB chip for multi-HAL devices
+         */
+        void onProtocolError(String stage, String notification, long suppressedCount,
+                String chipId);
+
+        /**
+         * Interface for receiving the UCI_STATUS_COMMAND_RETRY storms detected by the native
+         * stack. The commands of the GID/OID are rejected for a while after a storm.
+         *
+         * @param gid        : group identifier of the retried command
+         * @param oid        : opcode identifier of the retried command
+         * @param retryCount : number of retransmissions which triggered the storm
+         * @param chipId     : identifier of UWB chip for multi-HAL devices
+         */
+        void onUciRetryStorm(int gid, int oid, int retryCount, String chipId);
     }
 
     interface VendorNotification {
@@ -124,6 +213,71 @@ public interface INativeUwbManager {
          */
         void onVendorUciNotificationReceived(int gid, int oid, byte[] payload);
     }
+
+    interface TypedVendorNotification {
+        /**
+         * Interface for receiving the vendor notifications of the CCC stack, routed by the native
+         * stack from their GID instead of onVendorUciNotificationReceived.
+         *
+         * @param oid     : opcode identifier of the notification
+         * @param payload : payload of the notification
+         * @param chipId  : identifier of UWB chip for multi-HAL devices
+         */
+        void onCccVendorNotificationReceived(int oid, byte[] payload, String chipId);
+
+        /**
+         * Interface for receiving the vendor notifications of the radar, routed by the native
+         * stack from their GID instead of onVendorUciNotificationReceived.
+         *
+         * @param oid     : opcode identifier of the notification
+         * @param payload : payload of the notification
+         * @param chipId  : identifier of UWB chip for multi-HAL devices
+         */
+        void onRadarVendorNotificationReceived(int oid, byte[] payload, String chipId);
+
+        /**
+         * Interface for receiving the OEM diagnostics vendor notifications, routed by the native
+         * stack from their GID instead of onVendorUciNotificationReceived.
+         *
+         * @param oid     : opcode identifier of the notification
+         * @param payload : payload of the notification
+         * @param chipId  : identifier of UWB chip for multi-HAL devices
+         */
+        void onDiagnosticsVendorNotificationReceived(int oid, byte[] payload, String chipId);
+    }
+
+    interface FirmwareLogNotification {
+        /**
+         * Interface for receiving the firmware logs, separated from the vendor notifications.
+         *
+         * @param payload : firmware logs carried by the vendor notification
+         * @param chipId  : identifier of UWB chip for multi-HAL devices
+         */
+        void onFirmwareLogReceived(byte[] payload, String chipId);
+    }
+
+    interface AggregatedRangingNotification {
+        /**
+         * Interface for receiving the merged range data stream of an aggregation group, tagged
+         * with the session ID of each range data.
+         *
+         * @param groupId     : identifier of the aggregation group
+         * @param rangingData : range data of a session of the group
+         */
+        void onAggregatedRangeDataReceived(int groupId, UwbRangingData rangingData);
+    }
+
+    interface RangingAttestor {
+        /**
+         * Signs a segment of the measurement stream of an attested session, e.g. with a key of the
+         * secure element. Called from the native notification threads.
+         *
+         * @param sessionId : identifier of the attested session
+         * @param segment   : the bytes to sign, chained to the signature of the previous segment
+         * @return the signature of the digest of the segment, or null if it could not be signed
+         */
+        byte[] attest(int sessionId, byte[] segment);
+    }
     /* Unused now */
     /*interface RfTestNotification {
         void onPeriodicTxDataNotificationReceived(UwbTestPeriodicTxResult periodicTxData);
@@ -17,9 +17,12 @@ package com.android.server.uwb.jni;
 
 import android.annotation.NonNull;
 import android.annotation.Nullable;
+import android.os.Build;
+import android.os.PersistableBundle;
 import android.util.Log;
 
 import com.android.internal.annotations.Keep;
+import com.android.server.uwb.DeviceConfigFacade;
 import com.android.server.uwb.UciLogModeStore;
 import com.android.server.uwb.UwbInjector;
 import com.android.server.uwb.data.DtTagUpdateRangingRoundsStatus;
@@ -31,17 +34,65 @@ import com.android.server.uwb.data.UwbRangingData;
 import com.android.server.uwb.data.UwbTlvData;
 import com.android.server.uwb.data.UwbUciConstants;
 import com.android.server.uwb.data.UwbVendorUciResponse;
+import com.android.server.uwb.info.UwbAbortedDataTransfers;
+import com.android.server.uwb.info.UwbHalTransaction;
+import com.android.server.uwb.info.UwbNotificationStats;
+import com.android.server.uwb.info.UwbOwrAoaAdvertiserInfo;
+import com.android.server.uwb.info.UwbParseErrorStats;
 import com.android.server.uwb.info.UwbPowerStats;
+import com.android.server.uwb.info.UwbRangingAttestation;
+import com.android.server.uwb.info.UwbSessionMismatch;
+import com.android.server.uwb.info.UwbSessionRecord;
+import com.android.server.uwb.info.UwbSessionTokenMapping;
+import com.android.server.uwb.info.UwbUciCommandStats;
 import com.android.server.uwb.multchip.UwbMultichipData;
 
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
 import java.util.Arrays;
 import java.util.HashMap;
 import java.util.Map;
+import java.util.Optional;
 
 @Keep
 public class NativeUwbManager {
     private static final String TAG = NativeUwbManager.class.getSimpleName();
 
+    /* Modes of the UCI capture, must be kept in sync with UciCaptureMode in the native stack. */
+    public static final int UCI_CAPTURE_MODE_DISABLED = 0;
+    public static final int UCI_CAPTURE_MODE_ALL = 1;
+    public static final int UCI_CAPTURE_MODE_FILTERED = 2;
+
+    /* Modes of the radar sweep decimation, must be kept in sync with nativeSetRadarDecimation. */
+    public static final int RADAR_DECIMATION_NONE = 0;
+    public static final int RADAR_DECIMATION_KEEP_EVERY_NTH = 1;
+    public static final int RADAR_DECIMATION_AVERAGE = 2;
+
+    /* Radar sweep drop policies, must be kept in sync with nativeSetRadarDeliveryBudget. */
+    public static final int RADAR_DROP_OLDEST = 0;
+    public static final int RADAR_DROP_DECIMATE = 1;
+
+    /* Range data delivery modes, must be kept in sync with nativeSetSessionDeliveryPreference. */
+    public static final int DELIVERY_IMMEDIATE = 0;
+    public static final int DELIVERY_BATCHED = 1;
+    public static final int DELIVERY_MAX_RATE = 2;
+
+    /* Device config IDs of CORE_SET_CONFIG and CORE_GET_CONFIG, as defined by the UCI spec. */
+    public static final int DEVICE_CONFIG_DEVICE_STATE = 0x00;
+    public static final int DEVICE_CONFIG_LOW_POWER_MODE = 0x01;
+
+    /* Antenna configurations of the sessions. */
+    public static final int ANTENNA_CONFIG_OMNI = 0;
+    public static final int ANTENNA_CONFIG_DIRECTIONAL = 1;
+    public static final int ANTENNA_CONFIG_AOA_AZIMUTH = 2;
+    public static final int ANTENNA_CONFIG_AOA_ELEVATION = 3;
+    public static final int ANTENNA_CONFIG_AOA_AZIMUTH_ELEVATION = 4;
+    public static final int ANTENNA_CONFIG_AOA_INTERLEAVED = 5;
+
+    /* Vendor commands whose OID is defined by the vendor of the chip, must be kept in sync with
+     * VendorCommand in the native stack. */
+    public static final int VENDOR_COMMAND_SET_CALIBRATION = 0;
+
     public final Object mNativeLock = new Object();
     private final UwbInjector mUwbInjector;
     private final UciLogModeStore mUciLogModeStore;
@@ -50,6 +101,9 @@ public class NativeUwbManager {
     protected INativeUwbManager.SessionNotification mSessionListener;
     private long mDispatcherPointer;
     protected INativeUwbManager.VendorNotification mVendorListener;
+    protected INativeUwbManager.FirmwareLogNotification mFirmwareLogListener;
+    protected INativeUwbManager.TypedVendorNotification mTypedVendorListener;
+    protected INativeUwbManager.AggregatedRangingNotification mAggregatedRangingListener;
 
     public NativeUwbManager(@NonNull UwbInjector uwbInjector, UciLogModeStore uciLogModeStore,
             UwbMultichipData uwbMultichipData) {
@@ -78,6 +132,29 @@ public class NativeUwbManager {
         mVendorListener = vendorListener;
     }
 
+    public void setFirmwareLogListener(
+            INativeUwbManager.FirmwareLogNotification firmwareLogListener) {
+        mFirmwareLogListener = firmwareLogListener;
+    }
+
+    /**
+     * Sets the listener of the merged range data streams of the aggregation groups set through
+     * setSessionAggregation.
+     */
+    public void setAggregatedRangingListener(
+            INativeUwbManager.AggregatedRangingNotification aggregatedRangingListener) {
+        mAggregatedRangingListener = aggregatedRangingListener;
+    }
+
+    /**
+     * Sets the listener of the CCC, radar and OEM diagnostics vendor notifications. Their GIDs are
+     * routed to it when UWB is enabled, their notifications go to the vendor listener otherwise.
+     */
+    public void setTypedVendorListener(
+            INativeUwbManager.TypedVendorNotification typedVendorListener) {
+        mTypedVendorListener = typedVendorListener;
+    }
+
     /**
      * Device status callback invoked via the JNI
      */
@@ -94,17 +171,74 @@ public class NativeUwbManager {
         mDeviceListener.onCoreGenericErrorNotificationReceived(status, chipId);
     }
 
+    /**
+     * Notification overflow callback invoked via the JNI
+     */
+    public void onNotificationOverflow(long droppedCount, String chipId) {
+        Log.d(TAG, "onNotificationOverflow(" + droppedCount + ", " + chipId + ")");
+        mDeviceListener.onNotificationOverflow(droppedCount, chipId);
+    }
+
+    /**
+     * Protocol error callback invoked via the JNI
+     */
+    public void onProtocolError(String stage, String notification, long suppressedCount,
+            String chipId) {
+        Log.d(TAG, "onProtocolError(" + stage + ", " + suppressedCount + ", " + chipId + ")");
+        mDeviceListener.onProtocolError(stage, notification, suppressedCount, chipId);
+    }
+
+    /**
+     * UCI retry storm callback invoked via the JNI
+     */
+    public void onUciRetryStorm(int gid, int oid, int retryCount, String chipId) {
+        Log.d(TAG, "onUciRetryStorm(" + gid + ", " + oid + ", " + retryCount + ", " + chipId
+                + ")");
+        mDeviceListener.onUciRetryStorm(gid, oid, retryCount, chipId);
+    }
+
     public void onSessionStatusNotificationReceived(long id, int token, int state, int reasonCode) {
         Log.d(TAG, "onSessionStatusNotificationReceived(" + id + ", " + token + ", "
                 + state + ", " + reasonCode + ")");
         mSessionListener.onSessionStatusNotificationReceived(id, token, state, reasonCode);
     }
 
+    /**
+     * Session state transition violation callback invoked via the JNI
+     */
+    public void onSessionStateTransitionViolation(long id, int previousState, int state,
+            int reasonCode, int violation) {
+        Log.d(TAG, "onSessionStateTransitionViolation(" + id + ", " + previousState + ", "
+                + state + ", " + reasonCode + ", " + violation + ")");
+        mSessionListener.onSessionStateTransitionViolation(id, previousState, state, reasonCode,
+                violation);
+    }
+
     public void onRangeDataNotificationReceived(UwbRangingData rangeData) {
         Log.d(TAG, "onRangeDataNotificationReceived : " + rangeData);
         mSessionListener.onRangeDataNotificationReceived(rangeData);
     }
 
+    /**
+     * Range data callback invoked via the JNI for the sessions of an aggregation group, in the
+     * order of the merged stream of the group. The sessions still get their range data.
+     */
+    public void onAggregatedRangeDataReceived(int groupId, UwbRangingData rangeData) {
+        Log.d(TAG, "onAggregatedRangeDataReceived(" + groupId + ") : " + rangeData);
+        mSessionListener.onRangeDataNotificationReceived(rangeData);
+        if (mAggregatedRangingListener != null) {
+            mAggregatedRangingListener.onAggregatedRangeDataReceived(groupId, rangeData);
+        }
+    }
+
+    /**
+     * Ranging round without any measurement callback invoked via the JNI
+     */
+    public void onRangeKeepAlive(long sessionId, long sequenceNumber) {
+        Log.d(TAG, "onRangeKeepAlive(" + sessionId + ", " + sequenceNumber + ")");
+        mSessionListener.onRangeKeepAlive(sessionId, sequenceNumber);
+    }
+
     public void onMulticastListUpdateNotificationReceived(
             UwbMulticastListUpdateStatus multicastListUpdateData) {
         Log.d(TAG, "onMulticastListUpdateNotificationReceived : " + multicastListUpdateData);
@@ -128,6 +262,49 @@ public class NativeUwbManager {
         mVendorListener.onVendorUciNotificationReceived(gid, oid, payload);
     }
 
+    /**
+     * CCC vendor notification callback invoked via the JNI, for the GID routed to it
+     */
+    public void onCccVendorNotificationReceived(int oid, byte[] payload, String chipId) {
+        Log.d(TAG, "onCccVendorNotificationReceived: " + chipId + ", " + oid + ", "
+                + Arrays.toString(payload));
+        if (mTypedVendorListener != null) {
+            mTypedVendorListener.onCccVendorNotificationReceived(oid, payload, chipId);
+        }
+    }
+
+    /**
+     * Radar vendor notification callback invoked via the JNI, for the GID routed to it
+     */
+    public void onRadarVendorNotificationReceived(int oid, byte[] payload, String chipId) {
+        Log.d(TAG, "onRadarVendorNotificationReceived: " + chipId + ", " + oid + ", "
+                + Arrays.toString(payload));
+        if (mTypedVendorListener != null) {
+            mTypedVendorListener.onRadarVendorNotificationReceived(oid, payload, chipId);
+        }
+    }
+
+    /**
+     * OEM diagnostics vendor notification callback invoked via the JNI, for the GID routed to it
+     */
+    public void onDiagnosticsVendorNotificationReceived(int oid, byte[] payload, String chipId) {
+        Log.d(TAG, "onDiagnosticsVendorNotificationReceived: " + chipId + ", " + oid + ", "
+                + Arrays.toString(payload));
+        if (mTypedVendorListener != null) {
+            mTypedVendorListener.onDiagnosticsVendorNotificationReceived(oid, payload, chipId);
+        }
+    }
+
+    /**
+     * Firmware log callback invoked via the JNI, only when streaming is enabled
+     */
+    public void onFirmwareLogReceived(byte[] payload, String chipId) {
+        Log.v(TAG, "onFirmwareLogReceived: " + chipId + ", " + Arrays.toString(payload));
+        if (mFirmwareLogListener != null) {
+            mFirmwareLogListener.onFirmwareLogReceived(payload, chipId);
+        }
+    }
+
     /**
      * Enable UWB hardware.
      *
@@ -139,20 +316,127 @@ public class NativeUwbManager {
         UwbDeviceInfoResponse deviceInfoResponse = null;
         Map<String, UwbDeviceInfoResponse> chipIdToDeviceInfoResponseMap = new HashMap<>();
         synchronized (mNativeLock) {
-            mDispatcherPointer = nativeDispatcherNew(mUwbMultichipData.getChipIds().toArray());
+            setNativeFeatureFlags();
+            setUciInjectionPolicy();
+            DeviceConfigFacade deviceConfigFacade = mUwbInjector.getDeviceConfigFacade();
+            mDispatcherPointer = nativeDispatcherNew(mUwbMultichipData.getChipIds().toArray(),
+                    deviceConfigFacade.getNotificationBackpressurePolicy(),
+                    deviceConfigFacade.getNotificationQueueCapacity());
+            setVendorCommandOids();
             for (String chipId : mUwbMultichipData.getChipIds()) {
                 deviceInfoResponse = nativeDoInitialize(chipId);
                 if (deviceInfoResponse == null
                             || deviceInfoResponse.mStatusCode != UwbUciConstants.STATUS_CODE_OK) {
                     return null;
                 }
+                // The chip may have been restarted along with the HAL.
+                applyCalibration(chipId);
                 chipIdToDeviceInfoResponseMap.put(chipId, deviceInfoResponse);
             }
             nativeSetLogMode(mUciLogModeStore.getMode());
+            setFirmwareLog();
+            setVendorNotificationRoutes();
         }
         return chipIdToDeviceInfoResponseMap;
     }
 
+    private void setFirmwareLog() {
+        int gid = mUwbInjector.getFirmwareLogGid();
+        int oid = mUwbInjector.getFirmwareLogOid();
+        if (gid < 0 || oid < 0) {
+            return;
+        }
+        boolean writeFile = mUwbInjector.isFirmwareLogFileEnabled();
+        boolean stream = mUwbInjector.isFirmwareLogStreamEnabled();
+        for (String chipId : mUwbMultichipData.getChipIds()) {
+            if (!nativeSetFirmwareLog(gid, oid, writeFile, stream, chipId)) {
+                Log.e(TAG, "Failed to set the firmware log of chip " + chipId);
+            }
+        }
+    }
+
+    private void setVendorCommandOids() {
+        setVendorCommandOid(VENDOR_COMMAND_SET_CALIBRATION,
+                mUwbInjector.getSetCalibrationVendorOid());
+    }
+
+    private void setVendorCommandOid(int command, int oid) {
+        if (oid < 0) {
+            return;
+        }
+        for (String chipId : mUwbMultichipData.getChipIds()) {
+            if (!nativeSetVendorCommandOid(command, oid, chipId)) {
+                Log.e(TAG, "Failed to set the OID " + oid + " of vendor command " + command
+                        + " on chip " + chipId);
+            }
+        }
+    }
+
+    private void setVendorNotificationRoutes() {
+        if (mTypedVendorListener == null) {
+            return;
+        }
+        setVendorNotificationRoute(mUwbInjector.getCccVendorGid(),
+                "onCccVendorNotificationReceived");
+        setVendorNotificationRoute(mUwbInjector.getRadarVendorGid(),
+                "onRadarVendorNotificationReceived");
+        setVendorNotificationRoute(mUwbInjector.getDiagnosticsVendorGid(),
+                "onDiagnosticsVendorNotificationReceived");
+    }
+
+    private void setVendorNotificationRoute(int gid, String callbackName) {
+        if (gid < 0) {
+            return;
+        }
+        for (String chipId : mUwbMultichipData.getChipIds()) {
+            if (!nativeSetVendorNotificationCallback(gid, callbackName, chipId)) {
+                Log.e(TAG, "Failed to route the vendor notifications of GID " + gid + " to "
+                        + callbackName + " on chip " + chipId);
+            }
+        }
+    }
+
+    private void setNativeFeatureFlags() {
+        DeviceConfigFacade deviceConfigFacade = mUwbInjector.getDeviceConfigFacade();
+        String[] names = {
+                "native_controlee_batching",
+                "native_proximity_fast_path",
+                "native_antenna_pair_tagging",
+                "native_ranging_advisor",
+                "native_metrics_exporter",
+                "native_latency_tracing",
+                "native_error_triggered_uci_log",
+        };
+        boolean[] values = {
+                deviceConfigFacade.isNativeControleeBatchingEnabled(),
+                deviceConfigFacade.isNativeProximityFastPathEnabled(),
+                deviceConfigFacade.isNativeAntennaPairTaggingEnabled(),
+                deviceConfigFacade.isNativeRangingAdvisorEnabled(),
+                deviceConfigFacade.isNativeMetricsExporterEnabled(),
+                deviceConfigFacade.isNativeLatencyTracingEnabled(),
+                deviceConfigFacade.isNativeErrorTriggeredUciLogEnabled(),
+        };
+        if (!nativeSetFeatureFlags(names, values)) {
+            Log.e(TAG, "Failed to set the native feature flags");
+        }
+    }
+
+    private void setUciInjectionPolicy() {
+        int[] allowedGids = mUwbInjector.getDeviceConfigFacade().getUciInjectionGidAllowlist();
+        if (!nativeSetUciInjectionPolicy(Build.isDebuggable(), allowedGids)) {
+            Log.e(TAG, "Failed to set the UCI injection policy");
+        }
+    }
+
+    /**
+     * Gets the state of the native feature flags, for dumpsys.
+     */
+    public String getNativeFeatureFlagsDump() {
+        synchronized (mNativeLock) {
+            return nativeGetFeatureFlagsDump();
+        }
+    }
+
     /**
      * Disable UWB hardware.
      *
@@ -170,6 +454,82 @@ public class NativeUwbManager {
         return true;
     }
 
+    /**
+     * Get the device info of a UWB chip, as returned by CORE_GET_DEVICE_INFO.
+     *
+     * @return : the device info, or null if it could not be retrieved
+     */
+    @Nullable
+    public UwbDeviceInfoResponse getDeviceInfo(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetDeviceInfo(chipId);
+        }
+    }
+
+    /**
+     * Proxies the HAL of a UWB chip over a TCP port of the loopback interface, for a host-side UWB
+     * stack. Only one client is served at a time. Fails while UWB is enabled.
+     *
+     * @return : true if the proxy is listening, false otherwise
+     */
+    public boolean startUciProxy(int port, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeStartUciProxy(port, chipId);
+        }
+    }
+
+    /**
+     * Stops proxying the HAL of a UWB chip. The HAL is closed once this returns.
+     *
+     * @return : true if the chip is no longer proxied, false otherwise
+     */
+    public boolean stopUciProxy(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeStopUciProxy(chipId);
+        }
+    }
+
+    /**
+     * Serves the metrics of all the chips in the Prometheus text format over HTTP on a local TCP
+     * port, replacing the running exporter. The exporter is stopped when the stack is deinitialized.
+     *
+     * @return : true if the exporter is listening, false otherwise
+     */
+    public boolean startMetricsExporter(int port) {
+        synchronized (mNativeLock) {
+            return nativeStartMetricsExporter(port);
+        }
+    }
+
+    /**
+     * Stops serving the metrics of the chips.
+     *
+     * @return : true if the metrics are no longer served, false otherwise
+     */
+    public boolean stopMetricsExporter() {
+        synchronized (mNativeLock) {
+            return nativeStopMetricsExporter();
+        }
+    }
+
+    /**
+     * Replaces the object receiving the UCI notifications of all the chips. Returns once the
+     * notifications in flight are delivered, after which none is delivered to the previous object.
+     *
+     * <p>While no object is attached, the last core and session notifications are retained and
+     * delivered to the next object attached. The other notifications are dropped.
+     *
+     * <p>Not synchronized on mNativeLock, as the notifications in flight may need it. Must not be
+     * called from a notification callback, which would never return.
+     *
+     * @param callbackObject : object implementing the notification callbacks, or null to detach
+     *                         the current object
+     * @return : true if the callback object is replaced, false otherwise
+     */
+    public boolean swapCallbackObject(@Nullable NativeUwbManager callbackObject) {
+        return nativeSwapCallbackObject(callbackObject);
+    }
+
     /**
      * Gets the timestamp resolution in nanosecond
      *
@@ -190,6 +550,98 @@ public class NativeUwbManager {
         }
     }
 
+    /**
+     * Retrieves the notification dispatch statistics, one entry per callback type.
+     */
+    public UwbNotificationStats[] getNotificationStats(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetNotificationStats(chipId);
+        }
+    }
+
+    /**
+     * Fetches and clears the number of notifications the native stack failed to convert since the
+     * previous call, one entry per packet family.
+     */
+    public UwbParseErrorStats[] fetchAndClearParseErrorStats(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeFetchAndClearParseErrorStats(chipId);
+        }
+    }
+
+    /**
+     * Lists the sessions tracked by the native stack, ordered by session id.
+     */
+    public UwbSessionRecord[] listSessions(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeListSessions(chipId);
+        }
+    }
+
+    /**
+     * Lists the session tokens mapped to the sessions not deinitialized, ordered by session token,
+     * as learned by the native stack from the SESSION_STATUS_NTF.
+     */
+    public UwbSessionTokenMapping[] getSessionTokenMappings(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSessionTokenMappings(chipId);
+        }
+    }
+
+    /**
+     * Lists the last session tokens the UWBS reused for another session, oldest first, to debug
+     * the ranging callbacks delivered to the wrong session after rapid session churn.
+     */
+    public UwbSessionTokenMapping[] getSessionTokenRemaps(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSessionTokenRemaps(chipId);
+        }
+    }
+
+    /**
+     * Compares the sessions tracked by the native stack with the UWBS's view of them, as queried
+     * by SESSION_GET_COUNT and SESSION_GET_STATE. Returns null if the UWBS could not be queried.
+     */
+    public UwbSessionMismatch[] checkSessions(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeCheckSessions(chipId);
+        }
+    }
+
+    /**
+     * Retrieves the UCI command round-trip metrics, one entry per GID/OID. The metrics are
+     * collected from the packets passed to the UCI logger, so not while the log mode is Disabled.
+     */
+    public UwbUciCommandStats[] getUciMetrics(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetUciMetrics(chipId);
+        }
+    }
+
+    /**
+     * Retrieves the last UCI transactions exchanged with the HAL, oldest first, with how long
+     * the HAL took to accept each command and to pass back its response.
+     */
+    public UwbHalTransaction[] getHalTrace(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetHalTrace(chipId);
+        }
+    }
+
+    /**
+     * Retrieves the OWR AoA advertisers currently heard by an observer session.
+     *
+     * @param sessionId : Session ID of the OWR AoA observer session
+     * @param chipId    : Identifier of UWB chip for multi-HAL devices
+     * @return the advertisers ordered by MAC address, null if failed
+     */
+    @Nullable
+    public UwbOwrAoaAdvertiserInfo[] getOwrAoaAdvertiserRoster(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetOwrAoaAdvertiserRoster(sessionId, chipId);
+        }
+    }
+
     /**
      * Creates the new UWB session with parameter session ID and type of the session.
      *
@@ -201,8 +653,25 @@ public class NativeUwbManager {
      * @return : {@link UwbUciConstants}  Status code
      */
     public byte initSession(int sessionId, byte sessionType, String chipId) {
+        return initSession(sessionId, sessionType, false, chipId);
+    }
+
+    /**
+     * Creates the new UWB session with parameter session ID and type of the session, and whether
+     * its range data carry the raw RANGE_DATA_NTF. It is not copied otherwise, and
+     * {@link UwbRangingData#getRawNtfData()} is empty.
+     *
+     * @param sessionId             : Session ID is 4 Octets unique random number generated by
+     *                                application
+     * @param sessionType           : Type of session, see {@link #initSession(int, byte, String)}
+     * @param includeRawRangingData : Whether the range data carry the raw RANGE_DATA_NTF
+     * @param chipId                : Identifier of UWB chip for multi-HAL devices
+     * @return : {@link UwbUciConstants}  Status code
+     */
+    public byte initSession(int sessionId, byte sessionType, boolean includeRawRangingData,
+            String chipId) {
         synchronized (mNativeLock) {
-            return nativeSessionInit(sessionId, sessionType, chipId);
+            return nativeSessionInit(sessionId, sessionType, includeRawRangingData, chipId);
         }
     }
 
@@ -228,7 +697,36 @@ public class NativeUwbManager {
      */
     public byte deviceReset(byte resetConfig, String chipId) {
         synchronized (mNativeLock) {
-            return nativeDeviceReset(resetConfig, chipId);
+            byte status = nativeDeviceReset(resetConfig, chipId);
+            if (status == UwbUciConstants.STATUS_CODE_OK) {
+                applyCalibration(chipId);
+            }
+            return status;
+        }
+    }
+
+    /**
+     * Sets the calibration of a channel and an antenna on the chip with the
+     * ANDROID_SET_CALIBRATION vendor command. The calibration accepted by the chip is persisted,
+     * and applied again every time the chip is opened or reset.
+     *
+     * @param channel   : UWB channel of the calibration
+     * @param antennaId : Antenna of the calibration
+     * @param version   : Version of the calibration, older versions are rejected
+     * @param payload   : Payload of the command, as defined by the vendor of the chip
+     * @param chipId    : Identifier of UWB chip for multi-HAL devices
+     * @return : {@link UwbUciConstants}  Status code
+     */
+    public byte setCalibration(int channel, int antennaId, int version, byte[] payload,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetCalibration(channel, antennaId, version, payload, chipId);
+        }
+    }
+
+    private void applyCalibration(String chipId) {
+        if (!nativeApplyCalibration(chipId)) {
+            Log.e(TAG, "Failed to apply the calibration of chip " + chipId);
         }
     }
 
@@ -361,13 +859,18 @@ public class NativeUwbManager {
      * @param subSessionKeyList : Sub-session key list of Controlees
      * @return : refer to SESSION_SET_APP_CONFIG_RSP
      * in the Table 16: Control messages to set Application configurations
+     *
+     * <p>With the native_controlee_batching flag, a list of controlees to add which does not fit
+     * in a single command is split into several, when the UWBS reports the controlee statuses in
+     * the response. On failure, the returned status then lists every controlee of the commands
+     * sent, the added ones with MULTICAST_LIST_UPDATE_STATUS_OK.
      */
     public UwbMulticastListUpdateStatus controllerMulticastListUpdate(int sessionId, int action,
             int noOfControlee, byte[] addresses, int[] subSessionIds, byte[] subSessionKeyList,
             String chipId) {
         synchronized (mNativeLock) {
             return nativeControllerMulticastListUpdate(sessionId, (byte) action,
-                    (byte) noOfControlee, addresses, subSessionIds, subSessionKeyList, chipId,
+                    noOfControlee, addresses, subSessionIds, subSessionKeyList, chipId,
                     mUwbInjector.isMulticastListNtfV2Supported(),
                     mUwbInjector.isMulticastListRspV2Supported());
         }
@@ -392,6 +895,45 @@ public class NativeUwbManager {
         }
     }
 
+    /**
+     * Reads a device config with CORE_GET_CONFIG.
+     *
+     * @param configId : one of the DEVICE_CONFIG_* IDs
+     * @return the value of the config, e.g. 1 if LOW_POWER_MODE is enabled, or -1 if failed.
+     */
+    public int getDeviceConfig(int configId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetDeviceConfig(configId, chipId);
+        }
+    }
+
+    /**
+     * Sets a batch of device configs with CORE_SET_CONFIG, entirely or not at all: the configs
+     * applied are restored when the UWBS rejects any other config of the batch. The read-only
+     * configs, e.g. DEVICE_STATE, are rejected without being sent.
+     *
+     * @param configIds : the DEVICE_CONFIG_* IDs of the configs
+     * @param values : the values of the configs, in the order of {@code configIds}
+     * @return the UCI status of each config, or null if the batch could not be sent.
+     */
+    @Nullable
+    public byte[] setDeviceConfigs(int[] configIds, int[] values, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetDeviceConfigs(configIds, values, chipId);
+        }
+    }
+
+    /**
+     * Sets whether the UWBS may enter its low power mode, with the LOW_POWER_MODE device config.
+     *
+     * @return true if the config is set successfully, false otherwise.
+     */
+    public boolean setLowPowerMode(boolean enabled, String chipId) {
+        byte[] statuses = setDeviceConfigs(new int[] {DEVICE_CONFIG_LOW_POWER_MODE},
+                new int[] {enabled ? 1 : 0}, chipId);
+        return statuses != null && statuses[0] == UwbUciConstants.STATUS_CODE_OK;
+    }
+
     /**
      * Sets the log mode for the current and future UWB UCI messages.
      *
@@ -404,6 +946,355 @@ public class NativeUwbManager {
         }
     }
 
+    /**
+     * Sets the log mode of a single chip, until the next UWB restart. The log mode stored is not
+     * changed.
+     *
+     * @param logModeStr is one of Disabled, Filtered, or Unfiltered (case insensitive).
+     * @param chipId identifier of UWB chip for multi-HAL devices
+     * @return true if the log mode is set successfully, false otherwise.
+     */
+    public boolean setLogMode(String logModeStr, String chipId) {
+        Optional<UciLogModeStore.Mode> logMode = UciLogModeStore.Mode.fromName(logModeStr);
+        if (logMode.isEmpty()) {
+            return false;
+        }
+        synchronized (mNativeLock) {
+            return nativeSetLogModeForChip(logMode.get().getMode(), chipId);
+        }
+    }
+
+    /**
+     * Sets which UCI packets of the chip are written to the UCI log, on top of the log mode.
+     *
+     * @param mode one of UCI_CAPTURE_MODE_*.
+     * @param sessionTokens in UCI_CAPTURE_MODE_FILTERED, the session tokens to capture, or all
+     *                      the sessions if empty.
+     * @param gids in UCI_CAPTURE_MODE_FILTERED, the GIDs to capture, or all the GIDs if empty.
+     * @return true if the capture is set successfully, false otherwise.
+     */
+    public boolean setUciCapture(int mode, int[] sessionTokens, int[] gids, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetUciCapture(mode, sessionTokens, gids, chipId);
+        }
+    }
+
+    /**
+     * Registers the session as proximity-only, so that the native stack only reports the near/far
+     * edges of its peers through onProximityEvent instead of the full range data.
+     *
+     * @param enabled false to unregister the session.
+     * @param nearCm distance at which a peer becomes near.
+     * @param farCm distance at which a near peer becomes far again, greater than nearCm.
+     * @return true if the session is registered, false otherwise, e.g. if the feature is disabled.
+     */
+    public boolean setProximityOnlySession(int sessionId, boolean enabled, int nearCm, int farCm,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetProximityOnlySession(sessionId, enabled, nearCm, farCm, chipId);
+        }
+    }
+
+    /**
+     * Sets whether the SESSION_INFO_NTFs of the session are dropped by the native stack instead of
+     * being passed to onRangeDataNotificationReceived. The suppression ends when the session is
+     * deinitialized.
+     *
+     * @return true if the suppression is set successfully, false otherwise.
+     */
+    public boolean setSessionInfoSuppressed(int sessionId, boolean suppressed, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetSessionInfoSuppressed(sessionId, suppressed, chipId);
+        }
+    }
+
+    /**
+     * Sets whether the range data of all the sessions carry the raw RANGE_DATA_NTF, whatever was
+     * set when they were initialized, to debug them.
+     *
+     * @return true if the raw notifications are set successfully, false otherwise.
+     */
+    public boolean setRawRangingDataForAllSessions(boolean included, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRawRangingDataForAllSessions(included, chipId);
+        }
+    }
+
+    /**
+     * Sets whether the range data of the session are coarsened by the native stack before being
+     * passed to onRangeDataNotificationReceived: the distances are quantized to
+     * {@code distanceStepCm} and the angles of arrival, the raw notification and, for DL-TDoA,
+     * the timestamps and anchor locations are dropped. The policy ends when the session is
+     * deinitialized.
+     *
+     * @return true if the policy is set successfully, false otherwise.
+     */
+    public boolean setCoarseLocation(int sessionId, boolean enabled, int distanceStepCm,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetCoarseLocation(sessionId, enabled, distanceStepCm, chipId);
+        }
+    }
+
+    /**
+     * Simulates the departure of a controlee of the session, or its return: the measurements of
+     * the controlee are omitted by the native stack from the range data of the session while it
+     * is departed, so that the apps can test their handling of the peer loss against the real
+     * notification pipeline. The departure ends when the session is deinitialized.
+     *
+     * @param macAddress : the short or extended MAC address of the controlee, in the byte order
+     *                   of the ranging measurements
+     * @return true if the departure or the return is simulated successfully, false otherwise.
+     */
+    public boolean simulateControleeDeparture(int sessionId, byte[] macAddress, boolean departed,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSimulateControleeDeparture(sessionId, macAddress, departed, chipId);
+        }
+    }
+
+    /**
+     * Sets the extras of the session, e.g. experimental vendor app configs, without a JNI method
+     * of their own. The native stack validates each key and the type of its value against the
+     * schema of the extras it accepts, then sets the app configs they map to and applies the
+     * host-side policies they map to.
+     *
+     * @param extras : boolean, int or long values; any other type rejects the extras
+     * @return true if all the extras are set successfully, false otherwise.
+     */
+    public boolean setSessionExtras(int sessionId, PersistableBundle extras, String chipId) {
+        String[] keys = extras.keySet().toArray(new String[0]);
+        byte[][] values = new byte[keys.length][];
+        for (int i = 0; i < keys.length; i++) {
+            values[i] = encodeSessionExtra(extras.get(keys[i]));
+            if (values[i] == null) {
+                Log.e(TAG, "setSessionExtras: unsupported type of extra " + keys[i]);
+                return false;
+            }
+        }
+        synchronized (mNativeLock) {
+            return nativeSetSessionExtras(sessionId, keys, values, chipId);
+        }
+    }
+
+    /** Encodes a value of the session extras as expected by the native stack, null if invalid. */
+    @Nullable
+    private static byte[] encodeSessionExtra(Object value) {
+        if (value instanceof Boolean) {
+            return new byte[] {(byte) ((Boolean) value ? 1 : 0)};
+        }
+        if (value instanceof Integer) {
+            return ByteBuffer.allocate(Integer.BYTES).order(ByteOrder.LITTLE_ENDIAN)
+                    .putInt((Integer) value).array();
+        }
+        if (value instanceof Long) {
+            return ByteBuffer.allocate(Long.BYTES).order(ByteOrder.LITTLE_ENDIAN)
+                    .putLong((Long) value).array();
+        }
+        return null;
+    }
+
+    /**
+     * Sets the antenna configuration of the session, i.e. its antenna mode and the AoA it
+     * measures. The native stack rejects the AoA the chip does not report in its capabilities.
+     *
+     * @param antennaConfig : one of the ANTENNA_CONFIG_* constants
+     * @return : {@link UwbUciConstants}  Status code
+     */
+    public byte setAntennaConfig(int sessionId, int antennaConfig, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetAntennaConfig(sessionId, antennaConfig, chipId);
+        }
+    }
+
+    /**
+     * Switches the ranging role of the session, e.g. from initiator to responder, keeping its
+     * session ID. An active session is stopped, reconfigured and started again by the native
+     * stack, which restores the previous role if any step fails. The state changes of the stop and
+     * of the start are notified as usual.
+     *
+     * @param deviceType : DEVICE_TYPE value as defined by the FiRa spec, 0 for a controlee or 1
+     *                   for a controller
+     * @param deviceRole : DEVICE_ROLE value as defined by the FiRa spec, 0 for a responder or 1
+     *                   for an initiator
+     * @return : {@link UwbUciConstants}  Status code
+     */
+    public byte switchRangingRole(int sessionId, int deviceType, int deviceRole, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSwitchRangingRole(sessionId, deviceType, deviceRole, chipId);
+        }
+    }
+
+    /**
+     * Enables or disables the ranging diagnostics of the session. The native stack drops the
+     * diagnostics notifications of the sessions which did not enable them.
+     *
+     * @param frameReportFields : the fields of the frame reports, ignored when disabling them
+     * @return : {@link UwbUciConstants}  Status code
+     */
+    public byte setRangingDiagnostics(int sessionId, boolean enabled, byte frameReportFields,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRangingDiagnostics(sessionId, enabled, frameReportFields, chipId);
+        }
+    }
+
+    /**
+     * Sets how the radar sweeps of the session are decimated by the native stack before being
+     * passed to onRadarDataMessageReceived. The decimation ends when the session is deinitialized.
+     *
+     * @param mode : one of RADAR_DECIMATION_NONE, RADAR_DECIMATION_KEEP_EVERY_NTH or
+     *             RADAR_DECIMATION_AVERAGE
+     * @param factor : number of sweeps received for each sweep passed, ignored with
+     *               RADAR_DECIMATION_NONE
+     * @return true if the decimation is set successfully, false otherwise.
+     */
+    public boolean setRadarDecimation(int sessionId, int mode, int factor, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRadarDecimation(sessionId, mode, factor, chipId);
+        }
+    }
+
+    /**
+     * Bounds the rate of the radar sweeps of the session passed by the native stack to
+     * onRadarDataMessageReceived, after their decimation. The sweeps dropped beyond are counted in
+     * the next radar data passed. The budget ends when the session is deinitialized.
+     *
+     * @param maxSweepsPerSec : maximum number of sweeps passed per second, 0 to pass all of them
+     * @param dropPolicy : one of RADAR_DROP_OLDEST or RADAR_DROP_DECIMATE
+     * @return true if the budget is set successfully, false otherwise.
+     */
+    public boolean setRadarDeliveryBudget(int sessionId, int maxSweepsPerSec, int dropPolicy,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRadarDeliveryBudget(sessionId, maxSweepsPerSec, dropPolicy, chipId);
+        }
+    }
+
+    /**
+     * Sets how the range data of the session are delivered by the native stack to
+     * onRangeDataNotificationReceived. With DELIVERY_BATCHED, the range data held when the session
+     * stops ranging are delivered before its status notification. The preference ends when the
+     * session is deinitialized.
+     *
+     * @param mode : one of DELIVERY_IMMEDIATE, DELIVERY_BATCHED or DELIVERY_MAX_RATE
+     * @param value : number of range data delivered together with DELIVERY_BATCHED, minimum
+     *              interval in milliseconds between two range data with DELIVERY_MAX_RATE,
+     *              ignored with DELIVERY_IMMEDIATE
+     * @return true if the preference is set successfully, false otherwise.
+     */
+    public boolean setSessionDeliveryPreference(int sessionId, int mode, int value,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetSessionDeliveryPreference(sessionId, mode, value, chipId);
+        }
+    }
+
+    /**
+     * Merges the range data of several sessions, e.g. one per peer device, into the stream of an
+     * aggregation group passed to onAggregatedRangeDataReceived. The range data of the sessions
+     * are held over the window, then passed in the order they were received, without the
+     * duplicates. The delivery preference of the sessions is ignored while they are aggregated.
+     * A session is in one group at most; it leaves the group when it is deinitialized.
+     *
+     * @param groupId : identifier of the aggregation group
+     * @param sessionIds : sessions of the group, empty to remove the group
+     * @param windowMs : time in milliseconds the range data are held to be merged
+     * @return true if the group is set successfully, false otherwise.
+     */
+    public boolean setSessionAggregation(int groupId, int[] sessionIds, int windowMs,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetSessionAggregation(groupId, sessionIds, windowMs, chipId);
+        }
+    }
+
+    /**
+     * Sets the attestor signing the measurement streams of the sessions attested through
+     * setRangingAttestation, on all the chips.
+     *
+     * @param attestor : the platform attestor, or null to stop attesting the sessions
+     * @return true if the attestor is set successfully, false otherwise.
+     */
+    public boolean setRangingAttestor(@Nullable INativeUwbManager.RangingAttestor attestor) {
+        synchronized (mNativeLock) {
+            return nativeSetRangingAttestor(attestor);
+        }
+    }
+
+    /**
+     * Attests the measurement stream of the session: its SESSION_INFO_NTFs are signed by the
+     * attestor by segments, each chained to the signature of the previous one. The incomplete
+     * segment is signed when the session stops ranging.
+     *
+     * @param measurementsPerAttestation : number of SESSION_INFO_NTFs per segment, 0 to stop
+     *                                   attesting the session
+     * @return true if the attestation is set successfully, false otherwise.
+     */
+    public boolean setRangingAttestation(int sessionId, int measurementsPerAttestation,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRangingAttestation(sessionId, measurementsPerAttestation, chipId);
+        }
+    }
+
+    /**
+     * Fetches the attestations of the measurement stream of the session recorded since the
+     * previous call, ordered by sequence number.
+     */
+    public UwbRangingAttestation[] fetchRangingAttestations(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeFetchRangingAttestations(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Records the UCI packets exchanged with the HAL of a chip from its next opening, discarding
+     * the previous recording. The recording can be replayed in the unit tests of the native stack.
+     *
+     * @return true if the recording is armed, false otherwise.
+     */
+    public boolean startUciRecording(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeStartUciRecording(chipId);
+        }
+    }
+
+    /**
+     * Stops recording the UCI packets exchanged with the HAL of a chip, and writes the recording to
+     * a file.
+     *
+     * @return the path of the file, or null if the recording could not be written.
+     */
+    @Nullable
+    public String stopUciRecording(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeStopUciRecording(chipId);
+        }
+    }
+
+    /**
+     * Writes the data transfers in flight to a file before the service is shut down, so that
+     * they are reported as aborted by fetchAbortedDataTransfers once restarted.
+     *
+     * @return true if the transfers are written successfully, false otherwise.
+     */
+    public boolean persistDataTransfers(String chipId) {
+        synchronized (mNativeLock) {
+            return nativePersistDataTransfers(chipId);
+        }
+    }
+
+    /**
+     * Fetches the data transfers of each session aborted by the last shutdown of the service,
+     * ordered by session id. They are reported once. Returns null if they could not be read.
+     */
+    public UwbAbortedDataTransfers[] fetchAbortedDataTransfers(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeFetchAbortedDataTransfers(chipId);
+        }
+    }
+
     @NonNull
     public UwbVendorUciResponse sendRawVendorCmd(int mt, int gid, int oid, byte[] payload,
             String chipId) {
@@ -413,12 +1304,26 @@ public class NativeUwbManager {
     }
 
     /**
-     * Receive payload data from a remote device in a UWB ranging session.
+     * Injects a raw UCI message, bypassing the typed builders of the native stack. Any GID can be
+     * injected on debuggable builds, only the GIDs of the device config allowlist otherwise.
+     */
+    @NonNull
+    public UwbVendorUciResponse injectUciMessage(int mt, int gid, int oid, byte[] payload,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeInjectUciMessage(mt, gid, oid, payload, chipId);
+        }
+    }
+
+    /**
+     * Receive payload data from a remote device in a UWB ranging session. The age is the time
+     * elapsed since the native stack received the data from the UWBS.
      */
-    public void onDataReceived(
-            long sessionID, int status, long sequenceNum, byte[] address, byte[] data) {
+    public void onDataReceived(long sessionID, int status, long sequenceNum, byte[] address,
+            byte[] data, long ageNanos) {
         Log.d(TAG, "onDataReceived ");
-        mSessionListener.onDataReceived(sessionID, status, sequenceNum, address, data);
+        mSessionListener.onDataReceived(sessionID, status, sequenceNum, address, data,
+                mUwbInjector.getElapsedSinceBootNanos() - ageNanos);
     }
 
     /**
@@ -462,6 +1367,33 @@ public class NativeUwbManager {
                 dataTransferPhaseConfigStatus);
     }
 
+    /**
+     * Receive the loss of an OWR AoA advertiser
+     */
+    public void onOwrAoaAdvertiserLost(long sessionId, byte[] macAddress) {
+        Log.d(TAG, "onOwrAoaAdvertiserLost(" + sessionId + ")");
+        mSessionListener.onOwrAoaAdvertiserLost(sessionId, macAddress);
+    }
+
+    /**
+     * Receive a near/far edge of a proximity-only session
+     */
+    public void onProximityEvent(long sessionId, boolean isNear, int distanceCm,
+            byte[] macAddress) {
+        Log.d(TAG, "onProximityEvent(" + sessionId + ", " + isNear + ")");
+        mSessionListener.onProximityEvent(sessionId, isNear, distanceCm, macAddress);
+    }
+
+    /**
+     * Receive a ranging interval suggestion for a congested session
+     */
+    public void onRangingIntervalSuggestion(long sessionId, int rangingDurationMs,
+            int blockStride) {
+        Log.d(TAG, "onRangingIntervalSuggestion(" + sessionId + ", " + rangingDurationMs + ", "
+                + blockStride + ")");
+        mSessionListener.onRangingIntervalSuggestion(sessionId, rangingDurationMs, blockStride);
+    }
+
     /**
      * Update Ranging Rounds for DT Tag
      *
@@ -559,23 +1491,72 @@ public class NativeUwbManager {
             byte dataTransferControl, byte dtpmlSize, byte[] macAddress, byte[] slotBitmap,
             String chipId);
 
-    private native long nativeDispatcherNew(Object[] chipIds);
+    private native long nativeDispatcherNew(Object[] chipIds, int backpressurePolicy,
+            int notificationQueueCapacity);
 
     private native void nativeDispatcherDestroy();
 
+    private native boolean nativeSwapCallbackObject(Object callbackObject);
+
+    private native boolean nativeSetFirmwareLog(int gid, int oid, boolean writeFile,
+            boolean stream, String chipId);
+
+    private native boolean nativeSetVendorCommandOid(int command, int oid, String chipId);
+
     private native boolean nativeInit();
 
+    private native boolean nativeSetFeatureFlags(String[] names, boolean[] values);
+
+    private native boolean nativeSetUciInjectionPolicy(boolean debuggable, int[] allowedGids);
+
+    private native String nativeGetFeatureFlagsDump();
+
     private native UwbDeviceInfoResponse nativeDoInitialize(String chipIds);
 
     private native boolean nativeDoDeinitialize(String chipId);
 
+    private native UwbDeviceInfoResponse nativeGetDeviceInfo(String chipId);
+
+    private native boolean nativeStartUciProxy(int port, String chipId);
+
+    private native boolean nativeStopUciProxy(String chipId);
+
+    private native boolean nativeStartMetricsExporter(int port);
+
+    private native boolean nativeStopMetricsExporter();
+
     private native long nativeGetTimestampResolutionNanos();
 
     private native UwbPowerStats nativeGetPowerStats(String chipId);
 
+    private native UwbNotificationStats[] nativeGetNotificationStats(String chipId);
+
+    private native UwbUciCommandStats[] nativeGetUciMetrics(String chipId);
+
+    private native UwbHalTransaction[] nativeGetHalTrace(String chipId);
+
+    private native UwbParseErrorStats[] nativeFetchAndClearParseErrorStats(String chipId);
+
+    private native UwbSessionRecord[] nativeListSessions(String chipId);
+
+    private native UwbSessionTokenMapping[] nativeGetSessionTokenMappings(String chipId);
+
+    private native UwbSessionTokenMapping[] nativeGetSessionTokenRemaps(String chipId);
+
+    private native UwbSessionMismatch[] nativeCheckSessions(String chipId);
+
+    private native UwbOwrAoaAdvertiserInfo[] nativeGetOwrAoaAdvertiserRoster(int sessionId,
+            String chipId);
+
     private native byte nativeDeviceReset(byte resetConfig, String chipId);
 
-    private native byte nativeSessionInit(int sessionId, byte sessionType, String chipId);
+    private native byte nativeSetCalibration(int channel, int antennaId, int version,
+            byte[] payload, String chipId);
+
+    private native boolean nativeApplyCalibration(String chipId);
+
+    private native byte nativeSessionInit(int sessionId, byte sessionType,
+            boolean includeRawRangingData, String chipId);
 
     private native byte nativeSessionDeInit(int sessionId, String chipId);
 
@@ -599,17 +1580,86 @@ public class NativeUwbManager {
     private native UwbTlvData nativeGetCapsInfo(String chipId);
 
     private native UwbMulticastListUpdateStatus nativeControllerMulticastListUpdate(int sessionId,
-            byte action, byte noOfControlee, byte[] address, int[] subSessionId,
+            byte action, int noOfControlee, byte[] address, int[] subSessionId,
             byte[] subSessionKeyList, String chipId, boolean isMulticastListNtfV2Supported,
             boolean isMulticastListRspV2Supported);
 
     private native byte nativeSetCountryCode(byte[] countryCode, String chipId);
 
+    private native int nativeGetDeviceConfig(int configId, String chipId);
+
+    private native byte[] nativeSetDeviceConfigs(int[] configIds, int[] values, String chipId);
+
     private native boolean nativeSetLogMode(String logMode);
 
+    private native boolean nativeSetLogModeForChip(String logMode, String chipId);
+
+    private native boolean nativeSetUciCapture(int mode, int[] sessionTokens, int[] gids,
+            String chipId);
+
+    private native boolean nativeSetProximityOnlySession(int sessionId, boolean enabled,
+            int nearCm, int farCm, String chipId);
+
+    private native boolean nativeSetVendorNotificationCallback(int gid, String callbackName,
+            String chipId);
+
+    private native boolean nativeSetSessionInfoSuppressed(int sessionId, boolean suppressed,
+            String chipId);
+
+    private native boolean nativeSetRawRangingDataForAllSessions(boolean included,
+            String chipId);
+
+    private native boolean nativeSetCoarseLocation(int sessionId, boolean enabled,
+            int distanceStepCm, String chipId);
+
+    private native boolean nativeSimulateControleeDeparture(int sessionId, byte[] macAddress,
+            boolean departed, String chipId);
+
+    private native boolean nativeSetSessionExtras(int sessionId, String[] keys, byte[][] values,
+            String chipId);
+
+    private native byte nativeSetAntennaConfig(int sessionId, int antennaConfig, String chipId);
+
+    private native byte nativeSwitchRangingRole(int sessionId, int deviceType, int deviceRole,
+            String chipId);
+
+    private native byte nativeSetRangingDiagnostics(int sessionId, boolean enabled,
+            byte frameReportFields, String chipId);
+
+    private native boolean nativeSetRadarDecimation(int sessionId, int mode, int factor,
+            String chipId);
+
+    private native boolean nativeSetRadarDeliveryBudget(int sessionId, int maxSweepsPerSec,
+            int dropPolicy, String chipId);
+
+    private native boolean nativeSetSessionDeliveryPreference(int sessionId, int mode, int value,
+            String chipId);
+
+    private native boolean nativeSetSessionAggregation(int groupId, int[] sessionIds,
+            int windowMs, String chipId);
+
+    private native boolean nativeSetRangingAttestor(INativeUwbManager.RangingAttestor attestor);
+
+    private native boolean nativeSetRangingAttestation(int sessionId,
+            int measurementsPerAttestation, String chipId);
+
+    private native UwbRangingAttestation[] nativeFetchRangingAttestations(int sessionId,
+            String chipId);
+
+    private native boolean nativeStartUciRecording(String chipId);
+
+    private native String nativeStopUciRecording(String chipId);
+
+    private native boolean nativePersistDataTransfers(String chipId);
+
+    private native UwbAbortedDataTransfers[] nativeFetchAbortedDataTransfers(String chipId);
+
     private native UwbVendorUciResponse nativeSendRawVendorCmd(int mt, int gid, int oid,
             byte[] payload, String chipId);
 
+    private native UwbVendorUciResponse nativeInjectUciMessage(int mt, int gid, int oid,
+            byte[] payload, String chipId);
+
     private native DtTagUpdateRangingRoundsStatus nativeSessionUpdateDtTagRangingRounds(
             int sessionId, int noOfActiveRangingRounds, byte[] rangingRoundIndexes, String chipId);
 
@@ -21,6 +21,7 @@ import static com.google.uwb.support.fira.FiraParams.RANGE_DATA_NTF_CONFIG_ENABL
 import static com.google.uwb.support.fira.FiraParams.RANGE_DATA_NTF_CONFIG_ENABLE_PROXIMITY_AOA_EDGE_TRIG;
 import static com.google.uwb.support.fira.FiraParams.RANGE_DATA_NTF_CONFIG_ENABLE_PROXIMITY_AOA_LEVEL_TRIG;
 
+import android.annotation.Nullable;
 import android.uwb.UwbAddress;
 
 import com.android.modules.utils.build.SdkLevel;
@@ -47,13 +48,19 @@ public class FiraEncoder extends TlvEncoder {
 
     @Override
     public TlvBuffer getTlvBuffer(Params param, ProtocolVersion protocolVersion) {
+        return getTlvBuffer(param, protocolVersion, null);
+    }
+
+    @Override
+    public TlvBuffer getTlvBuffer(Params param, ProtocolVersion protocolVersion,
+            @Nullable String chipId) {
         // The "protocolVersion" is always expected to be of type "FiraProtocolVersion" here, but
         // in case it's not, we use a backup value of "PROTOCOL_VERSION_1_1".
         FiraProtocolVersion uwbsFiraProtocolVersion =
                 (protocolVersion instanceof FiraProtocolVersion)
                         ? (FiraProtocolVersion) protocolVersion : FiraParams.PROTOCOL_VERSION_1_1;
         if (param instanceof FiraOpenSessionParams) {
-            return getTlvBufferFromFiraOpenSessionParams(param, uwbsFiraProtocolVersion);
+            return getTlvBufferFromFiraOpenSessionParams(param, uwbsFiraProtocolVersion, chipId);
         }
 
         if (param instanceof FiraRangingReconfigureParams) {
@@ -70,7 +77,8 @@ public class FiraEncoder extends TlvEncoder {
     }
 
     private TlvBuffer getTlvBufferFromFiraOpenSessionParams(
-            Params baseParam, FiraProtocolVersion uwbsFiraProtocolVersion) {
+            Params baseParam, FiraProtocolVersion uwbsFiraProtocolVersion,
+            @Nullable String chipId) {
         FiraOpenSessionParams params = (FiraOpenSessionParams) baseParam;
         int deviceType = params.getDeviceType();
         int resultReportConfig = getResultReportConfig(params);
@@ -252,6 +260,10 @@ public class FiraEncoder extends TlvEncoder {
         if (mUwbInjector.getDeviceConfigFacade().isAntennaModeConfigSupported()) {
             tlvBufferBuilder.putByte(ConfigParam.ANTENNA_MODE, params.getAntennaMode());
         }
+        // The vendor app configs are only known once the chip of the session is.
+        if (chipId != null) {
+            mUwbInjector.getVendorAppConfigRegistry().appendTlvs(tlvBufferBuilder, params, chipId);
+        }
         return tlvBufferBuilder.build();
     }
 
@@ -0,0 +1,70 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+package com.android.server.uwb.params;
+
+import android.os.Build;
+
+import com.android.internal.annotations.VisibleForTesting;
+
+import java.util.Random;
+
+/**
+ * Static STS material derived from a seed, for the test sessions of interop runs: the same seed
+ * always gives the same over-the-air STS, so that the sniffer captures of repeated runs can be
+ * compared.
+ *
+ * <p>The material is predictable by design, so it is only available on debuggable builds.
+ */
+public class TestStsMaterial {
+    public static final int VENDOR_ID_LENGTH = 2;
+    public static final int STATIC_STS_IV_LENGTH = 6;
+
+    private final byte[] mVendorId;
+    private final byte[] mStaticStsIV;
+
+    @VisibleForTesting
+    TestStsMaterial(long seed) {
+        // java.util.Random is specified to produce the same sequence for a seed on every device.
+        Random random = new Random(seed);
+        mVendorId = new byte[VENDOR_ID_LENGTH];
+        random.nextBytes(mVendorId);
+        mStaticStsIV = new byte[STATIC_STS_IV_LENGTH];
+        random.nextBytes(mStaticStsIV);
+    }
+
+    /**
+     * Derives the STS material of {@code seed}.
+     *
+     * @throws IllegalStateException on non-debuggable builds.
+     */
+    public static TestStsMaterial fromSeed(long seed) {
+        if (!Build.isDebuggable()) {
+            throw new IllegalStateException("Seeded STS material is only allowed on test builds");
+        }
+        return new TestStsMaterial(seed);
+    }
+
+    /** Gets the vendor ID, which the static STS uses along with the IV. */
+    public byte[] getVendorId() {
+        return mVendorId.clone();
+    }
+
+    /** Gets the static STS IV. */
+    public byte[] getStaticStsIV() {
+        return mStaticStsIV.clone();
+    }
+}
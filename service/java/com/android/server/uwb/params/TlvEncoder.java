@@ -49,4 +49,12 @@ public abstract class TlvEncoder {
      * Convert the given {@code Params} into a TLV representation (that can be sent to the UWBS).
      */
     public abstract TlvBuffer getTlvBuffer(Params param, ProtocolVersion protocolVersion);
+
+    /**
+     * Convert the given {@code Params} into a TLV representation for the chip {@code chipId},
+     * which includes the vendor app configs registered for this chip.
+     */
+    public TlvBuffer getTlvBuffer(Params param, ProtocolVersion protocolVersion, String chipId) {
+        return getTlvBuffer(param, protocolVersion);
+    }
 }
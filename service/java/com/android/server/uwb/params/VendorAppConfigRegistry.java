@@ -0,0 +1,157 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+package com.android.server.uwb.params;
+
+import android.annotation.NonNull;
+import android.annotation.Nullable;
+import android.util.Log;
+
+import com.android.server.uwb.config.ConfigParam;
+
+import com.google.uwb.support.fira.FiraOpenSessionParams;
+
+import java.util.HashMap;
+import java.util.Map;
+import java.util.Set;
+import java.util.TreeMap;
+
+/**
+ * Registry of the vendor APP_CONFIG TLVs added by OEM code to the FiRa session configuration.
+ *
+ * <p>The TLVs are registered when the UWB service is constructed, either for all the chips or for
+ * a single chip, and are appended by {@link FiraEncoder} to the TLVs of the open session params.
+ * Only the IDs of the vendor range which are not used by the UWB service can be registered.
+ */
+public class VendorAppConfigRegistry {
+    private static final String TAG = "VendorAppConfigRegistry";
+
+    /** The first app config ID of the vendor range, the lower ones are defined by FiRa. */
+    public static final int VENDOR_APP_CONFIG_ID_MIN = 0xE0;
+    public static final int VENDOR_APP_CONFIG_ID_MAX = 0xFF;
+
+    /** The vendor app config IDs already set by {@link FiraEncoder}. */
+    private static final Set<Integer> RESERVED_VENDOR_APP_CONFIG_IDS = Set.of(
+            ConfigParam.NUM_RANGE_MEASUREMENTS,
+            ConfigParam.NUM_AOA_AZIMUTH_MEASUREMENTS,
+            ConfigParam.NUM_AOA_ELEVATION_MEASUREMENTS,
+            ConfigParam.ENABLE_DIAGNOSTICS_RSSI,
+            ConfigParam.ENABLE_DIAGRAMS_FRAME_REPORTS_FIELDS,
+            ConfigParam.ANTENNA_MODE);
+
+    /** Encodes the value of a vendor app config for a session. */
+    public interface Encoder {
+        /**
+         * Returns the value of the app config for the session opened with {@code params}, or
+         * null when it is not set for this session.
+         */
+        @Nullable
+        byte[] encode(@NonNull FiraOpenSessionParams params);
+    }
+
+    /** Checks the value of a vendor app config before it is sent to the UWBS. */
+    public interface Validator {
+        /** Returns true if {@code value} can be sent to the UWBS. */
+        boolean isValid(@NonNull byte[] value);
+    }
+
+    private static final class Entry {
+        final Encoder mEncoder;
+        final Validator mValidator;
+
+        Entry(Encoder encoder, Validator validator) {
+            mEncoder = encoder;
+            mValidator = validator;
+        }
+    }
+
+    // Sorted by ID, so that the TLVs are always appended in the same order.
+    private final Map<Integer, Entry> mAllChipsEntries = new TreeMap<>();
+    private final Map<String, Map<Integer, Entry>> mChipEntries = new HashMap<>();
+
+    /**
+     * Registers the vendor app config {@code configId} for all the chips.
+     *
+     * @throws IllegalArgumentException if the ID is not a free vendor app config ID, or is
+     *                                  already registered for a chip.
+     */
+    public synchronized void register(int configId, @NonNull Encoder encoder,
+            @NonNull Validator validator) {
+        checkConfigId(configId);
+        if (mAllChipsEntries.containsKey(configId) || mChipEntries.values().stream()
+                .anyMatch(entries -> entries.containsKey(configId))) {
+            throw new IllegalArgumentException(
+                    "App config " + toHex(configId) + " is already registered");
+        }
+        mAllChipsEntries.put(configId, new Entry(encoder, validator));
+    }
+
+    /**
+     * Registers the vendor app config {@code configId} for the chip {@code chipId} only.
+     *
+     * @throws IllegalArgumentException if the ID is not a free vendor app config ID, or is
+     *                                  already registered for this chip or for all the chips.
+     */
+    public synchronized void register(@NonNull String chipId, int configId,
+            @NonNull Encoder encoder, @NonNull Validator validator) {
+        checkConfigId(configId);
+        Map<Integer, Entry> entries = mChipEntries.computeIfAbsent(chipId, k -> new TreeMap<>());
+        if (mAllChipsEntries.containsKey(configId) || entries.containsKey(configId)) {
+            throw new IllegalArgumentException("App config " + toHex(configId)
+                    + " is already registered for chip " + chipId);
+        }
+        entries.put(configId, new Entry(encoder, validator));
+    }
+
+    /**
+     * Appends the vendor app configs registered for {@code chipId} to {@code builder}. The values
+     * rejected by their validator are not sent.
+     */
+    public synchronized void appendTlvs(@NonNull TlvBuffer.Builder builder,
+            @NonNull FiraOpenSessionParams params, @Nullable String chipId) {
+        Map<Integer, Entry> entries = new TreeMap<>(mAllChipsEntries);
+        if (chipId != null && mChipEntries.containsKey(chipId)) {
+            entries.putAll(mChipEntries.get(chipId));
+        }
+        for (Map.Entry<Integer, Entry> entry : entries.entrySet()) {
+            int configId = entry.getKey();
+            byte[] value = entry.getValue().mEncoder.encode(params);
+            if (value == null) {
+                continue;
+            }
+            if (!entry.getValue().mValidator.isValid(value)) {
+                Log.e(TAG, "Invalid value of app config " + toHex(configId) + ", not sent");
+                continue;
+            }
+            builder.putByteArray(configId, value);
+        }
+    }
+
+    private static void checkConfigId(int configId) {
+        if (configId < VENDOR_APP_CONFIG_ID_MIN || configId > VENDOR_APP_CONFIG_ID_MAX) {
+            throw new IllegalArgumentException(
+                    "App config " + toHex(configId) + " is not a vendor app config");
+        }
+        if (RESERVED_VENDOR_APP_CONFIG_IDS.contains(configId)) {
+            throw new IllegalArgumentException(
+                    "App config " + toHex(configId) + " is used by the UWB service");
+        }
+    }
+
+    private static String toHex(int configId) {
+        return String.format("0x%02X", configId);
+    }
+}
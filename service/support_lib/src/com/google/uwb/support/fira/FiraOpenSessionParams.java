@@ -154,6 +154,8 @@ public class FiraOpenSessionParams extends FiraParams {
     @Nullable private final int mReferenceSessionHandle;
     @Nullable private final int mSessionOffsetInMicroSeconds;
     private final int mApplicationDataEndpoint;
+    @Nullable private final FiraProtocolVersion mPinnedPhyVersion;
+    @Nullable private final FiraProtocolVersion mPinnedMacVersion;
 
     private static final int BUNDLE_VERSION_1 = 1;
     private static final int BUNDLE_VERSION_CURRENT = BUNDLE_VERSION_1;
@@ -265,6 +267,8 @@ public class FiraOpenSessionParams extends FiraParams {
     private static final String KEY_SESSION_OFFSET_IN_MICRO_SECONDS =
                 "session_offset_in_micro_seconds";
     private static final String KEY_APPLICATION_DATA_ENDPOINT = "application_data_endpoint";
+    private static final String KEY_PINNED_PHY_VERSION = "pinned_phy_version";
+    private static final String KEY_PINNED_MAC_VERSION = "pinned_mac_version";
     private static final String KEY_ANTENNA_MODE = "antenna_mode";
 
     private FiraOpenSessionParams(
@@ -354,7 +358,9 @@ public class FiraOpenSessionParams extends FiraParams {
             int referenceTimeBase,
             int referenceSessionHandle,
             int sessionOffsetInMicroSecond,
-            int applicationDataEndpoint) {
+            int applicationDataEndpoint,
+            @Nullable FiraProtocolVersion pinnedPhyVersion,
+            @Nullable FiraProtocolVersion pinnedMacVersion) {
         mProtocolVersion = protocolVersion;
         mSessionId = sessionId;
         mSessionType = sessionType;
@@ -442,6 +448,8 @@ public class FiraOpenSessionParams extends FiraParams {
         mReferenceSessionHandle = referenceSessionHandle;
         mSessionOffsetInMicroSeconds = sessionOffsetInMicroSecond;
         mApplicationDataEndpoint = applicationDataEndpoint;
+        mPinnedPhyVersion = pinnedPhyVersion;
+        mPinnedMacVersion = pinnedMacVersion;
     }
 
     @Override
@@ -824,6 +832,18 @@ public class FiraOpenSessionParams extends FiraParams {
         return mApplicationDataEndpoint;
     }
 
+    /** Gets the FiRa PHY version the session is pinned to, null if not pinned. */
+    @Nullable
+    public FiraProtocolVersion getPinnedPhyVersion() {
+        return mPinnedPhyVersion;
+    }
+
+    /** Gets the FiRa MAC version the session is pinned to, null if not pinned. */
+    @Nullable
+    public FiraProtocolVersion getPinnedMacVersion() {
+        return mPinnedMacVersion;
+    }
+
     @Nullable
     private static int[] byteArrayToIntArray(@Nullable byte[] bytes) {
         if (bytes == null) {
@@ -968,6 +988,12 @@ public class FiraOpenSessionParams extends FiraParams {
             bundle.putInt(KEY_SESSION_OFFSET_IN_MICRO_SECONDS, mSessionOffsetInMicroSeconds);
         }
         bundle.putInt(KEY_APPLICATION_DATA_ENDPOINT, mApplicationDataEndpoint);
+        if (mPinnedPhyVersion != null) {
+            bundle.putString(KEY_PINNED_PHY_VERSION, mPinnedPhyVersion.toString());
+        }
+        if (mPinnedMacVersion != null) {
+            bundle.putString(KEY_PINNED_MAC_VERSION, mPinnedMacVersion.toString());
+        }
         return bundle;
     }
 
@@ -1109,6 +1135,15 @@ public class FiraOpenSessionParams extends FiraParams {
                 .setApplicationDataEndpoint(bundle.getInt(
                         KEY_APPLICATION_DATA_ENDPOINT, APPLICATION_DATA_ENDPOINT_DEFAULT));
 
+        String pinnedPhyVersion = bundle.getString(KEY_PINNED_PHY_VERSION);
+        if (pinnedPhyVersion != null) {
+            builder.setPinnedPhyVersion(FiraProtocolVersion.fromString(pinnedPhyVersion));
+        }
+        String pinnedMacVersion = bundle.getString(KEY_PINNED_MAC_VERSION);
+        if (pinnedMacVersion != null) {
+            builder.setPinnedMacVersion(FiraProtocolVersion.fromString(pinnedMacVersion));
+        }
+
         if (builder.isTimeScheduledTwrSession()) {
             long[] destAddresses = bundle.getLongArray(KEY_DEST_ADDRESS_LIST);
             if (destAddresses != null) {
@@ -1381,6 +1416,10 @@ public class FiraOpenSessionParams extends FiraParams {
 
         private int mApplicationDataEndpoint = APPLICATION_DATA_ENDPOINT_DEFAULT;
 
+        @Nullable private FiraProtocolVersion mPinnedPhyVersion = null;
+
+        @Nullable private FiraProtocolVersion mPinnedMacVersion = null;
+
         public Builder() {}
 
         public Builder(@NonNull Builder builder) {
@@ -1472,6 +1511,8 @@ public class FiraOpenSessionParams extends FiraParams {
             mReferenceSessionHandle = builder.mReferenceSessionHandle;
             mSessionOffsetInMicroSeconds = builder.mSessionOffsetInMicroSeconds;
             mApplicationDataEndpoint = builder.mApplicationDataEndpoint;
+            mPinnedPhyVersion = builder.mPinnedPhyVersion;
+            mPinnedMacVersion = builder.mPinnedMacVersion;
         }
 
         public Builder(@NonNull FiraOpenSessionParams params) {
@@ -1564,6 +1605,8 @@ public class FiraOpenSessionParams extends FiraParams {
             mReferenceSessionHandle = params.mReferenceSessionHandle;
             mSessionOffsetInMicroSeconds = params.mSessionOffsetInMicroSeconds;
             mApplicationDataEndpoint = params.mApplicationDataEndpoint;
+            mPinnedPhyVersion = params.mPinnedPhyVersion;
+            mPinnedMacVersion = params.mPinnedMacVersion;
         }
 
         public FiraOpenSessionParams.Builder setProtocolVersion(FiraProtocolVersion version) {
@@ -2072,6 +2115,28 @@ public class FiraOpenSessionParams extends FiraParams {
             return this;
         }
 
+        /**
+         * Pins the session to a FiRa PHY version, for interoperability with peers which do not
+         * support the latest version of the UWBS. The version must be within the range supported
+         * by the UWBS, see FiraSpecificationParams.
+         */
+        public FiraOpenSessionParams.Builder setPinnedPhyVersion(
+                @Nullable FiraProtocolVersion pinnedPhyVersion) {
+            mPinnedPhyVersion = pinnedPhyVersion;
+            return this;
+        }
+
+        /**
+         * Pins the session to a FiRa MAC version, for interoperability with peers which do not
+         * support the latest version of the UWBS. The version must be within the range supported
+         * by the UWBS, see FiraSpecificationParams.
+         */
+        public FiraOpenSessionParams.Builder setPinnedMacVersion(
+                @Nullable FiraProtocolVersion pinnedMacVersion) {
+            mPinnedMacVersion = pinnedMacVersion;
+            return this;
+        }
+
         private void checkAddress() {
             checkArgument(
                     mMacAddressMode == MAC_ADDRESS_MODE_2_BYTES
@@ -2315,7 +2380,9 @@ public class FiraOpenSessionParams extends FiraParams {
                     mReferenceTimeBase,
                     mReferenceSessionHandle,
                     mSessionOffsetInMicroSeconds,
-                    mApplicationDataEndpoint);
+                    mApplicationDataEndpoint,
+                    mPinnedPhyVersion,
+                    mPinnedMacVersion);
         }
     }
 }
@@ -633,7 +633,8 @@ public abstract class FiraParams extends Params {
                     STATUS_CODE_DATA_TRANSFER_NTF_ERROR_REJECTED,
                     STATUS_CODE_DATA_TRANSFER_NTF_SESSION_TYPE_NOT_SUPPORTED,
                     STATUS_CODE_DATA_TRANSFER_NTF_ERROR_DATA_TRANSFER_IS_ONGOING,
-                    STATUS_CODE_DATA_TRANSFER_NTF_STATUS_INVALID_FORMAT
+                    STATUS_CODE_DATA_TRANSFER_NTF_STATUS_INVALID_FORMAT,
+                    STATUS_CODE_DATA_TRANSFER_NTF_CANCELLED
             })
     public @interface DataTransferStatusNtfCode {}
 
@@ -645,6 +646,8 @@ public abstract class FiraParams extends Params {
     public static final int STATUS_CODE_DATA_TRANSFER_NTF_SESSION_TYPE_NOT_SUPPORTED = 5;
     public static final int STATUS_CODE_DATA_TRANSFER_NTF_ERROR_DATA_TRANSFER_IS_ONGOING = 6;
     public static final int STATUS_CODE_DATA_TRANSFER_NTF_STATUS_INVALID_FORMAT = 7;
+    /** Not sent by the UWBS: reported for the sends still in flight when the session stops. */
+    public static final int STATUS_CODE_DATA_TRANSFER_NTF_CANCELLED = 0xFF;
 
     /**
      * Table TBD: Status codes in the SESSION_DATA_TRANSFER_PHASE_CONFIGURATION_NTF.
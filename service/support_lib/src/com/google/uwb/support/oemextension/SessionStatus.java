@@ -37,6 +37,7 @@ public class SessionStatus {
     private final String mAppPackageName;
     private final int mSessionToken;
     private final String mProtocolName;
+    private final int mHostStopReason;
     public static final String KEY_BUNDLE_VERSION = "bundle_version";
     public static final String SESSION_ID = "session_id";
     public static final String STATE = "state";
@@ -44,6 +45,20 @@ public class SessionStatus {
     public static final String APP_PACKAGE_NAME = "app_package_name";
     public static final String SESSION_TOKEN = "session_token";
     public static final String PROTOCOL_NAME = "protocol_name";
+    public static final String HOST_STOP_REASON = "host_stop_reason";
+
+    /** The session was not stopped by the host. */
+    public static final int HOST_STOP_REASON_NONE = 0;
+    /** The session was stopped on request of the app. */
+    public static final int HOST_STOP_REASON_LOCAL_API = 1;
+    /** The session was stopped after a streak of ranging errors. */
+    public static final int HOST_STOP_REASON_ERROR_STREAK = 2;
+    /** The session was stopped because its non-privileged app stayed in the background. */
+    public static final int HOST_STOP_REASON_BACKGROUND_APP = 3;
+    /** The session was stopped by a thermal mitigation policy. */
+    public static final int HOST_STOP_REASON_THERMAL = 4;
+    /** The session was stopped by a regulatory policy. */
+    public static final int HOST_STOP_REASON_REGULATORY = 5;
 
     public static int getBundleVersion() {
         return BUNDLE_VERSION_CURRENT;
@@ -74,14 +89,23 @@ public class SessionStatus {
         return mProtocolName;
     }
 
+    /**
+     * Gets why the host stopped the session, one of the HOST_STOP_REASON_* constants. Lets the
+     * platform stops be told apart from the stops requested by the app.
+     */
+    public int getHostStopReason() {
+        return mHostStopReason;
+    }
+
     private SessionStatus(long sessionId, int state, int reasonCode, String appPackageName,
-            int sessionToken, String protocolName) {
+            int sessionToken, String protocolName, int hostStopReason) {
         mSessionId = sessionId;
         mState = state;
         mReasonCode = reasonCode;
         mAppPackageName = appPackageName;
         mSessionToken = sessionToken;
         mProtocolName = protocolName;
+        mHostStopReason = hostStopReason;
     }
 
     public PersistableBundle toBundle() {
@@ -93,6 +117,7 @@ public class SessionStatus {
         bundle.putString(APP_PACKAGE_NAME, mAppPackageName);
         bundle.putInt(SESSION_TOKEN, mSessionToken);
         bundle.putString(PROTOCOL_NAME, mProtocolName);
+        bundle.putInt(HOST_STOP_REASON, mHostStopReason);
         return bundle;
     }
 
@@ -113,6 +138,7 @@ public class SessionStatus {
                 .setAppPackageName(bundle.getString(APP_PACKAGE_NAME))
                 .setSessiontoken(bundle.getInt(SESSION_TOKEN))
                 .setProtocolName(bundle.getString(PROTOCOL_NAME, "UnknownProtocolName"))
+                .setHostStopReason(bundle.getInt(HOST_STOP_REASON, HOST_STOP_REASON_NONE))
                 .build();
     }
 
@@ -124,6 +150,7 @@ public class SessionStatus {
         private String mAppPackageName = "UnknownPackageName";
         private int mSessionToken = 0;
         private String mProtocolName = "UnknownProtocolName";
+        private int mHostStopReason = HOST_STOP_REASON_NONE;
 
         public SessionStatus.Builder setSessionId(long sessionId) {
             mSessionId.set(sessionId);
@@ -155,6 +182,11 @@ public class SessionStatus {
             return this;
         }
 
+        public SessionStatus.Builder setHostStopReason(int hostStopReason) {
+            mHostStopReason = hostStopReason;
+            return this;
+        }
+
         public SessionStatus build() {
             return new SessionStatus(
                     mSessionId.get(),
@@ -162,7 +194,8 @@ public class SessionStatus {
                     mReasonCode.get(),
                     mAppPackageName,
                     mSessionToken,
-                    mProtocolName);
+                    mProtocolName,
+                    mHostStopReason);
         }
     }
 }
@@ -149,6 +149,8 @@ public class FiraTests {
         byte[] ulTdoaDeviceId = new byte[] {(byte) 0x0C, (byte) 0x0B};
         int ulTdoaTxTimestampType = TX_TIMESTAMP_40_BIT;
         int maxNumberOfMeasurements = 1;
+        FiraProtocolVersion pinnedPhyVersion = FiraParams.PROTOCOL_VERSION_1_1;
+        FiraProtocolVersion pinnedMacVersion = FiraParams.PROTOCOL_VERSION_1_1;
 
         FiraOpenSessionParams params =
                 new FiraOpenSessionParams.Builder()
@@ -218,9 +220,13 @@ public class FiraTests {
                         .setUlTdoaDeviceId(ulTdoaDeviceId)
                         .setUlTdoaTxTimestampType(ulTdoaTxTimestampType)
                         .setMaxNumberOfMeasurements(maxNumberOfMeasurements)
+                        .setPinnedPhyVersion(pinnedPhyVersion)
+                        .setPinnedMacVersion(pinnedMacVersion)
                         .build();
 
         assertEquals(params.getProtocolVersion(), protocolVersion);
+        assertEquals(params.getPinnedPhyVersion(), pinnedPhyVersion);
+        assertEquals(params.getPinnedMacVersion(), pinnedMacVersion);
         assertEquals(params.getSessionId(), sessionId);
         assertEquals(params.getSessionType(), sessionType);
         assertEquals(params.getDeviceType(), deviceType);
@@ -294,6 +300,8 @@ public class FiraTests {
         assertEquals(params.getMaxNumberOfMeasurements(), maxNumberOfMeasurements);
 
         FiraOpenSessionParams fromBundle = FiraOpenSessionParams.fromBundle(params.toBundle());
+        assertEquals(fromBundle.getPinnedPhyVersion(), pinnedPhyVersion);
+        assertEquals(fromBundle.getPinnedMacVersion(), pinnedMacVersion);
 
         assertEquals(fromBundle.getRangingRoundUsage(), rangingRoundUsage);
         assertEquals(fromBundle.getMultiNodeMode(), multiNodeMode);
@@ -443,6 +451,8 @@ public class FiraTests {
         assertArrayEquals(fromCopy.getUlTdoaDeviceId(), ulTdoaDeviceId);
         assertEquals(fromCopy.getUlTdoaTxTimestampType(), ulTdoaTxTimestampType);
         assertEquals(fromCopy.getMaxNumberOfMeasurements(), maxNumberOfMeasurements);
+        assertEquals(fromCopy.getPinnedPhyVersion(), pinnedPhyVersion);
+        assertEquals(fromCopy.getPinnedMacVersion(), pinnedMacVersion);
 
         verifyProtocolPresent(fromCopy);
         verifyBundlesEqual(params, fromCopy);
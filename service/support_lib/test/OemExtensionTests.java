@@ -69,6 +69,7 @@ public class OemExtensionTests {
         int reasonCode = 0;
         String appPackageName = "test_app";
         int sessionToken = 1000;
+        int hostStopReason = SessionStatus.HOST_STOP_REASON_ERROR_STREAK;
 
         SessionStatus sessionStatus = new SessionStatus.Builder()
                 .setSessionId(sessionId)
@@ -76,6 +77,7 @@ public class OemExtensionTests {
                 .setReasonCode(reasonCode)
                 .setAppPackageName(appPackageName)
                 .setSessiontoken(sessionToken)
+                .setHostStopReason(hostStopReason)
                 .build();
 
         assertEquals(sessionStatus.getSessionId(), sessionId);
@@ -83,6 +85,7 @@ public class OemExtensionTests {
         assertEquals(sessionStatus.getReasonCode(), reasonCode);
         assertEquals(sessionStatus.getAppPackageName(), appPackageName);
         assertEquals(sessionStatus.getSessionToken(), sessionToken);
+        assertEquals(sessionStatus.getHostStopReason(), hostStopReason);
 
         SessionStatus fromBundle = SessionStatus.fromBundle(sessionStatus.toBundle());
 
@@ -91,6 +94,7 @@ public class OemExtensionTests {
         assertEquals(fromBundle.getReasonCode(), reasonCode);
         assertEquals(fromBundle.getAppPackageName(), appPackageName);
         assertEquals(fromBundle.getSessionToken(), sessionToken);
+        assertEquals(fromBundle.getHostStopReason(), hostStopReason);
     }
 
     @Test
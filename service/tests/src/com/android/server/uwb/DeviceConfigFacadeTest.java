@@ -18,6 +18,7 @@ package com.android.server.uwb;
 
 import static com.android.dx.mockito.inline.extended.ExtendedMockito.verify;
 
+import static org.junit.Assert.assertArrayEquals;
 import static org.junit.Assert.assertEquals;
 import static org.mockito.ArgumentMatchers.any;
 import static org.mockito.ArgumentMatchers.anyBoolean;
@@ -240,6 +241,7 @@ public class DeviceConfigFacadeTest {
         assertEquals(false, mDeviceConfigFacade.isPersistentCacheUseForCountryCodeEnabled());
         assertEquals(false, mDeviceConfigFacade.isHwIdleTurnOffEnabled());
         assertEquals(false, mDeviceConfigFacade.isAntennaModeConfigSupported());
+        assertArrayEquals(new int[0], mDeviceConfigFacade.getUciInjectionGidAllowlist());
     }
 
     /**
@@ -378,6 +380,13 @@ public class DeviceConfigFacadeTest {
         assertEquals(true, mDeviceConfigFacade.isPersistentCacheUseForCountryCodeEnabled());
         assertEquals(true, mDeviceConfigFacade.isHwIdleTurnOffEnabled());
         assertEquals(true, mDeviceConfigFacade.isAntennaModeConfigSupported());
+
+        when(DeviceConfig.getString(anyString(), eq("uci_injection_gid_allowlist"),
+                anyString())).thenReturn("9, e,invalid");
+        mOnPropertiesChangedListenerCaptor.getValue().onPropertiesChanged(null);
+        assertArrayEquals(new int[] {0x9, 0xE},
+                mDeviceConfigFacade.getUciInjectionGidAllowlist());
+
         when(DeviceConfig.getString(anyString(), eq("pose_source_type"),
                 anyString())).thenReturn("NONE");
         mOnPropertiesChangedListenerCaptor.getValue().onPropertiesChanged(null);
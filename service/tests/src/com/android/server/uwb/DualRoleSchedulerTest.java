@@ -0,0 +1,75 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+package com.android.server.uwb;
+
+import static com.android.server.uwb.DualRoleScheduler.CONFLICT_AIRTIME_EXCEEDED;
+import static com.android.server.uwb.DualRoleScheduler.CONFLICT_INTERVAL_MISMATCH;
+import static com.android.server.uwb.DualRoleScheduler.NO_SESSION_ID;
+
+import static com.google.common.truth.Truth.assertThat;
+
+import com.android.server.uwb.DualRoleScheduler.Conflict;
+import com.android.server.uwb.DualRoleScheduler.Plan;
+import com.android.server.uwb.DualRoleScheduler.SessionTiming;
+
+import org.junit.Test;
+
+import java.util.List;
+
+public class DualRoleSchedulerTest {
+    @Test
+    public void testPlan_sameRoleOnly_noOffset() {
+        Plan plan = DualRoleScheduler.plan(
+                new SessionTiming(1, true, 12_000, 200_000),
+                List.of(new SessionTiming(2, true, 12_000, 300_000)));
+
+        assertThat(plan.initiationTimeOffsetMs).isEqualTo(0);
+        assertThat(plan.conflicts).isEmpty();
+    }
+
+    @Test
+    public void testPlan_initiatorOffsetPastResponders() {
+        Plan plan = DualRoleScheduler.plan(
+                new SessionTiming(1, true, 12_000, 200_000),
+                List.of(new SessionTiming(2, false, 10_500, 400_000),
+                        new SessionTiming(3, true, 12_000, 200_000)));
+
+        assertThat(plan.initiationTimeOffsetMs).isEqualTo(11);
+        assertThat(plan.conflicts).isEmpty();
+    }
+
+    @Test
+    public void testPlan_responderNotOffset_intervalMismatch() {
+        Plan plan = DualRoleScheduler.plan(
+                new SessionTiming(1, false, 12_000, 240_000),
+                List.of(new SessionTiming(2, true, 12_000, 200_000)));
+
+        assertThat(plan.initiationTimeOffsetMs).isEqualTo(0);
+        assertThat(plan.conflicts).containsExactly(
+                new Conflict(CONFLICT_INTERVAL_MISMATCH, 1, 2));
+    }
+
+    @Test
+    public void testPlan_airtimeExceeded() {
+        Plan plan = DualRoleScheduler.plan(
+                new SessionTiming(1, true, 60_000, 100_000),
+                List.of(new SessionTiming(2, false, 40_000, 100_000)));
+
+        assertThat(plan.conflicts).containsExactly(
+                new Conflict(CONFLICT_AIRTIME_EXCEEDED, 1, NO_SESSION_ID));
+    }
+}
@@ -0,0 +1,87 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+package com.android.server.uwb;
+
+import static com.android.server.uwb.LinkQualityTracker.RSSI_UNKNOWN;
+import static com.android.server.uwb.LinkQualityTracker.WINDOW_SIZE;
+
+import static com.google.common.truth.Truth.assertThat;
+
+import com.android.server.uwb.LinkQualityTracker.LinkQuality;
+
+import org.junit.Test;
+
+public class LinkQualityTrackerTest {
+    @Test
+    public void testGetLinkQuality() {
+        LinkQualityTracker tracker = new LinkQualityTracker(0);
+        tracker.onRound(true, -60, 40);
+        tracker.onRound(false, RSSI_UNKNOWN, 0);
+        tracker.onRound(true, -70, 60);
+        tracker.onRound(true, -80, 80);
+
+        LinkQuality linkQuality = tracker.getLinkQuality();
+        assertThat(linkQuality.roundCount).isEqualTo(4);
+        assertThat(linkQuality.successRatePercent).isEqualTo(75);
+        assertThat(linkQuality.averageRssiDbm).isEqualTo(-70);
+        assertThat(linkQuality.fomTrend).isEqualTo(40);
+    }
+
+    @Test
+    public void testGetLinkQuality_keepsLastRounds() {
+        LinkQualityTracker tracker = new LinkQualityTracker(0);
+        for (int i = 0; i < WINDOW_SIZE; i++) {
+            tracker.onRound(false, RSSI_UNKNOWN, 0);
+        }
+        for (int i = 0; i < WINDOW_SIZE; i++) {
+            tracker.onRound(true, -50, 90);
+        }
+
+        LinkQuality linkQuality = tracker.getLinkQuality();
+        assertThat(linkQuality.roundCount).isEqualTo(WINDOW_SIZE);
+        assertThat(linkQuality.successRatePercent).isEqualTo(100);
+        assertThat(linkQuality.averageRssiDbm).isEqualTo(-50);
+        assertThat(linkQuality.fomTrend).isEqualTo(0);
+    }
+
+    @Test
+    public void testOnRound_reportsThresholdCrossings() {
+        LinkQualityTracker tracker = new LinkQualityTracker(50);
+        // Not judged until the window is filled.
+        for (int i = 0; i < WINDOW_SIZE - 1; i++) {
+            assertThat(tracker.onRound(false, RSSI_UNKNOWN, 0)).isFalse();
+        }
+        assertThat(tracker.onRound(false, RSSI_UNKNOWN, 0)).isTrue();
+        assertThat(tracker.isBelowThreshold()).isTrue();
+
+        // Back up once half of the rounds succeed.
+        for (int i = 0; i < WINDOW_SIZE / 2 - 1; i++) {
+            assertThat(tracker.onRound(true, -60, 50)).isFalse();
+        }
+        assertThat(tracker.onRound(true, -60, 50)).isTrue();
+        assertThat(tracker.isBelowThreshold()).isFalse();
+    }
+
+    @Test
+    public void testOnRound_thresholdDisabled() {
+        LinkQualityTracker tracker = new LinkQualityTracker(0);
+        for (int i = 0; i < 2 * WINDOW_SIZE; i++) {
+            assertThat(tracker.onRound(false, RSSI_UNKNOWN, 0)).isFalse();
+        }
+        assertThat(tracker.isBelowThreshold()).isFalse();
+    }
+}
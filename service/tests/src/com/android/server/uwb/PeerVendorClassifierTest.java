@@ -0,0 +1,64 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+package com.android.server.uwb;
+
+import static com.google.common.truth.Truth.assertThat;
+
+import android.uwb.UwbAddress;
+
+import org.junit.Test;
+
+public class PeerVendorClassifierTest {
+    private static final UwbAddress VENDOR1_ADDRESS =
+            UwbAddress.fromBytes(new byte[] {0x00, 0x25, (byte) 0xDF, 1, 2, 3, 4, 5});
+    private static final UwbAddress VENDOR2_ADDRESS =
+            UwbAddress.fromBytes(new byte[] {0x70, (byte) 0xB3, (byte) 0xD5, 1, 2, 3, 4, 5});
+
+    @Test
+    public void testClassify() {
+        PeerVendorClassifier classifier =
+                PeerVendorClassifier.fromConfig("0025DF:vendor1, 70b3d5:vendor2", "");
+
+        assertThat(classifier.classify(VENDOR1_ADDRESS)).isEqualTo("vendor1");
+        assertThat(classifier.classify(VENDOR2_ADDRESS)).isEqualTo("vendor2");
+        assertThat(classifier.classify(
+                UwbAddress.fromBytes(new byte[] {0x01, 0x02, 0x03, 1, 2, 3, 4, 5}))).isNull();
+        // Short addresses carry no OUI.
+        assertThat(classifier.classify(UwbAddress.fromBytes(new byte[] {0x00, 0x25}))).isNull();
+    }
+
+    @Test
+    public void testFromConfig_skipsMalformedEntries() {
+        PeerVendorClassifier classifier = PeerVendorClassifier.fromConfig(
+                "0025DF,0025D:short,XYZXYZ:bad,70B3D5:vendor2,:empty", null);
+
+        assertThat(classifier.classify(VENDOR1_ADDRESS)).isNull();
+        assertThat(classifier.classify(VENDOR2_ADDRESS)).isEqualTo("vendor2");
+    }
+
+    @Test
+    public void testHasAoaAzimuthSignFlip() {
+        PeerVendorClassifier classifier =
+                PeerVendorClassifier.fromConfig("0025DF:vendor1,70B3D5:vendor2", " vendor1 ,");
+
+        assertThat(classifier.hasAoaAzimuthSignFlip(classifier.classify(VENDOR1_ADDRESS)))
+                .isTrue();
+        assertThat(classifier.hasAoaAzimuthSignFlip(classifier.classify(VENDOR2_ADDRESS)))
+                .isFalse();
+        assertThat(classifier.hasAoaAzimuthSignFlip(null)).isFalse();
+    }
+}
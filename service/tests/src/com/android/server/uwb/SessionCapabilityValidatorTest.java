@@ -0,0 +1,130 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+package com.android.server.uwb;
+
+import static com.google.common.truth.Truth.assertThat;
+
+import android.uwb.UwbAddress;
+
+import com.google.uwb.support.fira.FiraOpenSessionParams;
+import com.google.uwb.support.fira.FiraParams;
+import com.google.uwb.support.fira.FiraParams.StsCapabilityFlag;
+import com.google.uwb.support.fira.FiraSpecificationParams;
+import com.google.uwb.support.generic.GenericSpecificationParams;
+import com.google.uwb.support.radar.RadarSpecificationParams;
+
+import org.junit.Test;
+
+import java.util.List;
+
+public class SessionCapabilityValidatorTest {
+    private static final FiraSpecificationParams FIRA_SPECIFICATION_PARAMS =
+            new FiraSpecificationParams.Builder()
+                    .setSupportedChannels(List.of(5, 9))
+                    .setStsCapabilities(List.of(StsCapabilityFlag.HAS_STATIC_STS_SUPPORT))
+                    .build();
+
+    private static FiraOpenSessionParams.Builder createFiraOpenSessionParams() {
+        return new FiraOpenSessionParams.Builder()
+                .setProtocolVersion(FiraParams.PROTOCOL_VERSION_1_1)
+                .setSessionId(1)
+                .setSessionType(FiraParams.SESSION_TYPE_RANGING)
+                .setDeviceType(FiraParams.RANGING_DEVICE_TYPE_CONTROLLER)
+                .setDeviceRole(FiraParams.RANGING_DEVICE_ROLE_INITIATOR)
+                .setDeviceAddress(UwbAddress.fromBytes(new byte[] {0x4, 0x6}))
+                .setDestAddressList(List.of(UwbAddress.fromBytes(new byte[] {0x4, 0x7})))
+                .setMultiNodeMode(FiraParams.MULTI_NODE_MODE_UNICAST)
+                .setVendorId(new byte[] {0x5, 0x78})
+                .setStaticStsIV(new byte[] {0x1a, 0x55, 0x77, 0x47, 0x7e, 0x7d})
+                .setChannelNumber(9);
+    }
+
+    private static GenericSpecificationParams createSpecificationParams(
+            FiraSpecificationParams firaSpecificationParams) {
+        return new GenericSpecificationParams.Builder()
+                .setFiraSpecificationParams(firaSpecificationParams)
+                .build();
+    }
+
+    @Test
+    public void testValidateFiraSession_supported() {
+        assertThat(SessionCapabilityValidator.validateFiraSession(
+                createFiraOpenSessionParams().build(),
+                createSpecificationParams(FIRA_SPECIFICATION_PARAMS))).isNull();
+    }
+
+    @Test
+    public void testValidateFiraSession_unknownCapabilities() {
+        FiraOpenSessionParams params = createFiraOpenSessionParams()
+                .setStsConfig(FiraParams.STS_CONFIG_PROVISIONED)
+                .build();
+        assertThat(SessionCapabilityValidator.validateFiraSession(params, null)).isNull();
+    }
+
+    @Test
+    public void testValidateFiraSession_unsupportedChannel() {
+        FiraOpenSessionParams params = createFiraOpenSessionParams().setChannelNumber(6).build();
+        assertThat(SessionCapabilityValidator.validateFiraSession(
+                params, createSpecificationParams(FIRA_SPECIFICATION_PARAMS)))
+                .isEqualTo("Channel 6 is not supported, supported channels: [5, 9]");
+    }
+
+    @Test
+    public void testValidateFiraSession_unsupportedExtendedMacAddress() {
+        FiraOpenSessionParams params = createFiraOpenSessionParams()
+                .setMacAddressMode(FiraParams.MAC_ADDRESS_MODE_8_BYTES)
+                .setDeviceAddress(UwbAddress.fromBytes(new byte[] {1, 2, 3, 4, 5, 6, 7, 8}))
+                .setDestAddressList(List.of(
+                        UwbAddress.fromBytes(new byte[] {8, 7, 6, 5, 4, 3, 2, 1})))
+                .build();
+        assertThat(SessionCapabilityValidator.validateFiraSession(
+                params, createSpecificationParams(FIRA_SPECIFICATION_PARAMS)))
+                .isEqualTo("Extended MAC addresses are not supported");
+
+        FiraSpecificationParams firaSpecificationParams =
+                new FiraSpecificationParams.Builder(FIRA_SPECIFICATION_PARAMS)
+                        .hasExtendedMacAddressSupport(true)
+                        .build();
+        assertThat(SessionCapabilityValidator.validateFiraSession(
+                params, createSpecificationParams(firaSpecificationParams))).isNull();
+    }
+
+    @Test
+    public void testValidateFiraSession_unsupportedStsConfig() {
+        FiraOpenSessionParams params = createFiraOpenSessionParams()
+                .setStsConfig(FiraParams.STS_CONFIG_PROVISIONED)
+                .setSessionKey(new byte[16])
+                .build();
+        assertThat(SessionCapabilityValidator.validateFiraSession(
+                params, createSpecificationParams(FIRA_SPECIFICATION_PARAMS)))
+                .isEqualTo("STS config 3 is not supported");
+    }
+
+    @Test
+    public void testValidateRadarSession() {
+        assertThat(SessionCapabilityValidator.validateRadarSession(
+                createSpecificationParams(FIRA_SPECIFICATION_PARAMS)))
+                .isEqualTo("Radar is not supported");
+        assertThat(SessionCapabilityValidator.validateRadarSession(
+                new GenericSpecificationParams.Builder()
+                        .setFiraSpecificationParams(FIRA_SPECIFICATION_PARAMS)
+                        .setRadarSpecificationParams(
+                                new RadarSpecificationParams.Builder().build())
+                        .build())).isNull();
+        assertThat(SessionCapabilityValidator.validateRadarSession(null)).isNull();
+    }
+}
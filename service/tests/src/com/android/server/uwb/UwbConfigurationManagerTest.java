@@ -57,6 +57,7 @@ import com.android.server.uwb.data.UwbConfigStatusData;
 import com.android.server.uwb.data.UwbTlvData;
 import com.android.server.uwb.data.UwbUciConstants;
 import com.android.server.uwb.jni.NativeUwbManager;
+import com.android.server.uwb.params.VendorAppConfigRegistry;
 import com.android.server.uwb.proto.UwbStatsLog;
 import com.android.uwb.flags.FeatureFlags;
 
@@ -118,6 +119,7 @@ public class UwbConfigurationManagerTest {
     public void setUp() throws Exception {
         MockitoAnnotations.initMocks(this);
         mUwbConfigurationManager = new UwbConfigurationManager(mNativeUwbManager, mUwbInjector);
+        when(mUwbInjector.getVendorAppConfigRegistry()).thenReturn(new VendorAppConfigRegistry());
         mFiraParams = getFiraParams();
 
         when(mUwbSession.getSessionId()).thenReturn(1);
@@ -65,6 +65,23 @@ public class UwbControleeTest {
         assertThat(mControlee.getUwbAddress()).isEqualTo(UWB_ADDRESS);
     }
 
+    @Test
+    public void testPeerVendor() {
+        DeviceConfigFacade deviceConfigFacade = mock(DeviceConfigFacade.class);
+        when(mUwbInjector.getDeviceConfigFacade()).thenReturn(deviceConfigFacade);
+        when(deviceConfigFacade.getPeerVendorClassifier()).thenReturn(
+                PeerVendorClassifier.fromConfig("010203:vendor1", "vendor1"));
+
+        UwbControlee controlee = new UwbControlee(
+                UwbAddress.fromBytes(new byte[] {1, 2, 3, 4, 5, 6, 7, 8}), null, mUwbInjector);
+        assertThat(controlee.getPeerVendor()).isEqualTo("vendor1");
+        assertThat(controlee.hasAoaAzimuthSignFlip()).isTrue();
+
+        controlee = new UwbControlee(UWB_ADDRESS, null, mUwbInjector);
+        assertThat(controlee.getPeerVendor()).isNull();
+        assertThat(controlee.hasAoaAzimuthSignFlip()).isFalse();
+    }
+
     @Test
     public void testFilterMeasurement() {
         final double testRads = 0.1;
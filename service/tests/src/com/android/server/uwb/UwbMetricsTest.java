@@ -19,6 +19,8 @@ package com.android.server.uwb;
 import static com.android.dx.mockito.inline.extended.ExtendedMockito.verify;
 import static com.android.server.uwb.DeviceConfigFacade.DEFAULT_RANGING_RESULT_LOG_INTERVAL_MS;
 
+import static com.google.common.truth.Truth.assertThat;
+
 import static org.mockito.ArgumentMatchers.anyString;
 import static org.mockito.Mockito.times;
 import static org.mockito.Mockito.validateMockitoUsage;
@@ -41,6 +43,7 @@ import com.android.server.uwb.data.UwbOwrAoaMeasurement;
 import com.android.server.uwb.data.UwbRangingData;
 import com.android.server.uwb.data.UwbTwoWayMeasurement;
 import com.android.server.uwb.data.UwbUciConstants;
+import com.android.server.uwb.info.UwbParseErrorStats;
 import com.android.server.uwb.proto.UwbStatsLog;
 
 import com.google.uwb.support.fira.FiraOpenSessionParams;
@@ -468,6 +471,22 @@ public class UwbMetricsTest {
                 UwbStatsLog.UWB_DEVICE_ERROR_REPORTED__TYPE__UCI_GENERIC_ERROR));
     }
 
+    @Test
+    public void testAddParseErrorStats() throws Exception {
+        mUwbMetrics.addParseErrorStats(new UwbParseErrorStats[] {
+                new UwbParseErrorStats("SessionInfo", 2), new UwbParseErrorStats("Core", 1)});
+        mUwbMetrics.addParseErrorStats(new UwbParseErrorStats[] {
+                new UwbParseErrorStats("SessionInfo", 3)});
+        mUwbMetrics.addParseErrorStats(null);
+
+        ByteArrayOutputStream stream = new ByteArrayOutputStream();
+        PrintWriter writer = new PrintWriter(stream);
+        mUwbMetrics.dump(null, writer, null);
+        writer.flush();
+        String dump = stream.toString();
+        assertThat(dump).contains("Core = 1\nSessionInfo = 5\n");
+    }
+
     @Test
     public void testDumpStatsNoCrash() throws Exception {
         mUwbMetrics.logRangingInitEvent(mUwbSession, UwbUciConstants.STATUS_CODE_OK);
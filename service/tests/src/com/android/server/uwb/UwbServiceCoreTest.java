@@ -44,12 +44,14 @@ import static org.junit.Assert.assertTrue;
 import static org.junit.Assert.fail;
 import static org.mockito.ArgumentMatchers.any;
 import static org.mockito.ArgumentMatchers.anyBoolean;
+import static org.mockito.ArgumentMatchers.anyByte;
 import static org.mockito.ArgumentMatchers.anyInt;
 import static org.mockito.ArgumentMatchers.anyString;
 import static org.mockito.ArgumentMatchers.argThat;
 import static org.mockito.ArgumentMatchers.eq;
 import static org.mockito.Mockito.clearInvocations;
 import static org.mockito.Mockito.doAnswer;
+import static org.mockito.Mockito.inOrder;
 import static org.mockito.Mockito.mock;
 import static org.mockito.Mockito.never;
 import static org.mockito.Mockito.times;
@@ -88,6 +90,8 @@ import com.android.internal.annotations.VisibleForTesting;
 import com.android.server.uwb.data.UwbDeviceInfoResponse;
 import com.android.server.uwb.data.UwbUciConstants;
 import com.android.server.uwb.data.UwbVendorUciResponse;
+import com.android.server.uwb.info.UwbAbortedDataTransfers;
+import com.android.server.uwb.info.UwbSessionMismatch;
 import com.android.server.uwb.jni.NativeUwbManager;
 import com.android.server.uwb.multchip.UwbMultichipData;
 import com.android.server.uwb.multichip.MultichipConfigFileCreator;
@@ -124,6 +128,7 @@ import org.junit.Rule;
 import org.junit.Test;
 import org.junit.rules.TemporaryFolder;
 import org.junit.runner.RunWith;
+import org.mockito.InOrder;
 import org.mockito.Mock;
 import org.mockito.Mockito;
 import org.mockito.MockitoAnnotations;
@@ -439,6 +444,51 @@ public class UwbServiceCoreTest {
         verifyGetCachedSpecificationInfoSuccess();
     }
 
+    @Test
+    public void testCachedSpecificationNotCachedOnFailure() throws Exception {
+        enableUwbWithCountryCodeChangedCallback();
+        when(mUwbConfigurationManager
+                .getCapsInfo(eq(GenericParams.PROTOCOL_NAME), any(), anyString(), any()))
+                .thenReturn(Pair.create(UwbUciConstants.STATUS_CODE_FAILED, null));
+        assertThat(mUwbServiceCore.getCachedSpecificationParams(TEST_DEFAULT_CHIP_ID)).isNull();
+
+        clearInvocations(mUwbConfigurationManager);
+
+        // The capabilities are retrieved again on the next call.
+        verifyGetCachedSpecificationInfoSuccess();
+    }
+
+    @Test
+    public void testOnCountryCodeChanged_reportsRegulatoryImpact() throws Exception {
+        enableUwbWithCountryCodeChangedCallback();
+        // No session is active, so the channels are not checked.
+        assertThat(mUwbServiceCore.getRegulatoryImpact(TEST_DEFAULT_CHIP_ID)).isNull();
+
+        List<Integer> supportedChannels = List.of(5, 6, 8);
+        GenericSpecificationParams genericSpecificationParams =
+                new GenericSpecificationParams.Builder()
+                        .setFiraSpecificationParams(new FiraSpecificationParams.Builder()
+                                .setSupportedChannels(supportedChannels)
+                                .build())
+                        .build();
+        when(mUwbConfigurationManager.getCapsInfo(any(), any(), anyString(), any()))
+                .thenReturn(Pair.create(
+                        UwbUciConstants.STATUS_CODE_OK, genericSpecificationParams));
+        when(mUwbSessionManager.hasActiveSession(TEST_DEFAULT_CHIP_ID)).thenReturn(true);
+        when(mDeviceConfigFacade.isRegulatoryAutoSuspendEnabled()).thenReturn(true);
+        RegulatoryImpact regulatoryImpact = new RegulatoryImpact(TEST_DEFAULT_CHIP_ID,
+                VALID_COUNTRY_CODE, supportedChannels, List.of(1), true);
+        when(mUwbSessionManager.onRegulatoryChannelsChanged(TEST_DEFAULT_CHIP_ID,
+                VALID_COUNTRY_CODE, supportedChannels, true)).thenReturn(regulatoryImpact);
+
+        mUwbServiceCore.onCountryCodeChanged(STATUS_CODE_OK, VALID_COUNTRY_CODE);
+
+        verify(mUwbSessionManager).onRegulatoryChannelsChanged(TEST_DEFAULT_CHIP_ID,
+                VALID_COUNTRY_CODE, supportedChannels, true);
+        assertThat(mUwbServiceCore.getRegulatoryImpact(TEST_DEFAULT_CHIP_ID))
+                .isEqualTo(regulatoryImpact);
+    }
+
     @Test
     public void testEnable_failure() throws Exception {
         IUwbAdapterStateCallbacks cb = mock(IUwbAdapterStateCallbacks.class);
@@ -1089,6 +1139,37 @@ public class UwbServiceCoreTest {
 
     }
 
+    @Test
+    public void testOpenFiraRanging_unsupportedCapabilityRejected() throws Exception {
+        enableUwbWithCountryCodeChangedCallback();
+        GenericSpecificationParams genericSpecificationParams =
+                mock(GenericSpecificationParams.class);
+        FiraSpecificationParams firaSpecificationParams =
+                mock(FiraSpecificationParams.class);
+        when(mUwbConfigurationManager
+                .getCapsInfo(eq(GenericParams.PROTOCOL_NAME), any(), anyString(), any()))
+                .thenReturn(Pair.create(
+                        UwbUciConstants.STATUS_CODE_OK, genericSpecificationParams));
+        when(genericSpecificationParams.getFiraSpecificationParams())
+                .thenReturn(firaSpecificationParams);
+        when(firaSpecificationParams.hasExtendedMacAddressSupport()).thenReturn(false);
+        FiraOpenSessionParams params = new FiraOpenSessionParams.Builder(
+                TEST_FIRA_OPEN_SESSION_PARAMS.build())
+                .setMacAddressMode(FiraParams.MAC_ADDRESS_MODE_8_BYTES)
+                .setDeviceAddress(UwbAddress.fromBytes(new byte[] {1, 2, 3, 4, 5, 6, 7, 8}))
+                .setDestAddressList(List.of(
+                        UwbAddress.fromBytes(new byte[] {8, 7, 6, 5, 4, 3, 2, 1})))
+                .build();
+
+        IllegalArgumentException e = assertThrows(IllegalArgumentException.class,
+                () -> mUwbServiceCore.openRanging(TEST_ATTRIBUTION_SOURCE,
+                        mock(SessionHandle.class), mock(IUwbRangingCallbacks.class),
+                        params.toBundle(), TEST_DEFAULT_CHIP_ID));
+        assertThat(e).hasMessageThat().contains("Extended MAC addresses are not supported");
+        verify(mUwbSessionManager, never()).initSession(any(), any(), anyInt(), anyByte(),
+                any(), any(), any(), any());
+    }
+
     @Test
     public void testOpenAliroRanging() throws Exception {
         enableUwbWithCountryCodeChangedCallback();
@@ -1829,6 +1910,133 @@ public class UwbServiceCoreTest {
         verifyNoMoreInteractions(mNativeUwbManager, cb);
     }
 
+    @Test
+    public void testMaintenanceMode() throws Exception {
+        enableUwbWithCountryCodeChangedCallback();
+        clearInvocations(mNativeUwbManager, mUwbConfigurationManager);
+
+        assertThat(mUwbServiceCore.enterMaintenanceMode(TEST_DEFAULT_CHIP_ID)).isTrue();
+        verify(mUwbSessionManager).deinitAllSession(TEST_DEFAULT_CHIP_ID);
+        assertThrows(UwbServiceCore.BusyException.class,
+                () -> mUwbServiceCore.openRanging(TEST_ATTRIBUTION_SOURCE,
+                        mock(SessionHandle.class), mock(IUwbRangingCallbacks.class),
+                        TEST_FIRA_OPEN_SESSION_PARAMS.build().toBundle(), TEST_DEFAULT_CHIP_ID));
+
+        // The chip rebooting during the firmware update does not restart the stack.
+        mUwbServiceCore.onDeviceStatusNotificationReceived(UwbUciConstants.DEVICE_STATE_ERROR,
+                TEST_DEFAULT_CHIP_ID);
+        mTestLooper.dispatchAll();
+        verify(mNativeUwbManager, never()).doDeinitialize();
+
+        // The chip stays in maintenance mode until its device info is retrieved again.
+        when(mNativeUwbManager.getDeviceInfo(TEST_DEFAULT_CHIP_ID)).thenReturn(null);
+        assertThat(mUwbServiceCore.exitMaintenanceMode(TEST_DEFAULT_CHIP_ID)).isFalse();
+        assertThat(mUwbServiceCore.isInMaintenanceMode(TEST_DEFAULT_CHIP_ID)).isTrue();
+
+        GenericSpecificationParams genericSpecificationParams =
+                mock(GenericSpecificationParams.class);
+        when(genericSpecificationParams.toBundle()).thenReturn(mock(PersistableBundle.class));
+        when(mUwbConfigurationManager
+                .getCapsInfo(eq(GenericParams.PROTOCOL_NAME), any(), anyString(), any()))
+                .thenReturn(Pair.create(
+                        UwbUciConstants.STATUS_CODE_OK, genericSpecificationParams));
+        when(mNativeUwbManager.getDeviceInfo(TEST_DEFAULT_CHIP_ID))
+                .thenReturn(UWB_DEVICE_INFO_RESPONSE);
+        assertThat(mUwbServiceCore.exitMaintenanceMode(TEST_DEFAULT_CHIP_ID)).isTrue();
+        assertThat(mUwbServiceCore.isInMaintenanceMode(TEST_DEFAULT_CHIP_ID)).isFalse();
+        // The capabilities are revalidated.
+        verify(mUwbConfigurationManager).getCapsInfo(eq(GenericParams.PROTOCOL_NAME), any(),
+                eq(TEST_DEFAULT_CHIP_ID), any());
+        assertThat(mUwbServiceCore.getCachedSpecificationParams(TEST_DEFAULT_CHIP_ID))
+                .isEqualTo(genericSpecificationParams);
+    }
+
+    @Test
+    public void testMetricsExporter() throws Exception {
+        when(mNativeUwbManager.startMetricsExporter(anyInt())).thenReturn(true);
+        when(mNativeUwbManager.stopMetricsExporter()).thenReturn(true);
+
+        // The metrics only exist while the stack is initialized.
+        assertThat(mUwbServiceCore.startMetricsExporter(9464)).isFalse();
+        verify(mNativeUwbManager, never()).startMetricsExporter(anyInt());
+
+        enableUwbWithCountryCodeChangedCallback();
+        assertThat(mUwbServiceCore.startMetricsExporter(9464)).isTrue();
+        verify(mNativeUwbManager).startMetricsExporter(9464);
+
+        when(mNativeUwbManager.startMetricsExporter(anyInt())).thenReturn(false);
+        assertThat(mUwbServiceCore.startMetricsExporter(9465)).isFalse();
+
+        assertThat(mUwbServiceCore.stopMetricsExporter()).isTrue();
+        verify(mNativeUwbManager).stopMetricsExporter();
+    }
+
+    @Test
+    public void testUciProxy() throws Exception {
+        when(mNativeUwbManager.startUciProxy(anyInt(), anyString())).thenReturn(true);
+        when(mNativeUwbManager.stopUciProxy(anyString())).thenReturn(true);
+
+        // The HAL is not proxied while the stack uses it.
+        enableUwbWithCountryCodeChangedCallback();
+        assertThat(mUwbServiceCore.startUciProxy(TEST_DEFAULT_CHIP_ID, 8765)).isFalse();
+        verify(mNativeUwbManager, never()).startUciProxy(anyInt(), anyString());
+
+        disableUwb();
+        assertThat(mUwbServiceCore.startUciProxy(TEST_DEFAULT_CHIP_ID, 8765)).isTrue();
+        verify(mNativeUwbManager).startUciProxy(8765, TEST_DEFAULT_CHIP_ID);
+
+        // The stack takes the HAL back when UWB is enabled.
+        enableUwbWithCountryCodeChangedCallback();
+        InOrder inOrder = inOrder(mNativeUwbManager);
+        inOrder.verify(mNativeUwbManager).stopUciProxy(TEST_DEFAULT_CHIP_ID);
+        inOrder.verify(mNativeUwbManager).doInitialize();
+    }
+
+    @Test
+    public void testSessionHealthCheck() throws Exception {
+        when(mDeviceConfigFacade.getSessionHealthCheckIntervalMs()).thenReturn(1000);
+        when(mDeviceConfigFacade.isDeviceErrorBugreportEnabled()).thenReturn(true);
+        UwbSessionMismatch mismatch =
+                new UwbSessionMismatch(UwbSessionMismatch.TYPE_UNKNOWN_SESSION, 5, 0, 0);
+        when(mNativeUwbManager.checkSessions(TEST_DEFAULT_CHIP_ID))
+                .thenReturn(new UwbSessionMismatch[] {mismatch});
+        enableUwbWithCountryCodeChangedCallback();
+
+        // A mismatch found once may be a transition in flight.
+        mTestLooper.moveTimeForward(1000);
+        mTestLooper.dispatchAll();
+        verify(mNativeUwbManager).checkSessions(TEST_DEFAULT_CHIP_ID);
+        verify(mUwbDiagnostics, never()).takeBugReport(anyString());
+
+        mTestLooper.moveTimeForward(1000);
+        mTestLooper.dispatchAll();
+        verify(mNativeUwbManager, times(2)).checkSessions(TEST_DEFAULT_CHIP_ID);
+        verify(mUwbDiagnostics).takeBugReport(anyString());
+
+        // No more checks once UWB is disabled.
+        disableUwb();
+        mTestLooper.moveTimeForward(1000);
+        mTestLooper.dispatchAll();
+        verify(mNativeUwbManager, times(2)).checkSessions(TEST_DEFAULT_CHIP_ID);
+    }
+
+    @Test
+    public void testDataTransfersAbortedByShutdown() throws Exception {
+        // Not persisted while UWB is disabled.
+        mUwbServiceCore.persistDataTransfers();
+        verify(mNativeUwbManager, never()).persistDataTransfers(anyString());
+
+        when(mNativeUwbManager.fetchAbortedDataTransfers(TEST_DEFAULT_CHIP_ID))
+                .thenReturn(new UwbAbortedDataTransfers[] {
+                        new UwbAbortedDataTransfers(5, 2, 1, new long[] {0x0201L})});
+        when(mNativeUwbManager.persistDataTransfers(TEST_DEFAULT_CHIP_ID)).thenReturn(true);
+        enableUwbWithCountryCodeChangedCallback();
+        verify(mNativeUwbManager).fetchAbortedDataTransfers(TEST_DEFAULT_CHIP_ID);
+
+        mUwbServiceCore.persistDataTransfers();
+        verify(mNativeUwbManager).persistDataTransfers(TEST_DEFAULT_CHIP_ID);
+    }
+
     @Test
     public void testVendorUciNotificationCallback() throws Exception {
         enableUwbWithCountryCodeChangedCallback();
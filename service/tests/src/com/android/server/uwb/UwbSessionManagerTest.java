@@ -67,6 +67,7 @@ import static org.junit.Assert.assertNotNull;
 import static org.junit.Assert.assertNull;
 import static org.junit.Assert.assertThrows;
 import static org.mockito.ArgumentMatchers.any;
+import static org.mockito.ArgumentMatchers.anyBoolean;
 import static org.mockito.ArgumentMatchers.anyByte;
 import static org.mockito.ArgumentMatchers.anyInt;
 import static org.mockito.ArgumentMatchers.anyLong;
@@ -78,6 +79,7 @@ import static org.mockito.Mockito.clearInvocations;
 import static org.mockito.Mockito.doAnswer;
 import static org.mockito.Mockito.doReturn;
 import static org.mockito.Mockito.doThrow;
+import static org.mockito.Mockito.inOrder;
 import static org.mockito.Mockito.mock;
 import static org.mockito.Mockito.never;
 import static org.mockito.Mockito.spy;
@@ -146,6 +148,7 @@ import com.google.uwb.support.fira.FiraProtocolVersion;
 import com.google.uwb.support.fira.FiraRangingReconfigureParams;
 import com.google.uwb.support.fira.FiraSpecificationParams;
 import com.google.uwb.support.generic.GenericSpecificationParams;
+import com.google.uwb.support.oemextension.SessionStatus;
 import com.google.uwb.support.radar.RadarOpenSessionParams;
 import com.google.uwb.support.radar.RadarParams;
 
@@ -154,6 +157,7 @@ import org.junit.Before;
 import org.junit.Test;
 import org.mockito.ArgumentCaptor;
 import org.mockito.Captor;
+import org.mockito.InOrder;
 import org.mockito.Mock;
 import org.mockito.MockitoAnnotations;
 
@@ -197,6 +201,7 @@ public class UwbSessionManagerTest {
             UwbAddress.fromBytes(new byte[] {(byte) 0x07, (byte) 0x08 });
     private static final int TEST_RANGING_INTERVAL_MS = 200;
     private static final short DATA_SEQUENCE_NUM = 0;
+    private static final long DATA_RECEIVED_AT_NANOS = 1_000_000L;
     private static final short DATA_SEQUENCE_NUM_1 = 2;
     private static final int DATA_TRANSMISSION_COUNT = 1;
     private static final int DATA_TRANSMISSION_COUNT_3 = 3;
@@ -396,7 +401,7 @@ public class UwbSessionManagerTest {
                 .when(mUwbSessionManager).getUwbSession(eq(TEST_SESSION_ID));
 
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
     }
@@ -410,7 +415,7 @@ public class UwbSessionManagerTest {
                 .when(mUwbSessionManager).getUwbSession(eq(TEST_SESSION_ID));
 
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_BAD_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_BAD_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession, never()).addReceivedDataInfo(
                 isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics, never()).logDataRx(eq(mockUwbSession),
@@ -426,7 +431,8 @@ public class UwbSessionManagerTest {
                 .when(mUwbSessionManager).getUwbSession(eq(TEST_SESSION_ID));
 
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
     }
@@ -441,7 +447,8 @@ public class UwbSessionManagerTest {
                 .when(mUwbSessionManager).getUwbSession(eq(TEST_SESSION_ID));
 
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
 
         verify(mUwbSessionNotificationManager).onDataReceived(
                 isA(UwbSession.class), eq(PEER_EXTENDED_SHORT_UWB_ADDRESS),
@@ -451,6 +458,28 @@ public class UwbSessionManagerTest {
                 isA(UwbSessionManager.ReceivedDataInfo.class));
     }
 
+    @Test
+    public void onDataReceived_includesReceiveTimestamp() {
+        UwbSession mockUwbSession = mock(UwbSession.class);
+        when(mockUwbSession.getWaitObj()).thenReturn(mock(WaitObj.class));
+        when(mockUwbSession.getRangingRoundUsage()).thenReturn(
+                ROUND_USAGE_DS_TWR_NON_DEFERRED_MODE);
+        doReturn(mockUwbSession)
+                .when(mUwbSessionManager).getUwbSession(eq(TEST_SESSION_ID));
+
+        mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
+
+        ArgumentCaptor<PersistableBundle> parametersCaptor =
+                ArgumentCaptor.forClass(PersistableBundle.class);
+        verify(mUwbSessionNotificationManager).onDataReceived(
+                isA(UwbSession.class), eq(PEER_EXTENDED_SHORT_UWB_ADDRESS),
+                parametersCaptor.capture(), eq(DATA_PAYLOAD));
+        assertThat(parametersCaptor.getValue().getLong(
+                UwbSessionManager.KEY_DATA_RECEIVED_AT_NANOS)).isEqualTo(DATA_RECEIVED_AT_NANOS);
+    }
+
     @Test
     public void onRangeDataNotificationReceivedWithValidUwbSession_twoWay() {
         UwbRangingData uwbRangingData = UwbTestUtils.generateRangingData(
@@ -481,6 +510,19 @@ public class UwbSessionManagerTest {
                 .onRangingResult(any(), eq(uwbRangingData));
     }
 
+    @Test
+    public void onRangeKeepAlive_notReportedToApp() {
+        UwbSession mockUwbSession = mock(UwbSession.class);
+        doReturn(mockUwbSession)
+                .when(mUwbSessionManager).getUwbSession(eq(TEST_SESSION_ID));
+
+        mUwbSessionManager.onRangeKeepAlive(TEST_SESSION_ID, 5);
+        // Unknown sessions are ignored.
+        mUwbSessionManager.onRangeKeepAlive(TEST_SESSION_ID + 1, 6);
+
+        verify(mUwbSessionNotificationManager, never()).onRangingResult(any(), any());
+    }
+
     // Test scenario for receiving Application payload data followed by a RANGE_DATA_NTF with an
     // OWR Aoa Measurement (such that the ExtendedMacAddress format is used for the remote device).
     @Test
@@ -500,7 +542,7 @@ public class UwbSessionManagerTest {
 
         // First call onDataReceived() to get the application payload data.
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
 
@@ -546,7 +588,8 @@ public class UwbSessionManagerTest {
         // First call onDataReceived() to get the application payload data. This should always have
         // the MacAddress (in 8 Bytes), even for a Short MacAddress (MSB are zeroed out).
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
 
@@ -584,14 +627,17 @@ public class UwbSessionManagerTest {
 
         // First call onDataReceived() to get the application payload data.
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM_1, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM_1, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
 
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS_2, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS_2, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM_1, PEER_EXTENDED_MAC_ADDRESS_2, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM_1, PEER_EXTENDED_MAC_ADDRESS_2, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
 
         verify(mockUwbSession, times(4)).addReceivedDataInfo(
                 isA(UwbSessionManager.ReceivedDataInfo.class));
@@ -658,7 +704,8 @@ public class UwbSessionManagerTest {
         // First call onDataReceived() to get the application payload data. This should always have
         // the MacAddress (in 8 Bytes), even for a Short MacAddress (MSB are zeroed out).
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_SHORT_MAC_ADDRESS, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
 
         // Next call onRangeDataNotificationReceived() to process the RANGE_DATA_NTF.
         UwbRangingData uwbRangingData = UwbTestUtils.generateRangingData(
@@ -691,7 +738,7 @@ public class UwbSessionManagerTest {
 
         // First call onDataReceived() to get the application payload data.
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
 
         // Next call onRangeDataNotificationReceived() to process the RANGE_DATA_NTF.
         mUwbSessionManager.onRangeDataNotificationReceived(uwbRangingData);
@@ -712,7 +759,7 @@ public class UwbSessionManagerTest {
 
         // First call onDataReceived() to get the application payload data.
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
 
@@ -737,7 +784,7 @@ public class UwbSessionManagerTest {
 
         // First call onDataReceived() to get the application payload data.
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
 
@@ -766,7 +813,7 @@ public class UwbSessionManagerTest {
 
         // First call onDataReceived() to get the application payload data.
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
 
@@ -823,7 +870,8 @@ public class UwbSessionManagerTest {
         // onDataReceived() called for a different MacAddress, which should be equivalent to it
         // not being called.
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS_2, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS_2, DATA_PAYLOAD,
+                DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
 
@@ -862,7 +910,7 @@ public class UwbSessionManagerTest {
         // onDataReceived() called for a different UwbSessionID, which should be equivalent to it
         // not being called.
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID_2, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession2).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession2), eq(UwbUciConstants.STATUS_CODE_OK));
 
@@ -896,7 +944,7 @@ public class UwbSessionManagerTest {
                 .when(mUwbSessionManager).getUwbSession(eq(TEST_SESSION_ID));
 
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
         verify(mockUwbSession).addReceivedDataInfo(isA(UwbSessionManager.ReceivedDataInfo.class));
         verify(mUwbMetrics).logDataRx(eq(mockUwbSession), eq(UwbUciConstants.STATUS_CODE_OK));
 
@@ -1091,6 +1139,37 @@ public class UwbSessionManagerTest {
         assertThat(mTestLooper.nextMessage().what).isEqualTo(1); // SESSION_OPEN_RANGING
     }
 
+    @Test
+    public void initSession_pinnedFiraVersionNotSupported() throws RemoteException {
+        doReturn(0).when(mUwbSessionManager).getSessionCount();
+        doReturn(0L).when(mUwbSessionManager).getFiraSessionCount();
+        doReturn(false).when(mUwbSessionManager).isExistedSession(anyInt());
+        IUwbRangingCallbacks mockRangingCallbacks = mock(IUwbRangingCallbacks.class);
+        SessionHandle mockSessionHandle = mock(SessionHandle.class);
+        FiraOpenSessionParams mockParams = mock(FiraOpenSessionParams.class);
+        // FIRA_SPECIFICATION_PARAMS only supports the PHY versions 1.0 to 1.1.
+        when(mockParams.getPinnedPhyVersion()).thenReturn(new FiraProtocolVersion(2, 0));
+        IBinder mockBinder = mock(IBinder.class);
+
+        UwbSession uwbSession = spy(
+                mUwbSessionManager.new UwbSession(ATTRIBUTION_SOURCE, mockSessionHandle,
+                        TEST_SESSION_ID, TEST_SESSION_TYPE, FiraParams.PROTOCOL_NAME, mockParams,
+                        mockRangingCallbacks, TEST_CHIP_ID));
+        doReturn(mockBinder).when(uwbSession).getBinder();
+        doReturn(uwbSession).when(mUwbSessionManager).createUwbSession(any(), any(), anyInt(),
+                anyByte(), anyString(), any(), any(), anyString());
+
+        mUwbSessionManager.initSession(ATTRIBUTION_SOURCE, mockSessionHandle, TEST_SESSION_ID,
+                TEST_SESSION_TYPE, FiraParams.PROTOCOL_NAME, mockParams, mockRangingCallbacks,
+                TEST_CHIP_ID);
+
+        verify(mockRangingCallbacks).onRangingOpenFailed(eq(mockSessionHandle),
+                eq(RangingChangeReason.BAD_PARAMETERS), any());
+        verify(mUwbMetrics).logRangingInitEvent(eq(uwbSession),
+                eq(UwbUciConstants.STATUS_CODE_INVALID_PARAM));
+        assertThat(mUwbSessionManager.getUwbSession(TEST_SESSION_ID)).isNull();
+    }
+
     @Test
     public void initSessionMaxSessions_lowestPrioritySessionReplaced() throws RemoteException {
         doReturn(false).when(mUwbInjector).isSystemApp(UID, PACKAGE_NAME);
@@ -2235,6 +2314,39 @@ public class UwbSessionManagerTest {
         assertThat(mTestLooper.isIdle()).isFalse();
     }
 
+    private UwbSession openRangingForNonSystemAppInFgInChain(boolean hasFineLocation)
+            throws Exception {
+        when(mUwbInjector.checkFineLocationPermission(any())).thenReturn(hasFineLocation);
+        when(mNativeUwbManager.initSession(anyInt(), anyByte(), anyString()))
+                .thenReturn((byte) UwbUciConstants.STATUS_CODE_OK);
+        when(mUwbConfigurationManager.setAppConfigurations(anyInt(), any(), anyString(), any()))
+                .thenReturn(UwbUciConstants.STATUS_CODE_OK);
+        UwbSession uwbSession = initUwbSessionForNonSystemAppInFgInChain();
+        doReturn(UwbUciConstants.UWB_SESSION_STATE_INIT,
+                UwbUciConstants.UWB_SESSION_STATE_IDLE).when(uwbSession).getSessionState();
+        mTestLooper.dispatchAll();
+        verify(mUwbSessionNotificationManager).onRangingOpened(eq(uwbSession));
+        return uwbSession;
+    }
+
+    @Test
+    public void testOpenRangingWithNonSystemAppWithoutFineLocation_setsCoarseLocation()
+            throws Exception {
+        openRangingForNonSystemAppInFgInChain(false);
+
+        verify(mNativeUwbManager).setCoarseLocation(TEST_SESSION_ID, true,
+                UwbSessionManager.COARSE_LOCATION_DISTANCE_STEP_CM, TEST_CHIP_ID);
+    }
+
+    @Test
+    public void testOpenRangingWithNonSystemAppWithFineLocation_doesNotSetCoarseLocation()
+            throws Exception {
+        openRangingForNonSystemAppInFgInChain(true);
+
+        verify(mNativeUwbManager, never()).setCoarseLocation(
+                anyInt(), anyBoolean(), anyInt(), anyString());
+    }
+
     @Test
     public void testOpenRangingWithNonSystemAppInFgInChain_MoveToBgAndStayThere() throws Exception {
         UwbSession uwbSession = initUwbSessionForNonSystemAppInFgInChain();
@@ -2394,6 +2506,7 @@ public class UwbSessionManagerTest {
         assertThat(firaParams.getRangeDataNtfConfig()).isEqualTo(
                 FiraParams.RANGE_DATA_NTF_CONFIG_DISABLE);
         verify(mUwbSessionNotificationManager, never()).onRangingReconfigured(eq(uwbSession));
+        verify(mNativeUwbManager).setSessionInfoSuppressed(TEST_SESSION_ID, true, TEST_CHIP_ID);
 
         // Move to foreground.
         mOnUidImportanceListenerArgumentCaptor.getValue().onUidImportance(
@@ -2407,6 +2520,7 @@ public class UwbSessionManagerTest {
         assertThat(firaParams.getRangeDataNtfConfig()).isEqualTo(
                 FiraParams.RANGE_DATA_NTF_CONFIG_ENABLE);
         verify(mUwbSessionNotificationManager, never()).onRangingReconfigured(eq(uwbSession));
+        verify(mNativeUwbManager).setSessionInfoSuppressed(TEST_SESSION_ID, false, TEST_CHIP_ID);
     }
 
     @Test
@@ -3730,6 +3844,46 @@ public class UwbSessionManagerTest {
         verify(mUwbMetrics).logDataTx(eq(uwbSession), eq(UwbUciConstants.STATUS_CODE_FAILED));
     }
 
+    @Test
+    public void onProximityEvent_validSession() throws Exception {
+        UwbSession uwbSession = prepareExistingUwbSession();
+
+        mUwbSessionManager.onProximityEvent(
+                TEST_SESSION_ID, true, 150, PEER_EXTENDED_MAC_ADDRESS);
+        verify(mUwbSessionNotificationManager).onProximityEvent(
+                eq(uwbSession), eq(150), eq(PEER_EXTENDED_MAC_ADDRESS));
+
+        mUwbSessionManager.onProximityEvent(
+                TEST_SESSION_ID + 1, false, 250, PEER_EXTENDED_MAC_ADDRESS);
+        verify(mUwbSessionNotificationManager, never()).onProximityEvent(
+                any(), eq(250), any());
+    }
+
+    @Test
+    public void onRangingIntervalSuggestion_validSession() throws Exception {
+        UwbSession uwbSession = prepareExistingUwbSession();
+        assertThat(uwbSession.getSuggestedRangingDurationMs()).isEqualTo(-1);
+
+        mUwbSessionManager.onRangingIntervalSuggestion(TEST_SESSION_ID, 400, 1);
+        assertThat(uwbSession.getSuggestedRangingDurationMs()).isEqualTo(400);
+        assertThat(uwbSession.getSuggestedBlockStride()).isEqualTo(1);
+
+        // Unknown sessions are ignored.
+        mUwbSessionManager.onRangingIntervalSuggestion(TEST_SESSION_ID + 1, 800, 2);
+        assertThat(uwbSession.getSuggestedRangingDurationMs()).isEqualTo(400);
+    }
+
+    @Test
+    public void onSessionStateTransitionViolation_recordedForDump() throws Exception {
+        mUwbSessionManager.onSessionStateTransitionViolation(TEST_SESSION_ID,
+                UwbUciConstants.UWB_SESSION_STATE_ACTIVE, UwbUciConstants.UWB_SESSION_STATE_INIT,
+                UwbUciConstants.REASON_STATE_CHANGE_WITH_SESSION_MANAGEMENT_COMMANDS, 1);
+
+        assertThat(mUwbSessionManager.mDbgSessionTransitionViolations.getEntries())
+                .containsExactly("sessionId: " + TEST_SESSION_ID + ", previousState: 2"
+                        + ", sessionState: 0, reasonCode: 0, violation: 1");
+    }
+
     @Test
     public void onDataSendStatus_sessionNotFound() throws Exception {
         // Setup a uwbSession UwbSession to start ranging (and move it to active state).
@@ -3991,6 +4145,93 @@ public class UwbSessionManagerTest {
                 .onRangingStoppedWithApiReasonCode(eq(uwbSession),
                         eq(RangingChangeReason.LOCAL_API), any());
         verify(mUwbMetrics).longRangingStopEvent(eq(uwbSession));
+        assertThat(uwbSession.getHostStopReason())
+                .isEqualTo(SessionStatus.HOST_STOP_REASON_LOCAL_API);
+    }
+
+    @Test
+    public void execStopRanging_forSystemPolicy() throws Exception {
+        UwbSession uwbSession = prepareExistingUwbSession();
+        doReturn(UwbUciConstants.UWB_SESSION_STATE_ACTIVE, UwbUciConstants.UWB_SESSION_STATE_IDLE)
+                .when(uwbSession).getSessionState();
+        when(mNativeUwbManager.stopRanging(eq(TEST_SESSION_ID), anyString()))
+                .thenReturn((byte) UwbUciConstants.STATUS_CODE_OK);
+
+        mUwbSessionManager.stopRangingForSystemPolicy(uwbSession.getSessionHandle(),
+                SessionStatus.HOST_STOP_REASON_THERMAL);
+        mTestLooper.dispatchNext();
+
+        verify(mUwbSessionNotificationManager)
+                .onRangingStoppedWithApiReasonCode(eq(uwbSession),
+                        eq(RangingChangeReason.SYSTEM_POLICY), any());
+        assertThat(uwbSession.getHostStopReason())
+                .isEqualTo(SessionStatus.HOST_STOP_REASON_THERMAL);
+    }
+
+    @Test
+    public void onRegulatoryChannelsChanged_suspendsSessionsOnDisallowedChannel()
+            throws Exception {
+        // The FiRa session ranges on the default channel 9.
+        UwbSession uwbSession = prepareExistingUwbSession();
+        doReturn(UwbUciConstants.UWB_SESSION_STATE_ACTIVE).when(uwbSession).getSessionState();
+
+        RegulatoryImpact regulatoryImpact = mUwbSessionManager.onRegulatoryChannelsChanged(
+                TEST_CHIP_ID, "US", List.of(5, 9), true);
+        assertThat(regulatoryImpact.getAffectedSessionIds()).isEmpty();
+        assertThat(regulatoryImpact.isSuspended()).isFalse();
+
+        doReturn(UwbUciConstants.UWB_SESSION_STATE_ACTIVE, UwbUciConstants.UWB_SESSION_STATE_ACTIVE,
+                UwbUciConstants.UWB_SESSION_STATE_IDLE).when(uwbSession).getSessionState();
+        when(mNativeUwbManager.stopRanging(eq(TEST_SESSION_ID), anyString()))
+                .thenReturn((byte) UwbUciConstants.STATUS_CODE_OK);
+        regulatoryImpact = mUwbSessionManager.onRegulatoryChannelsChanged(
+                TEST_CHIP_ID, "JP", List.of(5), true);
+        mTestLooper.dispatchNext();
+
+        assertThat(regulatoryImpact.getAffectedSessionIds()).containsExactly(TEST_SESSION_ID);
+        assertThat(regulatoryImpact.isSuspended()).isTrue();
+        verify(mUwbSessionNotificationManager)
+                .onRangingStoppedWithApiReasonCode(eq(uwbSession),
+                        eq(RangingChangeReason.SYSTEM_REGULATION), any());
+        assertThat(uwbSession.getHostStopReason())
+                .isEqualTo(SessionStatus.HOST_STOP_REASON_REGULATORY);
+    }
+
+    @Test
+    public void execStopRanging_pendingDataSendCancelled() throws Exception {
+        UwbAddress uwbAddress = UwbAddress.fromBytes(PEER_EXTENDED_MAC_ADDRESS);
+        UwbSession uwbSession = prepareExistingUwbSession();
+        doReturn(UwbUciConstants.UWB_SESSION_STATE_ACTIVE).when(uwbSession).getSessionState();
+        when(mNativeUwbManager.sendData(eq(TEST_SESSION_ID), eq(PEER_EXTENDED_MAC_ADDRESS),
+                eq(DATA_SEQUENCE_NUM), eq(DATA_PAYLOAD), eq(TEST_CHIP_ID)))
+                .thenReturn((byte) UwbUciConstants.STATUS_CODE_OK);
+
+        // Send data, but stop the session before any DataTransferStatusNtf is received.
+        mUwbSessionManager.sendData(
+                uwbSession.getSessionHandle(), uwbAddress, PERSISTABLE_BUNDLE, DATA_PAYLOAD);
+        mTestLooper.dispatchNext();
+        assertThat(uwbSession.getSendDataInfo(DATA_SEQUENCE_NUM)).isNotNull();
+
+        doReturn(UwbUciConstants.UWB_SESSION_STATE_ACTIVE, UwbUciConstants.UWB_SESSION_STATE_IDLE)
+                .when(uwbSession).getSessionState();
+        when(mNativeUwbManager.stopRanging(eq(TEST_SESSION_ID), anyString()))
+                .thenReturn((byte) UwbUciConstants.STATUS_CODE_OK);
+        mUwbSessionManager.stopRanging(uwbSession.getSessionHandle());
+        mTestLooper.dispatchNext();
+
+        InOrder inOrder = inOrder(mUwbSessionNotificationManager);
+        inOrder.verify(mUwbSessionNotificationManager).onDataSendFailed(
+                eq(uwbSession), eq(uwbAddress),
+                eq(UwbUciConstants.STATUS_CODE_DATA_TRANSFER_CANCELLED), eq(PERSISTABLE_BUNDLE));
+        inOrder.verify(mUwbSessionNotificationManager).onRangingStoppedWithApiReasonCode(
+                eq(uwbSession), eq(RangingChangeReason.LOCAL_API), any());
+        assertThat(uwbSession.getSendDataInfo(DATA_SEQUENCE_NUM)).isNull();
+
+        // A late DataTransferStatusNtf is not reported again.
+        mUwbSessionManager.onDataSendStatus(
+                uwbSession.getSessionId(), UwbUciConstants.STATUS_CODE_DATA_TRANSFER_OK,
+                DATA_SEQUENCE_NUM, DATA_TRANSMISSION_COUNT);
+        verify(mUwbSessionNotificationManager, never()).onDataSent(any(), any(), any());
     }
 
     @Test
@@ -5427,6 +5668,37 @@ public class UwbSessionManagerTest {
         assertThat(mUwbSessionManager.getFiraSessionCount()).isEqualTo(0L);
     }
 
+    @Test
+    public void deinitAllSession_ofChip() {
+        UwbSession mockUwbSession1 = mock(UwbSession.class);
+        SessionHandle mockSessionHandle1 = mock(SessionHandle.class);
+        when(mockUwbSession1.getBinder()).thenReturn(mock(IBinder.class));
+        when(mockUwbSession1.getSessionId()).thenReturn(TEST_SESSION_ID);
+        when(mockUwbSession1.getProtocolName()).thenReturn(FiraParams.PROTOCOL_NAME);
+        when(mockUwbSession1.getSessionHandle()).thenReturn(mockSessionHandle1);
+        when(mockUwbSession1.getChipId()).thenReturn("chip1");
+        when(mockUwbSession1.getSessionState())
+                .thenReturn(UwbUciConstants.UWB_SESSION_STATE_ACTIVE);
+        mUwbSessionManager.mSessionTable.put(mockSessionHandle1, mockUwbSession1);
+
+        UwbSession mockUwbSession2 = mock(UwbSession.class);
+        SessionHandle mockSessionHandle2 = mock(SessionHandle.class);
+        when(mockUwbSession2.getBinder()).thenReturn(mock(IBinder.class));
+        when(mockUwbSession2.getSessionId()).thenReturn(TEST_SESSION_ID + 100);
+        when(mockUwbSession2.getProtocolName()).thenReturn(FiraParams.PROTOCOL_NAME);
+        when(mockUwbSession2.getSessionHandle()).thenReturn(mockSessionHandle2);
+        when(mockUwbSession2.getChipId()).thenReturn("chip2");
+        mUwbSessionManager.mSessionTable.put(mockSessionHandle2, mockUwbSession2);
+
+        mUwbSessionManager.deinitAllSession("chip1");
+
+        verify(mNativeUwbManager).stopRanging(TEST_SESSION_ID, "chip1");
+        verify(mNativeUwbManager).deInitSession(TEST_SESSION_ID, "chip1");
+        verify(mNativeUwbManager, never()).deInitSession(eq(TEST_SESSION_ID + 100), anyString());
+        verify(mUwbSessionManager).removeSession(mockUwbSession1);
+        assertThat(mUwbSessionManager.getSessionCount()).isEqualTo(1);
+    }
+
     @Test
     public void onSessionStatusNotification_session_deinit() throws Exception {
         UwbSession uwbSession = prepareExistingUwbSession();
@@ -5484,7 +5756,7 @@ public class UwbSessionManagerTest {
         when(mDeviceConfigFacade.getRxDataMaxPacketsToStore())
                 .thenReturn(MAX_RX_DATA_PACKETS_TO_STORE);
         mUwbSessionManager.onDataReceived(TEST_SESSION_ID, UwbUciConstants.STATUS_CODE_OK,
-                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD);
+                DATA_SEQUENCE_NUM, PEER_EXTENDED_MAC_ADDRESS, DATA_PAYLOAD, DATA_RECEIVED_AT_NANOS);
 
         // Next call onRangeDataNotificationReceived() to process the RANGE_DATA_NTF. Setup
         // isPointedTarget() to return "false", as in that scenario the stored AdvertiseTarget
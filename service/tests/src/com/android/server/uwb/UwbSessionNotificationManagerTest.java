@@ -52,6 +52,7 @@ import android.util.Pair;
 import android.uwb.IUwbOemExtensionCallback;
 import android.uwb.IUwbRangingCallbacks;
 import android.uwb.RangingChangeReason;
+import android.uwb.RangingMeasurement;
 import android.uwb.RangingReport;
 import android.uwb.SessionHandle;
 import android.uwb.UwbAddress;
@@ -80,6 +81,7 @@ import org.mockito.ArgumentCaptor;
 import org.mockito.Mock;
 import org.mockito.MockitoAnnotations;
 
+import java.util.List;
 import java.util.Set;
 
 /**
@@ -165,6 +167,30 @@ public class UwbSessionNotificationManagerTest {
         verify(mUwbMetrics, never()).logRangingResult(anyInt(), any(), any());
     }
 
+    @Test
+    public void testOnProximityEvent() throws Exception {
+        mUwbSessionNotificationManager.onProximityEvent(mUwbSession, 150, PEER_SHORT_MAC_ADDRESS);
+
+        ArgumentCaptor<RangingReport> reportCaptor = ArgumentCaptor.forClass(RangingReport.class);
+        verify(mIUwbRangingCallbacks).onRangingResult(eq(mSessionHandle), reportCaptor.capture());
+        List<RangingMeasurement> measurements = reportCaptor.getValue().getMeasurements();
+        assertThat(measurements).hasSize(1);
+        assertThat(measurements.get(0).getStatus())
+                .isEqualTo(RangingMeasurement.RANGING_STATUS_SUCCESS);
+        assertThat(measurements.get(0).getDistanceMeasurement().getMeters()).isEqualTo(1.5);
+        assertThat(measurements.get(0).getAngleOfArrivalMeasurement()).isNull();
+    }
+
+    @Test
+    public void testOnProximityEventWithoutUwbRangingPermission() throws Exception {
+        when(mUwbInjector.checkUwbRangingPermissionForStartDataDelivery(eq(ATTRIBUTION_SOURCE),
+                any())).thenReturn(false);
+        when(mUwbSession.isDataDeliveryPermissionCheckNeeded()).thenReturn(true);
+        mUwbSessionNotificationManager.onProximityEvent(mUwbSession, 150, PEER_SHORT_MAC_ADDRESS);
+
+        verify(mIUwbRangingCallbacks, never()).onRangingResult(any(), any());
+    }
+
     @Test
     public void testOnRangingResult_forTwoWay_WithAoa() throws Exception {
         Pair<UwbRangingData, RangingReport> testRangingDataAndRangingReport =
@@ -776,4 +802,21 @@ public class UwbSessionNotificationManagerTest {
                         == testUwbRadarDataAndRadarData.second.getSweepOffset()),
                 eq(new byte[] {}));
     }
+
+    @Test
+    public void testonRadarDataMessageReceived_withDroppedSweeps() throws Exception {
+        Pair<UwbRadarData, RadarData> testUwbRadarDataAndRadarData =
+                UwbTestUtils.generateUwbRadarDataAndRadarData(
+                        RADAR_DATA_TYPE_RADAR_SWEEP_SAMPLES);
+        testUwbRadarDataAndRadarData.first.droppedSweepCount = 7;
+        mUwbSessionNotificationManager.onRadarDataMessageReceived(
+                mUwbRadarSession, testUwbRadarDataAndRadarData.first);
+
+        verify(mIUwbRangingCallbacks).onDataReceived(
+                eq(mSessionHandle),
+                eq(UwbAddress.fromBytes(new byte[] {0x00, 0x00})),
+                argThat(p -> p.getInt(
+                        UwbSessionNotificationManager.KEY_RADAR_DROPPED_SWEEP_COUNT) == 7),
+                eq(new byte[] {}));
+    }
 }
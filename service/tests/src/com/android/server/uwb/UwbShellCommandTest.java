@@ -26,7 +26,9 @@ import static com.google.common.truth.Truth.assertThat;
 import static com.google.uwb.support.fira.FiraParams.RangeDataNtfConfigCapabilityFlag.HAS_RANGE_DATA_NTF_CONFIG_DISABLE;
 import static com.google.uwb.support.fira.FiraParams.RangeDataNtfConfigCapabilityFlag.HAS_RANGE_DATA_NTF_CONFIG_ENABLE;
 
+import static org.mockito.ArgumentMatchers.aryEq;
 import static org.mockito.ArgumentMatchers.anyInt;
+import static org.mockito.ArgumentMatchers.anyString;
 import static org.mockito.Mockito.any;
 import static org.mockito.Mockito.clearInvocations;
 import static org.mockito.Mockito.doAnswer;
@@ -55,6 +57,9 @@ import android.uwb.UwbTestUtils;
 import androidx.test.filters.SmallTest;
 import androidx.test.runner.AndroidJUnit4;
 
+import com.android.server.uwb.data.UwbVendorUciResponse;
+import com.android.server.uwb.jni.NativeUwbManager;
+
 import com.google.uwb.support.base.Params;
 import com.google.uwb.support.ccc.CccOpenRangingParams;
 import com.google.uwb.support.ccc.CccSpecificationParams;
@@ -90,6 +95,7 @@ public class UwbShellCommandTest {
     @Mock UwbCountryCode mUwbCountryCode;
     @Mock Context mContext;
     @Mock UwbServiceCore mUwbServiceCore;
+    @Mock NativeUwbManager mNativeUwbManager;
 
     UwbShellCommand mUwbShellCommand;
 
@@ -99,6 +105,7 @@ public class UwbShellCommandTest {
 
         when(mUwbInjector.getUwbCountryCode()).thenReturn(mUwbCountryCode);
         when(mUwbInjector.getUwbServiceCore()).thenReturn(mUwbServiceCore);
+        when(mUwbInjector.getNativeUwbManager()).thenReturn(mNativeUwbManager);
         doAnswer(invocation -> {
             FutureTask t = invocation.getArgument(0);
             t.run();
@@ -178,6 +185,64 @@ public class UwbShellCommandTest {
         verify(mUwbCountryCode).clearOverrideCountryCode();
     }
 
+    @Test
+    public void testSetUciCapture() throws Exception {
+        when(mUwbService.getDefaultChipId()).thenReturn("chip0");
+        when(mNativeUwbManager.setUciCapture(anyInt(), any(), any(), anyString()))
+                .thenReturn(true);
+
+        // not allowed for unrooted shell.
+        mUwbShellCommand.exec(
+                new Binder(), new FileDescriptor(), new FileDescriptor(), new FileDescriptor(),
+                new String[]{"set-uci-capture", "filtered", "-s", "1", "-g", "2"});
+        verify(mNativeUwbManager, never()).setUciCapture(anyInt(), any(), any(), anyString());
+
+        BinderUtil.setUid(Process.ROOT_UID);
+
+        // rooted shell.
+        mUwbShellCommand.exec(
+                new Binder(), new FileDescriptor(), new FileDescriptor(), new FileDescriptor(),
+                new String[]{"set-uci-capture", "filtered", "-s", "1", "-s", "4294967295",
+                        "-g", "2"});
+        verify(mNativeUwbManager).setUciCapture(
+                eq(NativeUwbManager.UCI_CAPTURE_MODE_FILTERED), eq(new int[]{1, -1}),
+                eq(new int[]{2}), eq("chip0"));
+
+        mUwbShellCommand.exec(
+                new Binder(), new FileDescriptor(), new FileDescriptor(), new FileDescriptor(),
+                new String[]{"set-uci-capture", "disabled"});
+        verify(mNativeUwbManager).setUciCapture(
+                eq(NativeUwbManager.UCI_CAPTURE_MODE_DISABLED), eq(new int[0]), eq(new int[0]),
+                eq("chip0"));
+    }
+
+    @Test
+    public void testStopUciRecording() throws Exception {
+        when(mUwbService.getDefaultChipId()).thenReturn("chip0");
+        when(mNativeUwbManager.stopUciRecording(anyString()))
+                .thenReturn("/data/misc/apexdata/com.android.uwb/log/uwb_uci_chip0.recording");
+
+        // not allowed for unrooted shell.
+        mUwbShellCommand.exec(
+                new Binder(), new FileDescriptor(), new FileDescriptor(), new FileDescriptor(),
+                new String[]{"stop-uci-recording"});
+        verify(mNativeUwbManager, never()).stopUciRecording(anyString());
+
+        BinderUtil.setUid(Process.ROOT_UID);
+
+        // rooted shell.
+        assertThat(mUwbShellCommand.exec(
+                new Binder(), new FileDescriptor(), new FileDescriptor(), new FileDescriptor(),
+                new String[]{"stop-uci-recording", "-c", "chip1"})).isEqualTo(0);
+        verify(mNativeUwbManager).stopUciRecording("chip1");
+
+        when(mNativeUwbManager.stopUciRecording(anyString())).thenReturn(null);
+        assertThat(mUwbShellCommand.exec(
+                new Binder(), new FileDescriptor(), new FileDescriptor(), new FileDescriptor(),
+                new String[]{"stop-uci-recording"})).isEqualTo(-1);
+        verify(mNativeUwbManager).stopUciRecording("chip0");
+    }
+
     @Test
     public void testGetCountryCode() throws Exception {
         mUwbShellCommand.exec(
@@ -436,6 +501,30 @@ public class UwbShellCommandTest {
                 openSessionParams);
     }
 
+    @Test
+    public void testInjectUciMessage() throws Exception {
+        when(mUwbService.getDefaultChipId()).thenReturn("chip0");
+        when(mUwbServiceCore.injectUciMessage(anyInt(), anyInt(), anyInt(), any(), anyString()))
+                .thenReturn(new UwbVendorUciResponse((byte) 0, 0xE, 0x1, new byte[] {0x0a}));
+
+        // not allowed for unrooted shell.
+        mUwbShellCommand.exec(
+                new Binder(), new FileDescriptor(), new FileDescriptor(), new FileDescriptor(),
+                new String[]{"inject-uci-message", "1", "e", "1", "0102"});
+        verify(mUwbServiceCore, never())
+                .injectUciMessage(anyInt(), anyInt(), anyInt(), any(), anyString());
+
+        BinderUtil.setUid(Process.ROOT_UID);
+
+        // rooted shell.
+        assertThat(mUwbShellCommand.exec(
+                new Binder(), new FileDescriptor(), new FileDescriptor(), new FileDescriptor(),
+                new String[]{"inject-uci-message", "1", "e", "1", "0102", "-c", "chip1"}))
+                .isEqualTo(0);
+        verify(mUwbServiceCore).injectUciMessage(
+                eq(1), eq(0xE), eq(0x1), aryEq(new byte[] {0x01, 0x02}), eq("chip1"));
+    }
+
     @Test
     public void testStopRadarSession() throws Exception {
         RadarOpenSessionParams openSessionParams = DEFAULT_RADAR_OPEN_SESSION_PARAMS.build();
@@ -76,6 +76,7 @@ public class UwbRangingDataTest {
     private static final int TEST_AOA_DEST_ELEVATION_FOM = 90;
     private static final int TEST_SLOT_IDX = 10;
     private static final int TEST_RSSI = 127;
+    private static final int TEST_ANTENNA_PAIR_ID = 2;
     private static final long TEST_TIMESTAMP = 500_000L;
     private static final int TEST_ANCHOR_CFO = 100;
     private static final int TEST_CFO = 200;
@@ -100,7 +101,8 @@ public class UwbRangingDataTest {
                 TEST_AOA_AZIMUTH_FOM, convertFloatToQFormat(TEST_AOA_ELEVATION, 9, 7),
                 TEST_AOA_ELEVATION_FOM, convertFloatToQFormat(TEST_AOA_DEST_AZIMUTH, 9, 7),
                 TEST_AOA_DEST_AZIMUTH_FOM, convertFloatToQFormat(TEST_AOA_DEST_ELEVATION, 9, 7),
-                TEST_AOA_DEST_ELEVATION_FOM, TEST_SLOT_IDX, TEST_RSSI);
+                TEST_AOA_DEST_ELEVATION_FOM, TEST_SLOT_IDX, -TEST_RSSI / 2f, TEST_RSSI,
+                TEST_ANTENNA_PAIR_ID);
         mUwbRangingData = new UwbRangingData(TEST_SEQ_COUNTER, TEST_SESSION_ID,
                 TEST_RCR_INDICATION, TEST_CURR_RANGING_INTERVAL, rangingMeasuresType,
                 TEST_MAC_ADDRESS_MODE, noOfRangingMeasures, uwbTwoWayMeasurements,
@@ -114,6 +116,14 @@ public class UwbRangingDataTest {
         assertThat(mUwbRangingData.getMacAddressMode()).isEqualTo(TEST_MAC_ADDRESS_MODE);
         assertThat(mUwbRangingData.getNoOfRangingMeasures()).isEqualTo(1);
         assertThat(mUwbRangingData.getRawNtfData()).isEqualTo(TEST_RAW_NTF_DATA);
+        assertThat(mUwbRangingData.getRangingTwoWayMeasures()[0].getRssi())
+                .isEqualTo(-(TEST_RSSI / 2));
+        assertThat(mUwbRangingData.getRangingTwoWayMeasures()[0].getRssiDbm())
+                .isEqualTo(-63.5f);
+        assertThat(mUwbRangingData.getRangingTwoWayMeasures()[0].getRssiRaw())
+                .isEqualTo(TEST_RSSI);
+        assertThat(mUwbRangingData.getRangingTwoWayMeasures()[0].getAntennaPairId())
+                .isEqualTo(TEST_ANTENNA_PAIR_ID);
 
         final String testString = "UwbRangingData { "
                 + " SeqCounter = " + TEST_SEQ_COUNTER
@@ -160,11 +170,36 @@ public class UwbRangingDataTest {
                 + ", RangingMeasuresType = " + rangingMeasuresType
                 + ", MacAddressMode = " + TEST_MAC_ADDRESS_MODE
                 + ", NoOfRangingMeasures = " + noOfRangingMeasures
-                + ", RangingOwrAoaMeasure = " + uwbOwrAoaMeasurement.toString()
+                + ", RangingOwrAoaMeasures = " + Arrays.toString(
+                        new UwbOwrAoaMeasurement[] {uwbOwrAoaMeasurement})
                 + ", RawNotificationData = " + Arrays.toString(TEST_RAW_NTF_DATA)
                 + '}';
 
         assertThat(mUwbRangingData.toString()).isEqualTo(testString);
+        assertThat(mUwbRangingData.getRangingOwrAoaMeasure()).isEqualTo(uwbOwrAoaMeasurement);
+        assertThat(mUwbRangingData.getRangingOwrAoaMeasures())
+                .asList().containsExactly(uwbOwrAoaMeasurement);
+    }
+
+    @Test
+    public void testInitializeUwbRangingData_withMultipleUwbOwrAoaMeasurements() throws Exception {
+        final int noOfRangingMeasures = 2;
+        final UwbOwrAoaMeasurement[] uwbOwrAoaMeasurements =
+                new UwbOwrAoaMeasurement[noOfRangingMeasures];
+        for (int i = 0; i < noOfRangingMeasures; i++) {
+            uwbOwrAoaMeasurements[i] = new UwbOwrAoaMeasurement(TEST_MAC_ADDRESS,
+                    TEST_STATUS, TEST_LOS, TEST_FRAME_SEQ_NUMBER + i, TEST_BLOCK_INDEX,
+                    TEST_AOA_AZIMUTH_Q97_FORMAT, TEST_AOA_AZIMUTH_FOM,
+                    TEST_AOA_ELEVATION_Q97_FORMAT, TEST_AOA_ELEVATION_FOM);
+        }
+        mUwbRangingData = new UwbRangingData(TEST_SEQ_COUNTER, TEST_SESSION_ID,
+                TEST_RCR_INDICATION, TEST_CURR_RANGING_INTERVAL, RANGING_MEASUREMENT_TYPE_OWR_AOA,
+                TEST_MAC_ADDRESS_MODE, noOfRangingMeasures, uwbOwrAoaMeasurements,
+                TEST_RAW_NTF_DATA);
+
+        assertThat(mUwbRangingData.getNoOfRangingMeasures()).isEqualTo(noOfRangingMeasures);
+        assertThat(mUwbRangingData.getRangingOwrAoaMeasures()).isEqualTo(uwbOwrAoaMeasurements);
+        assertThat(mUwbRangingData.getRangingOwrAoaMeasure()).isEqualTo(uwbOwrAoaMeasurements[0]);
     }
 
 
@@ -211,5 +246,28 @@ public class UwbRangingDataTest {
                 + '}';
 
         assertThat(mUwbRangingData.toString()).isEqualTo(testString);
+        assertThat(uwbDlTDoAMeasurements[0].getDecodedAnchorLocation()).isNull();
+    }
+
+    @Test
+    public void testUwbDlTDoAMeasurement_withDecodedAnchorLocation() throws Exception {
+        final UwbDlTDoAMeasurement uwbDlTDoAMeasurement = new UwbDlTDoAMeasurement(
+                TEST_MAC_ADDRESS, TEST_STATUS, TEST_MESSAGE_TYPE, TEST_MESSAGE_CONTROL,
+                TEST_BLOCK_INDEX, TEST_ROUND_INDEX, TEST_LOS, TEST_AOA_AZIMUTH_Q97_FORMAT,
+                TEST_AOA_AZIMUTH_FOM, TEST_AOA_ELEVATION_Q97_FORMAT, TEST_AOA_ELEVATION_FOM,
+                TEST_RSSI, TEST_TIMESTAMP, TEST_TIMESTAMP, TEST_ANCHOR_CFO, TEST_CFO,
+                TEST_INTIATOR_REPLY_TIME, TEST_RESPONDER_REPLY_TIME, TEST_INITIATOR_RESPONDER_TOF,
+                TEST_ANCHOR_LOCATION, TEST_ACTIVE_RANGING_ROUNDS,
+                UwbDlTDoAAnchorLocation.TYPE_WGS84, 48.5, -2.0, 35.0);
+
+        final UwbDlTDoAAnchorLocation location =
+                uwbDlTDoAMeasurement.getDecodedAnchorLocation();
+        assertThat(uwbDlTDoAMeasurement.getAnchorLocation()).isEqualTo(TEST_ANCHOR_LOCATION);
+        assertThat(location.getType()).isEqualTo(UwbDlTDoAAnchorLocation.TYPE_WGS84);
+        assertThat(location.getLatitude()).isEqualTo(48.5);
+        assertThat(location.getLongitude()).isEqualTo(-2.0);
+        assertThat(location.getAltitude()).isEqualTo(35.0);
+        assertThat(location.toString()).isEqualTo(
+                "UwbDlTDoAAnchorLocation{Latitude=48.5, Longitude=-2.0, Altitude=35.0}");
     }
 }
@@ -385,6 +385,27 @@ public class FiraEncoderTest {
         }
     }
 
+    @Test
+    public void testFiraOpenSessionParams_withVendorAppConfigs() throws Exception {
+        VendorAppConfigRegistry registry = new VendorAppConfigRegistry();
+        registry.register(0xF0, params -> new byte[] {0x01}, value -> true);
+        registry.register("chip1", 0xF1, params -> new byte[] {0x02, 0x03}, value -> true);
+        when(mUwbInjector.getVendorAppConfigRegistry()).thenReturn(registry);
+
+        FiraOpenSessionParams params = TEST_FIRA_OPEN_SESSION_PARAMS_V_1_1.build();
+        TlvBuffer tlvs = mFiraEncoder.getTlvBuffer(params, PROTOCOL_VERSION_1_1, "chip0");
+
+        assertThat(tlvs.getNoOfParams()).isEqualTo(47);
+        assertThat(tlvs.getByteArray()).isEqualTo(UwbUtil.getByteArray(
+                UwbUtil.toHexString(mFiraSessionv11TlvData) + "F00101"));
+
+        tlvs = mFiraEncoder.getTlvBuffer(params, PROTOCOL_VERSION_1_1, "chip1");
+
+        assertThat(tlvs.getNoOfParams()).isEqualTo(48);
+        assertThat(tlvs.getByteArray()).isEqualTo(UwbUtil.getByteArray(
+                UwbUtil.toHexString(mFiraSessionv11TlvData) + "F00101" + "F1020203"));
+    }
+
     @Test
     public void testFiraRangingReconfigureParams() throws Exception {
         FiraRangingReconfigureParams params = TEST_FIRA_RECONFIGURE_PARAMS.build();
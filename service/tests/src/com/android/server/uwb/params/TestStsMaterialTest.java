@@ -0,0 +1,54 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+package com.android.server.uwb.params;
+
+import static com.google.common.truth.Truth.assertThat;
+
+import android.platform.test.annotations.Presubmit;
+
+import androidx.test.filters.SmallTest;
+import androidx.test.runner.AndroidJUnit4;
+
+import org.junit.Test;
+import org.junit.runner.RunWith;
+
+/**
+ * Unit tests for {@link com.android.server.uwb.params.TestStsMaterial}.
+ */
+@RunWith(AndroidJUnit4.class)
+@SmallTest
+@Presubmit
+public class TestStsMaterialTest {
+    @Test
+    public void testSameSeedGivesSameMaterial() {
+        TestStsMaterial first = new TestStsMaterial(42);
+        TestStsMaterial second = new TestStsMaterial(42);
+
+        assertThat(first.getVendorId()).hasLength(TestStsMaterial.VENDOR_ID_LENGTH);
+        assertThat(first.getStaticStsIV()).hasLength(TestStsMaterial.STATIC_STS_IV_LENGTH);
+        assertThat(second.getVendorId()).isEqualTo(first.getVendorId());
+        assertThat(second.getStaticStsIV()).isEqualTo(first.getStaticStsIV());
+    }
+
+    @Test
+    public void testDifferentSeedsGiveDifferentMaterial() {
+        TestStsMaterial first = new TestStsMaterial(42);
+        TestStsMaterial second = new TestStsMaterial(43);
+
+        assertThat(second.getStaticStsIV()).isNotEqualTo(first.getStaticStsIV());
+    }
+}
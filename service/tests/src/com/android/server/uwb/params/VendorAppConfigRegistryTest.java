@@ -0,0 +1,112 @@
+/*
+ * Copyright (C) 2024 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+package com.android.server.uwb.params;
+
+import static com.google.common.truth.Truth.assertThat;
+
+import static org.junit.Assert.assertThrows;
+import static org.mockito.Mockito.mock;
+
+import android.platform.test.annotations.Presubmit;
+
+import androidx.test.filters.SmallTest;
+import androidx.test.runner.AndroidJUnit4;
+
+import com.android.server.uwb.config.ConfigParam;
+import com.android.server.uwb.util.UwbUtil;
+
+import com.google.uwb.support.fira.FiraOpenSessionParams;
+
+import org.junit.Before;
+import org.junit.Test;
+import org.junit.runner.RunWith;
+
+/**
+ * Unit tests for {@link com.android.server.uwb.params.VendorAppConfigRegistry}.
+ */
+@RunWith(AndroidJUnit4.class)
+@SmallTest
+@Presubmit
+public class VendorAppConfigRegistryTest {
+    private static final VendorAppConfigRegistry.Encoder ENCODER = params -> new byte[] {0x01};
+    private static final VendorAppConfigRegistry.Validator VALIDATOR = value -> true;
+
+    private VendorAppConfigRegistry mRegistry;
+    private FiraOpenSessionParams mParams;
+
+    @Before
+    public void setUp() {
+        mRegistry = new VendorAppConfigRegistry();
+        mParams = mock(FiraOpenSessionParams.class);
+    }
+
+    private String appendTlvs(String chipId) {
+        TlvBuffer.Builder builder = new TlvBuffer.Builder();
+        mRegistry.appendTlvs(builder, mParams, chipId);
+        return UwbUtil.toHexString(builder.build().getByteArray());
+    }
+
+    @Test
+    public void testRejectsSpecDefinedIds() {
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register(ConfigParam.DEVICE_TYPE, ENCODER, VALIDATOR));
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register(ConfigParam.SESSION_KEY, ENCODER, VALIDATOR));
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register("chip0", 0x100, ENCODER, VALIDATOR));
+        // The vendor IDs set by the UWB service itself.
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register(ConfigParam.ANTENNA_MODE, ENCODER, VALIDATOR));
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register(ConfigParam.NUM_RANGE_MEASUREMENTS, ENCODER, VALIDATOR));
+    }
+
+    @Test
+    public void testRejectsDuplicateIds() {
+        mRegistry.register(0xF0, ENCODER, VALIDATOR);
+        mRegistry.register("chip0", 0xF1, ENCODER, VALIDATOR);
+        // The same ID can be registered for several chips.
+        mRegistry.register("chip1", 0xF1, ENCODER, VALIDATOR);
+
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register(0xF0, ENCODER, VALIDATOR));
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register("chip0", 0xF0, ENCODER, VALIDATOR));
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register("chip0", 0xF1, ENCODER, VALIDATOR));
+        assertThrows(IllegalArgumentException.class,
+                () -> mRegistry.register(0xF1, ENCODER, VALIDATOR));
+    }
+
+    @Test
+    public void testAppendTlvsPerChip() {
+        mRegistry.register(0xF2, params -> new byte[] {0x02}, VALIDATOR);
+        mRegistry.register("chip0", 0xF1, params -> new byte[] {0x03, 0x04}, VALIDATOR);
+
+        assertThat(appendTlvs("chip0")).isEqualTo("F1020304" + "F20102");
+        assertThat(appendTlvs("chip1")).isEqualTo("F20102");
+    }
+
+    @Test
+    public void testAppendTlvsSkipsUnsetAndInvalidValues() {
+        mRegistry.register(0xF0, params -> null, VALIDATOR);
+        mRegistry.register(0xF1, params -> new byte[] {0x05}, value -> value[0] < 0x05);
+        mRegistry.register(0xF2, ENCODER, VALIDATOR);
+
+        assertThat(appendTlvs("chip0")).isEqualTo("F20101");
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-advertiser state of the OWR AoA sessions in which the device is the observer.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// An advertiser is reported lost when no measurement was received from it for this long.
+pub(crate) const DEFAULT_LOST_ADVERTISER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Statistics of the OWR AoA measurements received from one advertiser.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct AdvertiserStats {
+    /// Number of measurements received.
+    pub frame_count: u64,
+    /// Number of frames skipped according to the frame sequence numbers.
+    pub missed_frame_count: u64,
+    /// Number of measurements whose frame sequence number went backward or repeated.
+    pub out_of_order_count: u64,
+}
+
+/// State of one advertiser, identified by its session and MAC address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AdvertiserState {
+    pub session_token: u32,
+    pub mac_address: Vec<u8>,
+    pub last_block_index: u16,
+    pub last_frame_sequence_number: u8,
+    pub last_seen: Instant,
+    pub stats: AdvertiserStats,
+}
+
+impl AdvertiserState {
+    fn new(
+        session_token: u32,
+        mac_address: Vec<u8>,
+        block_index: u16,
+        frame_sequence_number: u8,
+        now: Instant,
+    ) -> Self {
+        Self {
+            session_token,
+            mac_address,
+            last_block_index: block_index,
+            last_frame_sequence_number: frame_sequence_number,
+            last_seen: now,
+            stats: AdvertiserStats { frame_count: 1, ..Default::default() },
+        }
+    }
+
+    fn update(&mut self, block_index: u16, frame_sequence_number: u8, now: Instant) {
+        // The frame sequence number is an 8-bit wrapping counter: a forward distance in the lower
+        // half of its range is a gap, anything else is a repeated or reordered frame.
+        let distance = frame_sequence_number.wrapping_sub(self.last_frame_sequence_number);
+        if distance == 0 || distance > u8::MAX / 2 {
+            self.stats.out_of_order_count = self.stats.out_of_order_count.saturating_add(1);
+        } else {
+            self.stats.missed_frame_count =
+                self.stats.missed_frame_count.saturating_add(u64::from(distance - 1));
+        }
+        self.stats.frame_count = self.stats.frame_count.saturating_add(1);
+        self.last_block_index = block_index;
+        self.last_frame_sequence_number = frame_sequence_number;
+        self.last_seen = now;
+    }
+}
+
+/// Tracks the advertisers heard by the OWR AoA sessions of one chip.
+#[derive(Debug)]
+pub(crate) struct AdvertiserTracker {
+    lost_timeout: Duration,
+    advertisers: HashMap<(u32, Vec<u8>), AdvertiserState>,
+}
+
+impl Default for AdvertiserTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOST_ADVERTISER_TIMEOUT)
+    }
+}
+
+impl AdvertiserTracker {
+    /// Constructs an empty tracker which reports the advertisers silent for `lost_timeout`.
+    pub fn new(lost_timeout: Duration) -> Self {
+        Self { lost_timeout, advertisers: HashMap::new() }
+    }
+
+    /// Records a measurement received from `mac_address` in the session.
+    pub fn on_measurement(
+        &mut self,
+        session_token: u32,
+        mac_address: &[u8],
+        block_index: u16,
+        frame_sequence_number: u8,
+        now: Instant,
+    ) {
+        match self.advertisers.get_mut(&(session_token, mac_address.to_vec())) {
+            Some(state) => state.update(block_index, frame_sequence_number, now),
+            None => {
+                self.advertisers.insert(
+                    (session_token, mac_address.to_vec()),
+                    AdvertiserState::new(
+                        session_token,
+                        mac_address.to_vec(),
+                        block_index,
+                        frame_sequence_number,
+                        now,
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Removes and returns the advertisers not heard from since the lost timeout.
+    pub fn take_lost_advertisers(&mut self, now: Instant) -> Vec<AdvertiserState> {
+        let lost_keys = self
+            .advertisers
+            .iter()
+            .filter(|(_, state)| {
+                now.saturating_duration_since(state.last_seen) >= self.lost_timeout
+            })
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        lost_keys.into_iter().filter_map(|key| self.advertisers.remove(&key)).collect()
+    }
+
+    /// Forgets all the advertisers of the session, e.g. when it is deinitialized.
+    pub fn remove_session(&mut self, session_token: u32) {
+        self.advertisers.retain(|(token, _), _| *token != session_token);
+    }
+
+    /// Gets the advertisers currently tracked in the session, ordered by MAC address.
+    pub fn roster(&self, session_token: u32) -> Vec<AdvertiserState> {
+        let mut roster = self
+            .advertisers
+            .values()
+            .filter(|state| state.session_token == session_token)
+            .cloned()
+            .collect::<Vec<_>>();
+        roster.sort_by(|a, b| a.mac_address.cmp(&b.mac_address));
+        roster
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_TOKEN: u32 = 1;
+
+    #[test]
+    fn test_frame_sequence_continuity() {
+        let mut tracker = AdvertiserTracker::default();
+        let now = Instant::now();
+        tracker.on_measurement(SESSION_TOKEN, &[0x01, 0x02], 10, 254, now);
+        tracker.on_measurement(SESSION_TOKEN, &[0x01, 0x02], 11, 255, now);
+        // Wraps around and skips the frame 0.
+        tracker.on_measurement(SESSION_TOKEN, &[0x01, 0x02], 13, 1, now);
+        // Repeated frame.
+        tracker.on_measurement(SESSION_TOKEN, &[0x01, 0x02], 13, 1, now);
+
+        let roster = tracker.roster(SESSION_TOKEN);
+        assert_eq!(roster.len(), 1);
+        assert_eq!(roster[0].last_block_index, 13);
+        assert_eq!(roster[0].last_frame_sequence_number, 1);
+        assert_eq!(
+            roster[0].stats,
+            AdvertiserStats { frame_count: 4, missed_frame_count: 1, out_of_order_count: 1 }
+        );
+    }
+
+    #[test]
+    fn test_roster_is_per_session() {
+        let mut tracker = AdvertiserTracker::default();
+        let now = Instant::now();
+        tracker.on_measurement(SESSION_TOKEN, &[0x02, 0x00], 0, 0, now);
+        tracker.on_measurement(SESSION_TOKEN, &[0x01, 0x00], 0, 0, now);
+        tracker.on_measurement(SESSION_TOKEN + 1, &[0x03, 0x00], 0, 0, now);
+
+        let roster = tracker.roster(SESSION_TOKEN);
+        assert_eq!(roster.len(), 2);
+        assert_eq!(roster[0].mac_address, vec![0x01, 0x00]);
+        assert_eq!(roster[1].mac_address, vec![0x02, 0x00]);
+
+        tracker.remove_session(SESSION_TOKEN);
+        assert!(tracker.roster(SESSION_TOKEN).is_empty());
+        assert_eq!(tracker.roster(SESSION_TOKEN + 1).len(), 1);
+    }
+
+    #[test]
+    fn test_take_lost_advertisers() {
+        let mut tracker = AdvertiserTracker::new(Duration::from_secs(1));
+        let start = Instant::now();
+        tracker.on_measurement(SESSION_TOKEN, &[0x01, 0x00], 0, 0, start);
+        tracker.on_measurement(SESSION_TOKEN, &[0x02, 0x00], 0, 0, start);
+        tracker.on_measurement(
+            SESSION_TOKEN,
+            &[0x02, 0x00],
+            1,
+            1,
+            start + Duration::from_millis(800),
+        );
+
+        assert!(tracker.take_lost_advertisers(start + Duration::from_millis(500)).is_empty());
+        let lost = tracker.take_lost_advertisers(start + Duration::from_millis(1200));
+        assert_eq!(lost.len(), 1);
+        assert_eq!(lost[0].mac_address, vec![0x01, 0x00]);
+        assert_eq!(tracker.roster(SESSION_TOKEN).len(), 1);
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Antenna configuration of the sessions, as the ANTENNA_MODE vendor app config and the
+//! AOA_RESULT_REQ app config, validated against the AoA capabilities of the chip reported by
+//! CORE_GET_CAPS_INFO.
+
+use log::error;
+use uwb_core::error::{Error, Result};
+
+/// The AOA_RESULT_REQ app config.
+const AOA_RESULT_REQ: u8 = 0x0d;
+/// The ANTENNA_MODE vendor app config.
+const ANTENNA_MODE: u8 = 0xea;
+const ANTENNA_MODE_OMNI: u8 = 0;
+const ANTENNA_MODE_DIRECTIONAL: u8 = 1;
+
+/// The SUPPORTED_AOA capability of FiRa 1.x and 2.0.
+const SUPPORTED_AOA_VER_1_0: u8 = 0x10;
+const SUPPORTED_AOA_VER_2_0: u8 = 0x13;
+/// The SUPPORTED_AOA_RESULT_REQ_ANTENNA_INTERLEAVING vendor capability.
+const SUPPORTED_AOA_RESULT_REQ_ANTENNA_INTERLEAVING: u8 = 0xe3;
+// Bits of SUPPORTED_AOA.
+const AOA_AZIMUTH_90: u8 = 0x1;
+const AOA_AZIMUTH_180: u8 = 0x2;
+const AOA_ELEVATION: u8 = 0x4;
+
+/// The angles of arrival measured by a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AoaAxes {
+    Azimuth,
+    Elevation,
+    AzimuthAndElevation,
+    /// Azimuth and elevation measured alternately, on distinct antenna pairs.
+    Interleaved,
+}
+
+/// The antenna configuration of a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AntennaConfig {
+    /// A single omnidirectional antenna, without AoA.
+    Omni,
+    /// A directional antenna, without AoA.
+    Directional,
+    /// The antenna pairs of the chip, measuring the AoA on the axes.
+    Aoa(AoaAxes),
+}
+
+impl TryFrom<i32> for AntennaConfig {
+    type Error = Error;
+
+    /// Decodes the ANTENNA_CONFIG_* constants of NativeUwbManager.
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(AntennaConfig::Omni),
+            1 => Ok(AntennaConfig::Directional),
+            2 => Ok(AntennaConfig::Aoa(AoaAxes::Azimuth)),
+            3 => Ok(AntennaConfig::Aoa(AoaAxes::Elevation)),
+            4 => Ok(AntennaConfig::Aoa(AoaAxes::AzimuthAndElevation)),
+            5 => Ok(AntennaConfig::Aoa(AoaAxes::Interleaved)),
+            _ => Err(Error::BadParameters),
+        }
+    }
+}
+
+/// The AoA capabilities of a chip.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct AoaCapabilities {
+    pub azimuth: bool,
+    pub elevation: bool,
+    pub interleaving: bool,
+}
+
+impl AoaCapabilities {
+    /// Extracts the AoA capabilities from the capabilities reported by CORE_GET_CAPS_INFO, as
+    /// (type, value).
+    pub fn from_caps(caps: &[(u8, Vec<u8>)]) -> Self {
+        let cap = |cap_type| caps.iter().find(|(t, _)| *t == cap_type).map(|(_, v)| &v[..]);
+        let aoa = match cap(SUPPORTED_AOA_VER_2_0).or_else(|| cap(SUPPORTED_AOA_VER_1_0)) {
+            Some([aoa, ..]) => *aoa,
+            _ => 0,
+        };
+        Self {
+            azimuth: aoa & (AOA_AZIMUTH_90 | AOA_AZIMUTH_180) != 0,
+            elevation: aoa & AOA_ELEVATION != 0,
+            interleaving: matches!(cap(SUPPORTED_AOA_RESULT_REQ_ANTENNA_INTERLEAVING), Some([1])),
+        }
+    }
+}
+
+impl AntennaConfig {
+    /// Checks that the chip can measure the AoA of the configuration.
+    pub fn validate(self, capabilities: &AoaCapabilities) -> Result<()> {
+        let supported = match self {
+            AntennaConfig::Omni | AntennaConfig::Directional => true,
+            AntennaConfig::Aoa(AoaAxes::Azimuth) => capabilities.azimuth,
+            AntennaConfig::Aoa(AoaAxes::Elevation) => capabilities.elevation,
+            AntennaConfig::Aoa(AoaAxes::AzimuthAndElevation) => {
+                capabilities.azimuth && capabilities.elevation
+            }
+            AntennaConfig::Aoa(AoaAxes::Interleaved) => {
+                capabilities.azimuth && capabilities.elevation && capabilities.interleaving
+            }
+        };
+        if !supported {
+            error!("UCI JNI: {:?} is not supported by {:?}", self, capabilities);
+            return Err(Error::BadParameters);
+        }
+        Ok(())
+    }
+
+    /// The app configs of the configuration, as (ID, value).
+    pub fn app_configs(self) -> Vec<(u8, u8)> {
+        let (antenna_mode, aoa_result_req) = match self {
+            AntennaConfig::Omni => (ANTENNA_MODE_OMNI, 0x00),
+            AntennaConfig::Directional => (ANTENNA_MODE_DIRECTIONAL, 0x00),
+            AntennaConfig::Aoa(AoaAxes::AzimuthAndElevation) => (ANTENNA_MODE_DIRECTIONAL, 0x01),
+            AntennaConfig::Aoa(AoaAxes::Azimuth) => (ANTENNA_MODE_DIRECTIONAL, 0x02),
+            AntennaConfig::Aoa(AoaAxes::Elevation) => (ANTENNA_MODE_DIRECTIONAL, 0x03),
+            AntennaConfig::Aoa(AoaAxes::Interleaved) => (ANTENNA_MODE_DIRECTIONAL, 0xf0),
+        };
+        vec![(ANTENNA_MODE, antenna_mode), (AOA_RESULT_REQ, aoa_result_req)]
+    }
+
+    /// Encodes the app configs as the TLVs passed to nativeSetAppConfigurations.
+    pub fn app_config_tlvs(self) -> Vec<u8> {
+        self.app_configs().into_iter().flat_map(|(cfg_id, value)| [cfg_id, 1, value]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_caps() {
+        assert_eq!(AoaCapabilities::from_caps(&[]), AoaCapabilities::default());
+        assert_eq!(
+            AoaCapabilities::from_caps(&[(SUPPORTED_AOA_VER_1_0, vec![AOA_AZIMUTH_90])]),
+            AoaCapabilities { azimuth: true, elevation: false, interleaving: false }
+        );
+        // The FiRa 2.0 capability prevails.
+        assert_eq!(
+            AoaCapabilities::from_caps(&[
+                (SUPPORTED_AOA_VER_1_0, vec![0]),
+                (SUPPORTED_AOA_VER_2_0, vec![AOA_AZIMUTH_180 | AOA_ELEVATION]),
+                (SUPPORTED_AOA_RESULT_REQ_ANTENNA_INTERLEAVING, vec![1]),
+            ]),
+            AoaCapabilities { azimuth: true, elevation: true, interleaving: true }
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let azimuth_only = AoaCapabilities { azimuth: true, elevation: false, interleaving: false };
+        assert!(AntennaConfig::Omni.validate(&AoaCapabilities::default()).is_ok());
+        assert!(AntennaConfig::Directional.validate(&AoaCapabilities::default()).is_ok());
+        assert!(AntennaConfig::Aoa(AoaAxes::Azimuth).validate(&azimuth_only).is_ok());
+        assert!(AntennaConfig::Aoa(AoaAxes::Elevation).validate(&azimuth_only).is_err());
+        assert!(AntennaConfig::Aoa(AoaAxes::AzimuthAndElevation).validate(&azimuth_only).is_err());
+
+        let full = AoaCapabilities { azimuth: true, elevation: true, interleaving: false };
+        assert!(AntennaConfig::Aoa(AoaAxes::AzimuthAndElevation).validate(&full).is_ok());
+        assert!(AntennaConfig::Aoa(AoaAxes::Interleaved).validate(&full).is_err());
+        let full = AoaCapabilities { interleaving: true, ..full };
+        assert!(AntennaConfig::Aoa(AoaAxes::Interleaved).validate(&full).is_ok());
+    }
+
+    #[test]
+    fn test_app_config_tlvs() {
+        assert_eq!(AntennaConfig::Omni.app_config_tlvs(), vec![0xea, 1, 0, 0x0d, 1, 0]);
+        assert_eq!(
+            AntennaConfig::Aoa(AoaAxes::Azimuth).app_config_tlvs(),
+            vec![0xea, 1, 1, 0x0d, 1, 2]
+        );
+        assert_eq!(AntennaConfig::try_from(5).unwrap(), AntennaConfig::Aoa(AoaAxes::Interleaved));
+        assert!(AntennaConfig::try_from(6).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extraction of the antenna pair which produced each two-way AoA measurement, for the chips
+//! reporting it in the vendor specific data of SESSION_INFO_NTF.
+
+/// Length of the SESSION_INFO_NTF payload preceding the ranging measurements.
+const SESSION_INFO_HEADER_LEN: usize = 25;
+/// Length of a two-way ranging measurement, whatever the MAC address mode.
+const TWO_WAY_MEASUREMENT_LEN: usize = 31;
+/// Length of the vendor specific data length field following the ranging measurements.
+const VENDOR_DATA_LENGTH_LEN: usize = 2;
+
+/// Gets the antenna pair IDs of the two-way measurements of a raw SESSION_INFO_NTF payload.
+///
+/// The chips tagging their measurements append one octet per measurement, in the order of the
+/// measurements, as vendor specific data. Returns None when the vendor specific data is missing
+/// or does not have this layout.
+pub(crate) fn parse_antenna_pair_ids(
+    raw_ranging_data: &[u8],
+    measurement_count: usize,
+) -> Option<Vec<u8>> {
+    let vendor_data_offset = SESSION_INFO_HEADER_LEN
+        .checked_add(measurement_count.checked_mul(TWO_WAY_MEASUREMENT_LEN)?)?;
+    let length_field = raw_ranging_data
+        .get(vendor_data_offset..vendor_data_offset.checked_add(VENDOR_DATA_LENGTH_LEN)?)?;
+    let vendor_data_len = usize::from(u16::from_le_bytes([length_field[0], length_field[1]]));
+    let vendor_data = &raw_ranging_data[vendor_data_offset + VENDOR_DATA_LENGTH_LEN..];
+    if measurement_count == 0
+        || vendor_data_len != measurement_count
+        || vendor_data.len() != vendor_data_len
+    {
+        return None;
+    }
+    Some(vendor_data.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_info(measurement_count: u8, vendor_data: Option<&[u8]>) -> Vec<u8> {
+        let mut payload = vec![0; SESSION_INFO_HEADER_LEN - 1];
+        payload.push(measurement_count);
+        payload.extend(vec![0; TWO_WAY_MEASUREMENT_LEN * usize::from(measurement_count)]);
+        if let Some(vendor_data) = vendor_data {
+            payload.extend((vendor_data.len() as u16).to_le_bytes());
+            payload.extend(vendor_data);
+        }
+        payload
+    }
+
+    #[test]
+    fn test_tagged_measurements() {
+        let payload = session_info(2, Some(&[0x01, 0x02]));
+        assert_eq!(parse_antenna_pair_ids(&payload, 2), Some(vec![0x01, 0x02]));
+    }
+
+    #[test]
+    fn test_untagged_measurements() {
+        // No vendor specific data.
+        assert_eq!(parse_antenna_pair_ids(&session_info(2, None), 2), None);
+        // Vendor specific data of another layout.
+        assert_eq!(parse_antenna_pair_ids(&session_info(2, Some(&[0x01, 0x02, 0x03])), 2), None);
+        assert_eq!(parse_antenna_pair_ids(&session_info(0, Some(&[])), 0), None);
+        // Truncated payload.
+        let mut payload = session_info(2, Some(&[0x01, 0x02]));
+        payload.pop();
+        assert_eq!(parse_antenna_pair_ids(&payload, 2), None);
+    }
+}
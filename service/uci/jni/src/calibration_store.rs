@@ -0,0 +1,223 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Calibration of the chips per channel and antenna, set with the ANDROID_SET_CALIBRATION vendor
+//! command and persisted, so that it is applied again every time the chip is opened or reset. The
+//! OID of the command is defined by the vendor of the chip, see vendor_commands.
+//!
+//! The payload of ANDROID_SET_CALIBRATION is defined by the vendor of the chip as well, so it is
+//! kept as is. The channel, the antenna and the version of a calibration are given by Java along
+//! with the payload, and a calibration is only recorded once the chip accepted its payload.
+
+use std::collections::BTreeMap;
+use std::io;
+
+use log::error;
+use uwb_core::error::{Error, Result};
+
+use crate::persistent_storage::{load_record, store_record, PersistentStorage, RecordFormat};
+
+/// The UWB channels of the UCI specification.
+const UWB_CHANNELS: [u8; 8] = [5, 6, 8, 9, 10, 12, 13, 14];
+/// Maximum length of the payload, so that the command fits in a single UCI packet.
+const MAX_PAYLOAD_LEN: usize = 255;
+/// Length of the channel, the antenna ID, the version and the payload length heading every
+/// persisted calibration.
+const CALIBRATION_HEADER_LEN: usize = 5;
+/// The format of the persisted calibration. Version 1 held the calibration data behind a header
+/// made up by the native stack, it is dropped on load.
+const STORE_FORMAT: RecordFormat = RecordFormat { magic: *b"UWBC", version: 2 };
+
+/// The calibration of a channel and an antenna.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Calibration {
+    pub channel: u8,
+    pub antenna_id: u8,
+    /// Increases with every new calibration of the channel and the antenna.
+    pub version: u16,
+    /// The payload of ANDROID_SET_CALIBRATION, sent to the chip as is.
+    pub payload: Vec<u8>,
+}
+
+impl Calibration {
+    /// Validates the calibration: the channel is a UWB channel, and the command fits in a single
+    /// UCI packet.
+    pub fn new(channel: u8, antenna_id: u8, version: u16, payload: Vec<u8>) -> Result<Self> {
+        if !UWB_CHANNELS.contains(&channel) || payload.is_empty() || payload.len() > MAX_PAYLOAD_LEN
+        {
+            error!(
+                "UCI JNI: invalid calibration of channel {} antenna {}, {} bytes",
+                channel,
+                antenna_id,
+                payload.len()
+            );
+            return Err(Error::BadParameters);
+        }
+        Ok(Self { channel, antenna_id, version, payload })
+    }
+}
+
+/// The key of the calibration of the chip in the persistent storage.
+fn storage_key(chip_id: &str) -> String {
+    format!("uwb_calibration_{}", chip_id)
+}
+
+/// The calibration of one chip, by channel and antenna.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct CalibrationStore {
+    calibrations: BTreeMap<(u8, u8), Calibration>,
+}
+
+impl CalibrationStore {
+    /// Loads the calibration persisted for the chip. A calibration which cannot be read or fails
+    /// its integrity check is dropped rather than applied.
+    pub fn load(storage: &dyn PersistentStorage, chip_id: &str) -> Self {
+        let body = match load_record(storage, &storage_key(chip_id), &STORE_FORMAT) {
+            Ok(Some(body)) => body,
+            Ok(None) => return Self::default(),
+            Err(e) => {
+                error!("UCI JNI: failed to load the calibration of chip {}: {:?}", chip_id, e);
+                return Self::default();
+            }
+        };
+        Self::decode(&body).unwrap_or_else(|| {
+            error!("UCI JNI: the calibration of chip {} is corrupted, dropping it", chip_id);
+            Self::default()
+        })
+    }
+
+    /// Persists the calibration of the chip.
+    pub fn persist(&self, storage: &dyn PersistentStorage, chip_id: &str) -> io::Result<()> {
+        store_record(storage, &storage_key(chip_id), &STORE_FORMAT, &self.encode())
+    }
+
+    /// Records the calibration of a channel and an antenna. Fails if the calibration recorded
+    /// for them is of a newer version.
+    pub fn set(&mut self, calibration: Calibration) -> Result<()> {
+        let key = (calibration.channel, calibration.antenna_id);
+        if let Some(current) = self.calibrations.get(&key) {
+            if current.version > calibration.version {
+                error!(
+                    "UCI JNI: calibration version {} of channel {} antenna {} is older than {}",
+                    calibration.version, key.0, key.1, current.version
+                );
+                return Err(Error::BadParameters);
+            }
+        }
+        self.calibrations.insert(key, calibration);
+        Ok(())
+    }
+
+    /// Lists the calibrations, ordered by channel and antenna.
+    pub fn calibrations(&self) -> Vec<Calibration> {
+        self.calibrations.values().cloned().collect()
+    }
+
+    /// Encodes the calibrations as their number, as a little endian u16, then every calibration
+    /// as its channel, its antenna ID, its version as a little endian u16, then its payload
+    /// prefixed by its length.
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = (self.calibrations.len() as u16).to_le_bytes().to_vec();
+        for calibration in self.calibrations.values() {
+            bytes.extend_from_slice(&[calibration.channel, calibration.antenna_id]);
+            bytes.extend_from_slice(&calibration.version.to_le_bytes());
+            // The payload length is bounded by the validation of the calibration.
+            bytes.push(calibration.payload.len() as u8);
+            bytes.extend_from_slice(&calibration.payload);
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let count = u16::from_le_bytes([*bytes.first()?, *bytes.get(1)?]);
+        let mut rest = &bytes[2..];
+        let mut store = Self::default();
+        for _ in 0..count {
+            let header = rest.get(..CALIBRATION_HEADER_LEN)?;
+            let len = header[4] as usize;
+            let payload = rest.get(CALIBRATION_HEADER_LEN..CALIBRATION_HEADER_LEN + len)?;
+            let version = u16::from_le_bytes([header[2], header[3]]);
+            let calibration =
+                Calibration::new(header[0], header[1], version, payload.to_vec()).ok()?;
+            store.set(calibration).ok()?;
+            rest = &rest[CALIBRATION_HEADER_LEN + len..];
+        }
+        rest.is_empty().then_some(store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistent_storage::MemoryStorage;
+
+    fn calibration(channel: u8, antenna_id: u8, version: u16) -> Calibration {
+        Calibration::new(channel, antenna_id, version, vec![0xa, 0xb]).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(Calibration::new(9, 1, 0, vec![0xa]).is_ok());
+        // Channel 7 is not a UWB channel.
+        assert!(Calibration::new(7, 1, 0, vec![0xa]).is_err());
+        assert!(Calibration::new(9, 1, 0, vec![]).is_err());
+        assert!(Calibration::new(9, 1, 0, vec![0xa; 256]).is_err());
+    }
+
+    #[test]
+    fn test_versioning() {
+        let mut store = CalibrationStore::default();
+        store.set(calibration(9, 1, 2)).unwrap();
+        store.set(calibration(5, 1, 1)).unwrap();
+        assert!(store.set(calibration(9, 1, 1)).is_err());
+        store.set(calibration(9, 1, 3)).unwrap();
+        assert_eq!(store.calibrations(), vec![calibration(5, 1, 1), calibration(9, 1, 3)]);
+    }
+
+    #[test]
+    fn test_persist_and_load() {
+        let storage = MemoryStorage::default();
+        assert_eq!(CalibrationStore::load(&storage, "chip"), CalibrationStore::default());
+
+        let mut store = CalibrationStore::default();
+        store.set(calibration(9, 1, 2)).unwrap();
+        store.set(calibration(9, 2, 1)).unwrap();
+        store.persist(&storage, "chip").unwrap();
+        assert_eq!(CalibrationStore::load(&storage, "chip"), store);
+        assert_eq!(CalibrationStore::load(&storage, "other chip"), CalibrationStore::default());
+    }
+
+    #[test]
+    fn test_corrupted_store_is_dropped() {
+        let storage = MemoryStorage::default();
+        let mut store = CalibrationStore::default();
+        store.set(calibration(9, 1, 2)).unwrap();
+        store.persist(&storage, "chip").unwrap();
+        let mut corrupted = storage.load(&storage_key("chip")).unwrap().unwrap();
+        corrupted[8] ^= 1;
+        storage.store(&storage_key("chip"), &corrupted).unwrap();
+        assert_eq!(CalibrationStore::load(&storage, "chip"), CalibrationStore::default());
+
+        // A calibration passing the integrity check, but of a channel which is not a UWB channel.
+        store_record(&storage, &storage_key("chip"), &STORE_FORMAT, &[1, 0, 7, 1, 0, 0, 1, 0xa])
+            .unwrap();
+        assert_eq!(CalibrationStore::load(&storage, "chip"), CalibrationStore::default());
+
+        // A calibration of the previous format.
+        let previous_format = RecordFormat { magic: *b"UWBC", version: 1 };
+        store_record(&storage, &storage_key("chip"), &previous_format, &[1, 0, 4, 9, 1, 0, 0])
+            .unwrap();
+        assert_eq!(CalibrationStore::load(&storage, "chip"), CalibrationStore::default());
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Epoch-tagged reference to the Java callback object, which can be replaced while the
+//! notifications are dispatched.
+
+use std::ops::Deref;
+use std::sync::{RwLock, RwLockReadGuard};
+
+use uwb_core::error::{Error, Result};
+
+struct EpochRef<T> {
+    epoch: u64,
+    value: T,
+}
+
+/// Holds the callback object shared by the notification managers of all the chips. A dispatch
+/// holds the slot for the whole notification, and a swap waits for the dispatches in flight, so
+/// that the replaced object is never called once the swap returned.
+pub(crate) struct CallbackSlot<T> {
+    current: RwLock<EpochRef<T>>,
+}
+
+/// The callback object of a dispatch in flight, which blocks the swaps until dropped.
+pub(crate) struct CallbackGuard<'a, T> {
+    guard: RwLockReadGuard<'a, EpochRef<T>>,
+}
+
+impl<'a, T> CallbackGuard<'a, T> {
+    /// Gets the epoch of the callback object, which changes on every swap.
+    pub fn epoch(&self) -> u64 {
+        self.guard.epoch
+    }
+}
+
+impl<'a, T> Deref for CallbackGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.guard.value
+    }
+}
+
+impl<T> CallbackSlot<T> {
+    /// Constructs the slot holding `value` at epoch 0.
+    pub fn new(value: T) -> Self {
+        Self { current: RwLock::new(EpochRef { epoch: 0, value }) }
+    }
+
+    /// Acquires the callback object for a dispatch. The dispatch must not swap the callback
+    /// object itself, which would deadlock.
+    pub fn acquire(&self) -> Result<CallbackGuard<'_, T>> {
+        let guard = self.current.read().map_err(|_| Error::Unknown)?;
+        Ok(CallbackGuard { guard })
+    }
+
+    /// Replaces the callback object once the dispatches in flight are done. Returns the new epoch
+    /// and the replaced object, which is released by the caller after the dispatches resumed.
+    pub fn swap(&self, value: T) -> Result<(u64, T)> {
+        let mut current = self.current.write().map_err(|_| Error::Unknown)?;
+        let epoch = current.epoch.wrapping_add(1);
+        let previous = std::mem::replace(&mut *current, EpochRef { epoch, value });
+        Ok((epoch, previous.value))
+    }
+}
+
+/// The callback object as seen by a dispatcher, through its own handle `W` on the object. The view
+/// catches up with the swaps of the slot at the start of every dispatch, so that a replaced object
+/// is never called.
+pub(crate) struct CallbackView<W> {
+    epoch: u64,
+    value: Option<W>,
+}
+
+impl<W> CallbackView<W> {
+    /// Constructs the view of the callback object of a dispatch.
+    pub fn new<T>(callback: &CallbackGuard<'_, Option<T>>, handle: impl FnOnce(&T) -> W) -> Self {
+        Self { epoch: callback.epoch(), value: callback.as_ref().map(handle) }
+    }
+
+    /// Catches up with a replacement of the callback object, before a dispatch with it. Returns
+    /// whether the object was replaced since the previous dispatch.
+    pub fn sync<T>(
+        &mut self,
+        callback: &CallbackGuard<'_, Option<T>>,
+        handle: impl FnOnce(&T) -> W,
+    ) -> bool {
+        if callback.epoch() == self.epoch {
+            return false;
+        }
+        self.epoch = callback.epoch();
+        self.value = callback.as_ref().map(handle);
+        true
+    }
+
+    /// Gets the callback object, None while it is detached.
+    pub fn get(&self) -> Option<&W> {
+        self.value.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::notification_queue::{BackpressurePolicy, NotificationQueue};
+
+    #[test]
+    fn test_swap_bumps_epoch() {
+        let slot = CallbackSlot::new("first");
+        assert_eq!(slot.acquire().unwrap().epoch(), 0);
+        assert_eq!(slot.swap("second").unwrap(), (1, "first"));
+        let callback = slot.acquire().unwrap();
+        assert_eq!(callback.epoch(), 1);
+        assert_eq!(*callback, "second");
+    }
+
+    #[test]
+    fn test_swap_waits_for_dispatch() {
+        let slot = Arc::new(CallbackSlot::new(1));
+        let callback = slot.acquire().unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let swapper = {
+            let slot = slot.clone();
+            thread::spawn(move || sender.send(slot.swap(2).unwrap()).unwrap())
+        };
+        // The dispatch in flight keeps using the previous object.
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+        assert_eq!(*callback, 1);
+
+        drop(callback);
+        assert_eq!(receiver.recv().unwrap(), (1, 1));
+        swapper.join().unwrap();
+        assert_eq!(*slot.acquire().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_view_follows_swaps() {
+        let slot = CallbackSlot::new(Some("first"));
+        let mut view = CallbackView::new(&slot.acquire().unwrap(), |value: &&str| value.len());
+        assert!(!view.sync(&slot.acquire().unwrap(), |value| value.len()));
+        assert_eq!(view.get(), Some(&5));
+
+        slot.swap(Some("second")).unwrap();
+        assert!(view.sync(&slot.acquire().unwrap(), |value| value.len()));
+        assert_eq!(view.get(), Some(&6));
+
+        // Detached, e.g. once the dispatcher is destroyed.
+        slot.swap(None).unwrap();
+        assert!(view.sync(&slot.acquire().unwrap(), |value| value.len()));
+        assert_eq!(view.get(), None);
+    }
+
+    #[test]
+    fn test_overflow_reported_to_swapped_callback() {
+        let queue =
+            NotificationQueue::new(1, BackpressurePolicy::DropOldest, |_: &u8| true).unwrap();
+        let slot = CallbackSlot::new(Some("first"));
+        let mut view =
+            CallbackView::new(&slot.acquire().unwrap(), |value: &&str| value.to_string());
+
+        assert!(queue.push(1).unwrap());
+        assert!(!queue.push(2).unwrap());
+        slot.swap(Some("second")).unwrap();
+
+        // The overflow is reported like the dispatch loop does: the callback object is acquired
+        // and synced first, so the report reaches the object set by the swap.
+        let dropped_count = queue.take_dropped_count();
+        let callback = slot.acquire().unwrap();
+        view.sync(&callback, |value| value.to_string());
+        assert_eq!((dropped_count, view.get().map(String::as_str)), (1, Some("second")));
+        drop(callback);
+
+        slot.swap(None).unwrap();
+        assert!(!queue.push(3).unwrap());
+        view.sync(&slot.acquire().unwrap(), |value| value.to_string());
+        assert_eq!((queue.take_dropped_count(), view.get()), (1, None));
+    }
+}
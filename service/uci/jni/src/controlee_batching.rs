@@ -0,0 +1,263 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Splitting of the controlee lists too large for a single SESSION_UPDATE_CONTROLLER_MULTICAST_LIST
+//! command into several commands, whose results are aggregated.
+
+use log::error;
+use uwb_core::error::Result;
+use uwb_core::params::SessionUpdateControllerMulticastResponse;
+use uwb_uci_packets::{ControleeStatusV2, Controlees, MulticastUpdateStatusCode, StatusCode};
+
+/// Maximum payload length of a UCI control packet.
+const MAX_UCI_PAYLOAD_LEN: usize = 255;
+/// Length of the session token, the action and the number of controlees of the command.
+const MULTICAST_LIST_CMD_HEADER_LEN: usize = 6;
+/// Length of a controlee: short address and subsession ID, then its subsession key if any.
+const CONTROLEE_LEN: usize = 6;
+const SHORT_SUBSESSION_KEY_LEN: usize = 16;
+const LONG_SUBSESSION_KEY_LEN: usize = 32;
+
+/// Gets the number of controlees of the list which fit in the payload of a single command.
+pub(crate) fn max_controlees_per_command(controlees: &Controlees) -> usize {
+    let controlee_len = match controlees {
+        Controlees::NoSessionKey(_) => CONTROLEE_LEN,
+        Controlees::ShortSessionKey(_) => CONTROLEE_LEN + SHORT_SUBSESSION_KEY_LEN,
+        Controlees::LongSessionKey(_) => CONTROLEE_LEN + LONG_SUBSESSION_KEY_LEN,
+    };
+    (MAX_UCI_PAYLOAD_LEN - MULTICAST_LIST_CMD_HEADER_LEN) / controlee_len
+}
+
+/// Gets the short addresses of the controlees, in the order of the list.
+pub(crate) fn controlee_addresses(controlees: &Controlees) -> Vec<[u8; 2]> {
+    match controlees {
+        Controlees::NoSessionKey(list) => list.iter().map(|c| c.short_address).collect(),
+        Controlees::ShortSessionKey(list) => list.iter().map(|c| c.short_address).collect(),
+        Controlees::LongSessionKey(list) => list.iter().map(|c| c.short_address).collect(),
+    }
+}
+
+fn split_list<T>(mut list: Vec<T>, batch_size: usize) -> Vec<Vec<T>> {
+    let mut batches = vec![];
+    while list.len() > batch_size {
+        let rest = list.split_off(batch_size);
+        batches.push(std::mem::replace(&mut list, rest));
+    }
+    batches.push(list);
+    batches
+}
+
+/// Splits the controlee list into batches of at most `batch_size` controlees, in order.
+pub(crate) fn split_controlees(controlees: Controlees, batch_size: usize) -> Vec<Controlees> {
+    let batch_size = batch_size.max(1);
+    match controlees {
+        Controlees::NoSessionKey(list) => {
+            split_list(list, batch_size).into_iter().map(Controlees::NoSessionKey).collect()
+        }
+        Controlees::ShortSessionKey(list) => {
+            split_list(list, batch_size).into_iter().map(Controlees::ShortSessionKey).collect()
+        }
+        Controlees::LongSessionKey(list) => {
+            split_list(list, batch_size).into_iter().map(Controlees::LongSessionKey).collect()
+        }
+    }
+}
+
+/// Sends the controlee list with `send`, split into as many commands as its size requires.
+///
+/// The responses are aggregated into a single one. Its status is the status of the first batch
+/// which failed, if any. On failure, it lists a status for each controlee of the batches sent:
+/// the controlees not listed by the response of their batch are reported as updated. The batches
+/// are not sent any further once a batch is rejected as a whole, and their controlees are not
+/// listed.
+pub(crate) fn update_multicast_list_by_batches<F>(
+    controlees: Controlees,
+    mut send: F,
+) -> Result<SessionUpdateControllerMulticastResponse>
+where
+    F: FnMut(Controlees) -> Result<SessionUpdateControllerMulticastResponse>,
+{
+    let batch_size = max_controlees_per_command(&controlees);
+    let batches = split_controlees(controlees, batch_size);
+    if batches.len() == 1 {
+        return send(batches.into_iter().next().unwrap());
+    }
+    let mut status = StatusCode::UciStatusOk;
+    let mut status_list = vec![];
+    for (index, batch) in batches.into_iter().enumerate() {
+        let addresses = controlee_addresses(&batch);
+        let response = match send(batch) {
+            Ok(response) => response,
+            // The controlees of the previous batches were updated, which must be reported.
+            Err(e) if index > 0 => {
+                error!("UCI JNI: batch {} of the multicast list update failed: {:?}", index, e);
+                status = StatusCode::UciStatusFailed;
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        if response.status != StatusCode::UciStatusOk && response.status_list.is_empty() {
+            error!("UCI JNI: batch {} of the multicast list update rejected", index);
+            if status == StatusCode::UciStatusOk {
+                status = response.status;
+            }
+            break;
+        }
+        if status == StatusCode::UciStatusOk {
+            status = response.status;
+        }
+        for mac_address in addresses {
+            status_list.push(
+                response
+                    .status_list
+                    .iter()
+                    .find(|cs| cs.mac_address == mac_address)
+                    .cloned()
+                    .unwrap_or(ControleeStatusV2 {
+                        mac_address,
+                        status: MulticastUpdateStatusCode::StatusOkMulticastListUpdate,
+                    }),
+            );
+        }
+    }
+    if status == StatusCode::UciStatusOk {
+        status_list.clear();
+    }
+    Ok(SessionUpdateControllerMulticastResponse { status, status_list })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uwb_core::error::Error;
+    use uwb_uci_packets::Controlee;
+
+    fn controlees(count: u16) -> Controlees {
+        Controlees::NoSessionKey(
+            (0..count)
+                .map(|i| Controlee { short_address: i.to_le_bytes(), subsession_id: i as u32 })
+                .collect(),
+        )
+    }
+
+    fn ok_response() -> Result<SessionUpdateControllerMulticastResponse> {
+        Ok(SessionUpdateControllerMulticastResponse {
+            status: StatusCode::UciStatusOk,
+            status_list: vec![],
+        })
+    }
+
+    #[test]
+    fn test_max_controlees_per_command() {
+        assert_eq!(max_controlees_per_command(&controlees(1)), 41);
+        assert_eq!(max_controlees_per_command(&Controlees::ShortSessionKey(vec![])), 11);
+        assert_eq!(max_controlees_per_command(&Controlees::LongSessionKey(vec![])), 6);
+    }
+
+    #[test]
+    fn test_split_controlees() {
+        let batches = split_controlees(controlees(5), 2);
+        let batch_addresses: Vec<_> = batches.iter().map(controlee_addresses).collect();
+        assert_eq!(batch_addresses, vec![vec![[0, 0], [1, 0]], vec![[2, 0], [3, 0]], vec![[4, 0]]]);
+        assert_eq!(split_controlees(controlees(2), 2).len(), 1);
+    }
+
+    #[test]
+    fn test_small_list_is_sent_as_is() {
+        let mut sent = 0;
+        let response = update_multicast_list_by_batches(controlees(41), |_| {
+            sent += 1;
+            Err(Error::Timeout)
+        });
+        assert!(matches!(response, Err(Error::Timeout)));
+        assert_eq!(sent, 1);
+    }
+
+    #[test]
+    fn test_all_batches_succeed() {
+        let mut sent = vec![];
+        let response = update_multicast_list_by_batches(controlees(100), |batch| {
+            sent.push(controlee_addresses(&batch).len());
+            ok_response()
+        })
+        .unwrap();
+        assert_eq!(sent, vec![41, 41, 18]);
+        assert_eq!(response.status, StatusCode::UciStatusOk);
+        assert!(response.status_list.is_empty());
+    }
+
+    #[test]
+    fn test_partial_failure_lists_every_controlee_sent() {
+        let mut index = 0;
+        let response = update_multicast_list_by_batches(controlees(50), |_| {
+            index += 1;
+            match index {
+                1 => ok_response(),
+                _ => Ok(SessionUpdateControllerMulticastResponse {
+                    status: StatusCode::UciStatusFailed,
+                    status_list: vec![ControleeStatusV2 {
+                        mac_address: [45, 0],
+                        status: MulticastUpdateStatusCode::StatusErrorMulticastListFull,
+                    }],
+                }),
+            }
+        })
+        .unwrap();
+        assert_eq!(response.status, StatusCode::UciStatusFailed);
+        assert_eq!(response.status_list.len(), 50);
+        let failed: Vec<_> = response
+            .status_list
+            .iter()
+            .filter(|cs| cs.status != MulticastUpdateStatusCode::StatusOkMulticastListUpdate)
+            .map(|cs| cs.mac_address)
+            .collect();
+        assert_eq!(failed, vec![[45, 0]]);
+    }
+
+    #[test]
+    fn test_rejected_batch_stops_the_update() {
+        let mut index = 0;
+        let response = update_multicast_list_by_batches(controlees(100), |_| {
+            index += 1;
+            match index {
+                1 => ok_response(),
+                _ => Ok(SessionUpdateControllerMulticastResponse {
+                    status: StatusCode::UciStatusRejected,
+                    status_list: vec![],
+                }),
+            }
+        })
+        .unwrap();
+        assert_eq!(index, 2);
+        assert_eq!(response.status, StatusCode::UciStatusRejected);
+        // Only the controlees of the first batch were updated.
+        assert_eq!(response.status_list.len(), 41);
+    }
+
+    #[test]
+    fn test_error_after_first_batch_reports_the_updated_controlees() {
+        let mut index = 0;
+        let response = update_multicast_list_by_batches(controlees(100), |_| {
+            index += 1;
+            match index {
+                1 => ok_response(),
+                _ => Err(Error::Timeout),
+            }
+        })
+        .unwrap();
+        assert_eq!(response.status, StatusCode::UciStatusFailed);
+        assert_eq!(response.status_list.len(), 41);
+    }
+}
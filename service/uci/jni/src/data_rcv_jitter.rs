@@ -0,0 +1,95 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Inter-arrival statistics of the data packets received by a session, for the streaming-style
+//! data consumers.
+
+use std::time::{Duration, Instant};
+
+/// Weight of the smoothing of the jitter, as in RFC 3550.
+const JITTER_SMOOTHING: i64 = 16;
+
+/// Inter-arrival statistics of the DATA_MESSAGE_RCV of a session, timestamped when the host
+/// receives them from the UWBS.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DataRcvJitter {
+    /// Number of data packets received.
+    pub packet_count: u64,
+    last_received_at: Option<Instant>,
+    last_interval: Option<Duration>,
+    interval_sum: Duration,
+    /// Smoothed variation between consecutive inter-arrival intervals, in nanoseconds.
+    jitter_nanos: i64,
+}
+
+impl DataRcvJitter {
+    /// Records a data packet received at `received_at`.
+    pub fn on_packet(&mut self, received_at: Instant) {
+        self.packet_count += 1;
+        let last_received_at = match self.last_received_at.replace(received_at) {
+            Some(last_received_at) => last_received_at,
+            None => return,
+        };
+        let interval = received_at.saturating_duration_since(last_received_at);
+        self.interval_sum += interval;
+        if let Some(last_interval) = self.last_interval.replace(interval) {
+            let variation = interval.abs_diff(last_interval).as_nanos() as i64;
+            self.jitter_nanos += (variation - self.jitter_nanos) / JITTER_SMOOTHING;
+        }
+    }
+
+    /// Gets the mean interval between two data packets, zero until two packets are received.
+    pub fn mean_interval(&self) -> Duration {
+        match self.packet_count {
+            0 | 1 => Duration::ZERO,
+            count => self.interval_sum / (count - 1) as u32,
+        }
+    }
+
+    /// Gets the smoothed inter-arrival jitter.
+    pub fn jitter(&self) -> Duration {
+        Duration::from_nanos(self.jitter_nanos as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regular_packets() {
+        let mut jitter = DataRcvJitter::default();
+        let start = Instant::now();
+        assert_eq!(jitter.mean_interval(), Duration::ZERO);
+        for i in 0..10 {
+            jitter.on_packet(start + Duration::from_millis(20 * i));
+        }
+        assert_eq!(jitter.packet_count, 10);
+        assert_eq!(jitter.mean_interval(), Duration::from_millis(20));
+        assert_eq!(jitter.jitter(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_irregular_packets() {
+        let mut jitter = DataRcvJitter::default();
+        let start = Instant::now();
+        // Intervals of 10ms, 26ms then 10ms.
+        for offset_ms in [0, 10, 36, 46] {
+            jitter.on_packet(start + Duration::from_millis(offset_ms));
+        }
+        assert_eq!(jitter.mean_interval(), Duration::from_millis(46) / 3);
+        // 16ms / 16 = 1ms, then 1ms + (16ms - 1ms) / 16.
+        assert_eq!(jitter.jitter(), Duration::from_micros(1000 + 937) + Duration::from_nanos(500));
+    }
+}
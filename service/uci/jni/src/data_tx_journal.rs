@@ -0,0 +1,280 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Accounting of the data packets sent and not yet acknowledged by a DATA_TRANSFER_STATUS_NTF,
+//! persisted across a shutdown of the service so that the transfers it aborts are reported.
+
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+
+use crate::persistent_storage::{load_record, store_record, PersistentStorage, RecordFormat};
+
+/// Maximum payload length of a UCI data packet.
+const MAX_DATA_PACKET_PAYLOAD_LEN: usize = 255;
+/// Length of the session token, the destination address, the UCI sequence number and the
+/// application data length of the DATA_MESSAGE_SND header, carried by the first fragment.
+const DATA_MESSAGE_SND_HEADER_LEN: usize = 16;
+/// DATA_TRANSFER_STATUS_REPETITION_OK as defined by the UCI specification: the packet is still
+/// being repeated.
+const DATA_TRANSFER_STATUS_REPETITION_OK: u8 = 0x01;
+/// The format of the persisted journal.
+const JOURNAL_FORMAT: RecordFormat = RecordFormat { magic: *b"UWBJ", version: 1 };
+
+/// The key of the journal of the chip in the persistent storage.
+fn storage_key(chip_id: &str) -> String {
+    format!("uwb_data_tx_{}", chip_id)
+}
+
+/// Gets the number of UCI data packets the application data is fragmented into.
+pub(crate) fn fragment_count(app_data_len: usize) -> u16 {
+    let len = DATA_MESSAGE_SND_HEADER_LEN + app_data_len;
+    len.div_ceil(MAX_DATA_PACKET_PAYLOAD_LEN) as u16
+}
+
+/// A data packet sent by a session and not yet acknowledged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct InFlightTransfer {
+    pub destination: Vec<u8>,
+    /// Fragments not yet passed to the UWBS, 0 once the whole packet was.
+    pub remaining_fragments: u16,
+}
+
+/// The transfers of a session aborted by the shutdown of the service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AbortedTransfers {
+    pub session_id: u32,
+    pub transfer_count: u32,
+    pub remaining_fragments: u32,
+    /// The destinations of the transfers, without duplicates, in the order they were sent to.
+    pub destinations: Vec<Vec<u8>>,
+}
+
+/// The in-flight transfers of the sessions of one chip, by UCI sequence number.
+#[derive(Debug, Default)]
+pub(crate) struct DataTxJournal {
+    sessions: BTreeMap<u32, BTreeMap<u16, InFlightTransfer>>,
+}
+
+impl DataTxJournal {
+    /// Records a data packet about to be sent.
+    pub fn on_send(
+        &mut self,
+        session_id: u32,
+        destination: &[u8],
+        uci_sequence_number: u16,
+        app_data_len: usize,
+    ) {
+        self.sessions.entry(session_id).or_default().insert(
+            uci_sequence_number,
+            InFlightTransfer {
+                destination: destination.to_vec(),
+                remaining_fragments: fragment_count(app_data_len),
+            },
+        );
+    }
+
+    /// Records the outcome of sending the fragments of a data packet. A packet which could not be
+    /// sent is forgotten, its failure being reported to Java synchronously.
+    pub fn on_sent(&mut self, session_id: u32, uci_sequence_number: u16, success: bool) {
+        if success {
+            if let Some(transfer) = self.get_mut(session_id, uci_sequence_number) {
+                transfer.remaining_fragments = 0;
+            }
+        } else {
+            self.remove(session_id, uci_sequence_number);
+        }
+    }
+
+    /// Records a DATA_TRANSFER_STATUS_NTF. The transfer is over unless it is still repeated.
+    pub fn on_transfer_status(&mut self, session_id: u32, uci_sequence_number: u16, status: u8) {
+        if status != DATA_TRANSFER_STATUS_REPETITION_OK {
+            self.remove(session_id, uci_sequence_number);
+        }
+    }
+
+    /// Forgets the transfers of a deinitialized session, whose cancellation Java reports.
+    pub fn on_session_deinit(&mut self, session_id: u32) {
+        self.sessions.remove(&session_id);
+    }
+
+    fn get_mut(
+        &mut self,
+        session_id: u32,
+        uci_sequence_number: u16,
+    ) -> Option<&mut InFlightTransfer> {
+        self.sessions.get_mut(&session_id)?.get_mut(&uci_sequence_number)
+    }
+
+    fn remove(&mut self, session_id: u32, uci_sequence_number: u16) {
+        if let Some(transfers) = self.sessions.get_mut(&session_id) {
+            transfers.remove(&uci_sequence_number);
+            if transfers.is_empty() {
+                self.sessions.remove(&session_id);
+            }
+        }
+    }
+
+    /// Persists the in-flight transfers of the chip, replacing the previous ones, or removes them
+    /// when there are none. Every transfer is written as its session ID, UCI sequence number and
+    /// remaining fragment count, as little endian integers, then its destination prefixed by its
+    /// length.
+    pub fn persist(&self, storage: &dyn PersistentStorage, chip_id: &str) -> io::Result<()> {
+        let mut bytes = vec![];
+        for (session_id, transfers) in self.sessions.iter() {
+            for (uci_sequence_number, transfer) in transfers.iter() {
+                bytes.extend_from_slice(&session_id.to_le_bytes());
+                bytes.extend_from_slice(&uci_sequence_number.to_le_bytes());
+                bytes.extend_from_slice(&transfer.remaining_fragments.to_le_bytes());
+                bytes.push(transfer.destination.len() as u8);
+                bytes.extend_from_slice(&transfer.destination);
+            }
+        }
+        if bytes.is_empty() {
+            return storage.remove(&storage_key(chip_id));
+        }
+        store_record(storage, &storage_key(chip_id), &JOURNAL_FORMAT, &bytes)
+    }
+}
+
+/// Reads the transfers of the chip persisted by the previous instance of the service, and removes
+/// them so that they are reported once. Returns the aborted transfers of each session, ordered by
+/// session ID.
+pub(crate) fn take_aborted_transfers(
+    storage: &dyn PersistentStorage,
+    chip_id: &str,
+) -> io::Result<Vec<AbortedTransfers>> {
+    let key = storage_key(chip_id);
+    let loaded = load_record(storage, &key, &JOURNAL_FORMAT);
+    // Removed whatever its content, so that a corrupted journal is not read again.
+    storage.remove(&key)?;
+    let bytes = match loaded? {
+        Some(bytes) => bytes,
+        None => return Ok(vec![]),
+    };
+    let mut sessions = BTreeMap::<u32, AbortedTransfers>::new();
+    let mut rest = &bytes[..];
+    while !rest.is_empty() {
+        if rest.len() < 9 || rest.len() < 9 + rest[8] as usize {
+            return Err(io::Error::new(ErrorKind::InvalidData, "truncated data transfer journal"));
+        }
+        let session_id = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+        let remaining_fragments = u16::from_le_bytes([rest[6], rest[7]]);
+        let destination = rest[9..9 + rest[8] as usize].to_vec();
+        rest = &rest[9 + rest[8] as usize..];
+
+        let aborted = sessions.entry(session_id).or_insert(AbortedTransfers {
+            session_id,
+            transfer_count: 0,
+            remaining_fragments: 0,
+            destinations: vec![],
+        });
+        aborted.transfer_count += 1;
+        aborted.remaining_fragments += remaining_fragments as u32;
+        if !aborted.destinations.contains(&destination) {
+            aborted.destinations.push(destination);
+        }
+    }
+    Ok(sessions.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistent_storage::MemoryStorage;
+
+    const DATA_TRANSFER_STATUS_OK: u8 = 0x00;
+
+    #[test]
+    fn test_fragment_count() {
+        assert_eq!(fragment_count(0), 1);
+        assert_eq!(fragment_count(239), 1);
+        assert_eq!(fragment_count(240), 2);
+        assert_eq!(fragment_count(1000), 4);
+    }
+
+    #[test]
+    fn test_transfer_lifecycle() {
+        let storage = MemoryStorage::default();
+        let mut journal = DataTxJournal::default();
+        journal.on_send(1, &[1, 2], 10, 100);
+        journal.on_sent(1, 10, true);
+        journal.on_transfer_status(1, 10, DATA_TRANSFER_STATUS_REPETITION_OK);
+        journal.on_send(1, &[3, 4], 11, 100);
+        journal.on_sent(1, 11, false);
+        journal.on_send(2, &[1, 2], 1, 100);
+        journal.on_transfer_status(2, 1, DATA_TRANSFER_STATUS_OK);
+        journal.persist(&storage, "chip").unwrap();
+
+        let aborted = take_aborted_transfers(&storage, "chip").unwrap();
+        assert_eq!(
+            aborted,
+            vec![AbortedTransfers {
+                session_id: 1,
+                transfer_count: 1,
+                remaining_fragments: 0,
+                destinations: vec![vec![1, 2]],
+            }]
+        );
+        // Reported once.
+        assert_eq!(storage.load(&storage_key("chip")).unwrap(), None);
+        assert_eq!(take_aborted_transfers(&storage, "chip").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_aborted_transfers_by_session() {
+        let storage = MemoryStorage::default();
+        let mut journal = DataTxJournal::default();
+        journal.on_send(2, &[1, 2, 3, 4, 5, 6, 7, 8], 1, 1000);
+        journal.on_send(1, &[1, 2], 1, 10);
+        journal.on_send(1, &[1, 2], 2, 10);
+        journal.on_send(1, &[3, 4], 3, 10);
+        journal.on_send(3, &[5, 6], 1, 10);
+        journal.on_session_deinit(3);
+        journal.persist(&storage, "chip").unwrap();
+
+        let aborted = take_aborted_transfers(&storage, "chip").unwrap();
+        assert_eq!(aborted.iter().map(|a| a.session_id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(aborted[0].transfer_count, 3);
+        assert_eq!(aborted[0].remaining_fragments, 3);
+        assert_eq!(aborted[0].destinations, vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(aborted[1].remaining_fragments, 4);
+    }
+
+    #[test]
+    fn test_persist_without_transfers_removes_file() {
+        let storage = MemoryStorage::default();
+        storage.store(&storage_key("chip"), &[0]).unwrap();
+        DataTxJournal::default().persist(&storage, "chip").unwrap();
+        assert_eq!(storage.load(&storage_key("chip")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_truncated_journal() {
+        let storage = MemoryStorage::default();
+        store_record(&storage, &storage_key("chip"), &JOURNAL_FORMAT, &[1, 0, 0, 0, 1, 0]).unwrap();
+        assert_eq!(
+            take_aborted_transfers(&storage, "chip").unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(storage.load(&storage_key("chip")).unwrap(), None);
+
+        // A corrupted journal is removed as well.
+        storage.store(&storage_key("chip"), &[1, 2, 3]).unwrap();
+        assert_eq!(
+            take_aborted_transfers(&storage, "chip").unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(storage.load(&storage_key("chip")).unwrap(), None);
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed device configs of CORE_SET_CONFIG and CORE_GET_CONFIG, and their setting in batches
+//! which the UWBS applies entirely or not at all.
+
+use log::error;
+use uwb_core::error::{Error, Result};
+use uwb_core::params::CoreSetConfigResponse;
+use uwb_uci_packets::{DeviceConfigId, DeviceConfigTlv, DeviceState, StatusCode};
+
+/// A device config with its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DeviceConfig {
+    /// DEVICE_STATE, which the UWBS reports but the host cannot set.
+    DeviceState(DeviceState),
+    /// LOW_POWER_MODE: whether the UWBS may enter its low power mode when idle.
+    LowPowerMode(bool),
+}
+
+impl DeviceConfig {
+    /// Decodes a device config read with CORE_GET_CONFIG or passed by Java.
+    pub fn from_tlv(tlv: &DeviceConfigTlv) -> Result<Self> {
+        match (tlv.cfg_id, &tlv.v[..]) {
+            (DeviceConfigId::DeviceState, [state]) => DeviceState::try_from(*state)
+                .map(DeviceConfig::DeviceState)
+                .map_err(|_| Error::BadParameters),
+            (DeviceConfigId::LowPowerMode, [0]) => Ok(DeviceConfig::LowPowerMode(false)),
+            (DeviceConfigId::LowPowerMode, [1]) => Ok(DeviceConfig::LowPowerMode(true)),
+            _ => {
+                error!("UCI JNI: invalid device config {:?}", tlv);
+                Err(Error::BadParameters)
+            }
+        }
+    }
+
+    /// Encodes the device config for CORE_SET_CONFIG. Fails for the read-only configs.
+    pub fn to_tlv(self) -> Result<DeviceConfigTlv> {
+        match self {
+            DeviceConfig::DeviceState(_) => {
+                error!("UCI JNI: DEVICE_STATE is read-only");
+                Err(Error::BadParameters)
+            }
+            DeviceConfig::LowPowerMode(enabled) => {
+                Ok(DeviceConfigTlv { cfg_id: DeviceConfigId::LowPowerMode, v: vec![enabled as u8] })
+            }
+        }
+    }
+
+    /// The value of the device config as passed to Java.
+    pub fn value(self) -> i32 {
+        match self {
+            DeviceConfig::DeviceState(state) => state as i32,
+            DeviceConfig::LowPowerMode(enabled) => enabled as i32,
+        }
+    }
+}
+
+/// Sets a batch of device configs with `set_config`, entirely or not at all: when the UWBS
+/// rejects some of them, the others are restored to their values read beforehand with
+/// `get_config`. Returns the status of each config, in the order of the batch.
+pub(crate) fn set_device_configs<G, S>(
+    configs: &[DeviceConfig],
+    mut get_config: G,
+    mut set_config: S,
+) -> Result<Vec<StatusCode>>
+where
+    G: FnMut(Vec<DeviceConfigId>) -> Result<Vec<DeviceConfigTlv>>,
+    S: FnMut(Vec<DeviceConfigTlv>) -> Result<CoreSetConfigResponse>,
+{
+    let tlvs = configs.iter().map(|config| config.to_tlv()).collect::<Result<Vec<_>>>()?;
+    let previous_tlvs = get_config(tlvs.iter().map(|tlv| tlv.cfg_id).collect())?;
+    let response = set_config(tlvs.clone())?;
+    if response.status == StatusCode::UciStatusOk {
+        return Ok(vec![StatusCode::UciStatusOk; tlvs.len()]);
+    }
+    if response.config_status.is_empty() {
+        // The whole batch is rejected.
+        return Ok(vec![response.status; tlvs.len()]);
+    }
+
+    // The configs not listed in the response are applied.
+    let statuses = tlvs
+        .iter()
+        .map(|tlv| {
+            response
+                .config_status
+                .iter()
+                .find(|config_status| config_status.cfg_id == tlv.cfg_id)
+                .map_or(StatusCode::UciStatusOk, |config_status| config_status.status)
+        })
+        .collect::<Vec<_>>();
+    let restored_tlvs = previous_tlvs
+        .into_iter()
+        .filter(|previous| {
+            tlvs.iter().zip(statuses.iter()).any(|(tlv, status)| {
+                tlv.cfg_id == previous.cfg_id && *status == StatusCode::UciStatusOk
+            })
+        })
+        .collect::<Vec<_>>();
+    if !restored_tlvs.is_empty() {
+        match set_config(restored_tlvs) {
+            Ok(response) if response.status == StatusCode::UciStatusOk => {}
+            result => error!("UCI JNI: failed to restore the device configs: {:?}", result),
+        }
+    }
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uwb_uci_packets::DeviceConfigStatus;
+
+    fn low_power_mode_tlv(enabled: bool) -> DeviceConfigTlv {
+        DeviceConfigTlv { cfg_id: DeviceConfigId::LowPowerMode, v: vec![enabled as u8] }
+    }
+
+    fn response(
+        status: StatusCode,
+        config_status: Vec<DeviceConfigStatus>,
+    ) -> CoreSetConfigResponse {
+        CoreSetConfigResponse { status, config_status }
+    }
+
+    #[test]
+    fn test_tlv() {
+        let config = DeviceConfig::from_tlv(&low_power_mode_tlv(true)).unwrap();
+        assert_eq!(config, DeviceConfig::LowPowerMode(true));
+        assert_eq!(config.value(), 1);
+        assert_eq!(config.to_tlv().unwrap(), low_power_mode_tlv(true));
+
+        let state = DeviceConfigTlv { cfg_id: DeviceConfigId::DeviceState, v: vec![0x01] };
+        let config = DeviceConfig::from_tlv(&state).unwrap();
+        assert_eq!(config, DeviceConfig::DeviceState(DeviceState::DeviceStateReady));
+        assert_eq!(config.value(), 1);
+        // DEVICE_STATE is read-only.
+        assert!(config.to_tlv().is_err());
+
+        let invalid = DeviceConfigTlv { cfg_id: DeviceConfigId::LowPowerMode, v: vec![2] };
+        assert!(DeviceConfig::from_tlv(&invalid).is_err());
+        let invalid = DeviceConfigTlv { cfg_id: DeviceConfigId::LowPowerMode, v: vec![] };
+        assert!(DeviceConfig::from_tlv(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_set_device_configs() {
+        let mut set_tlvs = vec![];
+        let statuses = set_device_configs(
+            &[DeviceConfig::LowPowerMode(true)],
+            |_| Ok(vec![low_power_mode_tlv(false)]),
+            |tlvs| {
+                set_tlvs.push(tlvs);
+                Ok(response(StatusCode::UciStatusOk, vec![]))
+            },
+        )
+        .unwrap();
+        assert_eq!(statuses, vec![StatusCode::UciStatusOk]);
+        assert_eq!(set_tlvs, vec![vec![low_power_mode_tlv(true)]]);
+    }
+
+    #[test]
+    fn test_read_only_config_is_not_sent() {
+        let mut sent = false;
+        let result = set_device_configs(
+            &[
+                DeviceConfig::LowPowerMode(true),
+                DeviceConfig::DeviceState(DeviceState::DeviceStateActive),
+            ],
+            |_| Ok(vec![]),
+            |_| {
+                sent = true;
+                Ok(response(StatusCode::UciStatusOk, vec![]))
+            },
+        );
+        assert!(result.is_err());
+        assert!(!sent);
+    }
+
+    #[test]
+    fn test_rejected_batch_is_not_restored() {
+        let mut set_count = 0;
+        let statuses = set_device_configs(
+            &[DeviceConfig::LowPowerMode(true)],
+            |_| Ok(vec![low_power_mode_tlv(false)]),
+            |_| {
+                set_count += 1;
+                Ok(response(StatusCode::UciStatusRejected, vec![]))
+            },
+        )
+        .unwrap();
+        assert_eq!(statuses, vec![StatusCode::UciStatusRejected]);
+        assert_eq!(set_count, 1);
+    }
+
+    #[test]
+    fn test_rejected_config() {
+        let mut set_count = 0;
+        let statuses = set_device_configs(
+            &[DeviceConfig::LowPowerMode(true)],
+            |_| Ok(vec![low_power_mode_tlv(false)]),
+            |_| {
+                set_count += 1;
+                Ok(response(
+                    StatusCode::UciStatusInvalidParam,
+                    vec![DeviceConfigStatus {
+                        cfg_id: DeviceConfigId::LowPowerMode,
+                        status: StatusCode::UciStatusInvalidRange,
+                    }],
+                ))
+            },
+        )
+        .unwrap();
+        assert_eq!(statuses, vec![StatusCode::UciStatusInvalidRange]);
+        // Nothing was applied, so nothing is restored.
+        assert_eq!(set_count, 1);
+    }
+
+    #[test]
+    fn test_partially_applied_batch_is_restored() {
+        let mut set_tlvs = vec![];
+        let statuses = set_device_configs(
+            &[DeviceConfig::LowPowerMode(true)],
+            |_| Ok(vec![low_power_mode_tlv(false)]),
+            |tlvs| {
+                set_tlvs.push(tlvs);
+                Ok(match set_tlvs.len() {
+                    // The UWBS lists only the configs it rejects, the others are restored.
+                    1 => response(
+                        StatusCode::UciStatusInvalidParam,
+                        vec![DeviceConfigStatus {
+                            cfg_id: DeviceConfigId::DeviceState,
+                            status: StatusCode::UciStatusReadOnly,
+                        }],
+                    ),
+                    _ => response(StatusCode::UciStatusOk, vec![]),
+                })
+            },
+        )
+        .unwrap();
+        assert_eq!(statuses, vec![StatusCode::UciStatusOk]);
+        assert_eq!(set_tlvs, vec![vec![low_power_mode_tlv(true)], vec![low_power_mode_tlv(false)]]);
+    }
+}
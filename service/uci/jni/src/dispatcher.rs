@@ -14,13 +14,45 @@
 
 //! Implementation of Dispatcher and related methods.
 
-use crate::notification_manager_android::NotificationManagerAndroidBuilder;
+use crate::advertiser_tracker::{AdvertiserState, AdvertiserTracker};
+use crate::calibration_store::{Calibration, CalibrationStore};
+use crate::callback_slot::CallbackSlot;
+use crate::data_tx_journal::{take_aborted_transfers, AbortedTransfers, DataTxJournal};
+use crate::feature_flags::{self, FeatureFlag};
+use crate::firmware_log::{FirmwareLog, FirmwareLogConfig};
+use crate::global_ref_accounting::TrackedGlobalRef;
+use crate::hal_trace::{HalTrace, HalTransaction, TracingUciHal};
+use crate::metrics_exporter::ChipMetricsSources;
+use crate::notification_bus::NotificationBus;
+use crate::notification_manager_android::{
+    NotificationManagerAndroidBuilder, QueuedNotificationManagerAndroidBuilder,
+};
+use crate::notification_queue::BackpressurePolicy;
+use crate::notification_stats::NotificationStats;
+use crate::parse_error_stats::ParseErrorStats;
+use crate::persistent_storage::{FileStorage, PersistentStorage};
+use crate::proximity::{ProximityConfig, ProximityTracker};
+use crate::radar_decimation::{RadarDecimation, RadarDecimator, RadarDeliveryBudget};
+use crate::ranging_attestation::{RangingAttestation, RangingAttestationRecord, RangingAttestor};
+use crate::session_aggregation::SessionAggregator;
+use crate::session_delivery::{DeliveryPreference, SessionDeliveryScheduler};
+use crate::session_notification_filter::SessionNotificationFilter;
+use crate::session_registry::{SessionRecord, SessionRegistry};
+use crate::session_token_audit::{SessionTokenAudit, SessionTokenMapping};
+use crate::uci_capture::{UciCaptureFilter, UciCaptureLogger, UciCaptureMode};
+use crate::uci_log_trigger::{UciLogTrigger, UciLogTriggerLogger};
+use crate::uci_metrics::{UciCommandStats, UciMetrics, UciMetricsLogger};
+use crate::uci_recording::{write_recording, RecordingUciHal, UciRecorder};
+use crate::vendor_commands::{VendorCommand, VendorCommandOids, GID_ANDROID};
+use crate::vendor_notification_router::VendorNotificationRouter;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
-use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
 
-use jni::objects::{GlobalRef, JObject, JString};
+use jni::objects::{JObject, JString};
 use jni::{JNIEnv, JavaVM, MonitorGuard};
 use lazy_static::lazy_static;
 use log::error;
@@ -32,82 +64,710 @@ use uwb_core::uci::uci_logger::UciLoggerMode;
 use uwb_core::uci::uci_logger_factory::UciLoggerFactory;
 use uwb_core::uci::uci_manager_sync::UciManagerSync;
 use uwb_core::uci::UciManagerImpl;
+use uwb_uci_packets::StatusCode;
+
+/// Size from which the UCI capture is rotated to a new file.
+const UCI_LOG_MAX_FILE_SIZE: usize = 2 * 1024 * 1024;
+/// Directory of the UCI captures and of the firmware logs.
+const UWB_LOG_PATH: &str = "/data/misc/apexdata/com.android.uwb/log";
+/// Directory of the native state which outlives the service, see persistent_storage.
+const UWB_PERSISTENT_STORAGE_PATH: &str = "/data/misc/apexdata/com.android.uwb";
+/// The message type of the UCI commands.
+const MESSAGE_TYPE_COMMAND: u32 = 1;
 
 lazy_static! {
     /// Shared unique dispatcher that may be created and deleted during runtime.
     static ref DISPATCHER: RwLock<Option<Dispatcher>> = RwLock::new(None);
 }
 
+/// The UCI manager of a chip and the state kept alongside it.
+pub(crate) struct ChipState {
+    /// The UCI manager of the chip.
+    pub manager: UciManagerSync<UciManagerImpl>,
+    /// Notification dispatch statistics of the chip, updated by NotificationManagerAndroid.
+    pub notification_stats: Arc<Mutex<NotificationStats>>,
+    /// OWR AoA advertisers heard by the chip, updated by NotificationManagerAndroid.
+    pub advertiser_tracker: Arc<Mutex<AdvertiserTracker>>,
+    /// Proximity-only sessions of the chip, read by NotificationManagerAndroid.
+    pub proximity_tracker: Arc<Mutex<ProximityTracker>>,
+    /// Filter of the UCI packets captured for the chip, set through nativeSetUciCapture.
+    pub uci_capture_filter: Arc<Mutex<UciCaptureFilter>>,
+    /// Error-triggered UCI log of the chip, armed while its UCI log is disabled.
+    pub uci_log_trigger: Arc<Mutex<UciLogTrigger>>,
+    /// Per-session notification filters of the chip, read by NotificationManagerAndroid.
+    pub session_notification_filter: Arc<Mutex<SessionNotificationFilter>>,
+    /// UCI command round-trip metrics of the chip, updated by the UCI logger.
+    pub uci_metrics: Arc<Mutex<UciMetrics>>,
+    /// Host-side bookkeeping of the sessions of the chip.
+    pub session_registry: Arc<Mutex<SessionRegistry>>,
+    /// Mapping of the session tokens to the session IDs of the chip, with its remaps.
+    pub session_token_audit: Arc<Mutex<SessionTokenAudit>>,
+    /// Notification conversion failures of the chip, updated by NotificationManagerAndroid.
+    pub parse_error_stats: Arc<Mutex<ParseErrorStats>>,
+    /// Firmware log channel of the chip, set through nativeSetFirmwareLog.
+    pub firmware_log: Arc<Mutex<FirmwareLog>>,
+    /// Java callbacks of the vendor notifications of the chip by GID, set through
+    /// nativeSetVendorNotificationCallback.
+    pub vendor_notification_router: Arc<Mutex<VendorNotificationRouter>>,
+    /// OIDs of the vendor commands supported by the chip, set through nativeSetVendorCommandOid.
+    pub vendor_command_oids: Mutex<VendorCommandOids>,
+    /// Radar sweep decimation of the sessions of the chip, set through nativeSetRadarDecimation.
+    pub radar_decimator: Arc<Mutex<RadarDecimator>>,
+    /// Range data delivery preferences of the sessions of the chip, set through
+    /// nativeSetSessionDeliveryPreference.
+    pub session_delivery: Arc<Mutex<SessionDeliveryScheduler>>,
+    /// Aggregation groups of the sessions of the chip, set through nativeSetSessionAggregation.
+    pub session_aggregator: Arc<Mutex<SessionAggregator>>,
+    /// Attestation of the measurement streams of the sessions of the chip, set through
+    /// nativeSetRangingAttestation.
+    pub ranging_attestation: Arc<Mutex<RangingAttestation>>,
+    /// Data packets sent by the sessions of the chip and not yet acknowledged.
+    pub data_tx_journal: Arc<Mutex<DataTxJournal>>,
+    /// The last UCI transactions exchanged with the HAL of the chip.
+    pub hal_trace: Arc<Mutex<HalTrace>>,
+    /// Recording of the UCI packets exchanged with the HAL of the chip, set through
+    /// nativeStartUciRecording.
+    pub uci_recorder: Arc<Mutex<UciRecorder>>,
+    /// The calibration of the chip, applied every time the chip is opened or reset.
+    pub calibration_store: Mutex<CalibrationStore>,
+}
+
 /// Dispatcher is managed by Java side. Construction and Destruction are provoked by JNI function
 /// nativeDispatcherNew and nativeDispatcherDestroy respectively.
 /// Destruction does NOT wait until the spawned threads are closed.
 pub(crate) struct Dispatcher {
-    pub manager_map: HashMap<String, UciManagerSync<UciManagerImpl>>,
+    /// The state of each chip.
+    pub chips: HashMap<String, ChipState>,
+    /// Where the calibration and the data transfer journal of the chips are persisted.
+    pub storage: Box<dyn PersistentStorage>,
+    /// The Java callback object of all the chips, which can be replaced through
+    /// nativeSwapCallbackObject.
+    pub callback_slot: Arc<CallbackSlot<Option<TrackedGlobalRef>>>,
     _runtime: Runtime,
 }
 impl Dispatcher {
     /// Constructs Dispatcher.
     fn new<T: AsRef<str>>(
         vm: &'static Arc<JavaVM>,
-        class_loader_obj: GlobalRef,
-        callback_obj: GlobalRef,
+        class_loader_obj: TrackedGlobalRef,
+        callback_obj: TrackedGlobalRef,
         chip_ids: &[T],
+        backpressure_policy: BackpressurePolicy,
+        notification_queue_capacity: usize,
     ) -> Result<Dispatcher> {
         let runtime = RuntimeBuilder::new_multi_thread()
             .thread_name("UwbService")
             .enable_all()
             .build()
             .map_err(|_| Error::ForeignFunctionInterface)?;
-        let mut manager_map = HashMap::<String, UciManagerSync<UciManagerImpl>>::new();
+        let mut chips = HashMap::new();
+        let storage: Box<dyn PersistentStorage> =
+            Box::new(FileStorage { dir: UWB_PERSISTENT_STORAGE_PATH.into() });
         let mut log_file_factory = PcapngUciLoggerFactoryBuilder::new()
-            .log_path("/data/misc/apexdata/com.android.uwb/log".into())
+            .log_path(UWB_LOG_PATH.into())
             .filename_prefix("uwb_uci".to_owned())
+            .max_file_size(UCI_LOG_MAX_FILE_SIZE)
             .runtime_handle(runtime.handle().to_owned())
             .build()
             .ok_or(Error::Unknown)?;
+        let callback_slot = Arc::new(CallbackSlot::new(Some(callback_obj)));
         for chip_id in chip_ids {
-            let logger = log_file_factory.build_logger(chip_id.as_ref()).ok_or(Error::Unknown)?;
-            let manager = UciManagerSync::new(
-                UciHalAndroid::new(chip_id.as_ref()),
-                NotificationManagerAndroidBuilder {
-                    chip_id: chip_id.as_ref().to_owned(),
-                    vm,
-                    class_loader_obj: class_loader_obj.clone(),
-                    callback_obj: callback_obj.clone(),
+            // The managers start in the Filtered log mode.
+            let mut uci_capture_filter = UciCaptureFilter::default();
+            uci_capture_filter.set_redact_identifiers(true);
+            let uci_capture_filter = Arc::new(Mutex::new(uci_capture_filter));
+            let uci_log_trigger = Arc::new(Mutex::new(UciLogTrigger::default()));
+            let uci_metrics = Arc::new(Mutex::new(UciMetrics::default()));
+            let logger = UciMetricsLogger::new(
+                UciCaptureLogger::new(
+                    UciLogTriggerLogger::new(
+                        log_file_factory.build_logger(chip_id.as_ref()).ok_or(Error::Unknown)?,
+                        uci_log_trigger.clone(),
+                    ),
+                    uci_capture_filter.clone(),
+                ),
+                uci_metrics.clone(),
+            );
+            let notification_stats = Arc::new(Mutex::new(NotificationStats::default()));
+            let advertiser_tracker = Arc::new(Mutex::new(AdvertiserTracker::default()));
+            let proximity_tracker = Arc::new(Mutex::new(ProximityTracker::default()));
+            let session_notification_filter =
+                Arc::new(Mutex::new(SessionNotificationFilter::default()));
+            let session_registry = Arc::new(Mutex::new(SessionRegistry::default()));
+            let session_token_audit = Arc::new(Mutex::new(SessionTokenAudit::default()));
+            let parse_error_stats = Arc::new(Mutex::new(ParseErrorStats::default()));
+            let firmware_log = Arc::new(Mutex::new(FirmwareLog::default()));
+            let vendor_notification_router =
+                Arc::new(Mutex::new(VendorNotificationRouter::default()));
+            let radar_decimator = Arc::new(Mutex::new(RadarDecimator::default()));
+            let session_delivery = Arc::new(Mutex::new(SessionDeliveryScheduler::default()));
+            let session_aggregator = Arc::new(Mutex::new(SessionAggregator::default()));
+            let ranging_attestation = Arc::new(Mutex::new(RangingAttestation::default()));
+            let data_tx_journal = Arc::new(Mutex::new(DataTxJournal::default()));
+            let hal_trace = Arc::new(Mutex::new(HalTrace::default()));
+            let uci_recorder = Arc::new(Mutex::new(UciRecorder::default()));
+            let mut notification_bus = NotificationBus::default();
+            notification_bus.subscribe("session registry", session_registry.clone());
+            notification_bus.subscribe("session token audit", session_token_audit.clone());
+            notification_bus.subscribe("data transfer journal", data_tx_journal.clone());
+            let builder = NotificationManagerAndroidBuilder {
+                chip_id: chip_id.as_ref().to_owned(),
+                vm,
+                class_loader_obj: class_loader_obj.clone(),
+                callback_slot: callback_slot.clone(),
+                notification_stats: notification_stats.clone(),
+                advertiser_tracker: advertiser_tracker.clone(),
+                proximity_tracker: proximity_tracker.clone(),
+                session_notification_filter: session_notification_filter.clone(),
+                notification_bus,
+                uci_metrics: uci_metrics.clone(),
+                parse_error_stats: parse_error_stats.clone(),
+                firmware_log: firmware_log.clone(),
+                vendor_notification_router: vendor_notification_router.clone(),
+                radar_decimator: radar_decimator.clone(),
+                session_delivery: session_delivery.clone(),
+                session_aggregator: session_aggregator.clone(),
+                uci_log_trigger: uci_log_trigger.clone(),
+                ranging_attestation: ranging_attestation.clone(),
+            };
+            let manager = match backpressure_policy {
+                BackpressurePolicy::Unbounded => UciManagerSync::new(
+                    TracingUciHal::new(
+                        RecordingUciHal::new(
+                            UciHalAndroid::new(chip_id.as_ref()),
+                            uci_recorder.clone(),
+                        ),
+                        hal_trace.clone(),
+                    ),
+                    builder,
+                    logger,
+                    UciLoggerMode::Filtered,
+                    runtime.handle().to_owned(),
+                )?,
+                policy => UciManagerSync::new(
+                    TracingUciHal::new(
+                        RecordingUciHal::new(
+                            UciHalAndroid::new(chip_id.as_ref()),
+                            uci_recorder.clone(),
+                        ),
+                        hal_trace.clone(),
+                    ),
+                    QueuedNotificationManagerAndroidBuilder {
+                        builder,
+                        capacity: notification_queue_capacity,
+                        policy,
+                    },
+                    logger,
+                    UciLoggerMode::Filtered,
+                    runtime.handle().to_owned(),
+                )?,
+            };
+            chips.insert(
+                chip_id.as_ref().to_string(),
+                ChipState {
+                    manager,
+                    notification_stats,
+                    advertiser_tracker,
+                    proximity_tracker,
+                    uci_capture_filter,
+                    uci_log_trigger,
+                    session_notification_filter,
+                    uci_metrics,
+                    session_registry,
+                    session_token_audit,
+                    parse_error_stats,
+                    firmware_log,
+                    vendor_notification_router,
+                    vendor_command_oids: Mutex::new(VendorCommandOids::default()),
+                    radar_decimator,
+                    session_delivery,
+                    session_aggregator,
+                    ranging_attestation,
+                    data_tx_journal,
+                    hal_trace,
+                    uci_recorder,
+                    calibration_store: Mutex::new(CalibrationStore::load(
+                        storage.as_ref(),
+                        chip_id.as_ref(),
+                    )),
                 },
-                logger,
-                UciLoggerMode::Filtered,
-                runtime.handle().to_owned(),
-            )?;
-            manager_map.insert(chip_id.as_ref().to_string(), manager);
+            );
+        }
+        Ok(Self { chips, storage, callback_slot, _runtime: runtime })
+    }
+
+    /// Gets the state of the chip.
+    fn chip(&self, chip_id: &str) -> Result<&ChipState> {
+        self.chips.get(chip_id).ok_or(Error::BadParameters)
+    }
+
+    /// Gets a snapshot of the notification dispatch statistics of the chip.
+    pub fn get_notification_stats(&self, chip_id: &str) -> Result<NotificationStats> {
+        let stats = &self.chip(chip_id)?.notification_stats;
+        let snapshot = stats.lock().map_err(|_| Error::Unknown)?.clone();
+        Ok(snapshot)
+    }
+
+    /// Gets a snapshot of the UCI command metrics of the chip, one entry per GID/OID.
+    pub fn get_uci_metrics(&self, chip_id: &str) -> Result<Vec<(u8, u8, UciCommandStats)>> {
+        let metrics = &self.chip(chip_id)?.uci_metrics;
+        let snapshot = metrics.lock().map_err(|_| Error::Unknown)?.snapshot();
+        Ok(snapshot)
+    }
+
+    /// Gets the last UCI transactions exchanged with the HAL of the chip, oldest first.
+    pub fn get_hal_trace(&self, chip_id: &str) -> Result<Vec<HalTransaction>> {
+        let trace = &self.chip(chip_id)?.hal_trace;
+        let snapshot = trace.lock().map_err(|_| Error::Unknown)?.snapshot();
+        Ok(snapshot)
+    }
+
+    /// Gets the metrics of every chip exported by the metrics exporter, ordered by chip ID.
+    pub fn get_metrics_sources(&self) -> Vec<(String, ChipMetricsSources)> {
+        let mut chips = self
+            .chips
+            .iter()
+            .map(|(chip_id, chip)| {
+                (
+                    chip_id.clone(),
+                    ChipMetricsSources {
+                        uci_metrics: chip.uci_metrics.clone(),
+                        notification_stats: chip.notification_stats.clone(),
+                        parse_error_stats: chip.parse_error_stats.clone(),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        chips.sort_by(|a, b| a.0.cmp(&b.0));
+        chips
+    }
+
+    /// Takes the notification conversion failures of the chip counted since the previous call,
+    /// one entry per packet family.
+    pub fn fetch_and_clear_parse_error_stats(
+        &self,
+        chip_id: &str,
+    ) -> Result<Vec<(&'static str, u64)>> {
+        let stats = &self.chip(chip_id)?.parse_error_stats;
+        let counts = stats.lock().map_err(|_| Error::Unknown)?.fetch_and_clear();
+        Ok(counts)
+    }
+
+    /// Lists the sessions of the chip known to the host, ordered by session ID.
+    pub fn list_sessions(&self, chip_id: &str) -> Result<Vec<(u32, SessionRecord)>> {
+        let registry = &self.chip(chip_id)?.session_registry;
+        let sessions = registry.lock().map_err(|_| Error::Unknown)?.list();
+        Ok(sessions)
+    }
+
+    /// Lists the session tokens of the chip mapped to the sessions not deinitialized, ordered by
+    /// session token.
+    pub fn list_session_token_mappings(&self, chip_id: &str) -> Result<Vec<SessionTokenMapping>> {
+        let audit = &self.chip(chip_id)?.session_token_audit;
+        let mappings = audit.lock().map_err(|_| Error::Unknown)?.mappings();
+        Ok(mappings)
+    }
+
+    /// Lists the last session tokens of the chip remapped to another session, oldest first.
+    pub fn list_session_token_remaps(&self, chip_id: &str) -> Result<Vec<SessionTokenMapping>> {
+        let audit = &self.chip(chip_id)?.session_token_audit;
+        let remaps = audit.lock().map_err(|_| Error::Unknown)?.remaps();
+        Ok(remaps)
+    }
+
+    /// Gets the OWR AoA advertisers currently heard by the session of the chip.
+    pub fn get_advertiser_roster(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+    ) -> Result<Vec<AdvertiserState>> {
+        let tracker = &self.chip(chip_id)?.advertiser_tracker;
+        let roster = tracker.lock().map_err(|_| Error::Unknown)?.roster(session_id);
+        Ok(roster)
+    }
+
+    /// Registers the session of the chip as proximity-only, or unregisters it when `config` is
+    /// None.
+    pub fn set_proximity_session(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        config: Option<ProximityConfig>,
+    ) -> Result<()> {
+        let tracker = &self.chip(chip_id)?.proximity_tracker;
+        tracker.lock().map_err(|_| Error::Unknown)?.set_session(session_id, config);
+        Ok(())
+    }
+
+    /// Sets whether the SESSION_INFO_NTFs of the session of the chip are suppressed.
+    pub fn set_session_info_suppressed(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        suppressed: bool,
+    ) -> Result<()> {
+        let filter = &self.chip(chip_id)?.session_notification_filter;
+        filter
+            .lock()
+            .map_err(|_| Error::Unknown)?
+            .set_session_info_suppressed(session_id, suppressed);
+        Ok(())
+    }
+
+    /// Sets whether the ranging diagnostics of the session are passed to Java.
+    pub fn set_diagnostics_enabled(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        enabled: bool,
+    ) -> Result<()> {
+        let filter = &self.chip(chip_id)?.session_notification_filter;
+        filter.lock().map_err(|_| Error::Unknown)?.set_diagnostics_enabled(session_id, enabled);
+        Ok(())
+    }
+
+    /// Sets whether the range data of the session are passed to Java with the raw
+    /// RANGE_DATA_NTF.
+    pub fn set_raw_ranging_data_included(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        included: bool,
+    ) -> Result<()> {
+        let filter = &self.chip(chip_id)?.session_notification_filter;
+        filter
+            .lock()
+            .map_err(|_| Error::Unknown)?
+            .set_raw_ranging_data_included(session_id, included);
+        Ok(())
+    }
+
+    /// Sets whether the range data of all the sessions of the chip are passed to Java with the raw
+    /// RANGE_DATA_NTF, to debug them.
+    pub fn set_raw_ranging_data_included_for_all(
+        &self,
+        chip_id: &str,
+        included: bool,
+    ) -> Result<()> {
+        let filter = &self.chip(chip_id)?.session_notification_filter;
+        filter.lock().map_err(|_| Error::Unknown)?.set_raw_ranging_data_included_for_all(included);
+        Ok(())
+    }
+
+    /// Sets the distance step the range data of the session of the chip are quantized to, None to
+    /// deliver them with their full precision. The policy is kept by session ID, which uwb_core
+    /// tags the range data with.
+    pub fn set_coarse_location(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        distance_step_cm: Option<u16>,
+    ) -> Result<()> {
+        let filter = &self.chip(chip_id)?.session_notification_filter;
+        filter
+            .lock()
+            .map_err(|_| Error::Unknown)?
+            .set_coarse_location(session_id, distance_step_cm);
+        Ok(())
+    }
+
+    /// Simulates the departure of the controlee `mac_address` of the session of the chip, or its
+    /// return: its measurements are omitted from the range data of the session while departed.
+    pub fn set_controlee_departed(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        mac_address: &[u8],
+        departed: bool,
+    ) -> Result<()> {
+        if mac_address.len() != 2 && mac_address.len() != 8 {
+            return Err(Error::BadParameters);
+        }
+        let filter = &self.chip(chip_id)?.session_notification_filter;
+        filter.lock().map_err(|_| Error::Unknown)?.set_controlee_departed(
+            session_id,
+            mac_address,
+            departed,
+        );
+        Ok(())
+    }
+
+    /// Sets which UCI packets of the chip are captured.
+    pub fn set_uci_capture(
+        &self,
+        chip_id: &str,
+        mode: UciCaptureMode,
+        session_tokens: HashSet<u32>,
+        gids: HashSet<u8>,
+    ) -> Result<()> {
+        let filter = &self.chip(chip_id)?.uci_capture_filter;
+        filter.lock().map_err(|_| Error::Unknown)?.configure(mode, session_tokens, gids);
+        Ok(())
+    }
+
+    /// Sets the vendor notification carrying the firmware logs of the chip, which are then kept
+    /// apart from the other vendor notifications, or stops separating them when `config` is None.
+    pub fn set_firmware_log(&self, chip_id: &str, config: Option<FirmwareLogConfig>) -> Result<()> {
+        let firmware_log = &self.chip(chip_id)?.firmware_log;
+        let path = Path::new(UWB_LOG_PATH).join(format!("uwb_fw_{}.log", chip_id));
+        firmware_log.lock().map_err(|_| Error::Unknown)?.configure(config, &path).map_err(|e| {
+            error!("UCI JNI: failed to open the firmware log file {:?}: {:?}", path, e);
+            Error::Unknown
+        })
+    }
+
+    /// Routes the vendor notifications of the GID of the chip to the Java callback
+    /// `callback_name`, or back to onVendorUciNotificationReceived when it is None.
+    pub fn set_vendor_notification_callback(
+        &self,
+        chip_id: &str,
+        gid: u32,
+        callback_name: Option<String>,
+    ) -> Result<()> {
+        let router = &self.chip(chip_id)?.vendor_notification_router;
+        router.lock().map_err(|_| Error::Unknown)?.set_java_callback(gid, callback_name)
+    }
+
+    /// Sets the OID of the vendor command on the chip, or marks the command as not supported by
+    /// the chip when `oid` is None.
+    pub fn set_vendor_command_oid(
+        &self,
+        chip_id: &str,
+        command: VendorCommand,
+        oid: Option<u8>,
+    ) -> Result<()> {
+        self.chip(chip_id)?
+            .vendor_command_oids
+            .lock()
+            .map_err(|_| Error::Unknown)?
+            .set_oid(command, oid)
+    }
+
+    /// Sets how the radar sweeps of the session of the chip are decimated before being passed to
+    /// Java, or passes all of them when `decimation` is None.
+    pub fn set_radar_decimation(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        decimation: Option<RadarDecimation>,
+    ) -> Result<()> {
+        let decimator = &self.chip(chip_id)?.radar_decimator;
+        decimator.lock().map_err(|_| Error::Unknown)?.set_session(session_id, decimation);
+        Ok(())
+    }
+
+    /// Sets how many radar sweeps of the session of the chip are passed to Java per second, or
+    /// passes all of them when `budget` is None.
+    pub fn set_radar_delivery_budget(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        budget: Option<RadarDeliveryBudget>,
+    ) -> Result<()> {
+        let decimator = &self.chip(chip_id)?.radar_decimator;
+        decimator.lock().map_err(|_| Error::Unknown)?.set_session_budget(
+            session_id,
+            budget,
+            Instant::now(),
+        );
+        Ok(())
+    }
+
+    /// Sets how the range data of the session of the chip are delivered to Java.
+    pub fn set_session_delivery_preference(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        preference: DeliveryPreference,
+    ) -> Result<()> {
+        let scheduler = &self.chip(chip_id)?.session_delivery;
+        scheduler.lock().map_err(|_| Error::Unknown)?.set_session(session_id, preference);
+        Ok(())
+    }
+
+    /// Merges the range data of the sessions of the chip into the stream of the group, held over
+    /// `window`. The group is removed when `session_ids` is empty.
+    pub fn set_session_aggregation(
+        &self,
+        chip_id: &str,
+        group_id: u32,
+        session_ids: &[u32],
+        window: Duration,
+    ) -> Result<()> {
+        let aggregator = &self.chip(chip_id)?.session_aggregator;
+        aggregator.lock().map_err(|_| Error::Unknown)?.set_group(group_id, session_ids, window);
+        Ok(())
+    }
+
+    /// Sets the attestor signing the measurement streams of the attested sessions of all the chips,
+    /// or stops attesting them when `attestor` is None.
+    pub fn set_ranging_attestor(&self, attestor: Option<Arc<dyn RangingAttestor>>) -> Result<()> {
+        for chip in self.chips.values() {
+            chip.ranging_attestation
+                .lock()
+                .map_err(|_| Error::Unknown)?
+                .set_attestor(attestor.clone());
         }
-        Ok(Self { manager_map, _runtime: runtime })
+        Ok(())
+    }
+
+    /// Attests the measurement stream of the session of the chip by segments of
+    /// `measurements_per_attestation` SESSION_INFO_NTFs, or stops attesting it when 0.
+    pub fn set_ranging_attestation(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+        measurements_per_attestation: u16,
+    ) -> Result<()> {
+        let attestation = &self.chip(chip_id)?.ranging_attestation;
+        attestation
+            .lock()
+            .map_err(|_| Error::Unknown)?
+            .set_session(session_id, measurements_per_attestation);
+        Ok(())
+    }
+
+    /// Takes the attestations of the measurement stream of the session of the chip recorded since
+    /// the previous call.
+    pub fn fetch_ranging_attestations(
+        &self,
+        chip_id: &str,
+        session_id: u32,
+    ) -> Result<Vec<RangingAttestationRecord>> {
+        let attestation = &self.chip(chip_id)?.ranging_attestation;
+        let records = attestation.lock().map_err(|_| Error::Unknown)?.fetch_and_clear(session_id);
+        Ok(records)
+    }
+
+    /// Persists the data transfers of the chip in flight, so that their abortion by a shutdown of
+    /// the service is reported by take_aborted_data_transfers once restarted.
+    pub fn persist_data_transfers(&self, chip_id: &str) -> Result<()> {
+        let journal = &self.chip(chip_id)?.data_tx_journal;
+        journal.lock().map_err(|_| Error::Unknown)?.persist(self.storage.as_ref(), chip_id).map_err(
+            |e| {
+                error!(
+                    "UCI JNI: failed to write the data transfer journal of chip {}: {:?}",
+                    chip_id, e
+                );
+                Error::Unknown
+            },
+        )
+    }
+
+    /// Gets the data transfers of each session of the chip aborted by the last shutdown of the
+    /// service, reporting them once.
+    pub fn take_aborted_data_transfers(&self, chip_id: &str) -> Result<Vec<AbortedTransfers>> {
+        self.chip(chip_id)?;
+        take_aborted_transfers(self.storage.as_ref(), chip_id).map_err(|e| {
+            error!(
+                "UCI JNI: failed to read the data transfer journal of chip {}: {:?}",
+                chip_id, e
+            );
+            Error::Unknown
+        })
+    }
+
+    /// Records the UCI packets exchanged with the HAL of the chip from its next opening, discarding
+    /// the previous recording.
+    pub fn start_uci_recording(&self, chip_id: &str) -> Result<()> {
+        let recorder = &self.chip(chip_id)?.uci_recorder;
+        recorder.lock().map_err(|_| Error::Unknown)?.arm();
+        Ok(())
+    }
+
+    /// Stops recording the UCI packets exchanged with the HAL of the chip, and writes the
+    /// recording to a file. Returns the path of the file.
+    pub fn stop_uci_recording(&self, chip_id: &str) -> Result<String> {
+        let recorder = &self.chip(chip_id)?.uci_recorder;
+        let packets = recorder.lock().map_err(|_| Error::Unknown)?.take();
+        let path = Path::new(UWB_LOG_PATH).join(format!("uwb_uci_{}.recording", chip_id));
+        write_recording(&path, &packets).map_err(|e| {
+            error!("UCI JNI: failed to write the UCI recording {:?}: {:?}", path, e);
+            Error::Unknown
+        })?;
+        Ok(path.to_string_lossy().into_owned())
     }
 
     /// Sets log mode for all chips.
     pub fn set_logger_mode(&self, logger_mode: UciLoggerMode) -> Result<()> {
-        for (_, manager) in self.manager_map.iter() {
-            manager.set_logger_mode(logger_mode.clone())?;
+        for chip_id in self.chips.keys() {
+            self.set_chip_logger_mode(chip_id, logger_mode.clone())?;
         }
         Ok(())
     }
 
+    /// Sets log mode for a single chip. The Filtered mode also redacts the identifying app
+    /// configurations of the captured packets, the key material is redacted in every mode. With
+    /// the error-triggered UCI log, the Disabled mode holds the packets of the Filtered mode until
+    /// the first error instead.
+    pub fn set_chip_logger_mode(&self, chip_id: &str, logger_mode: UciLoggerMode) -> Result<()> {
+        let chip = self.chip(chip_id)?;
+        let logger_mode = {
+            let mut trigger = chip.uci_log_trigger.lock().map_err(|_| Error::Unknown)?;
+            match logger_mode {
+                UciLoggerMode::Disabled
+                    if feature_flags::is_enabled(FeatureFlag::ErrorTriggeredUciLog) =>
+                {
+                    trigger.arm();
+                    UciLoggerMode::Filtered
+                }
+                logger_mode => {
+                    trigger.disarm();
+                    logger_mode
+                }
+            }
+        };
+        chip.uci_capture_filter
+            .lock()
+            .map_err(|_| Error::Unknown)?
+            .set_redact_identifiers(matches!(logger_mode, UciLoggerMode::Filtered));
+        chip.manager.set_logger_mode(logger_mode)
+    }
+
     /// Constructs the unique dispatcher.
     pub fn new_dispatcher<T: AsRef<str>>(
         vm: &'static Arc<JavaVM>,
-        class_loader_obj: GlobalRef,
-        callback_obj: GlobalRef,
+        class_loader_obj: TrackedGlobalRef,
+        callback_obj: TrackedGlobalRef,
         chip_ids: &[T],
+        backpressure_policy: BackpressurePolicy,
+        notification_queue_capacity: usize,
     ) -> Result<()> {
         if DISPATCHER.try_read().map_err(|_| Error::Unknown)?.is_some() {
             error!("UCI JNI: Dispatcher already exists when trying to create.");
             return Err(Error::BadParameters);
         }
-        let dispatcher = Dispatcher::new(vm, class_loader_obj, callback_obj, chip_ids)?;
+        let dispatcher = Dispatcher::new(
+            vm,
+            class_loader_obj,
+            callback_obj,
+            chip_ids,
+            backpressure_policy,
+            notification_queue_capacity,
+        )?;
         DISPATCHER.write().map_err(|_| Error::Unknown)?.replace(dispatcher);
         Ok(())
     }
 
+    /// Replaces the Java callback object of all the chips, once the notifications in flight are
+    /// dispatched, or detaches it when `callback_obj` is None. Returns the epoch of the new callback
+    /// object.
+    pub fn swap_callback_obj(callback_obj: Option<TrackedGlobalRef>) -> Result<u64> {
+        // The slot is cloned out of the dispatcher lock, which the Java callbacks in flight may
+        // need to return.
+        let callback_slot = DISPATCHER
+            .read()
+            .map_err(|_| Error::Unknown)?
+            .as_ref()
+            .ok_or(Error::BadParameters)?
+            .callback_slot
+            .clone();
+        // The notification managers only hold weak references to the callback object, so the
+        // replaced object is released here.
+        let (epoch, _replaced_callback_obj) = callback_slot.swap(callback_obj)?;
+        Ok(epoch)
+    }
+
     /// Gets pointer value of the unique dispatcher
     pub fn get_dispatcher_ptr() -> Result<*const Dispatcher> {
         let read_lock = DISPATCHER.read().map_err(|_| Error::Unknown)?;
@@ -123,7 +783,12 @@ impl Dispatcher {
             error!("UCI JNI: Dispatcher already does not exist when trying to destroy.");
             return Err(Error::BadParameters);
         }
-        let _ = DISPATCHER.write().map_err(|_| Error::Unknown)?.take();
+        let dispatcher = DISPATCHER.write().map_err(|_| Error::Unknown)?.take();
+        if let Some(dispatcher) = dispatcher {
+            // The spawned threads may outlive the dispatcher, the callback object is released
+            // before they are closed.
+            dispatcher.callback_slot.swap(None)?;
+        }
         Ok(())
     }
 
@@ -157,8 +822,116 @@ pub(crate) struct GuardedUciManager<'a> {
 impl<'a> Deref for GuardedUciManager<'a> {
     type Target = UciManagerSync<UciManagerImpl>;
     fn deref(&self) -> &Self::Target {
+        &self.chip().manager
+    }
+}
+
+impl<'a> GuardedUciManager<'a> {
+    fn chip(&self) -> &ChipState {
         // Unwrap GuardedUciManager will not panic since content is checked at creation.
-        self.read_lock.as_ref().unwrap().manager_map.get(&self.chip_id).unwrap()
+        self.read_lock.as_ref().unwrap().chips.get(&self.chip_id).unwrap()
+    }
+
+    /// Updates the host-side bookkeeping of the sessions of the chip.
+    pub fn update_session_registry<F: FnOnce(&mut SessionRegistry)>(&self, update: F) {
+        match self.chip().session_registry.lock() {
+            Ok(mut registry) => update(&mut registry),
+            Err(e) => error!("UCI JNI: failed to lock session registry: {:?}", e),
+        }
+    }
+
+    /// Rejects the commands of `gid`/`oid` while they are throttled after a retry storm, so that
+    /// the host stops feeding a UWBS which keeps asking for retransmissions.
+    pub fn check_retry_throttle(&self, gid: u8, oid: u8) -> Result<()> {
+        let metrics = &self.chip().uci_metrics;
+        if metrics.lock().map_err(|_| Error::Unknown)?.is_throttled(gid, oid, Instant::now()) {
+            error!("UCI JNI: GID {:#x} OID {:#x} is throttled after a retry storm", gid, oid);
+            return Err(Error::CommandRetry);
+        }
+        Ok(())
+    }
+
+    /// Updates the accounting of the data packets sent by the sessions of the chip.
+    pub fn update_data_tx_journal<F: FnOnce(&mut DataTxJournal)>(&self, update: F) {
+        match self.chip().data_tx_journal.lock() {
+            Ok(mut journal) => update(&mut journal),
+            Err(e) => error!("UCI JNI: failed to lock data transfer journal: {:?}", e),
+        }
+    }
+
+    /// Gets the OID of the vendor command on the chip, None if the chip does not support it.
+    pub fn vendor_command_oid(&self, command: VendorCommand) -> Option<u8> {
+        match self.chip().vendor_command_oids.lock() {
+            Ok(oids) => oids.oid(command),
+            Err(e) => {
+                error!("UCI JNI: failed to lock vendor command OIDs: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Sets the calibration on the chip with ANDROID_SET_CALIBRATION, through the raw UCI path
+    /// vendor commands are sent through. The calibration is recorded and persisted only once the
+    /// chip accepted it, so that the calibrations applied again on open and reset are the ones
+    /// which already went through that path. Returns the status of the chip.
+    pub fn set_calibration(&self, calibration: Calibration) -> Result<StatusCode> {
+        let oid = self.vendor_command_oid(VendorCommand::SetCalibration).ok_or_else(|| {
+            error!("UCI JNI: chip {} does not support the calibration", self.chip_id);
+            Error::BadParameters
+        })?;
+        let status = self.send_calibration(oid, &calibration)?;
+        if status != StatusCode::UciStatusOk {
+            return Ok(status);
+        }
+        // Unwrap GuardedUciManager will not panic since content is checked at creation.
+        let dispatcher = self.read_lock.as_ref().unwrap();
+        let mut store = self.chip().calibration_store.lock().map_err(|_| Error::Unknown)?;
+        store.set(calibration)?;
+        store.persist(dispatcher.storage.as_ref(), &self.chip_id).map_err(|e| {
+            error!("UCI JNI: failed to persist the calibration of chip {}: {:?}", self.chip_id, e);
+            Error::Unknown
+        })?;
+        Ok(status)
+    }
+
+    /// Applies the calibration of the chip again, after the chip was opened or reset. Fails if
+    /// any calibration was not applied, once all of them were tried.
+    pub fn apply_calibration(&self) -> Result<()> {
+        let calibrations =
+            self.chip().calibration_store.lock().map_err(|_| Error::Unknown)?.calibrations();
+        if calibrations.is_empty() {
+            return Ok(());
+        }
+        let oid = self.vendor_command_oid(VendorCommand::SetCalibration).ok_or_else(|| {
+            error!("UCI JNI: chip {} no longer supports the calibration", self.chip_id);
+            Error::BadParameters
+        })?;
+        let mut result = Ok(());
+        for calibration in calibrations {
+            match self.send_calibration(oid, &calibration) {
+                Ok(StatusCode::UciStatusOk) => {}
+                status => {
+                    error!(
+                        "UCI JNI: failed to apply the calibration of channel {} antenna {} to \
+                         chip {}: {:?}",
+                        calibration.channel, calibration.antenna_id, self.chip_id, status
+                    );
+                    result = Err(Error::Unknown);
+                }
+            }
+        }
+        result
+    }
+
+    fn send_calibration(&self, oid: u8, calibration: &Calibration) -> Result<StatusCode> {
+        let response = self.raw_uci_cmd(
+            MESSAGE_TYPE_COMMAND,
+            GID_ANDROID as u32,
+            oid as u32,
+            calibration.payload.clone(),
+        )?;
+        let status = response.payload.first().ok_or(Error::Unknown)?;
+        StatusCode::try_from(*status).map_err(|_| Error::Unknown)
     }
 }
 
@@ -183,7 +956,7 @@ impl<'a> GuardedDispatcher<'a> {
 
     /// Conversion to GuardedUciManager:
     pub fn into_guarded_uci_manager(self, chip_id: &str) -> Result<GuardedUciManager<'a>> {
-        let _uci_manager = self.manager_map.get(chip_id).ok_or(Error::BadParameters)?;
+        self.chip(chip_id)?;
         Ok(GuardedUciManager {
             _jni_guard: self._jni_guard,
             read_lock: self.read_lock,
@@ -0,0 +1,131 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding of the DT-Anchor location carried by the DL-TDoA measurements.
+
+/// Location types of the Message Control field, matching the constants of
+/// com.android.server.uwb.data.UwbDlTDoAAnchorLocation.
+pub(crate) const ANCHOR_LOCATION_TYPE_NONE: i32 = 0;
+pub(crate) const ANCHOR_LOCATION_TYPE_WGS84: i32 = 1;
+pub(crate) const ANCHOR_LOCATION_TYPE_RELATIVE: i32 = 2;
+
+const LOCATION_TYPE_SHIFT: u16 = 5;
+const LOCATION_TYPE_MASK: u16 = 0x3;
+const WGS84_LEN: usize = 12;
+const RELATIVE_LEN: usize = 10;
+
+/// A decoded DT-Anchor location.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum AnchorLocation {
+    /// WGS-84 coordinates: latitude and longitude in degrees, altitude in meters.
+    Wgs84 { latitude: f64, longitude: f64, altitude: f64 },
+    /// Coordinates relative to the reference anchor, in meters.
+    Relative { x: f64, y: f64, z: f64 },
+}
+
+impl AnchorLocation {
+    /// Returns the location type and the three coordinates, in the order expected by Java.
+    pub fn to_java_parts(self) -> (i32, f64, f64, f64) {
+        match self {
+            AnchorLocation::Wgs84 { latitude, longitude, altitude } => {
+                (ANCHOR_LOCATION_TYPE_WGS84, latitude, longitude, altitude)
+            }
+            AnchorLocation::Relative { x, y, z } => (ANCHOR_LOCATION_TYPE_RELATIVE, x, y, z),
+        }
+    }
+}
+
+/// Reads the `len` bits at `offset` of the little endian `value` as a two's complement number.
+fn signed_bits(value: u128, offset: u32, len: u32) -> i64 {
+    let shift = 128 - len;
+    ((((value >> offset) << shift) as i128) >> shift) as i64
+}
+
+/// Decodes `dt_anchor_location` according to the location type bits of `message_control`.
+///
+/// The WGS-84 format packs, least significant bit first, a 33-bit latitude and a 33-bit
+/// longitude in degrees with 25 fractional bits, then a 30-bit altitude in meters with 8
+/// fractional bits. The relative format packs 28-bit X and Y and a 24-bit Z, in centimeters.
+/// Returns None when no location is included, or when its length does not match its type.
+pub(crate) fn parse_anchor_location(
+    message_control: u16,
+    dt_anchor_location: &[u8],
+) -> Option<AnchorLocation> {
+    let location_type = ((message_control >> LOCATION_TYPE_SHIFT) & LOCATION_TYPE_MASK) as i32;
+    let expected_len = match location_type {
+        ANCHOR_LOCATION_TYPE_WGS84 => WGS84_LEN,
+        ANCHOR_LOCATION_TYPE_RELATIVE => RELATIVE_LEN,
+        _ => return None,
+    };
+    if dt_anchor_location.len() != expected_len {
+        return None;
+    }
+    let mut bytes = [0u8; 16];
+    bytes[..expected_len].copy_from_slice(dt_anchor_location);
+    let value = u128::from_le_bytes(bytes);
+
+    Some(if location_type == ANCHOR_LOCATION_TYPE_WGS84 {
+        AnchorLocation::Wgs84 {
+            latitude: signed_bits(value, 0, 33) as f64 / (1u64 << 25) as f64,
+            longitude: signed_bits(value, 33, 33) as f64 / (1u64 << 25) as f64,
+            altitude: signed_bits(value, 66, 30) as f64 / (1u64 << 8) as f64,
+        }
+    } else {
+        AnchorLocation::Relative {
+            x: signed_bits(value, 0, 28) as f64 / 100.0,
+            y: signed_bits(value, 28, 28) as f64 / 100.0,
+            z: signed_bits(value, 56, 24) as f64 / 100.0,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack(fields: &[(i64, u32)], len: usize) -> Vec<u8> {
+        let mut value = 0u128;
+        let mut offset = 0;
+        for &(field, bits) in fields {
+            value |= ((field as u128) & ((1u128 << bits) - 1)) << offset;
+            offset += bits;
+        }
+        value.to_le_bytes()[..len].to_vec()
+    }
+
+    #[test]
+    fn test_parse_wgs84() {
+        let location =
+            pack(&[((48 << 25) | (1 << 24), 33), (-(2 << 25), 33), (-(35 << 8), 30)], WGS84_LEN);
+        assert_eq!(
+            parse_anchor_location(0x1 << LOCATION_TYPE_SHIFT, &location),
+            Some(AnchorLocation::Wgs84 { latitude: 48.5, longitude: -2.0, altitude: -35.0 })
+        );
+        // Wrong length for the type.
+        assert_eq!(parse_anchor_location(0x1 << LOCATION_TYPE_SHIFT, &location[..10]), None);
+    }
+
+    #[test]
+    fn test_parse_relative() {
+        let location = pack(&[(150, 28), (-25, 28), (300, 24)], RELATIVE_LEN);
+        let parsed = parse_anchor_location((0x2 << LOCATION_TYPE_SHIFT) | 0x13, &location);
+        assert_eq!(parsed, Some(AnchorLocation::Relative { x: 1.5, y: -0.25, z: 3.0 }));
+        assert_eq!(
+            parsed.unwrap().to_java_parts(),
+            (ANCHOR_LOCATION_TYPE_RELATIVE, 1.5, -0.25, 3.0)
+        );
+        // No location included.
+        assert_eq!(parse_anchor_location(0x13, &[]), None);
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime feature flags gating the rollout of native features.
+//!
+//! The flag values are owned by the Java side and pushed down by nativeSetFeatureFlags before the
+//! dispatcher is created. Every flag is disabled until Java enables it, and unknown flag names are
+//! ignored so that the Java and native sides can be updated independently.
+
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use log::{error, info};
+use uwb_core::error::{Error, Result};
+
+lazy_static! {
+    /// The feature flags shared by the whole native stack.
+    static ref FEATURE_FLAGS: RwLock<FeatureFlags> = RwLock::new(FeatureFlags::default());
+}
+
+/// Native behaviors which are rolled out behind a flag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FeatureFlag {
+    /// Splitting large controlee lists into several multicast list update commands.
+    ControleeBatching = 0,
+    /// Reporting only the near/far edges of the proximity-only sessions.
+    ProximityFastPath = 1,
+    /// Tagging the two-way measurements with the antenna pair reported in the vendor data.
+    AntennaPairTagging = 2,
+    /// Suggesting longer ranging intervals to the sessions suffering from RF congestion.
+    RangingAdvisor = 3,
+    /// Serving the metrics of the chips in the Prometheus text format over a local TCP port.
+    MetricsExporter = 4,
+    /// Writing ATrace markers at the stages the notifications go through.
+    LatencyTracing = 5,
+    /// Holding the UCI packets while the UCI log is disabled, and writing them from the first
+    /// error.
+    ErrorTriggeredUciLog = 6,
+}
+
+impl FeatureFlag {
+    pub const ALL: [FeatureFlag; 7] = [
+        FeatureFlag::ControleeBatching,
+        FeatureFlag::ProximityFastPath,
+        FeatureFlag::AntennaPairTagging,
+        FeatureFlag::RangingAdvisor,
+        FeatureFlag::MetricsExporter,
+        FeatureFlag::LatencyTracing,
+        FeatureFlag::ErrorTriggeredUciLog,
+    ];
+
+    /// The name of the flag used by the Java side.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FeatureFlag::ControleeBatching => "native_controlee_batching",
+            FeatureFlag::ProximityFastPath => "native_proximity_fast_path",
+            FeatureFlag::AntennaPairTagging => "native_antenna_pair_tagging",
+            FeatureFlag::RangingAdvisor => "native_ranging_advisor",
+            FeatureFlag::MetricsExporter => "native_metrics_exporter",
+            FeatureFlag::LatencyTracing => "native_latency_tracing",
+            FeatureFlag::ErrorTriggeredUciLog => "native_error_triggered_uci_log",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|flag| flag.name() == name).copied()
+    }
+}
+
+/// The values of all the feature flags.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct FeatureFlags {
+    values: [bool; FeatureFlag::ALL.len()],
+}
+
+impl FeatureFlags {
+    /// Returns whether `flag` is enabled.
+    pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
+        self.values[flag as usize]
+    }
+
+    /// Sets the value of the flag named `name`. Returns false if the name is unknown.
+    fn set(&mut self, name: &str, value: bool) -> bool {
+        match FeatureFlag::from_name(name) {
+            Some(flag) => {
+                self.values[flag as usize] = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Formats the flag states for the dumpsys output, one flag per line.
+    pub fn dump(&self) -> String {
+        FeatureFlag::ALL
+            .iter()
+            .map(|&flag| format!("{}={}", flag.name(), self.is_enabled(flag)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Returns whether `flag` is enabled. Flags are considered disabled if they cannot be read.
+pub(crate) fn is_enabled(flag: FeatureFlag) -> bool {
+    match FEATURE_FLAGS.read() {
+        Ok(flags) => flags.is_enabled(flag),
+        Err(e) => {
+            error!("UCI JNI: failed to read feature flags: {:?}", e);
+            false
+        }
+    }
+}
+
+/// Updates the feature flags with the (name, value) pairs pushed by the Java side.
+pub(crate) fn update(new_values: &[(String, bool)]) -> Result<()> {
+    let mut flags = FEATURE_FLAGS.write().map_err(|_| Error::Unknown)?;
+    for (name, value) in new_values {
+        if flags.set(name, *value) {
+            info!("UCI JNI: feature flag {} set to {}", name, value);
+        } else {
+            info!("UCI JNI: ignoring unknown feature flag {}", name);
+        }
+    }
+    Ok(())
+}
+
+/// Gets a copy of the current feature flags.
+pub(crate) fn snapshot() -> Result<FeatureFlags> {
+    Ok(FEATURE_FLAGS.read().map_err(|_| Error::Unknown)?.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_are_disabled_by_default() {
+        let flags = FeatureFlags::default();
+        for flag in FeatureFlag::ALL {
+            assert!(!flags.is_enabled(flag));
+        }
+    }
+
+    #[test]
+    fn test_set_flag_by_name() {
+        let mut flags = FeatureFlags::default();
+        assert!(flags.set("native_antenna_pair_tagging", true));
+        assert!(!flags.set("native_unknown_feature", true));
+        assert!(!flags.is_enabled(FeatureFlag::ControleeBatching));
+        assert!(flags.is_enabled(FeatureFlag::AntennaPairTagging));
+        assert!(!flags.is_enabled(FeatureFlag::RangingAdvisor));
+
+        assert!(flags.set("native_antenna_pair_tagging", false));
+        assert!(!flags.is_enabled(FeatureFlag::AntennaPairTagging));
+    }
+
+    #[test]
+    fn test_dump() {
+        let mut flags = FeatureFlags::default();
+        flags.set("native_proximity_fast_path", true);
+        assert_eq!(
+            flags.dump(),
+            "native_controlee_batching=false\n\
+             native_proximity_fast_path=true\n\
+             native_antenna_pair_tagging=false\n\
+             native_ranging_advisor=false\n\
+             native_metrics_exporter=false\n\
+             native_latency_tracing=false\n\
+             native_error_triggered_uci_log=false"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Firmware logs carried by a vendor notification, kept apart from the other vendor
+//! notifications.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use log::error;
+
+/// Size from which the firmware log file is rotated.
+pub(crate) const FIRMWARE_LOG_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// The vendor notification carrying the firmware logs of a chip, and where they go.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct FirmwareLogConfig {
+    pub gid: u32,
+    pub oid: u32,
+    /// Whether the logs are written to the rotating firmware log file.
+    pub write_file: bool,
+    /// Whether the logs are streamed to Java.
+    pub stream: bool,
+}
+
+/// Firmware log file rotated to `<path>.old` once it reaches its maximum size. Every log is
+/// written as its length, as a little endian u32, followed by its payload.
+pub(crate) struct FirmwareLogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+}
+
+impl FirmwareLogFile {
+    /// Opens the file at `path`, appending to the logs already written.
+    pub fn open(path: &Path, max_size: u64) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path: path.to_owned(), file, size, max_size })
+    }
+
+    /// Writes a log, rotating the file first if the log would not fit.
+    pub fn write_log(&mut self, payload: &[u8]) -> io::Result<()> {
+        let record_size = 4 + payload.len() as u64;
+        if self.size > 0 && self.size + record_size > self.max_size {
+            self.rotate()?;
+        }
+        let len = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "log too long"))?;
+        self.file.write_all(&len.to_le_bytes())?;
+        self.file.write_all(payload)?;
+        self.size += record_size;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let mut old_path = self.path.clone().into_os_string();
+        old_path.push(".old");
+        fs::rename(&self.path, old_path)?;
+        self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Firmware log channel of one chip, configured through the Dispatcher.
+#[derive(Default)]
+pub(crate) struct FirmwareLog {
+    config: Option<FirmwareLogConfig>,
+    file: Option<FirmwareLogFile>,
+}
+
+impl FirmwareLog {
+    /// Sets the vendor notification carrying the firmware logs, or stops separating them when
+    /// `config` is None. The file at `path` is only opened when the logs are written to a file.
+    pub fn configure(&mut self, config: Option<FirmwareLogConfig>, path: &Path) -> io::Result<()> {
+        self.file = match config {
+            Some(FirmwareLogConfig { write_file: true, .. }) => {
+                Some(FirmwareLogFile::open(path, FIRMWARE_LOG_MAX_FILE_SIZE)?)
+            }
+            _ => None,
+        };
+        self.config = config;
+        Ok(())
+    }
+
+    /// Handles a vendor notification. Returns None when it does not carry firmware logs, or else
+    /// whether the logs are to be streamed to Java.
+    pub fn on_vendor_notification(&mut self, gid: u32, oid: u32, payload: &[u8]) -> Option<bool> {
+        let config = self.config.filter(|config| config.gid == gid && config.oid == oid)?;
+        if let Some(file) = self.file.as_mut() {
+            if let Err(e) = file.write_log(payload) {
+                error!("UCI JNI: failed to write the firmware log, disabling the file: {:?}", e);
+                self.file = None;
+            }
+        }
+        Some(config.stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_on_vendor_notification() {
+        let path = temp_path("uwb_fw_log_routing");
+        let mut firmware_log = FirmwareLog::default();
+        assert_eq!(firmware_log.on_vendor_notification(0xe, 0x1, &[1]), None);
+
+        let config = FirmwareLogConfig { gid: 0xe, oid: 0x1, write_file: true, stream: false };
+        firmware_log.configure(Some(config), &path).unwrap();
+        assert_eq!(firmware_log.on_vendor_notification(0xe, 0x1, &[1, 2]), Some(false));
+        assert_eq!(firmware_log.on_vendor_notification(0xe, 0x2, &[3]), None);
+        assert_eq!(fs::read(&path).unwrap(), vec![2, 0, 0, 0, 1, 2]);
+
+        firmware_log.configure(None, &path).unwrap();
+        assert_eq!(firmware_log.on_vendor_notification(0xe, 0x1, &[1]), None);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_rotation() {
+        let path = temp_path("uwb_fw_log_rotation");
+        let mut old_path = path.clone().into_os_string();
+        old_path.push(".old");
+        let mut file = FirmwareLogFile::open(&path, 12).unwrap();
+        file.write_log(&[1, 2, 3]).unwrap();
+        file.write_log(&[4]).unwrap();
+        // Does not fit in the 12 bytes anymore.
+        file.write_log(&[5, 6]).unwrap();
+
+        assert_eq!(fs::read(&old_path).unwrap(), vec![3, 0, 0, 0, 1, 2, 3, 1, 0, 0, 0, 4]);
+        assert_eq!(fs::read(&path).unwrap(), vec![2, 0, 0, 0, 5, 6]);
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&old_path);
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Accounting of the JNI global references held by the native stack, so that a reference leaked
+//! across the init/deinit cycles of the stack shows as a count not returning to its baseline.
+//!
+//! A reference is counted from its creation until the last clone of its TrackedRef is dropped.
+//! The notification managers only hold a WeakTrackedRef to the callback object, which is then
+//! released as soon as the Dispatcher replaces or drops it.
+
+use std::ops::Deref;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Weak};
+
+use jni::objects::GlobalRef;
+
+/// What a global reference is held for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GlobalRefKind {
+    /// The class loader of the Java thread which created the Dispatcher.
+    ClassLoader,
+    /// The Java callback object of the notifications.
+    CallbackObject,
+    /// A Java class cached by a notification manager.
+    ClassCache,
+}
+
+impl GlobalRefKind {
+    pub const ALL: [GlobalRefKind; 3] =
+        [GlobalRefKind::ClassLoader, GlobalRefKind::CallbackObject, GlobalRefKind::ClassCache];
+
+    /// The label of the kind in the metrics.
+    pub fn label(self) -> &'static str {
+        match self {
+            GlobalRefKind::ClassLoader => "class_loader",
+            GlobalRefKind::CallbackObject => "callback_object",
+            GlobalRefKind::ClassCache => "class_cache",
+        }
+    }
+}
+
+/// The number of live references of each kind.
+#[derive(Debug)]
+pub(crate) struct GlobalRefCounts {
+    // Signed, so that a release accounted twice shows as a negative count rather than wrapping.
+    counts: [AtomicI64; GlobalRefKind::ALL.len()],
+}
+
+impl GlobalRefCounts {
+    const fn new() -> Self {
+        Self { counts: [AtomicI64::new(0), AtomicI64::new(0), AtomicI64::new(0)] }
+    }
+
+    fn count(&self, kind: GlobalRefKind) -> &AtomicI64 {
+        &self.counts[kind as usize]
+    }
+
+    /// Lists the number of live references of each kind.
+    pub fn snapshot(&self) -> Vec<(GlobalRefKind, i64)> {
+        GlobalRefKind::ALL
+            .iter()
+            .map(|&kind| (kind, self.count(kind).load(Ordering::Relaxed)))
+            .collect()
+    }
+}
+
+/// The references held by the native stack.
+pub(crate) static GLOBAL_REF_COUNTS: GlobalRefCounts = GlobalRefCounts::new();
+
+/// Counts a reference until dropped.
+#[derive(Debug)]
+struct Account {
+    counts: &'static GlobalRefCounts,
+    kind: GlobalRefKind,
+}
+
+impl Account {
+    fn new(counts: &'static GlobalRefCounts, kind: GlobalRefKind) -> Self {
+        counts.count(kind).fetch_add(1, Ordering::Relaxed);
+        Self { counts, kind }
+    }
+}
+
+impl Drop for Account {
+    fn drop(&mut self) {
+        self.counts.count(self.kind).fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+struct Tracked<T> {
+    value: T,
+    _account: Account,
+}
+
+/// A reference counted in GLOBAL_REF_COUNTS until its last clone is dropped.
+#[derive(Debug)]
+pub(crate) struct TrackedRef<T>(Arc<Tracked<T>>);
+
+/// A global reference counted in GLOBAL_REF_COUNTS.
+pub(crate) type TrackedGlobalRef = TrackedRef<GlobalRef>;
+
+/// A reference which does not keep its TrackedRef alive.
+#[derive(Debug)]
+pub(crate) struct WeakTrackedRef<T>(Weak<Tracked<T>>);
+
+impl<T> TrackedRef<T> {
+    /// Starts counting `value` as a reference of `kind`.
+    pub fn new(value: T, kind: GlobalRefKind) -> Self {
+        Self::new_in(&GLOBAL_REF_COUNTS, value, kind)
+    }
+
+    fn new_in(counts: &'static GlobalRefCounts, value: T, kind: GlobalRefKind) -> Self {
+        Self(Arc::new(Tracked { value, _account: Account::new(counts, kind) }))
+    }
+
+    pub fn downgrade(&self) -> WeakTrackedRef<T> {
+        WeakTrackedRef(Arc::downgrade(&self.0))
+    }
+}
+
+impl<T> Clone for TrackedRef<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Deref for TrackedRef<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0.value
+    }
+}
+
+impl<T> WeakTrackedRef<T> {
+    /// Gets the reference, None if it was released.
+    pub fn upgrade(&self) -> Option<TrackedRef<T>> {
+        self.0.upgrade().map(TrackedRef)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(counts: &GlobalRefCounts, kind: GlobalRefKind) -> i64 {
+        counts.count(kind).load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_counted_until_last_clone_dropped() {
+        static COUNTS: GlobalRefCounts = GlobalRefCounts::new();
+        let tracked = TrackedRef::new_in(&COUNTS, 1, GlobalRefKind::ClassCache);
+        let clone = tracked.clone();
+        assert_eq!(*clone, 1);
+        assert_eq!(count(&COUNTS, GlobalRefKind::ClassCache), 1);
+        assert_eq!(count(&COUNTS, GlobalRefKind::ClassLoader), 0);
+
+        drop(tracked);
+        assert_eq!(count(&COUNTS, GlobalRefKind::ClassCache), 1);
+        drop(clone);
+        assert_eq!(count(&COUNTS, GlobalRefKind::ClassCache), 0);
+    }
+
+    #[test]
+    fn test_weak_ref_does_not_hold_the_ref() {
+        static COUNTS: GlobalRefCounts = GlobalRefCounts::new();
+        let tracked = TrackedRef::new_in(&COUNTS, 1, GlobalRefKind::CallbackObject);
+        let weak = tracked.downgrade();
+        assert_eq!(weak.upgrade().as_deref(), Some(&1));
+
+        drop(tracked);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(count(&COUNTS, GlobalRefKind::CallbackObject), 0);
+    }
+
+    #[test]
+    fn test_init_deinit_cycles_return_to_baseline() {
+        static COUNTS: GlobalRefCounts = GlobalRefCounts::new();
+        let baseline = COUNTS.snapshot();
+        for _ in 0..1000 {
+            // The references the Dispatcher and the notification managers of two chips hold.
+            let class_loader = TrackedRef::new_in(&COUNTS, (), GlobalRefKind::ClassLoader);
+            let mut callback_obj =
+                Some(TrackedRef::new_in(&COUNTS, (), GlobalRefKind::CallbackObject));
+            let managers = (0..2)
+                .map(|_| {
+                    let class_cache = (0..4)
+                        .map(|_| TrackedRef::new_in(&COUNTS, (), GlobalRefKind::ClassCache))
+                        .collect::<Vec<_>>();
+                    let callback_obj = callback_obj.as_ref().map(TrackedRef::downgrade);
+                    (class_loader.clone(), callback_obj, class_cache)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(count(&COUNTS, GlobalRefKind::ClassCache), 8);
+
+            // The callback object is swapped, then detached.
+            let replaced = callback_obj.replace(TrackedRef::new_in(
+                &COUNTS,
+                (),
+                GlobalRefKind::CallbackObject,
+            ));
+            drop(replaced);
+            assert_eq!(count(&COUNTS, GlobalRefKind::CallbackObject), 1);
+            callback_obj = None;
+            assert_eq!(count(&COUNTS, GlobalRefKind::CallbackObject), 0);
+            assert!(managers
+                .iter()
+                .all(|(_, weak, _)| weak.as_ref().and_then(WeakTrackedRef::upgrade).is_none()));
+
+            drop(managers);
+            assert_eq!(count(&COUNTS, GlobalRefKind::ClassLoader), 1);
+            drop((class_loader, callback_obj));
+        }
+        assert_eq!(COUNTS.snapshot(), baseline);
+    }
+}
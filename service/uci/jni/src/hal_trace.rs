@@ -0,0 +1,289 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracing of the UCI transactions exchanged with the HAL of a chip, to locate the delays between
+//! the stack and the vendor HAL.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use log::{debug, error};
+use tokio::sync::mpsc;
+use uwb_core::error::Result;
+use uwb_core::uci::uci_hal::{UciHal, UciHalPacket};
+
+use crate::latency_trace;
+
+/// Number of transactions kept by the trace.
+pub(crate) const HAL_TRACE_CAPACITY: usize = 200;
+
+const MESSAGE_TYPE_COMMAND: u8 = 1;
+const MESSAGE_TYPE_RESPONSE: u8 = 2;
+const MESSAGE_TYPE_NOTIFICATION: u8 = 3;
+const HEADER_LEN: usize = 4;
+
+/// A UCI command passed to the HAL, and what followed it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct HalTransaction {
+    pub correlation_id: u32,
+    pub gid: u8,
+    pub oid: u8,
+    /// When the last segment of the command was passed to the HAL.
+    pub sent_at: Instant,
+    /// How long the HAL took to accept the command, None if it failed to.
+    pub hal_send_duration: Option<Duration>,
+    /// How long the response took to come back from the HAL, None until it does.
+    pub response_latency: Option<Duration>,
+    /// Number of notifications received from the HAL since the command, until the next one.
+    pub notification_count: u32,
+    /// How long the first of these notifications took to come back from the HAL.
+    pub first_notification_latency: Option<Duration>,
+}
+
+/// The last transactions exchanged with the HAL of a chip, oldest first. Each command gets a
+/// correlation ID, which tags the response and the notifications which follow it in the logs.
+#[derive(Debug, Default)]
+pub(crate) struct HalTrace {
+    transactions: VecDeque<HalTransaction>,
+    next_correlation_id: u32,
+}
+
+impl HalTrace {
+    /// Records a command passed to the HAL at `sent_at`, which took until `now` to be accepted.
+    /// Returns its correlation ID.
+    pub fn on_command(
+        &mut self,
+        gid: u8,
+        oid: u8,
+        sent_at: Instant,
+        now: Instant,
+        accepted: bool,
+    ) -> u32 {
+        let correlation_id = self.next_correlation_id;
+        self.next_correlation_id = self.next_correlation_id.wrapping_add(1);
+        if self.transactions.len() == HAL_TRACE_CAPACITY {
+            self.transactions.pop_front();
+        }
+        self.transactions.push_back(HalTransaction {
+            correlation_id,
+            gid,
+            oid,
+            sent_at,
+            hal_send_duration: accepted.then(|| now.saturating_duration_since(sent_at)),
+            response_latency: None,
+            notification_count: 0,
+            first_notification_latency: None,
+        });
+        correlation_id
+    }
+
+    /// Records a response received at `now`. Returns the correlation ID of its command, if it
+    /// is the last one passed to the HAL and still awaiting it.
+    pub fn on_response(&mut self, gid: u8, oid: u8, now: Instant) -> Option<u32> {
+        let transaction = self.transactions.back_mut()?;
+        if transaction.gid != gid
+            || transaction.oid != oid
+            || transaction.response_latency.is_some()
+        {
+            return None;
+        }
+        transaction.response_latency = Some(now.saturating_duration_since(transaction.sent_at));
+        Some(transaction.correlation_id)
+    }
+
+    /// Records a notification received at `now`. Returns the correlation ID of the last command
+    /// passed to the HAL, which it is accounted to.
+    pub fn on_notification(&mut self, now: Instant) -> Option<u32> {
+        let transaction = self.transactions.back_mut()?;
+        transaction.notification_count = transaction.notification_count.saturating_add(1);
+        if transaction.first_notification_latency.is_none() {
+            transaction.first_notification_latency =
+                Some(now.saturating_duration_since(transaction.sent_at));
+        }
+        Some(transaction.correlation_id)
+    }
+
+    /// Gets the transactions kept, oldest first.
+    pub fn snapshot(&self) -> Vec<HalTransaction> {
+        self.transactions.iter().cloned().collect()
+    }
+}
+
+/// Gets the message type, GID and OID of a UCI control packet, and whether it is the last
+/// segment of its message. None for the data packets.
+fn parse_control_header(packet: &[u8]) -> Option<(u8, u8, u8, bool)> {
+    let header = packet.get(..HEADER_LEN)?;
+    let message_type = header[0] >> 5;
+    if message_type != MESSAGE_TYPE_COMMAND
+        && message_type != MESSAGE_TYPE_RESPONSE
+        && message_type != MESSAGE_TYPE_NOTIFICATION
+    {
+        return None;
+    }
+    let last_segment = header[0] & 0x10 == 0;
+    Some((message_type, header[0] & 0x0f, header[1] & 0x3f, last_segment))
+}
+
+fn lock_trace(trace: &Mutex<HalTrace>) -> Option<MutexGuard<HalTrace>> {
+    match trace.lock() {
+        Ok(trace) => Some(trace),
+        Err(e) => {
+            error!("UCI JNI: failed to lock HAL trace: {:?}", e);
+            None
+        }
+    }
+}
+
+/// Records a packet received from the HAL.
+fn trace_received_packet(trace: &Mutex<HalTrace>, packet: &[u8]) {
+    let (message_type, gid, oid) = match parse_control_header(packet) {
+        Some((message_type, gid, oid, true)) => (message_type, gid, oid),
+        _ => return,
+    };
+    let now = Instant::now();
+    latency_trace::instant(|| format!("UWB HAL read GID {:#x} OID {:#x}", gid, oid));
+    let mut trace = match lock_trace(trace) {
+        Some(trace) => trace,
+        None => return,
+    };
+    if message_type == MESSAGE_TYPE_RESPONSE {
+        if let Some(correlation_id) = trace.on_response(gid, oid, now) {
+            debug!("UCI HAL trace #{}: response GID {:#x} OID {:#x}", correlation_id, gid, oid);
+        }
+    } else if message_type == MESSAGE_TYPE_NOTIFICATION {
+        if let Some(correlation_id) = trace.on_notification(now) {
+            debug!("UCI HAL trace #{}: notification GID {:#x} OID {:#x}", correlation_id, gid, oid);
+        }
+    }
+}
+
+/// UciHal which traces the UCI transactions exchanged with `inner` into the shared trace.
+pub(crate) struct TracingUciHal<H: UciHal> {
+    inner: H,
+    trace: Arc<Mutex<HalTrace>>,
+}
+
+impl<H: UciHal> TracingUciHal<H> {
+    /// Constructs the HAL.
+    pub fn new(inner: H, trace: Arc<Mutex<HalTrace>>) -> Self {
+        Self { inner, trace }
+    }
+}
+
+#[async_trait]
+impl<H: UciHal> UciHal for TracingUciHal<H> {
+    async fn open(&mut self, packet_sender: mpsc::UnboundedSender<UciHalPacket>) -> Result<()> {
+        // The packets from the HAL are traced on their way to the UciManager. The forwarding
+        // ends once the HAL drops its sender, when it is closed.
+        let (traced_sender, mut traced_receiver) = mpsc::unbounded_channel::<UciHalPacket>();
+        let trace = self.trace.clone();
+        tokio::spawn(async move {
+            while let Some(packet) = traced_receiver.recv().await {
+                trace_received_packet(&trace, &packet);
+                if packet_sender.send(packet).is_err() {
+                    break;
+                }
+            }
+        });
+        self.inner.open(traced_sender).await
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.inner.close().await
+    }
+
+    async fn send_packet(&mut self, packet: UciHalPacket) -> Result<()> {
+        let command = match parse_control_header(&packet) {
+            Some((MESSAGE_TYPE_COMMAND, gid, oid, true)) => Some((gid, oid)),
+            _ => None,
+        };
+        let sent_at = Instant::now();
+        let result = self.inner.send_packet(packet).await;
+        if let Some((gid, oid)) = command {
+            let now = Instant::now();
+            if let Some(mut trace) = lock_trace(&self.trace) {
+                let correlation_id = trace.on_command(gid, oid, sent_at, now, result.is_ok());
+                debug!(
+                    "UCI HAL trace #{}: command GID {:#x} OID {:#x} sent in {:?}, {:?}",
+                    correlation_id,
+                    gid,
+                    oid,
+                    now.saturating_duration_since(sent_at),
+                    result
+                );
+            }
+        }
+        result
+    }
+
+    async fn notify_session_initialized(&mut self, session_id: u32) -> Result<()> {
+        self.inner.notify_session_initialized(session_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_control_header() {
+        assert_eq!(parse_control_header(&[0x21, 0x03, 0x00, 0x00]), Some((1, 0x1, 0x3, true)));
+        assert_eq!(parse_control_header(&[0x31, 0x03, 0x00, 0x00]), Some((1, 0x1, 0x3, false)));
+        assert_eq!(parse_control_header(&[0x61, 0x02, 0x00, 0x00]), Some((3, 0x1, 0x2, true)));
+        // Data packets are not traced.
+        assert_eq!(parse_control_header(&[0x01, 0x00, 0x00, 0x00]), None);
+        assert_eq!(parse_control_header(&[0x21]), None);
+    }
+
+    #[test]
+    fn test_transaction() {
+        let mut trace = HalTrace::default();
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        assert_eq!(trace.on_notification(start), None);
+
+        let id = trace.on_command(0x1, 0x3, start, start + ms(1), true);
+        // Another command's response is not matched.
+        assert_eq!(trace.on_response(0x1, 0x4, start + ms(2)), None);
+        assert_eq!(trace.on_response(0x1, 0x3, start + ms(5)), Some(id));
+        assert_eq!(trace.on_response(0x1, 0x3, start + ms(6)), None);
+        assert_eq!(trace.on_notification(start + ms(8)), Some(id));
+        assert_eq!(trace.on_notification(start + ms(9)), Some(id));
+
+        let transactions = trace.snapshot();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].hal_send_duration, Some(ms(1)));
+        assert_eq!(transactions[0].response_latency, Some(ms(5)));
+        assert_eq!(transactions[0].notification_count, 2);
+        assert_eq!(transactions[0].first_notification_latency, Some(ms(8)));
+
+        let id = trace.on_command(0x1, 0x3, start + ms(10), start + ms(11), false);
+        assert_eq!(trace.snapshot()[1].correlation_id, id);
+        assert_eq!(trace.snapshot()[1].hal_send_duration, None);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut trace = HalTrace::default();
+        let start = Instant::now();
+        for _ in 0..HAL_TRACE_CAPACITY + 5 {
+            trace.on_command(0x0, 0x0, start, start, true);
+        }
+        let transactions = trace.snapshot();
+        assert_eq!(transactions.len(), HAL_TRACE_CAPACITY);
+        assert_eq!(transactions[0].correlation_id, 5);
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The optional Java classes and callbacks, which older framework builds may lack.
+
+use std::collections::HashSet;
+
+use log::error;
+
+/// A group of Java classes and callback methods needed by one kind of notification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum JavaSupport {
+    /// UwbRadarData, UwbRadarSweepData and onRadarDataMessageReceived.
+    RadarData,
+    /// UwbDlTDoAMeasurement.
+    DlTdoaMeasurement,
+    /// UwbOwrAoaMeasurement.
+    OwrAoaMeasurement,
+    /// onProtocolError.
+    ProtocolError,
+    /// onSessionStateTransitionViolation.
+    SessionTransitionViolation,
+    /// onRangeKeepAlive.
+    RangeKeepAlive,
+}
+
+impl JavaSupport {
+    pub const ALL: [JavaSupport; 6] = [
+        JavaSupport::RadarData,
+        JavaSupport::DlTdoaMeasurement,
+        JavaSupport::OwrAoaMeasurement,
+        JavaSupport::ProtocolError,
+        JavaSupport::SessionTransitionViolation,
+        JavaSupport::RangeKeepAlive,
+    ];
+}
+
+/// What the Java side of a chip supports, probed once when its callbacks are registered. Until
+/// probed, everything is assumed to be supported.
+#[derive(Debug, Default)]
+pub(crate) struct JavaCapabilities {
+    missing: HashSet<JavaSupport>,
+    /// The missing supports which were already logged.
+    logged: HashSet<JavaSupport>,
+}
+
+impl JavaCapabilities {
+    /// Probes every support with `is_supported`.
+    pub fn probe(mut is_supported: impl FnMut(JavaSupport) -> bool) -> Self {
+        let missing =
+            JavaSupport::ALL.iter().copied().filter(|&support| !is_supported(support)).collect();
+        Self { missing, logged: HashSet::new() }
+    }
+
+    /// Returns whether `support` is available. The first time it is found missing, logs that the
+    /// notification is `downgrade`d, e.g. "skipped".
+    pub fn check(&mut self, support: JavaSupport, downgrade: &str) -> bool {
+        if !self.missing.contains(&support) {
+            return true;
+        }
+        if self.logged.insert(support) {
+            error!(
+                "UCI JNI: Java support of {:?} is missing, notifications are {}.",
+                support, downgrade
+            );
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_everything_supported_by_default() {
+        let mut capabilities = JavaCapabilities::default();
+        for support in JavaSupport::ALL {
+            assert!(capabilities.check(support, "skipped"));
+        }
+    }
+
+    #[test]
+    fn test_probe() {
+        let mut capabilities = JavaCapabilities::probe(|support| support != JavaSupport::RadarData);
+        assert!(!capabilities.check(JavaSupport::RadarData, "skipped"));
+        assert!(!capabilities.check(JavaSupport::RadarData, "skipped"));
+        assert_eq!(capabilities.logged, HashSet::from([JavaSupport::RadarData]));
+        assert!(capabilities.check(JavaSupport::OwrAoaMeasurement, "skipped"));
+    }
+}
@@ -13,11 +13,26 @@
 // limitations under the License.
 
 //! Name of java classes for UWB response and notifications:
+pub(crate) const ABORTED_DATA_TRANSFERS_CLASS: &str =
+    "com/android/server/uwb/info/UwbAbortedDataTransfers";
 pub(crate) const CONFIG_STATUS_DATA_CLASS: &str = "com/android/server/uwb/data/UwbConfigStatusData";
+pub(crate) const HAL_TRANSACTION_CLASS: &str = "com/android/server/uwb/info/UwbHalTransaction";
 pub(crate) const MULTICAST_LIST_UPDATE_STATUS_CLASS: &str =
     "com/android/server/uwb/data/UwbMulticastListUpdateStatus";
+pub(crate) const NOTIFICATION_STATS_CLASS: &str =
+    "com/android/server/uwb/info/UwbNotificationStats";
+pub(crate) const OWR_AOA_ADVERTISER_INFO_CLASS: &str =
+    "com/android/server/uwb/info/UwbOwrAoaAdvertiserInfo";
+pub(crate) const PARSE_ERROR_STATS_CLASS: &str = "com/android/server/uwb/info/UwbParseErrorStats";
 pub(crate) const POWER_STATS_CLASS: &str = "com/android/server/uwb/info/UwbPowerStats";
+pub(crate) const RANGING_ATTESTATION_CLASS: &str =
+    "com/android/server/uwb/info/UwbRangingAttestation";
+pub(crate) const SESSION_MISMATCH_CLASS: &str = "com/android/server/uwb/info/UwbSessionMismatch";
+pub(crate) const SESSION_RECORD_CLASS: &str = "com/android/server/uwb/info/UwbSessionRecord";
+pub(crate) const SESSION_TOKEN_MAPPING_CLASS: &str =
+    "com/android/server/uwb/info/UwbSessionTokenMapping";
 pub(crate) const TLV_DATA_CLASS: &str = "com/android/server/uwb/data/UwbTlvData";
+pub(crate) const UCI_COMMAND_STATS_CLASS: &str = "com/android/server/uwb/info/UwbUciCommandStats";
 pub(crate) const UWB_DEVICE_INFO_RESPONSE_CLASS: &str =
     "com/android/server/uwb/data/UwbDeviceInfoResponse";
 pub(crate) const UWB_RANGING_DATA_CLASS: &str = "com/android/server/uwb/data/UwbRangingData";
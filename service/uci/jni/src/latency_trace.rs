@@ -0,0 +1,130 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ATrace markers of the stages the notifications go through in the native stack, so that the
+//! systrace and Perfetto captures show where the latency of the ranging notifications is spent.
+//! The markers are written to the trace marker of the kernel in the format of libcutils, while
+//! the native_latency_tracing flag is enabled.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::process;
+
+use lazy_static::lazy_static;
+use log::error;
+
+use crate::feature_flags::{self, FeatureFlag};
+
+const TRACE_MARKER_PATHS: [&str; 2] =
+    ["/sys/kernel/tracing/trace_marker", "/sys/kernel/debug/tracing/trace_marker"];
+
+lazy_static! {
+    /// The trace marker, opened on the first marker written. None if it cannot be opened.
+    static ref TRACE_MARKER: Option<File> = open_trace_marker();
+}
+
+fn open_trace_marker() -> Option<File> {
+    let file =
+        TRACE_MARKER_PATHS.iter().find_map(|path| OpenOptions::new().write(true).open(path).ok());
+    if file.is_none() {
+        error!("UCI JNI: failed to open the trace marker, the latency is not traced");
+    }
+    file
+}
+
+fn begin_record(pid: u32, name: &str) -> String {
+    format!("B|{}|{}", pid, name)
+}
+
+fn end_record(pid: u32) -> String {
+    format!("E|{}", pid)
+}
+
+fn instant_record(pid: u32, name: &str) -> String {
+    format!("I|{}|{}", pid, name)
+}
+
+/// Writes a record to the trace marker, in a single write so that it is not interleaved with
+/// the records of the other threads.
+fn write_record(record: String) {
+    if let Some(mut file) = TRACE_MARKER.as_ref() {
+        // A marker lost is not worth a log per notification.
+        let _ = file.write_all(record.as_bytes());
+    }
+}
+
+fn is_enabled() -> bool {
+    feature_flags::is_enabled(FeatureFlag::LatencyTracing)
+}
+
+/// Marks an instant of the calling thread. `name` is only built when the latency is traced.
+pub(crate) fn instant<F: FnOnce() -> String>(name: F) {
+    if is_enabled() {
+        write_record(instant_record(process::id(), &name()));
+    }
+}
+
+/// A section of the calling thread, from its beginning until it is dropped.
+pub(crate) struct TraceSection {
+    begun: bool,
+}
+
+impl TraceSection {
+    /// Begins a section. `name` is only built when the latency is traced.
+    pub fn begin<F: FnOnce() -> String>(name: F) -> Self {
+        let begun = is_enabled();
+        if begun {
+            write_record(begin_record(process::id(), &name()));
+        }
+        Self { begun }
+    }
+}
+
+impl Drop for TraceSection {
+    fn drop(&mut self) {
+        // Ended even if the flag was disabled meanwhile, so that the section is closed.
+        if self.begun {
+            write_record(end_record(process::id()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        assert_eq!(
+            begin_record(1234, "UWB dispatch SessionInfo session 0x1"),
+            "B|1234|UWB dispatch SessionInfo session 0x1"
+        );
+        assert_eq!(end_record(1234), "E|1234");
+        assert_eq!(
+            instant_record(1234, "UWB HAL read GID 0x2 OID 0x0"),
+            "I|1234|UWB HAL read GID 0x2 OID 0x0"
+        );
+    }
+
+    #[test]
+    fn test_disabled_section_is_not_begun() {
+        let mut name_built = false;
+        let section = TraceSection::begin(|| {
+            name_built = true;
+            String::new()
+        });
+        assert!(!section.begun);
+        assert!(!name_built);
+    }
+}
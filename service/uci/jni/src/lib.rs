@@ -18,10 +18,63 @@
 //! UciManager. In conjunction with libuci_hal_android and libuwb_core, this provides a replacement
 //! for libuwb_uci_jni_rust.
 
+mod advertiser_tracker;
+mod antenna_config;
+mod antenna_pair;
+mod calibration_store;
+mod callback_slot;
+mod controlee_batching;
+mod data_rcv_jitter;
+mod data_tx_journal;
+mod device_config;
 mod dispatcher;
+mod dl_tdoa_anchor_location;
+mod feature_flags;
+mod firmware_log;
+mod global_ref_accounting;
+mod hal_trace;
 mod helper;
+mod java_capabilities;
 mod jclass_name;
+mod latency_trace;
+mod location_privacy;
+mod measurement_conversion;
+mod measurement_units;
+mod metrics_exporter;
+mod notification_bus;
 mod notification_manager_android;
+mod notification_queue;
+mod notification_replay;
+mod notification_stats;
+mod parse_error_stats;
+mod persistent_storage;
+mod protocol_error;
+mod proximity;
+mod radar_config;
+mod radar_decimation;
+mod ranging_advisor;
+mod ranging_attestation;
+mod ranging_diagnostics;
+mod retry_storm;
+mod role_switch;
+mod rssi;
+mod secret_bytes;
+mod session_aggregation;
+mod session_delivery;
+mod session_extras;
+mod session_notification_filter;
+mod session_registry;
+mod session_token_audit;
+mod session_transition;
+mod uci_capture;
+mod uci_injection;
+mod uci_log_redaction;
+mod uci_log_trigger;
+mod uci_metrics;
+mod uci_proxy;
+mod uci_recording;
 mod unique_jvm;
+mod vendor_commands;
+mod vendor_notification_router;
 
 pub mod uci_jni_android_new;
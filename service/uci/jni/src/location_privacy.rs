@@ -0,0 +1,268 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Coarse location privacy of the range data, reducing their precision before they cross JNI
+//! for the sessions of the apps which may not locate their peers precisely.
+
+use uwb_core::uci::{RangingMeasurements, SessionRangeData};
+use uwb_uci_packets::DlTdoaRangingMeasurement;
+
+/// Distance step of the coarse location privacy, in centimeters, when the service sets none.
+pub(crate) const DEFAULT_DISTANCE_STEP_CM: u16 = 50;
+
+/// Location type bits of the Message Control field of the DL-TDoA measurements.
+const DT_ANCHOR_LOCATION_TYPE_MASK: u16 = 0x3 << 5;
+
+/// Rounds a distance to the nearest multiple of `step_cm`. The distances beyond the last
+/// multiple which fits are rounded down to it.
+fn quantize_distance(distance: u16, step_cm: u16) -> u16 {
+    if step_cm <= 1 {
+        return distance;
+    }
+    let step = u32::from(step_cm);
+    let rounded = (u32::from(distance) + step / 2) / step * step;
+    u16::try_from(rounded).unwrap_or(distance / step_cm * step_cm)
+}
+
+/// Drops everything a DL-TDoA measurement carries which locates the device: the angles, the
+/// timestamps and reply times the position is derived from, and the DT-Anchor location.
+fn coarsen_dl_tdoa_measurement(measurement: &mut DlTdoaRangingMeasurement) {
+    measurement.aoa_azimuth = 0;
+    measurement.aoa_azimuth_fom = 0;
+    measurement.aoa_elevation = 0;
+    measurement.aoa_elevation_fom = 0;
+    measurement.tx_timestamp = 0;
+    measurement.rx_timestamp = 0;
+    measurement.anchor_cfo = 0;
+    measurement.cfo = 0;
+    measurement.initiator_reply_time = 0;
+    measurement.responder_reply_time = 0;
+    measurement.initiator_responder_tof = 0;
+    measurement.message_control &= !DT_ANCHOR_LOCATION_TYPE_MASK;
+    measurement.dt_anchor_location.clear();
+}
+
+/// Reduces the precision of range data in place: the distances are quantized to `step_cm`, the
+/// AoA are stripped with their figures of merit, the DL-TDoA measurements lose their timestamps
+/// and anchor locations, and the raw notification, which holds the precise values, is cleared.
+pub(crate) fn coarsen_range_data(range_data: &mut SessionRangeData, step_cm: u16) {
+    macro_rules! coarsen_two_way {
+        ($measurements:expr) => {
+            for measurement in $measurements.iter_mut() {
+                measurement.distance = quantize_distance(measurement.distance, step_cm);
+                measurement.aoa_azimuth = 0;
+                measurement.aoa_azimuth_fom = 0;
+                measurement.aoa_elevation = 0;
+                measurement.aoa_elevation_fom = 0;
+                measurement.aoa_destination_azimuth = 0;
+                measurement.aoa_destination_azimuth_fom = 0;
+                measurement.aoa_destination_elevation = 0;
+                measurement.aoa_destination_elevation_fom = 0;
+            }
+        };
+    }
+    macro_rules! coarsen_owr_aoa {
+        ($measurements:expr) => {
+            for measurement in $measurements.iter_mut() {
+                measurement.aoa_azimuth = 0;
+                measurement.aoa_azimuth_fom = 0;
+                measurement.aoa_elevation = 0;
+                measurement.aoa_elevation_fom = 0;
+            }
+        };
+    }
+    match &mut range_data.ranging_measurements {
+        RangingMeasurements::ShortAddressTwoWay(measurements) => coarsen_two_way!(measurements),
+        RangingMeasurements::ExtendedAddressTwoWay(measurements) => coarsen_two_way!(measurements),
+        RangingMeasurements::ShortAddressOwrAoa(measurements) => coarsen_owr_aoa!(measurements),
+        RangingMeasurements::ExtendedAddressOwrAoa(measurements) => coarsen_owr_aoa!(measurements),
+        RangingMeasurements::ShortAddressDltdoa(measurements) => {
+            for measurement in measurements.iter_mut() {
+                coarsen_dl_tdoa_measurement(&mut measurement.measurement);
+            }
+        }
+        RangingMeasurements::ExtendedAddressDltdoa(measurements) => {
+            for measurement in measurements.iter_mut() {
+                coarsen_dl_tdoa_measurement(&mut measurement.measurement);
+            }
+        }
+    }
+    range_data.raw_ranging_data.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uwb_uci_packets::{
+        ExtendedAddressOwrAoaRangingMeasurement, RangingMeasurementType,
+        ShortAddressDlTdoaRangingMeasurement, ShortAddressTwoWayRangingMeasurement, StatusCode,
+    };
+
+    fn range_data(ranging_measurements: RangingMeasurements) -> SessionRangeData {
+        SessionRangeData {
+            sequence_number: 1,
+            session_token: 2,
+            current_ranging_interval_ms: 100,
+            ranging_measurement_type: RangingMeasurementType::TwoWay,
+            ranging_measurements,
+            rcr_indicator: 0,
+            raw_ranging_data: vec![0x62, 0x00, 0x00, 0x04, 0x12, 0x34, 0x56, 0x78],
+        }
+    }
+
+    #[test]
+    fn test_quantize_distance() {
+        assert_eq!(quantize_distance(0, 50), 0);
+        assert_eq!(quantize_distance(24, 50), 0);
+        assert_eq!(quantize_distance(25, 50), 50);
+        assert_eq!(quantize_distance(137, 50), 150);
+        assert_eq!(quantize_distance(174, 50), 150);
+        // The last multiple of the step which fits is 65500.
+        assert_eq!(quantize_distance(65530, 50), 65500);
+        assert_eq!(quantize_distance(u16::MAX, 50), 65500);
+        assert_eq!(quantize_distance(137, 1), 137);
+        assert_eq!(quantize_distance(137, 0), 137);
+    }
+
+    #[test]
+    fn test_coarsen_two_way_range_data() {
+        let measurement = ShortAddressTwoWayRangingMeasurement {
+            mac_address: 0x1234,
+            status: StatusCode::UciStatusOk,
+            nlos: 1,
+            distance: 137,
+            aoa_azimuth: 0x1234,
+            aoa_azimuth_fom: 90,
+            aoa_elevation: 0x0567,
+            aoa_elevation_fom: 80,
+            aoa_destination_azimuth: 0x2345,
+            aoa_destination_azimuth_fom: 70,
+            aoa_destination_elevation: 0x0678,
+            aoa_destination_elevation_fom: 60,
+            slot_index: 3,
+            rssi: 150,
+        };
+        let mut data =
+            range_data(RangingMeasurements::ShortAddressTwoWay(vec![measurement.clone()]));
+        coarsen_range_data(&mut data, DEFAULT_DISTANCE_STEP_CM);
+
+        assert_eq!(
+            data.ranging_measurements,
+            RangingMeasurements::ShortAddressTwoWay(vec![ShortAddressTwoWayRangingMeasurement {
+                distance: 150,
+                aoa_azimuth: 0,
+                aoa_azimuth_fom: 0,
+                aoa_elevation: 0,
+                aoa_elevation_fom: 0,
+                aoa_destination_azimuth: 0,
+                aoa_destination_azimuth_fom: 0,
+                aoa_destination_elevation: 0,
+                aoa_destination_elevation_fom: 0,
+                ..measurement
+            }])
+        );
+        // The raw notification would give the precise values away.
+        assert!(data.raw_ranging_data.is_empty());
+        assert_eq!(data.session_token, 2);
+    }
+
+    #[test]
+    fn test_coarsen_owr_aoa_range_data() {
+        let measurement = ExtendedAddressOwrAoaRangingMeasurement {
+            mac_address: 0x1234_5678,
+            status: StatusCode::UciStatusOk,
+            nlos: 0,
+            frame_sequence_number: 1,
+            block_index: 2,
+            aoa_azimuth: 0x1234,
+            aoa_azimuth_fom: 90,
+            aoa_elevation: 0x0567,
+            aoa_elevation_fom: 80,
+        };
+        let mut data =
+            range_data(RangingMeasurements::ExtendedAddressOwrAoa(vec![measurement.clone()]));
+        coarsen_range_data(&mut data, DEFAULT_DISTANCE_STEP_CM);
+
+        assert_eq!(
+            data.ranging_measurements,
+            RangingMeasurements::ExtendedAddressOwrAoa(vec![
+                ExtendedAddressOwrAoaRangingMeasurement {
+                    aoa_azimuth: 0,
+                    aoa_azimuth_fom: 0,
+                    aoa_elevation: 0,
+                    aoa_elevation_fom: 0,
+                    ..measurement
+                }
+            ])
+        );
+        assert!(data.raw_ranging_data.is_empty());
+    }
+
+    #[test]
+    fn test_coarsen_dl_tdoa_range_data() {
+        let measurement = ShortAddressDlTdoaRangingMeasurement {
+            mac_address: 0x1234,
+            measurement: DlTdoaRangingMeasurement {
+                status: 0,
+                message_type: 1,
+                // WGS-84 anchor location included.
+                message_control: 0x0023,
+                block_index: 2,
+                round_index: 3,
+                nlos: 0,
+                aoa_azimuth: 0x1234,
+                aoa_azimuth_fom: 90,
+                aoa_elevation: 0x0567,
+                aoa_elevation_fom: 80,
+                rssi: 150,
+                tx_timestamp: 0x1122_3344_5566,
+                rx_timestamp: 0x2233_4455_6677,
+                anchor_cfo: 0x10,
+                cfo: 0x20,
+                initiator_reply_time: 0x3000,
+                responder_reply_time: 0x4000,
+                initiator_responder_tof: 0x50,
+                dt_anchor_location: vec![0xab; 12],
+                ranging_rounds: vec![1, 2],
+            },
+        };
+        let mut data =
+            range_data(RangingMeasurements::ShortAddressDltdoa(vec![measurement.clone()]));
+        coarsen_range_data(&mut data, DEFAULT_DISTANCE_STEP_CM);
+
+        assert_eq!(
+            data.ranging_measurements,
+            RangingMeasurements::ShortAddressDltdoa(vec![ShortAddressDlTdoaRangingMeasurement {
+                mac_address: 0x1234,
+                measurement: DlTdoaRangingMeasurement {
+                    message_control: 0x0003,
+                    aoa_azimuth: 0,
+                    aoa_azimuth_fom: 0,
+                    aoa_elevation: 0,
+                    aoa_elevation_fom: 0,
+                    tx_timestamp: 0,
+                    rx_timestamp: 0,
+                    anchor_cfo: 0,
+                    cfo: 0,
+                    initiator_reply_time: 0,
+                    responder_reply_time: 0,
+                    initiator_responder_tof: 0,
+                    dt_anchor_location: vec![],
+                    ..measurement.measurement
+                },
+            }])
+        );
+        assert!(data.raw_ranging_data.is_empty());
+    }
+}
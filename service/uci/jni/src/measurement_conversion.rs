@@ -0,0 +1,1032 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion of the range data to the arguments of the Java measurement and range data
+//! constructors, as pure functions which do not need JNI. NotificationManagerAndroid builds the
+//! Java objects from these arguments, which other notification sinks may use as well.
+
+use crate::antenna_pair::parse_antenna_pair_ids;
+use crate::dl_tdoa_anchor_location::{
+    parse_anchor_location, AnchorLocation, ANCHOR_LOCATION_TYPE_NONE,
+};
+use crate::measurement_units::{aoa_azimuth_to_java, aoa_elevation_to_java, fom_to_java};
+use crate::rssi::Rssi;
+
+use uwb_core::uci::{RangingMeasurements, SessionRangeData};
+use uwb_uci_packets::{
+    ExtendedAddressDlTdoaRangingMeasurement, ExtendedAddressOwrAoaRangingMeasurement,
+    ExtendedAddressTwoWayRangingMeasurement, MacAddressIndicator,
+    ShortAddressDlTdoaRangingMeasurement, ShortAddressOwrAoaRangingMeasurement,
+    ShortAddressTwoWayRangingMeasurement, StatusCode,
+};
+
+/// UwbTwoWayMeasurement.ANTENNA_PAIR_ID_UNKNOWN
+pub(crate) const ANTENNA_PAIR_ID_UNKNOWN: i32 = -1;
+/// Signature of the UwbTwoWayMeasurement constructor.
+pub(crate) const TWO_WAY_MEASUREMENT_SIGNATURE: &str = "([BIIIIIIIIIIIIFII)V";
+/// Signature of the UwbOwrAoaMeasurement constructor.
+pub(crate) const OWR_AOA_MEASUREMENT_SIGNATURE: &str = "([BIIIIIIII)V";
+/// Signature of the UwbDlTDoAMeasurement constructor.
+pub(crate) const DL_TDOA_MEASUREMENT_SIGNATURE: &str = "([BIIIIIIIIIIIJJIIJJI[B[BIDDD)V";
+
+/// Length of the SESSION_INFO_NTF payload preceding the ranging measurements, the last octet of
+/// which is the number of measurements.
+const SESSION_INFO_HEADER_LEN: usize = 25;
+/// Length of an OWR AoA measurement without its MAC address: status, NLoS, frame sequence number,
+/// block index (2), AoA azimuth (2) and FoM, AoA elevation (2) and FoM.
+const OWR_AOA_MEASUREMENT_LEN: usize = 11;
+
+/// An argument of a Java constructor. The byte arrays are held as plain bytes until the Java
+/// object is built.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum JavaArg {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Bytes(Vec<u8>),
+}
+
+/// The MAC address of a measurement.
+#[derive(Debug, PartialEq)]
+pub(crate) enum MacAddress {
+    Short(u16),
+    Extended(u64),
+}
+impl MacAddress {
+    /// Returns the bytes of the address, in the order they are passed to Java.
+    pub fn to_ne_bytes(&self) -> Vec<u8> {
+        match self {
+            MacAddress::Short(val) => val.to_ne_bytes().into(),
+            MacAddress::Extended(val) => val.to_ne_bytes().into(),
+        }
+    }
+}
+
+/// A two-way ranging measurement, of either MAC address size.
+pub(crate) struct TwoWayRangingMeasurement {
+    pub mac_address: MacAddress,
+    pub status: StatusCode,
+    pub nlos: u8,
+    pub distance: u16,
+    pub aoa_azimuth: u16,
+    pub aoa_azimuth_fom: u8,
+    pub aoa_elevation: u16,
+    pub aoa_elevation_fom: u8,
+    pub aoa_destination_azimuth: u16,
+    pub aoa_destination_azimuth_fom: u8,
+    pub aoa_destination_elevation: u16,
+    pub aoa_destination_elevation_fom: u8,
+    pub slot_index: u8,
+    pub rssi: u8,
+    /// The antenna pair which produced the AoA, for the chips reporting it.
+    pub antenna_pair_id: Option<u8>,
+}
+
+/// A one-way AoA measurement, of either MAC address size.
+pub(crate) struct OwrAoaRangingMeasurement {
+    pub mac_address: MacAddress,
+    pub status: StatusCode,
+    pub nlos: u8,
+    pub frame_sequence_number: u8,
+    pub block_index: u16,
+    pub aoa_azimuth: u16,
+    pub aoa_azimuth_fom: u8,
+    pub aoa_elevation: u16,
+    pub aoa_elevation_fom: u8,
+}
+
+impl From<ShortAddressTwoWayRangingMeasurement> for TwoWayRangingMeasurement {
+    fn from(measurement: ShortAddressTwoWayRangingMeasurement) -> Self {
+        TwoWayRangingMeasurement {
+            mac_address: MacAddress::Short(measurement.mac_address),
+            status: (measurement.status),
+            nlos: (measurement.nlos),
+            distance: (measurement.distance),
+            aoa_azimuth: (measurement.aoa_azimuth),
+            aoa_azimuth_fom: (measurement.aoa_azimuth_fom),
+            aoa_elevation: (measurement.aoa_elevation),
+            aoa_elevation_fom: (measurement.aoa_elevation_fom),
+            aoa_destination_azimuth: (measurement.aoa_destination_azimuth),
+            aoa_destination_azimuth_fom: (measurement.aoa_destination_azimuth_fom),
+            aoa_destination_elevation: (measurement.aoa_destination_elevation),
+            aoa_destination_elevation_fom: (measurement.aoa_destination_elevation_fom),
+            slot_index: (measurement.slot_index),
+            rssi: (measurement.rssi),
+            antenna_pair_id: None,
+        }
+    }
+}
+
+impl From<ExtendedAddressTwoWayRangingMeasurement> for TwoWayRangingMeasurement {
+    fn from(measurement: ExtendedAddressTwoWayRangingMeasurement) -> Self {
+        TwoWayRangingMeasurement {
+            mac_address: MacAddress::Extended(measurement.mac_address),
+            status: (measurement.status),
+            nlos: (measurement.nlos),
+            distance: (measurement.distance),
+            aoa_azimuth: (measurement.aoa_azimuth),
+            aoa_azimuth_fom: (measurement.aoa_azimuth_fom),
+            aoa_elevation: (measurement.aoa_elevation),
+            aoa_elevation_fom: (measurement.aoa_elevation_fom),
+            aoa_destination_azimuth: (measurement.aoa_destination_azimuth),
+            aoa_destination_azimuth_fom: (measurement.aoa_destination_azimuth_fom),
+            aoa_destination_elevation: (measurement.aoa_destination_elevation),
+            aoa_destination_elevation_fom: (measurement.aoa_destination_elevation_fom),
+            slot_index: (measurement.slot_index),
+            rssi: (measurement.rssi),
+            antenna_pair_id: None,
+        }
+    }
+}
+
+impl From<ShortAddressOwrAoaRangingMeasurement> for OwrAoaRangingMeasurement {
+    fn from(measurement: ShortAddressOwrAoaRangingMeasurement) -> Self {
+        OwrAoaRangingMeasurement {
+            mac_address: MacAddress::Short(measurement.mac_address),
+            status: (measurement.status),
+            nlos: (measurement.nlos),
+            frame_sequence_number: (measurement.frame_sequence_number),
+            block_index: (measurement.block_index),
+            aoa_azimuth: (measurement.aoa_azimuth),
+            aoa_azimuth_fom: (measurement.aoa_azimuth_fom),
+            aoa_elevation: (measurement.aoa_elevation),
+            aoa_elevation_fom: (measurement.aoa_elevation_fom),
+        }
+    }
+}
+
+impl From<ExtendedAddressOwrAoaRangingMeasurement> for OwrAoaRangingMeasurement {
+    fn from(measurement: ExtendedAddressOwrAoaRangingMeasurement) -> Self {
+        OwrAoaRangingMeasurement {
+            mac_address: MacAddress::Extended(measurement.mac_address),
+            status: (measurement.status),
+            nlos: (measurement.nlos),
+            frame_sequence_number: (measurement.frame_sequence_number),
+            block_index: (measurement.block_index),
+            aoa_azimuth: (measurement.aoa_azimuth),
+            aoa_azimuth_fom: (measurement.aoa_azimuth_fom),
+            aoa_elevation: (measurement.aoa_elevation),
+            aoa_elevation_fom: (measurement.aoa_elevation_fom),
+        }
+    }
+}
+
+/// A DL-TDoA measurement, of either MAC address size.
+pub(crate) struct DlTdoaRangingMeasurement {
+    pub mac_address: MacAddress,
+    pub status: u8,
+    pub message_type: u8,
+    pub message_control: u16,
+    pub block_index: u16,
+    pub round_index: u8,
+    pub nlos: u8,
+    pub aoa_azimuth: u16,
+    pub aoa_azimuth_fom: u8,
+    pub aoa_elevation: u16,
+    pub aoa_elevation_fom: u8,
+    pub rssi: u8,
+    pub tx_timestamp: u64,
+    pub rx_timestamp: u64,
+    pub anchor_cfo: u16,
+    pub cfo: u16,
+    pub initiator_reply_time: u32,
+    pub responder_reply_time: u32,
+    pub initiator_responder_tof: u16,
+    pub dt_anchor_location: Vec<u8>,
+    pub ranging_rounds: Vec<u8>,
+}
+
+impl From<ExtendedAddressDlTdoaRangingMeasurement> for DlTdoaRangingMeasurement {
+    fn from(measurement: ExtendedAddressDlTdoaRangingMeasurement) -> Self {
+        DlTdoaRangingMeasurement {
+            mac_address: MacAddress::Extended(measurement.mac_address),
+            status: (measurement.measurement.status),
+            message_type: (measurement.measurement.message_type),
+            message_control: (measurement.measurement.message_control),
+            block_index: (measurement.measurement.block_index),
+            round_index: (measurement.measurement.round_index),
+            nlos: (measurement.measurement.nlos),
+            aoa_azimuth: (measurement.measurement.aoa_azimuth),
+            aoa_azimuth_fom: (measurement.measurement.aoa_azimuth_fom),
+            aoa_elevation: (measurement.measurement.aoa_elevation),
+            aoa_elevation_fom: (measurement.measurement.aoa_elevation_fom),
+            rssi: (measurement.measurement.rssi),
+            tx_timestamp: (measurement.measurement.tx_timestamp),
+            rx_timestamp: (measurement.measurement.rx_timestamp),
+            anchor_cfo: (measurement.measurement.anchor_cfo),
+            cfo: (measurement.measurement.cfo),
+            initiator_reply_time: (measurement.measurement.initiator_reply_time),
+            responder_reply_time: (measurement.measurement.responder_reply_time),
+            initiator_responder_tof: (measurement.measurement.initiator_responder_tof),
+            dt_anchor_location: (measurement.measurement.dt_anchor_location),
+            ranging_rounds: (measurement.measurement.ranging_rounds),
+        }
+    }
+}
+
+impl From<ShortAddressDlTdoaRangingMeasurement> for DlTdoaRangingMeasurement {
+    fn from(measurement: ShortAddressDlTdoaRangingMeasurement) -> Self {
+        DlTdoaRangingMeasurement {
+            mac_address: MacAddress::Short(measurement.mac_address),
+            status: (measurement.measurement.status),
+            message_type: (measurement.measurement.message_type),
+            message_control: (measurement.measurement.message_control),
+            block_index: (measurement.measurement.block_index),
+            round_index: (measurement.measurement.round_index),
+            nlos: (measurement.measurement.nlos),
+            aoa_azimuth: (measurement.measurement.aoa_azimuth),
+            aoa_azimuth_fom: (measurement.measurement.aoa_azimuth_fom),
+            aoa_elevation: (measurement.measurement.aoa_elevation),
+            aoa_elevation_fom: (measurement.measurement.aoa_elevation_fom),
+            rssi: (measurement.measurement.rssi),
+            tx_timestamp: (measurement.measurement.tx_timestamp),
+            rx_timestamp: (measurement.measurement.rx_timestamp),
+            anchor_cfo: (measurement.measurement.anchor_cfo),
+            cfo: (measurement.measurement.cfo),
+            initiator_reply_time: (measurement.measurement.initiator_reply_time),
+            responder_reply_time: (measurement.measurement.responder_reply_time),
+            initiator_responder_tof: (measurement.measurement.initiator_responder_tof),
+            dt_anchor_location: (measurement.measurement.dt_anchor_location),
+            ranging_rounds: (measurement.measurement.ranging_rounds),
+        }
+    }
+}
+
+/// Takes the two-way measurements of range data, tagged with the antenna pair which produced
+/// their AoA when `tag_antenna_pairs` and the raw notification reports it. None if the range data
+/// are not two-way.
+pub(crate) fn two_way_measurements(
+    ranging_measurements: RangingMeasurements,
+    raw_ranging_data: &[u8],
+    tag_antenna_pairs: bool,
+) -> Option<Vec<TwoWayRangingMeasurement>> {
+    let mut measurements = match ranging_measurements {
+        RangingMeasurements::ShortAddressTwoWay(v) => {
+            v.into_iter().map(TwoWayRangingMeasurement::from).collect::<Vec<_>>()
+        }
+        RangingMeasurements::ExtendedAddressTwoWay(v) => {
+            v.into_iter().map(TwoWayRangingMeasurement::from).collect::<Vec<_>>()
+        }
+        _ => return None,
+    };
+    if tag_antenna_pairs {
+        if let Some(antenna_pair_ids) = parse_antenna_pair_ids(raw_ranging_data, measurements.len())
+        {
+            for (measurement, antenna_pair_id) in measurements.iter_mut().zip(antenna_pair_ids) {
+                measurement.antenna_pair_id = Some(antenna_pair_id);
+            }
+        }
+    }
+    Some(measurements)
+}
+
+/// Takes the OWR AoA measurements of range data. None if the range data are not OWR AoA.
+///
+/// The UCI spec allows an observer to report several advertisers in the same notification, but
+/// uwb_core only decodes the first one. The following ones are parsed from the raw notification,
+/// and are left out if it is missing or malformed.
+pub(crate) fn owr_aoa_measurements(
+    ranging_measurements: RangingMeasurements,
+    raw_ranging_data: &[u8],
+) -> Option<Vec<OwrAoaRangingMeasurement>> {
+    let (first, mac_address_len) = match ranging_measurements {
+        RangingMeasurements::ShortAddressOwrAoa(m) => (m.into(), 2),
+        RangingMeasurements::ExtendedAddressOwrAoa(m) => (m.into(), 8),
+        _ => return None,
+    };
+    let mut measurements = vec![first];
+    measurements.extend(
+        parse_owr_aoa_measurements(raw_ranging_data, mac_address_len)
+            .map(|parsed| parsed.into_iter().skip(1).collect::<Vec<_>>())
+            .unwrap_or_default(),
+    );
+    Some(measurements)
+}
+
+/// Parses all the OWR AoA measurements of a raw SESSION_INFO_NTF payload. None if the payload is
+/// truncated or one of the statuses is unknown.
+fn parse_owr_aoa_measurements(
+    raw_ranging_data: &[u8],
+    mac_address_len: usize,
+) -> Option<Vec<OwrAoaRangingMeasurement>> {
+    let count = usize::from(*raw_ranging_data.get(SESSION_INFO_HEADER_LEN - 1)?);
+    let measurement_len = mac_address_len + OWR_AOA_MEASUREMENT_LEN;
+    let measurements = raw_ranging_data
+        .get(SESSION_INFO_HEADER_LEN..SESSION_INFO_HEADER_LEN + count * measurement_len)?;
+    measurements
+        .chunks_exact(measurement_len)
+        .map(|bytes| {
+            let (mac_address, fields) = bytes.split_at(mac_address_len);
+            let u16_at = |offset: usize| u16::from_le_bytes([fields[offset], fields[offset + 1]]);
+            Some(OwrAoaRangingMeasurement {
+                mac_address: if mac_address_len == 2 {
+                    MacAddress::Short(u16::from_le_bytes(mac_address.try_into().ok()?))
+                } else {
+                    MacAddress::Extended(u64::from_le_bytes(mac_address.try_into().ok()?))
+                },
+                status: StatusCode::try_from(fields[0]).ok()?,
+                nlos: fields[1],
+                frame_sequence_number: fields[2],
+                block_index: u16_at(3),
+                aoa_azimuth: u16_at(5),
+                aoa_azimuth_fom: fields[7],
+                aoa_elevation: u16_at(8),
+                aoa_elevation_fom: fields[10],
+            })
+        })
+        .collect()
+}
+
+/// Takes the DL-TDoA measurements of range data. None if the range data are not DL-TDoA.
+pub(crate) fn dl_tdoa_measurements(
+    ranging_measurements: RangingMeasurements,
+) -> Option<Vec<DlTdoaRangingMeasurement>> {
+    match ranging_measurements {
+        RangingMeasurements::ShortAddressDltdoa(v) => {
+            Some(v.into_iter().map(DlTdoaRangingMeasurement::from).collect())
+        }
+        RangingMeasurements::ExtendedAddressDltdoa(v) => {
+            Some(v.into_iter().map(DlTdoaRangingMeasurement::from).collect())
+        }
+        _ => None,
+    }
+}
+
+/// Arguments of the UwbTwoWayMeasurement constructor.
+pub(crate) fn two_way_measurement_args(measurement: &TwoWayRangingMeasurement) -> Vec<JavaArg> {
+    let rssi = Rssi::from(measurement.rssi);
+    vec![
+        JavaArg::Bytes(measurement.mac_address.to_ne_bytes()),
+        JavaArg::Int(i32::from(measurement.status)),
+        JavaArg::Int(measurement.nlos as i32),
+        JavaArg::Int(measurement.distance as i32),
+        JavaArg::Int(aoa_azimuth_to_java(measurement.aoa_azimuth)),
+        JavaArg::Int(fom_to_java(measurement.aoa_azimuth_fom)),
+        JavaArg::Int(aoa_elevation_to_java(measurement.aoa_elevation)),
+        JavaArg::Int(fom_to_java(measurement.aoa_elevation_fom)),
+        JavaArg::Int(aoa_azimuth_to_java(measurement.aoa_destination_azimuth)),
+        JavaArg::Int(fom_to_java(measurement.aoa_destination_azimuth_fom)),
+        JavaArg::Int(aoa_elevation_to_java(measurement.aoa_destination_elevation)),
+        JavaArg::Int(fom_to_java(measurement.aoa_destination_elevation_fom)),
+        JavaArg::Int(measurement.slot_index as i32),
+        JavaArg::Float(rssi.dbm),
+        JavaArg::Int(rssi.raw as i32),
+        JavaArg::Int(measurement.antenna_pair_id.map_or(ANTENNA_PAIR_ID_UNKNOWN, i32::from)),
+    ]
+}
+
+/// Arguments of the UwbOwrAoaMeasurement constructor.
+pub(crate) fn owr_aoa_measurement_args(measurement: &OwrAoaRangingMeasurement) -> Vec<JavaArg> {
+    vec![
+        JavaArg::Bytes(measurement.mac_address.to_ne_bytes()),
+        JavaArg::Int(i32::from(measurement.status)),
+        JavaArg::Int(measurement.nlos as i32),
+        JavaArg::Int(measurement.frame_sequence_number as i32),
+        JavaArg::Int(measurement.block_index as i32),
+        JavaArg::Int(aoa_azimuth_to_java(measurement.aoa_azimuth)),
+        JavaArg::Int(fom_to_java(measurement.aoa_azimuth_fom)),
+        JavaArg::Int(aoa_elevation_to_java(measurement.aoa_elevation)),
+        JavaArg::Int(fom_to_java(measurement.aoa_elevation_fom)),
+    ]
+}
+
+/// Arguments of the UwbDlTDoAMeasurement constructor. The raw anchor location is passed along
+/// with its decoded form.
+pub(crate) fn dl_tdoa_measurement_args(measurement: &DlTdoaRangingMeasurement) -> Vec<JavaArg> {
+    let (anchor_location_type, anchor_coordinate_1, anchor_coordinate_2, anchor_coordinate_3) =
+        parse_anchor_location(measurement.message_control, &measurement.dt_anchor_location)
+            .map(AnchorLocation::to_java_parts)
+            .unwrap_or((ANCHOR_LOCATION_TYPE_NONE, 0.0, 0.0, 0.0));
+    vec![
+        JavaArg::Bytes(measurement.mac_address.to_ne_bytes()),
+        JavaArg::Int(measurement.status as i32),
+        JavaArg::Int(measurement.message_type as i32),
+        JavaArg::Int(measurement.message_control as i32),
+        JavaArg::Int(measurement.block_index as i32),
+        JavaArg::Int(measurement.round_index as i32),
+        JavaArg::Int(measurement.nlos as i32),
+        JavaArg::Int(aoa_azimuth_to_java(measurement.aoa_azimuth)),
+        JavaArg::Int(fom_to_java(measurement.aoa_azimuth_fom)),
+        JavaArg::Int(aoa_elevation_to_java(measurement.aoa_elevation)),
+        JavaArg::Int(fom_to_java(measurement.aoa_elevation_fom)),
+        JavaArg::Int(measurement.rssi as i32),
+        JavaArg::Long(measurement.tx_timestamp as i64),
+        JavaArg::Long(measurement.rx_timestamp as i64),
+        JavaArg::Int(measurement.anchor_cfo as i32),
+        JavaArg::Int(measurement.cfo as i32),
+        JavaArg::Long(measurement.initiator_reply_time as i64),
+        JavaArg::Long(measurement.responder_reply_time as i64),
+        JavaArg::Int(measurement.initiator_responder_tof as i32),
+        JavaArg::Bytes(measurement.dt_anchor_location.clone()),
+        JavaArg::Bytes(measurement.ranging_rounds.clone()),
+        JavaArg::Int(anchor_location_type),
+        JavaArg::Double(anchor_coordinate_1),
+        JavaArg::Double(anchor_coordinate_2),
+        JavaArg::Double(anchor_coordinate_3),
+    ]
+}
+
+/// Arguments of the UwbRangingData constructor which precede the number of measurements, the
+/// measurement array and the raw notification. The number of measurements is the length of the
+/// array, which may hold more measurements than decoded by uwb_core.
+pub(crate) fn range_data_header_args(range_data: &SessionRangeData) -> Vec<JavaArg> {
+    let mac_indicator = match &range_data.ranging_measurements {
+        RangingMeasurements::ShortAddressTwoWay(_)
+        | RangingMeasurements::ShortAddressDltdoa(_)
+        | RangingMeasurements::ShortAddressOwrAoa(_) => MacAddressIndicator::ShortAddress,
+        RangingMeasurements::ExtendedAddressTwoWay(_)
+        | RangingMeasurements::ExtendedAddressDltdoa(_)
+        | RangingMeasurements::ExtendedAddressOwrAoa(_) => MacAddressIndicator::ExtendedAddress,
+    };
+    vec![
+        JavaArg::Long(range_data.sequence_number as i64),
+        // session_token below has already been mapped to session_id by uci layer.
+        JavaArg::Long(range_data.session_token as i64),
+        JavaArg::Int(range_data.rcr_indicator as i32),
+        JavaArg::Long(range_data.current_ranging_interval_ms as i64),
+        JavaArg::Int(range_data.ranging_measurement_type as i32),
+        JavaArg::Int(mac_indicator as i32),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uwb_uci_packets::RangingMeasurementType;
+
+    #[test]
+    fn test_get_two_way_ranigng_measurement_from_short_address_two_way_ranging_measurement() {
+        let short_address_measurement = ShortAddressTwoWayRangingMeasurement {
+            mac_address: 0x1234,
+            status: StatusCode::UciStatusOk,
+            nlos: 0,
+            distance: 1,
+            aoa_azimuth: 2,
+            aoa_azimuth_fom: 3,
+            aoa_elevation: 4,
+            aoa_elevation_fom: 5,
+            aoa_destination_azimuth: 6,
+            aoa_destination_azimuth_fom: 7,
+            aoa_destination_elevation: 8,
+            aoa_destination_elevation_fom: 9,
+            slot_index: 10,
+            rssi: 11,
+        };
+        let measurement = TwoWayRangingMeasurement::from(short_address_measurement.clone());
+        assert_eq!(
+            measurement.mac_address,
+            MacAddress::Short(short_address_measurement.mac_address)
+        );
+        assert_eq!(measurement.status, short_address_measurement.status);
+        assert_eq!(measurement.nlos, short_address_measurement.nlos);
+        assert_eq!(measurement.distance, short_address_measurement.distance);
+        assert_eq!(measurement.aoa_azimuth, short_address_measurement.aoa_azimuth);
+        assert_eq!(measurement.aoa_azimuth_fom, short_address_measurement.aoa_azimuth_fom);
+        assert_eq!(measurement.aoa_elevation, short_address_measurement.aoa_elevation);
+        assert_eq!(measurement.aoa_elevation_fom, short_address_measurement.aoa_elevation_fom);
+        assert_eq!(
+            measurement.aoa_destination_azimuth,
+            short_address_measurement.aoa_destination_azimuth
+        );
+        assert_eq!(
+            measurement.aoa_destination_azimuth_fom,
+            short_address_measurement.aoa_destination_azimuth_fom
+        );
+        assert_eq!(
+            measurement.aoa_destination_elevation,
+            short_address_measurement.aoa_destination_elevation
+        );
+        assert_eq!(
+            measurement.aoa_destination_elevation_fom,
+            short_address_measurement.aoa_destination_elevation_fom
+        );
+        assert_eq!(measurement.slot_index, short_address_measurement.slot_index);
+        assert_eq!(measurement.rssi, short_address_measurement.rssi);
+        assert_eq!(measurement.antenna_pair_id, None);
+    }
+
+    #[test]
+    fn test_get_two_way_ranigng_measurement_from_extended_address_two_way_ranging_measurement() {
+        let extended_address_measurement = ExtendedAddressTwoWayRangingMeasurement {
+            mac_address: 0x1234_5678,
+            status: StatusCode::UciStatusOk,
+            nlos: 0,
+            distance: 1,
+            aoa_azimuth: 2,
+            aoa_azimuth_fom: 3,
+            aoa_elevation: 4,
+            aoa_elevation_fom: 5,
+            aoa_destination_azimuth: 6,
+            aoa_destination_azimuth_fom: 7,
+            aoa_destination_elevation: 8,
+            aoa_destination_elevation_fom: 9,
+            slot_index: 10,
+            rssi: 11,
+        };
+        let measurement = TwoWayRangingMeasurement::from(extended_address_measurement.clone());
+        assert_eq!(
+            measurement.mac_address,
+            MacAddress::Extended(extended_address_measurement.mac_address)
+        );
+        assert_eq!(measurement.status, extended_address_measurement.status);
+        assert_eq!(measurement.nlos, extended_address_measurement.nlos);
+        assert_eq!(measurement.distance, extended_address_measurement.distance);
+        assert_eq!(measurement.aoa_azimuth, extended_address_measurement.aoa_azimuth);
+        assert_eq!(measurement.aoa_azimuth_fom, extended_address_measurement.aoa_azimuth_fom);
+        assert_eq!(measurement.aoa_elevation, extended_address_measurement.aoa_elevation);
+        assert_eq!(measurement.aoa_elevation_fom, extended_address_measurement.aoa_elevation_fom);
+        assert_eq!(
+            measurement.aoa_destination_azimuth,
+            extended_address_measurement.aoa_destination_azimuth
+        );
+        assert_eq!(
+            measurement.aoa_destination_azimuth_fom,
+            extended_address_measurement.aoa_destination_azimuth_fom
+        );
+        assert_eq!(
+            measurement.aoa_destination_elevation,
+            extended_address_measurement.aoa_destination_elevation
+        );
+        assert_eq!(
+            measurement.aoa_destination_elevation_fom,
+            extended_address_measurement.aoa_destination_elevation_fom
+        );
+        assert_eq!(measurement.slot_index, extended_address_measurement.slot_index);
+        assert_eq!(measurement.rssi, extended_address_measurement.rssi);
+    }
+
+    #[test]
+    fn test_get_owr_aoa_ranging_measurement_from_short_address_measurement() {
+        let short_address_measurement = ShortAddressOwrAoaRangingMeasurement {
+            mac_address: 0x1234,
+            status: StatusCode::UciStatusOk,
+            nlos: 0,
+            frame_sequence_number: 1,
+            block_index: 2,
+            aoa_azimuth: 3,
+            aoa_azimuth_fom: 4,
+            aoa_elevation: 5,
+            aoa_elevation_fom: 6,
+        };
+        let measurement = OwrAoaRangingMeasurement::from(short_address_measurement.clone());
+        assert_eq!(
+            measurement.mac_address,
+            MacAddress::Short(short_address_measurement.mac_address)
+        );
+        assert_eq!(measurement.status, short_address_measurement.status);
+        assert_eq!(measurement.nlos, short_address_measurement.nlos);
+        assert_eq!(
+            measurement.frame_sequence_number,
+            short_address_measurement.frame_sequence_number
+        );
+        assert_eq!(measurement.block_index, short_address_measurement.block_index);
+        assert_eq!(measurement.aoa_azimuth, short_address_measurement.aoa_azimuth);
+        assert_eq!(measurement.aoa_azimuth_fom, short_address_measurement.aoa_azimuth_fom);
+        assert_eq!(measurement.aoa_elevation, short_address_measurement.aoa_elevation);
+        assert_eq!(measurement.aoa_elevation_fom, short_address_measurement.aoa_elevation_fom);
+    }
+
+    #[test]
+    fn test_get_owr_aoa_ranging_measurement_from_extended_address_measurement() {
+        let extended_address_measurement = ExtendedAddressOwrAoaRangingMeasurement {
+            mac_address: 0x1234_5678,
+            status: StatusCode::UciStatusOk,
+            nlos: 0,
+            frame_sequence_number: 1,
+            block_index: 2,
+            aoa_azimuth: 3,
+            aoa_azimuth_fom: 4,
+            aoa_elevation: 5,
+            aoa_elevation_fom: 6,
+        };
+        let measurement = OwrAoaRangingMeasurement::from(extended_address_measurement.clone());
+        assert_eq!(
+            measurement.mac_address,
+            MacAddress::Extended(extended_address_measurement.mac_address)
+        );
+        assert_eq!(measurement.status, extended_address_measurement.status);
+        assert_eq!(measurement.nlos, extended_address_measurement.nlos);
+        assert_eq!(
+            measurement.frame_sequence_number,
+            extended_address_measurement.frame_sequence_number
+        );
+        assert_eq!(measurement.block_index, extended_address_measurement.block_index);
+        assert_eq!(measurement.aoa_azimuth, extended_address_measurement.aoa_azimuth);
+        assert_eq!(measurement.aoa_azimuth_fom, extended_address_measurement.aoa_azimuth_fom);
+        assert_eq!(measurement.aoa_elevation, extended_address_measurement.aoa_elevation);
+        assert_eq!(measurement.aoa_elevation_fom, extended_address_measurement.aoa_elevation_fom);
+    }
+
+    #[test]
+    fn test_get_dl_tdoa_ranging_measurement_from_short_address_measurement() {
+        let bytes = [
+            // All Fields in Little Endian (LE)
+            0x0a, 0x01, 0x33, 0x05, // 2(Mac address), Status, Message Type
+            0x53, 0x05, 0x02, 0x05, // 2(Message control), 2(Block Index)
+            0x07, 0x09, 0x0a, 0x01, // Round Index, NLoS, 2(AoA Azimuth)
+            0x02, 0x05, 0x07, 0x09, // AoA Azimuth FOM, 2(AoA Elevation), AoA Elevation FOM
+            0x0a, 0x01, 0x02, 0x05, // RSSI, 3(Tx Timestamp..)
+            0x07, 0x09, 0x0a, 0x01, // 4(Tx Timestamp..)
+            0x02, 0x05, 0x07, 0x09, // Tx Timestamp, 3(Rx Timestamp..)
+            0x05, 0x07, 0x09, 0x0a, // 2(Rx Timestamp), 2(Anchor Cfo)
+            0x01, 0x02, 0x05, 0x07, // 2(Cfo), 2(Initiator Reply Time..)
+            0x09, 0x05, 0x07, 0x09, // 2(Initiator Reply Time), 2(Responder Reply Time..)
+            0x0a, 0x01, 0x02, 0x05, // 2(Responder Reply Time), 2(Initiator-Responder ToF)
+            0x07, 0x09, 0x07, 0x09, // 4(Anchor Location..)
+            0x05, 0x07, 0x09, 0x0a, // 4(Anchor Location..)
+            0x01, 0x02, 0x05, 0x07, // 2(Anchor Location..), 2(Active Ranging Rounds..)
+            0x09, 0x0a, 0x01, 0x02, // 4(Active Ranging Rounds..)
+            0x05, 0x07, 0x09, 0x05, // 4(Active Ranging Rounds)
+        ];
+        let short_address_measurement_vec =
+            ShortAddressDlTdoaRangingMeasurement::parse(&bytes, 1).unwrap();
+        let short_address_measurement = &short_address_measurement_vec[0];
+        let measurement = DlTdoaRangingMeasurement::from(short_address_measurement.clone());
+        assert_eq!(
+            measurement.mac_address,
+            MacAddress::Short(short_address_measurement.mac_address)
+        );
+        assert_eq!(measurement.status, short_address_measurement.measurement.status);
+        assert_eq!(measurement.message_type, short_address_measurement.measurement.message_type);
+        assert_eq!(
+            measurement.message_control,
+            short_address_measurement.measurement.message_control
+        );
+        assert_eq!(measurement.block_index, short_address_measurement.measurement.block_index);
+        assert_eq!(measurement.round_index, short_address_measurement.measurement.round_index);
+        assert_eq!(measurement.nlos, short_address_measurement.measurement.nlos);
+        assert_eq!(measurement.aoa_azimuth, short_address_measurement.measurement.aoa_azimuth);
+        assert_eq!(
+            measurement.aoa_azimuth_fom,
+            short_address_measurement.measurement.aoa_azimuth_fom
+        );
+        assert_eq!(measurement.aoa_elevation, short_address_measurement.measurement.aoa_elevation);
+        assert_eq!(
+            measurement.aoa_elevation_fom,
+            short_address_measurement.measurement.aoa_elevation_fom
+        );
+        assert_eq!(measurement.rssi, short_address_measurement.measurement.rssi);
+        assert_eq!(measurement.tx_timestamp, short_address_measurement.measurement.tx_timestamp);
+        assert_eq!(measurement.rx_timestamp, short_address_measurement.measurement.rx_timestamp);
+        assert_eq!(measurement.anchor_cfo, short_address_measurement.measurement.anchor_cfo);
+        assert_eq!(measurement.cfo, short_address_measurement.measurement.cfo);
+        assert_eq!(
+            measurement.initiator_reply_time,
+            short_address_measurement.measurement.initiator_reply_time
+        );
+        assert_eq!(
+            measurement.responder_reply_time,
+            short_address_measurement.measurement.responder_reply_time
+        );
+        assert_eq!(
+            measurement.initiator_responder_tof,
+            short_address_measurement.measurement.initiator_responder_tof
+        );
+        assert_eq!(
+            measurement.dt_anchor_location,
+            short_address_measurement.measurement.dt_anchor_location
+        );
+        assert_eq!(
+            measurement.ranging_rounds,
+            short_address_measurement.measurement.ranging_rounds
+        );
+    }
+
+    #[test]
+    fn test_get_dl_tdoa_ranging_measurement_from_extended_address_measurement() {
+        let bytes = [
+            // All Fields in Little Endian (LE)
+            0x0a, 0x01, 0x33, 0x05, // 4(Mac address..)
+            0x33, 0x05, 0x02, 0x05, // 4(Mac address)
+            0x07, 0x09, 0x0a, 0x01, // Status, Message Type, 2(Message control),
+            0x02, 0x05, 0x07, 0x09, // 2(Block Index), Round Index, NLoS,
+            0x0a, 0x01, 0x02, 0x05, // 2(AoA Azimuth), AoA Azimuth FOM, 1(AoA Elevation..)
+            0x07, 0x09, 0x0a, // 1(AoA Elevation), AoA Elevation FOM, RSSI,
+            0x01, 0x02, 0x05, 0x07, // 4(Tx Timestamp..)
+            0x09, 0x05, 0x07, 0x09, // 4(Tx Timestamp),
+            0x0a, 0x01, 0x02, 0x05, // 4(Rx Timestamp..)
+            0x07, 0x09, 0x05, 0x07, // 4(Rx Timestamp)
+            0x09, 0x0a, 0x01, 0x02, // 2(Anchor Cfo), 2(Cfo),
+            0x05, 0x07, 0x09, 0x05, // 4(Initiator Reply Time)
+            0x07, 0x09, 0x0a, 0x01, // 4(Responder Reply Time),
+            0x02, 0x05, 0x02, 0x05, // 2(Initiator-Responder ToF), 2(Active Ranging Rounds)
+        ];
+        let extended_address_measurement_vec =
+            ExtendedAddressDlTdoaRangingMeasurement::parse(&bytes, 1).unwrap();
+        let extended_address_measurement = &extended_address_measurement_vec[0];
+
+        let measurement = DlTdoaRangingMeasurement::from(extended_address_measurement.clone());
+        assert_eq!(
+            measurement.mac_address,
+            MacAddress::Extended(extended_address_measurement.mac_address)
+        );
+        assert_eq!(measurement.status, extended_address_measurement.measurement.status);
+        assert_eq!(measurement.message_type, extended_address_measurement.measurement.message_type);
+        assert_eq!(
+            measurement.message_control,
+            extended_address_measurement.measurement.message_control
+        );
+        assert_eq!(measurement.block_index, extended_address_measurement.measurement.block_index);
+        assert_eq!(measurement.round_index, extended_address_measurement.measurement.round_index);
+        assert_eq!(measurement.nlos, extended_address_measurement.measurement.nlos);
+        assert_eq!(measurement.aoa_azimuth, extended_address_measurement.measurement.aoa_azimuth);
+        assert_eq!(
+            measurement.aoa_azimuth_fom,
+            extended_address_measurement.measurement.aoa_azimuth_fom
+        );
+        assert_eq!(
+            measurement.aoa_elevation,
+            extended_address_measurement.measurement.aoa_elevation
+        );
+        assert_eq!(
+            measurement.aoa_elevation_fom,
+            extended_address_measurement.measurement.aoa_elevation_fom
+        );
+        assert_eq!(measurement.rssi, extended_address_measurement.measurement.rssi);
+        assert_eq!(measurement.tx_timestamp, extended_address_measurement.measurement.tx_timestamp);
+        assert_eq!(measurement.rx_timestamp, extended_address_measurement.measurement.rx_timestamp);
+        assert_eq!(measurement.anchor_cfo, extended_address_measurement.measurement.anchor_cfo);
+        assert_eq!(measurement.cfo, extended_address_measurement.measurement.cfo);
+        assert_eq!(
+            measurement.initiator_reply_time,
+            extended_address_measurement.measurement.initiator_reply_time
+        );
+        assert_eq!(
+            measurement.responder_reply_time,
+            extended_address_measurement.measurement.responder_reply_time
+        );
+        assert_eq!(
+            measurement.initiator_responder_tof,
+            extended_address_measurement.measurement.initiator_responder_tof
+        );
+        assert_eq!(
+            measurement.dt_anchor_location,
+            extended_address_measurement.measurement.dt_anchor_location
+        );
+        assert_eq!(
+            measurement.ranging_rounds,
+            extended_address_measurement.measurement.ranging_rounds
+        );
+    }
+
+    /// Splits the parameter types of a constructor signature, e.g. "[B" or "I".
+    fn signature_arg_types(signature: &str) -> Vec<String> {
+        let params = &signature[1..signature.find(')').unwrap()];
+        let mut types = vec![];
+        let mut current = String::new();
+        for c in params.chars() {
+            current.push(c);
+            if c != '[' {
+                types.push(std::mem::take(&mut current));
+            }
+        }
+        types
+    }
+
+    fn arg_type(arg: &JavaArg) -> &'static str {
+        match arg {
+            JavaArg::Int(_) => "I",
+            JavaArg::Long(_) => "J",
+            JavaArg::Float(_) => "F",
+            JavaArg::Double(_) => "D",
+            JavaArg::Bytes(_) => "[B",
+        }
+    }
+
+    fn assert_args_match_signature(args: &[JavaArg], signature: &str) {
+        assert_eq!(args.iter().map(arg_type).collect::<Vec<_>>(), signature_arg_types(signature));
+    }
+
+    fn short_address_two_way_measurement(mac_address: u16) -> ShortAddressTwoWayRangingMeasurement {
+        ShortAddressTwoWayRangingMeasurement {
+            mac_address,
+            status: StatusCode::UciStatusOk,
+            nlos: 1,
+            distance: 100,
+            // Above 180 degrees.
+            aoa_azimuth: 0x7fff,
+            // Reserved.
+            aoa_azimuth_fom: 101,
+            aoa_elevation: 20 << 7,
+            aoa_elevation_fom: 50,
+            aoa_destination_azimuth: 0,
+            aoa_destination_azimuth_fom: 0,
+            aoa_destination_elevation: 0,
+            aoa_destination_elevation_fom: 0,
+            slot_index: 3,
+            rssi: 11,
+        }
+    }
+
+    #[test]
+    fn test_two_way_measurement_args() {
+        let measurement = TwoWayRangingMeasurement::from(short_address_two_way_measurement(0x1234));
+        let args = two_way_measurement_args(&measurement);
+        assert_args_match_signature(&args, TWO_WAY_MEASUREMENT_SIGNATURE);
+        assert_eq!(
+            args,
+            vec![
+                JavaArg::Bytes(0x1234u16.to_ne_bytes().to_vec()),
+                JavaArg::Int(0),
+                JavaArg::Int(1),
+                JavaArg::Int(100),
+                JavaArg::Int(180 << 7),
+                JavaArg::Int(0),
+                JavaArg::Int(20 << 7),
+                JavaArg::Int(50),
+                JavaArg::Int(0),
+                JavaArg::Int(0),
+                JavaArg::Int(0),
+                JavaArg::Int(0),
+                JavaArg::Int(3),
+                JavaArg::Float(-5.5),
+                JavaArg::Int(11),
+                JavaArg::Int(ANTENNA_PAIR_ID_UNKNOWN),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_two_way_measurements() {
+        let ranging_measurements = RangingMeasurements::ShortAddressTwoWay(vec![
+            short_address_two_way_measurement(1),
+            short_address_two_way_measurement(2),
+        ]);
+        let measurements = two_way_measurements(ranging_measurements, &[], true).unwrap();
+        assert_eq!(measurements.len(), 2);
+        // The antenna pairs are not reported.
+        assert!(measurements.iter().all(|measurement| measurement.antenna_pair_id.is_none()));
+
+        assert!(two_way_measurements(RangingMeasurements::ShortAddressDltdoa(vec![]), &[], false)
+            .is_none());
+    }
+
+    #[test]
+    fn test_owr_aoa_measurement_args() {
+        let measurement = OwrAoaRangingMeasurement {
+            mac_address: MacAddress::Extended(0x1234_5678_9abc_def0),
+            status: StatusCode::UciStatusFailed,
+            nlos: 0,
+            frame_sequence_number: 7,
+            block_index: 300,
+            aoa_azimuth: 10 << 7,
+            aoa_azimuth_fom: 90,
+            // -100 degrees, below -90.
+            aoa_elevation: (-100i16 << 7) as u16,
+            aoa_elevation_fom: 80,
+        };
+        let args = owr_aoa_measurement_args(&measurement);
+        assert_args_match_signature(&args, OWR_AOA_MEASUREMENT_SIGNATURE);
+        assert_eq!(
+            args,
+            vec![
+                JavaArg::Bytes(0x1234_5678_9abc_def0u64.to_ne_bytes().to_vec()),
+                JavaArg::Int(i32::from(StatusCode::UciStatusFailed)),
+                JavaArg::Int(0),
+                JavaArg::Int(7),
+                JavaArg::Int(300),
+                JavaArg::Int(10 << 7),
+                JavaArg::Int(90),
+                JavaArg::Int((-90i16 << 7) as u16 as i32),
+                JavaArg::Int(80),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_owr_aoa_measurements() {
+        let first = ShortAddressOwrAoaRangingMeasurement {
+            mac_address: 0x1234,
+            status: StatusCode::UciStatusOk,
+            nlos: 0,
+            frame_sequence_number: 1,
+            block_index: 2,
+            aoa_azimuth: 3,
+            aoa_azimuth_fom: 4,
+            aoa_elevation: 5,
+            aoa_elevation_fom: 6,
+        };
+        let mut raw_ranging_data = vec![0; SESSION_INFO_HEADER_LEN - 1];
+        raw_ranging_data.push(2);
+        raw_ranging_data.extend_from_slice(&[
+            0x34, 0x12, 0x00, 0x00, 0x01, 0x02, 0x00, 0x03, 0x00, 0x04, 0x05, 0x00, 0x06,
+        ]);
+        raw_ranging_data.extend_from_slice(&[
+            0x78, 0x56, 0x00, 0x01, 0x07, 0x2c, 0x01, 0x00, 0x05, 0x5a, 0x80, 0x01, 0x50,
+        ]);
+
+        let measurements = owr_aoa_measurements(
+            RangingMeasurements::ShortAddressOwrAoa(first.clone()),
+            &raw_ranging_data,
+        )
+        .unwrap();
+        assert_eq!(measurements.len(), 2);
+        assert_eq!(measurements[0].mac_address, MacAddress::Short(0x1234));
+        let second = &measurements[1];
+        assert_eq!(second.mac_address, MacAddress::Short(0x5678));
+        assert_eq!(second.status, StatusCode::UciStatusOk);
+        assert_eq!(second.nlos, 1);
+        assert_eq!(second.frame_sequence_number, 7);
+        assert_eq!(second.block_index, 300);
+        assert_eq!(second.aoa_azimuth, 0x0500);
+        assert_eq!(second.aoa_azimuth_fom, 90);
+        assert_eq!(second.aoa_elevation, 0x0180);
+        assert_eq!(second.aoa_elevation_fom, 80);
+
+        // Only the measurement decoded by uwb_core without the raw notification, or when it is
+        // truncated.
+        raw_ranging_data.pop();
+        for raw_ranging_data in [&raw_ranging_data[..], &[]] {
+            let measurements = owr_aoa_measurements(
+                RangingMeasurements::ShortAddressOwrAoa(first.clone()),
+                raw_ranging_data,
+            )
+            .unwrap();
+            assert_eq!(measurements.len(), 1);
+        }
+        assert!(
+            owr_aoa_measurements(RangingMeasurements::ShortAddressTwoWay(vec![]), &[]).is_none()
+        );
+    }
+
+    #[test]
+    fn test_dl_tdoa_measurement_args() {
+        let measurement = DlTdoaRangingMeasurement {
+            mac_address: MacAddress::Short(0x0102),
+            status: 0,
+            message_type: 1,
+            // No anchor location.
+            message_control: 0,
+            block_index: 2,
+            round_index: 3,
+            nlos: 0,
+            aoa_azimuth: 0,
+            aoa_azimuth_fom: 0,
+            aoa_elevation: 0,
+            aoa_elevation_fom: 0,
+            rssi: 4,
+            tx_timestamp: 5,
+            rx_timestamp: 6,
+            anchor_cfo: 7,
+            cfo: 8,
+            initiator_reply_time: 9,
+            responder_reply_time: 10,
+            initiator_responder_tof: 11,
+            dt_anchor_location: vec![],
+            ranging_rounds: vec![0, 1],
+        };
+        let args = dl_tdoa_measurement_args(&measurement);
+        assert_args_match_signature(&args, DL_TDOA_MEASUREMENT_SIGNATURE);
+        assert_eq!(args[12..14], [JavaArg::Long(5), JavaArg::Long(6)]);
+        assert_eq!(
+            args[19..],
+            [
+                JavaArg::Bytes(vec![]),
+                JavaArg::Bytes(vec![0, 1]),
+                JavaArg::Int(ANCHOR_LOCATION_TYPE_NONE),
+                JavaArg::Double(0.0),
+                JavaArg::Double(0.0),
+                JavaArg::Double(0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_range_data_header_args() {
+        let range_data = SessionRangeData {
+            sequence_number: 1,
+            session_token: 2,
+            current_ranging_interval_ms: 200,
+            ranging_measurement_type: RangingMeasurementType::TwoWay,
+            ranging_measurements: RangingMeasurements::ShortAddressTwoWay(vec![
+                short_address_two_way_measurement(1),
+            ]),
+            rcr_indicator: 3,
+            raw_ranging_data: vec![],
+        };
+        assert_eq!(
+            range_data_header_args(&range_data),
+            vec![
+                JavaArg::Long(1),
+                JavaArg::Long(2),
+                JavaArg::Int(3),
+                JavaArg::Long(200),
+                JavaArg::Int(RangingMeasurementType::TwoWay as i32),
+                JavaArg::Int(MacAddressIndicator::ShortAddress as i32),
+            ]
+        );
+    }
+}
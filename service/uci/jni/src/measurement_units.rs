@@ -0,0 +1,84 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion of the measurement fields of the ranging notifications to the values passed to the
+//! Java measurement constructors, which never receive a value outside the domain of the field.
+
+/// Highest azimuth magnitude, 180 degrees in FP Q9.7 format.
+const AZIMUTH_MAX_Q9_7: i16 = 180 << 7;
+/// Highest elevation magnitude, 90 degrees in FP Q9.7 format.
+const ELEVATION_MAX_Q9_7: i16 = 90 << 7;
+/// Highest figure of merit, in percent.
+const FOM_MAX: u8 = 100;
+
+/// Converts an AoA azimuth, a signed FP Q9.7 value in degrees, clamped to [-180, 180]. The value
+/// is passed to Java in its 16-bit two's complement encoding, as the UWBS reports it.
+pub(crate) fn aoa_azimuth_to_java(raw: u16) -> i32 {
+    clamp_q9_7(raw, AZIMUTH_MAX_Q9_7)
+}
+
+/// Converts an AoA elevation, a signed FP Q9.7 value in degrees, clamped to [-90, 90]. The value
+/// is passed to Java in its 16-bit two's complement encoding, as the UWBS reports it.
+pub(crate) fn aoa_elevation_to_java(raw: u16) -> i32 {
+    clamp_q9_7(raw, ELEVATION_MAX_Q9_7)
+}
+
+/// Converts a figure of merit, from 0 to 100. The values above 100 are reserved, and passed as 0
+/// since they do not tell how reliable the measurement is.
+pub(crate) fn fom_to_java(raw: u8) -> i32 {
+    if raw > FOM_MAX {
+        0
+    } else {
+        raw as i32
+    }
+}
+
+fn clamp_q9_7(raw: u16, max: i16) -> i32 {
+    (raw as i16).clamp(-max, max) as u16 as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aoa_azimuth_to_java() {
+        assert_eq!(aoa_azimuth_to_java(0x0000), 0);
+        // -1/128 degree.
+        assert_eq!(aoa_azimuth_to_java(0xFFFF), 0xFFFF);
+        assert_eq!(aoa_azimuth_to_java(0x5A00), 0x5A00);
+        assert_eq!(aoa_azimuth_to_java(0xA600), 0xA600);
+        // Clamped to +/-180 degrees.
+        assert_eq!(aoa_azimuth_to_java(0x5A01), 0x5A00);
+        assert_eq!(aoa_azimuth_to_java(0x7FFF), 0x5A00);
+        assert_eq!(aoa_azimuth_to_java(0x8000), 0xA600);
+    }
+
+    #[test]
+    fn test_aoa_elevation_to_java() {
+        assert_eq!(aoa_elevation_to_java(0x2D00), 0x2D00);
+        assert_eq!(aoa_elevation_to_java(0xD300), 0xD300);
+        // Clamped to +/-90 degrees.
+        assert_eq!(aoa_elevation_to_java(0x5A00), 0x2D00);
+        assert_eq!(aoa_elevation_to_java(0x8000), 0xD300);
+    }
+
+    #[test]
+    fn test_fom_to_java() {
+        assert_eq!(fom_to_java(0), 0);
+        assert_eq!(fom_to_java(100), 100);
+        assert_eq!(fom_to_java(101), 0);
+        assert_eq!(fom_to_java(0xFF), 0);
+    }
+}
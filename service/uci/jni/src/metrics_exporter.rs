@@ -0,0 +1,468 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exporter of the metrics of the chips in the Prometheus text format, served over HTTP on a local
+//! TCP port, letting the lab soak tests graph the health of the stack, e.g. through `adb forward`.
+//!
+//! The exporter is gated by the native_metrics_exporter flag, and runs while the dispatcher
+//! exists: it is stopped before the dispatcher is destroyed. Only `GET /metrics` is served, one
+//! request per connection, and the connections are served one after the other.
+
+use std::fmt::{Display, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener as StdTcpListener};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use log::{error, info};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use uwb_core::error::{Error, Result};
+
+use crate::global_ref_accounting::{GlobalRefKind, GLOBAL_REF_COUNTS};
+use crate::notification_stats::{CallbackType, NotificationStats, LATENCY_BUCKET_BOUNDS_US};
+use crate::parse_error_stats::ParseErrorStats;
+use crate::uci_metrics::{UciCommandStats, UciMetrics};
+
+/// Size of the buffer of the reads from the client.
+const READ_BUFFER_SIZE: usize = 1024;
+/// Size above which a request is not read any further.
+const MAX_REQUEST_SIZE: usize = 8 * READ_BUFFER_SIZE;
+/// How long a client has to send its request and read the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    /// The running exporter, if any.
+    static ref METRICS_EXPORTER: Mutex<Option<MetricsExporter>> = Mutex::new(None);
+}
+
+/// The metrics of one chip, shared with the components which update them.
+#[derive(Clone)]
+pub(crate) struct ChipMetricsSources {
+    pub uci_metrics: Arc<Mutex<UciMetrics>>,
+    pub notification_stats: Arc<Mutex<NotificationStats>>,
+    pub parse_error_stats: Arc<Mutex<ParseErrorStats>>,
+}
+
+/// A snapshot of the metrics of one chip.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChipMetrics {
+    pub uci_commands: Vec<(u8, u8, UciCommandStats)>,
+    pub notification_stats: NotificationStats,
+    pub parse_errors: Vec<(&'static str, u64)>,
+}
+
+impl ChipMetricsSources {
+    /// Takes a snapshot of the metrics. The metrics which cannot be locked are reported empty.
+    fn snapshot(&self) -> ChipMetrics {
+        let mut metrics = ChipMetrics::default();
+        match self.uci_metrics.lock() {
+            Ok(uci_metrics) => metrics.uci_commands = uci_metrics.snapshot(),
+            Err(e) => error!("Metrics exporter: failed to lock UCI metrics: {:?}", e),
+        }
+        match self.notification_stats.lock() {
+            Ok(stats) => metrics.notification_stats = stats.clone(),
+            Err(e) => error!("Metrics exporter: failed to lock notification stats: {:?}", e),
+        }
+        match self.parse_error_stats.lock() {
+            Ok(stats) => metrics.parse_errors = stats.totals(),
+            Err(e) => error!("Metrics exporter: failed to lock parse error stats: {:?}", e),
+        }
+        metrics
+    }
+}
+
+fn callback_label(callback_type: CallbackType) -> &'static str {
+    match callback_type {
+        CallbackType::Ranging => "ranging",
+        CallbackType::Radar => "radar",
+        CallbackType::Vendor => "vendor",
+        CallbackType::DataRcv => "data_rcv",
+    }
+}
+
+fn us_to_seconds(us: u64) -> f64 {
+    us as f64 / 1_000_000.0
+}
+
+/// Writes metric families in the Prometheus text format. The samples of a family must be written
+/// right after it.
+#[derive(Default)]
+struct TextWriter {
+    text: String,
+}
+
+impl TextWriter {
+    fn family(&mut self, name: &str, metric_type: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {} {}", name, help);
+        let _ = writeln!(self.text, "# TYPE {} {}", name, metric_type);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels = labels
+                .iter()
+                .map(|(label, value)| format!("{}=\"{}\"", label, escape_label_value(value)))
+                .collect::<Vec<_>>()
+                .join(",");
+            let _ = write!(self.text, "{{{}}}", labels);
+        }
+        let _ = writeln!(self.text, " {}", value);
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// The name and the help of a counter of the UCI command metrics, and how to get its value.
+type UciCounter = (&'static str, &'static str, fn(&UciCommandStats) -> u64);
+
+/// Formats the metrics of the chips, and the JNI global references held, in the Prometheus text
+/// format.
+pub(crate) fn render(
+    chips: &[(String, ChipMetrics)],
+    global_refs: &[(GlobalRefKind, i64)],
+) -> String {
+    let mut writer = TextWriter::default();
+
+    let uci_counters: [UciCounter; 5] = [
+        ("uwb_uci_commands_total", "UCI commands sent, retransmissions excluded.", |s| {
+            s.command_count
+        }),
+        ("uwb_uci_responses_total", "UCI commands which got a response.", |s| s.response_count),
+        ("uwb_uci_retries_total", "UCI command retransmissions.", |s| s.retry_count),
+        ("uwb_uci_timeouts_total", "UCI command attempts which got no response.", |s| {
+            s.timeout_count
+        }),
+        ("uwb_uci_retry_storms_total", "UCI_STATUS_COMMAND_RETRY storms.", |s| s.storm_count),
+    ];
+    for (name, help, value) in uci_counters {
+        writer.family(name, "counter", help);
+        for (chip_id, metrics) in chips {
+            for (gid, oid, stats) in metrics.uci_commands.iter() {
+                let (gid, oid) = (format!("{:#x}", gid), format!("{:#x}", oid));
+                let labels = [("chip", chip_id.as_str()), ("gid", &gid), ("oid", &oid)];
+                writer.sample(name, &labels, value(stats));
+            }
+        }
+    }
+    let name = "uwb_uci_response_latency_seconds";
+    writer.family(name, "summary", "Round-trip time of the UCI commands, from the first attempt.");
+    for (chip_id, metrics) in chips {
+        for (gid, oid, stats) in metrics.uci_commands.iter() {
+            let (gid, oid) = (format!("{:#x}", gid), format!("{:#x}", oid));
+            let labels = [("chip", chip_id.as_str()), ("gid", &gid), ("oid", &oid)];
+            writer.sample(&format!("{}_sum", name), &labels, us_to_seconds(stats.total_latency_us));
+            writer.sample(&format!("{}_count", name), &labels, stats.response_count);
+        }
+    }
+    let name = "uwb_uci_response_latency_max_seconds";
+    writer.family(name, "gauge", "Longest round-trip time of the UCI commands.");
+    for (chip_id, metrics) in chips {
+        for (gid, oid, stats) in metrics.uci_commands.iter() {
+            let (gid, oid) = (format!("{:#x}", gid), format!("{:#x}", oid));
+            let labels = [("chip", chip_id.as_str()), ("gid", &gid), ("oid", &oid)];
+            writer.sample(name, &labels, us_to_seconds(stats.max_latency_us));
+        }
+    }
+
+    let name = "uwb_notification_dispatch_latency_seconds";
+    writer.family(name, "histogram", "Time taken by the Java callbacks of the notifications.");
+    for (chip_id, metrics) in chips {
+        for callback_type in CallbackType::ALL {
+            let stats = metrics.notification_stats.get(callback_type);
+            let callback = callback_label(callback_type);
+            let mut cumulative_count = 0;
+            for (bucket, count) in stats.latency_histogram.iter().enumerate() {
+                cumulative_count += count;
+                let le = match LATENCY_BUCKET_BOUNDS_US.get(bucket) {
+                    Some(&bound) => us_to_seconds(bound).to_string(),
+                    None => "+Inf".to_owned(),
+                };
+                let labels = [("chip", chip_id.as_str()), ("callback", callback), ("le", &le)];
+                writer.sample(&format!("{}_bucket", name), &labels, cumulative_count);
+            }
+            let labels = [("chip", chip_id.as_str()), ("callback", callback)];
+            writer.sample(&format!("{}_sum", name), &labels, us_to_seconds(stats.total_latency_us));
+            writer.sample(&format!("{}_count", name), &labels, stats.count);
+        }
+    }
+    let name = "uwb_notification_dispatch_failures_total";
+    writer.family(name, "counter", "Java callbacks of the notifications which failed.");
+    for (chip_id, metrics) in chips {
+        for callback_type in CallbackType::ALL {
+            let labels = [("chip", chip_id.as_str()), ("callback", callback_label(callback_type))];
+            writer.sample(
+                name,
+                &labels,
+                metrics.notification_stats.get(callback_type).failure_count,
+            );
+        }
+    }
+    let name = "uwb_notifications_dropped_total";
+    writer.family(name, "counter", "Notifications dropped by the notification queue.");
+    for (chip_id, metrics) in chips {
+        writer.sample(name, &[("chip", chip_id)], metrics.notification_stats.dropped_count());
+    }
+    let name = "uwb_notification_parse_errors_total";
+    writer.family(name, "counter", "Notifications which could not be converted, by family.");
+    for (chip_id, metrics) in chips {
+        for (family, count) in metrics.parse_errors.iter() {
+            writer.sample(name, &[("chip", chip_id), ("family", family)], count);
+        }
+    }
+    let name = "uwb_jni_global_refs";
+    writer.family(name, "gauge", "JNI global references held by the native stack, by kind.");
+    for (kind, count) in global_refs {
+        writer.sample(name, &[("kind", kind.label())], count);
+    }
+    writer.text
+}
+
+/// Builds the HTTP response to `request`, rendering the metrics for `GET /metrics` only.
+fn build_response<F: FnOnce() -> String>(request: &[u8], render_metrics: F) -> Vec<u8> {
+    let request_line = request.split(|&b| b == b'\r' || b == b'\n').next().unwrap_or_default();
+    let mut parts = request_line.split(|&b| b == b' ');
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some(b"GET"), Some(b"/metrics")) => {
+            ("200 OK", "text/plain; version=0.0.4; charset=utf-8", render_metrics())
+        }
+        (Some(b"GET"), _) => ("404 Not Found", "text/plain; charset=utf-8", String::new()),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", String::new()),
+    };
+    format!(
+        "HTTP/1.0 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+/// An exporter listening on a local port, which stops when dropped.
+struct MetricsExporter {
+    shutdown_sender: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<()>>,
+    runtime: Runtime,
+}
+
+impl MetricsExporter {
+    fn new(port: u16, chips: Vec<(String, ChipMetricsSources)>) -> Result<Self> {
+        let runtime = RuntimeBuilder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("UwbMetricsExporter")
+            .enable_all()
+            .build()
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        // Only reachable from the device itself, the host connects through adb.
+        let std_listener = StdTcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|e| {
+                error!("Metrics exporter: failed to listen on port {}: {:?}", port, e);
+                Error::BadParameters
+            })?;
+        let listener = {
+            let _guard = runtime.enter();
+            TcpListener::from_std(std_listener).map_err(|_| Error::ForeignFunctionInterface)?
+        };
+        info!("Metrics exporter: listening on {:?}", listener.local_addr());
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+        let task = runtime.spawn(async move { serve(listener, chips, shutdown_receiver).await });
+        Ok(Self { shutdown_sender: Some(shutdown_sender), task: Some(task), runtime })
+    }
+}
+
+impl Drop for MetricsExporter {
+    fn drop(&mut self) {
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let _ = shutdown_sender.send(());
+        }
+        if let Some(task) = self.task.take() {
+            if let Err(e) = self.runtime.block_on(task) {
+                error!("Metrics exporter: the server task failed: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Serves the clients of the listener one after the other, until `shutdown_receiver` fires.
+async fn serve(
+    listener: TcpListener,
+    chips: Vec<(String, ChipMetricsSources)>,
+    mut shutdown_receiver: oneshot::Receiver<()>,
+) {
+    loop {
+        let stream = tokio::select! {
+            _ = &mut shutdown_receiver => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    error!("Metrics exporter: failed to accept a client: {:?}", e);
+                    continue;
+                }
+            },
+        };
+        tokio::select! {
+            _ = &mut shutdown_receiver => return,
+            served = tokio::time::timeout(CLIENT_TIMEOUT, serve_client(stream, &chips)) => {
+                match served {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => error!("Metrics exporter: failed to serve a client: {:?}", e),
+                    Err(_) => error!("Metrics exporter: the client timed out"),
+                }
+            }
+        }
+    }
+}
+
+/// Reads the request of the client and writes the response.
+async fn serve_client(
+    mut stream: TcpStream,
+    chips: &[(String, ChipMetricsSources)],
+) -> std::io::Result<()> {
+    let mut request = vec![];
+    let mut buffer = [0; READ_BUFFER_SIZE];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_SIZE {
+        let len = stream.read(&mut buffer).await?;
+        if len == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..len]);
+    }
+    let response = build_response(&request, || {
+        let snapshots = chips
+            .iter()
+            .map(|(chip_id, sources)| (chip_id.clone(), sources.snapshot()))
+            .collect::<Vec<_>>();
+        render(&snapshots, &GLOBAL_REF_COUNTS.snapshot())
+    });
+    stream.write_all(&response).await?;
+    stream.shutdown().await
+}
+
+/// Starts exporting the metrics of the chips on the local port, replacing the running exporter.
+pub(crate) fn start(port: u16, chips: Vec<(String, ChipMetricsSources)>) -> Result<()> {
+    let mut exporter = METRICS_EXPORTER.lock().map_err(|_| Error::Unknown)?;
+    // The previous exporter may be listening on the same port.
+    *exporter = None;
+    *exporter = Some(MetricsExporter::new(port, chips)?);
+    Ok(())
+}
+
+/// Stops exporting the metrics, if they are exported.
+pub(crate) fn stop() -> Result<()> {
+    let exporter = METRICS_EXPORTER.lock().map_err(|_| Error::Unknown)?.take();
+    if exporter.is_some() {
+        info!("Metrics exporter: stopped");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chip_metrics() -> ChipMetrics {
+        let mut notification_stats = NotificationStats::default();
+        notification_stats.record(CallbackType::Ranging, Duration::from_micros(50), true);
+        notification_stats.record(CallbackType::Ranging, Duration::from_millis(2), false);
+        notification_stats.on_dropped(3);
+        ChipMetrics {
+            uci_commands: vec![(
+                0x1,
+                0x3,
+                UciCommandStats {
+                    command_count: 2,
+                    response_count: 2,
+                    total_latency_us: 3_000,
+                    max_latency_us: 2_000,
+                    ..Default::default()
+                },
+            )],
+            notification_stats,
+            parse_errors: vec![("SessionInfo", 4)],
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let text = render(
+            &[("default".to_owned(), chip_metrics())],
+            &[(GlobalRefKind::ClassLoader, 1), (GlobalRefKind::ClassCache, 12)],
+        );
+        let lines: Vec<_> = text.lines().collect();
+        for expected in [
+            "# TYPE uwb_uci_commands_total counter",
+            "uwb_uci_commands_total{chip=\"default\",gid=\"0x1\",oid=\"0x3\"} 2",
+            "uwb_uci_response_latency_seconds_sum{chip=\"default\",gid=\"0x1\",oid=\"0x3\"} 0.003",
+            "uwb_uci_response_latency_max_seconds{chip=\"default\",gid=\"0x1\",oid=\"0x3\"} 0.002",
+            "uwb_notification_dispatch_latency_seconds_bucket\
+             {chip=\"default\",callback=\"ranging\",le=\"0.0001\"} 1",
+            "uwb_notification_dispatch_latency_seconds_bucket\
+             {chip=\"default\",callback=\"ranging\",le=\"0.001\"} 1",
+            "uwb_notification_dispatch_latency_seconds_bucket\
+             {chip=\"default\",callback=\"ranging\",le=\"0.005\"} 2",
+            "uwb_notification_dispatch_latency_seconds_bucket\
+             {chip=\"default\",callback=\"ranging\",le=\"+Inf\"} 2",
+            "uwb_notification_dispatch_latency_seconds_sum\
+             {chip=\"default\",callback=\"ranging\"} 0.00205",
+            "uwb_notification_dispatch_latency_seconds_count\
+             {chip=\"default\",callback=\"ranging\"} 2",
+            "uwb_notification_dispatch_failures_total{chip=\"default\",callback=\"ranging\"} 1",
+            "uwb_notifications_dropped_total{chip=\"default\"} 3",
+            "uwb_notification_parse_errors_total{chip=\"default\",family=\"SessionInfo\"} 4",
+            "# TYPE uwb_jni_global_refs gauge",
+            "uwb_jni_global_refs{kind=\"class_loader\"} 1",
+            "uwb_jni_global_refs{kind=\"class_cache\"} 12",
+        ] {
+            assert!(lines.contains(&expected), "missing {}", expected);
+        }
+    }
+
+    #[test]
+    fn test_render_groups_the_samples_by_family() {
+        let text =
+            render(&[("a".to_owned(), chip_metrics()), ("b".to_owned(), chip_metrics())], &[]);
+        assert_eq!(text.matches("# TYPE uwb_uci_commands_total ").count(), 1);
+        let chip_b_sample = text.find("uwb_uci_commands_total{chip=\"b\"").unwrap();
+        assert!(chip_b_sample < text.find("# TYPE uwb_uci_responses_total ").unwrap());
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_build_response() {
+        let response = build_response(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n", || {
+            "uwb 1\n".to_owned()
+        });
+        assert_eq!(
+            String::from_utf8(response).unwrap(),
+            "HTTP/1.0 200 OK\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+             Content-Length: 6\r\nConnection: close\r\n\r\nuwb 1\n"
+        );
+        let response = build_response(b"GET / HTTP/1.1\r\n\r\n", || unreachable!());
+        assert!(response.starts_with(b"HTTP/1.0 404 Not Found\r\n"));
+        let response = build_response(b"POST /metrics HTTP/1.1\r\n\r\n", || unreachable!());
+        assert!(response.starts_with(b"HTTP/1.0 405 Method Not Allowed\r\n"));
+    }
+}
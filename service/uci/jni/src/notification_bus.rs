@@ -0,0 +1,202 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bus publishing the notifications of a chip to the host-side consumers which track them,
+//! besides their delivery to Java by NotificationManagerAndroid.
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use log::error;
+use uwb_core::uci::SessionNotification;
+use uwb_uci_packets::SessionState;
+
+use crate::data_tx_journal::DataTxJournal;
+use crate::session_registry::SessionRegistry;
+use crate::session_token_audit::SessionTokenAudit;
+
+/// A notification published on the bus.
+pub(crate) enum NotificationEvent<'a> {
+    /// A session notification, published before it is delivered to Java.
+    Session(&'a SessionNotification),
+    /// A data packet received by a session from the UWBS at `received_at`.
+    DataRcv { session_token: u32, received_at: Instant },
+}
+
+/// A consumer of the notifications published on the bus.
+pub(crate) trait NotificationSubscriber: Send {
+    /// Handles a notification published at `now`.
+    fn on_notification_event(&mut self, event: &NotificationEvent, now: Instant);
+}
+
+struct Subscription {
+    name: &'static str,
+    subscriber: Arc<Mutex<dyn NotificationSubscriber>>,
+}
+
+/// The subscribers of the notifications of one chip, in the order they are published to.
+#[derive(Default)]
+pub(crate) struct NotificationBus {
+    subscriptions: Vec<Subscription>,
+}
+
+impl NotificationBus {
+    /// Subscribes a consumer to the notifications. `name` identifies it in the logs.
+    pub fn subscribe(
+        &mut self,
+        name: &'static str,
+        subscriber: Arc<Mutex<dyn NotificationSubscriber>>,
+    ) {
+        self.subscriptions.push(Subscription { name, subscriber });
+    }
+
+    /// Publishes a notification to every subscriber. A subscriber which cannot be locked misses
+    /// it, without keeping the others from handling it.
+    pub fn publish(&self, event: NotificationEvent) {
+        let now = Instant::now();
+        for subscription in self.subscriptions.iter() {
+            match subscription.subscriber.lock() {
+                Ok(mut subscriber) => subscriber.on_notification_event(&event, now),
+                Err(e) => error!("UCI JNI: failed to lock {}: {:?}", subscription.name, e),
+            }
+        }
+    }
+}
+
+/// The session registry tracks the state and the activity of the sessions.
+impl NotificationSubscriber for SessionRegistry {
+    fn on_notification_event(&mut self, event: &NotificationEvent, now: Instant) {
+        match event {
+            NotificationEvent::Session(SessionNotification::Status {
+                session_id,
+                session_state,
+                ..
+            }) => self.on_session_state(*session_id, *session_state as u8, now),
+            NotificationEvent::Session(SessionNotification::SessionInfo(range_data)) => {
+                self.on_session_activity(range_data.session_token, now)
+            }
+            NotificationEvent::DataRcv { session_token, received_at } => {
+                self.on_data_rcv(*session_token, *received_at)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The session token audit learns the session token of each session from its status
+/// notifications.
+impl NotificationSubscriber for SessionTokenAudit {
+    fn on_notification_event(&mut self, event: &NotificationEvent, now: Instant) {
+        if let NotificationEvent::Session(SessionNotification::Status {
+            session_id,
+            session_token,
+            session_state,
+            ..
+        }) = event
+        {
+            self.on_session_status(
+                *session_id,
+                *session_token,
+                *session_state == SessionState::SessionStateDeinit,
+                now,
+            )
+        }
+    }
+}
+
+/// The data transfer journal accounts the data packets sent with their acknowledgements, and
+/// forgets the packets of the deinitialized sessions.
+impl NotificationSubscriber for DataTxJournal {
+    fn on_notification_event(&mut self, event: &NotificationEvent, _now: Instant) {
+        match event {
+            NotificationEvent::Session(SessionNotification::DataTransferStatus {
+                session_token,
+                uci_sequence_number,
+                status,
+                ..
+            }) => self.on_transfer_status(*session_token, *uci_sequence_number, u8::from(*status)),
+            NotificationEvent::Session(SessionNotification::Status {
+                session_id,
+                session_state,
+                ..
+            }) if *session_state == SessionState::SessionStateDeinit => {
+                self.on_session_deinit(*session_id)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct DataRcvRecorder {
+        session_tokens: Vec<u32>,
+    }
+
+    impl NotificationSubscriber for DataRcvRecorder {
+        fn on_notification_event(&mut self, event: &NotificationEvent, _now: Instant) {
+            if let NotificationEvent::DataRcv { session_token, .. } = event {
+                self.session_tokens.push(*session_token);
+            }
+        }
+    }
+
+    #[test]
+    fn test_publish_to_every_subscriber() {
+        let first = Arc::new(Mutex::new(DataRcvRecorder::default()));
+        let second = Arc::new(Mutex::new(DataRcvRecorder::default()));
+        let mut bus = NotificationBus::default();
+        bus.subscribe("first", first.clone());
+        bus.subscribe("second", second.clone());
+
+        bus.publish(NotificationEvent::DataRcv { session_token: 1, received_at: Instant::now() });
+        bus.publish(NotificationEvent::DataRcv { session_token: 2, received_at: Instant::now() });
+        assert_eq!(first.lock().unwrap().session_tokens, vec![1, 2]);
+        assert_eq!(second.lock().unwrap().session_tokens, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_poisoned_subscriber_does_not_block_the_others() {
+        let poisoned = Arc::new(Mutex::new(DataRcvRecorder::default()));
+        let healthy = Arc::new(Mutex::new(DataRcvRecorder::default()));
+        let poisoned_clone = poisoned.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoned_clone.lock().unwrap();
+            panic!("poisons the subscriber");
+        })
+        .join();
+        let mut bus = NotificationBus::default();
+        bus.subscribe("poisoned", poisoned);
+        bus.subscribe("healthy", healthy.clone());
+
+        bus.publish(NotificationEvent::DataRcv { session_token: 1, received_at: Instant::now() });
+        assert_eq!(healthy.lock().unwrap().session_tokens, vec![1]);
+    }
+
+    #[test]
+    fn test_session_registry_subscriber() {
+        let start = Instant::now();
+        let registry = Arc::new(Mutex::new(SessionRegistry::default()));
+        registry.lock().unwrap().on_session_init(1, 0x00, start);
+        let mut bus = NotificationBus::default();
+        bus.subscribe("session registry", registry.clone());
+
+        let received_at = start + std::time::Duration::from_secs(1);
+        bus.publish(NotificationEvent::DataRcv { session_token: 1, received_at });
+        assert_eq!(registry.lock().unwrap().list()[0].1.last_activity, received_at);
+    }
+}
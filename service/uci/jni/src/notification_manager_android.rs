@@ -14,243 +14,75 @@
 
 //! Implementation of NotificationManagerAndroid and its builder.
 
+use crate::advertiser_tracker::AdvertiserTracker;
+use crate::callback_slot::{CallbackGuard, CallbackSlot, CallbackView};
+use crate::feature_flags::{self, FeatureFlag};
+use crate::firmware_log::FirmwareLog;
+use crate::global_ref_accounting::{GlobalRefKind, TrackedGlobalRef, TrackedRef, WeakTrackedRef};
+use crate::java_capabilities::{JavaCapabilities, JavaSupport};
 use crate::jclass_name::{
     MULTICAST_LIST_UPDATE_STATUS_CLASS, UWB_DL_TDOA_MEASUREMENT_CLASS,
     UWB_OWR_AOA_MEASUREMENT_CLASS, UWB_RADAR_DATA_CLASS, UWB_RADAR_SWEEP_DATA_CLASS,
     UWB_RANGING_DATA_CLASS, UWB_TWO_WAY_MEASUREMENT_CLASS,
 };
+use crate::latency_trace::{self, TraceSection};
+use crate::location_privacy::{coarsen_range_data, DEFAULT_DISTANCE_STEP_CM};
+use crate::measurement_conversion::{
+    dl_tdoa_measurement_args, dl_tdoa_measurements, owr_aoa_measurement_args, owr_aoa_measurements,
+    range_data_header_args, two_way_measurement_args, two_way_measurements, JavaArg,
+    TwoWayRangingMeasurement, DL_TDOA_MEASUREMENT_SIGNATURE, OWR_AOA_MEASUREMENT_SIGNATURE,
+    TWO_WAY_MEASUREMENT_SIGNATURE,
+};
+use crate::notification_bus::{NotificationBus, NotificationEvent};
+use crate::notification_queue::{BackpressurePolicy, NotificationQueue};
+use crate::notification_replay::{ReplayBuffer, ReplayedNotification, SessionStatusKey};
+use crate::notification_stats::{CallbackType, NotificationStats};
+use crate::parse_error_stats::ParseErrorStats;
+use crate::protocol_error::{NotificationDump, ProtocolErrorReporter};
+use crate::proximity::{ProximityEvent, ProximityTracker};
+use crate::radar_decimation::RadarDecimator;
+use crate::ranging_advisor::RangingAdvisor;
+use crate::ranging_attestation::{AttestationSigner, RangingAttestation};
+use crate::ranging_diagnostics::diagnostics_session_token;
+use crate::session_aggregation::SessionAggregator;
+use crate::session_delivery::SessionDeliveryScheduler;
+use crate::session_notification_filter::SessionNotificationFilter;
+use crate::session_transition::SessionTransitionValidator;
+use crate::uci_log_trigger::UciLogTrigger;
+use crate::uci_metrics::UciMetrics;
+use crate::vendor_notification_router::{VendorNotificationRouter, VENDOR_CALLBACK_SIGNATURE};
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Instant;
 
 use jni::errors::Error as JNIError;
 use jni::objects::{GlobalRef, JClass, JMethodID, JObject, JValue};
 use jni::signature::TypeSignature;
-use jni::sys::jvalue;
+use jni::sys::{jboolean, jbyteArray, jvalue};
 use jni::{AttachGuard, JavaVM};
 use log::{debug, error};
 use uwb_core::error::{Error as UwbError, Result as UwbResult};
-use uwb_core::params::{ControleeStatusList, UwbAddress};
+use uwb_core::params::{ControleeStatusList, RawUciMessage, UwbAddress};
 use uwb_core::uci::uci_manager_sync::{NotificationManager, NotificationManagerBuilder};
 use uwb_core::uci::{
     CoreNotification, DataRcvNotification, RadarDataRcvNotification, RangingMeasurements,
     SessionNotification, SessionRangeData,
 };
 use uwb_uci_packets::{
-    radar_bytes_per_sample_value, ExtendedAddressDlTdoaRangingMeasurement,
-    ExtendedAddressOwrAoaRangingMeasurement, ExtendedAddressTwoWayRangingMeasurement,
-    MacAddressIndicator, RangingMeasurementType, SessionState,
-    ShortAddressDlTdoaRangingMeasurement, ShortAddressOwrAoaRangingMeasurement,
-    ShortAddressTwoWayRangingMeasurement, StatusCode,
+    radar_bytes_per_sample_value, RangingMeasurementType, SessionState, StatusCode,
 };
 
-// Byte size of mac address length:
-const SHORT_MAC_ADDRESS_LEN: i32 = 2;
-const EXTENDED_MAC_ADDRESS_LEN: i32 = 8;
-const MAX_ANCHOR_LOCATION_LEN: i32 = 12;
-const MAX_RANGING_ROUNDS_LEN: i32 = 16;
 const MAX_RADAR_VENDOR_DATA_LEN: i32 = 256;
+// UCI_STATUS_OK, for the statuses which are not parsed into a StatusCode.
+const UCI_STATUS_OK: u8 = 0x00;
+// STATUS_CODE_DATA_TRANSFER_NTF_ERROR_NO_CREDIT_AVAILABLE in the DATA_TRANSFER_STATUS_NTF.
+const DATA_TRANSFER_STATUS_ERROR_NO_CREDIT_AVAILABLE: u8 = 0x03;
 
 // Maximum allowed number of Java Object to be allocated inside with_local_frame
 const MAX_JAVA_OBJECTS_CAPACITY: i32 = 50;
 
-#[derive(Debug, PartialEq)]
-enum MacAddress {
-    Short(u16),
-    Extended(u64),
-}
-impl MacAddress {
-    fn into_ne_bytes(self) -> Vec<u8> {
-        match self {
-            MacAddress::Short(val) => val.to_ne_bytes().into(),
-            MacAddress::Extended(val) => val.to_ne_bytes().into(),
-        }
-    }
-}
-
-struct TwoWayRangingMeasurement {
-    mac_address: MacAddress,
-    status: StatusCode,
-    nlos: u8,
-    distance: u16,
-    aoa_azimuth: u16,
-    aoa_azimuth_fom: u8,
-    aoa_elevation: u16,
-    aoa_elevation_fom: u8,
-    aoa_destination_azimuth: u16,
-    aoa_destination_azimuth_fom: u8,
-    aoa_destination_elevation: u16,
-    aoa_destination_elevation_fom: u8,
-    slot_index: u8,
-    rssi: u8,
-}
-
-struct OwrAoaRangingMeasurement {
-    mac_address: MacAddress,
-    status: StatusCode,
-    nlos: u8,
-    frame_sequence_number: u8,
-    block_index: u16,
-    aoa_azimuth: u16,
-    aoa_azimuth_fom: u8,
-    aoa_elevation: u16,
-    aoa_elevation_fom: u8,
-}
-
-impl From<ShortAddressTwoWayRangingMeasurement> for TwoWayRangingMeasurement {
-    fn from(measurement: ShortAddressTwoWayRangingMeasurement) -> Self {
-        TwoWayRangingMeasurement {
-            mac_address: MacAddress::Short(measurement.mac_address),
-            status: (measurement.status),
-            nlos: (measurement.nlos),
-            distance: (measurement.distance),
-            aoa_azimuth: (measurement.aoa_azimuth),
-            aoa_azimuth_fom: (measurement.aoa_azimuth_fom),
-            aoa_elevation: (measurement.aoa_elevation),
-            aoa_elevation_fom: (measurement.aoa_elevation_fom),
-            aoa_destination_azimuth: (measurement.aoa_destination_azimuth),
-            aoa_destination_azimuth_fom: (measurement.aoa_destination_azimuth_fom),
-            aoa_destination_elevation: (measurement.aoa_destination_elevation),
-            aoa_destination_elevation_fom: (measurement.aoa_destination_elevation_fom),
-            slot_index: (measurement.slot_index),
-            rssi: (measurement.rssi),
-        }
-    }
-}
-
-impl From<ExtendedAddressTwoWayRangingMeasurement> for TwoWayRangingMeasurement {
-    fn from(measurement: ExtendedAddressTwoWayRangingMeasurement) -> Self {
-        TwoWayRangingMeasurement {
-            mac_address: MacAddress::Extended(measurement.mac_address),
-            status: (measurement.status),
-            nlos: (measurement.nlos),
-            distance: (measurement.distance),
-            aoa_azimuth: (measurement.aoa_azimuth),
-            aoa_azimuth_fom: (measurement.aoa_azimuth_fom),
-            aoa_elevation: (measurement.aoa_elevation),
-            aoa_elevation_fom: (measurement.aoa_elevation_fom),
-            aoa_destination_azimuth: (measurement.aoa_destination_azimuth),
-            aoa_destination_azimuth_fom: (measurement.aoa_destination_azimuth_fom),
-            aoa_destination_elevation: (measurement.aoa_destination_elevation),
-            aoa_destination_elevation_fom: (measurement.aoa_destination_elevation_fom),
-            slot_index: (measurement.slot_index),
-            rssi: (measurement.rssi),
-        }
-    }
-}
-
-impl From<ShortAddressOwrAoaRangingMeasurement> for OwrAoaRangingMeasurement {
-    fn from(measurement: ShortAddressOwrAoaRangingMeasurement) -> Self {
-        OwrAoaRangingMeasurement {
-            mac_address: MacAddress::Short(measurement.mac_address),
-            status: (measurement.status),
-            nlos: (measurement.nlos),
-            frame_sequence_number: (measurement.frame_sequence_number),
-            block_index: (measurement.block_index),
-            aoa_azimuth: (measurement.aoa_azimuth),
-            aoa_azimuth_fom: (measurement.aoa_azimuth_fom),
-            aoa_elevation: (measurement.aoa_elevation),
-            aoa_elevation_fom: (measurement.aoa_elevation_fom),
-        }
-    }
-}
-
-impl From<ExtendedAddressOwrAoaRangingMeasurement> for OwrAoaRangingMeasurement {
-    fn from(measurement: ExtendedAddressOwrAoaRangingMeasurement) -> Self {
-        OwrAoaRangingMeasurement {
-            mac_address: MacAddress::Extended(measurement.mac_address),
-            status: (measurement.status),
-            nlos: (measurement.nlos),
-            frame_sequence_number: (measurement.frame_sequence_number),
-            block_index: (measurement.block_index),
-            aoa_azimuth: (measurement.aoa_azimuth),
-            aoa_azimuth_fom: (measurement.aoa_azimuth_fom),
-            aoa_elevation: (measurement.aoa_elevation),
-            aoa_elevation_fom: (measurement.aoa_elevation_fom),
-        }
-    }
-}
-
-struct DlTdoaRangingMeasurement {
-    mac_address: MacAddress,
-    pub status: u8,
-    pub message_type: u8,
-    pub message_control: u16,
-    pub block_index: u16,
-    pub round_index: u8,
-    pub nlos: u8,
-    pub aoa_azimuth: u16,
-    pub aoa_azimuth_fom: u8,
-    pub aoa_elevation: u16,
-    pub aoa_elevation_fom: u8,
-    pub rssi: u8,
-    pub tx_timestamp: u64,
-    pub rx_timestamp: u64,
-    pub anchor_cfo: u16,
-    pub cfo: u16,
-    pub initiator_reply_time: u32,
-    pub responder_reply_time: u32,
-    pub initiator_responder_tof: u16,
-    pub dt_anchor_location: Vec<u8>,
-    pub ranging_rounds: Vec<u8>,
-}
-
-impl From<ExtendedAddressDlTdoaRangingMeasurement> for DlTdoaRangingMeasurement {
-    fn from(measurement: ExtendedAddressDlTdoaRangingMeasurement) -> Self {
-        DlTdoaRangingMeasurement {
-            mac_address: MacAddress::Extended(measurement.mac_address),
-            status: (measurement.measurement.status),
-            message_type: (measurement.measurement.message_type),
-            message_control: (measurement.measurement.message_control),
-            block_index: (measurement.measurement.block_index),
-            round_index: (measurement.measurement.round_index),
-            nlos: (measurement.measurement.nlos),
-            aoa_azimuth: (measurement.measurement.aoa_azimuth),
-            aoa_azimuth_fom: (measurement.measurement.aoa_azimuth_fom),
-            aoa_elevation: (measurement.measurement.aoa_elevation),
-            aoa_elevation_fom: (measurement.measurement.aoa_elevation_fom),
-            rssi: (measurement.measurement.rssi),
-            tx_timestamp: (measurement.measurement.tx_timestamp),
-            rx_timestamp: (measurement.measurement.rx_timestamp),
-            anchor_cfo: (measurement.measurement.anchor_cfo),
-            cfo: (measurement.measurement.cfo),
-            initiator_reply_time: (measurement.measurement.initiator_reply_time),
-            responder_reply_time: (measurement.measurement.responder_reply_time),
-            initiator_responder_tof: (measurement.measurement.initiator_responder_tof),
-            dt_anchor_location: (measurement.measurement.dt_anchor_location),
-            ranging_rounds: (measurement.measurement.ranging_rounds),
-        }
-    }
-}
-
-impl From<ShortAddressDlTdoaRangingMeasurement> for DlTdoaRangingMeasurement {
-    fn from(measurement: ShortAddressDlTdoaRangingMeasurement) -> Self {
-        DlTdoaRangingMeasurement {
-            mac_address: MacAddress::Short(measurement.mac_address),
-            status: (measurement.measurement.status),
-            message_type: (measurement.measurement.message_type),
-            message_control: (measurement.measurement.message_control),
-            block_index: (measurement.measurement.block_index),
-            round_index: (measurement.measurement.round_index),
-            nlos: (measurement.measurement.nlos),
-            aoa_azimuth: (measurement.measurement.aoa_azimuth),
-            aoa_azimuth_fom: (measurement.measurement.aoa_azimuth_fom),
-            aoa_elevation: (measurement.measurement.aoa_elevation),
-            aoa_elevation_fom: (measurement.measurement.aoa_elevation_fom),
-            rssi: (measurement.measurement.rssi),
-            tx_timestamp: (measurement.measurement.tx_timestamp),
-            rx_timestamp: (measurement.measurement.rx_timestamp),
-            anchor_cfo: (measurement.measurement.anchor_cfo),
-            cfo: (measurement.measurement.cfo),
-            initiator_reply_time: (measurement.measurement.initiator_reply_time),
-            responder_reply_time: (measurement.measurement.responder_reply_time),
-            initiator_responder_tof: (measurement.measurement.initiator_responder_tof),
-            dt_anchor_location: (measurement.measurement.dt_anchor_location),
-            ranging_rounds: (measurement.measurement.ranging_rounds),
-        }
-    }
-}
-
 pub(crate) struct NotificationManagerAndroid {
     pub chip_id: String,
     // 'static annotation is needed as env is 'sent' by tokio::task::spawn_local.
@@ -258,13 +90,59 @@ pub(crate) struct NotificationManagerAndroid {
     /// Global reference to the class loader object (java/lang/ClassLoader) from the java thread
     /// that local java UCI classes can be loaded.
     /// See http://yangyingchao.github.io/android/2015/01/13/Android-JNI-FindClass-Error.html
-    pub class_loader_obj: GlobalRef,
-    /// Global reference to the java class holding the various UCI notification callback functions.
-    pub callback_obj: GlobalRef,
+    pub class_loader_obj: TrackedGlobalRef,
+    /// Weak reference to the java class holding the various UCI notification callback functions,
+    /// as of the last dispatch. None while the callback object is detached. The callback object is
+    /// owned by `callback_slot`, so that it is released as soon as it is replaced.
+    pub callback: CallbackView<WeakTrackedRef<GlobalRef>>,
+    /// The callback object shared with the Dispatcher, which may replace it.
+    pub callback_slot: Arc<CallbackSlot<Option<TrackedGlobalRef>>>,
+    /// The core and session notifications received while the callback object is detached.
+    pub replay_buffer: ReplayBuffer<CoreNotification, SessionNotification>,
     // *_jmethod_id are cached for faster callback using call_method_unchecked
     pub jmethod_id_map: HashMap<String, JMethodID>,
     // jclass are cached for faster callback
-    pub jclass_map: HashMap<String, GlobalRef>,
+    pub jclass_map: HashMap<String, TrackedGlobalRef>,
+    /// Dispatch statistics of this chip, shared with the Dispatcher.
+    pub notification_stats: Arc<Mutex<NotificationStats>>,
+    /// OWR AoA advertisers heard by this chip, shared with the Dispatcher.
+    pub advertiser_tracker: Arc<Mutex<AdvertiserTracker>>,
+    /// Proximity-only sessions of this chip, registered through the Dispatcher.
+    pub proximity_tracker: Arc<Mutex<ProximityTracker>>,
+    /// Per-session notification filters of this chip, set through the Dispatcher.
+    pub session_notification_filter: Arc<Mutex<SessionNotificationFilter>>,
+    /// Host-side consumers of the notifications of this chip, subscribed by the Dispatcher.
+    pub notification_bus: NotificationBus,
+    /// UCI command metrics of this chip, which detect the retry storms.
+    pub uci_metrics: Arc<Mutex<UciMetrics>>,
+    /// Conversion failures of this chip, fetched through the Dispatcher.
+    pub parse_error_stats: Arc<Mutex<ParseErrorStats>>,
+    /// Firmware log channel of this chip, set through the Dispatcher.
+    pub firmware_log: Arc<Mutex<FirmwareLog>>,
+    /// Java callbacks of the vendor notifications of this chip by GID, set through the
+    /// Dispatcher.
+    pub vendor_notification_router: Arc<Mutex<VendorNotificationRouter>>,
+    /// Radar sweep decimation of the sessions of this chip, set through the Dispatcher.
+    pub radar_decimator: Arc<Mutex<RadarDecimator>>,
+    /// Range data delivery preferences of the sessions of this chip, set through the Dispatcher.
+    pub session_delivery: Arc<Mutex<SessionDeliveryScheduler>>,
+    /// Aggregation groups of the sessions of this chip, set through the Dispatcher.
+    pub session_aggregator: Arc<Mutex<SessionAggregator>>,
+    /// Error-triggered UCI log of this chip, started by the protocol errors.
+    pub uci_log_trigger: Arc<Mutex<UciLogTrigger>>,
+    /// Attestation of the measurement streams of the sessions of this chip, set through the
+    /// Dispatcher.
+    pub ranging_attestation: Arc<Mutex<RangingAttestation>>,
+    /// Signs the segments of the attested measurement streams of this chip off the dispatch thread.
+    pub attestation_signer: AttestationSigner,
+    /// Rate limiter of the protocol error reports of this chip.
+    pub protocol_error_reporter: ProtocolErrorReporter,
+    /// Ranging interval suggestions of the sessions of this chip.
+    pub ranging_advisor: RangingAdvisor,
+    /// Validator of the session state transitions notified by this chip.
+    pub session_transition_validator: SessionTransitionValidator,
+    /// The optional Java classes and callbacks available, probed when the manager is built.
+    pub java_capabilities: JavaCapabilities,
 }
 
 // TODO(b/246678053): Need to add callbacks for Data Packet Rx, and Data Packet Tx events (like
@@ -273,8 +151,8 @@ impl NotificationManagerAndroid {
     /// Finds JClass stored in jclass map. Should be a member function, but disjoint field borrow
     /// checker fails and mutability of individual fields has to be annotated.
     fn find_local_class<'a>(
-        jclass_map: &'a mut HashMap<String, GlobalRef>,
-        class_loader_obj: &'a GlobalRef,
+        jclass_map: &'a mut HashMap<String, TrackedGlobalRef>,
+        class_loader_obj: &'a TrackedGlobalRef,
         env: &'a AttachGuard<'static>,
         class_name: &'a str,
     ) -> Result<JClass<'a>, JNIError> {
@@ -306,18 +184,335 @@ impl NotificationManagerAndroid {
                 }
             }?;
             // Cache JClass as a global reference.
-            jclass_map.insert(
-                class_name.to_owned(),
-                env.new_global_ref(jclass).map_err(|e| {
-                    error!("UCI JNI: global reference conversion failed: {:?}", e);
-                    e
-                })?,
-            );
+            let jclass = env.new_global_ref(jclass).map_err(|e| {
+                error!("UCI JNI: global reference conversion failed: {:?}", e);
+                e
+            })?;
+            jclass_map
+                .insert(class_name.to_owned(), TrackedRef::new(jclass, GlobalRefKind::ClassCache));
         }
         // Return JClass
         Ok(jclass_map.get(class_name).unwrap().as_obj().into())
     }
 
+    /// Catches up with a replacement of the callback object, before dispatching a notification
+    /// with it. The new object may be of another class, so the method IDs and the optional
+    /// callbacks are probed again. Returns whether a callback object is attached.
+    fn sync_callback_obj(&mut self, callback: &CallbackGuard<Option<TrackedGlobalRef>>) -> bool {
+        if self.callback.sync(callback, TrackedRef::downgrade) {
+            debug!("UCI JNI: callback object replaced, epoch {}", callback.epoch());
+            self.jmethod_id_map.clear();
+            if self.callback.get().is_some() {
+                self.probe_java_capabilities();
+            }
+        }
+        self.callback.get().is_some()
+    }
+
+    /// Replays the notifications received while the callback object was detached.
+    fn replay_notifications(&mut self) {
+        if self.replay_buffer.is_empty() {
+            return;
+        }
+        let (notifications, dropped_count) = self.replay_buffer.take();
+        debug!("UCI JNI: replaying {} notifications", notifications.len());
+        if dropped_count > 0 {
+            if let Err(e) = self.report_notification_overflow(dropped_count) {
+                error!("UCI JNI: failed to report the notifications not replayed: {:?}", e);
+            }
+        }
+        for notification in notifications {
+            let result = match notification {
+                ReplayedNotification::Core(notification) => {
+                    self.dispatch_core_notification(notification)
+                }
+                ReplayedNotification::Session(notification) => {
+                    self.dispatch_session_notification(notification)
+                }
+            };
+            if let Err(e) = result {
+                error!("UCI JNI: failed to replay a notification: {:?}", e);
+            }
+        }
+    }
+
+    /// Probes the optional Java classes and callbacks, which are cached when found.
+    fn probe_java_capabilities(&mut self) {
+        let java_capabilities = JavaCapabilities::probe(|support| self.is_java_supported(support));
+        self.java_capabilities = java_capabilities;
+    }
+
+    fn is_java_supported(&mut self, support: JavaSupport) -> bool {
+        match support {
+            JavaSupport::RadarData => {
+                self.has_java_class(UWB_RADAR_DATA_CLASS)
+                    && self.has_java_class(UWB_RADAR_SWEEP_DATA_CLASS)
+                    && self.has_java_callback(
+                        "onRadarDataMessageReceived",
+                        &("(L".to_owned() + UWB_RADAR_DATA_CLASS + ";)V"),
+                    )
+            }
+            JavaSupport::DlTdoaMeasurement => self.has_java_class(UWB_DL_TDOA_MEASUREMENT_CLASS),
+            JavaSupport::OwrAoaMeasurement => self.has_java_class(UWB_OWR_AOA_MEASUREMENT_CLASS),
+            JavaSupport::ProtocolError => self.has_java_callback(
+                "onProtocolError",
+                "(Ljava/lang/String;Ljava/lang/String;JLjava/lang/String;)V",
+            ),
+            JavaSupport::SessionTransitionViolation => {
+                self.has_java_callback("onSessionStateTransitionViolation", "(JIIII)V")
+            }
+            JavaSupport::RangeKeepAlive => self.has_java_callback("onRangeKeepAlive", "(JJ)V"),
+        }
+    }
+
+    /// Returns whether the class can be loaded. A missing class leaves a pending Java exception,
+    /// which is cleared.
+    fn has_java_class(&mut self, class_name: &str) -> bool {
+        let found = NotificationManagerAndroid::find_local_class(
+            &mut self.jclass_map,
+            &self.class_loader_obj,
+            &self.env,
+            class_name,
+        )
+        .is_ok();
+        if !found {
+            let _ = self.env.exception_clear();
+        }
+        found
+    }
+
+    /// Returns whether the callback object has the method. A missing method leaves a pending Java
+    /// exception, which is cleared.
+    fn has_java_callback(&mut self, name: &str, sig: &str) -> bool {
+        let name_signature = name.to_owned() + sig;
+        if self.jmethod_id_map.contains_key(&name_signature) {
+            return true;
+        }
+        let callback_obj = match self.callback.get().and_then(WeakTrackedRef::upgrade) {
+            Some(callback_obj) => callback_obj,
+            None => return false,
+        };
+        match self.env.get_method_id(callback_obj.as_obj(), name, sig) {
+            Ok(jmethod_id) => {
+                self.jmethod_id_map.insert(name_signature, jmethod_id);
+                true
+            }
+            Err(_) => {
+                let _ = self.env.exception_clear();
+                false
+            }
+        }
+    }
+
+    /// Streams the firmware logs carried by a vendor notification to Java.
+    fn stream_firmware_log(&mut self, start: Instant, payload: Vec<u8>) -> UwbResult<()> {
+        let env = *self.env;
+        let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+            let payload_jbytearray = self.env.byte_array_from_slice(&payload)?;
+            // Safety: payload_jbytearray safely instantiated above.
+            let payload_jobject = unsafe { JObject::from_raw(payload_jbytearray) };
+            let chip_id_jobject = *self.env.new_string(&self.chip_id)?;
+            self.cached_jni_call(
+                "onFirmwareLogReceived",
+                "([BLjava/lang/String;)V",
+                &[
+                    jvalue::from(JValue::Object(payload_jobject)),
+                    jvalue::from(JValue::Object(chip_id_jobject)),
+                ],
+            )
+        });
+        self.record_dispatch(CallbackType::Vendor, start, result.is_ok());
+        result.map_err(|_| UwbError::ForeignFunctionInterface)?;
+        Ok(())
+    }
+
+    /// Passes a vendor notification to the Java callback registered for its GID.
+    fn route_vendor_notification(
+        &mut self,
+        start: Instant,
+        java_callback: &str,
+        vendor_notification: RawUciMessage,
+    ) -> UwbResult<()> {
+        let env = *self.env;
+        let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+            let payload_jbytearray =
+                self.env.byte_array_from_slice(&vendor_notification.payload)?;
+            // Safety: payload_jbytearray safely instantiated above.
+            let payload_jobject = unsafe { JObject::from_raw(payload_jbytearray) };
+            let chip_id_jobject = *self.env.new_string(&self.chip_id)?;
+            self.cached_jni_call(
+                java_callback,
+                VENDOR_CALLBACK_SIGNATURE,
+                &[
+                    jvalue::from(JValue::Int(
+                        vendor_notification
+                            .oid
+                            .try_into()
+                            .map_err(|_| JNIError::InvalidCtorReturn)?,
+                    )),
+                    jvalue::from(JValue::Object(payload_jobject)),
+                    jvalue::from(JValue::Object(chip_id_jobject)),
+                ],
+            )
+        });
+        self.record_dispatch(CallbackType::Vendor, start, result.is_ok());
+        if result.is_err() {
+            error!(
+                "UCI JNI: failed to route the vendor notification of GID {:#x} to {}",
+                vendor_notification.gid, java_callback
+            );
+            self.report_protocol_error(
+                "Vendor",
+                NotificationDump::Raw(vendor_notification.payload),
+            );
+        }
+        result.map_err(|_| UwbError::ForeignFunctionInterface)?;
+        Ok(())
+    }
+
+    /// Records the dispatch of a notification of `callback_type` which started at `start`.
+    fn record_dispatch(&self, callback_type: CallbackType, start: Instant, success: bool) {
+        match self.notification_stats.lock() {
+            Ok(mut stats) => stats.record(callback_type, start.elapsed(), success),
+            Err(e) => error!("UCI JNI: failed to lock notification stats: {:?}", e),
+        }
+    }
+
+    /// Records an OWR AoA measurement received from the advertiser `mac_address`.
+    fn track_advertiser(
+        &self,
+        session_id: u32,
+        mac_address: &[u8],
+        block_index: u16,
+        frame_sequence_number: u8,
+    ) {
+        match self.advertiser_tracker.lock() {
+            Ok(mut tracker) => tracker.on_measurement(
+                session_id,
+                mac_address,
+                block_index,
+                frame_sequence_number,
+                Instant::now(),
+            ),
+            Err(e) => error!("UCI JNI: failed to lock advertiser tracker: {:?}", e),
+        }
+    }
+
+    /// Forgets the advertisers of a session which is deinitialized.
+    fn untrack_session_advertisers(&self, session_id: u32) {
+        match self.advertiser_tracker.lock() {
+            Ok(mut tracker) => tracker.remove_session(session_id),
+            Err(e) => error!("UCI JNI: failed to lock advertiser tracker: {:?}", e),
+        }
+    }
+
+    /// Reports the advertisers which were not heard from since the lost advertiser timeout. The
+    /// timeout is evaluated whenever a session notification is received.
+    fn report_lost_advertisers(&mut self) -> Result<(), JNIError> {
+        let lost_advertisers = match self.advertiser_tracker.lock() {
+            Ok(mut tracker) => tracker.take_lost_advertisers(Instant::now()),
+            Err(e) => {
+                error!("UCI JNI: failed to lock advertiser tracker: {:?}", e);
+                return Ok(());
+            }
+        };
+        for advertiser in lost_advertisers {
+            let env = *self.env;
+            env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+                let mac_address_jbytearray =
+                    self.env.byte_array_from_slice(&advertiser.mac_address)?;
+                // Safety: mac_address_jbytearray is safely instantiated above.
+                let mac_address_jobject = unsafe { JObject::from_raw(mac_address_jbytearray) };
+                self.cached_jni_call(
+                    "onOwrAoaAdvertiserLost",
+                    "(J[B)V",
+                    &[
+                        jvalue::from(JValue::Long(advertiser.session_token as i64)),
+                        jvalue::from(JValue::Object(mac_address_jobject)),
+                    ],
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Reports the retry storms detected by the UCI logger since the previous notification. The
+    /// logger runs on the UCI manager thread, which cannot call Java.
+    fn report_retry_storms(&mut self) {
+        let storms = match self.uci_metrics.lock() {
+            Ok(mut metrics) => metrics.take_unreported_storms(),
+            Err(e) => {
+                error!("UCI JNI: failed to lock UCI metrics: {:?}", e);
+                return;
+            }
+        };
+        for storm in storms {
+            let env = *self.env;
+            let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+                let env_chip_id_jobject = *env.new_string(&self.chip_id)?;
+                self.cached_jni_call(
+                    "onUciRetryStorm",
+                    "(IIILjava/lang/String;)V",
+                    &[
+                        jvalue::from(JValue::Int(storm.gid as i32)),
+                        jvalue::from(JValue::Int(storm.oid as i32)),
+                        jvalue::from(JValue::Int(storm.retry_count as i32)),
+                        jvalue::from(JValue::Object(env_chip_id_jobject)),
+                    ],
+                )
+            });
+            if let Err(e) = result {
+                error!("UCI JNI: failed to report the retry storm: {:?}", e);
+            }
+        }
+    }
+
+    /// Feeds the ranging advisor with a session notification, and reports its suggestion if any.
+    fn advise_ranging(&mut self, session_notification: &SessionNotification) {
+        let suggestion = match session_notification {
+            SessionNotification::SessionInfo(range_data) => {
+                let (successful_count, measurement_count) =
+                    count_successful_measurements(&range_data.ranging_measurements);
+                self.ranging_advisor.on_ranging_round(
+                    range_data.session_token,
+                    range_data.current_ranging_interval_ms,
+                    successful_count,
+                    measurement_count,
+                )
+            }
+            SessionNotification::DataTransferStatus { session_token, status, .. } => {
+                if u8::from(*status) == DATA_TRANSFER_STATUS_ERROR_NO_CREDIT_AVAILABLE {
+                    self.ranging_advisor.on_starved_transfer(*session_token);
+                }
+                None
+            }
+            SessionNotification::Status { session_id, session_state, .. } => {
+                if *session_state == SessionState::SessionStateDeinit {
+                    self.ranging_advisor.on_session_deinit(*session_id);
+                }
+                None
+            }
+            _ => None,
+        };
+        let suggestion = match suggestion {
+            Some(suggestion) => suggestion,
+            None => return,
+        };
+        let result = self.cached_jni_call(
+            "onRangingIntervalSuggestion",
+            "(JII)V",
+            &[
+                // session_token below has already been mapped to session_id by uci layer.
+                jvalue::from(JValue::Long(suggestion.session_token as i64)),
+                jvalue::from(JValue::Int(suggestion.ranging_duration_ms as i32)),
+                jvalue::from(JValue::Int(suggestion.block_stride as i32)),
+            ],
+        );
+        if let Err(e) = result {
+            error!("UCI JNI: failed to report the ranging interval suggestion: {:?}", e);
+        }
+    }
+
     fn cached_jni_call(
         &mut self,
         name: &str,
@@ -337,18 +532,25 @@ impl NotificationManagerAndroid {
             );
             return Err(jni::errors::Error::InvalidArgList(type_signature));
         }
+        let callback_obj = self
+            .callback
+            .get()
+            .and_then(WeakTrackedRef::upgrade)
+            .ok_or(JNIError::NullPtr("callback_obj"))?;
         let name_signature = name.to_owned() + sig;
         if !self.jmethod_id_map.contains_key(&name_signature) {
             self.jmethod_id_map.insert(
                 name_signature.clone(),
-                self.env.get_method_id(self.callback_obj.as_obj(), name, sig).map_err(|e| {
+                self.env.get_method_id(callback_obj.as_obj(), name, sig).map_err(|e| {
                     error!("UCI JNI: failed to get method: {:?}", e);
                     e
                 })?,
             );
         }
+        // Ends when the callback returns.
+        let _trace = TraceSection::begin(|| format!("UWB JNI {}", name));
         match self.env.call_method_unchecked(
-            self.callback_obj.as_obj(),
+            callback_obj.as_obj(),
             self.jmethod_id_map.get(&name_signature).unwrap().to_owned(),
             type_signature.ret,
             args,
@@ -368,6 +570,33 @@ impl NotificationManagerAndroid {
         session_state: SessionState,
         reason_code: u8,
     ) -> Result<JObject, JNIError> {
+        if session_state == SessionState::SessionStateDeinit {
+            self.untrack_session_advertisers(session_id);
+            match self.proximity_tracker.lock() {
+                Ok(mut tracker) => tracker.set_session(session_id, None),
+                Err(e) => error!("UCI JNI: failed to lock proximity tracker: {:?}", e),
+            }
+            match self.session_notification_filter.lock() {
+                Ok(mut filter) => {
+                    filter.set_session_info_suppressed(session_id, false);
+                    filter.set_coarse_location(session_id, None);
+                    filter.clear_departed_controlees(session_id);
+                    filter.set_diagnostics_enabled(session_id, false);
+                    filter.set_raw_ranging_data_included(session_id, false);
+                    filter.set_session_id(session_token, None);
+                }
+                Err(e) => error!("UCI JNI: failed to lock session notification filter: {:?}", e),
+            }
+            match self.radar_decimator.lock() {
+                Ok(mut decimator) => decimator.remove_session(session_id),
+                Err(e) => error!("UCI JNI: failed to lock radar decimator: {:?}", e),
+            }
+        } else {
+            match self.session_notification_filter.lock() {
+                Ok(mut filter) => filter.set_session_id(session_token, Some(session_id)),
+                Err(e) => error!("UCI JNI: failed to lock session notification filter: {:?}", e),
+            }
+        }
         self.cached_jni_call(
             "onSessionStatusNotificationReceived",
             "(JIII)V",
@@ -455,169 +684,125 @@ impl NotificationManagerAndroid {
         )
     }
 
-    // TODO(b/246678053): Re-factor usage of the RangingMeasurement enum below, to extract the
-    // fields in a common/caller method (and preferably not handle TwoWay/OwrAoa in this method).
+    /// Copies the raw RANGE_DATA_NTF into a Java byte array, only for the sessions which included
+    /// it, as most consumers ignore it. The others get an empty array.
+    fn raw_ranging_data_jbytearray(
+        &self,
+        range_data: &SessionRangeData,
+    ) -> Result<jbyteArray, JNIError> {
+        // session_token below has already been mapped to session_id by uci layer.
+        let included = match self.session_notification_filter.lock() {
+            Ok(filter) => filter.is_raw_ranging_data_included(range_data.session_token),
+            Err(e) => {
+                error!("UCI JNI: failed to lock session notification filter: {:?}", e);
+                false
+            }
+        };
+        if included {
+            self.env.byte_array_from_slice(&range_data.raw_ranging_data)
+        } else {
+            self.env.new_byte_array(0)
+        }
+    }
+
     fn on_session_dl_tdoa_range_data_notification(
         &mut self,
         range_data: SessionRangeData,
     ) -> Result<JObject, JNIError> {
-        let raw_notification_jbytearray =
-            self.env.byte_array_from_slice(&range_data.raw_ranging_data)?;
-        let measurement_jclass = NotificationManagerAndroid::find_local_class(
-            &mut self.jclass_map,
-            &self.class_loader_obj,
-            &self.env,
+        let raw_notification_jbytearray = self.raw_ranging_data_jbytearray(&range_data)?;
+        let header_args = range_data_header_args(&range_data);
+        let measurements = dl_tdoa_measurements(range_data.ranging_measurements)
+            .ok_or(JNIError::InvalidCtorReturn)?;
+        self.pass_range_data_args(
+            range_data.session_token,
+            header_args,
             UWB_DL_TDOA_MEASUREMENT_CLASS,
-        )?;
-        let bytearray_len: i32 = match &range_data.ranging_measurements {
-            uwb_core::uci::RangingMeasurements::ShortAddressTwoWay(_) => SHORT_MAC_ADDRESS_LEN,
-            uwb_core::uci::RangingMeasurements::ExtendedAddressTwoWay(_) => {
-                EXTENDED_MAC_ADDRESS_LEN
-            }
-            uwb_core::uci::RangingMeasurements::ShortAddressDltdoa(_) => SHORT_MAC_ADDRESS_LEN,
-            uwb_core::uci::RangingMeasurements::ExtendedAddressDltdoa(_) => {
-                EXTENDED_MAC_ADDRESS_LEN
-            }
-            _ => {
-                return Err(JNIError::InvalidCtorReturn);
-            }
-        };
-        let address_jbytearray = self.env.new_byte_array(bytearray_len)?;
-        let anchor_location = self.env.new_byte_array(MAX_ANCHOR_LOCATION_LEN)?;
-        let active_ranging_rounds = self.env.new_byte_array(MAX_RANGING_ROUNDS_LEN)?;
-
-        // Safety: address_jbytearray is safely instantiated above.
-        let address_jobject = unsafe { JObject::from_raw(address_jbytearray) };
-        // Safety: anchor_location is safely instantiated above.
-        let anchor_jobject = unsafe { JObject::from_raw(anchor_location) };
-        // Safety: active_ranging_rounds is safely instantiated above.
-        let active_ranging_rounds_jobject = unsafe { JObject::from_raw(active_ranging_rounds) };
-
-        let zero_initiated_measurement_jobject = self
-            .env
-            .new_object(
-                measurement_jclass,
-                "([BIIIIIIIIIIIJJIIJJI[B[B)V",
-                &[
-                    JValue::Object(address_jobject),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Long(0),
-                    JValue::Long(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Long(0),
-                    JValue::Long(0),
-                    JValue::Int(0),
-                    JValue::Object(anchor_jobject),
-                    JValue::Object(active_ranging_rounds_jobject),
-                ],
-            )
-            .map_err(|e| {
-                error!("UCI JNI: measurement object creation failed: {:?}", e);
-                e
-            })?;
-        let measurement_count: i32 = match &range_data.ranging_measurements {
-            RangingMeasurements::ShortAddressTwoWay(v) => v.len(),
-            RangingMeasurements::ExtendedAddressTwoWay(v) => v.len(),
-            RangingMeasurements::ShortAddressDltdoa(v) => v.len(),
-            RangingMeasurements::ExtendedAddressDltdoa(v) => v.len(),
-            _ => {
-                return Err(JNIError::InvalidCtorReturn);
+            DL_TDOA_MEASUREMENT_SIGNATURE,
+            measurements.iter().map(dl_tdoa_measurement_args).collect(),
+            raw_notification_jbytearray,
+        )
+    }
+
+    fn on_session_owr_aoa_range_data_notification(
+        &mut self,
+        range_data: SessionRangeData,
+    ) -> Result<JObject, JNIError> {
+        if range_data.ranging_measurement_type != RangingMeasurementType::OwrAoa {
+            return Err(JNIError::InvalidCtorReturn);
+        }
+        let raw_notification_jbytearray = self.raw_ranging_data_jbytearray(&range_data)?;
+        let header_args = range_data_header_args(&range_data);
+        let measurements =
+            owr_aoa_measurements(range_data.ranging_measurements, &range_data.raw_ranging_data)
+                .ok_or(JNIError::InvalidCtorReturn)?;
+        for measurement in measurements.iter() {
+            if measurement.status == StatusCode::UciStatusOk {
+                self.track_advertiser(
+                    range_data.session_token,
+                    &measurement.mac_address.to_ne_bytes(),
+                    measurement.block_index,
+                    measurement.frame_sequence_number,
+                );
             }
         }
-        .try_into()
-        .map_err(|_| JNIError::InvalidCtorReturn)?;
-        let mac_indicator = match &range_data.ranging_measurements {
-            RangingMeasurements::ShortAddressTwoWay(_) => MacAddressIndicator::ShortAddress,
-            RangingMeasurements::ExtendedAddressTwoWay(_) => MacAddressIndicator::ExtendedAddress,
-            RangingMeasurements::ShortAddressDltdoa(_) => MacAddressIndicator::ShortAddress,
-            RangingMeasurements::ExtendedAddressDltdoa(_) => MacAddressIndicator::ExtendedAddress,
-            _ => {
-                return Err(JNIError::InvalidCtorReturn);
+        self.pass_range_data_args(
+            range_data.session_token,
+            header_args,
+            UWB_OWR_AOA_MEASUREMENT_CLASS,
+            OWR_AOA_MEASUREMENT_SIGNATURE,
+            measurements.iter().map(owr_aoa_measurement_args).collect(),
+            raw_notification_jbytearray,
+        )
+    }
+
+    /// Converts a constructor argument to a JValue, creating its Java byte array if any.
+    fn java_arg_to_jvalue<'a>(
+        env: &AttachGuard<'static>,
+        arg: &JavaArg,
+    ) -> Result<JValue<'a>, JNIError> {
+        Ok(match arg {
+            JavaArg::Int(value) => JValue::Int(*value),
+            JavaArg::Long(value) => JValue::Long(*value),
+            JavaArg::Float(value) => JValue::Float(*value),
+            JavaArg::Double(value) => JValue::Double(*value),
+            JavaArg::Bytes(bytes) => {
+                let jbytearray = env.byte_array_from_slice(bytes)?;
+                // Safety: jbytearray is safely instantiated above.
+                JValue::Object(unsafe { JObject::from_raw(jbytearray) })
             }
-        };
+        })
+    }
 
-        let measurements_jobjectarray = self.env.new_object_array(
-            measurement_count,
-            measurement_jclass,
-            zero_initiated_measurement_jobject,
+    /// Builds the UwbRangingData of a session from the constructor arguments of its header and
+    /// of its measurements, see measurement_conversion, and passes it to Java. The number of
+    /// measurements passed to Java is the one of `measurement_args`.
+    fn pass_range_data_args(
+        &mut self,
+        session_token: u32,
+        header_args: Vec<JavaArg>,
+        measurement_class: &str,
+        measurement_signature: &str,
+        measurement_args: Vec<Vec<JavaArg>>,
+        raw_notification_jbytearray: jbyteArray,
+    ) -> Result<JObject, JNIError> {
+        let measurement_count: i32 =
+            measurement_args.len().try_into().map_err(|_| JNIError::InvalidCtorReturn)?;
+        let measurement_jclass = NotificationManagerAndroid::find_local_class(
+            &mut self.jclass_map,
+            &self.class_loader_obj,
+            &self.env,
+            measurement_class,
         )?;
-
-        for (i, measurement) in match range_data.ranging_measurements {
-            RangingMeasurements::ShortAddressDltdoa(v) => {
-                v.into_iter().map(DlTdoaRangingMeasurement::from).collect::<Vec<_>>()
-            }
-            RangingMeasurements::ExtendedAddressDltdoa(v) => {
-                v.into_iter().map(DlTdoaRangingMeasurement::from).collect::<Vec<_>>()
-            }
-            _ => Vec::new(),
-        }
-        .into_iter()
-        .enumerate()
-        {
-            // cast to i8 as java do not support unsigned:
-            let mac_address_i8 = measurement
-                .mac_address
-                .into_ne_bytes()
+        let measurements_jobjectarray =
+            self.env.new_object_array(measurement_count, measurement_jclass, JObject::null())?;
+        for (i, args) in measurement_args.iter().enumerate() {
+            let jvalues = args
                 .iter()
-                .map(|b| b.to_owned() as i8)
-                .collect::<Vec<_>>();
-            let mac_address_jbytearray = self.env.new_byte_array(mac_address_i8.len() as i32)?;
-            self.env.set_byte_array_region(mac_address_jbytearray, 0, &mac_address_i8)?;
-
-            let dt_anchor_location_jbytearray =
-                self.env.byte_array_from_slice(&measurement.dt_anchor_location)?;
-
-            let ranging_rounds_jbytearray =
-                self.env.byte_array_from_slice(&measurement.ranging_rounds)?;
-
-            // Safety: mac_address_jbytearray is safely instantiated above.
-            let mac_address_jobject = unsafe { JObject::from_raw(mac_address_jbytearray) };
-            let dt_anchor_location_jobject =
-            // Safety: dt_anchor_location_jbytearray is safely instantiated above.
-                unsafe { JObject::from_raw(dt_anchor_location_jbytearray) };
-            // Safety: ranging_rounds_jbytearray is safely instantiated above.
-            let ranging_rounds_jobject = unsafe { JObject::from_raw(ranging_rounds_jbytearray) };
-
+                .map(|arg| NotificationManagerAndroid::java_arg_to_jvalue(&self.env, arg))
+                .collect::<Result<Vec<_>, JNIError>>()?;
             let measurement_jobject = self
                 .env
-                .new_object(
-                    measurement_jclass,
-                    "([BIIIIIIIIIIIJJIIJJI[B[B)V",
-                    &[
-                        JValue::Object(mac_address_jobject),
-                        JValue::Int(measurement.status as i32),
-                        JValue::Int(measurement.message_type as i32),
-                        JValue::Int(measurement.message_control as i32),
-                        JValue::Int(measurement.block_index as i32),
-                        JValue::Int(measurement.round_index as i32),
-                        JValue::Int(measurement.nlos as i32),
-                        JValue::Int(measurement.aoa_azimuth as i32),
-                        JValue::Int(measurement.aoa_azimuth_fom as i32),
-                        JValue::Int(measurement.aoa_elevation as i32),
-                        JValue::Int(measurement.aoa_elevation_fom as i32),
-                        JValue::Int(measurement.rssi as i32),
-                        JValue::Long(measurement.tx_timestamp as i64),
-                        JValue::Long(measurement.rx_timestamp as i64),
-                        JValue::Int(measurement.anchor_cfo as i32),
-                        JValue::Int(measurement.cfo as i32),
-                        JValue::Long(measurement.initiator_reply_time as i64),
-                        JValue::Long(measurement.responder_reply_time as i64),
-                        JValue::Int(measurement.initiator_responder_tof as i32),
-                        JValue::Object(dt_anchor_location_jobject),
-                        JValue::Object(ranging_rounds_jobject),
-                    ],
-                )
+                .new_object(measurement_jclass, measurement_signature, &jvalues)
                 .map_err(|e| {
                     error!("UCI JNI: measurement object creation failed: {:?}", e);
                     e
@@ -629,6 +814,11 @@ impl NotificationManagerAndroid {
                     e
                 })?;
         }
+        // Safety: measurements_jobjectarray is safely instantiated above.
+        let measurements_jobject = unsafe { JObject::from_raw(measurements_jobjectarray) };
+        // Safety: raw_notification_jbytearray is safely instantiated by the caller.
+        let raw_notification_jobject = unsafe { JObject::from_raw(raw_notification_jbytearray) };
+
         // Create UwbRangingData
         let ranging_data_jclass = NotificationManagerAndroid::find_local_class(
             &mut self.jclass_map,
@@ -636,342 +826,257 @@ impl NotificationManagerAndroid {
             &self.env,
             UWB_RANGING_DATA_CLASS,
         )?;
-
-        let method_sig = "(JJIJIII[L".to_owned() + UWB_DL_TDOA_MEASUREMENT_CLASS + ";[B)V";
-
-        // Safety: measurements_jobjectarray is safely instantiated above.
-        let measurements_jobject = unsafe { JObject::from_raw(measurements_jobjectarray) };
-        // Safety: raw_notification_jbytearray is safely instantiated above.
-        let raw_notification_jobject = unsafe { JObject::from_raw(raw_notification_jbytearray) };
-
-        let range_data_jobject = self
-            .env
-            .new_object(
-                ranging_data_jclass,
-                &method_sig,
-                &[
-                    JValue::Long(range_data.sequence_number as i64),
-                    // session_token below has already been mapped to session_id by uci layer.
-                    JValue::Long(range_data.session_token as i64),
-                    JValue::Int(range_data.rcr_indicator as i32),
-                    JValue::Long(range_data.current_ranging_interval_ms as i64),
-                    JValue::Int(range_data.ranging_measurement_type as i32),
-                    JValue::Int(mac_indicator as i32),
-                    JValue::Int(measurement_count),
-                    JValue::Object(measurements_jobject),
-                    JValue::Object(raw_notification_jobject),
-                ],
-            )
-            .map_err(|e| {
+        let method_sig = "(JJIJIII[L".to_owned() + measurement_class + ";[B)V";
+        let mut jvalues = header_args
+            .iter()
+            .map(|arg| NotificationManagerAndroid::java_arg_to_jvalue(&self.env, arg))
+            .collect::<Result<Vec<_>, JNIError>>()?;
+        jvalues.push(JValue::Int(measurement_count));
+        jvalues.push(JValue::Object(measurements_jobject));
+        jvalues.push(JValue::Object(raw_notification_jobject));
+        let range_data_jobject =
+            self.env.new_object(ranging_data_jclass, &method_sig, &jvalues).map_err(|e| {
                 error!("UCI JNI: Ranging Data object creation failed: {:?}", e);
                 e
             })?;
-
-        let method_sig = "(L".to_owned() + UWB_RANGING_DATA_CLASS + ";)V";
-        self.cached_jni_call(
-            "onRangeDataNotificationReceived",
-            &method_sig,
-            &[jvalue::from(JValue::Object(range_data_jobject))],
-        )
+        self.pass_range_data_object(session_token, range_data_jobject)
     }
 
-    fn on_two_way_range_data_notification(
-        &mut self,
-        bytearray_len: i32,
-        measurement_count: i32,
-        measurements: Vec<TwoWayRangingMeasurement>,
-    ) -> Result<jni::sys::jobjectArray, JNIError> {
-        let measurement_jclass = NotificationManagerAndroid::find_local_class(
-            &mut self.jclass_map,
-            &self.class_loader_obj,
-            &self.env,
-            UWB_TWO_WAY_MEASUREMENT_CLASS,
-        )?;
-        let address_jbytearray = self.env.new_byte_array(bytearray_len)?;
-
-        // Safety: address_jbytearray is safely instantiated above.
-        let address_jobject = unsafe { JObject::from_raw(address_jbytearray) };
-
-        let zero_initiated_measurement_jobject = self
-            .env
-            .new_object(
-                measurement_jclass,
-                "([BIIIIIIIIIIIII)V",
-                &[
-                    JValue::Object(address_jobject),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                    JValue::Int(0),
-                ],
-            )
-            .map_err(|e| {
-                error!("UCI JNI: measurement object creation failed: {:?}", e);
-                e
-            })?;
-
-        let measurements_jobjectarray = self.env.new_object_array(
-            measurement_count,
-            measurement_jclass,
-            zero_initiated_measurement_jobject,
-        )?;
-        for (i, measurement) in measurements.into_iter().enumerate() {
-            // cast to i8 as java do not support unsigned:
-            let mac_address_i8 = measurement
-                .mac_address
-                .into_ne_bytes()
-                .iter()
-                .map(|b| b.to_owned() as i8)
-                .collect::<Vec<_>>();
-            let mac_address_jbytearray = self.env.new_byte_array(mac_address_i8.len() as i32)?;
-            self.env.set_byte_array_region(mac_address_jbytearray, 0, &mac_address_i8)?;
-            // casting as i32 is fine since it is wider than actual integer type.
-
-            // Safety: mac_address_jbytearray is safely instantiated above.
-            let mac_address_jobject = unsafe { JObject::from_raw(mac_address_jbytearray) };
-            let measurement_jobject = self
-                .env
-                .new_object(
-                    measurement_jclass,
-                    "([BIIIIIIIIIIIII)V",
-                    &[
-                        JValue::Object(mac_address_jobject),
-                        JValue::Int(i32::from(measurement.status)),
-                        JValue::Int(measurement.nlos as i32),
-                        JValue::Int(measurement.distance as i32),
-                        JValue::Int(measurement.aoa_azimuth as i32),
-                        JValue::Int(measurement.aoa_azimuth_fom as i32),
-                        JValue::Int(measurement.aoa_elevation as i32),
-                        JValue::Int(measurement.aoa_elevation_fom as i32),
-                        JValue::Int(measurement.aoa_destination_azimuth as i32),
-                        JValue::Int(measurement.aoa_destination_azimuth_fom as i32),
-                        JValue::Int(measurement.aoa_destination_elevation as i32),
-                        JValue::Int(measurement.aoa_destination_elevation_fom as i32),
-                        JValue::Int(measurement.slot_index as i32),
-                        JValue::Int(measurement.rssi as i32),
-                    ],
-                )
-                .map_err(|e| {
-                    error!("UCI JNI: measurement object creation failed: {:?}", e);
-                    e
-                })?;
-            self.env
-                .set_object_array_element(measurements_jobjectarray, i as i32, measurement_jobject)
-                .map_err(|e| {
-                    error!("UCI JNI: measurement object copy failed: {:?}", e);
-                    e
-                })?;
+    /// Feeds the measurement streams of the attested sessions, and queues their segments to be
+    /// signed once complete. A session which stops ranging has its incomplete segment signed.
+    fn attest_ranging(&self, session_notification: &SessionNotification) {
+        let pending_attestation = match self.ranging_attestation.lock() {
+            Ok(mut attestation) => match session_notification {
+                SessionNotification::SessionInfo(range_data) => attestation
+                    .on_range_data(range_data.session_token, &range_data.raw_ranging_data),
+                SessionNotification::Status { session_id, session_state, .. }
+                    if *session_state != SessionState::SessionStateActive =>
+                {
+                    attestation.on_session_stopped(*session_id)
+                }
+                _ => None,
+            },
+            Err(e) => {
+                error!("UCI JNI: failed to lock ranging attestation: {:?}", e);
+                None
+            }
+        };
+        if let Some(pending_attestation) = pending_attestation {
+            self.attestation_signer.submit(pending_attestation);
         }
-
-        Ok(measurements_jobjectarray)
     }
 
-    fn on_session_owr_aoa_range_data_notification(
-        &mut self,
-        range_data: SessionRangeData,
-    ) -> Result<JObject, JNIError> {
-        if range_data.ranging_measurement_type != RangingMeasurementType::OwrAoa {
-            return Err(JNIError::InvalidCtorReturn);
+    /// Reports the session state transitions breaking the session state machine. The notification
+    /// itself is still dispatched, the report is only a diagnostic.
+    fn validate_session_transition(&mut self, session_notification: &SessionNotification) {
+        let invalid_transition = match session_notification {
+            SessionNotification::Status { session_id, session_state, reason_code, .. } => self
+                .session_transition_validator
+                .on_session_state(*session_id, *session_state as u8, *reason_code),
+            _ => None,
+        };
+        let invalid_transition = match invalid_transition {
+            Some(invalid_transition) => invalid_transition,
+            None => return,
+        };
+        error!("UCI JNI: invalid session state transition: {:?}", invalid_transition);
+        if !self.java_capabilities.check(JavaSupport::SessionTransitionViolation, "only logged") {
+            return;
         }
+        let result = self.cached_jni_call(
+            "onSessionStateTransitionViolation",
+            "(JIIII)V",
+            &[
+                jvalue::from(JValue::Long(invalid_transition.session_id as i64)),
+                jvalue::from(JValue::Int(invalid_transition.previous_state as i32)),
+                jvalue::from(JValue::Int(invalid_transition.session_state as i32)),
+                jvalue::from(JValue::Int(invalid_transition.reason_code as i32)),
+                jvalue::from(JValue::Int(invalid_transition.violation as i32)),
+            ],
+        );
+        if let Err(e) = result {
+            error!("UCI JNI: failed to report the invalid session state transition: {:?}", e);
+        }
+    }
 
-        let raw_notification_jbytearray =
-            self.env.byte_array_from_slice(&range_data.raw_ranging_data)?;
-
-        let (mac_indicator, measurement): (MacAddressIndicator, OwrAoaRangingMeasurement) =
-            match range_data.ranging_measurements {
-                RangingMeasurements::ExtendedAddressOwrAoa(m) => {
-                    (MacAddressIndicator::ExtendedAddress, m.into())
-                }
-                RangingMeasurements::ShortAddressOwrAoa(m) => {
-                    (MacAddressIndicator::ShortAddress, m.into())
-                }
-                _ => {
-                    return Err(JNIError::InvalidCtorReturn);
-                }
-            };
-
-        // cast to i8 as java do not support unsigned.
-        let mac_address_i8 = measurement
-            .mac_address
-            .into_ne_bytes()
-            .iter()
-            .map(|b| b.to_owned() as i8)
-            .collect::<Vec<_>>();
-        // casting as i32 is fine since it is wider than actual integer type.
-        let mac_address_jbytearray = self.env.new_byte_array(mac_address_i8.len() as i32)?;
-        self.env.set_byte_array_region(mac_address_jbytearray, 0, &mac_address_i8)?;
-        // Safety: mac_address_jbytearray is safely instantiated above.
-        let mac_address_jobject = unsafe { JObject::from_raw(mac_address_jbytearray) };
-
-        let measurement_jclass = NotificationManagerAndroid::find_local_class(
-            &mut self.jclass_map,
-            &self.class_loader_obj,
-            &self.env,
-            UWB_OWR_AOA_MEASUREMENT_CLASS,
-        )?;
-        let measurement_jobject = self
-            .env
-            .new_object(
-                measurement_jclass,
-                "([BIIIIIIII)V",
-                &[
-                    JValue::Object(mac_address_jobject),
-                    JValue::Int(i32::from(measurement.status)),
-                    JValue::Int(measurement.nlos as i32),
-                    JValue::Int(measurement.frame_sequence_number as i32),
-                    JValue::Int(measurement.block_index as i32),
-                    JValue::Int(measurement.aoa_azimuth as i32),
-                    JValue::Int(measurement.aoa_azimuth_fom as i32),
-                    JValue::Int(measurement.aoa_elevation as i32),
-                    JValue::Int(measurement.aoa_elevation_fom as i32),
-                ],
-            )
-            .map_err(|e| {
-                error!("UCI JNI: OwrAoA measurement jobject creation failed: {:?}", e);
-                e
-            })?;
+    /// Returns whether the SESSION_INFO_NTFs of the session are dropped instead of being reported.
+    fn is_session_info_suppressed(&self, session_token: u32) -> bool {
+        match self.session_notification_filter.lock() {
+            Ok(filter) => filter.is_session_info_suppressed(session_token),
+            Err(e) => {
+                error!("UCI JNI: failed to lock session notification filter: {:?}", e);
+                false
+            }
+        }
+    }
 
-        // Create UwbRangingData
-        let ranging_data_jclass = NotificationManagerAndroid::find_local_class(
-            &mut self.jclass_map,
-            &self.class_loader_obj,
-            &self.env,
-            UWB_RANGING_DATA_CLASS,
-        )?;
-        let method_sig = "(JJIJIIIL".to_owned() + UWB_OWR_AOA_MEASUREMENT_CLASS + ";[B)V";
+    /// Returns the distance step the range data of the session are quantized to, if the session
+    /// is under the coarse location privacy. The range data of every session are coarsened while
+    /// the filter cannot be read, rather than risking to deliver precise ones.
+    fn coarse_location(&self, session_id: u32) -> Option<u16> {
+        match self.session_notification_filter.lock() {
+            Ok(filter) => filter.coarse_location(session_id),
+            Err(e) => {
+                error!("UCI JNI: failed to lock session notification filter: {:?}", e);
+                Some(DEFAULT_DISTANCE_STEP_CM)
+            }
+        }
+    }
 
-        // Safety: raw_notification_jobject is safely instantiated above.
-        let raw_notification_jobject = unsafe { JObject::from_raw(raw_notification_jbytearray) };
+    /// Omits the measurements of the controlees whose departure is simulated from range data.
+    fn omit_departed_controlees(&self, range_data: &mut SessionRangeData) {
+        match self.session_notification_filter.lock() {
+            Ok(filter) => filter.omit_departed_controlees(range_data),
+            Err(e) => error!("UCI JNI: failed to lock session notification filter: {:?}", e),
+        }
+    }
 
-        let range_data_jobject = self
-            .env
-            .new_object(
-                ranging_data_jclass,
-                &method_sig,
-                &[
-                    JValue::Long(range_data.sequence_number as i64),
-                    // session_token below has already been mapped to session_id by uci layer.
-                    JValue::Long(range_data.session_token as i64),
-                    JValue::Int(range_data.rcr_indicator as i32),
-                    JValue::Long(range_data.current_ranging_interval_ms as i64),
-                    JValue::Int(range_data.ranging_measurement_type as i32),
-                    JValue::Int(mac_indicator as i32),
-                    JValue::Int(1), // measurement_count
-                    JValue::Object(measurement_jobject),
-                    JValue::Object(raw_notification_jobject),
-                ],
-            )
-            .map_err(|e| {
-                error!("UCI JNI: Ranging Data object creation failed: {:?}", e);
-                e
-            })?;
-        let method_sig = "(L".to_owned() + UWB_RANGING_DATA_CLASS + ";)V";
-        self.cached_jni_call(
-            "onRangeDataNotificationReceived",
-            &method_sig,
-            &[jvalue::from(JValue::Object(range_data_jobject))],
-        )
+    /// Returns whether the range data of the session only need to be reported as proximity edges.
+    fn is_proximity_only_session(&self, session_token: u32) -> bool {
+        if !feature_flags::is_enabled(FeatureFlag::ProximityFastPath) {
+            return false;
+        }
+        match self.proximity_tracker.lock() {
+            Ok(tracker) => tracker.is_proximity_only(session_token),
+            Err(e) => {
+                error!("UCI JNI: failed to lock proximity tracker: {:?}", e);
+                false
+            }
+        }
     }
 
-    fn on_session_two_way_range_data_notification(
+    /// Reports the near/far edges of a proximity-only session, without building the measurement
+    /// objects of the regular range data path.
+    fn on_session_proximity_range_data_notification(
         &mut self,
         range_data: SessionRangeData,
     ) -> Result<JObject, JNIError> {
-        let raw_notification_jbytearray =
-            self.env.byte_array_from_slice(&range_data.raw_ranging_data)?;
-
-        let (bytearray_len, mac_indicator) = match &range_data.ranging_measurements {
-            RangingMeasurements::ExtendedAddressTwoWay(_) => {
-                (EXTENDED_MAC_ADDRESS_LEN, MacAddressIndicator::ExtendedAddress)
+        let measurements = match range_data.ranging_measurements {
+            RangingMeasurements::ShortAddressTwoWay(v) => {
+                v.into_iter().map(TwoWayRangingMeasurement::from).collect::<Vec<_>>()
             }
-            RangingMeasurements::ShortAddressTwoWay(_) => {
-                (SHORT_MAC_ADDRESS_LEN, MacAddressIndicator::ShortAddress)
+            RangingMeasurements::ExtendedAddressTwoWay(v) => {
+                v.into_iter().map(TwoWayRangingMeasurement::from).collect::<Vec<_>>()
             }
             _ => {
                 return Err(JNIError::InvalidCtorReturn);
             }
         };
-
-        let measurement_count: i32 = match &range_data.ranging_measurements {
-            RangingMeasurements::ShortAddressTwoWay(v) => v.len().try_into(),
-            RangingMeasurements::ExtendedAddressTwoWay(v) => v.len().try_into(),
-            _ => {
+        let events = match self.proximity_tracker.lock() {
+            Ok(mut tracker) => measurements
+                .into_iter()
+                .filter(|measurement| measurement.status == StatusCode::UciStatusOk)
+                .filter_map(|measurement| {
+                    tracker.on_distance(
+                        range_data.session_token,
+                        &measurement.mac_address.to_ne_bytes(),
+                        measurement.distance,
+                    )
+                })
+                .collect::<Vec<ProximityEvent>>(),
+            Err(e) => {
+                error!("UCI JNI: failed to lock proximity tracker: {:?}", e);
                 return Err(JNIError::InvalidCtorReturn);
             }
+        };
+        for event in events {
+            let mac_address_jbytearray = self.env.byte_array_from_slice(&event.mac_address)?;
+            // Safety: mac_address_jbytearray is safely instantiated above.
+            let mac_address_jobject = unsafe { JObject::from_raw(mac_address_jbytearray) };
+            self.cached_jni_call(
+                "onProximityEvent",
+                "(JZI[B)V",
+                &[
+                    // session_token below has already been mapped to session_id by uci layer.
+                    jvalue::from(JValue::Long(event.session_token as i64)),
+                    jvalue::from(JValue::Bool(event.is_near as jboolean)),
+                    jvalue::from(JValue::Int(event.distance_cm as i32)),
+                    jvalue::from(JValue::Object(mac_address_jobject)),
+                ],
+            )?;
         }
-        .map_err(|_| JNIError::InvalidCtorReturn)?;
+        Ok(JObject::null())
+    }
 
-        let measurements_jobjectarray = match range_data.ranging_measurement_type {
-            RangingMeasurementType::TwoWay => {
-                let measurements = match range_data.ranging_measurements {
-                    RangingMeasurements::ExtendedAddressTwoWay(v) => {
-                        v.into_iter().map(TwoWayRangingMeasurement::from).collect::<Vec<_>>()
-                    }
-                    RangingMeasurements::ShortAddressTwoWay(v) => {
-                        v.into_iter().map(TwoWayRangingMeasurement::from).collect::<Vec<_>>()
-                    }
-                    _ => return Err(JNIError::InvalidCtorReturn),
-                };
-                self.on_two_way_range_data_notification(
-                    bytearray_len,
-                    measurement_count,
-                    measurements,
-                )?
+    /// Reports a SESSION_INFO_NTF without any measurement, without building a UwbRangingData.
+    fn on_session_range_keep_alive(
+        &mut self,
+        range_data: SessionRangeData,
+    ) -> Result<JObject, JNIError> {
+        self.cached_jni_call(
+            "onRangeKeepAlive",
+            "(JJ)V",
+            &[
+                // session_token below has already been mapped to session_id by uci layer.
+                jvalue::from(JValue::Long(range_data.session_token as i64)),
+                jvalue::from(JValue::Long(range_data.sequence_number as i64)),
+            ],
+        )
+    }
+
+    /// Passes a UwbRangingData to Java, through the callback of the aggregated stream when its
+    /// session is a member of an aggregation group.
+    fn pass_range_data_object(
+        &mut self,
+        session_token: u32,
+        range_data_jobject: JObject,
+    ) -> Result<JObject, JNIError> {
+        match self.aggregation_group(session_token) {
+            Some(group_id) => {
+                let method_sig = "(IL".to_owned() + UWB_RANGING_DATA_CLASS + ";)V";
+                self.cached_jni_call(
+                    "onAggregatedRangeDataReceived",
+                    &method_sig,
+                    &[
+                        jvalue::from(JValue::Int(group_id as i32)),
+                        jvalue::from(JValue::Object(range_data_jobject)),
+                    ],
+                )
             }
-            _ => {
-                return Err(JNIError::InvalidCtorReturn);
+            None => {
+                let method_sig = "(L".to_owned() + UWB_RANGING_DATA_CLASS + ";)V";
+                self.cached_jni_call(
+                    "onRangeDataNotificationReceived",
+                    &method_sig,
+                    &[jvalue::from(JValue::Object(range_data_jobject))],
+                )
             }
-        };
+        }
+    }
 
-        // Create UwbRangingData
-        let ranging_data_jclass = NotificationManagerAndroid::find_local_class(
-            &mut self.jclass_map,
-            &self.class_loader_obj,
-            &self.env,
-            UWB_RANGING_DATA_CLASS,
-        )?;
-        let method_sig = "(JJIJIII[L".to_owned() + UWB_TWO_WAY_MEASUREMENT_CLASS + ";[B)V";
+    /// Gets the aggregation group the session is a member of, if any.
+    fn aggregation_group(&self, session_token: u32) -> Option<u32> {
+        match self.session_aggregator.lock() {
+            Ok(aggregator) => aggregator.group_of(session_token),
+            Err(e) => {
+                error!("UCI JNI: failed to lock session aggregator: {:?}", e);
+                None
+            }
+        }
+    }
 
-        // Safety: measurements_jobjectarray is safely instantiated above.
-        let measurements_jobject = unsafe { JObject::from_raw(measurements_jobjectarray) };
-        // Safety: raw_notification_jobject is safely instantiated above.
-        let raw_notification_jobject = unsafe { JObject::from_raw(raw_notification_jbytearray) };
-        let range_data_jobject = self
-            .env
-            .new_object(
-                ranging_data_jclass,
-                &method_sig,
-                &[
-                    JValue::Long(range_data.sequence_number as i64),
-                    // session_token below has already been mapped to session_id by uci layer.
-                    JValue::Long(range_data.session_token as i64),
-                    JValue::Int(range_data.rcr_indicator as i32),
-                    JValue::Long(range_data.current_ranging_interval_ms as i64),
-                    JValue::Int(range_data.ranging_measurement_type as i32),
-                    JValue::Int(mac_indicator as i32),
-                    JValue::Int(measurement_count),
-                    JValue::Object(measurements_jobject),
-                    JValue::Object(raw_notification_jobject),
-                ],
-            )
-            .map_err(|e| {
-                error!("UCI JNI: Ranging Data object creation failed: {:?}", e);
-                e
-            })?;
-        let method_sig = "(L".to_owned() + UWB_RANGING_DATA_CLASS + ";)V";
-        self.cached_jni_call(
-            "onRangeDataNotificationReceived",
-            &method_sig,
-            &[jvalue::from(JValue::Object(range_data_jobject))],
+    fn on_session_two_way_range_data_notification(
+        &mut self,
+        range_data: SessionRangeData,
+    ) -> Result<JObject, JNIError> {
+        if range_data.ranging_measurement_type != RangingMeasurementType::TwoWay {
+            return Err(JNIError::InvalidCtorReturn);
+        }
+        let raw_notification_jbytearray = self.raw_ranging_data_jbytearray(&range_data)?;
+        let header_args = range_data_header_args(&range_data);
+        let measurements = two_way_measurements(
+            range_data.ranging_measurements,
+            &range_data.raw_ranging_data,
+            feature_flags::is_enabled(FeatureFlag::AntennaPairTagging),
+        )
+        .ok_or(JNIError::InvalidCtorReturn)?;
+        self.pass_range_data_args(
+            range_data.session_token,
+            header_args,
+            UWB_TWO_WAY_MEASUREMENT_CLASS,
+            TWO_WAY_MEASUREMENT_SIGNATURE,
+            measurements.iter().map(two_way_measurement_args).collect(),
+            raw_notification_jbytearray,
         )
     }
 
@@ -1008,13 +1113,185 @@ impl NotificationManagerAndroid {
             ],
         )
     }
+
+    /// Reports the notifications dropped by the backpressure policy of the notification queue.
+    fn on_notification_overflow(&mut self, dropped_count: u64) -> UwbResult<()> {
+        debug!("UCI JNI: notification overflow callback.");
+        // Held during the report, so that the callback object is not replaced meanwhile.
+        let callback_slot = self.callback_slot.clone();
+        let callback = callback_slot.acquire()?;
+        if !self.sync_callback_obj(&callback) {
+            debug!("UCI JNI: no callback object, overflow reported with the replay.");
+            self.replay_buffer.add_dropped(dropped_count);
+            return Ok(());
+        }
+        self.report_notification_overflow(dropped_count)
+    }
+
+    /// Reports dropped notifications with the callback object acquired by the caller.
+    fn report_notification_overflow(&mut self, dropped_count: u64) -> UwbResult<()> {
+        match self.notification_stats.lock() {
+            Ok(mut stats) => stats.on_dropped(dropped_count),
+            Err(e) => error!("UCI JNI: failed to lock notification stats: {:?}", e),
+        }
+        let env = *self.env;
+        env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+            let env_chip_id_jobject = *env.new_string(&self.chip_id).map_err(|e| {
+                error!("UCI JNI: failed to create Java String: {e:?}");
+                e
+            })?;
+            self.cached_jni_call(
+                "onNotificationOverflow",
+                "(JLjava/lang/String;)V",
+                &[
+                    jvalue::from(JValue::Long(dropped_count.try_into().unwrap_or(i64::MAX))),
+                    jvalue::from(JValue::Object(env_chip_id_jobject)),
+                ],
+            )
+        })
+        .map_err(|_| UwbError::ForeignFunctionInterface)?;
+        Ok(())
+    }
+
+    /// Logs and reports to Java a notification of `stage` which could not be converted to Java
+    /// objects. The reports are rate-limited, the number of suppressed ones is passed along.
+    fn report_protocol_error(&mut self, stage: &'static str, notification: NotificationDump) {
+        match self.parse_error_stats.lock() {
+            Ok(mut stats) => stats.on_failure(stage),
+            Err(e) => error!("UCI JNI: failed to lock the parse error stats: {:?}", e),
+        }
+        match self.uci_log_trigger.lock() {
+            Ok(mut trigger) => trigger.on_error(),
+            Err(e) => error!("UCI JNI: failed to lock UCI log trigger: {:?}", e),
+        }
+        let suppressed_count = match self.protocol_error_reporter.on_failure(Instant::now()) {
+            Some(suppressed_count) => suppressed_count,
+            None => return,
+        };
+        error!(
+            "UCI JNI: failed to convert {} notification ({} suppressed since last report): {}",
+            stage, suppressed_count, notification
+        );
+        if !self.java_capabilities.check(JavaSupport::ProtocolError, "only logged") {
+            return;
+        }
+        // The failed conversion may have left a Java exception pending, which would fail the
+        // report as well.
+        if self.env.exception_check().unwrap_or(false) {
+            let _ = self.env.exception_describe();
+            let _ = self.env.exception_clear();
+        }
+        let env = *self.env;
+        let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+            let stage_jobject = *env.new_string(stage)?;
+            let notification_jobject = *env.new_string(notification.to_string())?;
+            let env_chip_id_jobject = *env.new_string(&self.chip_id)?;
+            self.cached_jni_call(
+                "onProtocolError",
+                "(Ljava/lang/String;Ljava/lang/String;JLjava/lang/String;)V",
+                &[
+                    jvalue::from(JValue::Object(stage_jobject)),
+                    jvalue::from(JValue::Object(notification_jobject)),
+                    jvalue::from(JValue::Long(suppressed_count.try_into().unwrap_or(i64::MAX))),
+                    jvalue::from(JValue::Object(env_chip_id_jobject)),
+                ],
+            )
+        });
+        if let Err(e) = result {
+            error!("UCI JNI: failed to report the protocol error: {:?}", e);
+        }
+    }
 }
 
-impl NotificationManager for NotificationManagerAndroid {
-    fn on_core_notification(&mut self, core_notification: CoreNotification) -> UwbResult<()> {
-        debug!("UCI JNI: core notification callback.");
+/// Gets the optional Java support needed to convert the measurements of a SESSION_INFO_NTF, if
+/// any.
+fn range_data_java_support(ranging_measurements: &RangingMeasurements) -> Option<JavaSupport> {
+    match ranging_measurements {
+        RangingMeasurements::ShortAddressOwrAoa(_)
+        | RangingMeasurements::ExtendedAddressOwrAoa(_) => Some(JavaSupport::OwrAoaMeasurement),
+        RangingMeasurements::ShortAddressDltdoa(_)
+        | RangingMeasurements::ExtendedAddressDltdoa(_) => Some(JavaSupport::DlTdoaMeasurement),
+        RangingMeasurements::ShortAddressTwoWay(_)
+        | RangingMeasurements::ExtendedAddressTwoWay(_) => None,
+    }
+}
+
+/// Returns whether a SESSION_INFO_NTF has no measurement, i.e. only keeps the session alive.
+fn has_no_measurements(ranging_measurements: &RangingMeasurements) -> bool {
+    count_successful_measurements(ranging_measurements).1 == 0
+}
+
+/// Counts the successful measurements of a SESSION_INFO_NTF, along with all its measurements.
+fn count_successful_measurements(ranging_measurements: &RangingMeasurements) -> (usize, usize) {
+    match ranging_measurements {
+        RangingMeasurements::ShortAddressTwoWay(v) => {
+            (v.iter().filter(|m| m.status == StatusCode::UciStatusOk).count(), v.len())
+        }
+        RangingMeasurements::ExtendedAddressTwoWay(v) => {
+            (v.iter().filter(|m| m.status == StatusCode::UciStatusOk).count(), v.len())
+        }
+        RangingMeasurements::ShortAddressOwrAoa(v) => {
+            (v.iter().filter(|m| m.status == StatusCode::UciStatusOk).count(), v.len())
+        }
+        RangingMeasurements::ExtendedAddressOwrAoa(v) => {
+            (v.iter().filter(|m| m.status == StatusCode::UciStatusOk).count(), v.len())
+        }
+        RangingMeasurements::ShortAddressDltdoa(v) => {
+            (v.iter().filter(|m| m.measurement.status == UCI_STATUS_OK).count(), v.len())
+        }
+        RangingMeasurements::ExtendedAddressDltdoa(v) => {
+            (v.iter().filter(|m| m.measurement.status == UCI_STATUS_OK).count(), v.len())
+        }
+    }
+}
+
+/// Gets the name of the conversion stage of a session notification, used in the protocol error
+/// reports.
+fn session_notification_stage(session_notification: &SessionNotification) -> &'static str {
+    match session_notification {
+        SessionNotification::Status { .. } => "SessionStatus",
+        SessionNotification::UpdateControllerMulticastListV1 { .. }
+        | SessionNotification::UpdateControllerMulticastListV2 { .. } => {
+            "SessionUpdateControllerMulticastList"
+        }
+        SessionNotification::SessionInfo(_) => "SessionInfo",
+        SessionNotification::DataTransferStatus { .. } => "DataTransferStatus",
+        SessionNotification::DataCredit { .. } => "DataCredit",
+        SessionNotification::DataTransferPhaseConfig { .. } => "DataTransferPhaseConfig",
+    }
+}
+
+/// Returns the token of the session a session notification is about.
+fn session_notification_token(session_notification: &SessionNotification) -> u32 {
+    match session_notification {
+        SessionNotification::Status { session_token, .. }
+        | SessionNotification::UpdateControllerMulticastListV1 { session_token, .. }
+        | SessionNotification::UpdateControllerMulticastListV2 { session_token, .. }
+        | SessionNotification::DataTransferStatus { session_token, .. }
+        | SessionNotification::DataCredit { session_token, .. }
+        | SessionNotification::DataTransferPhaseConfig { session_token, .. } => *session_token,
+        SessionNotification::SessionInfo(range_data) => range_data.session_token,
+    }
+}
+
+/// Marks the session notification handed over by the UCI manager, once parsed.
+fn trace_parsed_session_notification(session_notification: &SessionNotification) {
+    latency_trace::instant(|| {
+        format!(
+            "UWB parsed {} session {:#x}",
+            session_notification_stage(session_notification),
+            session_notification_token(session_notification)
+        )
+    });
+}
+
+impl NotificationManagerAndroid {
+    /// Dispatches a core notification to the attached callback object.
+    fn dispatch_core_notification(&mut self, core_notification: CoreNotification) -> UwbResult<()> {
+        self.report_retry_storms();
+        let notification_dump = NotificationDump::Parsed(format!("{:?}", core_notification));
         let env = *self.env;
-        env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+        let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
             let env_chip_id_jobject = *env.new_string(&self.chip_id).map_err(|e| {
                 error!("UCI JNI: failed to create Java String: {e:?}");
                 e
@@ -1038,19 +1315,168 @@ impl NotificationManager for NotificationManagerAndroid {
                     ],
                 ),
             }
-        })
-        .map_err(|_| UwbError::ForeignFunctionInterface)?;
+        });
+        if result.is_err() {
+            self.report_protocol_error("Core", notification_dump);
+        }
+        result.map_err(|_| UwbError::ForeignFunctionInterface)?;
 
         Ok(())
     }
 
-    fn on_session_notification(
+    /// Dispatches a session notification, or keeps it for the replay while the callback object
+    /// is detached.
+    fn handle_session_notification(
         &mut self,
         session_notification: SessionNotification,
     ) -> UwbResult<()> {
         debug!("UCI JNI: session notification callback.");
+        // Held during the dispatch, so that the callback object is not replaced meanwhile.
+        let callback_slot = self.callback_slot.clone();
+        let callback = callback_slot.acquire()?;
+        if !self.sync_callback_obj(&callback) {
+            let status = match &session_notification {
+                SessionNotification::Status {
+                    session_token, session_state, reason_code, ..
+                } => Some(SessionStatusKey {
+                    session_token: *session_token,
+                    session_state: *session_state as u8,
+                    reason_code: *reason_code,
+                }),
+                _ => None,
+            };
+            self.replay_buffer.push_session(session_notification, status);
+            return Ok(());
+        }
+        self.replay_notifications();
+        self.dispatch_session_notification(session_notification)
+    }
+
+    /// Dispatches a session notification to the attached callback object. The range data are
+    /// delivered as the delivery preference of their session requires.
+    fn dispatch_session_notification(
+        &mut self,
+        session_notification: SessionNotification,
+    ) -> UwbResult<()> {
+        let _trace = TraceSection::begin(|| {
+            format!(
+                "UWB dispatch {} session {:#x}",
+                session_notification_stage(&session_notification),
+                session_notification_token(&session_notification)
+            )
+        });
+        self.report_retry_storms();
+        self.notification_bus.publish(NotificationEvent::Session(&session_notification));
+        self.validate_session_transition(&session_notification);
+        self.attest_ranging(&session_notification);
+        if feature_flags::is_enabled(FeatureFlag::RangingAdvisor) {
+            self.advise_ranging(&session_notification);
+        }
+        let range_data_to_deliver = match session_notification {
+            SessionNotification::SessionInfo(range_data) => {
+                return self.schedule_range_data(range_data);
+            }
+            // The range data held for a session, or for its aggregation group, are delivered
+            // before it stops ranging.
+            SessionNotification::Status { session_id, session_state, .. }
+                if session_state != SessionState::SessionStateActive =>
+            {
+                let deinitialized = session_state == SessionState::SessionStateDeinit;
+                let mut range_data_to_deliver = match self.session_delivery.lock() {
+                    Ok(mut scheduler) => scheduler.on_session_stopped(session_id, deinitialized),
+                    Err(e) => {
+                        error!("UCI JNI: failed to lock session delivery scheduler: {:?}", e);
+                        vec![]
+                    }
+                };
+                match self.session_aggregator.lock() {
+                    Ok(mut aggregator) => range_data_to_deliver
+                        .extend(aggregator.on_session_stopped(session_id, deinitialized)),
+                    Err(e) => error!("UCI JNI: failed to lock session aggregator: {:?}", e),
+                }
+                range_data_to_deliver
+            }
+            _ => vec![],
+        };
+        for range_data in range_data_to_deliver {
+            self.deliver_session_notification(SessionNotification::SessionInfo(range_data))?;
+        }
+        self.deliver_session_notification(session_notification)
+    }
+
+    /// Delivers the range data which the delivery preference of their session lets through. The
+    /// range data of the sessions of an aggregation group are held by the group instead.
+    fn schedule_range_data(&mut self, range_data: SessionRangeData) -> UwbResult<()> {
+        let now = Instant::now();
+        let range_data_to_deliver = if self.aggregation_group(range_data.session_token).is_some() {
+            match self.session_aggregator.lock() {
+                Ok(mut aggregator) => aggregator.on_range_data(range_data, now),
+                Err(e) => {
+                    error!("UCI JNI: failed to lock session aggregator: {:?}", e);
+                    vec![range_data]
+                }
+            }
+        } else {
+            match self.session_delivery.lock() {
+                Ok(mut scheduler) => scheduler.on_range_data(range_data, now),
+                Err(e) => {
+                    error!("UCI JNI: failed to lock session delivery scheduler: {:?}", e);
+                    vec![range_data]
+                }
+            }
+        };
+        for range_data in range_data_to_deliver {
+            self.deliver_session_notification(SessionNotification::SessionInfo(range_data))?;
+        }
+        Ok(())
+    }
+
+    /// Converts a session notification and passes it to the attached callback object.
+    fn deliver_session_notification(
+        &mut self,
+        mut session_notification: SessionNotification,
+    ) -> UwbResult<()> {
+        if let SessionNotification::SessionInfo(range_data) = &mut session_notification {
+            if self.is_session_info_suppressed(range_data.session_token) {
+                debug!("UCI JNI: session info of {} is suppressed.", range_data.session_token);
+                return Ok(());
+            }
+            // Before anything of the range data is converted, or kept for a failure report.
+            self.omit_departed_controlees(range_data);
+            // session_token below has already been mapped to session_id by uci layer.
+            if let Some(distance_step_cm) = self.coarse_location(range_data.session_token) {
+                coarsen_range_data(range_data, distance_step_cm);
+            }
+            if !self.is_proximity_only_session(range_data.session_token) {
+                if let Some(support) = range_data_java_support(&range_data.ranging_measurements) {
+                    if !self.java_capabilities.check(support, "skipped") {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        // Checked after the controlees are omitted, which may leave no measurement.
+        let is_keep_alive = match &session_notification {
+            SessionNotification::SessionInfo(range_data) => {
+                has_no_measurements(&range_data.ranging_measurements)
+                    && self
+                        .java_capabilities
+                        .check(JavaSupport::RangeKeepAlive, "sent as empty range data")
+            }
+            _ => false,
+        };
+        let start = Instant::now();
+        let is_ranging = matches!(session_notification, SessionNotification::SessionInfo(_));
+        // The notification is consumed by the conversion, keep what a failure report needs.
+        let stage = session_notification_stage(&session_notification);
+        let notification_dump = match &session_notification {
+            SessionNotification::SessionInfo(range_data) => {
+                NotificationDump::Raw(range_data.raw_ranging_data.clone())
+            }
+            notification => NotificationDump::Parsed(format!("{:?}", notification)),
+        };
         let env = *self.env;
-        env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+        let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
             match session_notification {
                 SessionNotification::Status {
                     session_id,
@@ -1080,7 +1506,15 @@ impl NotificationManager for NotificationManagerAndroid {
                     0_usize,
                     ControleeStatusList::V2(status_list),
                 ),
+                SessionNotification::SessionInfo(range_data) if is_keep_alive => {
+                    self.on_session_range_keep_alive(range_data)
+                }
                 // TODO(b/246678053): Match here on range_data.ranging_measurement_type instead.
+                SessionNotification::SessionInfo(range_data)
+                    if self.is_proximity_only_session(range_data.session_token) =>
+                {
+                    self.on_session_proximity_range_data_notification(range_data)
+                }
                 SessionNotification::SessionInfo(range_data) => {
                     match range_data.ranging_measurements {
                         uwb_core::uci::RangingMeasurements::ShortAddressTwoWay(_) => {
@@ -1128,18 +1562,158 @@ impl NotificationManager for NotificationManagerAndroid {
                     self.on_data_transfer_phase_config_notification(session_token, u8::from(status))
                 }
             }
-        })
-        .map_err(|_| UwbError::ForeignFunctionInterface)?;
+        });
+        if is_ranging {
+            self.record_dispatch(CallbackType::Ranging, start, result.is_ok());
+        }
+        if result.is_err() {
+            self.report_protocol_error(stage, notification_dump);
+        }
+        result.map_err(|_| UwbError::ForeignFunctionInterface)?;
+        self.report_lost_advertisers().map_err(|_| UwbError::ForeignFunctionInterface)?;
+        Ok(())
+    }
+
+    /// Dispatches a data packet received from the UWBS at `received_at`. The age of the packet is
+    /// passed to Java, which timestamps the packet on its own clock.
+    fn dispatch_data_rcv_notification(
+        &mut self,
+        data_rcv_notification: DataRcvNotification,
+        received_at: Instant,
+    ) -> UwbResult<()> {
+        debug!("UCI JNI: Data Rcv notification callback.");
+        self.notification_bus.publish(NotificationEvent::DataRcv {
+            session_token: data_rcv_notification.session_token,
+            received_at,
+        });
+        // Held during the dispatch, so that the callback object is not replaced meanwhile.
+        let callback_slot = self.callback_slot.clone();
+        let callback = callback_slot.acquire()?;
+        if !self.sync_callback_obj(&callback) {
+            debug!("UCI JNI: no callback object, notification dropped.");
+            return Ok(());
+        }
+        let start = Instant::now();
+        let env = *self.env;
+        let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+            let source_address_jbytearray = match &data_rcv_notification.source_address {
+                UwbAddress::Short(a) => self.env.byte_array_from_slice(a)?,
+                UwbAddress::Extended(a) => self.env.byte_array_from_slice(a)?,
+            };
+            // Safety: source_address_jbytearray safely instantiated above.
+            let source_address_jobject = unsafe { JObject::from_raw(source_address_jbytearray) };
+            let payload_jbytearray =
+                self.env.byte_array_from_slice(&data_rcv_notification.payload)?;
+            // Safety: payload_jbytearray safely instantiated above.
+            let payload_jobject = unsafe { JObject::from_raw(payload_jbytearray) };
+            self.cached_jni_call(
+                "onDataReceived",
+                "(JIJ[B[BJ)V",
+                &[
+                    // session_token below has already been mapped to session_id by uci layer.
+                    jvalue::from(JValue::Long(data_rcv_notification.session_token as i64)),
+                    jvalue::from(JValue::Int(i32::from(data_rcv_notification.status))),
+                    jvalue::from(JValue::Long(data_rcv_notification.uci_sequence_num as i64)),
+                    jvalue::from(JValue::Object(source_address_jobject)),
+                    jvalue::from(JValue::Object(payload_jobject)),
+                    jvalue::from(JValue::Long(
+                        start.saturating_duration_since(received_at).as_nanos() as i64,
+                    )),
+                ],
+            )
+        });
+        self.record_dispatch(CallbackType::DataRcv, start, result.is_ok());
+        if result.is_err() {
+            self.report_protocol_error(
+                "DataRcv",
+                NotificationDump::Raw(data_rcv_notification.payload),
+            );
+        }
+        result.map_err(|_| UwbError::ForeignFunctionInterface)?;
         Ok(())
     }
+}
+
+impl NotificationManager for NotificationManagerAndroid {
+    fn on_core_notification(&mut self, core_notification: CoreNotification) -> UwbResult<()> {
+        debug!("UCI JNI: core notification callback.");
+        // Held during the dispatch, so that the callback object is not replaced meanwhile.
+        let callback_slot = self.callback_slot.clone();
+        let callback = callback_slot.acquire()?;
+        if !self.sync_callback_obj(&callback) {
+            self.replay_buffer.push_core(core_notification);
+            return Ok(());
+        }
+        self.replay_notifications();
+        self.dispatch_core_notification(core_notification)
+    }
+
+    fn on_session_notification(
+        &mut self,
+        session_notification: SessionNotification,
+    ) -> UwbResult<()> {
+        trace_parsed_session_notification(&session_notification);
+        self.handle_session_notification(session_notification)
+    }
 
     fn on_vendor_notification(
         &mut self,
         vendor_notification: uwb_core::params::RawUciMessage,
     ) -> UwbResult<()> {
         debug!("UCI JNI: vendor notification callback.");
+        if let Some(session_token) = diagnostics_session_token(
+            vendor_notification.gid,
+            vendor_notification.oid,
+            &vendor_notification.payload,
+        ) {
+            match self.session_notification_filter.lock() {
+                Ok(filter) if !filter.is_diagnostics_enabled_for_token(session_token) => {
+                    debug!(
+                        "UCI JNI: diagnostics of session {} not enabled, dropped",
+                        session_token
+                    );
+                    return Ok(());
+                }
+                Ok(_) => {}
+                Err(e) => error!("UCI JNI: failed to lock session notification filter: {:?}", e),
+            }
+        }
+        // Held during the dispatch, so that the callback object is not replaced meanwhile.
+        let callback_slot = self.callback_slot.clone();
+        let callback = callback_slot.acquire()?;
+        if !self.sync_callback_obj(&callback) {
+            debug!("UCI JNI: no callback object, notification dropped.");
+            return Ok(());
+        }
+        let start = Instant::now();
+        let firmware_log_stream = match self.firmware_log.lock() {
+            Ok(mut firmware_log) => firmware_log.on_vendor_notification(
+                vendor_notification.gid,
+                vendor_notification.oid,
+                &vendor_notification.payload,
+            ),
+            Err(e) => {
+                error!("UCI JNI: failed to lock firmware log: {:?}", e);
+                None
+            }
+        };
+        match firmware_log_stream {
+            Some(true) => return self.stream_firmware_log(start, vendor_notification.payload),
+            Some(false) => return Ok(()),
+            None => {}
+        }
+        let java_callback = match self.vendor_notification_router.lock() {
+            Ok(router) => router.java_callback(vendor_notification.gid).map(str::to_owned),
+            Err(e) => {
+                error!("UCI JNI: failed to lock vendor notification router: {:?}", e);
+                None
+            }
+        };
+        if let Some(java_callback) = java_callback {
+            return self.route_vendor_notification(start, &java_callback, vendor_notification);
+        }
         let env = *self.env;
-        env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+        let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
             let payload_jbytearray =
                 self.env.byte_array_from_slice(&vendor_notification.payload)?;
 
@@ -1165,8 +1739,15 @@ impl NotificationManager for NotificationManagerAndroid {
                     jvalue::from(JValue::Object(payload_jobject)),
                 ],
             )
-        })
-        .map_err(|_| UwbError::ForeignFunctionInterface)?;
+        });
+        self.record_dispatch(CallbackType::Vendor, start, result.is_ok());
+        if result.is_err() {
+            self.report_protocol_error(
+                "Vendor",
+                NotificationDump::Raw(vendor_notification.payload),
+            );
+        }
+        result.map_err(|_| UwbError::ForeignFunctionInterface)?;
         Ok(())
     }
 
@@ -1174,34 +1755,7 @@ impl NotificationManager for NotificationManagerAndroid {
         &mut self,
         data_rcv_notification: DataRcvNotification,
     ) -> UwbResult<()> {
-        debug!("UCI JNI: Data Rcv notification callback.");
-        let env = *self.env;
-        env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
-            let source_address_jbytearray = match &data_rcv_notification.source_address {
-                UwbAddress::Short(a) => self.env.byte_array_from_slice(a)?,
-                UwbAddress::Extended(a) => self.env.byte_array_from_slice(a)?,
-            };
-            let payload_jbytearray =
-                self.env.byte_array_from_slice(&data_rcv_notification.payload)?;
-            // Safety: source_address_jbytearray safely instantiated above.
-            let source_address_jobject = unsafe { JObject::from_raw(source_address_jbytearray) };
-            // Safety: payload_jbytearray safely instantiated above.
-            let payload_jobject = unsafe { JObject::from_raw(payload_jbytearray) };
-            self.cached_jni_call(
-                "onDataReceived",
-                "(JIJ[B[B)V",
-                &[
-                    // session_token below has already been mapped to session_id by uci layer.
-                    jvalue::from(JValue::Long(data_rcv_notification.session_token as i64)),
-                    jvalue::from(JValue::Int(i32::from(data_rcv_notification.status))),
-                    jvalue::from(JValue::Long(data_rcv_notification.uci_sequence_num as i64)),
-                    jvalue::from(JValue::Object(source_address_jobject)),
-                    jvalue::from(JValue::Object(payload_jobject)),
-                ],
-            )
-        })
-        .map_err(|_| UwbError::ForeignFunctionInterface)?;
-        Ok(())
+        self.dispatch_data_rcv_notification(data_rcv_notification, Instant::now())
     }
 
     fn on_radar_data_rcv_notification(
@@ -1209,8 +1763,39 @@ impl NotificationManager for NotificationManagerAndroid {
         radar_data_rcv_notification: RadarDataRcvNotification,
     ) -> UwbResult<()> {
         debug!("UCI JNI: Radar Data Rcv notification callback.");
+        // Held during the dispatch, so that the callback object is not replaced meanwhile.
+        let callback_slot = self.callback_slot.clone();
+        let callback = callback_slot.acquire()?;
+        if !self.sync_callback_obj(&callback) {
+            debug!("UCI JNI: no callback object, notification dropped.");
+            return Ok(());
+        }
+        if !self.java_capabilities.check(JavaSupport::RadarData, "skipped") {
+            return Ok(());
+        }
+        let (radar_data_rcv_notification, dropped_sweep_count) = match self.radar_decimator.lock() {
+            Ok(mut decimator) => {
+                match decimator.process(radar_data_rcv_notification, Instant::now()) {
+                    Some(processed) => processed,
+                    None => return Ok(()),
+                }
+            }
+            Err(e) => {
+                error!("UCI JNI: failed to lock radar decimator: {:?}", e);
+                (radar_data_rcv_notification, 0)
+            }
+        };
+        let start = Instant::now();
+        // The sweep data is too large to be reported, only its header is kept.
+        let notification_dump = NotificationDump::Parsed(format!(
+            "session_token: {}, status: {:?}, radar_data_type: {:?}, sweeps: {}",
+            radar_data_rcv_notification.session_token,
+            radar_data_rcv_notification.status,
+            radar_data_rcv_notification.radar_data_type,
+            radar_data_rcv_notification.sweep_data.len()
+        ));
         let env = *self.env;
-        env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
+        let result = env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
             let radar_sweep_data_jclass = NotificationManagerAndroid::find_local_class(
                 &mut self.jclass_map,
                 &self.class_loader_obj,
@@ -1319,7 +1904,7 @@ impl NotificationManager for NotificationManagerAndroid {
                 .env
                 .new_object(
                     radar_data_jclass,
-                    "(JIIIII[L".to_owned() + UWB_RADAR_SWEEP_DATA_CLASS + ";)V",
+                    "(JIIIII[L".to_owned() + UWB_RADAR_SWEEP_DATA_CLASS + ";I)V",
                     &[
                         // session_token below has already been mapped to session_id by uci layer.
                         JValue::Long(radar_data_rcv_notification.session_token as i64),
@@ -1329,6 +1914,7 @@ impl NotificationManager for NotificationManagerAndroid {
                         JValue::Int(radar_data_rcv_notification.bits_per_sample as i32),
                         JValue::Int(radar_data_rcv_notification.sweep_offset as i32),
                         JValue::Object(radar_sweep_data_array_jobject),
+                        JValue::Int(dropped_sweep_count.try_into().unwrap_or(i32::MAX)),
                     ],
                 )
                 .map_err(|e| {
@@ -1343,352 +1929,230 @@ impl NotificationManager for NotificationManagerAndroid {
                 &method_sig,
                 &[jvalue::from(JValue::Object(radar_data_jobject))],
             )
-        })
-        .map_err(|_| UwbError::ForeignFunctionInterface)?;
+        });
+        self.record_dispatch(CallbackType::Radar, start, result.is_ok());
+        if result.is_err() {
+            self.report_protocol_error("RadarDataRcv", notification_dump);
+        }
+        result.map_err(|_| UwbError::ForeignFunctionInterface)?;
         Ok(())
     }
 }
 pub(crate) struct NotificationManagerAndroidBuilder {
     pub chip_id: String,
     pub vm: &'static Arc<JavaVM>,
-    pub class_loader_obj: GlobalRef,
-    pub callback_obj: GlobalRef,
+    pub class_loader_obj: TrackedGlobalRef,
+    pub callback_slot: Arc<CallbackSlot<Option<TrackedGlobalRef>>>,
+    pub notification_stats: Arc<Mutex<NotificationStats>>,
+    pub advertiser_tracker: Arc<Mutex<AdvertiserTracker>>,
+    pub proximity_tracker: Arc<Mutex<ProximityTracker>>,
+    pub session_notification_filter: Arc<Mutex<SessionNotificationFilter>>,
+    pub notification_bus: NotificationBus,
+    pub uci_metrics: Arc<Mutex<UciMetrics>>,
+    pub parse_error_stats: Arc<Mutex<ParseErrorStats>>,
+    pub firmware_log: Arc<Mutex<FirmwareLog>>,
+    pub vendor_notification_router: Arc<Mutex<VendorNotificationRouter>>,
+    pub radar_decimator: Arc<Mutex<RadarDecimator>>,
+    pub session_delivery: Arc<Mutex<SessionDeliveryScheduler>>,
+    pub session_aggregator: Arc<Mutex<SessionAggregator>>,
+    pub uci_log_trigger: Arc<Mutex<UciLogTrigger>>,
+    pub ranging_attestation: Arc<Mutex<RangingAttestation>>,
 }
 
 impl NotificationManagerBuilder for NotificationManagerAndroidBuilder {
     type NotificationManager = NotificationManagerAndroid;
 
     fn build(self) -> Option<Self::NotificationManager> {
+        let callback = match self.callback_slot.acquire() {
+            Ok(callback) => CallbackView::new(&callback, TrackedRef::downgrade),
+            Err(e) => {
+                error!("UCI JNI: failed to acquire the callback object: {:?}", e);
+                return None;
+            }
+        };
+        let attestation_signer = AttestationSigner::new(
+            format!("UwbAttestation-{}", self.chip_id),
+            self.ranging_attestation.clone(),
+        )?;
         if let Ok(env) = self.vm.attach_current_thread() {
-            Some(NotificationManagerAndroid {
+            let mut notification_manager = NotificationManagerAndroid {
                 chip_id: self.chip_id,
                 env,
                 class_loader_obj: self.class_loader_obj,
-                callback_obj: self.callback_obj,
+                callback,
+                callback_slot: self.callback_slot,
+                replay_buffer: ReplayBuffer::default(),
                 jmethod_id_map: HashMap::new(),
                 jclass_map: HashMap::new(),
-            })
+                notification_stats: self.notification_stats,
+                advertiser_tracker: self.advertiser_tracker,
+                proximity_tracker: self.proximity_tracker,
+                session_notification_filter: self.session_notification_filter,
+                notification_bus: self.notification_bus,
+                uci_metrics: self.uci_metrics,
+                parse_error_stats: self.parse_error_stats,
+                firmware_log: self.firmware_log,
+                vendor_notification_router: self.vendor_notification_router,
+                radar_decimator: self.radar_decimator,
+                session_delivery: self.session_delivery,
+                session_aggregator: self.session_aggregator,
+                uci_log_trigger: self.uci_log_trigger,
+                ranging_attestation: self.ranging_attestation,
+                attestation_signer,
+                protocol_error_reporter: ProtocolErrorReporter::default(),
+                ranging_advisor: RangingAdvisor::default(),
+                session_transition_validator: SessionTransitionValidator::default(),
+                java_capabilities: JavaCapabilities::default(),
+            };
+            notification_manager.probe_java_capabilities();
+            Some(notification_manager)
         } else {
             None
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_two_way_ranigng_measurement_from_short_address_two_way_ranging_measurement() {
-        let short_address_measurement = ShortAddressTwoWayRangingMeasurement {
-            mac_address: 0x1234,
-            status: StatusCode::UciStatusOk,
-            nlos: 0,
-            distance: 1,
-            aoa_azimuth: 2,
-            aoa_azimuth_fom: 3,
-            aoa_elevation: 4,
-            aoa_elevation_fom: 5,
-            aoa_destination_azimuth: 6,
-            aoa_destination_azimuth_fom: 7,
-            aoa_destination_elevation: 8,
-            aoa_destination_elevation_fom: 9,
-            slot_index: 10,
-            rssi: 11,
-        };
-        let measurement = TwoWayRangingMeasurement::from(short_address_measurement.clone());
-        assert_eq!(
-            measurement.mac_address,
-            MacAddress::Short(short_address_measurement.mac_address)
-        );
-        assert_eq!(measurement.status, short_address_measurement.status);
-        assert_eq!(measurement.nlos, short_address_measurement.nlos);
-        assert_eq!(measurement.distance, short_address_measurement.distance);
-        assert_eq!(measurement.aoa_azimuth, short_address_measurement.aoa_azimuth);
-        assert_eq!(measurement.aoa_azimuth_fom, short_address_measurement.aoa_azimuth_fom);
-        assert_eq!(measurement.aoa_elevation, short_address_measurement.aoa_elevation);
-        assert_eq!(measurement.aoa_elevation_fom, short_address_measurement.aoa_elevation_fom);
-        assert_eq!(
-            measurement.aoa_destination_azimuth,
-            short_address_measurement.aoa_destination_azimuth
-        );
-        assert_eq!(
-            measurement.aoa_destination_azimuth_fom,
-            short_address_measurement.aoa_destination_azimuth_fom
-        );
-        assert_eq!(
-            measurement.aoa_destination_elevation,
-            short_address_measurement.aoa_destination_elevation
-        );
-        assert_eq!(
-            measurement.aoa_destination_elevation_fom,
-            short_address_measurement.aoa_destination_elevation_fom
-        );
-        assert_eq!(measurement.slot_index, short_address_measurement.slot_index);
-        assert_eq!(measurement.rssi, short_address_measurement.rssi);
-    }
-
-    #[test]
-    fn test_get_two_way_ranigng_measurement_from_extended_address_two_way_ranging_measurement() {
-        let extended_address_measurement = ExtendedAddressTwoWayRangingMeasurement {
-            mac_address: 0x1234_5678,
-            status: StatusCode::UciStatusOk,
-            nlos: 0,
-            distance: 1,
-            aoa_azimuth: 2,
-            aoa_azimuth_fom: 3,
-            aoa_elevation: 4,
-            aoa_elevation_fom: 5,
-            aoa_destination_azimuth: 6,
-            aoa_destination_azimuth_fom: 7,
-            aoa_destination_elevation: 8,
-            aoa_destination_elevation_fom: 9,
-            slot_index: 10,
-            rssi: 11,
-        };
-        let measurement = TwoWayRangingMeasurement::from(extended_address_measurement.clone());
-        assert_eq!(
-            measurement.mac_address,
-            MacAddress::Extended(extended_address_measurement.mac_address)
-        );
-        assert_eq!(measurement.status, extended_address_measurement.status);
-        assert_eq!(measurement.nlos, extended_address_measurement.nlos);
-        assert_eq!(measurement.distance, extended_address_measurement.distance);
-        assert_eq!(measurement.aoa_azimuth, extended_address_measurement.aoa_azimuth);
-        assert_eq!(measurement.aoa_azimuth_fom, extended_address_measurement.aoa_azimuth_fom);
-        assert_eq!(measurement.aoa_elevation, extended_address_measurement.aoa_elevation);
-        assert_eq!(measurement.aoa_elevation_fom, extended_address_measurement.aoa_elevation_fom);
-        assert_eq!(
-            measurement.aoa_destination_azimuth,
-            extended_address_measurement.aoa_destination_azimuth
-        );
-        assert_eq!(
-            measurement.aoa_destination_azimuth_fom,
-            extended_address_measurement.aoa_destination_azimuth_fom
-        );
-        assert_eq!(
-            measurement.aoa_destination_elevation,
-            extended_address_measurement.aoa_destination_elevation
-        );
-        assert_eq!(
-            measurement.aoa_destination_elevation_fom,
-            extended_address_measurement.aoa_destination_elevation_fom
-        );
-        assert_eq!(measurement.slot_index, extended_address_measurement.slot_index);
-        assert_eq!(measurement.rssi, extended_address_measurement.rssi);
-    }
-
-    #[test]
-    fn test_get_owr_aoa_ranging_measurement_from_short_address_measurement() {
-        let short_address_measurement = ShortAddressOwrAoaRangingMeasurement {
-            mac_address: 0x1234,
-            status: StatusCode::UciStatusOk,
-            nlos: 0,
-            frame_sequence_number: 1,
-            block_index: 2,
-            aoa_azimuth: 3,
-            aoa_azimuth_fom: 4,
-            aoa_elevation: 5,
-            aoa_elevation_fom: 6,
-        };
-        let measurement = OwrAoaRangingMeasurement::from(short_address_measurement.clone());
-        assert_eq!(
-            measurement.mac_address,
-            MacAddress::Short(short_address_measurement.mac_address)
-        );
-        assert_eq!(measurement.status, short_address_measurement.status);
-        assert_eq!(measurement.nlos, short_address_measurement.nlos);
-        assert_eq!(
-            measurement.frame_sequence_number,
-            short_address_measurement.frame_sequence_number
-        );
-        assert_eq!(measurement.block_index, short_address_measurement.block_index);
-        assert_eq!(measurement.aoa_azimuth, short_address_measurement.aoa_azimuth);
-        assert_eq!(measurement.aoa_azimuth_fom, short_address_measurement.aoa_azimuth_fom);
-        assert_eq!(measurement.aoa_elevation, short_address_measurement.aoa_elevation);
-        assert_eq!(measurement.aoa_elevation_fom, short_address_measurement.aoa_elevation_fom);
-    }
-
-    #[test]
-    fn test_get_owr_aoa_ranging_measurement_from_extended_address_measurement() {
-        let extended_address_measurement = ExtendedAddressOwrAoaRangingMeasurement {
-            mac_address: 0x1234_5678,
-            status: StatusCode::UciStatusOk,
-            nlos: 0,
-            frame_sequence_number: 1,
-            block_index: 2,
-            aoa_azimuth: 3,
-            aoa_azimuth_fom: 4,
-            aoa_elevation: 5,
-            aoa_elevation_fom: 6,
-        };
-        let measurement = OwrAoaRangingMeasurement::from(extended_address_measurement.clone());
-        assert_eq!(
-            measurement.mac_address,
-            MacAddress::Extended(extended_address_measurement.mac_address)
-        );
-        assert_eq!(measurement.status, extended_address_measurement.status);
-        assert_eq!(measurement.nlos, extended_address_measurement.nlos);
-        assert_eq!(
-            measurement.frame_sequence_number,
-            extended_address_measurement.frame_sequence_number
-        );
-        assert_eq!(measurement.block_index, extended_address_measurement.block_index);
-        assert_eq!(measurement.aoa_azimuth, extended_address_measurement.aoa_azimuth);
-        assert_eq!(measurement.aoa_azimuth_fom, extended_address_measurement.aoa_azimuth_fom);
-        assert_eq!(measurement.aoa_elevation, extended_address_measurement.aoa_elevation);
-        assert_eq!(measurement.aoa_elevation_fom, extended_address_measurement.aoa_elevation_fom);
-    }
-
-    #[test]
-    fn test_get_dl_tdoa_ranging_measurement_from_short_address_measurement() {
-        let bytes = [
-            // All Fields in Little Endian (LE)
-            0x0a, 0x01, 0x33, 0x05, // 2(Mac address), Status, Message Type
-            0x53, 0x05, 0x02, 0x05, // 2(Message control), 2(Block Index)
-            0x07, 0x09, 0x0a, 0x01, // Round Index, NLoS, 2(AoA Azimuth)
-            0x02, 0x05, 0x07, 0x09, // AoA Azimuth FOM, 2(AoA Elevation), AoA Elevation FOM
-            0x0a, 0x01, 0x02, 0x05, // RSSI, 3(Tx Timestamp..)
-            0x07, 0x09, 0x0a, 0x01, // 4(Tx Timestamp..)
-            0x02, 0x05, 0x07, 0x09, // Tx Timestamp, 3(Rx Timestamp..)
-            0x05, 0x07, 0x09, 0x0a, // 2(Rx Timestamp), 2(Anchor Cfo)
-            0x01, 0x02, 0x05, 0x07, // 2(Cfo), 2(Initiator Reply Time..)
-            0x09, 0x05, 0x07, 0x09, // 2(Initiator Reply Time), 2(Responder Reply Time..)
-            0x0a, 0x01, 0x02, 0x05, // 2(Responder Reply Time), 2(Initiator-Responder ToF)
-            0x07, 0x09, 0x07, 0x09, // 4(Anchor Location..)
-            0x05, 0x07, 0x09, 0x0a, // 4(Anchor Location..)
-            0x01, 0x02, 0x05, 0x07, // 2(Anchor Location..), 2(Active Ranging Rounds..)
-            0x09, 0x0a, 0x01, 0x02, // 4(Active Ranging Rounds..)
-            0x05, 0x07, 0x09, 0x05, // 4(Active Ranging Rounds)
-        ];
-        let short_address_measurement_vec =
-            ShortAddressDlTdoaRangingMeasurement::parse(&bytes, 1).unwrap();
-        let short_address_measurement = &short_address_measurement_vec[0];
-        let measurement = DlTdoaRangingMeasurement::from(short_address_measurement.clone());
-        assert_eq!(
-            measurement.mac_address,
-            MacAddress::Short(short_address_measurement.mac_address)
-        );
-        assert_eq!(measurement.status, short_address_measurement.measurement.status);
-        assert_eq!(measurement.message_type, short_address_measurement.measurement.message_type);
-        assert_eq!(
-            measurement.message_control,
-            short_address_measurement.measurement.message_control
-        );
-        assert_eq!(measurement.block_index, short_address_measurement.measurement.block_index);
-        assert_eq!(measurement.round_index, short_address_measurement.measurement.round_index);
-        assert_eq!(measurement.nlos, short_address_measurement.measurement.nlos);
-        assert_eq!(measurement.aoa_azimuth, short_address_measurement.measurement.aoa_azimuth);
-        assert_eq!(
-            measurement.aoa_azimuth_fom,
-            short_address_measurement.measurement.aoa_azimuth_fom
-        );
-        assert_eq!(measurement.aoa_elevation, short_address_measurement.measurement.aoa_elevation);
-        assert_eq!(
-            measurement.aoa_elevation_fom,
-            short_address_measurement.measurement.aoa_elevation_fom
-        );
-        assert_eq!(measurement.rssi, short_address_measurement.measurement.rssi);
-        assert_eq!(measurement.tx_timestamp, short_address_measurement.measurement.tx_timestamp);
-        assert_eq!(measurement.rx_timestamp, short_address_measurement.measurement.rx_timestamp);
-        assert_eq!(measurement.anchor_cfo, short_address_measurement.measurement.anchor_cfo);
-        assert_eq!(measurement.cfo, short_address_measurement.measurement.cfo);
-        assert_eq!(
-            measurement.initiator_reply_time,
-            short_address_measurement.measurement.initiator_reply_time
-        );
-        assert_eq!(
-            measurement.responder_reply_time,
-            short_address_measurement.measurement.responder_reply_time
-        );
-        assert_eq!(
-            measurement.initiator_responder_tof,
-            short_address_measurement.measurement.initiator_responder_tof
-        );
-        assert_eq!(
-            measurement.dt_anchor_location,
-            short_address_measurement.measurement.dt_anchor_location
-        );
-        assert_eq!(
-            measurement.ranging_rounds,
-            short_address_measurement.measurement.ranging_rounds
-        );
+/// A notification waiting in the notification queue to be dispatched.
+enum QueuedNotification {
+    Core(CoreNotification),
+    Session(SessionNotification),
+    Vendor(RawUciMessage),
+    /// A data packet, with the time it was received from the UWBS.
+    DataRcv(DataRcvNotification, Instant),
+    RadarDataRcv(RadarDataRcvNotification),
+}
+
+impl QueuedNotification {
+    /// Whether the notification may be dropped when the queue is full: only the range data and
+    /// the radar data are, the state changes and the received data are always dispatched.
+    fn is_droppable(&self) -> bool {
+        matches!(
+            self,
+            QueuedNotification::Session(SessionNotification::SessionInfo(_))
+                | QueuedNotification::RadarDataRcv(_)
+        )
     }
+}
 
-    #[test]
-    fn test_get_dl_tdoa_ranging_measurement_from_extended_address_measurement() {
-        let bytes = [
-            // All Fields in Little Endian (LE)
-            0x0a, 0x01, 0x33, 0x05, // 4(Mac address..)
-            0x33, 0x05, 0x02, 0x05, // 4(Mac address)
-            0x07, 0x09, 0x0a, 0x01, // Status, Message Type, 2(Message control),
-            0x02, 0x05, 0x07, 0x09, // 2(Block Index), Round Index, NLoS,
-            0x0a, 0x01, 0x02, 0x05, // 2(AoA Azimuth), AoA Azimuth FOM, 1(AoA Elevation..)
-            0x07, 0x09, 0x0a, // 1(AoA Elevation), AoA Elevation FOM, RSSI,
-            0x01, 0x02, 0x05, 0x07, // 4(Tx Timestamp..)
-            0x09, 0x05, 0x07, 0x09, // 4(Tx Timestamp),
-            0x0a, 0x01, 0x02, 0x05, // 4(Rx Timestamp..)
-            0x07, 0x09, 0x05, 0x07, // 4(Rx Timestamp)
-            0x09, 0x0a, 0x01, 0x02, // 2(Anchor Cfo), 2(Cfo),
-            0x05, 0x07, 0x09, 0x05, // 4(Initiator Reply Time)
-            0x07, 0x09, 0x0a, 0x01, // 4(Responder Reply Time),
-            0x02, 0x05, 0x02, 0x05, // 2(Initiator-Responder ToF), 2(Active Ranging Rounds)
-        ];
-        let extended_address_measurement_vec =
-            ExtendedAddressDlTdoaRangingMeasurement::parse(&bytes, 1).unwrap();
-        let extended_address_measurement = &extended_address_measurement_vec[0];
-
-        let measurement = DlTdoaRangingMeasurement::from(extended_address_measurement.clone());
-        assert_eq!(
-            measurement.mac_address,
-            MacAddress::Extended(extended_address_measurement.mac_address)
-        );
-        assert_eq!(measurement.status, extended_address_measurement.measurement.status);
-        assert_eq!(measurement.message_type, extended_address_measurement.measurement.message_type);
-        assert_eq!(
-            measurement.message_control,
-            extended_address_measurement.measurement.message_control
-        );
-        assert_eq!(measurement.block_index, extended_address_measurement.measurement.block_index);
-        assert_eq!(measurement.round_index, extended_address_measurement.measurement.round_index);
-        assert_eq!(measurement.nlos, extended_address_measurement.measurement.nlos);
-        assert_eq!(measurement.aoa_azimuth, extended_address_measurement.measurement.aoa_azimuth);
-        assert_eq!(
-            measurement.aoa_azimuth_fom,
-            extended_address_measurement.measurement.aoa_azimuth_fom
-        );
-        assert_eq!(
-            measurement.aoa_elevation,
-            extended_address_measurement.measurement.aoa_elevation
-        );
-        assert_eq!(
-            measurement.aoa_elevation_fom,
-            extended_address_measurement.measurement.aoa_elevation_fom
-        );
-        assert_eq!(measurement.rssi, extended_address_measurement.measurement.rssi);
-        assert_eq!(measurement.tx_timestamp, extended_address_measurement.measurement.tx_timestamp);
-        assert_eq!(measurement.rx_timestamp, extended_address_measurement.measurement.rx_timestamp);
-        assert_eq!(measurement.anchor_cfo, extended_address_measurement.measurement.anchor_cfo);
-        assert_eq!(measurement.cfo, extended_address_measurement.measurement.cfo);
-        assert_eq!(
-            measurement.initiator_reply_time,
-            extended_address_measurement.measurement.initiator_reply_time
-        );
-        assert_eq!(
-            measurement.responder_reply_time,
-            extended_address_measurement.measurement.responder_reply_time
-        );
-        assert_eq!(
-            measurement.initiator_responder_tof,
-            extended_address_measurement.measurement.initiator_responder_tof
-        );
-        assert_eq!(
-            measurement.dt_anchor_location,
-            extended_address_measurement.measurement.dt_anchor_location
-        );
-        assert_eq!(
-            measurement.ranging_rounds,
-            extended_address_measurement.measurement.ranging_rounds
+/// NotificationManager which queues the notifications instead of calling the Java callbacks from
+/// the UCI manager thread. A dedicated thread dispatches the queued notifications through
+/// NotificationManagerAndroid, so a stalled Java callback cannot pile range data up unboundedly.
+pub(crate) struct QueuedNotificationManagerAndroid {
+    queue: Arc<NotificationQueue<QueuedNotification>>,
+}
+
+impl QueuedNotificationManagerAndroid {
+    fn push(&self, notification: QueuedNotification) -> UwbResult<()> {
+        if !self.queue.push(notification)? {
+            debug!("UCI JNI: notification queue is full, a notification is dropped.");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for QueuedNotificationManagerAndroid {
+    fn drop(&mut self) {
+        // Stops the dispatch thread, which detaches from the JVM on exit.
+        self.queue.close();
+    }
+}
+
+impl NotificationManager for QueuedNotificationManagerAndroid {
+    fn on_core_notification(&mut self, core_notification: CoreNotification) -> UwbResult<()> {
+        self.push(QueuedNotification::Core(core_notification))
+    }
+
+    fn on_session_notification(
+        &mut self,
+        session_notification: SessionNotification,
+    ) -> UwbResult<()> {
+        trace_parsed_session_notification(&session_notification);
+        self.push(QueuedNotification::Session(session_notification))
+    }
+
+    fn on_vendor_notification(&mut self, vendor_notification: RawUciMessage) -> UwbResult<()> {
+        self.push(QueuedNotification::Vendor(vendor_notification))
+    }
+
+    fn on_data_rcv_notification(
+        &mut self,
+        data_rcv_notification: DataRcvNotification,
+    ) -> UwbResult<()> {
+        self.push(QueuedNotification::DataRcv(data_rcv_notification, Instant::now()))
+    }
+
+    fn on_radar_data_rcv_notification(
+        &mut self,
+        radar_data_rcv_notification: RadarDataRcvNotification,
+    ) -> UwbResult<()> {
+        self.push(QueuedNotification::RadarDataRcv(radar_data_rcv_notification))
+    }
+}
+
+/// Builder of QueuedNotificationManagerAndroid. The NotificationManagerAndroid dispatching the
+/// notifications is built on the dispatch thread, as it must be attached to the JVM there.
+pub(crate) struct QueuedNotificationManagerAndroidBuilder {
+    pub builder: NotificationManagerAndroidBuilder,
+    pub capacity: usize,
+    pub policy: BackpressurePolicy,
+}
+
+impl QueuedNotificationManagerAndroidBuilder {
+    fn dispatch_loop(
+        mut notification_manager: NotificationManagerAndroid,
+        queue: Arc<NotificationQueue<QueuedNotification>>,
+    ) {
+        while let Some(notification) = queue.pop() {
+            let dropped_count = queue.take_dropped_count();
+            if dropped_count > 0 {
+                let _ = notification_manager.on_notification_overflow(dropped_count);
+            }
+            // The failures are already logged by NotificationManagerAndroid.
+            let _ = match notification {
+                QueuedNotification::Core(n) => notification_manager.on_core_notification(n),
+                QueuedNotification::Session(n) => {
+                    notification_manager.handle_session_notification(n)
+                }
+                QueuedNotification::Vendor(n) => notification_manager.on_vendor_notification(n),
+                QueuedNotification::DataRcv(n, received_at) => {
+                    notification_manager.dispatch_data_rcv_notification(n, received_at)
+                }
+                QueuedNotification::RadarDataRcv(n) => {
+                    notification_manager.on_radar_data_rcv_notification(n)
+                }
+            };
+        }
+        debug!("UCI JNI: notification dispatch thread of {} exits.", notification_manager.chip_id);
+    }
+}
+
+impl NotificationManagerBuilder for QueuedNotificationManagerAndroidBuilder {
+    type NotificationManager = QueuedNotificationManagerAndroid;
+
+    fn build(self) -> Option<Self::NotificationManager> {
+        let queue = Arc::new(
+            NotificationQueue::new(self.capacity, self.policy, QueuedNotification::is_droppable)
+                .ok()?,
         );
+        let dispatch_queue = queue.clone();
+        let builder = self.builder;
+        let (build_result_sender, build_result_receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name(format!("UwbNotification-{}", builder.chip_id))
+            .spawn(move || match builder.build() {
+                Some(notification_manager) => {
+                    let _ = build_result_sender.send(true);
+                    Self::dispatch_loop(notification_manager, dispatch_queue);
+                }
+                None => {
+                    let _ = build_result_sender.send(false);
+                }
+            })
+            .map_err(|e| error!("UCI JNI: failed to spawn the notification thread: {:?}", e))
+            .ok()?;
+        match build_result_receiver.recv() {
+            Ok(true) => Some(QueuedNotificationManagerAndroid { queue }),
+            _ => None,
+        }
     }
 }
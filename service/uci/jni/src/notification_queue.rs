@@ -0,0 +1,256 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bounded queue of the notifications waiting to be dispatched to the Java callbacks.
+//!
+//! Only the notifications a consumer can miss, e.g. range data, are dropped when the queue is
+//! full. The others, e.g. the session status changes, are always queued, even beyond the capacity,
+//! as losing them would leave the Java state machines out of sync. The producer, the UCI manager
+//! thread, never waits for room.
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+
+use uwb_core::error::{Error, Result};
+
+/// What to do with a notification when the queue is full. The discriminant is the value used by
+/// the Java side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BackpressurePolicy {
+    /// The notifications are dispatched directly from the UCI manager thread, without a queue.
+    Unbounded = 0,
+    /// The oldest queued notification is dropped to make room for the new one.
+    DropOldest = 1,
+    /// The new notification is dropped.
+    DropNewest = 2,
+}
+
+impl TryFrom<i32> for BackpressurePolicy {
+    type Error = Error;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(BackpressurePolicy::Unbounded),
+            1 => Ok(BackpressurePolicy::DropOldest),
+            2 => Ok(BackpressurePolicy::DropNewest),
+            _ => Err(Error::BadParameters),
+        }
+    }
+}
+
+struct QueueState<T> {
+    items: VecDeque<T>,
+    dropped_count: u64,
+    closed: bool,
+}
+
+/// A FIFO queue holding at most `capacity` items, unless more items which cannot be dropped are
+/// pushed, shared by one producer pushing the notifications and one consumer dispatching them.
+pub(crate) struct NotificationQueue<T> {
+    capacity: usize,
+    policy: BackpressurePolicy,
+    /// Whether an item may be dropped when the queue is full.
+    is_droppable: fn(&T) -> bool,
+    state: Mutex<QueueState<T>>,
+    not_empty: Condvar,
+}
+
+impl<T> NotificationQueue<T> {
+    /// Constructs the queue. `policy` must not be Unbounded and `capacity` must not be zero. Only
+    /// the items for which `is_droppable` returns true are dropped by the policy.
+    pub fn new(
+        capacity: usize,
+        policy: BackpressurePolicy,
+        is_droppable: fn(&T) -> bool,
+    ) -> Result<Self> {
+        if capacity == 0 || policy == BackpressurePolicy::Unbounded {
+            return Err(Error::BadParameters);
+        }
+        Ok(Self {
+            capacity,
+            policy,
+            is_droppable,
+            state: Mutex::new(QueueState {
+                items: VecDeque::with_capacity(capacity),
+                dropped_count: 0,
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+        })
+    }
+
+    /// Pushes `item` to the back of the queue, applying the backpressure policy if the queue is
+    /// full. Returns false if the item, or an older one, was dropped. An item which cannot be
+    /// dropped is queued even when no room can be made for it.
+    pub fn push(&self, item: T) -> Result<bool> {
+        let mut state = self.state.lock().map_err(|_| Error::Unknown)?;
+        if state.closed {
+            return Err(Error::Unknown);
+        }
+        let mut accepted_without_drop = true;
+        if state.items.len() >= self.capacity {
+            let oldest_droppable = match self.policy {
+                BackpressurePolicy::DropOldest => {
+                    state.items.iter().position(|queued| (self.is_droppable)(queued))
+                }
+                BackpressurePolicy::DropNewest | BackpressurePolicy::Unbounded => None,
+            };
+            if let Some(index) = oldest_droppable {
+                state.items.remove(index);
+                state.dropped_count = state.dropped_count.saturating_add(1);
+                accepted_without_drop = false;
+            } else if (self.is_droppable)(&item) {
+                state.dropped_count = state.dropped_count.saturating_add(1);
+                return Ok(false);
+            }
+        }
+        state.items.push_back(item);
+        self.not_empty.notify_one();
+        Ok(accepted_without_drop)
+    }
+
+    /// Pops the item at the front of the queue, waiting until there is one. Returns None once the
+    /// queue is closed.
+    pub fn pop(&self) -> Option<T> {
+        let mut state = self.state.lock().ok()?;
+        loop {
+            if state.closed {
+                return None;
+            }
+            if let Some(item) = state.items.pop_front() {
+                return Some(item);
+            }
+            state = self.not_empty.wait(state).ok()?;
+        }
+    }
+
+    /// Returns the number of items dropped since the previous call, and resets it.
+    pub fn take_dropped_count(&self) -> u64 {
+        match self.state.lock() {
+            Ok(mut state) => std::mem::take(&mut state.dropped_count),
+            Err(_) => 0,
+        }
+    }
+
+    /// Closes the queue. The pending items are discarded, and the waiting consumer is woken up.
+    pub fn close(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.closed = true;
+            state.items.clear();
+        }
+        self.not_empty.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// The items below 100 can be dropped, like the range data, the others cannot, like the
+    /// session status changes.
+    fn is_droppable(item: &u8) -> bool {
+        *item < 100
+    }
+
+    #[test]
+    fn test_new_with_invalid_parameters() {
+        assert!(NotificationQueue::new(0, BackpressurePolicy::DropOldest, is_droppable).is_err());
+        assert!(NotificationQueue::new(4, BackpressurePolicy::Unbounded, is_droppable).is_err());
+    }
+
+    #[test]
+    fn test_policy_from_i32() {
+        assert_eq!(BackpressurePolicy::try_from(1).unwrap(), BackpressurePolicy::DropOldest);
+        assert_eq!(BackpressurePolicy::try_from(2).unwrap(), BackpressurePolicy::DropNewest);
+        assert!(BackpressurePolicy::try_from(3).is_err());
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let queue =
+            NotificationQueue::new(2, BackpressurePolicy::DropOldest, is_droppable).unwrap();
+        assert!(queue.push(1).unwrap());
+        assert!(queue.push(2).unwrap());
+        assert!(!queue.push(3).unwrap());
+        assert!(!queue.push(4).unwrap());
+
+        assert_eq!(queue.take_dropped_count(), 2);
+        assert_eq!(queue.take_dropped_count(), 0);
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(4));
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_undroppable_items() {
+        let queue =
+            NotificationQueue::new(2, BackpressurePolicy::DropOldest, is_droppable).unwrap();
+        assert!(queue.push(100).unwrap());
+        assert!(queue.push(1).unwrap());
+        // The oldest droppable item makes room.
+        assert!(!queue.push(2).unwrap());
+        assert!(!queue.push(101).unwrap());
+        // Nothing can be dropped anymore: the undroppable item is queued beyond the capacity, the
+        // droppable one is dropped.
+        assert!(queue.push(102).unwrap());
+        assert!(!queue.push(3).unwrap());
+
+        assert_eq!(queue.take_dropped_count(), 3);
+        assert_eq!(queue.pop(), Some(100));
+        assert_eq!(queue.pop(), Some(101));
+        assert_eq!(queue.pop(), Some(102));
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let queue =
+            NotificationQueue::new(2, BackpressurePolicy::DropNewest, is_droppable).unwrap();
+        assert!(queue.push(1).unwrap());
+        assert!(queue.push(2).unwrap());
+        assert!(!queue.push(3).unwrap());
+
+        assert_eq!(queue.take_dropped_count(), 1);
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+    }
+
+    #[test]
+    fn test_drop_newest_keeps_undroppable_items() {
+        let queue =
+            NotificationQueue::new(1, BackpressurePolicy::DropNewest, is_droppable).unwrap();
+        assert!(queue.push(1).unwrap());
+        assert!(queue.push(100).unwrap());
+
+        assert_eq!(queue.take_dropped_count(), 0);
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(100));
+    }
+
+    #[test]
+    fn test_close_wakes_up_consumer() {
+        let queue = Arc::new(
+            NotificationQueue::new(1, BackpressurePolicy::DropOldest, is_droppable).unwrap(),
+        );
+        let consumer_queue = queue.clone();
+        let consumer = thread::spawn(move || consumer_queue.pop());
+        thread::sleep(Duration::from_millis(50));
+
+        queue.close();
+        assert_eq!(consumer.join().unwrap(), None);
+        assert!(queue.push(1).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bounded buffer of the notifications received while no Java callback object is attached, which
+//! are replayed once one is attached again.
+
+use std::collections::VecDeque;
+
+/// Number of notifications retained while no Java callback object is attached.
+pub(crate) const REPLAY_BUFFER_CAPACITY: usize = 64;
+
+/// A core or session notification waiting to be replayed.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ReplayedNotification<C, S> {
+    Core(C),
+    Session(S),
+}
+
+/// Session status carried by a session notification, which is not replayed twice in a row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SessionStatusKey {
+    pub session_token: u32,
+    pub session_state: u8,
+    pub reason_code: u8,
+}
+
+/// Retains the last REPLAY_BUFFER_CAPACITY core and session notifications, dropping the oldest.
+#[derive(Debug)]
+pub(crate) struct ReplayBuffer<C, S> {
+    capacity: usize,
+    notifications: VecDeque<(Option<SessionStatusKey>, ReplayedNotification<C, S>)>,
+    dropped_count: u64,
+}
+
+impl<C, S> Default for ReplayBuffer<C, S> {
+    fn default() -> Self {
+        Self::new(REPLAY_BUFFER_CAPACITY)
+    }
+}
+
+impl<C, S> ReplayBuffer<C, S> {
+    /// Constructs a buffer retaining up to `capacity` notifications.
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, notifications: VecDeque::new(), dropped_count: 0 }
+    }
+
+    /// Retains a core notification.
+    pub fn push_core(&mut self, notification: C) {
+        self.push(None, ReplayedNotification::Core(notification));
+    }
+
+    /// Retains a session notification. A session status equal to the last status retained for
+    /// the same session is not retained again.
+    pub fn push_session(&mut self, notification: S, status: Option<SessionStatusKey>) {
+        if let Some(status) = status {
+            let last_status =
+                self.notifications.iter().rev().find_map(|(key, _)| {
+                    key.filter(|key| key.session_token == status.session_token)
+                });
+            if last_status == Some(status) {
+                return;
+            }
+        }
+        self.push(status, ReplayedNotification::Session(notification));
+    }
+
+    fn push(&mut self, status: Option<SessionStatusKey>, notification: ReplayedNotification<C, S>) {
+        if self.capacity == 0 {
+            self.dropped_count += 1;
+            return;
+        }
+        if self.notifications.len() == self.capacity {
+            self.notifications.pop_front();
+            self.dropped_count += 1;
+        }
+        self.notifications.push_back((status, notification));
+    }
+
+    /// Accounts for notifications dropped elsewhere while the callback object is detached, so that
+    /// they are reported with the replay.
+    pub fn add_dropped(&mut self, dropped_count: u64) {
+        self.dropped_count += dropped_count;
+    }
+
+    /// Returns whether there is nothing to replay.
+    pub fn is_empty(&self) -> bool {
+        self.notifications.is_empty() && self.dropped_count == 0
+    }
+
+    /// Takes the notifications to replay, oldest first, and the number of notifications dropped
+    /// for lack of room.
+    pub fn take(&mut self) -> (Vec<ReplayedNotification<C, S>>, u64) {
+        let notifications =
+            std::mem::take(&mut self.notifications).into_iter().map(|(_, n)| n).collect();
+        (notifications, std::mem::take(&mut self.dropped_count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(session_token: u32, session_state: u8) -> Option<SessionStatusKey> {
+        Some(SessionStatusKey { session_token, session_state, reason_code: 0 })
+    }
+
+    #[test]
+    fn test_session_status_dedup() {
+        let mut buffer = ReplayBuffer::<u8, &str>::default();
+        assert!(buffer.is_empty());
+        buffer.push_session("idle", status(1, 2));
+        buffer.push_session("other idle", status(2, 2));
+        buffer.push_session("idle again", status(1, 2));
+        buffer.push_core(7);
+        buffer.push_session("active", status(1, 3));
+        buffer.push_session("range data", None);
+        buffer.push_session("range data", None);
+
+        assert_eq!(
+            buffer.take(),
+            (
+                vec![
+                    ReplayedNotification::Session("idle"),
+                    ReplayedNotification::Session("other idle"),
+                    ReplayedNotification::Core(7),
+                    ReplayedNotification::Session("active"),
+                    ReplayedNotification::Session("range data"),
+                    ReplayedNotification::Session("range data"),
+                ],
+                0
+            )
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut buffer = ReplayBuffer::<u8, ()>::new(2);
+        buffer.push_core(1);
+        buffer.push_core(2);
+        buffer.push_core(3);
+        assert_eq!(
+            buffer.take(),
+            (vec![ReplayedNotification::Core(2), ReplayedNotification::Core(3)], 1)
+        );
+    }
+
+    #[test]
+    fn test_add_dropped() {
+        let mut buffer = ReplayBuffer::<u8, ()>::new(1);
+        buffer.add_dropped(3);
+        assert!(!buffer.is_empty());
+        buffer.push_core(1);
+        buffer.push_core(2);
+        assert_eq!(buffer.take(), (vec![ReplayedNotification::Core(2)], 4));
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-chip statistics of the notifications dispatched by NotificationManagerAndroid.
+
+use std::time::Duration;
+
+/// Upper bounds (exclusive) of the dispatch latency histogram buckets, in microseconds. The last
+/// bucket of the histogram collects every dispatch slower than the last bound.
+pub(crate) const LATENCY_BUCKET_BOUNDS_US: [u64; 6] = [100, 500, 1_000, 5_000, 10_000, 50_000];
+pub(crate) const NUM_LATENCY_BUCKETS: usize = LATENCY_BUCKET_BOUNDS_US.len() + 1;
+
+/// The Java callback families tracked by NotificationStats. The discriminant is the value
+/// reported to Java.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CallbackType {
+    Ranging = 0,
+    Radar = 1,
+    Vendor = 2,
+    DataRcv = 3,
+}
+
+impl CallbackType {
+    pub const ALL: [CallbackType; 4] =
+        [CallbackType::Ranging, CallbackType::Radar, CallbackType::Vendor, CallbackType::DataRcv];
+}
+
+/// Dispatch statistics of a single callback type.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CallbackStats {
+    pub count: u64,
+    pub failure_count: u64,
+    pub total_latency_us: u64,
+    pub max_latency_us: u64,
+    pub latency_histogram: [u64; NUM_LATENCY_BUCKETS],
+}
+
+impl CallbackStats {
+    fn record(&mut self, latency: Duration, success: bool) {
+        let latency_us = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let bucket = LATENCY_BUCKET_BOUNDS_US
+            .iter()
+            .position(|&bound| latency_us < bound)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS_US.len());
+        self.count = self.count.saturating_add(1);
+        if !success {
+            self.failure_count = self.failure_count.saturating_add(1);
+        }
+        self.total_latency_us = self.total_latency_us.saturating_add(latency_us);
+        self.max_latency_us = self.max_latency_us.max(latency_us);
+        self.latency_histogram[bucket] = self.latency_histogram[bucket].saturating_add(1);
+    }
+}
+
+/// Dispatch statistics of all the callback types of one chip.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct NotificationStats {
+    callback_stats: [CallbackStats; CallbackType::ALL.len()],
+    /// Number of notifications dropped by the backpressure policy of the notification queue.
+    dropped_count: u64,
+}
+
+impl NotificationStats {
+    /// Records one dispatch of `callback_type` that took `latency` to complete.
+    pub fn record(&mut self, callback_type: CallbackType, latency: Duration, success: bool) {
+        self.callback_stats[callback_type as usize].record(latency, success);
+    }
+
+    /// Gets the statistics of `callback_type`.
+    pub fn get(&self, callback_type: CallbackType) -> &CallbackStats {
+        &self.callback_stats[callback_type as usize]
+    }
+
+    /// Records `count` notifications dropped before their dispatch.
+    pub fn on_dropped(&mut self, count: u64) {
+        self.dropped_count = self.dropped_count.saturating_add(count);
+    }
+
+    /// Gets the number of notifications dropped before their dispatch.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_latency_buckets() {
+        let mut stats = NotificationStats::default();
+        stats.record(CallbackType::Ranging, Duration::from_micros(50), true);
+        stats.record(CallbackType::Ranging, Duration::from_micros(100), true);
+        stats.record(CallbackType::Ranging, Duration::from_millis(7), false);
+        stats.record(CallbackType::Ranging, Duration::from_secs(1), true);
+
+        let ranging_stats = stats.get(CallbackType::Ranging);
+        assert_eq!(ranging_stats.count, 4);
+        assert_eq!(ranging_stats.failure_count, 1);
+        assert_eq!(ranging_stats.total_latency_us, 1_007_150);
+        assert_eq!(ranging_stats.max_latency_us, 1_000_000);
+        assert_eq!(ranging_stats.latency_histogram, [1, 1, 0, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_record_is_per_callback_type() {
+        let mut stats = NotificationStats::default();
+        stats.record(CallbackType::Vendor, Duration::from_micros(10), true);
+        stats.record(CallbackType::DataRcv, Duration::from_micros(10), false);
+
+        assert_eq!(stats.get(CallbackType::Ranging), &CallbackStats::default());
+        assert_eq!(stats.get(CallbackType::Radar), &CallbackStats::default());
+        assert_eq!(stats.get(CallbackType::Vendor).count, 1);
+        assert_eq!(stats.get(CallbackType::Vendor).failure_count, 0);
+        assert_eq!(stats.get(CallbackType::DataRcv).count, 1);
+        assert_eq!(stats.get(CallbackType::DataRcv).failure_count, 1);
+    }
+
+    #[test]
+    fn test_dropped_count() {
+        let mut stats = NotificationStats::default();
+        stats.on_dropped(3);
+        stats.on_dropped(2);
+        assert_eq!(stats.dropped_count(), 5);
+        assert_eq!(stats.get(CallbackType::Ranging).count, 0);
+    }
+}
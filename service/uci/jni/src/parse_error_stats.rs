@@ -0,0 +1,71 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-chip counts of the notifications which could not be converted, by packet family.
+
+use std::collections::BTreeMap;
+
+/// Counts of the conversion failures of one chip since the last fetch. Unlike the protocol error
+/// reports, every failure is counted.
+#[derive(Debug, Default)]
+pub(crate) struct ParseErrorStats {
+    counts: BTreeMap<&'static str, u64>,
+    /// The counts since the stats were created, which fetches do not clear.
+    totals: BTreeMap<&'static str, u64>,
+}
+
+impl ParseErrorStats {
+    /// Records a conversion failure of a notification of `family`.
+    pub fn on_failure(&mut self, family: &'static str) {
+        let count = self.counts.entry(family).or_default();
+        *count = count.saturating_add(1);
+        let total = self.totals.entry(family).or_default();
+        *total = total.saturating_add(1);
+    }
+
+    /// Takes the counts accumulated since the previous call, ordered by family.
+    pub fn fetch_and_clear(&mut self) -> Vec<(&'static str, u64)> {
+        std::mem::take(&mut self.counts).into_iter().collect()
+    }
+
+    /// Gets the counts since the stats were created, ordered by family.
+    pub fn totals(&self) -> Vec<(&'static str, u64)> {
+        self.totals.iter().map(|(&family, &count)| (family, count)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_and_clear() {
+        let mut stats = ParseErrorStats::default();
+        stats.on_failure("SessionInfo");
+        stats.on_failure("Core");
+        stats.on_failure("SessionInfo");
+        assert_eq!(stats.fetch_and_clear(), vec![("Core", 1), ("SessionInfo", 2)]);
+        assert_eq!(stats.fetch_and_clear(), vec![]);
+    }
+
+    #[test]
+    fn test_totals_are_not_cleared() {
+        let mut stats = ParseErrorStats::default();
+        stats.on_failure("Core");
+        stats.fetch_and_clear();
+        stats.on_failure("Core");
+        assert_eq!(stats.totals(), vec![("Core", 2)]);
+        assert_eq!(stats.fetch_and_clear(), vec![("Core", 1)]);
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistence of the native state which outlives the service, such as the calibration of the
+//! chips and the data transfers in flight at shutdown.
+//!
+//! The state is stored as records by key. Every record is framed by the magic and the version of
+//! its format, and followed by the CRC-32 of both and of its body, so that a record which is
+//! corrupted or of another format is detected rather than used.
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+/// Where the records are persisted.
+pub(crate) trait PersistentStorage: Send + Sync {
+    /// Reads the record of the key, None if there is none.
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    /// Persists the record of the key, replacing the previous one.
+    fn store(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+    /// Removes the record of the key, if any.
+    fn remove(&self, key: &str) -> io::Result<()>;
+}
+
+/// Persists every record in a file of a directory, named after its key.
+pub(crate) struct FileStorage {
+    pub dir: PathBuf,
+}
+
+impl FileStorage {
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.bin", key))
+    }
+}
+
+impl PersistentStorage for FileStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn store(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        // Written aside first, so that a shutdown meanwhile does not leave a truncated file.
+        let path = self.path(key);
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, path)
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The format of a kind of record.
+pub(crate) struct RecordFormat {
+    pub magic: [u8; 4],
+    /// Changed whenever the layout of the body changes.
+    pub version: u8,
+}
+
+/// CRC-32 (IEEE 802.3) of `bytes`.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Persists `body` as the record of the key, in the format `format`.
+pub(crate) fn store_record(
+    storage: &dyn PersistentStorage,
+    key: &str,
+    format: &RecordFormat,
+    body: &[u8],
+) -> io::Result<()> {
+    let mut bytes = format.magic.to_vec();
+    bytes.push(format.version);
+    bytes.extend_from_slice(body);
+    let crc = crc32(&bytes);
+    bytes.extend_from_slice(&crc.to_le_bytes());
+    storage.store(key, &bytes)
+}
+
+/// Reads the body of the record of the key, None if there is none. Fails with InvalidData if the
+/// record is corrupted or not in the format `format`.
+pub(crate) fn load_record(
+    storage: &dyn PersistentStorage,
+    key: &str,
+    format: &RecordFormat,
+) -> io::Result<Option<Vec<u8>>> {
+    let bytes = match storage.load(key)? {
+        Some(bytes) => bytes,
+        None => return Ok(None),
+    };
+    let header_len = format.magic.len() + 1;
+    let content_len = bytes
+        .len()
+        .checked_sub(4)
+        .filter(|&len| len >= header_len)
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "truncated record"))?;
+    let (content, crc) = bytes.split_at(content_len);
+    if crc32(content).to_le_bytes() != crc {
+        return Err(io::Error::new(ErrorKind::InvalidData, "corrupted record"));
+    }
+    if content[..format.magic.len()] != format.magic
+        || content[format.magic.len()] != format.version
+    {
+        return Err(io::Error::new(ErrorKind::InvalidData, "unknown record format"));
+    }
+    Ok(Some(content[header_len..].to_vec()))
+}
+
+/// Keeps the records in memory, for the tests of the modules persisting their state.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MemoryStorage {
+    records: std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>,
+}
+
+#[cfg(test)]
+impl PersistentStorage for MemoryStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.records.lock().unwrap().get(key).cloned())
+    }
+
+    fn store(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.records.lock().unwrap().insert(key.to_owned(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        self.records.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMAT: RecordFormat = RecordFormat { magic: *b"TEST", version: 2 };
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_store_and_load_record() {
+        let storage = MemoryStorage::default();
+        assert_eq!(load_record(&storage, "key", &FORMAT).unwrap(), None);
+
+        store_record(&storage, "key", &FORMAT, &[1, 2, 3]).unwrap();
+        let bytes = storage.load("key").unwrap().unwrap();
+        assert_eq!(&bytes[..8], b"TEST\x02\x01\x02\x03");
+        assert_eq!(load_record(&storage, "key", &FORMAT).unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(load_record(&storage, "other key", &FORMAT).unwrap(), None);
+
+        store_record(&storage, "key", &FORMAT, &[]).unwrap();
+        assert_eq!(load_record(&storage, "key", &FORMAT).unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_invalid_records() {
+        let storage = MemoryStorage::default();
+        store_record(&storage, "key", &FORMAT, &[1, 2, 3]).unwrap();
+        let bytes = storage.load("key").unwrap().unwrap();
+        let invalid_data = |storage: &MemoryStorage, format: &RecordFormat| {
+            load_record(storage, "key", format).unwrap_err().kind() == ErrorKind::InvalidData
+        };
+
+        // Another format.
+        assert!(invalid_data(&storage, &RecordFormat { magic: *b"TEST", version: 3 }));
+        assert!(invalid_data(&storage, &RecordFormat { magic: *b"TSET", version: 2 }));
+
+        let mut corrupted = bytes.clone();
+        corrupted[6] ^= 1;
+        storage.store("key", &corrupted).unwrap();
+        assert!(invalid_data(&storage, &FORMAT));
+        storage.store("key", &bytes[..bytes.len() - 1]).unwrap();
+        assert!(invalid_data(&storage, &FORMAT));
+        storage.store("key", &[]).unwrap();
+        assert!(invalid_data(&storage, &FORMAT));
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let dir = std::env::temp_dir().join(format!("uwb_storage_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let storage = FileStorage { dir: dir.clone() };
+        assert_eq!(storage.load("key").unwrap(), None);
+        storage.store("key", &[1, 2]).unwrap();
+        assert_eq!(storage.load("key").unwrap(), Some(vec![1, 2]));
+        storage.remove("key").unwrap();
+        assert_eq!(storage.load("key").unwrap(), None);
+        // Removing a missing record is not an error.
+        storage.remove("key").unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rate-limited reporting of the notifications which could not be converted to Java objects.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Minimum interval between two protocol error reports of a chip.
+pub(crate) const MIN_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The content of a notification which failed the conversion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum NotificationDump {
+    /// The raw payload of the notification, displayed in hex.
+    Raw(Vec<u8>),
+    /// The notification as parsed by uwb_core, when its raw payload is not available.
+    Parsed(String),
+}
+
+impl fmt::Display for NotificationDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotificationDump::Raw(payload) => {
+                payload.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+            }
+            NotificationDump::Parsed(notification) => f.write_str(notification),
+        }
+    }
+}
+
+/// Decides which conversion failures are reported, so that a UWBS repeatedly sending a malformed
+/// notification does not flood the logs and the Java callbacks.
+#[derive(Debug)]
+pub(crate) struct ProtocolErrorReporter {
+    min_interval: Duration,
+    last_report: Option<Instant>,
+    suppressed_count: u64,
+}
+
+impl Default for ProtocolErrorReporter {
+    fn default() -> Self {
+        Self::new(MIN_REPORT_INTERVAL)
+    }
+}
+
+impl ProtocolErrorReporter {
+    /// Constructs a reporter which reports at most one failure per `min_interval`.
+    pub fn new(min_interval: Duration) -> Self {
+        Self { min_interval, last_report: None, suppressed_count: 0 }
+    }
+
+    /// Records a conversion failure at `now`. Returns the number of failures suppressed since the
+    /// previous report if this failure should be reported, or None if it is suppressed.
+    pub fn on_failure(&mut self, now: Instant) -> Option<u64> {
+        match self.last_report {
+            Some(last_report) if now.saturating_duration_since(last_report) < self.min_interval => {
+                self.suppressed_count = self.suppressed_count.saturating_add(1);
+                None
+            }
+            _ => {
+                self.last_report = Some(now);
+                Some(std::mem::take(&mut self.suppressed_count))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_display() {
+        assert_eq!(NotificationDump::Raw(vec![0x01, 0xab, 0x00]).to_string(), "01ab00");
+        assert_eq!(NotificationDump::Raw(vec![]).to_string(), "");
+        assert_eq!(NotificationDump::Parsed("DeviceStatus".to_owned()).to_string(), "DeviceStatus");
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut reporter = ProtocolErrorReporter::new(Duration::from_secs(10));
+        let start = Instant::now();
+        assert_eq!(reporter.on_failure(start), Some(0));
+        assert_eq!(reporter.on_failure(start + Duration::from_secs(1)), None);
+        assert_eq!(reporter.on_failure(start + Duration::from_secs(9)), None);
+        assert_eq!(reporter.on_failure(start + Duration::from_secs(10)), Some(2));
+        assert_eq!(reporter.on_failure(start + Duration::from_secs(25)), Some(0));
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Near/far edge detection of the proximity-only sessions.
+
+use std::collections::HashMap;
+
+use uwb_core::error::{Error, Result};
+
+/// Proximity thresholds of a session, in centimeters. A peer becomes near at `near_cm` or less,
+/// and far again at `far_cm` or more, so that a peer hovering around one threshold does not
+/// flood the app with edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ProximityConfig {
+    near_cm: u16,
+    far_cm: u16,
+}
+
+impl ProximityConfig {
+    /// Constructs the thresholds, `near_cm` must be lower than `far_cm`.
+    pub fn new(near_cm: u16, far_cm: u16) -> Result<Self> {
+        if near_cm >= far_cm {
+            return Err(Error::BadParameters);
+        }
+        Ok(Self { near_cm, far_cm })
+    }
+}
+
+/// A peer crossed one of the thresholds of its session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ProximityEvent {
+    pub session_token: u32,
+    pub mac_address: Vec<u8>,
+    pub is_near: bool,
+    pub distance_cm: u16,
+}
+
+/// Tracks the peers of the proximity-only sessions of one chip.
+#[derive(Debug, Default)]
+pub(crate) struct ProximityTracker {
+    sessions: HashMap<u32, ProximityConfig>,
+    /// Whether each peer was last reported near, keyed by session token and MAC address.
+    peers: HashMap<(u32, Vec<u8>), bool>,
+}
+
+impl ProximityTracker {
+    /// Registers the session as proximity-only, or unregisters it when `config` is None.
+    pub fn set_session(&mut self, session_token: u32, config: Option<ProximityConfig>) {
+        self.peers.retain(|(token, _), _| *token != session_token);
+        match config {
+            Some(config) => self.sessions.insert(session_token, config),
+            None => self.sessions.remove(&session_token),
+        };
+    }
+
+    /// Returns whether the session is proximity-only.
+    pub fn is_proximity_only(&self, session_token: u32) -> bool {
+        self.sessions.contains_key(&session_token)
+    }
+
+    /// Records a distance measured to `mac_address`, and returns the event to report if the peer
+    /// crossed a threshold. The first measurement of a peer always reports its state.
+    pub fn on_distance(
+        &mut self,
+        session_token: u32,
+        mac_address: &[u8],
+        distance_cm: u16,
+    ) -> Option<ProximityEvent> {
+        let config = self.sessions.get(&session_token)?;
+        let key = (session_token, mac_address.to_vec());
+        let is_near = match self.peers.get(&key) {
+            Some(true) if distance_cm >= config.far_cm => false,
+            Some(false) if distance_cm <= config.near_cm => true,
+            Some(_) => return None,
+            None => distance_cm <= config.near_cm,
+        };
+        self.peers.insert(key, is_near);
+        Some(ProximityEvent {
+            session_token,
+            mac_address: mac_address.to_vec(),
+            is_near,
+            distance_cm,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_TOKEN: u32 = 1;
+    const MAC_ADDRESS: [u8; 2] = [0x01, 0x02];
+
+    #[test]
+    fn test_config_rejects_inverted_thresholds() {
+        assert!(ProximityConfig::new(100, 200).is_ok());
+        assert_eq!(ProximityConfig::new(200, 200), Err(Error::BadParameters));
+        assert_eq!(ProximityConfig::new(300, 200), Err(Error::BadParameters));
+    }
+
+    #[test]
+    fn test_edges_with_hysteresis() {
+        let mut tracker = ProximityTracker::default();
+        tracker.set_session(SESSION_TOKEN, Some(ProximityConfig::new(100, 200).unwrap()));
+
+        let first = tracker.on_distance(SESSION_TOKEN, &MAC_ADDRESS, 150).unwrap();
+        assert!(!first.is_near);
+        assert_eq!(tracker.on_distance(SESSION_TOKEN, &MAC_ADDRESS, 120), None);
+        let near = tracker.on_distance(SESSION_TOKEN, &MAC_ADDRESS, 100).unwrap();
+        assert!(near.is_near);
+        assert_eq!(near.distance_cm, 100);
+        // Inside the hysteresis band.
+        assert_eq!(tracker.on_distance(SESSION_TOKEN, &MAC_ADDRESS, 150), None);
+        let far = tracker.on_distance(SESSION_TOKEN, &MAC_ADDRESS, 250).unwrap();
+        assert!(!far.is_near);
+    }
+
+    #[test]
+    fn test_unregistered_session() {
+        let mut tracker = ProximityTracker::default();
+        assert!(!tracker.is_proximity_only(SESSION_TOKEN));
+        assert_eq!(tracker.on_distance(SESSION_TOKEN, &MAC_ADDRESS, 50), None);
+
+        tracker.set_session(SESSION_TOKEN, Some(ProximityConfig::new(100, 200).unwrap()));
+        assert!(tracker.on_distance(SESSION_TOKEN, &MAC_ADDRESS, 50).is_some());
+        tracker.set_session(SESSION_TOKEN, None);
+        assert!(!tracker.is_proximity_only(SESSION_TOKEN));
+        assert_eq!(tracker.on_distance(SESSION_TOKEN, &MAC_ADDRESS, 50), None);
+    }
+}
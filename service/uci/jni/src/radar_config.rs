@@ -0,0 +1,109 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of the radar app configs, which rejects the values the UWBS cannot accept before
+//! ANDROID_SET_RADAR_CONFIG is sent.
+
+use log::error;
+use uwb_core::error::{Error, Result};
+use uwb_core::params::RadarConfigTlv;
+
+/// Radar app config IDs as defined by the Android UCI vendor extensions.
+const RADAR_TIMING_PARAMS: u8 = 0x00;
+const SAMPLES_PER_SWEEP: u8 = 0x01;
+const BITS_PER_SAMPLE: u8 = 0x08;
+
+/// RADAR_TIMING_PARAMS: BURST_PERIOD (4 bytes, ms), SWEEP_PERIOD (2 bytes, RSTU) and
+/// SWEEPS_PER_BURST (1 byte).
+const RADAR_TIMING_PARAMS_LEN: usize = 7;
+const RSTU_PER_MS: u64 = 1200;
+/// Highest BITS_PER_SAMPLE value, for 64 bits per sample.
+const BITS_PER_SAMPLE_MAX: u8 = 0x02;
+
+/// Checks the timing, samples per sweep and bits per sample of the radar app configs.
+pub(crate) fn validate_radar_config_tlvs(tlvs: &[RadarConfigTlv]) -> Result<()> {
+    for tlv in tlvs {
+        let valid = match u8::from(tlv.cfg_id) {
+            RADAR_TIMING_PARAMS => is_valid_timing(&tlv.v),
+            SAMPLES_PER_SWEEP => matches!(tlv.v[..], [samples] if samples > 0),
+            BITS_PER_SAMPLE => matches!(tlv.v[..], [bits] if bits <= BITS_PER_SAMPLE_MAX),
+            _ => true,
+        };
+        if !valid {
+            error!("UCI JNI: invalid radar app config {:?}", tlv);
+            return Err(Error::BadParameters);
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the sweeps of a burst are not empty and, when the bursts are periodic, fit in
+/// the burst period.
+fn is_valid_timing(value: &[u8]) -> bool {
+    if value.len() != RADAR_TIMING_PARAMS_LEN {
+        return false;
+    }
+    let burst_period_ms = u32::from_le_bytes([value[0], value[1], value[2], value[3]]) as u64;
+    let sweep_period_rstu = u16::from_le_bytes([value[4], value[5]]) as u64;
+    let sweeps_per_burst = value[6] as u64;
+    if sweep_period_rstu == 0 || sweeps_per_burst == 0 {
+        return false;
+    }
+    burst_period_ms == 0 || sweep_period_rstu * sweeps_per_burst <= burst_period_ms * RSTU_PER_MS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uwb_uci_packets::RadarConfigTlvType;
+
+    fn timing(
+        burst_period_ms: u32,
+        sweep_period_rstu: u16,
+        sweeps_per_burst: u8,
+    ) -> RadarConfigTlv {
+        let mut v = burst_period_ms.to_le_bytes().to_vec();
+        v.extend_from_slice(&sweep_period_rstu.to_le_bytes());
+        v.push(sweeps_per_burst);
+        RadarConfigTlv { cfg_id: RadarConfigTlvType::RadarTimingParams, v }
+    }
+
+    #[test]
+    fn test_valid_radar_configs() {
+        let tlvs = vec![
+            timing(1000, 1200, 10),
+            RadarConfigTlv { cfg_id: RadarConfigTlvType::SamplesPerSweep, v: vec![64] },
+            RadarConfigTlv { cfg_id: RadarConfigTlvType::BitsPerSample, v: vec![0x02] },
+            RadarConfigTlv { cfg_id: RadarConfigTlvType::ChannelNumber, v: vec![9] },
+        ];
+        assert!(validate_radar_config_tlvs(&tlvs).is_ok());
+        // The burst period is not checked when the bursts are not periodic.
+        assert!(validate_radar_config_tlvs(&[timing(0, 1200, 10)]).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_radar_configs() {
+        for tlv in [
+            timing(10, 1200, 11),
+            timing(1000, 0, 10),
+            timing(1000, 1200, 0),
+            RadarConfigTlv { cfg_id: RadarConfigTlvType::RadarTimingParams, v: vec![0x0, 0x1] },
+            RadarConfigTlv { cfg_id: RadarConfigTlvType::SamplesPerSweep, v: vec![0] },
+            RadarConfigTlv { cfg_id: RadarConfigTlvType::BitsPerSample, v: vec![0x03] },
+            RadarConfigTlv { cfg_id: RadarConfigTlvType::BitsPerSample, v: vec![] },
+        ] {
+            assert_eq!(validate_radar_config_tlvs(&[tlv]), Err(Error::BadParameters));
+        }
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-session decimation and delivery budget of the radar sweeps, which reduce the sweeps passed
+//! to Java for the radar sessions with a high sweep rate.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use uwb_core::uci::{RadarDataRcvNotification, RadarSweepData};
+use uwb_uci_packets::radar_bytes_per_sample_value;
+
+/// How the sweeps of a radar session are decimated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RadarDecimation {
+    /// Keeps the first sweep of every group of N sweeps.
+    KeepEveryNth(u16),
+    /// Replaces every group of N sweeps by their average.
+    Average(u16),
+}
+
+impl RadarDecimation {
+    /// Gets the number of sweeps received for each sweep passed to Java.
+    pub fn factor(&self) -> u16 {
+        match self {
+            RadarDecimation::KeepEveryNth(n) | RadarDecimation::Average(n) => *n,
+        }
+    }
+}
+
+/// Which sweeps of a notification are dropped when they exceed the delivery budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RadarDropPolicy {
+    /// Keeps the most recent sweeps.
+    DropOldest,
+    /// Keeps sweeps evenly spread over the notification.
+    Decimate,
+}
+
+/// Bounds the rate of the sweeps of a radar session passed to Java.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RadarDeliveryBudget {
+    pub max_sweeps_per_sec: u16,
+    pub drop_policy: RadarDropPolicy,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    budget: RadarDeliveryBudget,
+    /// Sweeps which can still be passed, refilled at the budget rate up to one second of sweeps.
+    tokens: f64,
+    last_refill: Instant,
+    /// Sweeps dropped since the last notification passed to Java.
+    dropped_sweep_count: u32,
+}
+
+impl BudgetState {
+    fn new(budget: RadarDeliveryBudget, now: Instant) -> Self {
+        Self {
+            budget,
+            tokens: budget.max_sweeps_per_sec as f64,
+            last_refill: now,
+            dropped_sweep_count: 0,
+        }
+    }
+
+    /// Drops the sweeps exceeding the budget, returns the ones left.
+    fn apply(&mut self, sweeps: Vec<RadarSweepData>, now: Instant) -> Vec<RadarSweepData> {
+        let rate = self.budget.max_sweeps_per_sec as f64;
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate);
+        self.last_refill = now;
+
+        let count = sweeps.len();
+        let allowed = (self.tokens as usize).min(count);
+        let kept: Vec<_> = match self.budget.drop_policy {
+            RadarDropPolicy::DropOldest => sweeps.into_iter().skip(count - allowed).collect(),
+            // Keeps the sweeps at which the running sum of allowed/count reaches a new integer.
+            RadarDropPolicy::Decimate => sweeps
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| (i * allowed) % count < allowed)
+                .map(|(_, sweep)| sweep)
+                .collect(),
+        };
+        self.tokens -= kept.len() as f64;
+        self.dropped_sweep_count =
+            self.dropped_sweep_count.saturating_add((count - allowed) as u32);
+        kept
+    }
+}
+
+#[derive(Debug, Default)]
+struct SessionDecimator {
+    decimation: Option<RadarDecimation>,
+    /// Number of sweeps received by the session since its decimation was set.
+    sweep_count: u64,
+    /// The sweeps of the group being averaged.
+    pending: Vec<RadarSweepData>,
+    budget: Option<BudgetState>,
+}
+
+impl SessionDecimator {
+    fn decimate(
+        &mut self,
+        decimation: RadarDecimation,
+        sweeps: Vec<RadarSweepData>,
+        component_len: usize,
+    ) -> Vec<RadarSweepData> {
+        let mut decimated = Vec::new();
+        for sweep in sweeps {
+            let index = self.sweep_count;
+            self.sweep_count += 1;
+            match decimation {
+                RadarDecimation::KeepEveryNth(n) => {
+                    if index % n as u64 == 0 {
+                        decimated.push(sweep);
+                    }
+                }
+                RadarDecimation::Average(n) => {
+                    // A change of the sample layout restarts the group.
+                    if self
+                        .pending
+                        .first()
+                        .is_some_and(|first| first.sample_data.len() != sweep.sample_data.len())
+                    {
+                        self.pending.clear();
+                    }
+                    self.pending.push(sweep);
+                    if self.pending.len() == n as usize {
+                        decimated
+                            .push(average_sweeps(std::mem::take(&mut self.pending), component_len));
+                    }
+                }
+            }
+        }
+        decimated
+    }
+}
+
+/// The radar sessions of one chip whose sweeps are decimated or bounded by a delivery budget.
+#[derive(Debug, Default)]
+pub(crate) struct RadarDecimator {
+    sessions: HashMap<u32, SessionDecimator>,
+}
+
+impl RadarDecimator {
+    /// Sets the decimation of the session, or removes it when `decimation` is None or keeps every
+    /// sweep.
+    pub fn set_session(&mut self, session_token: u32, decimation: Option<RadarDecimation>) {
+        let session = self.sessions.entry(session_token).or_default();
+        session.decimation = decimation.filter(|decimation| decimation.factor() > 1);
+        session.sweep_count = 0;
+        session.pending.clear();
+        self.remove_if_unused(session_token);
+    }
+
+    /// Sets the delivery budget of the session, or removes it when `budget` is None or allows no
+    /// sweep.
+    pub fn set_session_budget(
+        &mut self,
+        session_token: u32,
+        budget: Option<RadarDeliveryBudget>,
+        now: Instant,
+    ) {
+        let session = self.sessions.entry(session_token).or_default();
+        session.budget = budget
+            .filter(|budget| budget.max_sweeps_per_sec > 0)
+            .map(|budget| BudgetState::new(budget, now));
+        self.remove_if_unused(session_token);
+    }
+
+    /// Forgets the session, e.g. once deinitialized.
+    pub fn remove_session(&mut self, session_token: u32) {
+        self.sessions.remove(&session_token);
+    }
+
+    fn remove_if_unused(&mut self, session_token: u32) {
+        if self
+            .sessions
+            .get(&session_token)
+            .is_some_and(|session| session.decimation.is_none() && session.budget.is_none())
+        {
+            self.sessions.remove(&session_token);
+        }
+    }
+
+    /// Decimates the sweeps of the notification, then drops the ones exceeding the delivery
+    /// budget. Returns None when no sweep is left to pass to Java, otherwise the notification with
+    /// the number of sweeps dropped by the budget since the previous notification passed.
+    pub fn process(
+        &mut self,
+        mut notification: RadarDataRcvNotification,
+        now: Instant,
+    ) -> Option<(RadarDataRcvNotification, u32)> {
+        let session = match self.sessions.get_mut(&notification.session_token) {
+            Some(session) => session,
+            None => return Some((notification, 0)),
+        };
+        let component_len = radar_bytes_per_sample_value(notification.bits_per_sample) as usize / 2;
+        let mut sweeps = std::mem::take(&mut notification.sweep_data);
+        if let Some(decimation) = session.decimation {
+            sweeps = session.decimate(decimation, sweeps, component_len);
+        }
+        if let Some(budget) = session.budget.as_mut() {
+            sweeps = budget.apply(sweeps, now);
+        }
+        if sweeps.is_empty() {
+            return None;
+        }
+        let dropped_sweep_count = session
+            .budget
+            .as_mut()
+            .map_or(0, |budget| std::mem::take(&mut budget.dropped_sweep_count));
+        notification.number_of_sweeps = sweeps.len() as u8;
+        notification.sweep_data = sweeps;
+        Some((notification, dropped_sweep_count))
+    }
+}
+
+/// Averages the sample data of the sweeps, whose I and Q components are little-endian signed
+/// integers of `component_len` bytes. The averaged sweep carries the header of the last sweep.
+fn average_sweeps(mut sweeps: Vec<RadarSweepData>, component_len: usize) -> RadarSweepData {
+    let mut last = sweeps.pop().expect("no sweep to average");
+    if component_len == 0 || component_len > 8 {
+        return last;
+    }
+    let count = sweeps.len() as i64 + 1;
+    for offset in (0..last.sample_data.len() / component_len * component_len).step_by(component_len)
+    {
+        let range = offset..offset + component_len;
+        let sum = sweeps
+            .iter()
+            .map(|sweep| read_component(&sweep.sample_data[range.clone()]))
+            .sum::<i64>()
+            + read_component(&last.sample_data[range.clone()]);
+        let average = (sum / count).to_le_bytes();
+        last.sample_data[range].copy_from_slice(&average[..component_len]);
+    }
+    last
+}
+
+/// Reads a little-endian signed integer of up to 8 bytes.
+fn read_component(bytes: &[u8]) -> i64 {
+    let mut value = [0u8; 8];
+    value[..bytes.len()].copy_from_slice(bytes);
+    // Sign-extends from the most significant byte read.
+    let shift = 64 - 8 * bytes.len() as u32;
+    (i64::from_le_bytes(value) << shift) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use uwb_uci_packets::{BitsPerSample, DataRcvStatusCode, RadarDataType};
+
+    fn sweep(sequence_number: u32, sample_data: Vec<u8>) -> RadarSweepData {
+        RadarSweepData {
+            sequence_number,
+            timestamp: sequence_number * 10,
+            vendor_specific_data: vec![],
+            sample_data,
+        }
+    }
+
+    fn notification(sweep_data: Vec<RadarSweepData>) -> RadarDataRcvNotification {
+        RadarDataRcvNotification {
+            session_token: 1,
+            status: DataRcvStatusCode::UciStatusSuccess,
+            radar_data_type: RadarDataType::RadarSweepSamples,
+            number_of_sweeps: sweep_data.len() as u8,
+            samples_per_sweep: 1,
+            bits_per_sample: BitsPerSample::Value32,
+            sweep_offset: 0,
+            sweep_data,
+        }
+    }
+
+    /// Processes the notification, ignoring the delivery budget.
+    fn process(
+        decimator: &mut RadarDecimator,
+        notification: RadarDataRcvNotification,
+    ) -> Option<RadarDataRcvNotification> {
+        decimator.process(notification, Instant::now()).map(|(notification, _)| notification)
+    }
+
+    fn sequence_numbers(notification: &RadarDataRcvNotification) -> Vec<u32> {
+        notification.sweep_data.iter().map(|sweep| sweep.sequence_number).collect()
+    }
+
+    #[test]
+    fn test_keep_every_nth() {
+        let mut decimator = RadarDecimator::default();
+        decimator.set_session(1, Some(RadarDecimation::KeepEveryNth(3)));
+
+        let sweeps = (0..4).map(|i| sweep(i, vec![0; 4])).collect();
+        let decimated = process(&mut decimator, notification(sweeps)).unwrap();
+        assert_eq!(sequence_numbers(&decimated), vec![0, 3]);
+        assert_eq!(decimated.number_of_sweeps, 2);
+
+        // The groups span the notifications.
+        assert_eq!(process(&mut decimator, notification(vec![sweep(4, vec![0; 4])])), None);
+        assert_eq!(process(&mut decimator, notification(vec![sweep(5, vec![0; 4])])), None);
+        let decimated = process(&mut decimator, notification(vec![sweep(6, vec![0; 4])])).unwrap();
+        assert_eq!(sequence_numbers(&decimated), vec![6]);
+    }
+
+    #[test]
+    fn test_average() {
+        let mut decimator = RadarDecimator::default();
+        decimator.set_session(1, Some(RadarDecimation::Average(2)));
+
+        // I and Q are 16-bit: (100, -4) then (300, -8).
+        let first = sweep(0, [100i16.to_le_bytes(), (-4i16).to_le_bytes()].concat());
+        let second = sweep(1, [300i16.to_le_bytes(), (-8i16).to_le_bytes()].concat());
+        assert_eq!(process(&mut decimator, notification(vec![first])), None);
+        let averaged = process(&mut decimator, notification(vec![second])).unwrap();
+        assert_eq!(averaged.sweep_data.len(), 1);
+        assert_eq!(averaged.sweep_data[0].sequence_number, 1);
+        assert_eq!(
+            averaged.sweep_data[0].sample_data,
+            [200i16.to_le_bytes(), (-6i16).to_le_bytes()].concat()
+        );
+    }
+
+    #[test]
+    fn test_undecimated_session() {
+        let mut decimator = RadarDecimator::default();
+        decimator.set_session(1, Some(RadarDecimation::KeepEveryNth(2)));
+        decimator.set_session(1, Some(RadarDecimation::Average(1)));
+
+        let sweeps = (0..3).map(|i| sweep(i, vec![0; 4])).collect();
+        let decimated = process(&mut decimator, notification(sweeps)).unwrap();
+        assert_eq!(sequence_numbers(&decimated), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_budget_drop_oldest() {
+        let mut decimator = RadarDecimator::default();
+        let start = Instant::now();
+        let budget =
+            RadarDeliveryBudget { max_sweeps_per_sec: 4, drop_policy: RadarDropPolicy::DropOldest };
+        decimator.set_session_budget(1, Some(budget), start);
+
+        let sweeps = (0..6).map(|i| sweep(i, vec![0; 4])).collect();
+        let (delivered, dropped) = decimator.process(notification(sweeps), start).unwrap();
+        assert_eq!(sequence_numbers(&delivered), vec![2, 3, 4, 5]);
+        assert_eq!(delivered.number_of_sweeps, 4);
+        assert_eq!(dropped, 2);
+
+        // The budget is spent until it is refilled.
+        assert_eq!(decimator.process(notification(vec![sweep(6, vec![0; 4])]), start), None);
+        let later = start + Duration::from_millis(500);
+        let sweeps = (7..10).map(|i| sweep(i, vec![0; 4])).collect();
+        let (delivered, dropped) = decimator.process(notification(sweeps), later).unwrap();
+        assert_eq!(sequence_numbers(&delivered), vec![8, 9]);
+        // Includes the sweep of the notification which was not passed.
+        assert_eq!(dropped, 2);
+    }
+
+    #[test]
+    fn test_budget_decimate() {
+        let mut decimator = RadarDecimator::default();
+        let start = Instant::now();
+        let budget =
+            RadarDeliveryBudget { max_sweeps_per_sec: 3, drop_policy: RadarDropPolicy::Decimate };
+        decimator.set_session_budget(1, Some(budget), start);
+
+        let sweeps = (0..6).map(|i| sweep(i, vec![0; 4])).collect();
+        let (delivered, dropped) = decimator.process(notification(sweeps), start).unwrap();
+        assert_eq!(sequence_numbers(&delivered), vec![0, 2, 4]);
+        assert_eq!(dropped, 3);
+    }
+
+    #[test]
+    fn test_budget_after_decimation() {
+        let mut decimator = RadarDecimator::default();
+        let start = Instant::now();
+        decimator.set_session(1, Some(RadarDecimation::KeepEveryNth(2)));
+        let budget =
+            RadarDeliveryBudget { max_sweeps_per_sec: 2, drop_policy: RadarDropPolicy::DropOldest };
+        decimator.set_session_budget(1, Some(budget), start);
+
+        // Only the sweeps left by the decimation count against the budget.
+        let sweeps = (0..6).map(|i| sweep(i, vec![0; 4])).collect();
+        let (delivered, dropped) = decimator.process(notification(sweeps), start).unwrap();
+        assert_eq!(sequence_numbers(&delivered), vec![2, 4]);
+        assert_eq!(dropped, 1);
+
+        // Removing the decimation keeps the budget.
+        decimator.set_session(1, None);
+        let later = start + Duration::from_secs(1);
+        let sweeps = (6..9).map(|i| sweep(i, vec![0; 4])).collect();
+        let (delivered, _) = decimator.process(notification(sweeps), later).unwrap();
+        assert_eq!(sequence_numbers(&delivered), vec![7, 8]);
+
+        decimator.remove_session(1);
+        let sweeps = (9..12).map(|i| sweep(i, vec![0; 4])).collect();
+        let (delivered, dropped) = decimator.process(notification(sweeps), later).unwrap();
+        assert_eq!(sequence_numbers(&delivered), vec![9, 10, 11]);
+        assert_eq!(dropped, 0);
+    }
+
+    #[test]
+    fn test_read_component() {
+        assert_eq!(read_component(&[0xff, 0xff, 0xff]), -1);
+        assert_eq!(read_component(&[0x00, 0x00, 0x80]), -(1 << 23));
+        assert_eq!(read_component(&[0x34, 0x12]), 0x1234);
+    }
+}
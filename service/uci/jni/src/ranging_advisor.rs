@@ -0,0 +1,171 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Advisory ranging interval adjustments for the sessions suffering from RF congestion.
+
+use std::collections::HashMap;
+
+/// Number of ranging rounds over which the congestion of a session is evaluated.
+pub(crate) const EVALUATION_ROUNDS: u32 = 20;
+/// Percentage of missed rounds from which a session is considered congested.
+pub(crate) const CONGESTED_MISSED_PERCENT: u32 = 30;
+/// Percentage of missed rounds under which a session is considered clear.
+pub(crate) const CLEAR_MISSED_PERCENT: u32 = 5;
+/// Number of data transfers rejected for lack of credit from which a session is considered
+/// starved, whatever its missed rounds.
+pub(crate) const STARVED_TRANSFER_COUNT: u32 = 3;
+/// Maximum block stride suggested, each step doubling the interval of the previous one.
+pub(crate) const MAX_BACKOFF_STEPS: u8 = 3;
+/// Bounds of the suggested ranging durations.
+pub(crate) const MIN_RANGING_DURATION_MS: u32 = 24;
+pub(crate) const MAX_RANGING_DURATION_MS: u32 = 2000;
+
+/// Suggested adjustment of the ranging of a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RangingSuggestion {
+    pub session_token: u32,
+    /// The RANGING_DURATION suggested, in milliseconds.
+    pub ranging_duration_ms: u32,
+    /// The BLOCK_STRIDE_LENGTH suggested.
+    pub block_stride: u8,
+}
+
+#[derive(Debug, Default)]
+struct SessionWindow {
+    rounds: u32,
+    missed_rounds: u32,
+    starved_transfers: u32,
+    /// Number of backoff steps suggested so far, which is also the suggested block stride.
+    backoff_steps: u8,
+}
+
+/// Watches the missed ranging rounds and the data credit starvation of the sessions of one chip.
+/// Every EVALUATION_ROUNDS rounds, a congested session is advised to back off one step, and a
+/// clear session which backed off is advised to step back.
+#[derive(Debug, Default)]
+pub(crate) struct RangingAdvisor {
+    sessions: HashMap<u32, SessionWindow>,
+}
+
+impl RangingAdvisor {
+    /// Records a ranging round of the session, with `successful_count` of its `measurement_count`
+    /// measurements succeeding. A round without any successful measurement is missed. Returns the
+    /// suggestion to report, if any.
+    pub fn on_ranging_round(
+        &mut self,
+        session_token: u32,
+        current_interval_ms: u32,
+        successful_count: usize,
+        measurement_count: usize,
+    ) -> Option<RangingSuggestion> {
+        let window = self.sessions.entry(session_token).or_default();
+        window.rounds += 1;
+        if measurement_count == 0 || successful_count == 0 {
+            window.missed_rounds += 1;
+        }
+        if window.rounds < EVALUATION_ROUNDS {
+            return None;
+        }
+
+        let missed_percent = window.missed_rounds * 100 / window.rounds;
+        let starved = window.starved_transfers >= STARVED_TRANSFER_COUNT;
+        window.rounds = 0;
+        window.missed_rounds = 0;
+        window.starved_transfers = 0;
+        let ranging_duration_ms = if missed_percent >= CONGESTED_MISSED_PERCENT || starved {
+            if window.backoff_steps >= MAX_BACKOFF_STEPS {
+                return None;
+            }
+            window.backoff_steps += 1;
+            current_interval_ms.saturating_mul(2)
+        } else if missed_percent <= CLEAR_MISSED_PERCENT && window.backoff_steps > 0 {
+            window.backoff_steps -= 1;
+            current_interval_ms / 2
+        } else {
+            return None;
+        };
+        Some(RangingSuggestion {
+            session_token,
+            ranging_duration_ms: ranging_duration_ms
+                .clamp(MIN_RANGING_DURATION_MS, MAX_RANGING_DURATION_MS),
+            block_stride: window.backoff_steps,
+        })
+    }
+
+    /// Records a data transfer of the session rejected because no credit was available.
+    pub fn on_starved_transfer(&mut self, session_token: u32) {
+        let window = self.sessions.entry(session_token).or_default();
+        window.starved_transfers = window.starved_transfers.saturating_add(1);
+    }
+
+    /// Forgets a deinitialized session.
+    pub fn on_session_deinit(&mut self, session_token: u32) {
+        self.sessions.remove(&session_token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_rounds(
+        advisor: &mut RangingAdvisor,
+        interval_ms: u32,
+        missed: u32,
+    ) -> Option<RangingSuggestion> {
+        let mut suggestion = None;
+        for round in 0..EVALUATION_ROUNDS {
+            let successful_count = if round < missed { 0 } else { 1 };
+            suggestion = advisor.on_ranging_round(1, interval_ms, successful_count, 1);
+            if round + 1 < EVALUATION_ROUNDS {
+                assert_eq!(suggestion, None);
+            }
+        }
+        suggestion
+    }
+
+    #[test]
+    fn test_backoff_and_recovery() {
+        let mut advisor = RangingAdvisor::default();
+        assert_eq!(
+            run_rounds(&mut advisor, 200, 10),
+            Some(RangingSuggestion { session_token: 1, ranging_duration_ms: 400, block_stride: 1 })
+        );
+        // Neither congested nor clear.
+        assert_eq!(run_rounds(&mut advisor, 400, 2), None);
+        assert_eq!(
+            run_rounds(&mut advisor, 400, 0),
+            Some(RangingSuggestion { session_token: 1, ranging_duration_ms: 200, block_stride: 0 })
+        );
+        // Nothing to step back from.
+        assert_eq!(run_rounds(&mut advisor, 200, 0), None);
+    }
+
+    #[test]
+    fn test_starvation_and_max_backoff() {
+        let mut advisor = RangingAdvisor::default();
+        for step in 1..=MAX_BACKOFF_STEPS {
+            for _ in 0..STARVED_TRANSFER_COUNT {
+                advisor.on_starved_transfer(1);
+            }
+            let suggestion = run_rounds(&mut advisor, 1500, 0).unwrap();
+            assert_eq!(suggestion.block_stride, step);
+            assert_eq!(suggestion.ranging_duration_ms, MAX_RANGING_DURATION_MS);
+        }
+        assert_eq!(run_rounds(&mut advisor, 1500, EVALUATION_ROUNDS), None);
+
+        advisor.on_session_deinit(1);
+        assert_eq!(run_rounds(&mut advisor, 200, 0), None);
+    }
+}
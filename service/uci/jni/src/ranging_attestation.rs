@@ -0,0 +1,494 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Attestation of the ranging measurement streams, for the applications which need tamper-evident
+//! ranging logs.
+//!
+//! The SESSION_INFO_NTFs of an attested session are split into segments of a fixed number of
+//! notifications. Each segment is signed by a platform-provided attestor, and chained to the
+//! signature of the previous segment, so that a segment can be neither altered, removed nor
+//! reordered without breaking the chain.
+//!
+//! The segments are signed by an AttestationSigner on a dedicated thread, so that a slow attestor
+//! does not delay the dispatch of the notifications.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use log::{debug, error};
+
+/// Maximum number of attestations kept per session until they are fetched, the oldest are dropped.
+const MAX_ATTESTATIONS_PER_SESSION: usize = 64;
+
+/// Maximum number of segments waiting to be signed, the newer ones are dropped.
+const MAX_PENDING_ATTESTATIONS: usize = 16;
+
+/// A platform-provided signer of the ranging measurement streams, e.g. backed by the secure
+/// element.
+pub(crate) trait RangingAttestor: Send + Sync {
+    /// Signs the digest of a segment of the measurement stream of the session. Returns None if the
+    /// segment could not be signed.
+    fn attest(&self, session_id: u32, segment: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// The signature of a segment of the measurement stream of a session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RangingAttestationRecord {
+    /// Position of the segment in the stream of the session, from 0. A gap in the sequence numbers
+    /// reveals a segment which could not be signed.
+    pub sequence_number: u32,
+    /// Number of SESSION_INFO_NTFs in the segment.
+    pub measurement_count: u16,
+    pub signature: Vec<u8>,
+}
+
+/// A complete segment of the measurement stream of a session, to be signed outside of the lock of
+/// the RangingAttestation.
+pub(crate) struct PendingAttestation {
+    pub attestor: Arc<dyn RangingAttestor>,
+    pub session_id: u32,
+    pub sequence_number: u32,
+    pub measurement_count: u16,
+    /// The length-prefixed raw SESSION_INFO_NTFs of the segment.
+    pub measurements: Vec<u8>,
+}
+
+impl PendingAttestation {
+    /// Returns the signed bytes: the session ID and the sequence number as little-endian u32, the
+    /// length-prefixed signature of the previous segment, then the length-prefixed raw
+    /// SESSION_INFO_NTFs.
+    pub fn segment(&self, previous_signature: &[u8]) -> Vec<u8> {
+        let mut segment =
+            Vec::with_capacity(10 + previous_signature.len() + self.measurements.len());
+        segment.extend_from_slice(&self.session_id.to_le_bytes());
+        segment.extend_from_slice(&self.sequence_number.to_le_bytes());
+        push_length_prefixed(&mut segment, previous_signature);
+        segment.extend_from_slice(&self.measurements);
+        segment
+    }
+
+    /// Signs the segment, chained to `previous_signature`, with the attestor.
+    pub fn sign(&self, previous_signature: &[u8]) -> Option<Vec<u8>> {
+        self.attestor.attest(self.session_id, &self.segment(previous_signature))
+    }
+}
+
+#[derive(Debug, Default)]
+struct SessionAttestation {
+    measurements_per_attestation: u16,
+    sequence_number: u32,
+    measurement_count: u16,
+    /// The length-prefixed raw SESSION_INFO_NTFs of the current segment.
+    measurements: Vec<u8>,
+    previous_signature: Vec<u8>,
+    records: VecDeque<RangingAttestationRecord>,
+}
+
+impl SessionAttestation {
+    fn take_segment(&mut self) -> Option<(u32, u16, Vec<u8>)> {
+        if self.measurement_count == 0 {
+            return None;
+        }
+        let sequence_number = self.sequence_number;
+        let measurement_count = std::mem::take(&mut self.measurement_count);
+        self.sequence_number = self.sequence_number.wrapping_add(1);
+        Some((sequence_number, measurement_count, std::mem::take(&mut self.measurements)))
+    }
+}
+
+/// The attested sessions of one chip.
+#[derive(Default)]
+pub(crate) struct RangingAttestation {
+    attestor: Option<Arc<dyn RangingAttestor>>,
+    sessions: HashMap<u32, SessionAttestation>,
+}
+
+impl RangingAttestation {
+    /// Sets the attestor signing the segments, or stops the attestation of all the sessions when
+    /// `attestor` is None. The segments in progress are dropped.
+    pub fn set_attestor(&mut self, attestor: Option<Arc<dyn RangingAttestor>>) {
+        self.attestor = attestor;
+        for session in self.sessions.values_mut() {
+            session.measurement_count = 0;
+            session.measurements.clear();
+        }
+    }
+
+    /// Attests the measurement stream of the session by segments of `measurements_per_attestation`
+    /// SESSION_INFO_NTFs, or stops attesting it when 0. The attestations not fetched yet are
+    /// dropped.
+    pub fn set_session(&mut self, session_id: u32, measurements_per_attestation: u16) {
+        if measurements_per_attestation == 0 {
+            self.sessions.remove(&session_id);
+            return;
+        }
+        self.sessions.insert(
+            session_id,
+            SessionAttestation { measurements_per_attestation, ..Default::default() },
+        );
+    }
+
+    /// Adds a SESSION_INFO_NTF of the session to its current segment. Returns the segment to sign
+    /// once it is complete.
+    pub fn on_range_data(
+        &mut self,
+        session_id: u32,
+        raw_ranging_data: &[u8],
+    ) -> Option<PendingAttestation> {
+        let attestor = self.attestor.clone()?;
+        let session = self.sessions.get_mut(&session_id)?;
+        push_length_prefixed(&mut session.measurements, raw_ranging_data);
+        session.measurement_count += 1;
+        if session.measurement_count < session.measurements_per_attestation {
+            return None;
+        }
+        let (sequence_number, measurement_count, measurements) = session.take_segment()?;
+        Some(PendingAttestation {
+            attestor,
+            session_id,
+            sequence_number,
+            measurement_count,
+            measurements,
+        })
+    }
+
+    /// Returns the incomplete segment of a session which stopped ranging, so that the end of its
+    /// stream is attested too.
+    pub fn on_session_stopped(&mut self, session_id: u32) -> Option<PendingAttestation> {
+        let attestor = self.attestor.clone()?;
+        let session = self.sessions.get_mut(&session_id)?;
+        let (sequence_number, measurement_count, measurements) = session.take_segment()?;
+        Some(PendingAttestation {
+            attestor,
+            session_id,
+            sequence_number,
+            measurement_count,
+            measurements,
+        })
+    }
+
+    /// Returns the signature the next segment of the session is chained to, or None if the
+    /// session is no longer attested.
+    pub fn previous_signature(&self, session_id: u32) -> Option<Vec<u8>> {
+        self.sessions.get(&session_id).map(|session| session.previous_signature.clone())
+    }
+
+    /// Records the signature of a segment, which the next segment of the session is chained to.
+    pub fn on_signed(&mut self, attestation: &PendingAttestation, signature: Option<Vec<u8>>) {
+        let session = match self.sessions.get_mut(&attestation.session_id) {
+            Some(session) => session,
+            // The attestation of the session was stopped while the segment was signed.
+            None => return,
+        };
+        let signature = match signature {
+            Some(signature) => signature,
+            None => {
+                error!(
+                    "UCI JNI: failed to attest segment {} of session {}",
+                    attestation.sequence_number, attestation.session_id
+                );
+                return;
+            }
+        };
+        session.previous_signature = signature.clone();
+        if session.records.len() == MAX_ATTESTATIONS_PER_SESSION {
+            session.records.pop_front();
+        }
+        session.records.push_back(RangingAttestationRecord {
+            sequence_number: attestation.sequence_number,
+            measurement_count: attestation.measurement_count,
+            signature,
+        });
+    }
+
+    /// Takes the attestations of the session recorded since the previous call, in the order of
+    /// their sequence numbers.
+    pub fn fetch_and_clear(&mut self, session_id: u32) -> Vec<RangingAttestationRecord> {
+        self.sessions
+            .get_mut(&session_id)
+            .map_or(vec![], |session| session.records.drain(..).collect())
+    }
+}
+
+/// Signs the segments of the measurement streams of one chip on a dedicated thread. The segments
+/// are signed one at a time in the order they are submitted, so that each is chained to the
+/// signature of the previous segment of its session.
+pub(crate) struct AttestationSigner {
+    sender: SyncSender<PendingAttestation>,
+}
+
+impl AttestationSigner {
+    /// Spawns the signing thread, which records the signatures in `attestation` and exits once
+    /// the signer is dropped.
+    pub fn new(name: String, attestation: Arc<Mutex<RangingAttestation>>) -> Option<Self> {
+        let (sender, receiver) = mpsc::sync_channel::<PendingAttestation>(MAX_PENDING_ATTESTATIONS);
+        thread::Builder::new()
+            .name(name)
+            .spawn(move || {
+                for pending_attestation in receiver {
+                    Self::sign(&attestation, pending_attestation);
+                }
+                debug!("UCI JNI: attestation signing thread exits.");
+            })
+            .map_err(|e| error!("UCI JNI: failed to spawn the attestation thread: {:?}", e))
+            .ok()?;
+        Some(Self { sender })
+    }
+
+    /// Queues a segment to be signed, without waiting for the attestor. The segment is dropped,
+    /// leaving a gap in the sequence numbers, when too many segments are waiting already.
+    pub fn submit(&self, pending_attestation: PendingAttestation) {
+        match self.sender.try_send(pending_attestation) {
+            Ok(()) => {}
+            Err(TrySendError::Full(pending_attestation)) => error!(
+                "UCI JNI: attestor too slow, segment {} of session {} dropped",
+                pending_attestation.sequence_number, pending_attestation.session_id
+            ),
+            Err(TrySendError::Disconnected(_)) => {
+                error!("UCI JNI: attestation signing thread is gone")
+            }
+        }
+    }
+
+    /// Signs a segment without holding the lock of the attestation, as the attestor may be slow.
+    fn sign(attestation: &Mutex<RangingAttestation>, pending_attestation: PendingAttestation) {
+        let previous_signature = match attestation.lock() {
+            Ok(attestation) => attestation.previous_signature(pending_attestation.session_id),
+            Err(e) => {
+                error!("UCI JNI: failed to lock ranging attestation: {:?}", e);
+                return;
+            }
+        };
+        // The attestation of the session was stopped before the segment was signed.
+        let previous_signature = match previous_signature {
+            Some(previous_signature) => previous_signature,
+            None => return,
+        };
+        let signature = pending_attestation.sign(&previous_signature);
+        match attestation.lock() {
+            Ok(mut attestation) => attestation.on_signed(&pending_attestation, signature),
+            Err(e) => error!("UCI JNI: failed to lock ranging attestation: {:?}", e),
+        }
+    }
+}
+
+fn push_length_prefixed(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    // The SESSION_INFO_NTFs and the signatures are far below 64KiB.
+    buffer.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, Instant};
+
+    /// Signs a segment with its length and last byte.
+    struct FakeAttestor {}
+    impl RangingAttestor for FakeAttestor {
+        fn attest(&self, _session_id: u32, segment: &[u8]) -> Option<Vec<u8>> {
+            Some(vec![segment.len() as u8, *segment.last()?])
+        }
+    }
+
+    fn sign(attestation: &mut RangingAttestation, pending: Option<PendingAttestation>) {
+        let pending = pending.unwrap();
+        let previous_signature = attestation.previous_signature(pending.session_id).unwrap();
+        let signature = pending.sign(&previous_signature);
+        attestation.on_signed(&pending, signature);
+    }
+
+    #[test]
+    fn test_segments_are_chained() {
+        let mut attestation = RangingAttestation::default();
+        attestation.set_attestor(Some(Arc::new(FakeAttestor {})));
+        attestation.set_session(1, 2);
+
+        assert!(attestation.on_range_data(1, &[0xa]).is_none());
+        let pending = attestation.on_range_data(1, &[0xb, 0xc]).unwrap();
+        assert_eq!(pending.sequence_number, 0);
+        assert_eq!(pending.measurement_count, 2);
+        assert_eq!(
+            pending.segment(&attestation.previous_signature(1).unwrap()),
+            vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0xa, 2, 0, 0xb, 0xc]
+        );
+        sign(&mut attestation, Some(pending));
+
+        assert!(attestation.on_range_data(1, &[0xd]).is_none());
+        let pending = attestation.on_range_data(1, &[0xe]).unwrap();
+        assert_eq!(pending.sequence_number, 1);
+        // Chained to the signature of the first segment.
+        assert_eq!(
+            pending.segment(&attestation.previous_signature(1).unwrap()),
+            vec![1, 0, 0, 0, 1, 0, 0, 0, 2, 0, 17, 0xc, 1, 0, 0xd, 1, 0, 0xe]
+        );
+        sign(&mut attestation, Some(pending));
+
+        assert_eq!(
+            attestation.fetch_and_clear(1),
+            vec![
+                RangingAttestationRecord {
+                    sequence_number: 0,
+                    measurement_count: 2,
+                    signature: vec![17, 0xc]
+                },
+                RangingAttestationRecord {
+                    sequence_number: 1,
+                    measurement_count: 2,
+                    signature: vec![18, 0xe]
+                },
+            ]
+        );
+        assert!(attestation.fetch_and_clear(1).is_empty());
+    }
+
+    #[test]
+    fn test_incomplete_segment_is_attested_when_stopped() {
+        let mut attestation = RangingAttestation::default();
+        attestation.set_attestor(Some(Arc::new(FakeAttestor {})));
+        attestation.set_session(1, 10);
+
+        assert!(attestation.on_session_stopped(1).is_none());
+        assert!(attestation.on_range_data(1, &[0xa]).is_none());
+        let pending = attestation.on_session_stopped(1);
+        sign(&mut attestation, pending);
+
+        let records = attestation.fetch_and_clear(1);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].measurement_count, 1);
+    }
+
+    #[test]
+    fn test_failed_attestation_leaves_a_gap() {
+        let mut attestation = RangingAttestation::default();
+        attestation.set_attestor(Some(Arc::new(FakeAttestor {})));
+        attestation.set_session(1, 1);
+
+        let pending = attestation.on_range_data(1, &[0xa]).unwrap();
+        attestation.on_signed(&pending, None);
+        let pending = attestation.on_range_data(1, &[0xb]);
+        sign(&mut attestation, pending);
+
+        let records = attestation.fetch_and_clear(1);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sequence_number, 1);
+    }
+
+    #[test]
+    fn test_not_attested_without_attestor_or_session() {
+        let mut attestation = RangingAttestation::default();
+        attestation.set_session(1, 1);
+        assert!(attestation.on_range_data(1, &[0xa]).is_none());
+
+        attestation.set_attestor(Some(Arc::new(FakeAttestor {})));
+        assert!(attestation.on_range_data(2, &[0xa]).is_none());
+        attestation.set_session(1, 0);
+        assert!(attestation.on_range_data(1, &[0xa]).is_none());
+        assert!(attestation.fetch_and_clear(1).is_empty());
+    }
+
+    #[test]
+    fn test_oldest_attestations_are_dropped() {
+        let mut attestation = RangingAttestation::default();
+        attestation.set_attestor(Some(Arc::new(FakeAttestor {})));
+        attestation.set_session(1, 1);
+
+        for _ in 0..MAX_ATTESTATIONS_PER_SESSION + 1 {
+            let pending = attestation.on_range_data(1, &[0xa]);
+            sign(&mut attestation, pending);
+        }
+        let records = attestation.fetch_and_clear(1);
+        assert_eq!(records.len(), MAX_ATTESTATIONS_PER_SESSION);
+        assert_eq!(records[0].sequence_number, 1);
+    }
+
+    /// Blocks the signatures until released, like an attestor backed by a slow secure element.
+    struct BlockedAttestor {
+        started: Mutex<mpsc::Sender<()>>,
+        released: Mutex<mpsc::Receiver<()>>,
+    }
+    impl RangingAttestor for BlockedAttestor {
+        fn attest(&self, session_id: u32, segment: &[u8]) -> Option<Vec<u8>> {
+            self.started.lock().unwrap().send(()).ok()?;
+            self.released.lock().unwrap().recv().ok()?;
+            FakeAttestor {}.attest(session_id, segment)
+        }
+    }
+
+    fn wait_for_records(attestation: &Mutex<RangingAttestation>, count: usize) -> Vec<u32> {
+        let mut sequence_numbers = vec![];
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while sequence_numbers.len() < count && Instant::now() < deadline {
+            let records = attestation.lock().unwrap().fetch_and_clear(1);
+            sequence_numbers.extend(records.iter().map(|record| record.sequence_number));
+            thread::sleep(Duration::from_millis(10));
+        }
+        sequence_numbers
+    }
+
+    #[test]
+    fn test_signer_does_not_block_on_slow_attestor() {
+        let (started_sender, started_receiver) = mpsc::channel();
+        let (released_sender, released_receiver) = mpsc::channel();
+        let attestation = Arc::new(Mutex::new(RangingAttestation::default()));
+        attestation.lock().unwrap().set_attestor(Some(Arc::new(BlockedAttestor {
+            started: Mutex::new(started_sender),
+            released: Mutex::new(released_receiver),
+        })));
+        attestation.lock().unwrap().set_session(1, 1);
+        let signer = AttestationSigner::new("test".to_string(), attestation.clone()).unwrap();
+        let submit = || {
+            let pending = attestation.lock().unwrap().on_range_data(1, &[0xa]).unwrap();
+            signer.submit(pending);
+        };
+
+        // The first segment blocks the attestor, the next ones fill the queue and the last one is
+        // dropped, without waiting for the attestor.
+        submit();
+        started_receiver.recv().unwrap();
+        let started_at = Instant::now();
+        for _ in 0..MAX_PENDING_ATTESTATIONS + 1 {
+            submit();
+        }
+        assert!(started_at.elapsed() < Duration::from_secs(1));
+
+        for _ in 0..MAX_PENDING_ATTESTATIONS + 1 {
+            released_sender.send(()).unwrap();
+        }
+        let sequence_numbers = wait_for_records(&attestation, MAX_PENDING_ATTESTATIONS + 1);
+        assert_eq!(sequence_numbers, (0..=MAX_PENDING_ATTESTATIONS as u32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_signer_chains_segments_in_order() {
+        let attestation = Arc::new(Mutex::new(RangingAttestation::default()));
+        attestation.lock().unwrap().set_attestor(Some(Arc::new(FakeAttestor {})));
+        attestation.lock().unwrap().set_session(1, 1);
+        let signer = AttestationSigner::new("test".to_string(), attestation.clone()).unwrap();
+
+        // Both segments are submitted before the first one is signed.
+        let first = attestation.lock().unwrap().on_range_data(1, &[0xa]).unwrap();
+        let second = attestation.lock().unwrap().on_range_data(1, &[0xb]).unwrap();
+        let first_signature = first.sign(&[]).unwrap();
+        let second_signature = second.sign(&first_signature).unwrap();
+        signer.submit(first);
+        signer.submit(second);
+
+        assert_eq!(wait_for_records(&attestation, 2), vec![0, 1]);
+        assert_eq!(attestation.lock().unwrap().previous_signature(1), Some(second_signature));
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-session reporting of the ranging diagnostics in ANDROID_RANGE_DIAGNOSTICS_NTF, enabled by
+//! the ENABLE_DIAGNOSTICS vendor app config. The diagnostics of the sessions which did not enable
+//! them are dropped before being converted for Java.
+
+use crate::vendor_commands::GID_ANDROID;
+
+/// The ENABLE_DIAGNOSTICS vendor app config.
+const ENABLE_DIAGNOSTICS: u8 = 0xe8;
+/// The DIAGRAMS_FRAME_REPORTS_FIELDS vendor app config: the fields of the frame reports.
+const DIAGRAMS_FRAME_REPORTS_FIELDS: u8 = 0xe9;
+/// The ANDROID_RANGE_DIAGNOSTICS_NTF vendor notification.
+const OID_ANDROID_RANGE_DIAGNOSTICS: u8 = 0x02;
+
+/// The diagnostics reporting of a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DiagnosticsConfig {
+    pub enabled: bool,
+    /// The fields of the frame reports, ignored when the diagnostics are disabled.
+    pub frame_report_fields: u8,
+}
+
+impl DiagnosticsConfig {
+    /// The app configs of the configuration, as (ID, value).
+    pub fn app_configs(self) -> Vec<(u8, u8)> {
+        if self.enabled {
+            vec![(ENABLE_DIAGNOSTICS, 1), (DIAGRAMS_FRAME_REPORTS_FIELDS, self.frame_report_fields)]
+        } else {
+            vec![(ENABLE_DIAGNOSTICS, 0)]
+        }
+    }
+
+    /// Encodes the app configs as the TLVs passed to nativeSetAppConfigurations.
+    pub fn app_config_tlvs(self) -> Vec<u8> {
+        self.app_configs().into_iter().flat_map(|(cfg_id, value)| [cfg_id, 1, value]).collect()
+    }
+}
+
+/// Finds whether app config TLVs, as passed to nativeSetAppConfigurations, enable or disable the
+/// diagnostics. None if they do not set ENABLE_DIAGNOSTICS.
+pub(crate) fn find_diagnostics_enabled(mut tlvs: &[u8]) -> Option<bool> {
+    let mut enabled = None;
+    while let [cfg_id, len, rest @ ..] = tlvs {
+        let value = rest.get(..*len as usize)?;
+        if *cfg_id == ENABLE_DIAGNOSTICS {
+            enabled = Some(matches!(value.first(), Some(value) if *value != 0));
+        }
+        tlvs = &rest[*len as usize..];
+    }
+    enabled
+}
+
+/// Gets the session token of an ANDROID_RANGE_DIAGNOSTICS_NTF, None for the other vendor
+/// notifications.
+pub(crate) fn diagnostics_session_token(gid: u32, oid: u32, payload: &[u8]) -> Option<u32> {
+    if gid != GID_ANDROID as u32 || oid != OID_ANDROID_RANGE_DIAGNOSTICS as u32 {
+        return None;
+    }
+    Some(u32::from_le_bytes(payload.get(..4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_config_tlvs() {
+        let config = DiagnosticsConfig { enabled: true, frame_report_fields: 0x3 };
+        assert_eq!(config.app_config_tlvs(), vec![0xe8, 1, 1, 0xe9, 1, 0x3]);
+        let config = DiagnosticsConfig { enabled: false, frame_report_fields: 0x3 };
+        assert_eq!(config.app_config_tlvs(), vec![0xe8, 1, 0]);
+    }
+
+    #[test]
+    fn test_find_diagnostics_enabled() {
+        assert_eq!(find_diagnostics_enabled(&[]), None);
+        assert_eq!(find_diagnostics_enabled(&[0x0d, 1, 1]), None);
+        assert_eq!(find_diagnostics_enabled(&[0x0d, 2, 0xe8, 1, 0xe8, 1, 1]), Some(true));
+        assert_eq!(find_diagnostics_enabled(&[0xe8, 1, 0, 0xe9, 1, 3]), Some(false));
+        // Truncated TLVs.
+        assert_eq!(find_diagnostics_enabled(&[0xe8, 2, 1]), None);
+    }
+
+    #[test]
+    fn test_diagnostics_session_token() {
+        let payload = [0x78, 0x56, 0x34, 0x12, 0x01];
+        assert_eq!(diagnostics_session_token(0xc, 0x2, &payload), Some(0x1234_5678));
+        assert_eq!(diagnostics_session_token(0xc, 0x1, &payload), None);
+        assert_eq!(diagnostics_session_token(0xe, 0x2, &payload), None);
+        assert_eq!(diagnostics_session_token(0xc, 0x2, &payload[..3]), None);
+    }
+}
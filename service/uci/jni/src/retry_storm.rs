@@ -0,0 +1,122 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detection of the UCI_STATUS_COMMAND_RETRY storms, i.e. a UWBS which keeps asking for the
+//! retransmission of the commands of a GID/OID.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Number of retries within STORM_WINDOW which make a storm.
+pub(crate) const STORM_RETRY_THRESHOLD: usize = 5;
+pub(crate) const STORM_WINDOW: Duration = Duration::from_secs(1);
+/// How long the commands of a GID/OID are rejected by the host after a storm.
+pub(crate) const STORM_THROTTLE_DURATION: Duration = Duration::from_secs(2);
+
+/// A storm detected on the commands of a GID/OID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RetryStorm {
+    pub gid: u8,
+    pub oid: u8,
+    /// Number of retries within the window which triggered the detection.
+    pub retry_count: usize,
+}
+
+#[derive(Debug, Default)]
+struct CommandRetries {
+    /// When the retries within the window were seen, oldest first.
+    recent: VecDeque<Instant>,
+    throttled_until: Option<Instant>,
+}
+
+/// Tracks the retries of every GID/OID of a chip.
+#[derive(Debug, Default)]
+pub(crate) struct RetryStormDetector {
+    retries: HashMap<(u8, u8), CommandRetries>,
+    /// The storms not yet reported to Java.
+    unreported: Vec<RetryStorm>,
+}
+
+impl RetryStormDetector {
+    /// Records a retry of a command of `gid`/`oid` at `now`. Returns whether it starts a storm,
+    /// in which case the GID/OID is throttled.
+    pub fn on_retry(&mut self, gid: u8, oid: u8, now: Instant) -> bool {
+        let retries = self.retries.entry((gid, oid)).or_default();
+        while let Some(&oldest) = retries.recent.front() {
+            if now.saturating_duration_since(oldest) < STORM_WINDOW {
+                break;
+            }
+            retries.recent.pop_front();
+        }
+        retries.recent.push_back(now);
+        if retries.recent.len() < STORM_RETRY_THRESHOLD
+            || retries.throttled_until.is_some_and(|until| now < until)
+        {
+            return false;
+        }
+        retries.throttled_until = Some(now + STORM_THROTTLE_DURATION);
+        self.unreported.push(RetryStorm { gid, oid, retry_count: retries.recent.len() });
+        retries.recent.clear();
+        true
+    }
+
+    /// Returns whether the commands of `gid`/`oid` are throttled at `now`.
+    pub fn is_throttled(&self, gid: u8, oid: u8, now: Instant) -> bool {
+        self.retries
+            .get(&(gid, oid))
+            .and_then(|retries| retries.throttled_until)
+            .is_some_and(|until| now < until)
+    }
+
+    /// Takes the storms detected since the previous call.
+    pub fn take_unreported(&mut self) -> Vec<RetryStorm> {
+        std::mem::take(&mut self.unreported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storm_detection() {
+        let mut detector = RetryStormDetector::default();
+        let start = Instant::now();
+        for i in 0..(STORM_RETRY_THRESHOLD as u32 - 1) {
+            assert!(!detector.on_retry(0x1, 0x3, start + Duration::from_millis(100) * i));
+        }
+        assert!(!detector.is_throttled(0x1, 0x3, start));
+
+        let storm_start = start + Duration::from_millis(500);
+        assert!(detector.on_retry(0x1, 0x3, storm_start));
+        assert!(detector.is_throttled(0x1, 0x3, storm_start));
+        assert!(!detector.is_throttled(0x1, 0x4, storm_start));
+        assert!(!detector.is_throttled(0x1, 0x3, storm_start + STORM_THROTTLE_DURATION));
+        assert_eq!(
+            detector.take_unreported(),
+            vec![RetryStorm { gid: 0x1, oid: 0x3, retry_count: STORM_RETRY_THRESHOLD }]
+        );
+        assert!(detector.take_unreported().is_empty());
+    }
+
+    #[test]
+    fn test_sparse_retries_are_not_a_storm() {
+        let mut detector = RetryStormDetector::default();
+        let start = Instant::now();
+        for i in 0..(3 * STORM_RETRY_THRESHOLD as u32) {
+            assert!(!detector.on_retry(0x2, 0x0, start + STORM_WINDOW / 4 * i));
+        }
+        assert!(detector.take_unreported().is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Switch of the ranging role of a session, e.g. from initiator to responder, as a single
+//! operation. An active session is stopped, its DEVICE_TYPE and DEVICE_ROLE app configs are set,
+//! then it is started again. When a step fails, the previous role is restored, and the session
+//! restarted if it was active.
+
+use log::error;
+use uwb_core::error::{Error, Result};
+
+/// The DEVICE_TYPE app config.
+const DEVICE_TYPE: u8 = 0x00;
+/// The DEVICE_ROLE app config.
+const DEVICE_ROLE: u8 = 0x11;
+
+/// The DEVICE_ROLES capability.
+const SUPPORTED_DEVICE_ROLES: u8 = 0x02;
+// Bits of DEVICE_ROLES.
+const DEVICE_ROLE_RESPONDER_SUPPORTED: u8 = 0x1;
+const DEVICE_ROLE_INITIATOR_SUPPORTED: u8 = 0x2;
+
+/// The DEVICE_TYPE of a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DeviceType {
+    Controlee = 0,
+    Controller = 1,
+}
+
+/// The DEVICE_ROLE of a session, restricted to the roles of two-way ranging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DeviceRole {
+    Responder = 0,
+    Initiator = 1,
+}
+
+/// The ranging role of a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RangingRole {
+    pub device_type: DeviceType,
+    pub device_role: DeviceRole,
+}
+
+impl RangingRole {
+    /// Decodes the DEVICE_TYPE and DEVICE_ROLE values, as defined by the FiRa spec.
+    pub fn new(device_type: i32, device_role: i32) -> Result<Self> {
+        let device_type = match device_type {
+            0 => DeviceType::Controlee,
+            1 => DeviceType::Controller,
+            _ => return Err(Error::BadParameters),
+        };
+        let device_role = match device_role {
+            0 => DeviceRole::Responder,
+            1 => DeviceRole::Initiator,
+            _ => return Err(Error::BadParameters),
+        };
+        Ok(Self { device_type, device_role })
+    }
+
+    /// Decodes the role from the app configs read with SESSION_GET_APP_CONFIG, as (ID, value).
+    pub fn from_app_configs(app_configs: &[(u8, Vec<u8>)]) -> Result<Self> {
+        let app_config = |cfg_id| match app_configs.iter().find(|(t, _)| *t == cfg_id) {
+            Some((_, v)) if v.len() == 1 => Ok(v[0] as i32),
+            _ => {
+                error!("UCI JNI: invalid app config {:#x} in {:?}", cfg_id, app_configs);
+                Err(Error::BadParameters)
+            }
+        };
+        Self::new(app_config(DEVICE_TYPE)?, app_config(DEVICE_ROLE)?)
+    }
+
+    /// Checks that the chip supports the role, as reported by CORE_GET_CAPS_INFO, as (type,
+    /// value).
+    pub fn validate(self, caps: &[(u8, Vec<u8>)]) -> Result<()> {
+        let device_roles = match caps.iter().find(|(t, _)| *t == SUPPORTED_DEVICE_ROLES) {
+            Some((_, v)) => v.first().copied().unwrap_or(0),
+            None => 0,
+        };
+        let supported = match self.device_role {
+            DeviceRole::Responder => device_roles & DEVICE_ROLE_RESPONDER_SUPPORTED != 0,
+            DeviceRole::Initiator => device_roles & DEVICE_ROLE_INITIATOR_SUPPORTED != 0,
+        };
+        if !supported {
+            error!("UCI JNI: {:?} is not supported by device roles {:#x}", self, device_roles);
+            return Err(Error::BadParameters);
+        }
+        Ok(())
+    }
+
+    /// The app configs of the role, as (ID, value).
+    pub fn app_configs(self) -> Vec<(u8, u8)> {
+        vec![(DEVICE_TYPE, self.device_type as u8), (DEVICE_ROLE, self.device_role as u8)]
+    }
+
+    /// Encodes the app configs as the TLVs passed to nativeSetAppConfigurations.
+    pub fn app_config_tlvs(self) -> Vec<u8> {
+        self.app_configs().into_iter().flat_map(|(cfg_id, value)| [cfg_id, 1, value]).collect()
+    }
+
+    /// The IDs of the app configs of the role.
+    pub fn app_config_ids() -> [u8; 2] {
+        [DEVICE_TYPE, DEVICE_ROLE]
+    }
+}
+
+/// Switches the role of a session from `previous` to `target` with `set_role`, stopping it before
+/// with `stop` and starting it after with `start` when it is `active`. When setting the role or
+/// starting the session fails, `previous` is set again and the session restarted; the error of the
+/// switch is returned either way.
+pub(crate) fn switch_ranging_role<S, P, T>(
+    previous: RangingRole,
+    target: RangingRole,
+    active: bool,
+    mut set_role: S,
+    mut stop: P,
+    mut start: T,
+) -> Result<()>
+where
+    S: FnMut(RangingRole) -> Result<()>,
+    P: FnMut() -> Result<()>,
+    T: FnMut() -> Result<()>,
+{
+    if previous == target {
+        return Ok(());
+    }
+    if active {
+        // Nothing is changed yet when the session cannot be stopped.
+        stop()?;
+    }
+    let mut apply = |role| {
+        set_role(role)?;
+        if active {
+            start()?;
+        }
+        Ok(())
+    };
+    if let Err(e) = apply(target) {
+        error!("UCI JNI: failed to switch to {:?}, restoring {:?}: {:?}", target, previous, e);
+        if let Err(restore_error) = apply(previous) {
+            error!("UCI JNI: failed to restore {:?}: {:?}", previous, restore_error);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const INITIATOR: RangingRole =
+        RangingRole { device_type: DeviceType::Controller, device_role: DeviceRole::Initiator };
+    const RESPONDER: RangingRole =
+        RangingRole { device_type: DeviceType::Controlee, device_role: DeviceRole::Responder };
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Step {
+        Stop,
+        SetRole(RangingRole),
+        Start,
+    }
+
+    /// Runs the switch, failing the steps of `failures`, and returns its result and its steps.
+    fn run_switch(active: bool, failures: &[Step]) -> (Result<()>, Vec<Step>) {
+        let steps = RefCell::new(vec![]);
+        let run = |step| {
+            steps.borrow_mut().push(step);
+            if failures.contains(&step) {
+                Err(Error::Unknown)
+            } else {
+                Ok(())
+            }
+        };
+        let result = switch_ranging_role(
+            INITIATOR,
+            RESPONDER,
+            active,
+            |role| run(Step::SetRole(role)),
+            || run(Step::Stop),
+            || run(Step::Start),
+        );
+        (result, steps.into_inner())
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(RangingRole::new(1, 1).unwrap(), INITIATOR);
+        assert_eq!(RangingRole::new(0, 0).unwrap(), RESPONDER);
+        assert!(RangingRole::new(2, 0).is_err());
+        // The roles other than for two-way ranging, e.g. UT-Tag, are not switched.
+        assert!(RangingRole::new(0, 4).is_err());
+    }
+
+    #[test]
+    fn test_from_app_configs() {
+        let app_configs = vec![(DEVICE_ROLE, vec![1]), (DEVICE_TYPE, vec![1])];
+        assert_eq!(RangingRole::from_app_configs(&app_configs).unwrap(), INITIATOR);
+        assert!(RangingRole::from_app_configs(&[(DEVICE_TYPE, vec![1])]).is_err());
+        assert!(RangingRole::from_app_configs(&[(DEVICE_TYPE, vec![]), (DEVICE_ROLE, vec![1])])
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let responder_only = [(SUPPORTED_DEVICE_ROLES, vec![DEVICE_ROLE_RESPONDER_SUPPORTED])];
+        assert!(RESPONDER.validate(&responder_only).is_ok());
+        assert!(INITIATOR.validate(&responder_only).is_err());
+        assert!(INITIATOR.validate(&[(SUPPORTED_DEVICE_ROLES, vec![0x3])]).is_ok());
+        assert!(RESPONDER.validate(&[]).is_err());
+    }
+
+    #[test]
+    fn test_app_config_tlvs() {
+        assert_eq!(INITIATOR.app_config_tlvs(), vec![0x00, 1, 1, 0x11, 1, 1]);
+        assert_eq!(RESPONDER.app_config_tlvs(), vec![0x00, 1, 0, 0x11, 1, 0]);
+    }
+
+    #[test]
+    fn test_switch_active_session() {
+        let (result, steps) = run_switch(true, &[]);
+        assert!(result.is_ok());
+        assert_eq!(steps, vec![Step::Stop, Step::SetRole(RESPONDER), Step::Start]);
+    }
+
+    #[test]
+    fn test_switch_idle_session() {
+        let (result, steps) = run_switch(false, &[]);
+        assert!(result.is_ok());
+        assert_eq!(steps, vec![Step::SetRole(RESPONDER)]);
+    }
+
+    #[test]
+    fn test_same_role_is_not_switched() {
+        let mut called = false;
+        let result = switch_ranging_role(
+            INITIATOR,
+            INITIATOR,
+            true,
+            |_| {
+                called = true;
+                Ok(())
+            },
+            || Ok(()),
+            || Ok(()),
+        );
+        assert!(result.is_ok());
+        assert!(!called);
+    }
+
+    #[test]
+    fn test_stop_failure() {
+        let (result, steps) = run_switch(true, &[Step::Stop]);
+        assert!(result.is_err());
+        assert_eq!(steps, vec![Step::Stop]);
+    }
+
+    #[test]
+    fn test_set_role_failure_is_rolled_back() {
+        let (result, steps) = run_switch(true, &[Step::SetRole(RESPONDER)]);
+        assert!(result.is_err());
+        assert_eq!(
+            steps,
+            vec![Step::Stop, Step::SetRole(RESPONDER), Step::SetRole(INITIATOR), Step::Start]
+        );
+
+        let (result, steps) = run_switch(false, &[Step::SetRole(RESPONDER)]);
+        assert!(result.is_err());
+        assert_eq!(steps, vec![Step::SetRole(RESPONDER), Step::SetRole(INITIATOR)]);
+    }
+
+    #[test]
+    fn test_start_failure_is_rolled_back() {
+        // The session is restarted with its previous role, the first start failure is reported.
+        let (result, steps) = run_switch(true, &[Step::Start]);
+        assert!(result.is_err());
+        assert_eq!(
+            steps,
+            vec![
+                Step::Stop,
+                Step::SetRole(RESPONDER),
+                Step::Start,
+                Step::SetRole(INITIATOR),
+                Step::Start
+            ]
+        );
+    }
+}
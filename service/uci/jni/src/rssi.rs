@@ -0,0 +1,47 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion of the RSSI reported in the ranging notifications.
+
+/// RSSI of a ranging measurement.
+///
+/// The UWBS reports the absolute value of the RSSI in dBm, encoded in FP Q7.1 format (see FiRa
+/// UCI Generic Technical Specification v2.0.0). The raw value is kept alongside the decoded one,
+/// since the decoding loses nothing but Java only has signed integers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Rssi {
+    /// The value reported by the UWBS.
+    pub raw: u8,
+    /// The signed value in dBm.
+    pub dbm: f32,
+}
+
+impl From<u8> for Rssi {
+    fn from(raw: u8) -> Self {
+        Rssi { raw, dbm: -f32::from(raw) / 2.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rssi_from_q7_1() {
+        assert_eq!(Rssi::from(0), Rssi { raw: 0, dbm: 0.0 });
+        assert_eq!(Rssi::from(11), Rssi { raw: 11, dbm: -5.5 });
+        assert_eq!(Rssi::from(150), Rssi { raw: 150, dbm: -75.0 });
+        assert_eq!(Rssi::from(u8::MAX), Rssi { raw: u8::MAX, dbm: -127.5 });
+    }
+}
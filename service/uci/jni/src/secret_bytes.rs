@@ -0,0 +1,79 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Key material held by the native stack: the session keys, the sub-session keys and the STS IVs
+//! passed by Java, and the buffers carrying them.
+//!
+//! The bytes are zeroed as soon as they are dropped, and never printed. The copies made by
+//! uwb_core, which takes its parameters by value, are out of reach; the key material is kept out
+//! of the UCI capture by uci_log_redaction instead.
+
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Zeroes `bytes` in a way the compiler does not elide.
+pub(crate) fn zeroize(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        // Safety: `byte` is a valid and aligned reference.
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Secret bytes, zeroed when dropped and never printed.
+#[derive(Default)]
+pub(crate) struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    /// Takes the ownership of the secret bytes.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        zeroize(&mut self.0);
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretBytes({} bytes)", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroize() {
+        let mut bytes = vec![0xaa; 4];
+        zeroize(&mut bytes);
+        assert_eq!(bytes, vec![0; 4]);
+    }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let secret = SecretBytes::new(vec![0xaa, 0xbb]);
+        assert_eq!(format!("{:?}", secret), "SecretBytes(2 bytes)");
+        assert_eq!(format!("{:?}", Some(&secret)), "Some(SecretBytes(2 bytes))");
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Aggregation of the range data of several sessions of one chip, e.g. one per peer device, into a
+//! single stream delivered through one callback. The range data of the member sessions are held
+//! over the window of their group, then delivered in the order they were received, without the
+//! duplicates.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use uwb_core::uci::SessionRangeData;
+
+#[derive(Debug)]
+struct PendingRangeData {
+    received_at: Instant,
+    range_data: SessionRangeData,
+}
+
+#[derive(Debug)]
+struct AggregationGroup {
+    window: Duration,
+    /// The sequence number of the last range data held for each member session.
+    last_sequence_numbers: HashMap<u32, Option<u32>>,
+    /// The range data held over the window, in the order they were received.
+    pending: Vec<PendingRangeData>,
+}
+
+impl AggregationGroup {
+    /// Takes the range data held since `window` before `now`, all of them when `now` is None.
+    fn take_expired(&mut self, now: Option<Instant>) -> Vec<SessionRangeData> {
+        let count = match now {
+            Some(now) => self
+                .pending
+                .iter()
+                .take_while(|pending| {
+                    now.saturating_duration_since(pending.received_at) >= self.window
+                })
+                .count(),
+            None => self.pending.len(),
+        };
+        self.pending.drain(..count).map(|pending| pending.range_data).collect()
+    }
+}
+
+/// The aggregation groups of the sessions of one chip.
+#[derive(Debug, Default)]
+pub(crate) struct SessionAggregator {
+    groups: HashMap<u32, AggregationGroup>,
+    /// The group of each member session.
+    session_groups: HashMap<u32, u32>,
+}
+
+impl SessionAggregator {
+    /// Sets the member sessions of the group, whose range data are held over `window`. The group
+    /// is removed when it has no member. A session is a member of one group at most, it leaves its
+    /// former group. The range data held for the groups are dropped.
+    pub fn set_group(&mut self, group_id: u32, session_tokens: &[u32], window: Duration) {
+        self.groups.remove(&group_id);
+        self.session_groups.retain(|_, member_group_id| *member_group_id != group_id);
+        for session_token in session_tokens {
+            if let Some(former_group_id) = self.session_groups.remove(session_token) {
+                if let Some(former_group) = self.groups.get_mut(&former_group_id) {
+                    former_group.last_sequence_numbers.remove(session_token);
+                    former_group.pending.clear();
+                    if former_group.last_sequence_numbers.is_empty() {
+                        self.groups.remove(&former_group_id);
+                    }
+                }
+            }
+        }
+        if session_tokens.is_empty() {
+            return;
+        }
+        let last_sequence_numbers =
+            session_tokens.iter().map(|&session_token| (session_token, None)).collect();
+        self.groups
+            .insert(group_id, AggregationGroup { window, last_sequence_numbers, pending: vec![] });
+        self.session_groups
+            .extend(session_tokens.iter().map(|&session_token| (session_token, group_id)));
+    }
+
+    /// Gets the group the session is a member of.
+    pub fn group_of(&self, session_token: u32) -> Option<u32> {
+        self.session_groups.get(&session_token).copied()
+    }
+
+    /// Holds the range data of a member session received at `now`, and returns the range data of
+    /// its group to deliver now. A range data whose sequence number is not past the last one of
+    /// its session is a duplicate, and dropped.
+    pub fn on_range_data(
+        &mut self,
+        range_data: SessionRangeData,
+        now: Instant,
+    ) -> Vec<SessionRangeData> {
+        let group = match self.group_of(range_data.session_token) {
+            Some(group_id) => self.groups.get_mut(&group_id),
+            None => None,
+        };
+        let group = match group {
+            Some(group) => group,
+            None => return vec![range_data],
+        };
+        let last_sequence_number =
+            group.last_sequence_numbers.entry(range_data.session_token).or_default();
+        if let Some(last) = *last_sequence_number {
+            // Compared in the sequence space, which wraps.
+            if range_data.sequence_number.wrapping_sub(last) as i32 <= 0 {
+                return group.take_expired(Some(now));
+            }
+        }
+        *last_sequence_number = Some(range_data.sequence_number);
+        group.pending.push(PendingRangeData { received_at: now, range_data });
+        group.take_expired(Some(now))
+    }
+
+    /// Takes the range data held for the group of a session which stopped ranging, so that they
+    /// are delivered before its state change. The session leaves its group when it is
+    /// deinitialized.
+    pub fn on_session_stopped(
+        &mut self,
+        session_token: u32,
+        deinitialized: bool,
+    ) -> Vec<SessionRangeData> {
+        let group_id = match self.group_of(session_token) {
+            Some(group_id) => group_id,
+            None => return vec![],
+        };
+        let group = match self.groups.get_mut(&group_id) {
+            Some(group) => group,
+            None => return vec![],
+        };
+        let flushed = group.take_expired(None);
+        // The sequence numbers restart with the next ranging.
+        group.last_sequence_numbers.insert(session_token, None);
+        if deinitialized {
+            group.last_sequence_numbers.remove(&session_token);
+            self.session_groups.remove(&session_token);
+            if group.last_sequence_numbers.is_empty() {
+                self.groups.remove(&group_id);
+            }
+        }
+        flushed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uwb_core::uci::RangingMeasurements;
+    use uwb_uci_packets::RangingMeasurementType;
+
+    fn range_data(session_token: u32, sequence_number: u32) -> SessionRangeData {
+        SessionRangeData {
+            sequence_number,
+            session_token,
+            current_ranging_interval_ms: 100,
+            ranging_measurement_type: RangingMeasurementType::TwoWay,
+            ranging_measurements: RangingMeasurements::ShortAddressTwoWay(vec![]),
+            rcr_indicator: 0,
+            raw_ranging_data: vec![],
+        }
+    }
+
+    fn sources(range_data: &[SessionRangeData]) -> Vec<(u32, u32)> {
+        range_data
+            .iter()
+            .map(|range_data| (range_data.session_token, range_data.sequence_number))
+            .collect()
+    }
+
+    #[test]
+    fn test_merged_in_reception_order() {
+        let mut aggregator = SessionAggregator::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        aggregator.set_group(7, &[1, 2], Duration::from_millis(50));
+        assert_eq!(aggregator.group_of(2), Some(7));
+        assert_eq!(aggregator.group_of(3), None);
+
+        assert!(aggregator.on_range_data(range_data(1, 0), at(0)).is_empty());
+        assert!(aggregator.on_range_data(range_data(2, 0), at(10)).is_empty());
+        // The sessions out of the group are not held.
+        assert_eq!(sources(&aggregator.on_range_data(range_data(3, 0), at(20))), vec![(3, 0)]);
+        assert!(aggregator.on_range_data(range_data(1, 1), at(40)).is_empty());
+        assert_eq!(
+            sources(&aggregator.on_range_data(range_data(2, 1), at(60))),
+            vec![(1, 0), (2, 0)]
+        );
+        assert_eq!(sources(&aggregator.on_session_stopped(1, false)), vec![(1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn test_duplicates_dropped() {
+        let mut aggregator = SessionAggregator::default();
+        let now = Instant::now();
+        aggregator.set_group(7, &[1, 2], Duration::ZERO);
+
+        assert_eq!(sources(&aggregator.on_range_data(range_data(1, 4), now)), vec![(1, 4)]);
+        assert!(aggregator.on_range_data(range_data(1, 4), now).is_empty());
+        assert!(aggregator.on_range_data(range_data(1, 3), now).is_empty());
+        // Only the sequence numbers of the same session are compared.
+        assert_eq!(sources(&aggregator.on_range_data(range_data(2, 4), now)), vec![(2, 4)]);
+        // The sequence number wraps.
+        assert!(aggregator.on_range_data(range_data(1, u32::MAX), now).is_empty());
+        aggregator.on_session_stopped(1, false);
+        assert_eq!(sources(&aggregator.on_range_data(range_data(1, 0), now)), vec![(1, 0)]);
+    }
+
+    #[test]
+    fn test_set_group() {
+        let mut aggregator = SessionAggregator::default();
+        let now = Instant::now();
+        aggregator.set_group(7, &[1, 2], Duration::from_secs(1));
+        assert!(aggregator.on_range_data(range_data(1, 0), now).is_empty());
+
+        // The session moves to another group, the range data held for its former group are
+        // dropped.
+        aggregator.set_group(8, &[1], Duration::ZERO);
+        assert!(aggregator.on_session_stopped(2, false).is_empty());
+        assert_eq!(aggregator.group_of(1), Some(8));
+        assert_eq!(aggregator.group_of(2), Some(7));
+
+        // A group without member is removed.
+        aggregator.set_group(7, &[], Duration::ZERO);
+        assert_eq!(aggregator.group_of(2), None);
+        assert!(aggregator.on_session_stopped(1, true).is_empty());
+        assert_eq!(aggregator.group_of(1), None);
+        assert!(aggregator.groups.is_empty());
+    }
+}
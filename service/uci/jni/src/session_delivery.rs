@@ -0,0 +1,195 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-session delivery preferences of the range data, which let the sessions of one chip trade
+//! latency for fewer callbacks into Java.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use uwb_core::uci::SessionRangeData;
+
+/// How the range data of a session are delivered to Java.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DeliveryPreference {
+    /// Delivers every range data as soon as it is received, the default.
+    Immediate,
+    /// Holds the range data until N of them are received, then delivers them together.
+    Batched(u16),
+    /// Delivers at most one range data per interval, the others are dropped.
+    MaxRate(Duration),
+}
+
+#[derive(Debug)]
+struct SessionDelivery {
+    preference: DeliveryPreference,
+    /// The range data held until the batch is complete.
+    pending: Vec<SessionRangeData>,
+    last_delivered_at: Option<Instant>,
+}
+
+/// The delivery preferences of the sessions of one chip.
+#[derive(Debug, Default)]
+pub(crate) struct SessionDeliveryScheduler {
+    sessions: HashMap<u32, SessionDelivery>,
+}
+
+impl SessionDeliveryScheduler {
+    /// Sets the delivery preference of the session. The range data held for the session are
+    /// dropped.
+    pub fn set_session(&mut self, session_token: u32, preference: DeliveryPreference) {
+        match preference {
+            DeliveryPreference::Immediate
+            | DeliveryPreference::Batched(0 | 1)
+            | DeliveryPreference::MaxRate(Duration::ZERO) => {
+                self.sessions.remove(&session_token);
+            }
+            preference => {
+                self.sessions.insert(
+                    session_token,
+                    SessionDelivery { preference, pending: Vec::new(), last_delivered_at: None },
+                );
+            }
+        }
+    }
+
+    /// Gets the delivery preference of the session.
+    pub fn preference(&self, session_token: u32) -> DeliveryPreference {
+        self.sessions
+            .get(&session_token)
+            .map_or(DeliveryPreference::Immediate, |session| session.preference)
+    }
+
+    /// Schedules the range data received at `now`, and returns the range data of the session to
+    /// deliver now, in the order they were received.
+    pub fn on_range_data(
+        &mut self,
+        range_data: SessionRangeData,
+        now: Instant,
+    ) -> Vec<SessionRangeData> {
+        let session = match self.sessions.get_mut(&range_data.session_token) {
+            Some(session) => session,
+            None => return vec![range_data],
+        };
+        match session.preference {
+            DeliveryPreference::Immediate => vec![range_data],
+            DeliveryPreference::Batched(count) => {
+                session.pending.push(range_data);
+                if session.pending.len() < count as usize {
+                    return vec![];
+                }
+                std::mem::take(&mut session.pending)
+            }
+            DeliveryPreference::MaxRate(interval) => {
+                if session
+                    .last_delivered_at
+                    .is_some_and(|last| now.saturating_duration_since(last) < interval)
+                {
+                    return vec![];
+                }
+                session.last_delivered_at = Some(now);
+                vec![range_data]
+            }
+        }
+    }
+
+    /// Takes the range data held for a session which stopped ranging, so that they are delivered
+    /// before its state change. The preference of the session is forgotten when it is
+    /// deinitialized.
+    pub fn on_session_stopped(
+        &mut self,
+        session_token: u32,
+        deinitialized: bool,
+    ) -> Vec<SessionRangeData> {
+        if deinitialized {
+            return self.sessions.remove(&session_token).map_or(vec![], |session| session.pending);
+        }
+        self.sessions
+            .get_mut(&session_token)
+            .map_or(vec![], |session| std::mem::take(&mut session.pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uwb_core::uci::RangingMeasurements;
+    use uwb_uci_packets::RangingMeasurementType;
+
+    fn range_data(session_token: u32, sequence_number: u32) -> SessionRangeData {
+        SessionRangeData {
+            sequence_number,
+            session_token,
+            current_ranging_interval_ms: 100,
+            ranging_measurement_type: RangingMeasurementType::TwoWay,
+            ranging_measurements: RangingMeasurements::ShortAddressTwoWay(vec![]),
+            rcr_indicator: 0,
+            raw_ranging_data: vec![],
+        }
+    }
+
+    fn sequence_numbers(range_data: &[SessionRangeData]) -> Vec<u32> {
+        range_data.iter().map(|range_data| range_data.sequence_number).collect()
+    }
+
+    #[test]
+    fn test_immediate() {
+        let mut scheduler = SessionDeliveryScheduler::default();
+        let now = Instant::now();
+        assert_eq!(scheduler.preference(1), DeliveryPreference::Immediate);
+        assert_eq!(sequence_numbers(&scheduler.on_range_data(range_data(1, 0), now)), vec![0]);
+
+        // A batch of one range data is an immediate delivery.
+        scheduler.set_session(1, DeliveryPreference::Batched(1));
+        assert_eq!(scheduler.preference(1), DeliveryPreference::Immediate);
+    }
+
+    #[test]
+    fn test_batched() {
+        let mut scheduler = SessionDeliveryScheduler::default();
+        let now = Instant::now();
+        scheduler.set_session(1, DeliveryPreference::Batched(3));
+
+        assert!(scheduler.on_range_data(range_data(1, 0), now).is_empty());
+        assert!(scheduler.on_range_data(range_data(1, 1), now).is_empty());
+        // The other sessions are not held.
+        assert_eq!(sequence_numbers(&scheduler.on_range_data(range_data(2, 7), now)), vec![7]);
+        assert_eq!(
+            sequence_numbers(&scheduler.on_range_data(range_data(1, 2), now)),
+            vec![0, 1, 2]
+        );
+
+        // The incomplete batch is delivered when the session stops.
+        assert!(scheduler.on_range_data(range_data(1, 3), now).is_empty());
+        assert_eq!(sequence_numbers(&scheduler.on_session_stopped(1, false)), vec![3]);
+        assert_eq!(scheduler.preference(1), DeliveryPreference::Batched(3));
+        assert!(scheduler.on_session_stopped(1, true).is_empty());
+        assert_eq!(scheduler.preference(1), DeliveryPreference::Immediate);
+    }
+
+    #[test]
+    fn test_max_rate() {
+        let mut scheduler = SessionDeliveryScheduler::default();
+        let start = Instant::now();
+        scheduler.set_session(1, DeliveryPreference::MaxRate(Duration::from_millis(200)));
+
+        let delivered = (0..6)
+            .flat_map(|i| {
+                scheduler
+                    .on_range_data(range_data(1, i), start + Duration::from_millis(100 * i as u64))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sequence_numbers(&delivered), vec![0, 2, 4]);
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Key-value extras of the sessions, passed by Java as a PersistableBundle and validated against
+//! the schema below, so that an experimental parameter is piped through by adding an entry to the
+//! schema rather than a JNI method.
+//!
+//! Java encodes a boolean as 1 byte, and an int as 4 bytes and a long as 8 bytes in little
+//! endian.
+
+use log::error;
+use uwb_core::error::{Error, Result};
+
+/// The type of the value of an extra.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExtraType {
+    Bool,
+    U8,
+    U16,
+}
+
+/// Where the value of an extra is applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExtraTarget {
+    /// An app config of the session, sent with SESSION_SET_APP_CONFIG.
+    AppConfig(u8),
+    /// The distance step of the coarse location privacy of the session, 0 disabling it.
+    CoarseLocationStep,
+}
+
+struct ExtraSchema {
+    key: &'static str,
+    value_type: ExtraType,
+    target: ExtraTarget,
+}
+
+/// The extras accepted from Java.
+const EXTRAS_SCHEMA: &[ExtraSchema] = &[
+    ExtraSchema {
+        key: "vendor.nb_of_range_measurements",
+        value_type: ExtraType::U8,
+        target: ExtraTarget::AppConfig(0xe3),
+    },
+    ExtraSchema {
+        key: "vendor.nb_of_azimuth_measurements",
+        value_type: ExtraType::U8,
+        target: ExtraTarget::AppConfig(0xe4),
+    },
+    ExtraSchema {
+        key: "vendor.nb_of_elevation_measurements",
+        value_type: ExtraType::U8,
+        target: ExtraTarget::AppConfig(0xe5),
+    },
+    ExtraSchema {
+        key: "vendor.enable_diagnostics",
+        value_type: ExtraType::Bool,
+        target: ExtraTarget::AppConfig(0xe8),
+    },
+    ExtraSchema {
+        key: "vendor.diagrams_frame_reports_fields",
+        value_type: ExtraType::U8,
+        target: ExtraTarget::AppConfig(0xe9),
+    },
+    ExtraSchema {
+        key: "host.coarse_location_step_cm",
+        value_type: ExtraType::U16,
+        target: ExtraTarget::CoarseLocationStep,
+    },
+];
+
+/// The extras of a session, sorted by where they are applied.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct SessionExtras {
+    /// The app configs to set, as (ID, value).
+    pub app_configs: Vec<(u8, Vec<u8>)>,
+    /// The coarse location privacy to set, if any: the distance step, None disabling it.
+    pub coarse_location: Option<Option<u16>>,
+}
+
+impl SessionExtras {
+    /// Encodes the app configs as the TLVs passed to nativeSetAppConfigurations.
+    pub fn app_config_tlvs(&self) -> Vec<u8> {
+        let mut tlvs = vec![];
+        for (cfg_id, value) in self.app_configs.iter() {
+            tlvs.push(*cfg_id);
+            tlvs.push(value.len() as u8);
+            tlvs.extend_from_slice(value);
+        }
+        tlvs
+    }
+}
+
+/// Decodes an int or a long encoded by Java, rejecting the values above `max`.
+fn decode_unsigned(value: &[u8], max: u64) -> Option<u64> {
+    let value = match value.len() {
+        4 => u32::from_le_bytes(value.try_into().ok()?) as u64,
+        8 => u64::from_le_bytes(value.try_into().ok()?),
+        _ => return None,
+    };
+    (value <= max).then_some(value)
+}
+
+/// Decodes a value as the little-endian bytes of the type, None if it does not match it.
+fn decode_value(value_type: ExtraType, value: &[u8]) -> Option<Vec<u8>> {
+    match value_type {
+        ExtraType::Bool => matches!(value, [0] | [1]).then(|| value.to_vec()),
+        ExtraType::U8 => decode_unsigned(value, u8::MAX.into()).map(|v| vec![v as u8]),
+        ExtraType::U16 => {
+            decode_unsigned(value, u16::MAX.into()).map(|v| (v as u16).to_le_bytes().to_vec())
+        }
+    }
+}
+
+/// Validates the extras against the schema. They are rejected all together if any of them is
+/// unknown, repeated or of another type than its schema.
+pub(crate) fn parse_session_extras(extras: &[(String, Vec<u8>)]) -> Result<SessionExtras> {
+    let mut session_extras = SessionExtras::default();
+    for (i, (key, value)) in extras.iter().enumerate() {
+        if extras[..i].iter().any(|(previous_key, _)| previous_key == key) {
+            error!("UCI JNI: session extra {} is repeated", key);
+            return Err(Error::BadParameters);
+        }
+        let schema = EXTRAS_SCHEMA.iter().find(|schema| schema.key == key).ok_or_else(|| {
+            error!("UCI JNI: unknown session extra {}", key);
+            Error::BadParameters
+        })?;
+        let value = decode_value(schema.value_type, value).ok_or_else(|| {
+            error!("UCI JNI: invalid value {:?} of session extra {}", value, key);
+            Error::BadParameters
+        })?;
+        match schema.target {
+            ExtraTarget::AppConfig(cfg_id) => session_extras.app_configs.push((cfg_id, value)),
+            ExtraTarget::CoarseLocationStep => {
+                let step_cm = u16::from_le_bytes([value[0], value[1]]);
+                session_extras.coarse_location = Some((step_cm != 0).then_some(step_cm));
+            }
+        }
+    }
+    Ok(session_extras)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extra(key: &str, value: &[u8]) -> (String, Vec<u8>) {
+        (key.to_owned(), value.to_vec())
+    }
+
+    #[test]
+    fn test_schema_keys_are_unique() {
+        for (i, schema) in EXTRAS_SCHEMA.iter().enumerate() {
+            assert!(EXTRAS_SCHEMA[..i].iter().all(|previous| previous.key != schema.key));
+        }
+    }
+
+    #[test]
+    fn test_decode_value() {
+        assert_eq!(decode_value(ExtraType::Bool, &[1]), Some(vec![1]));
+        assert_eq!(decode_value(ExtraType::Bool, &[2]), None);
+        assert_eq!(decode_value(ExtraType::U8, &200i32.to_le_bytes()), Some(vec![200]));
+        assert_eq!(decode_value(ExtraType::U8, &256i32.to_le_bytes()), None);
+        // Negative ints are out of range.
+        assert_eq!(decode_value(ExtraType::U16, &(-1i32).to_le_bytes()), None);
+        assert_eq!(decode_value(ExtraType::U16, &300i64.to_le_bytes()), Some(vec![0x2c, 0x01]));
+        assert_eq!(decode_value(ExtraType::U16, &0x1_0000i64.to_le_bytes()), None);
+        assert_eq!(decode_value(ExtraType::U8, &[1, 2]), None);
+    }
+
+    #[test]
+    fn test_parse_session_extras() {
+        let extras = parse_session_extras(&[
+            extra("vendor.nb_of_range_measurements", &5i32.to_le_bytes()),
+            extra("vendor.enable_diagnostics", &[1]),
+            extra("host.coarse_location_step_cm", &100i32.to_le_bytes()),
+        ])
+        .unwrap();
+        assert_eq!(extras.app_configs, vec![(0xe3, vec![5]), (0xe8, vec![1])]);
+        assert_eq!(extras.coarse_location, Some(Some(100)));
+        assert_eq!(extras.app_config_tlvs(), vec![0xe3, 1, 5, 0xe8, 1, 1]);
+
+        let extras =
+            parse_session_extras(&[extra("host.coarse_location_step_cm", &0i32.to_le_bytes())])
+                .unwrap();
+        assert_eq!(extras.coarse_location, Some(None));
+        assert_eq!(parse_session_extras(&[]).unwrap(), SessionExtras::default());
+    }
+
+    #[test]
+    fn test_invalid_session_extras() {
+        assert!(parse_session_extras(&[extra("vendor.unknown", &[1])]).is_err());
+        assert!(parse_session_extras(&[extra("vendor.enable_diagnostics", &[1, 0])]).is_err());
+        assert!(parse_session_extras(&[
+            extra("vendor.enable_diagnostics", &[1]),
+            extra("vendor.enable_diagnostics", &[0]),
+        ])
+        .is_err());
+    }
+}
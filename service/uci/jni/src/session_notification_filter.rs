@@ -0,0 +1,374 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-session filtering of the session notifications before they cross JNI.
+
+use std::collections::{HashMap, HashSet};
+
+use uwb_core::uci::{RangingMeasurements, SessionRangeData};
+
+/// The sessions of one chip whose SESSION_INFO_NTFs are dropped instead of being passed to Java,
+/// e.g. while the app owning the session is in the background, and those whose range data are
+/// coarsened before, as the app owning the session may not locate its peers precisely. The
+/// departure of the controlees can also be simulated, to test the handling of the peer loss. The
+/// ranging diagnostics are only passed for the sessions which enabled them, and so is the raw
+/// RANGE_DATA_NTF, unless it is included for all the sessions to debug them.
+///
+/// The sessions are identified by their session ID, which uwb_core tags the range data with in
+/// place of the session token of the UWBS. The two differ from UCI 2.0 on.
+#[derive(Debug, Default)]
+pub(crate) struct SessionNotificationFilter {
+    suppressed_session_info: HashSet<u32>,
+    /// The distance step of the sessions under the coarse location privacy, in centimeters.
+    coarse_location: HashMap<u32, u16>,
+    /// The MAC addresses of the controlees whose departure is simulated, by session, in little
+    /// endian as passed to Java.
+    departed_controlees: HashMap<u32, HashSet<Vec<u8>>>,
+    /// The sessions which enabled the ranging diagnostics.
+    diagnostics_enabled: HashSet<u32>,
+    /// The session IDs of the session tokens, as carried by the SESSION_STATUS_NTFs, for the
+    /// vendor notifications which are tagged with the session token.
+    session_ids: HashMap<u32, u32>,
+    /// The sessions whose range data are passed with the raw RANGE_DATA_NTF.
+    raw_ranging_data_included: HashSet<u32>,
+    /// The raw RANGE_DATA_NTF is passed for all the sessions, to debug them.
+    raw_ranging_data_included_for_all: bool,
+}
+
+impl SessionNotificationFilter {
+    /// Sets whether the SESSION_INFO_NTFs of the session are suppressed.
+    pub fn set_session_info_suppressed(&mut self, session_id: u32, suppressed: bool) {
+        if suppressed {
+            self.suppressed_session_info.insert(session_id);
+        } else {
+            self.suppressed_session_info.remove(&session_id);
+        }
+    }
+
+    /// Returns whether the SESSION_INFO_NTFs of the session are suppressed.
+    pub fn is_session_info_suppressed(&self, session_id: u32) -> bool {
+        self.suppressed_session_info.contains(&session_id)
+    }
+
+    /// Sets the distance step the range data of the session are quantized to, None to deliver
+    /// them with their full precision.
+    pub fn set_coarse_location(&mut self, session_id: u32, distance_step_cm: Option<u16>) {
+        match distance_step_cm {
+            Some(distance_step_cm) => self.coarse_location.insert(session_id, distance_step_cm),
+            None => self.coarse_location.remove(&session_id),
+        };
+    }
+
+    /// Returns the distance step the range data of the session are quantized to, if the session
+    /// is under the coarse location privacy.
+    pub fn coarse_location(&self, session_id: u32) -> Option<u16> {
+        self.coarse_location.get(&session_id).copied()
+    }
+
+    /// Sets whether the departure of the controlee `mac_address` of the session is simulated:
+    /// its measurements are omitted from the range data of the session until it returns.
+    pub fn set_controlee_departed(&mut self, session_id: u32, mac_address: &[u8], departed: bool) {
+        if departed {
+            self.departed_controlees.entry(session_id).or_default().insert(mac_address.to_vec());
+        } else if let Some(departed_controlees) = self.departed_controlees.get_mut(&session_id) {
+            departed_controlees.remove(mac_address);
+            if departed_controlees.is_empty() {
+                self.departed_controlees.remove(&session_id);
+            }
+        }
+    }
+
+    /// Ends the simulated departures of the controlees of the session.
+    pub fn clear_departed_controlees(&mut self, session_id: u32) {
+        self.departed_controlees.remove(&session_id);
+    }
+
+    /// Records the session ID of the session token, or forgets the session token when `session_id`
+    /// is None, e.g. once the session is deinitialized.
+    pub fn set_session_id(&mut self, session_token: u32, session_id: Option<u32>) {
+        match session_id {
+            Some(session_id) => self.session_ids.insert(session_token, session_id),
+            None => self.session_ids.remove(&session_token),
+        };
+    }
+
+    /// Sets whether the ranging diagnostics of the session are passed to Java.
+    pub fn set_diagnostics_enabled(&mut self, session_id: u32, enabled: bool) {
+        if enabled {
+            self.diagnostics_enabled.insert(session_id);
+        } else {
+            self.diagnostics_enabled.remove(&session_id);
+        }
+    }
+
+    /// Returns whether the ranging diagnostics of the session are passed to Java.
+    pub fn is_diagnostics_enabled(&self, session_id: u32) -> bool {
+        self.diagnostics_enabled.contains(&session_id)
+    }
+
+    /// Returns whether the ranging diagnostics tagged with the session token are passed to Java.
+    /// They are not for the session tokens whose session is unknown.
+    pub fn is_diagnostics_enabled_for_token(&self, session_token: u32) -> bool {
+        self.session_ids
+            .get(&session_token)
+            .is_some_and(|session_id| self.is_diagnostics_enabled(*session_id))
+    }
+
+    /// Sets whether the range data of the session are passed with the raw RANGE_DATA_NTF.
+    pub fn set_raw_ranging_data_included(&mut self, session_id: u32, included: bool) {
+        if included {
+            self.raw_ranging_data_included.insert(session_id);
+        } else {
+            self.raw_ranging_data_included.remove(&session_id);
+        }
+    }
+
+    /// Sets whether the range data of all the sessions are passed with the raw RANGE_DATA_NTF,
+    /// whatever their own setting.
+    pub fn set_raw_ranging_data_included_for_all(&mut self, included: bool) {
+        self.raw_ranging_data_included_for_all = included;
+    }
+
+    /// Returns whether the range data of the session are passed with the raw RANGE_DATA_NTF.
+    pub fn is_raw_ranging_data_included(&self, session_id: u32) -> bool {
+        self.raw_ranging_data_included_for_all
+            || self.raw_ranging_data_included.contains(&session_id)
+    }
+
+    /// Omits the measurements of the departed controlees from range data. The raw notification
+    /// is left as received.
+    pub fn omit_departed_controlees(&self, range_data: &mut SessionRangeData) {
+        // session_token below has already been mapped to session_id by uci layer.
+        let departed_controlees = match self.departed_controlees.get(&range_data.session_token) {
+            Some(departed_controlees) => departed_controlees,
+            None => return,
+        };
+        macro_rules! omit_departed {
+            ($measurements:expr) => {
+                $measurements.retain(|measurement| {
+                    !departed_controlees.contains(&measurement.mac_address.to_le_bytes()[..])
+                })
+            };
+        }
+        match &mut range_data.ranging_measurements {
+            RangingMeasurements::ShortAddressTwoWay(measurements) => omit_departed!(measurements),
+            RangingMeasurements::ExtendedAddressTwoWay(measurements) => {
+                omit_departed!(measurements)
+            }
+            RangingMeasurements::ShortAddressDltdoa(measurements) => omit_departed!(measurements),
+            RangingMeasurements::ExtendedAddressDltdoa(measurements) => {
+                omit_departed!(measurements)
+            }
+            RangingMeasurements::ShortAddressOwrAoa(measurements) => omit_departed!(measurements),
+            RangingMeasurements::ExtendedAddressOwrAoa(measurements) => {
+                omit_departed!(measurements)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uwb_uci_packets::{
+        RangingMeasurementType, ShortAddressTwoWayRangingMeasurement, StatusCode,
+    };
+
+    #[test]
+    fn test_session_info_suppression() {
+        let mut filter = SessionNotificationFilter::default();
+        assert!(!filter.is_session_info_suppressed(1));
+
+        filter.set_session_info_suppressed(1, true);
+        assert!(filter.is_session_info_suppressed(1));
+        assert!(!filter.is_session_info_suppressed(2));
+
+        filter.set_session_info_suppressed(1, false);
+        assert!(!filter.is_session_info_suppressed(1));
+    }
+
+    #[test]
+    fn test_coarse_location() {
+        let mut filter = SessionNotificationFilter::default();
+        assert_eq!(filter.coarse_location(1), None);
+
+        filter.set_coarse_location(1, Some(50));
+        assert_eq!(filter.coarse_location(1), Some(50));
+        assert_eq!(filter.coarse_location(2), None);
+
+        filter.set_coarse_location(1, None);
+        assert_eq!(filter.coarse_location(1), None);
+    }
+
+    #[test]
+    fn test_coarse_location_of_session_with_distinct_token() {
+        // From UCI 2.0 on, the UWBS hands out a session token distinct from the session ID, and
+        // uwb_core tags the range data with the session ID instead.
+        const SESSION_ID: u32 = 1;
+        const SESSION_TOKEN: u32 = 0x8000_0001;
+        let mut filter = SessionNotificationFilter::default();
+        filter.set_coarse_location(SESSION_ID, Some(50));
+
+        let range_data = two_way_range_data(SESSION_ID, &[0x1234]);
+        assert_eq!(filter.coarse_location(range_data.session_token), Some(50));
+        assert_eq!(filter.coarse_location(SESSION_TOKEN), None);
+    }
+
+    fn two_way_range_data(session_token: u32, mac_addresses: &[u16]) -> SessionRangeData {
+        let measurements = mac_addresses
+            .iter()
+            .map(|mac_address| ShortAddressTwoWayRangingMeasurement {
+                mac_address: *mac_address,
+                status: StatusCode::UciStatusOk,
+                nlos: 0,
+                distance: 100,
+                aoa_azimuth: 0,
+                aoa_azimuth_fom: 0,
+                aoa_elevation: 0,
+                aoa_elevation_fom: 0,
+                aoa_destination_azimuth: 0,
+                aoa_destination_azimuth_fom: 0,
+                aoa_destination_elevation: 0,
+                aoa_destination_elevation_fom: 0,
+                slot_index: 0,
+                rssi: 0,
+            })
+            .collect();
+        SessionRangeData {
+            sequence_number: 1,
+            session_token,
+            current_ranging_interval_ms: 100,
+            ranging_measurement_type: RangingMeasurementType::TwoWay,
+            ranging_measurements: RangingMeasurements::ShortAddressTwoWay(measurements),
+            rcr_indicator: 0,
+            raw_ranging_data: vec![],
+        }
+    }
+
+    fn mac_addresses(range_data: &SessionRangeData) -> Vec<u16> {
+        match &range_data.ranging_measurements {
+            RangingMeasurements::ShortAddressTwoWay(measurements) => {
+                measurements.iter().map(|measurement| measurement.mac_address).collect()
+            }
+            _ => panic!("unexpected measurements"),
+        }
+    }
+
+    #[test]
+    fn test_departed_controlees() {
+        let mut filter = SessionNotificationFilter::default();
+        filter.set_controlee_departed(1, &0x1234u16.to_le_bytes(), true);
+
+        let mut range_data = two_way_range_data(1, &[0x1234, 0x5678]);
+        filter.omit_departed_controlees(&mut range_data);
+        assert_eq!(mac_addresses(&range_data), vec![0x5678]);
+        // The other sessions are not affected.
+        let mut range_data = two_way_range_data(2, &[0x1234, 0x5678]);
+        filter.omit_departed_controlees(&mut range_data);
+        assert_eq!(mac_addresses(&range_data), vec![0x1234, 0x5678]);
+
+        // The controlee returns.
+        filter.set_controlee_departed(1, &0x1234u16.to_le_bytes(), false);
+        let mut range_data = two_way_range_data(1, &[0x1234, 0x5678]);
+        filter.omit_departed_controlees(&mut range_data);
+        assert_eq!(mac_addresses(&range_data), vec![0x1234, 0x5678]);
+    }
+
+    #[test]
+    fn test_departed_controlees_of_session_with_distinct_token() {
+        const SESSION_ID: u32 = 1;
+        const SESSION_TOKEN: u32 = 0x8000_0001;
+        let mut filter = SessionNotificationFilter::default();
+        filter.set_controlee_departed(SESSION_ID, &0x1234u16.to_le_bytes(), true);
+
+        // The range data are tagged with the session ID by uwb_core.
+        let mut range_data = two_way_range_data(SESSION_ID, &[0x1234, 0x5678]);
+        filter.omit_departed_controlees(&mut range_data);
+        assert_eq!(mac_addresses(&range_data), vec![0x5678]);
+
+        filter.clear_departed_controlees(SESSION_TOKEN);
+        let mut range_data = two_way_range_data(SESSION_ID, &[0x1234, 0x5678]);
+        filter.omit_departed_controlees(&mut range_data);
+        assert_eq!(mac_addresses(&range_data), vec![0x5678]);
+    }
+
+    #[test]
+    fn test_clear_departed_controlees() {
+        let mut filter = SessionNotificationFilter::default();
+        filter.set_controlee_departed(1, &0x1234u16.to_le_bytes(), true);
+        filter.set_controlee_departed(1, &0x5678u16.to_le_bytes(), true);
+        filter.clear_departed_controlees(1);
+
+        let mut range_data = two_way_range_data(1, &[0x1234, 0x5678]);
+        filter.omit_departed_controlees(&mut range_data);
+        assert_eq!(mac_addresses(&range_data), vec![0x1234, 0x5678]);
+    }
+
+    #[test]
+    fn test_diagnostics_enabled() {
+        let mut filter = SessionNotificationFilter::default();
+        assert!(!filter.is_diagnostics_enabled(1));
+        filter.set_diagnostics_enabled(1, true);
+        assert!(filter.is_diagnostics_enabled(1));
+        assert!(!filter.is_diagnostics_enabled(2));
+        filter.set_diagnostics_enabled(1, false);
+        assert!(!filter.is_diagnostics_enabled(1));
+    }
+
+    #[test]
+    fn test_diagnostics_enabled_for_token() {
+        const SESSION_ID: u32 = 1;
+        const SESSION_TOKEN: u32 = 0x8000_0001;
+        let mut filter = SessionNotificationFilter::default();
+        filter.set_diagnostics_enabled(SESSION_ID, true);
+        // The session of the token is not known yet.
+        assert!(!filter.is_diagnostics_enabled_for_token(SESSION_TOKEN));
+
+        filter.set_session_id(SESSION_TOKEN, Some(SESSION_ID));
+        assert!(filter.is_diagnostics_enabled_for_token(SESSION_TOKEN));
+        assert!(!filter.is_diagnostics_enabled_for_token(SESSION_ID));
+
+        filter.set_session_id(SESSION_TOKEN, None);
+        assert!(!filter.is_diagnostics_enabled_for_token(SESSION_TOKEN));
+    }
+
+    #[test]
+    fn test_raw_ranging_data_included() {
+        let mut filter = SessionNotificationFilter::default();
+        assert!(!filter.is_raw_ranging_data_included(1));
+        filter.set_raw_ranging_data_included(1, true);
+        assert!(filter.is_raw_ranging_data_included(1));
+        assert!(!filter.is_raw_ranging_data_included(2));
+
+        filter.set_raw_ranging_data_included_for_all(true);
+        assert!(filter.is_raw_ranging_data_included(2));
+        filter.set_raw_ranging_data_included(1, false);
+        assert!(filter.is_raw_ranging_data_included(1));
+        filter.set_raw_ranging_data_included_for_all(false);
+        assert!(!filter.is_raw_ranging_data_included(1));
+    }
+
+    #[test]
+    fn test_raw_ranging_data_included_of_session_with_distinct_token() {
+        const SESSION_ID: u32 = 1;
+        const SESSION_TOKEN: u32 = 0x8000_0001;
+        let mut filter = SessionNotificationFilter::default();
+        filter.set_raw_ranging_data_included(SESSION_ID, true);
+
+        // The range data are tagged with the session ID by uwb_core.
+        let range_data = two_way_range_data(SESSION_ID, &[0x1234]);
+        assert!(filter.is_raw_ranging_data_included(range_data.session_token));
+        assert!(!filter.is_raw_ranging_data_included(SESSION_TOKEN));
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Host-side bookkeeping of the sessions of one chip.
+
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+
+use crate::data_rcv_jitter::DataRcvJitter;
+
+/// SESSION_STATE_DEINIT as defined by the UCI specification.
+const SESSION_STATE_DEINIT: u8 = 0x01;
+
+/// What the host knows about a session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SessionRecord {
+    pub session_type: u8,
+    /// The last state notified by the UWBS, None until the first SESSION_STATUS_NTF.
+    pub session_state: Option<u8>,
+    /// Short addresses of the controlees added by successful multicast list updates.
+    pub controlees: HashSet<[u8; 2]>,
+    /// When the last command or notification of the session was seen.
+    pub last_activity: Instant,
+    /// Inter-arrival statistics of the data packets received by the session.
+    pub data_rcv_jitter: DataRcvJitter,
+}
+
+/// The sessions of one chip, from their initialization until their deinitialization.
+#[derive(Debug, Default)]
+pub(crate) struct SessionRegistry {
+    sessions: BTreeMap<u32, SessionRecord>,
+}
+
+impl SessionRegistry {
+    /// Records the successful initialization of a session.
+    pub fn on_session_init(&mut self, session_id: u32, session_type: u8, now: Instant) {
+        self.sessions.insert(
+            session_id,
+            SessionRecord {
+                session_type,
+                session_state: None,
+                controlees: HashSet::new(),
+                last_activity: now,
+                data_rcv_jitter: DataRcvJitter::default(),
+            },
+        );
+    }
+
+    /// Records a state notified by the UWBS. The session is forgotten once deinitialized.
+    pub fn on_session_state(&mut self, session_id: u32, session_state: u8, now: Instant) {
+        if session_state == SESSION_STATE_DEINIT {
+            self.sessions.remove(&session_id);
+            return;
+        }
+        if let Some(record) = self.sessions.get_mut(&session_id) {
+            record.session_state = Some(session_state);
+            record.last_activity = now;
+        }
+    }
+
+    /// Records a successful multicast list update of a session.
+    pub fn on_controlees_updated(
+        &mut self,
+        session_id: u32,
+        added: bool,
+        short_addresses: &[[u8; 2]],
+        now: Instant,
+    ) {
+        if let Some(record) = self.sessions.get_mut(&session_id) {
+            for address in short_addresses {
+                if added {
+                    record.controlees.insert(*address);
+                } else {
+                    record.controlees.remove(address);
+                }
+            }
+            record.last_activity = now;
+        }
+    }
+
+    /// Records any other activity of a session, e.g. range data.
+    pub fn on_session_activity(&mut self, session_id: u32, now: Instant) {
+        if let Some(record) = self.sessions.get_mut(&session_id) {
+            record.last_activity = now;
+        }
+    }
+
+    /// Records a data packet received by a session at `received_at`.
+    pub fn on_data_rcv(&mut self, session_id: u32, received_at: Instant) {
+        if let Some(record) = self.sessions.get_mut(&session_id) {
+            record.data_rcv_jitter.on_packet(received_at);
+            record.last_activity = received_at;
+        }
+    }
+
+    /// Lists the sessions ordered by session ID.
+    pub fn list(&self) -> Vec<(u32, SessionRecord)> {
+        self.sessions.iter().map(|(&session_id, record)| (session_id, record.clone())).collect()
+    }
+}
+
+/// A difference between the sessions known to the host and the UWBS's view of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SessionMismatch {
+    /// The UWBS counts another number of sessions than the host knows.
+    Count { host: usize, uwbs: u8 },
+    /// The UWBS does not know a session of the host.
+    UnknownSession { session_id: u32 },
+    /// The UWBS reports another state than the last one it notified for the session.
+    State { session_id: u32, host: u8, uwbs: u8 },
+}
+
+/// Compares the sessions known to the host with the UWBS's view of them: the session count
+/// reported by the UWBS, and the state of each session, None if the UWBS does not know it.
+///
+/// The states of the sessions without any notified state are not compared.
+pub(crate) fn find_session_mismatches<F: FnMut(u32) -> Option<u8>>(
+    sessions: &[(u32, SessionRecord)],
+    uwbs_session_count: u8,
+    mut uwbs_session_state: F,
+) -> Vec<SessionMismatch> {
+    let mut mismatches = vec![];
+    if sessions.len() != uwbs_session_count as usize {
+        mismatches.push(SessionMismatch::Count { host: sessions.len(), uwbs: uwbs_session_count });
+    }
+    for (session_id, record) in sessions {
+        match (record.session_state, uwbs_session_state(*session_id)) {
+            (_, None) => {
+                mismatches.push(SessionMismatch::UnknownSession { session_id: *session_id })
+            }
+            (Some(host), Some(uwbs)) if host != uwbs => {
+                mismatches.push(SessionMismatch::State { session_id: *session_id, host, uwbs })
+            }
+            _ => {}
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const SESSION_STATE_IDLE: u8 = 0x03;
+
+    #[test]
+    fn test_session_lifecycle() {
+        let mut registry = SessionRegistry::default();
+        let start = Instant::now();
+        registry.on_session_init(2, 0x00, start);
+        registry.on_session_init(1, 0x02, start);
+        // Unknown sessions are ignored.
+        registry.on_session_state(3, SESSION_STATE_IDLE, start);
+
+        let sessions = registry.list();
+        assert_eq!(sessions.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(sessions[0].1.session_type, 0x02);
+        assert_eq!(sessions[0].1.session_state, None);
+
+        let later = start + Duration::from_secs(1);
+        registry.on_session_state(1, SESSION_STATE_IDLE, later);
+        let sessions = registry.list();
+        assert_eq!(sessions[0].1.session_state, Some(SESSION_STATE_IDLE));
+        assert_eq!(sessions[0].1.last_activity, later);
+
+        registry.on_session_state(1, SESSION_STATE_DEINIT, later);
+        assert_eq!(registry.list().iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_controlees() {
+        let mut registry = SessionRegistry::default();
+        let start = Instant::now();
+        registry.on_session_init(1, 0x00, start);
+        registry.on_controlees_updated(1, true, &[[0x01, 0x02], [0x03, 0x04]], start);
+        registry.on_controlees_updated(1, true, &[[0x01, 0x02]], start);
+        assert_eq!(registry.list()[0].1.controlees.len(), 2);
+        registry.on_controlees_updated(1, false, &[[0x03, 0x04]], start);
+        assert_eq!(registry.list()[0].1.controlees, HashSet::from([[0x01, 0x02]]));
+    }
+
+    #[test]
+    fn test_data_rcv() {
+        let mut registry = SessionRegistry::default();
+        let start = Instant::now();
+        registry.on_session_init(1, 0x00, start);
+        // Unknown sessions are ignored.
+        registry.on_data_rcv(2, start);
+        registry.on_data_rcv(1, start);
+        registry.on_data_rcv(1, start + Duration::from_millis(50));
+
+        let sessions = registry.list();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].1.data_rcv_jitter.packet_count, 2);
+        assert_eq!(sessions[0].1.data_rcv_jitter.mean_interval(), Duration::from_millis(50));
+        assert_eq!(sessions[0].1.last_activity, start + Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_find_session_mismatches() {
+        const SESSION_STATE_ACTIVE: u8 = 0x02;
+        let mut registry = SessionRegistry::default();
+        let start = Instant::now();
+        registry.on_session_init(1, 0x00, start);
+        registry.on_session_init(2, 0x00, start);
+        registry.on_session_init(3, 0x00, start);
+        registry.on_session_state(1, SESSION_STATE_IDLE, start);
+        registry.on_session_state(2, SESSION_STATE_ACTIVE, start);
+        let sessions = registry.list();
+
+        let uwbs_session_state = |session_id| match session_id {
+            1 => Some(SESSION_STATE_IDLE),
+            2 => Some(SESSION_STATE_ACTIVE),
+            // Not compared since no state was notified.
+            3 => Some(SESSION_STATE_ACTIVE),
+            _ => None,
+        };
+        assert!(find_session_mismatches(&sessions, 3, uwbs_session_state).is_empty());
+
+        let uwbs_session_state = |session_id| match session_id {
+            1 => Some(SESSION_STATE_IDLE),
+            2 => Some(SESSION_STATE_IDLE),
+            _ => None,
+        };
+        assert_eq!(
+            find_session_mismatches(&sessions, 2, uwbs_session_state),
+            vec![
+                SessionMismatch::Count { host: 3, uwbs: 2 },
+                SessionMismatch::State {
+                    session_id: 2,
+                    host: SESSION_STATE_ACTIVE,
+                    uwbs: SESSION_STATE_IDLE
+                },
+                SessionMismatch::UnknownSession { session_id: 3 },
+            ]
+        );
+    }
+}
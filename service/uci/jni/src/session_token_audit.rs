@@ -0,0 +1,222 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Audit of the mapping of the session tokens to the session IDs, as learned from the
+//! SESSION_STATUS_NTF which carry both, to debug the range data delivered to the wrong session
+//! after a session token was reused by the UWBS.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Instant;
+
+/// Number of remaps kept in the history.
+const MAX_REMAPS: usize = 32;
+/// Number of deinitialized mappings remembered to detect the reuse of their session token.
+const MAX_RETIRED_MAPPINGS: usize = 32;
+
+/// A session token mapped to a session ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SessionTokenMapping {
+    pub session_token: u32,
+    pub session_id: u32,
+    /// The session the token was mapped to before, if the token was reused for another session.
+    pub previous_session_id: Option<u32>,
+    /// When the mapping was first notified.
+    pub mapped_at: Instant,
+}
+
+/// The session tokens of one chip, with the history of the tokens remapped to another session.
+#[derive(Debug, Default)]
+pub(crate) struct SessionTokenAudit {
+    /// The mappings of the sessions not deinitialized, by session token.
+    mappings: BTreeMap<u32, SessionTokenMapping>,
+    /// The last mappings of the deinitialized sessions, oldest first.
+    retired: VecDeque<SessionTokenMapping>,
+    /// The last remaps, oldest first.
+    remaps: VecDeque<SessionTokenMapping>,
+}
+
+impl SessionTokenAudit {
+    /// Records the mapping carried by a SESSION_STATUS_NTF.
+    pub fn on_session_status(
+        &mut self,
+        session_id: u32,
+        session_token: u32,
+        deinit: bool,
+        now: Instant,
+    ) {
+        if deinit {
+            if let Some(mapping) = self.mappings.remove(&session_token) {
+                self.retire(mapping);
+            }
+            return;
+        }
+        if self.mappings.get(&session_token).map(|mapping| mapping.session_id) == Some(session_id) {
+            return;
+        }
+
+        // The session was given another token, its previous one is not valid anymore.
+        let stale_tokens = self
+            .mappings
+            .values()
+            .filter(|mapping| mapping.session_id == session_id)
+            .map(|mapping| mapping.session_token)
+            .collect::<Vec<_>>();
+        for token in stale_tokens {
+            if let Some(mapping) = self.mappings.remove(&token) {
+                self.retire(mapping);
+            }
+        }
+
+        let previous_session_id = match self.mappings.remove(&session_token) {
+            // The token was reused before the deinitialization of its session was notified.
+            Some(mapping) => Some(mapping.session_id),
+            None => self
+                .retired
+                .iter()
+                .rev()
+                .find(|mapping| mapping.session_token == session_token)
+                .map(|mapping| mapping.session_id),
+        }
+        .filter(|previous_session_id| *previous_session_id != session_id);
+        self.retired.retain(|mapping| mapping.session_token != session_token);
+
+        let mapping =
+            SessionTokenMapping { session_token, session_id, previous_session_id, mapped_at: now };
+        if previous_session_id.is_some() {
+            if self.remaps.len() == MAX_REMAPS {
+                self.remaps.pop_front();
+            }
+            self.remaps.push_back(mapping.clone());
+        }
+        self.mappings.insert(session_token, mapping);
+    }
+
+    fn retire(&mut self, mapping: SessionTokenMapping) {
+        if self.retired.len() == MAX_RETIRED_MAPPINGS {
+            self.retired.pop_front();
+        }
+        self.retired.push_back(mapping);
+    }
+
+    /// Lists the mappings of the sessions not deinitialized, ordered by session token.
+    pub fn mappings(&self) -> Vec<SessionTokenMapping> {
+        self.mappings.values().cloned().collect()
+    }
+
+    /// Lists the last remaps of a session token to another session, oldest first.
+    pub fn remaps(&self) -> Vec<SessionTokenMapping> {
+        self.remaps.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_mappings() {
+        let mut audit = SessionTokenAudit::default();
+        let start = Instant::now();
+        audit.on_session_status(2, 0x200, false, start);
+        audit.on_session_status(1, 0x100, false, start);
+        // The following notifications of the session keep the time it was mapped at.
+        audit.on_session_status(1, 0x100, false, start + Duration::from_secs(1));
+
+        let mappings = audit.mappings();
+        assert_eq!(
+            mappings,
+            vec![
+                SessionTokenMapping {
+                    session_token: 0x100,
+                    session_id: 1,
+                    previous_session_id: None,
+                    mapped_at: start,
+                },
+                SessionTokenMapping {
+                    session_token: 0x200,
+                    session_id: 2,
+                    previous_session_id: None,
+                    mapped_at: start,
+                },
+            ]
+        );
+
+        audit.on_session_status(1, 0x100, true, start);
+        assert_eq!(audit.mappings().iter().map(|m| m.session_id).collect::<Vec<_>>(), vec![2]);
+        assert!(audit.remaps().is_empty());
+    }
+
+    #[test]
+    fn test_token_reused_after_deinit() {
+        let mut audit = SessionTokenAudit::default();
+        let start = Instant::now();
+        audit.on_session_status(1, 0x100, false, start);
+        audit.on_session_status(1, 0x100, true, start);
+        // The same session initialized again with the same token is not a remap.
+        audit.on_session_status(1, 0x100, false, start);
+        audit.on_session_status(1, 0x100, true, start);
+
+        let later = start + Duration::from_secs(1);
+        audit.on_session_status(2, 0x100, false, later);
+        let remap = SessionTokenMapping {
+            session_token: 0x100,
+            session_id: 2,
+            previous_session_id: Some(1),
+            mapped_at: later,
+        };
+        assert_eq!(audit.remaps(), vec![remap.clone()]);
+        assert_eq!(audit.mappings(), vec![remap]);
+    }
+
+    #[test]
+    fn test_token_reused_without_deinit() {
+        let mut audit = SessionTokenAudit::default();
+        let start = Instant::now();
+        audit.on_session_status(1, 0x100, false, start);
+        audit.on_session_status(2, 0x100, false, start);
+        assert_eq!(audit.remaps()[0].previous_session_id, Some(1));
+        assert_eq!(audit.mappings().len(), 1);
+    }
+
+    #[test]
+    fn test_session_given_another_token() {
+        let mut audit = SessionTokenAudit::default();
+        let start = Instant::now();
+        audit.on_session_status(1, 0x100, false, start);
+        audit.on_session_status(1, 0x101, false, start);
+        assert_eq!(
+            audit.mappings().iter().map(|m| m.session_token).collect::<Vec<_>>(),
+            vec![0x101]
+        );
+        assert!(audit.remaps().is_empty());
+
+        // The stale token of the session reused by another one is a remap.
+        audit.on_session_status(2, 0x100, false, start);
+        assert_eq!(audit.remaps()[0].previous_session_id, Some(1));
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut audit = SessionTokenAudit::default();
+        let start = Instant::now();
+        for session_id in 0..(MAX_REMAPS as u32 + 2) {
+            audit.on_session_status(session_id, 0x100, false, start);
+        }
+        let remaps = audit.remaps();
+        assert_eq!(remaps.len(), MAX_REMAPS);
+        assert_eq!(remaps[0].previous_session_id, Some(1));
+        assert_eq!(remaps[MAX_REMAPS - 1].session_id, MAX_REMAPS as u32 + 1);
+    }
+}
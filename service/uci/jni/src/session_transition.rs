@@ -0,0 +1,189 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of the session state transitions notified by the UWBS against their reason code.
+
+use std::collections::HashMap;
+
+/// Session states as defined by the UCI specification.
+const SESSION_STATE_INIT: u8 = 0x00;
+const SESSION_STATE_DEINIT: u8 = 0x01;
+const SESSION_STATE_ACTIVE: u8 = 0x02;
+
+/// Reason codes of SESSION_STATUS_NTF as defined by the UCI specification.
+const REASON_MAX_RANGING_ROUND_RETRY_COUNT_REACHED: u8 = 0x01;
+const REASON_MAX_NUMBER_OF_MEASUREMENTS_REACHED: u8 = 0x02;
+const REASON_SESSION_RESUMED_DUE_TO_INBAND_SIGNAL: u8 = 0x04;
+const REASON_SESSION_STOPPED_DUE_TO_INBAND_SIGNAL: u8 = 0x05;
+/// Range of the reason codes reporting a configuration error, which stop the ranging.
+const REASON_ERROR_FIRST: u8 = 0x1D;
+const REASON_ERROR_LAST: u8 = 0x7F;
+
+/// How a session state transition breaks the UCI session state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TransitionViolation {
+    /// The session cannot move from the previous state to the new one, e.g. ACTIVE to INIT.
+    IllegalTransition = 1,
+    /// The reason code contradicts the new state, e.g. a ranging error while ACTIVE.
+    UnexpectedReasonCode = 2,
+}
+
+/// A transition breaking the session state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct InvalidTransition {
+    pub session_id: u32,
+    pub previous_state: u8,
+    pub session_state: u8,
+    pub reason_code: u8,
+    pub violation: TransitionViolation,
+}
+
+/// Returns how the transition from `previous_state` to `session_state` with `reason_code` breaks
+/// the session state machine, if it does.
+fn check_transition(
+    previous_state: u8,
+    session_state: u8,
+    reason_code: u8,
+) -> Option<TransitionViolation> {
+    match (previous_state, session_state) {
+        // A session is only deinitialized by the host or the UWBS, whatever its state.
+        (_, SESSION_STATE_DEINIT) => return None,
+        (SESSION_STATE_INIT, SESSION_STATE_INIT) => {}
+        (_, SESSION_STATE_INIT) | (SESSION_STATE_INIT, SESSION_STATE_ACTIVE) => {
+            return Some(TransitionViolation::IllegalTransition)
+        }
+        _ => {}
+    }
+    let stops_ranging = matches!(
+        reason_code,
+        REASON_MAX_RANGING_ROUND_RETRY_COUNT_REACHED
+            | REASON_MAX_NUMBER_OF_MEASUREMENTS_REACHED
+            | REASON_SESSION_STOPPED_DUE_TO_INBAND_SIGNAL
+            | REASON_ERROR_FIRST..=REASON_ERROR_LAST
+    );
+    let resumes_ranging = reason_code == REASON_SESSION_RESUMED_DUE_TO_INBAND_SIGNAL;
+    if (session_state == SESSION_STATE_ACTIVE && stops_ranging)
+        || (session_state != SESSION_STATE_ACTIVE && resumes_ranging)
+    {
+        return Some(TransitionViolation::UnexpectedReasonCode);
+    }
+    None
+}
+
+/// Tracks the state of the sessions of one chip to validate the transitions notified for them.
+#[derive(Debug, Default)]
+pub(crate) struct SessionTransitionValidator {
+    states: HashMap<u32, u8>,
+}
+
+impl SessionTransitionValidator {
+    /// Records the state notified for a session, and returns the violation of the session state
+    /// machine if any. The first state notified for a session is not validated.
+    pub fn on_session_state(
+        &mut self,
+        session_id: u32,
+        session_state: u8,
+        reason_code: u8,
+    ) -> Option<InvalidTransition> {
+        let previous_state = if session_state == SESSION_STATE_DEINIT {
+            self.states.remove(&session_id)
+        } else {
+            self.states.insert(session_id, session_state)
+        }?;
+        check_transition(previous_state, session_state, reason_code).map(|violation| {
+            InvalidTransition { session_id, previous_state, session_state, reason_code, violation }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_STATE_IDLE: u8 = 0x03;
+    const REASON_STATE_CHANGE_WITH_SESSION_MANAGEMENT_COMMANDS: u8 = 0x00;
+    const REASON_ERROR_SLOT_LENGTH_NOT_SUPPORTED: u8 = 0x20;
+
+    #[test]
+    fn test_check_transition() {
+        let ok = REASON_STATE_CHANGE_WITH_SESSION_MANAGEMENT_COMMANDS;
+        assert_eq!(check_transition(SESSION_STATE_INIT, SESSION_STATE_IDLE, ok), None);
+        assert_eq!(check_transition(SESSION_STATE_IDLE, SESSION_STATE_ACTIVE, ok), None);
+        assert_eq!(
+            check_transition(
+                SESSION_STATE_ACTIVE,
+                SESSION_STATE_IDLE,
+                REASON_MAX_RANGING_ROUND_RETRY_COUNT_REACHED
+            ),
+            None
+        );
+        assert_eq!(
+            check_transition(
+                SESSION_STATE_IDLE,
+                SESSION_STATE_ACTIVE,
+                REASON_SESSION_RESUMED_DUE_TO_INBAND_SIGNAL
+            ),
+            None
+        );
+        assert_eq!(check_transition(SESSION_STATE_ACTIVE, SESSION_STATE_DEINIT, 0x21), None);
+
+        assert_eq!(
+            check_transition(SESSION_STATE_ACTIVE, SESSION_STATE_INIT, ok),
+            Some(TransitionViolation::IllegalTransition)
+        );
+        assert_eq!(
+            check_transition(SESSION_STATE_INIT, SESSION_STATE_ACTIVE, ok),
+            Some(TransitionViolation::IllegalTransition)
+        );
+        assert_eq!(
+            check_transition(
+                SESSION_STATE_IDLE,
+                SESSION_STATE_ACTIVE,
+                REASON_ERROR_SLOT_LENGTH_NOT_SUPPORTED
+            ),
+            Some(TransitionViolation::UnexpectedReasonCode)
+        );
+        assert_eq!(
+            check_transition(
+                SESSION_STATE_ACTIVE,
+                SESSION_STATE_IDLE,
+                REASON_SESSION_RESUMED_DUE_TO_INBAND_SIGNAL
+            ),
+            Some(TransitionViolation::UnexpectedReasonCode)
+        );
+    }
+
+    #[test]
+    fn test_validator_tracks_sessions() {
+        let mut validator = SessionTransitionValidator::default();
+        // The first state of a session is not validated.
+        assert_eq!(validator.on_session_state(1, SESSION_STATE_ACTIVE, 0), None);
+        assert_eq!(validator.on_session_state(2, SESSION_STATE_INIT, 0), None);
+        assert_eq!(
+            validator.on_session_state(1, SESSION_STATE_INIT, 0),
+            Some(InvalidTransition {
+                session_id: 1,
+                previous_state: SESSION_STATE_ACTIVE,
+                session_state: SESSION_STATE_INIT,
+                reason_code: 0,
+                violation: TransitionViolation::IllegalTransition,
+            })
+        );
+        assert_eq!(validator.on_session_state(2, SESSION_STATE_IDLE, 0), None);
+
+        // Deinitialized sessions are forgotten.
+        assert_eq!(validator.on_session_state(2, SESSION_STATE_DEINIT, 0), None);
+        assert_eq!(validator.on_session_state(2, SESSION_STATE_ACTIVE, 0), None);
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime filtering of the UCI packets written to the pcapng capture.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use log::error;
+use uwb_core::error::{Error, Result};
+use uwb_core::uci::uci_logger::UciLogger;
+use uwb_uci_packets::{Packet, UciControlPacket, UciDataPacket};
+
+use crate::uci_log_redaction::redact_sensitive_app_configs;
+
+const MESSAGE_TYPE_DATA: u8 = 0;
+const MESSAGE_TYPE_COMMAND: u8 = 1;
+const MESSAGE_TYPE_RESPONSE: u8 = 2;
+const MESSAGE_TYPE_NOTIFICATION: u8 = 3;
+const GID_SESSION_CONFIG: u8 = 0x1;
+const GID_SESSION_CONTROL: u8 = 0x2;
+const OID_SESSION_INFO: u8 = 0x0;
+const HEADER_LEN: usize = 4;
+
+/// Which UCI packets are captured. Must be kept in sync with NativeUwbManager.UCI_CAPTURE_MODE_*.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum UciCaptureMode {
+    /// No packet is captured.
+    Disabled = 0,
+    /// All the packets are captured.
+    #[default]
+    All = 1,
+    /// Only the packets matching the session tokens and GIDs of the filter are captured.
+    Filtered = 2,
+}
+
+impl TryFrom<i32> for UciCaptureMode {
+    type Error = Error;
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(UciCaptureMode::Disabled),
+            1 => Ok(UciCaptureMode::All),
+            2 => Ok(UciCaptureMode::Filtered),
+            _ => Err(Error::BadParameters),
+        }
+    }
+}
+
+/// Decides which UCI packets of a chip are captured.
+///
+/// In the filtered mode, a control packet is captured when its GID is in `gids` and, for the
+/// session GIDs, its session token is in `session_tokens`. An empty set matches everything. A
+/// response carries no session token, so it follows the decision taken for its command.
+#[derive(Debug, Default)]
+pub(crate) struct UciCaptureFilter {
+    mode: UciCaptureMode,
+    session_tokens: HashSet<u32>,
+    gids: HashSet<u8>,
+    last_command_captured: bool,
+    /// Whether the identifying app configurations are redacted, which is the case in the Filtered
+    /// log mode. The key material is redacted in every mode.
+    redact_identifiers: bool,
+}
+
+impl UciCaptureFilter {
+    /// Replaces the capture configuration.
+    pub fn configure(
+        &mut self,
+        mode: UciCaptureMode,
+        session_tokens: HashSet<u32>,
+        gids: HashSet<u8>,
+    ) {
+        self.mode = mode;
+        self.session_tokens = session_tokens;
+        self.gids = gids;
+    }
+
+    /// Gets the current capture mode.
+    pub fn mode(&self) -> UciCaptureMode {
+        self.mode
+    }
+
+    /// Sets whether the identifying app configurations of the captured packets are redacted.
+    pub fn set_redact_identifiers(&mut self, redact: bool) {
+        self.redact_identifiers = redact;
+    }
+
+    /// Returns whether the encoded control packet should be captured.
+    pub fn accepts_control_packet(&mut self, packet: &[u8]) -> bool {
+        let header = match packet.get(..HEADER_LEN) {
+            Some(header) => header,
+            None => return self.mode == UciCaptureMode::All,
+        };
+        let message_type = header[0] >> 5;
+        let gid = header[0] & 0x0f;
+        let oid = header[1] & 0x3f;
+        let accepted = match self.mode {
+            UciCaptureMode::Disabled => false,
+            UciCaptureMode::All => true,
+            UciCaptureMode::Filtered if message_type == MESSAGE_TYPE_RESPONSE => {
+                self.last_command_captured
+            }
+            UciCaptureMode::Filtered => {
+                (self.gids.is_empty() || self.gids.contains(&gid))
+                    && self.matches_session(control_packet_session_token(
+                        message_type,
+                        gid,
+                        oid,
+                        &packet[HEADER_LEN..],
+                    ))
+            }
+        };
+        if message_type == MESSAGE_TYPE_COMMAND {
+            self.last_command_captured = accepted;
+        }
+        accepted
+    }
+
+    /// Returns whether the encoded data packet should be captured. The GIDs do not apply to data
+    /// packets.
+    pub fn accepts_data_packet(&self, packet: &[u8]) -> bool {
+        match self.mode {
+            UciCaptureMode::Disabled => false,
+            UciCaptureMode::All => true,
+            UciCaptureMode::Filtered => self.matches_session(
+                packet
+                    .get(HEADER_LEN..HEADER_LEN + 4)
+                    .filter(|_| packet[0] >> 5 == MESSAGE_TYPE_DATA)
+                    .map(|token| u32::from_le_bytes(token.try_into().unwrap())),
+            ),
+        }
+    }
+
+    fn matches_session(&self, session_token: Option<u32>) -> bool {
+        if self.session_tokens.is_empty() {
+            return true;
+        }
+        session_token.is_some_and(|token| self.session_tokens.contains(&token))
+    }
+}
+
+/// Gets the session token of a session command or notification, if any.
+fn control_packet_session_token(message_type: u8, gid: u8, oid: u8, payload: &[u8]) -> Option<u32> {
+    if gid != GID_SESSION_CONFIG && gid != GID_SESSION_CONTROL {
+        return None;
+    }
+    // The SESSION_INFO_NTF starts with the sequence number, the other session packets with the
+    // session token.
+    let offset = match message_type {
+        MESSAGE_TYPE_NOTIFICATION if gid == GID_SESSION_CONTROL && oid == OID_SESSION_INFO => 4,
+        MESSAGE_TYPE_COMMAND | MESSAGE_TYPE_NOTIFICATION => 0,
+        _ => return None,
+    };
+    payload.get(offset..offset + 4).map(|token| u32::from_le_bytes(token.try_into().unwrap()))
+}
+
+/// UciLogger which forwards to `inner` only the packets accepted by the shared filter.
+pub(crate) struct UciCaptureLogger<L: UciLogger> {
+    inner: L,
+    filter: Arc<Mutex<UciCaptureFilter>>,
+}
+
+impl<L: UciLogger> UciCaptureLogger<L> {
+    /// Constructs the logger. The filter may be reconfigured at any time through `filter`.
+    pub fn new(inner: L, filter: Arc<Mutex<UciCaptureFilter>>) -> Self {
+        Self { inner, filter }
+    }
+}
+
+impl<L: UciLogger> UciLogger for UciCaptureLogger<L> {
+    fn log_uci_control_packet(&mut self, packet: UciControlPacket) {
+        let (accepted, redact_identifiers) = match self.filter.lock() {
+            // Only encode the packet when the filter needs to look into it.
+            Ok(mut filter) => (
+                match filter.mode() {
+                    UciCaptureMode::Disabled => false,
+                    UciCaptureMode::All => true,
+                    UciCaptureMode::Filtered => {
+                        filter.accepts_control_packet(&packet.clone().to_vec())
+                    }
+                },
+                filter.redact_identifiers,
+            ),
+            Err(_) => (true, true),
+        };
+        if !accepted {
+            return;
+        }
+        let mut bytes = packet.clone().to_vec();
+        if !redact_sensitive_app_configs(&mut bytes, redact_identifiers) {
+            self.inner.log_uci_control_packet(packet);
+            return;
+        }
+        // A packet which cannot be redacted is not captured rather than leaking its secrets.
+        match UciControlPacket::parse(&bytes) {
+            Ok(redacted_packet) => self.inner.log_uci_control_packet(redacted_packet),
+            Err(e) => error!("UCI JNI: failed to redact a captured packet: {:?}", e),
+        }
+    }
+
+    fn log_uci_data_packet(&mut self, packet: &UciDataPacket) {
+        let accepted = match self.filter.lock() {
+            Ok(filter) => match filter.mode() {
+                UciCaptureMode::Disabled => false,
+                UciCaptureMode::All => true,
+                UciCaptureMode::Filtered => filter.accepts_data_packet(&packet.clone().to_vec()),
+            },
+            Err(_) => true,
+        };
+        if accepted {
+            self.inner.log_uci_data_packet(packet);
+        }
+    }
+
+    fn log_hal_open(&mut self, result: Result<()>) {
+        self.inner.log_hal_open(result);
+    }
+
+    fn log_hal_close(&mut self, result: Result<()>) {
+        self.inner.log_hal_close(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_TOKEN: u32 = 0x01020304;
+    const OTHER_SESSION_TOKEN: u32 = 0x0a0b0c0d;
+
+    fn filtered(session_tokens: &[u32], gids: &[u8]) -> UciCaptureFilter {
+        let mut filter = UciCaptureFilter::default();
+        filter.configure(
+            UciCaptureMode::Filtered,
+            session_tokens.iter().copied().collect(),
+            gids.iter().copied().collect(),
+        );
+        filter
+    }
+
+    fn control_packet(message_type: u8, gid: u8, oid: u8, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![(message_type << 5) | gid, oid, 0, payload.len() as u8];
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    #[test]
+    fn test_mode_from_i32() {
+        assert_eq!(UciCaptureMode::try_from(0), Ok(UciCaptureMode::Disabled));
+        assert_eq!(UciCaptureMode::try_from(2), Ok(UciCaptureMode::Filtered));
+        assert_eq!(UciCaptureMode::try_from(3), Err(Error::BadParameters));
+    }
+
+    #[test]
+    fn test_disabled_and_all() {
+        let packet = control_packet(MESSAGE_TYPE_NOTIFICATION, 0x0, 0x1, &[0x01]);
+        let mut filter = UciCaptureFilter::default();
+        assert!(filter.accepts_control_packet(&packet));
+        filter.configure(UciCaptureMode::Disabled, HashSet::new(), HashSet::new());
+        assert!(!filter.accepts_control_packet(&packet));
+        assert!(!filter.accepts_data_packet(&[0x00, 0x00, 0x00, 0x00]));
+    }
+
+    #[test]
+    fn test_filter_by_session_token() {
+        let mut filter = filtered(&[SESSION_TOKEN], &[]);
+        let token = SESSION_TOKEN.to_le_bytes();
+        let other_token = OTHER_SESSION_TOKEN.to_le_bytes();
+
+        // SESSION_START_CMD and its response.
+        let start_cmd = control_packet(MESSAGE_TYPE_COMMAND, GID_SESSION_CONTROL, 0x0, &token);
+        let start_rsp = control_packet(MESSAGE_TYPE_RESPONSE, GID_SESSION_CONTROL, 0x0, &[0x00]);
+        assert!(filter.accepts_control_packet(&start_cmd));
+        assert!(filter.accepts_control_packet(&start_rsp));
+        let other_cmd =
+            control_packet(MESSAGE_TYPE_COMMAND, GID_SESSION_CONTROL, 0x0, &other_token);
+        assert!(!filter.accepts_control_packet(&other_cmd));
+        assert!(!filter.accepts_control_packet(&start_rsp));
+
+        // SESSION_INFO_NTF has the sequence number first.
+        let mut info_payload = vec![0x00, 0x00, 0x00, 0x00];
+        info_payload.extend_from_slice(&token);
+        let info_ntf = control_packet(
+            MESSAGE_TYPE_NOTIFICATION,
+            GID_SESSION_CONTROL,
+            OID_SESSION_INFO,
+            &info_payload,
+        );
+        assert!(filter.accepts_control_packet(&info_ntf));
+
+        // Core packets have no session token.
+        let core_ntf = control_packet(MESSAGE_TYPE_NOTIFICATION, 0x0, 0x1, &[0x01]);
+        assert!(!filter.accepts_control_packet(&core_ntf));
+
+        let mut data = vec![MESSAGE_TYPE_DATA << 5, 0x00, 0x04, 0x00];
+        data.extend_from_slice(&token);
+        assert!(filter.accepts_data_packet(&data));
+        data[HEADER_LEN..].copy_from_slice(&other_token);
+        assert!(!filter.accepts_data_packet(&data));
+    }
+
+    #[test]
+    fn test_filter_by_gid() {
+        let mut filter = filtered(&[], &[0x0]);
+        let core_ntf = control_packet(MESSAGE_TYPE_NOTIFICATION, 0x0, 0x1, &[0x01]);
+        let session_ntf = control_packet(
+            MESSAGE_TYPE_NOTIFICATION,
+            GID_SESSION_CONFIG,
+            0x2,
+            &SESSION_TOKEN.to_le_bytes(),
+        );
+        assert!(filter.accepts_control_packet(&core_ntf));
+        assert!(!filter.accepts_control_packet(&session_ntf));
+        assert!(filter.accepts_data_packet(&[MESSAGE_TYPE_DATA << 5, 0x00, 0x00, 0x00]));
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Policy of the raw UCI messages injected by the vendor debugging tools.
+//!
+//! The injected messages bypass the typed builders of the UCI manager. Any GID can be injected on
+//! debuggable builds, only the allowlisted GIDs on production builds. The policy is owned by the
+//! Java side and pushed down by nativeSetUciInjectionPolicy; nothing can be injected until then.
+
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use log::{error, info};
+use uwb_core::error::{Error, Result};
+
+/// The GID field of the UCI header is 4 bits wide.
+const MAX_GID: u32 = 0xF;
+
+lazy_static! {
+    /// The injection policy shared by all the chips.
+    static ref INJECTION_POLICY: RwLock<InjectionPolicy> =
+        RwLock::new(InjectionPolicy::default());
+}
+
+/// Which UCI messages may be injected.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct InjectionPolicy {
+    debuggable: bool,
+    allowed_gids: BTreeSet<u32>,
+}
+
+impl InjectionPolicy {
+    pub fn new(debuggable: bool, allowed_gids: &[u32]) -> Self {
+        Self { debuggable, allowed_gids: allowed_gids.iter().copied().collect() }
+    }
+
+    /// Returns whether the messages of `gid` may be injected.
+    pub fn allows(&self, gid: u32) -> bool {
+        gid <= MAX_GID && (self.debuggable || self.allowed_gids.contains(&gid))
+    }
+}
+
+/// Replaces the injection policy with the one pushed by the Java side.
+pub(crate) fn set_policy(policy: InjectionPolicy) -> Result<()> {
+    info!("UCI JNI: UCI injection policy set to {:?}", policy);
+    *INJECTION_POLICY.write().map_err(|_| Error::Unknown)? = policy;
+    Ok(())
+}
+
+/// Checks that the messages of `gid` may be injected.
+pub(crate) fn check(gid: u32) -> Result<()> {
+    let policy = INJECTION_POLICY.read().map_err(|_| Error::Unknown)?;
+    if !policy.allows(gid) {
+        error!("UCI JNI: injecting GID {:#x} is not allowed", gid);
+        return Err(Error::BadParameters);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nothing_allowed_by_default() {
+        let policy = InjectionPolicy::default();
+        for gid in 0..=MAX_GID {
+            assert!(!policy.allows(gid));
+        }
+    }
+
+    #[test]
+    fn test_production_allowlist() {
+        let policy = InjectionPolicy::new(false, &[0x9, 0xE]);
+        assert!(policy.allows(0x9));
+        assert!(policy.allows(0xE));
+        assert!(!policy.allows(0x1));
+        assert!(!policy.allows(0xF));
+    }
+
+    #[test]
+    fn test_debuggable_allows_any_gid() {
+        let policy = InjectionPolicy::new(true, &[]);
+        for gid in 0..=MAX_GID {
+            assert!(policy.allows(gid));
+        }
+        assert!(!policy.allows(MAX_GID + 1));
+    }
+}
@@ -14,25 +14,61 @@
 
 //! Implementation of JNI functions.
 
+use crate::advertiser_tracker::AdvertiserState;
+use crate::antenna_config::{AntennaConfig, AoaCapabilities};
+use crate::calibration_store::Calibration;
+use crate::controlee_batching;
+use crate::data_tx_journal::AbortedTransfers;
+use crate::device_config::{set_device_configs, DeviceConfig};
 use crate::dispatcher::Dispatcher;
+use crate::feature_flags::{self, FeatureFlag};
+use crate::firmware_log::FirmwareLogConfig;
+use crate::global_ref_accounting::{GlobalRefKind, TrackedGlobalRef, TrackedRef};
+use crate::hal_trace::HalTransaction;
 use crate::helper::{boolean_result_helper, byte_result_helper, option_result_helper};
 use crate::jclass_name::{
-    CONFIG_STATUS_DATA_CLASS, DT_RANGING_ROUNDS_STATUS_CLASS, MULTICAST_LIST_UPDATE_STATUS_CLASS,
-    POWER_STATS_CLASS, TLV_DATA_CLASS, UWB_DEVICE_INFO_RESPONSE_CLASS, UWB_RANGING_DATA_CLASS,
-    VENDOR_RESPONSE_CLASS,
+    ABORTED_DATA_TRANSFERS_CLASS, CONFIG_STATUS_DATA_CLASS, DT_RANGING_ROUNDS_STATUS_CLASS,
+    HAL_TRANSACTION_CLASS, MULTICAST_LIST_UPDATE_STATUS_CLASS, NOTIFICATION_STATS_CLASS,
+    OWR_AOA_ADVERTISER_INFO_CLASS, PARSE_ERROR_STATS_CLASS, POWER_STATS_CLASS,
+    RANGING_ATTESTATION_CLASS, SESSION_MISMATCH_CLASS, SESSION_RECORD_CLASS,
+    SESSION_TOKEN_MAPPING_CLASS, TLV_DATA_CLASS, UCI_COMMAND_STATS_CLASS,
+    UWB_DEVICE_INFO_RESPONSE_CLASS, UWB_RANGING_DATA_CLASS, VENDOR_RESPONSE_CLASS,
 };
+use crate::metrics_exporter;
+use crate::notification_queue::BackpressurePolicy;
+use crate::notification_stats::{CallbackType, NotificationStats};
+use crate::proximity::ProximityConfig;
+use crate::radar_config::validate_radar_config_tlvs;
+use crate::radar_decimation::{RadarDecimation, RadarDeliveryBudget, RadarDropPolicy};
+use crate::ranging_attestation::{RangingAttestationRecord, RangingAttestor};
+use crate::ranging_diagnostics::{find_diagnostics_enabled, DiagnosticsConfig};
+use crate::role_switch::{switch_ranging_role, RangingRole};
+use crate::secret_bytes::{zeroize, SecretBytes};
+use crate::session_delivery::DeliveryPreference;
+use crate::session_extras::parse_session_extras;
+use crate::session_registry::{find_session_mismatches, SessionMismatch, SessionRecord};
+use crate::session_token_audit::SessionTokenMapping;
+use crate::uci_capture::UciCaptureMode;
+use crate::uci_injection::{self, InjectionPolicy};
+use crate::uci_metrics::UciCommandStats;
+use crate::uci_proxy;
 use crate::unique_jvm;
+use crate::vendor_commands::VendorCommand;
 
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::iter::zip;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use jni::errors::Error as JNIError;
 use jni::objects::{GlobalRef, JObject, JString, JValue};
 use jni::signature::ReturnType;
 use jni::sys::{
-    jboolean, jbyte, jbyteArray, jint, jintArray, jlong, jobject, jobjectArray, jshort, jvalue,
+    jboolean, jbooleanArray, jbyte, jbyteArray, jint, jintArray, jlong, jobject, jobjectArray,
+    jshort, jstring, jvalue,
 };
-use jni::JNIEnv;
+use jni::{JNIEnv, JavaVM};
 use log::{debug, error};
 use uwb_core::error::{Error, Result};
 use uwb_core::params::{
@@ -42,11 +78,35 @@ use uwb_core::params::{
 };
 use uwb_uci_packets::{
     AppConfigTlvType, CapTlv, Controlee, ControleePhaseList, Controlee_V2_0_16_Byte_Version,
-    Controlee_V2_0_32_Byte_Version, Controlees, MacAddressIndicator, PhaseListExtendedMacAddress,
+    Controlee_V2_0_32_Byte_Version, Controlees, DeviceConfigId, DeviceConfigTlv,
+    MacAddressIndicator, MulticastUpdateStatusCode, PhaseListExtendedMacAddress,
     PhaseListShortMacAddress, PowerStats, ResetConfig, SessionState, SessionType, StatusCode,
     UpdateMulticastListAction,
 };
 
+// GID/OID of the commands rejected while throttled after a retry storm. The commands stopping or
+// deinitializing a session are never throttled.
+const GID_SESSION_CONFIG: u8 = 0x1;
+const OID_SESSION_INIT: u8 = 0x0;
+const OID_SESSION_SET_APP_CONFIG: u8 = 0x3;
+const OID_SESSION_UPDATE_CONTROLLER_MULTICAST_LIST: u8 = 0x7;
+const GID_SESSION_CONTROL: u8 = 0x2;
+const OID_SESSION_START: u8 = 0x0;
+
+// Radar decimation modes of nativeSetRadarDecimation, as defined in NativeUwbManager.
+const RADAR_DECIMATION_NONE: jint = 0;
+const RADAR_DECIMATION_KEEP_EVERY_NTH: jint = 1;
+const RADAR_DECIMATION_AVERAGE: jint = 2;
+
+// Radar drop policies of nativeSetRadarDeliveryBudget, as defined in NativeUwbManager.
+const RADAR_DROP_OLDEST: jint = 0;
+const RADAR_DROP_DECIMATE: jint = 1;
+
+// Range data delivery modes of nativeSetSessionDeliveryPreference, as defined in NativeUwbManager.
+const DELIVERY_IMMEDIATE: jint = 0;
+const DELIVERY_BATCHED: jint = 1;
+const DELIVERY_MAX_RATE: jint = 2;
+
 /// Macro capturing the name of the function calling this macro.
 ///
 /// function_name()! -> &'static str
@@ -90,6 +150,63 @@ fn native_init(env: JNIEnv) -> Result<()> {
     unique_jvm::set_once(jvm)
 }
 
+/// Set the native feature flags. Expected to be called before the dispatcher is created.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetFeatureFlags(
+    env: JNIEnv,
+    _obj: JObject,
+    names_jarray: jobjectArray,
+    values_jarray: jbooleanArray,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_feature_flags(env, names_jarray, values_jarray),
+        function_name!(),
+    )
+}
+
+fn native_set_feature_flags(
+    env: JNIEnv,
+    names_jarray: jobjectArray,
+    values_jarray: jbooleanArray,
+) -> Result<()> {
+    let names_len =
+        env.get_array_length(names_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
+    let values_len =
+        env.get_array_length(values_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
+    if names_len != values_len {
+        return Err(Error::BadParameters);
+    }
+    let names = (0..names_len)
+        .map(|i| env.get_string(env.get_object_array_element(names_jarray, i)?.into()))
+        .collect::<std::result::Result<Vec<_>, JNIError>>()
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let mut values = vec![0; values_len as usize];
+    env.get_boolean_array_region(values_jarray, 0, &mut values)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let new_values = zip(names.into_iter().map(String::from), values.into_iter().map(|v| v != 0))
+        .collect::<Vec<_>>();
+    feature_flags::update(&new_values)
+}
+
+/// Get the state of the native feature flags for dumpsys. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetFeatureFlagsDump(
+    env: JNIEnv,
+    _obj: JObject,
+) -> jstring {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_feature_flags_dump(env), function_name!()) {
+        Some(dump) => dump,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_feature_flags_dump(env: JNIEnv) -> Result<jstring> {
+    let dump = feature_flags::snapshot()?.dump();
+    Ok(env.new_string(dump).map_err(|_| Error::ForeignFunctionInterface)?.into_raw())
+}
+
 fn create_device_info_response(rsp: GetDeviceInfoResponse, env: JNIEnv) -> Result<jobject> {
     let device_info_response_class = env
         .find_class(UWB_DEVICE_INFO_RESPONSE_CLASS)
@@ -142,6 +259,10 @@ fn native_do_initialize(
     obj: JObject,
     chip_id: JString,
 ) -> Result<GetDeviceInfoResponse> {
+    // A proxy of the chip left running would compete with the stack for the HAL.
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    uci_proxy::stop(&chip_id_str)?;
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
     uci_manager.open_hal()
 }
@@ -162,6 +283,108 @@ fn native_do_deinitialize(env: JNIEnv, obj: JObject, chip_id: JString) -> Result
     uci_manager.close_hal(true)
 }
 
+/// Start proxying the HAL of a single UWB chip over a local TCP port, for a host-side instance of
+/// uwb_core. Fails while the dispatcher exists, as the stack may then be using the HAL.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeStartUciProxy(
+    env: JNIEnv,
+    _obj: JObject,
+    port: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_start_uci_proxy(env, port, chip_id), function_name!())
+}
+
+fn native_start_uci_proxy(env: JNIEnv, port: jint, chip_id: JString) -> Result<()> {
+    let port = u16::try_from(port).map_err(|_| Error::BadParameters)?;
+    let chip_id =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    if Dispatcher::get_dispatcher_ptr().is_ok() {
+        error!("UCI JNI: cannot proxy chip {} while the dispatcher exists", chip_id);
+        return Err(Error::BadParameters);
+    }
+    uci_proxy::start(&chip_id, port)
+}
+
+/// Stop proxying the HAL of a single UWB chip. The HAL is closed once this returns.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeStopUciProxy(
+    env: JNIEnv,
+    _obj: JObject,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_stop_uci_proxy(env, chip_id), function_name!())
+}
+
+fn native_stop_uci_proxy(env: JNIEnv, chip_id: JString) -> Result<()> {
+    let chip_id =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    uci_proxy::stop(&chip_id)
+}
+
+/// Start exporting the metrics of all the chips in the Prometheus text format over HTTP on a local
+/// TCP port, replacing the running exporter. Requires the native_metrics_exporter flag.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeStartMetricsExporter(
+    env: JNIEnv,
+    obj: JObject,
+    port: jint,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_start_metrics_exporter(env, obj, port), function_name!())
+}
+
+fn native_start_metrics_exporter(env: JNIEnv, obj: JObject, port: jint) -> Result<()> {
+    if !feature_flags::is_enabled(FeatureFlag::MetricsExporter) {
+        error!("UCI JNI: the metrics exporter is disabled");
+        return Err(Error::BadParameters);
+    }
+    let port = u16::try_from(port).map_err(|_| Error::BadParameters)?;
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    metrics_exporter::start(port, dispatcher.get_metrics_sources())
+}
+
+/// Stop exporting the metrics of the chips.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeStopMetricsExporter(
+    _env: JNIEnv,
+    _obj: JObject,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(metrics_exporter::stop(), function_name!())
+}
+
+/// Get the device info of a single UWB chip, e.g. to revalidate it after a firmware update.
+/// Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetDeviceInfo(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobject {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_device_info(env, obj, chip_id), function_name!()) {
+        Some(rsp) => create_device_info_response(rsp, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_device_info(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<GetDeviceInfoResponse> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.core_get_device_info()
+}
+
 /// Get nanos. Not currently used and returns placeholder value.
 #[no_mangle]
 pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetTimestampResolutionNanos(
@@ -197,11 +420,19 @@ pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSe
     obj: JObject,
     session_id: jint,
     session_type: jbyte,
+    include_raw_ranging_data: jboolean,
     chip_id: JString,
 ) -> jbyte {
     debug!("{}: enter", function_name!());
     byte_result_helper(
-        native_session_init(env, obj, session_id, session_type, chip_id),
+        native_session_init(
+            env,
+            obj,
+            session_id,
+            session_type,
+            include_raw_ranging_data != 0,
+            chip_id,
+        ),
         function_name!(),
     )
 }
@@ -211,12 +442,40 @@ fn native_session_init(
     obj: JObject,
     session_id: jint,
     session_type: jbyte,
+    include_raw_ranging_data: bool,
     chip_id: JString,
 ) -> Result<()> {
+    let session_type_value = session_type as u8;
     let session_type =
-        SessionType::try_from(session_type as u8).map_err(|_| Error::BadParameters)?;
-    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.session_init(session_id as u32, session_type)
+        SessionType::try_from(session_type_value).map_err(|_| Error::BadParameters)?;
+    {
+        let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+        uci_manager.check_retry_throttle(GID_SESSION_CONFIG, OID_SESSION_INIT)?;
+        uci_manager.session_init(session_id as u32, session_type)?;
+        uci_manager.update_session_registry(|registry| {
+            registry.on_session_init(session_id as u32, session_type_value, Instant::now())
+        });
+    }
+    if include_raw_ranging_data {
+        set_raw_ranging_data_included(env, obj, session_id as u32, chip_id);
+    }
+    Ok(())
+}
+
+/// Passes the range data of the session to Java with the raw RANGE_DATA_NTF. The session is
+/// already initialized, so a failure is only logged.
+fn set_raw_ranging_data_included(env: JNIEnv, obj: JObject, session_id: u32, chip_id: JString) {
+    let result = Dispatcher::get_dispatcher(env, obj).and_then(|dispatcher| {
+        let chip_id_str =
+            String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+        dispatcher.set_raw_ranging_data_included(&chip_id_str, session_id, true)
+    });
+    if let Err(e) = result {
+        error!(
+            "UCI JNI: failed to include the raw ranging data of session {}: {:?}",
+            session_id, e
+        );
+    }
 }
 
 /// DeInit the session on a single UWB device. Return value defined by uci_packets.pdl
@@ -280,6 +539,7 @@ fn native_ranging_start(
     chip_id: JString,
 ) -> Result<()> {
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.check_retry_throttle(GID_SESSION_CONTROL, OID_SESSION_START)?;
     uci_manager.range_start(session_id as u32)
 }
 
@@ -473,11 +733,43 @@ fn native_set_app_configurations(
     app_config_params: jbyteArray,
     chip_id: JString,
 ) -> Result<SetAppConfigResponse> {
-    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    let config_byte_array =
-        env.convert_byte_array(app_config_params).map_err(|_| Error::ForeignFunctionInterface)?;
-    let tlvs = parse_app_config_tlv_vec(no_of_params, &config_byte_array)?;
-    uci_manager.session_set_app_config(session_id as u32, tlvs)
+    // The TLVs may carry the session keys and the STS IV.
+    let config_byte_array = SecretBytes::new(
+        env.convert_byte_array(app_config_params).map_err(|_| Error::ForeignFunctionInterface)?,
+    );
+    let response = {
+        let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+        uci_manager.check_retry_throttle(GID_SESSION_CONFIG, OID_SESSION_SET_APP_CONFIG)?;
+        // Parsed right before being moved to uwb_core: AppConfigTlv, as the other parameters of
+        // uwb_core::params, holds its value in a plain Vec, which uwb_core drops without zeroing.
+        let tlvs = parse_app_config_tlv_vec(no_of_params, config_byte_array.as_bytes())?;
+        uci_manager.session_set_app_config(session_id as u32, tlvs)?
+    };
+    if let Some(enabled) = find_diagnostics_enabled(config_byte_array.as_bytes()) {
+        if response.status == StatusCode::UciStatusOk {
+            set_diagnostics_enabled(env, obj, session_id as u32, enabled, chip_id);
+        }
+    }
+    Ok(response)
+}
+
+/// Passes the ranging diagnostics of the session to Java or drops them, as set by
+/// ENABLE_DIAGNOSTICS. The app config is already set, so a failure is only logged.
+fn set_diagnostics_enabled(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    enabled: bool,
+    chip_id: JString,
+) {
+    let result = Dispatcher::get_dispatcher(env, obj).and_then(|dispatcher| {
+        let chip_id_str =
+            String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+        dispatcher.set_diagnostics_enabled(&chip_id_str, session_id, enabled)
+    });
+    if let Err(e) = result {
+        error!("UCI JNI: failed to gate the diagnostics of session {}: {:?}", session_id, e);
+    }
 }
 
 /// Set radar app configurations on a single UWB device. Return null JObject if failed.
@@ -525,6 +817,7 @@ fn native_set_radar_app_configurations(
     let config_byte_array =
         env.convert_byte_array(radar_config_params).map_err(|_| Error::ForeignFunctionInterface)?;
     let tlvs = parse_radar_config_tlv_vec(no_of_params, &config_byte_array)?;
+    validate_radar_config_tlvs(&tlvs)?;
     uci_manager.android_set_radar_config(session_id as u32, tlvs)
 }
 
@@ -723,15 +1016,20 @@ fn create_get_config_response(tlvs: Vec<AppConfigTlv>, env: JNIEnv) -> Result<jb
     let tlv_data_class =
         env.find_class(TLV_DATA_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
     let tlvs_len = tlvs.len();
-    let mut buf = Vec::<u8>::new();
-    for tlv in tlvs.into_iter() {
-        let tlv = tlv.into_inner();
+    // The TLVs may carry the session keys and the STS IV. uwb_core no longer holds them, so their
+    // values are zeroed once copied. The buffer is allocated once, so that no reallocation leaves
+    // a copy behind.
+    let mut tlvs = tlvs.into_iter().map(AppConfigTlv::into_inner).collect::<Vec<_>>();
+    let mut buf = Vec::<u8>::with_capacity(tlvs.iter().map(|tlv| 2 + tlv.v.len()).sum());
+    for tlv in tlvs.iter_mut() {
         buf.push(u8::from(tlv.cfg_id));
         buf.push(tlv.v.len() as u8);
         buf.extend(&tlv.v);
+        zeroize(&mut tlv.v);
     }
+    let buf = SecretBytes::new(buf);
     let tlvs_jbytearray =
-        env.byte_array_from_slice(&buf).map_err(|_| Error::ForeignFunctionInterface)?;
+        env.byte_array_from_slice(buf.as_bytes()).map_err(|_| Error::ForeignFunctionInterface)?;
 
     // Safety: tlvs_jbytearray is safely instantiated above.
     let tlvs_jobject = unsafe { JObject::from_raw(tlvs_jbytearray) };
@@ -912,7 +1210,7 @@ pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeCo
     obj: JObject,
     session_id: jint,
     action: jbyte,
-    no_of_controlee: jbyte,
+    no_of_controlee: jint,
     addresses: jbyteArray,
     sub_session_ids: jintArray,
     sub_session_keys: jbyteArray,
@@ -954,7 +1252,7 @@ fn native_controller_multicast_list_update(
     obj: JObject,
     session_id: jint,
     action: jbyte,
-    no_of_controlee: jbyte,
+    no_of_controlee: jint,
     addresses: jbyteArray,
     sub_session_ids: jintArray,
     sub_session_keys: jbyteArray,
@@ -963,6 +1261,8 @@ fn native_controller_multicast_list_update(
     is_multicast_list_rsp_v2_supported: jboolean,
 ) -> Result<SessionUpdateControllerMulticastResponse> {
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager
+        .check_retry_throttle(GID_SESSION_CONFIG, OID_SESSION_UPDATE_CONTROLLER_MULTICAST_LIST)?;
 
     let addresses_bytes =
         env.convert_byte_array(addresses).map_err(|_| Error::ForeignFunctionInterface)?;
@@ -984,6 +1284,7 @@ fn native_controller_multicast_list_update(
     {
         return Err(Error::BadParameters);
     }
+    let short_addresses = address_list.clone();
     let controlee_list = match UpdateMulticastListAction::try_from(action as u8)
         .map_err(|_| Error::BadParameters)?
     {
@@ -1005,9 +1306,12 @@ fn native_controller_multicast_list_update(
                 Controlees::ShortSessionKey(
                     zip(
                         zip(address_list, sub_session_id_list),
-                        env.convert_byte_array(sub_session_keys)
-                            .map_err(|_| Error::ForeignFunctionInterface)?
-                            .chunks(16),
+                        SecretBytes::new(
+                            env.convert_byte_array(sub_session_keys)
+                                .map_err(|_| Error::ForeignFunctionInterface)?,
+                        )
+                        .as_bytes()
+                        .chunks(16),
                     )
                     .map(|((address, id), key)| {
                         Ok(Controlee_V2_0_16_Byte_Version {
@@ -1031,9 +1335,12 @@ fn native_controller_multicast_list_update(
                 Controlees::LongSessionKey(
                     zip(
                         zip(address_list, sub_session_id_list),
-                        env.convert_byte_array(sub_session_keys)
-                            .map_err(|_| Error::ForeignFunctionInterface)?
-                            .chunks(32),
+                        SecretBytes::new(
+                            env.convert_byte_array(sub_session_keys)
+                                .map_err(|_| Error::ForeignFunctionInterface)?,
+                        )
+                        .as_bytes()
+                        .chunks(32),
                     )
                     .map(|((address, id), key)| {
                         Ok(Controlee_V2_0_32_Byte_Version {
@@ -1047,13 +1354,54 @@ fn native_controller_multicast_list_update(
             }
         }
     };
-    uci_manager.session_update_controller_multicast_list(
-        session_id as u32,
-        UpdateMulticastListAction::try_from(action as u8).map_err(|_| Error::BadParameters)?,
-        controlee_list,
-        is_multicast_list_ntf_v2_supported != 0,
-        is_multicast_list_rsp_v2_supported != 0,
-    )
+    let action =
+        UpdateMulticastListAction::try_from(action as u8).map_err(|_| Error::BadParameters)?;
+    // The sub-session keys are copied into the controlees of uwb_core::params, which uwb_core
+    // drops without zeroing, like the batches split from them.
+    let send = |controlee_list| {
+        uci_manager.session_update_controller_multicast_list(
+            session_id as u32,
+            action,
+            controlee_list,
+            is_multicast_list_ntf_v2_supported != 0,
+            is_multicast_list_rsp_v2_supported != 0,
+        )
+    };
+    // Only the additions are split, as their result is complete in the response, when the UWBS
+    // reports the per-controlee statuses in it. The other updates are confirmed by a single
+    // notification, which the service waits for.
+    let response = if feature_flags::is_enabled(FeatureFlag::ControleeBatching)
+        && is_multicast_list_rsp_v2_supported != 0
+        && action != UpdateMulticastListAction::RemoveControlee
+    {
+        controlee_batching::update_multicast_list_by_batches(controlee_list, send)?
+    } else {
+        send(controlee_list)?
+    };
+    // On failure, the response lists the controlees which were not updated, if it lists any.
+    // The aggregated response of several batches also lists the updated ones.
+    let updated_addresses =
+        match response.status {
+            StatusCode::UciStatusOk => short_addresses,
+            _ if response.status_list.is_empty() => vec![],
+            _ => short_addresses
+                .into_iter()
+                .filter(|address| {
+                    response.status_list.iter().filter(|cs| cs.mac_address == *address).all(|cs| {
+                        cs.status == MulticastUpdateStatusCode::StatusOkMulticastListUpdate
+                    })
+                })
+                .collect(),
+        };
+    uci_manager.update_session_registry(|registry| {
+        registry.on_controlees_updated(
+            session_id as u32,
+            action != UpdateMulticastListAction::RemoveControlee,
+            &updated_addresses,
+            Instant::now(),
+        )
+    });
+    Ok(response)
 }
 
 /// Set country code on a single UWB device. Return value defined by uci_packets.pdl
@@ -1086,6 +1434,92 @@ fn native_set_country_code(
     )
 }
 
+/// Get a device config of a single UWB device with CORE_GET_CONFIG. Returns its value, or -1 if
+/// failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetDeviceConfig(
+    env: JNIEnv,
+    obj: JObject,
+    config_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    option_result_helper(native_get_device_config(env, obj, config_id, chip_id), function_name!())
+        .unwrap_or(-1)
+}
+
+fn native_get_device_config(
+    env: JNIEnv,
+    obj: JObject,
+    config_id: jint,
+    chip_id: JString,
+) -> Result<i32> {
+    let cfg_id = u8::try_from(config_id)
+        .ok()
+        .and_then(|config_id| DeviceConfigId::try_from(config_id).ok())
+        .ok_or(Error::BadParameters)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let tlvs = uci_manager.core_get_config(vec![cfg_id])?;
+    let tlv = tlvs.iter().find(|tlv| tlv.cfg_id == cfg_id).ok_or(Error::Unknown)?;
+    Ok(DeviceConfig::from_tlv(tlv)?.value())
+}
+
+/// Set a batch of device configs of a single UWB device with CORE_SET_CONFIG, entirely or not at
+/// all. Returns the status of each config, or a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetDeviceConfigs(
+    env: JNIEnv,
+    obj: JObject,
+    config_ids_jarray: jintArray,
+    values_jarray: jintArray,
+    chip_id: JString,
+) -> jbyteArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_set_device_configs(env, obj, config_ids_jarray, values_jarray, chip_id),
+        function_name!(),
+    ) {
+        Some(statuses) => {
+            let statuses = statuses.into_iter().map(u8::from).collect::<Vec<_>>();
+            env.byte_array_from_slice(&statuses).unwrap_or_else(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                *JObject::null()
+            })
+        }
+        None => *JObject::null(),
+    }
+}
+
+fn native_set_device_configs(
+    env: JNIEnv,
+    obj: JObject,
+    config_ids_jarray: jintArray,
+    values_jarray: jintArray,
+    chip_id: JString,
+) -> Result<Vec<StatusCode>> {
+    let config_ids = get_int_array(env, config_ids_jarray)?;
+    let values = get_int_array(env, values_jarray)?;
+    if config_ids.len() != values.len() {
+        return Err(Error::BadParameters);
+    }
+    let configs = zip(config_ids, values)
+        .map(|(config_id, value)| {
+            let cfg_id = u8::try_from(config_id)
+                .ok()
+                .and_then(|config_id| DeviceConfigId::try_from(config_id).ok())
+                .ok_or(Error::BadParameters)?;
+            let value = u8::try_from(value).map_err(|_| Error::BadParameters)?;
+            DeviceConfig::from_tlv(&DeviceConfigTlv { cfg_id, v: vec![value] })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    set_device_configs(
+        &configs,
+        |config_ids| uci_manager.core_get_config(config_ids),
+        |tlvs| uci_manager.core_set_config(tlvs),
+    )
+}
+
 /// Set log mode.
 #[no_mangle]
 pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetLogMode(
@@ -1107,6 +1541,94 @@ fn native_set_log_mode(env: JNIEnv, obj: JObject, log_mode_jstring: JString) ->
     dispatcher.set_logger_mode(logger_mode)
 }
 
+/// Set log mode of a single chip.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetLogModeForChip(
+    env: JNIEnv,
+    obj: JObject,
+    log_mode_jstring: JString,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_log_mode_for_chip(env, obj, log_mode_jstring, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_log_mode_for_chip(
+    env: JNIEnv,
+    obj: JObject,
+    log_mode_jstring: JString,
+    chip_id: JString,
+) -> Result<()> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let logger_mode_str = String::from(
+        env.get_string(log_mode_jstring).map_err(|_| Error::ForeignFunctionInterface)?,
+    );
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    debug!("UCI log: log started in {} mode for chip {}", &logger_mode_str, &chip_id_str);
+    let logger_mode = logger_mode_str.try_into()?;
+    dispatcher.set_chip_logger_mode(&chip_id_str, logger_mode)
+}
+
+/// Set which UCI packets of the chip are captured in the pcapng log.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetUciCapture(
+    env: JNIEnv,
+    obj: JObject,
+    mode: jint,
+    session_tokens: jintArray,
+    gids: jintArray,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_uci_capture(env, obj, mode, session_tokens, gids, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_uci_capture(
+    env: JNIEnv,
+    obj: JObject,
+    mode: jint,
+    session_tokens: jintArray,
+    gids: jintArray,
+    chip_id: JString,
+) -> Result<()> {
+    let mode = UciCaptureMode::try_from(mode)?;
+    let session_tokens = get_int_array(env, session_tokens)?;
+    let gids = get_int_array(env, gids)?
+        .into_iter()
+        .map(|gid| u8::try_from(gid).ok().filter(|gid| *gid <= 0x0f).ok_or(Error::BadParameters))
+        .collect::<Result<HashSet<_>>>()?;
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    debug!("UCI log: capture set to {:?} for chip {}", mode, &chip_id_str);
+    dispatcher.set_uci_capture(
+        &chip_id_str,
+        mode,
+        session_tokens.into_iter().map(|token| token as u32).collect(),
+        gids,
+    )
+}
+
+fn get_int_array(env: JNIEnv, jarray: jintArray) -> Result<Vec<i32>> {
+    let mut values = vec![
+        0i32;
+        env.get_array_length(jarray)
+            .map_err(|_| Error::ForeignFunctionInterface)?
+            .try_into()
+            .map_err(|_| Error::BadParameters)?
+    ];
+    env.get_int_array_region(jarray, 0, &mut values)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    Ok(values)
+}
+
 // # Safety
 //
 // For this to be safe, the validity of msg should be checked before calling.
@@ -1236,47 +1758,1783 @@ fn native_send_raw_vendor_cmd(
     uci_manager.raw_uci_cmd(mt as u32, gid as u32, oid as u32, payload)
 }
 
-fn create_power_stats(power_stats: PowerStats, env: JNIEnv) -> Result<jobject> {
-    let power_stats_class =
-        env.find_class(POWER_STATS_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
-    match env.new_object(
-        power_stats_class,
-        "(IIII)V",
-        &[
-            JValue::Int(power_stats.idle_time_ms as i32),
-            JValue::Int(power_stats.tx_time_ms as i32),
-            JValue::Int(power_stats.rx_time_ms as i32),
-            JValue::Int(power_stats.total_wake_count as i32),
-        ],
-    ) {
-        Ok(o) => Ok(*o),
-        Err(_) => Err(Error::ForeignFunctionInterface),
-    }
-}
-
-/// Get UWB power stats on a single UWB device. Returns a null object if failed.
+/// Set the calibration of a channel and an antenna on the chip with ANDROID_SET_CALIBRATION. The
+/// calibration accepted by the chip is persisted and applied again by nativeApplyCalibration.
+/// Return value defined by uci_packets.pdl.
 #[no_mangle]
-pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetPowerStats(
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetCalibration(
     env: JNIEnv,
     obj: JObject,
+    channel: jint,
+    antenna_id: jint,
+    version: jint,
+    payload: jbyteArray,
     chip_id: JString,
-) -> jobject {
+) -> jbyte {
     debug!("{}: enter", function_name!());
-    match option_result_helper(native_get_power_stats(env, obj, chip_id), function_name!()) {
-        Some(ps) => create_power_stats(ps, env)
-            .map_err(|e| {
-                error!("{} failed with {:?}", function_name!(), &e);
-                e
-            })
-            .unwrap_or(*JObject::null()),
-        None => *JObject::null(),
+    match native_set_calibration(env, obj, channel, antenna_id, version, payload, chip_id) {
+        Ok(status) => u8::from(status) as jbyte,
+        Err(e) => byte_result_helper::<()>(Err(e), function_name!()),
     }
 }
 
-fn native_get_power_stats(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<PowerStats> {
-    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.android_get_power_stats()
-}
+fn native_set_calibration(
+    env: JNIEnv,
+    obj: JObject,
+    channel: jint,
+    antenna_id: jint,
+    version: jint,
+    payload: jbyteArray,
+    chip_id: JString,
+) -> Result<StatusCode> {
+    let calibration = Calibration::new(
+        u8::try_from(channel).map_err(|_| Error::BadParameters)?,
+        u8::try_from(antenna_id).map_err(|_| Error::BadParameters)?,
+        u16::try_from(version).map_err(|_| Error::BadParameters)?,
+        env.convert_byte_array(payload).map_err(|_| Error::ForeignFunctionInterface)?,
+    )?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.set_calibration(calibration)
+}
+
+/// Apply the calibration of the chip again, after the chip was opened or reset. Return false if
+/// any calibration was not applied.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeApplyCalibration(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_apply_calibration(env, obj, chip_id), function_name!())
+}
+
+fn native_apply_calibration(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.apply_calibration()
+}
+
+/// Set the OID of a vendor command of the ANDROID GID on the chip, as defined by the vendor of the
+/// chip.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetVendorCommandOid(
+    env: JNIEnv,
+    obj: JObject,
+    command: jint,
+    oid: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_vendor_command_oid(env, obj, command, oid, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_vendor_command_oid(
+    env: JNIEnv,
+    obj: JObject,
+    command: jint,
+    oid: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let command = VendorCommand::try_from(command)?;
+    let oid = oid.try_into().map_err(|_| Error::BadParameters)?;
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_vendor_command_oid(&chip_id_str, command, Some(oid))
+}
+
+/// Set which raw UCI messages may be injected by nativeInjectUciMessage: any GID if the build is
+/// debuggable, otherwise only the allowlisted GIDs.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetUciInjectionPolicy(
+    env: JNIEnv,
+    _obj: JObject,
+    debuggable: jboolean,
+    allowed_gids_jarray: jintArray,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_uci_injection_policy(env, debuggable, allowed_gids_jarray),
+        function_name!(),
+    )
+}
+
+fn native_set_uci_injection_policy(
+    env: JNIEnv,
+    debuggable: jboolean,
+    allowed_gids_jarray: jintArray,
+) -> Result<()> {
+    let allowed_gids_len =
+        env.get_array_length(allowed_gids_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
+    let mut allowed_gids = vec![0; allowed_gids_len as usize];
+    env.get_int_array_region(allowed_gids_jarray, 0, &mut allowed_gids)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let allowed_gids = allowed_gids.into_iter().map(|gid| gid as u32).collect::<Vec<_>>();
+    uci_injection::set_policy(InjectionPolicy::new(debuggable != 0, &allowed_gids))
+}
+
+/// Inject a raw UCI message on a single UWB device, bypassing the typed builders, if the injection
+/// policy allows its GID. Returns the response of the UWBS.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeInjectUciMessage(
+    env: JNIEnv,
+    obj: JObject,
+    mt: jint,
+    gid: jint,
+    oid: jint,
+    payload_jarray: jbyteArray,
+    chip_id: JString,
+) -> jobject {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_inject_uci_message(env, obj, mt, gid, oid, payload_jarray, chip_id),
+        function_name!(),
+    ) {
+        // Safety: create_vendor_response is unsafe, however msg is safely returned from
+        // native_inject_uci_message.
+        Some(msg) => unsafe {
+            create_vendor_response(msg, env)
+                .map_err(|e| {
+                    error!("{} failed with {:?}", function_name!(), &e);
+                    e
+                })
+                .unwrap_or_else(|_| create_invalid_vendor_response(env).unwrap())
+        },
+        None => create_invalid_vendor_response(env).unwrap(),
+    }
+}
+
+fn native_inject_uci_message(
+    env: JNIEnv,
+    obj: JObject,
+    mt: jint,
+    gid: jint,
+    oid: jint,
+    payload_jarray: jbyteArray,
+    chip_id: JString,
+) -> Result<RawUciMessage> {
+    uci_injection::check(gid as u32)?;
+    native_send_raw_vendor_cmd(env, obj, mt, gid, oid, payload_jarray, chip_id)
+}
+
+fn create_power_stats(power_stats: PowerStats, env: JNIEnv) -> Result<jobject> {
+    let power_stats_class =
+        env.find_class(POWER_STATS_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    match env.new_object(
+        power_stats_class,
+        "(IIII)V",
+        &[
+            JValue::Int(power_stats.idle_time_ms as i32),
+            JValue::Int(power_stats.tx_time_ms as i32),
+            JValue::Int(power_stats.rx_time_ms as i32),
+            JValue::Int(power_stats.total_wake_count as i32),
+        ],
+    ) {
+        Ok(o) => Ok(*o),
+        Err(_) => Err(Error::ForeignFunctionInterface),
+    }
+}
+
+/// Get UWB power stats on a single UWB device. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetPowerStats(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobject {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_power_stats(env, obj, chip_id), function_name!()) {
+        Some(ps) => create_power_stats(ps, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_power_stats(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<PowerStats> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.android_get_power_stats()
+}
+
+fn create_notification_stats(stats: NotificationStats, env: JNIEnv) -> Result<jobjectArray> {
+    let notification_stats_class =
+        env.find_class(NOTIFICATION_STATS_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let stats_jobjectarray = env
+        .new_object_array(CallbackType::ALL.len() as i32, notification_stats_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, &callback_type) in CallbackType::ALL.iter().enumerate() {
+        let callback_stats = stats.get(callback_type);
+        // Java do not support unsigned, the counters will not overflow i64 in practice.
+        let histogram =
+            callback_stats.latency_histogram.iter().map(|&c| c as i64).collect::<Vec<_>>();
+        let histogram_jlongarray = env
+            .new_long_array(histogram.len() as i32)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_long_array_region(histogram_jlongarray, 0, &histogram)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        // Safety: histogram_jlongarray is safely instantiated above.
+        let histogram_jobject = unsafe { JObject::from_raw(histogram_jlongarray) };
+        let callback_stats_jobject = env
+            .new_object(
+                notification_stats_class,
+                "(IJJJ[J)V",
+                &[
+                    JValue::Int(callback_type as i32),
+                    JValue::Long(callback_stats.count as i64),
+                    JValue::Long(callback_stats.failure_count as i64),
+                    JValue::Long(callback_stats.max_latency_us as i64),
+                    JValue::Object(histogram_jobject),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(stats_jobjectarray, i as i32, callback_stats_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(stats_jobjectarray)
+}
+
+/// Get the notification dispatch statistics of a single UWB device, one entry per callback type.
+/// Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetNotificationStats(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_notification_stats(env, obj, chip_id), function_name!()) {
+        Some(stats) => create_notification_stats(stats, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_notification_stats(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<NotificationStats> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.get_notification_stats(&chip_id_str)
+}
+
+fn create_uci_metrics(
+    metrics: Vec<(u8, u8, UciCommandStats)>,
+    env: JNIEnv,
+) -> Result<jobjectArray> {
+    let command_stats_class =
+        env.find_class(UCI_COMMAND_STATS_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let metrics_jobjectarray = env
+        .new_object_array(metrics.len() as i32, command_stats_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, (gid, oid, stats)) in metrics.into_iter().enumerate() {
+        // Java do not support unsigned, the counters will not overflow i64 in practice.
+        let command_stats_jobject = env
+            .new_object(
+                command_stats_class,
+                "(IIJJJJJJJ)V",
+                &[
+                    JValue::Int(gid as i32),
+                    JValue::Int(oid as i32),
+                    JValue::Long(stats.command_count as i64),
+                    JValue::Long(stats.response_count as i64),
+                    JValue::Long(stats.retry_count as i64),
+                    JValue::Long(stats.timeout_count as i64),
+                    JValue::Long(stats.total_latency_us as i64),
+                    JValue::Long(stats.max_latency_us as i64),
+                    JValue::Long(stats.storm_count as i64),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(metrics_jobjectarray, i as i32, command_stats_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(metrics_jobjectarray)
+}
+
+/// Get the UCI command round-trip metrics of a single UWB device, one entry per GID/OID.
+/// Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetUciMetrics(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_uci_metrics(env, obj, chip_id), function_name!()) {
+        Some(metrics) => create_uci_metrics(metrics, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_uci_metrics(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Vec<(u8, u8, UciCommandStats)>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.get_uci_metrics(&chip_id_str)
+}
+
+fn create_hal_trace(transactions: Vec<HalTransaction>, env: JNIEnv) -> Result<jobjectArray> {
+    let hal_transaction_class =
+        env.find_class(HAL_TRANSACTION_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let transactions_jobjectarray = env
+        .new_object_array(transactions.len() as i32, hal_transaction_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let now = Instant::now();
+    // The durations not measured are passed as -1.
+    let duration_us = |duration: Option<Duration>| {
+        duration.map(|d| i64::try_from(d.as_micros()).unwrap_or(i64::MAX)).unwrap_or(-1)
+    };
+    for (i, transaction) in transactions.into_iter().enumerate() {
+        let age = now.saturating_duration_since(transaction.sent_at);
+        let hal_transaction_jobject = env
+            .new_object(
+                hal_transaction_class,
+                "(IIIJJJIJ)V",
+                &[
+                    JValue::Int(transaction.correlation_id as i32),
+                    JValue::Int(transaction.gid as i32),
+                    JValue::Int(transaction.oid as i32),
+                    JValue::Long(duration_us(Some(age))),
+                    JValue::Long(duration_us(transaction.hal_send_duration)),
+                    JValue::Long(duration_us(transaction.response_latency)),
+                    JValue::Int(transaction.notification_count as i32),
+                    JValue::Long(duration_us(transaction.first_notification_latency)),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(transactions_jobjectarray, i as i32, hal_transaction_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(transactions_jobjectarray)
+}
+
+/// Get the last UCI transactions exchanged with the HAL of a single UWB device, oldest first.
+/// Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetHalTrace(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_hal_trace(env, obj, chip_id), function_name!()) {
+        Some(transactions) => create_hal_trace(transactions, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_hal_trace(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Vec<HalTransaction>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.get_hal_trace(&chip_id_str)
+}
+
+fn create_parse_error_stats(counts: Vec<(&'static str, u64)>, env: JNIEnv) -> Result<jobjectArray> {
+    let parse_error_stats_class =
+        env.find_class(PARSE_ERROR_STATS_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let counts_jobjectarray = env
+        .new_object_array(counts.len() as i32, parse_error_stats_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, (family, count)) in counts.into_iter().enumerate() {
+        let family_jobject = env.new_string(family).map_err(|_| Error::ForeignFunctionInterface)?;
+        let parse_error_stats_jobject = env
+            .new_object(
+                parse_error_stats_class,
+                "(Ljava/lang/String;J)V",
+                &[JValue::Object(*family_jobject), JValue::Long(count as i64)],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(counts_jobjectarray, i as i32, parse_error_stats_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(counts_jobjectarray)
+}
+
+/// Fetch and clear the notification conversion failures of a single UWB device counted since the
+/// previous call, one entry per packet family. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeFetchAndClearParseErrorStats(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_fetch_and_clear_parse_error_stats(env, obj, chip_id),
+        function_name!(),
+    ) {
+        Some(counts) => create_parse_error_stats(counts, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_fetch_and_clear_parse_error_stats(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Vec<(&'static str, u64)>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.fetch_and_clear_parse_error_stats(&chip_id_str)
+}
+
+fn create_session_records(
+    sessions: Vec<(u32, SessionRecord)>,
+    env: JNIEnv,
+) -> Result<jobjectArray> {
+    let session_record_class =
+        env.find_class(SESSION_RECORD_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let sessions_jobjectarray = env
+        .new_object_array(sessions.len() as i32, session_record_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let now = Instant::now();
+    for (i, (session_id, record)) in sessions.into_iter().enumerate() {
+        let ms_since_last_activity =
+            now.saturating_duration_since(record.last_activity).as_millis();
+        let session_record_jobject = env
+            .new_object(
+                session_record_class,
+                "(IIIIJJJJ)V",
+                &[
+                    JValue::Int(session_id as i32),
+                    JValue::Int(record.session_type as i32),
+                    // -1 until the state is notified by the UWBS.
+                    JValue::Int(record.session_state.map_or(-1, |state| state as i32)),
+                    JValue::Int(record.controlees.len() as i32),
+                    JValue::Long(ms_since_last_activity.try_into().unwrap_or(i64::MAX)),
+                    JValue::Long(record.data_rcv_jitter.packet_count as i64),
+                    JValue::Long(record.data_rcv_jitter.mean_interval().as_micros() as i64),
+                    JValue::Long(record.data_rcv_jitter.jitter().as_micros() as i64),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(sessions_jobjectarray, i as i32, session_record_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(sessions_jobjectarray)
+}
+
+/// List the sessions of a single UWB device known to the host, from their initialization until
+/// their deinitialization. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeListSessions(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_list_sessions(env, obj, chip_id), function_name!()) {
+        Some(sessions) => create_session_records(sessions, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_list_sessions(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Vec<(u32, SessionRecord)>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.list_sessions(&chip_id_str)
+}
+
+/// Compares the sessions of a single UWB device known to the host with the UWBS's view of them,
+/// as queried by SESSION_GET_COUNT and SESSION_GET_STATE. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeCheckSessions(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_check_sessions(env, obj, chip_id), function_name!()) {
+        Some(mismatches) => create_session_mismatches(mismatches, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_check_sessions(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Vec<SessionMismatch>> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    // Snapshot the registry, so that it is not locked while waiting for the UWBS.
+    let mut sessions = vec![];
+    uci_manager.update_session_registry(|registry| sessions = registry.list());
+    let uwbs_session_count = uci_manager.session_get_count()?;
+    Ok(find_session_mismatches(&sessions, uwbs_session_count, |session_id| {
+        uci_manager.session_get_state(session_id).ok().map(|state| state as u8)
+    }))
+}
+
+fn create_session_mismatches(
+    mismatches: Vec<SessionMismatch>,
+    env: JNIEnv,
+) -> Result<jobjectArray> {
+    let session_mismatch_class =
+        env.find_class(SESSION_MISMATCH_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let mismatches_jobjectarray = env
+        .new_object_array(mismatches.len() as i32, session_mismatch_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, mismatch) in mismatches.into_iter().enumerate() {
+        // Types as defined by UwbSessionMismatch.
+        let (mismatch_type, session_id, host_value, uwbs_value) = match mismatch {
+            SessionMismatch::Count { host, uwbs } => (0, 0, host as i32, uwbs as i32),
+            SessionMismatch::UnknownSession { session_id } => (1, session_id as i32, 0, 0),
+            SessionMismatch::State { session_id, host, uwbs } => {
+                (2, session_id as i32, host as i32, uwbs as i32)
+            }
+        };
+        let session_mismatch_jobject = env
+            .new_object(
+                session_mismatch_class,
+                "(IIII)V",
+                &[
+                    JValue::Int(mismatch_type),
+                    JValue::Int(session_id),
+                    JValue::Int(host_value),
+                    JValue::Int(uwbs_value),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(mismatches_jobjectarray, i as i32, session_mismatch_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(mismatches_jobjectarray)
+}
+
+fn create_session_token_mappings(
+    mappings: Vec<SessionTokenMapping>,
+    env: JNIEnv,
+) -> Result<jobjectArray> {
+    let session_token_mapping_class =
+        env.find_class(SESSION_TOKEN_MAPPING_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let mappings_jobjectarray = env
+        .new_object_array(mappings.len() as i32, session_token_mapping_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let now = Instant::now();
+    for (i, mapping) in mappings.into_iter().enumerate() {
+        let ms_since_mapped = now.saturating_duration_since(mapping.mapped_at).as_millis();
+        let session_token_mapping_jobject = env
+            .new_object(
+                session_token_mapping_class,
+                "(IIZIJ)V",
+                &[
+                    JValue::Int(mapping.session_token as i32),
+                    JValue::Int(mapping.session_id as i32),
+                    JValue::Bool(mapping.previous_session_id.is_some() as u8),
+                    JValue::Int(mapping.previous_session_id.unwrap_or(0) as i32),
+                    JValue::Long(ms_since_mapped.try_into().unwrap_or(i64::MAX)),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(
+            mappings_jobjectarray,
+            i as i32,
+            session_token_mapping_jobject,
+        )
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(mappings_jobjectarray)
+}
+
+/// Lists the session tokens of a single UWB device mapped to the sessions not deinitialized, as
+/// learned from the SESSION_STATUS_NTF. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSessionTokenMappings(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_session_token_mappings(env, obj, chip_id),
+        function_name!(),
+    ) {
+        Some(mappings) => create_session_token_mappings(mappings, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_session_token_mappings(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Vec<SessionTokenMapping>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.list_session_token_mappings(&chip_id_str)
+}
+
+/// Lists the last session tokens of a single UWB device which the UWBS reused for another
+/// session, oldest first. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSessionTokenRemaps(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_session_token_remaps(env, obj, chip_id), function_name!())
+    {
+        Some(remaps) => create_session_token_mappings(remaps, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_session_token_remaps(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Vec<SessionTokenMapping>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.list_session_token_remaps(&chip_id_str)
+}
+
+fn create_owr_aoa_advertiser_roster(
+    roster: Vec<AdvertiserState>,
+    env: JNIEnv,
+) -> Result<jobjectArray> {
+    let advertiser_info_class = env
+        .find_class(OWR_AOA_ADVERTISER_INFO_CLASS)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let roster_jobjectarray = env
+        .new_object_array(roster.len() as i32, advertiser_info_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let now = Instant::now();
+    for (i, advertiser) in roster.into_iter().enumerate() {
+        let mac_address_jbytearray = env
+            .byte_array_from_slice(&advertiser.mac_address)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        // Safety: mac_address_jbytearray is safely instantiated above.
+        let mac_address_jobject = unsafe { JObject::from_raw(mac_address_jbytearray) };
+        let ms_since_last_seen = now.saturating_duration_since(advertiser.last_seen).as_millis();
+        let advertiser_info_jobject = env
+            .new_object(
+                advertiser_info_class,
+                "([BIIJJJJ)V",
+                &[
+                    JValue::Object(mac_address_jobject),
+                    JValue::Int(advertiser.last_block_index as i32),
+                    JValue::Int(advertiser.last_frame_sequence_number as i32),
+                    JValue::Long(advertiser.stats.frame_count as i64),
+                    JValue::Long(advertiser.stats.missed_frame_count as i64),
+                    JValue::Long(advertiser.stats.out_of_order_count as i64),
+                    JValue::Long(ms_since_last_seen.try_into().unwrap_or(i64::MAX)),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(roster_jobjectarray, i as i32, advertiser_info_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(roster_jobjectarray)
+}
+
+/// Get the OWR AoA advertisers currently heard by an observer session of a single UWB device.
+/// Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetOwrAoaAdvertiserRoster(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_owr_aoa_advertiser_roster(env, obj, session_id as u32, chip_id),
+        function_name!(),
+    ) {
+        Some(roster) => create_owr_aoa_advertiser_roster(roster, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_owr_aoa_advertiser_roster(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    chip_id: JString,
+) -> Result<Vec<AdvertiserState>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.get_advertiser_roster(&chip_id_str, session_id)
+}
+
+/// Register a session whose range data are only reported as near/far edges through
+/// onProximityEvent, or unregister it. Return false if the proximity fast path is disabled.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetProximityOnlySession(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    enabled: jboolean,
+    near_cm: jint,
+    far_cm: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_proximity_only_session(
+            env,
+            obj,
+            session_id as u32,
+            enabled != 0,
+            near_cm,
+            far_cm,
+            chip_id,
+        ),
+        function_name!(),
+    )
+}
+
+fn native_set_proximity_only_session(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    enabled: bool,
+    near_cm: jint,
+    far_cm: jint,
+    chip_id: JString,
+) -> Result<()> {
+    if !feature_flags::is_enabled(FeatureFlag::ProximityFastPath) {
+        return Err(Error::BadParameters);
+    }
+    let config = match enabled {
+        true => Some(ProximityConfig::new(
+            near_cm.try_into().map_err(|_| Error::BadParameters)?,
+            far_cm.try_into().map_err(|_| Error::BadParameters)?,
+        )?),
+        false => None,
+    };
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_proximity_session(&chip_id_str, session_id, config)
+}
+
+/// Set the vendor notification carrying the firmware logs of the chip, which are written to the
+/// firmware log file and/or streamed through onFirmwareLogReceived instead of being passed to the
+/// vendor callback. Firmware logs are not separated anymore when both are false.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetFirmwareLog(
+    env: JNIEnv,
+    obj: JObject,
+    gid: jint,
+    oid: jint,
+    write_file: jboolean,
+    stream: jboolean,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_firmware_log(env, obj, gid, oid, write_file != 0, stream != 0, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_firmware_log(
+    env: JNIEnv,
+    obj: JObject,
+    gid: jint,
+    oid: jint,
+    write_file: bool,
+    stream: bool,
+    chip_id: JString,
+) -> Result<()> {
+    let config = if write_file || stream {
+        Some(FirmwareLogConfig {
+            gid: gid.try_into().map_err(|_| Error::BadParameters)?,
+            oid: oid.try_into().map_err(|_| Error::BadParameters)?,
+            write_file,
+            stream,
+        })
+    } else {
+        None
+    };
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_firmware_log(&chip_id_str, config)
+}
+
+/// Route the vendor notifications of a GID of the chip to the Java callback `callback_name` of the
+/// callback object, called with their OID, payload and chip ID. A null name routes them back to
+/// onVendorUciNotificationReceived.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetVendorNotificationCallback(
+    env: JNIEnv,
+    obj: JObject,
+    gid: jint,
+    callback_name: JString,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_vendor_notification_callback(env, obj, gid, callback_name, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_vendor_notification_callback(
+    env: JNIEnv,
+    obj: JObject,
+    gid: jint,
+    callback_name: JString,
+    chip_id: JString,
+) -> Result<()> {
+    let gid = gid.try_into().map_err(|_| Error::BadParameters)?;
+    let callback_name = if callback_name.is_null() {
+        None
+    } else {
+        Some(String::from(
+            env.get_string(callback_name).map_err(|_| Error::ForeignFunctionInterface)?,
+        ))
+    };
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_vendor_notification_callback(&chip_id_str, gid, callback_name)
+}
+
+/// Set whether the SESSION_INFO_NTFs of a session are dropped in the native stack instead of being
+/// passed to Java.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetSessionInfoSuppressed(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    suppressed: jboolean,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_session_info_suppressed(env, obj, session_id as u32, suppressed != 0, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_session_info_suppressed(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    suppressed: bool,
+    chip_id: JString,
+) -> Result<()> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_session_info_suppressed(&chip_id_str, session_id, suppressed)
+}
+
+/// Set whether the range data of all the sessions are passed to Java with the raw
+/// RANGE_DATA_NTF, whatever was set at their init, to debug them.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRawRangingDataForAllSessions(
+    env: JNIEnv,
+    obj: JObject,
+    included: jboolean,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_raw_ranging_data_for_all_sessions(env, obj, included != 0, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_raw_ranging_data_for_all_sessions(
+    env: JNIEnv,
+    obj: JObject,
+    included: bool,
+    chip_id: JString,
+) -> Result<()> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_raw_ranging_data_included_for_all(&chip_id_str, included)
+}
+
+/// Set whether the range data of a session are coarsened in the native stack before being passed
+/// to Java: the distances are quantized to `distance_step_cm` and the angles are stripped.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetCoarseLocation(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    enabled: jboolean,
+    distance_step_cm: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_coarse_location(
+            env,
+            obj,
+            session_id as u32,
+            enabled != 0,
+            distance_step_cm,
+            chip_id,
+        ),
+        function_name!(),
+    )
+}
+
+fn native_set_coarse_location(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    enabled: bool,
+    distance_step_cm: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let distance_step_cm = match enabled {
+        true => Some(match u16::try_from(distance_step_cm) {
+            Ok(distance_step_cm) if distance_step_cm > 0 => distance_step_cm,
+            _ => return Err(Error::BadParameters),
+        }),
+        false => None,
+    };
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_coarse_location(&chip_id_str, session_id, distance_step_cm)
+}
+
+/// Simulate the departure of a controlee of a session, or its return, for the apps to test their
+/// handling of the peer loss: the measurements of the controlee are omitted from the range data
+/// of the session while it is departed. The departure ends when the session is deinitialized.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSimulateControleeDeparture(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mac_address: jbyteArray,
+    departed: jboolean,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_simulate_controlee_departure(
+            env,
+            obj,
+            session_id as u32,
+            mac_address,
+            departed != 0,
+            chip_id,
+        ),
+        function_name!(),
+    )
+}
+
+fn native_simulate_controlee_departure(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    mac_address: jbyteArray,
+    departed: bool,
+    chip_id: JString,
+) -> Result<()> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    let mac_address =
+        env.convert_byte_array(mac_address).map_err(|_| Error::ForeignFunctionInterface)?;
+    dispatcher.set_controlee_departed(&chip_id_str, session_id, &mac_address, departed)
+}
+
+/// Set the extras of a session, as key-value pairs validated against the schema of
+/// session_extras.rs: the app configs they map to are set first, then the host-side policies.
+/// The extras are rejected all together if any of them is invalid.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetSessionExtras(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    keys_jarray: jobjectArray,
+    values_jarray: jobjectArray,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_session_extras(env, obj, session_id as u32, keys_jarray, values_jarray, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_session_extras(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    keys_jarray: jobjectArray,
+    values_jarray: jobjectArray,
+    chip_id: JString,
+) -> Result<()> {
+    let keys_len =
+        env.get_array_length(keys_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
+    let values_len =
+        env.get_array_length(values_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
+    if keys_len != values_len {
+        return Err(Error::BadParameters);
+    }
+    let extras = (0..keys_len)
+        .map(|i| -> std::result::Result<(String, Vec<u8>), JNIError> {
+            let key = env.get_string(env.get_object_array_element(keys_jarray, i)?.into())?;
+            let value = env.convert_byte_array(*env.get_object_array_element(values_jarray, i)?)?;
+            Ok((String::from(key), value))
+        })
+        .collect::<std::result::Result<Vec<_>, JNIError>>()
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let extras = parse_session_extras(&extras)?;
+
+    if !extras.app_configs.is_empty() {
+        let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+        uci_manager.check_retry_throttle(GID_SESSION_CONFIG, OID_SESSION_SET_APP_CONFIG)?;
+        let tlvs =
+            parse_app_config_tlv_vec(extras.app_configs.len() as i32, &extras.app_config_tlvs())?;
+        let response = uci_manager.session_set_app_config(session_id, tlvs)?;
+        if response.status != StatusCode::UciStatusOk {
+            error!("UCI JNI: session extras rejected by the UWBS: {:?}", response.config_status);
+            return Err(Error::BadParameters);
+        }
+    }
+    if let Some(enabled) = find_diagnostics_enabled(&extras.app_config_tlvs()) {
+        set_diagnostics_enabled(env, obj, session_id, enabled, chip_id);
+    }
+    if let Some(distance_step_cm) = extras.coarse_location {
+        let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+        let chip_id_str =
+            String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+        dispatcher.set_coarse_location(&chip_id_str, session_id, distance_step_cm)?;
+    }
+    Ok(())
+}
+
+/// Set the antenna configuration of a session, as one of the ANTENNA_CONFIG_* constants of
+/// NativeUwbManager. The configurations measuring an AoA the chip does not report in its
+/// capabilities are rejected. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetAntennaConfig(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    antenna_config: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_antenna_config(env, obj, session_id as u32, antenna_config, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_antenna_config(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    antenna_config: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let antenna_config = AntennaConfig::try_from(antenna_config)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let caps = uci_manager
+        .core_get_caps_info()?
+        .into_iter()
+        .map(|cap| (u8::from(cap.t), cap.v))
+        .collect::<Vec<_>>();
+    antenna_config.validate(&AoaCapabilities::from_caps(&caps))?;
+    uci_manager.check_retry_throttle(GID_SESSION_CONFIG, OID_SESSION_SET_APP_CONFIG)?;
+    let tlvs = parse_app_config_tlv_vec(
+        antenna_config.app_configs().len() as i32,
+        &antenna_config.app_config_tlvs(),
+    )?;
+    let response = uci_manager.session_set_app_config(session_id, tlvs)?;
+    if response.status != StatusCode::UciStatusOk {
+        error!("UCI JNI: antenna config rejected by the UWBS: {:?}", response.config_status);
+        return Err(Error::BadParameters);
+    }
+    Ok(())
+}
+
+/// Switch the ranging role of a session, as its DEVICE_TYPE and DEVICE_ROLE values defined by the
+/// FiRa spec. An active session is stopped, reconfigured and started again while the other calls
+/// of NativeUwbManager wait, and keeps its previous role if any step fails. The roles the chip
+/// does not report in its capabilities are rejected. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSwitchRangingRole(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    device_type: jint,
+    device_role: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_switch_ranging_role(env, obj, session_id as u32, device_type, device_role, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_switch_ranging_role(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    device_type: jint,
+    device_role: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let target = RangingRole::new(device_type, device_role)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let caps = uci_manager
+        .core_get_caps_info()?
+        .into_iter()
+        .map(|cap| (u8::from(cap.t), cap.v))
+        .collect::<Vec<_>>();
+    target.validate(&caps)?;
+    uci_manager.check_retry_throttle(GID_SESSION_CONFIG, OID_SESSION_SET_APP_CONFIG)?;
+    uci_manager.check_retry_throttle(GID_SESSION_CONTROL, OID_SESSION_START)?;
+
+    let config_ids = RangingRole::app_config_ids()
+        .into_iter()
+        .map(|cfg_id| AppConfigTlvType::try_from(cfg_id).map_err(|_| Error::BadParameters))
+        .collect::<Result<Vec<_>>>()?;
+    let app_configs = uci_manager
+        .session_get_app_config(session_id, config_ids)?
+        .into_iter()
+        .map(|tlv| {
+            let tlv = tlv.into_inner();
+            (u8::from(tlv.cfg_id), tlv.v)
+        })
+        .collect::<Vec<_>>();
+    let previous = RangingRole::from_app_configs(&app_configs)?;
+    let active = uci_manager.session_get_state(session_id)? == SessionState::SessionStateActive;
+
+    // The manager is held from the first command to the last, so that no other command of Java
+    // interleaves with the switch.
+    switch_ranging_role(
+        previous,
+        target,
+        active,
+        |role| {
+            let tlvs =
+                parse_app_config_tlv_vec(role.app_configs().len() as i32, &role.app_config_tlvs())?;
+            let response = uci_manager.session_set_app_config(session_id, tlvs)?;
+            if response.status != StatusCode::UciStatusOk {
+                error!("UCI JNI: {:?} rejected by the UWBS: {:?}", role, response.config_status);
+                return Err(Error::BadParameters);
+            }
+            Ok(())
+        },
+        || uci_manager.range_stop(session_id),
+        || uci_manager.range_start(session_id),
+    )
+}
+
+/// Enable or disable the ranging diagnostics of a session, and the fields of their frame reports.
+/// The diagnostics of the sessions which did not enable them are dropped by the native stack.
+/// Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRangingDiagnostics(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    enabled: jboolean,
+    frame_report_fields: jbyte,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    let config =
+        DiagnosticsConfig { enabled: enabled != 0, frame_report_fields: frame_report_fields as u8 };
+    byte_result_helper(
+        native_set_ranging_diagnostics(env, obj, session_id as u32, config, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_ranging_diagnostics(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    config: DiagnosticsConfig,
+    chip_id: JString,
+) -> Result<()> {
+    {
+        let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+        uci_manager.check_retry_throttle(GID_SESSION_CONFIG, OID_SESSION_SET_APP_CONFIG)?;
+        let tlvs =
+            parse_app_config_tlv_vec(config.app_configs().len() as i32, &config.app_config_tlvs())?;
+        let response = uci_manager.session_set_app_config(session_id, tlvs)?;
+        if response.status != StatusCode::UciStatusOk {
+            error!(
+                "UCI JNI: diagnostics config rejected by the UWBS: {:?}",
+                response.config_status
+            );
+            return Err(Error::BadParameters);
+        }
+    }
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_diagnostics_enabled(&chip_id_str, session_id, config.enabled)
+}
+
+/// Set how the radar sweeps of a session are decimated in the native stack before being passed
+/// to Java.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRadarDecimation(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mode: jint,
+    factor: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_radar_decimation(env, obj, session_id as u32, mode, factor, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_radar_decimation(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    mode: jint,
+    factor: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let factor = u16::try_from(factor).map_err(|_| Error::BadParameters)?;
+    let decimation = match mode {
+        RADAR_DECIMATION_NONE => None,
+        RADAR_DECIMATION_KEEP_EVERY_NTH => Some(RadarDecimation::KeepEveryNth(factor)),
+        RADAR_DECIMATION_AVERAGE => Some(RadarDecimation::Average(factor)),
+        _ => return Err(Error::BadParameters),
+    };
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_radar_decimation(&chip_id_str, session_id, decimation)
+}
+
+/// Set how many radar sweeps of a session are passed to Java per second by the native stack, and
+/// which sweeps are dropped beyond.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRadarDeliveryBudget(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    max_sweeps_per_sec: jint,
+    drop_policy: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_radar_delivery_budget(
+            env,
+            obj,
+            session_id as u32,
+            max_sweeps_per_sec,
+            drop_policy,
+            chip_id,
+        ),
+        function_name!(),
+    )
+}
+
+fn native_set_radar_delivery_budget(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    max_sweeps_per_sec: jint,
+    drop_policy: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let max_sweeps_per_sec = u16::try_from(max_sweeps_per_sec).map_err(|_| Error::BadParameters)?;
+    let drop_policy = match drop_policy {
+        RADAR_DROP_OLDEST => RadarDropPolicy::DropOldest,
+        RADAR_DROP_DECIMATE => RadarDropPolicy::Decimate,
+        _ => return Err(Error::BadParameters),
+    };
+    // A budget of 0 sweeps per second removes the budget.
+    let budget = Some(RadarDeliveryBudget { max_sweeps_per_sec, drop_policy });
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_radar_delivery_budget(&chip_id_str, session_id, budget)
+}
+
+/// Set how the range data of a session are delivered to Java by the native stack.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetSessionDeliveryPreference(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mode: jint,
+    value: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_session_delivery_preference(env, obj, session_id as u32, mode, value, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_session_delivery_preference(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    mode: jint,
+    value: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let preference = match mode {
+        DELIVERY_IMMEDIATE => DeliveryPreference::Immediate,
+        DELIVERY_BATCHED => {
+            DeliveryPreference::Batched(u16::try_from(value).map_err(|_| Error::BadParameters)?)
+        }
+        DELIVERY_MAX_RATE => DeliveryPreference::MaxRate(Duration::from_millis(
+            u64::try_from(value).map_err(|_| Error::BadParameters)?,
+        )),
+        _ => return Err(Error::BadParameters),
+    };
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_session_delivery_preference(&chip_id_str, session_id, preference)
+}
+
+/// Merge the range data of several sessions into one stream delivered to Java.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetSessionAggregation(
+    env: JNIEnv,
+    obj: JObject,
+    group_id: jint,
+    session_ids: jintArray,
+    window_ms: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_session_aggregation(env, obj, group_id, session_ids, window_ms, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_session_aggregation(
+    env: JNIEnv,
+    obj: JObject,
+    group_id: jint,
+    session_ids: jintArray,
+    window_ms: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let window = Duration::from_millis(u64::try_from(window_ms).map_err(|_| Error::BadParameters)?);
+    let mut session_id_list = vec![
+        0i32;
+        env.get_array_length(session_ids)
+            .map_err(|_| Error::ForeignFunctionInterface)?
+            .try_into()
+            .map_err(|_| Error::BadParameters)?
+    ];
+    env.get_int_array_region(session_ids, 0, &mut session_id_list)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let session_id_list =
+        session_id_list.into_iter().map(|session_id| session_id as u32).collect::<Vec<_>>();
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_session_aggregation(&chip_id_str, group_id as u32, &session_id_list, window)
+}
+
+/// The attestor set by nativeSetRangingAttestor, which signs the segments of the measurement
+/// streams in Java.
+struct JavaRangingAttestor {
+    vm: &'static Arc<JavaVM>,
+    attestor_obj: GlobalRef,
+}
+
+impl RangingAttestor for JavaRangingAttestor {
+    fn attest(&self, session_id: u32, segment: &[u8]) -> Option<Vec<u8>> {
+        // The segments are signed from the attestation thread of each chip, which stays attached
+        // until it exits, so the local references must be released here.
+        let env = self.vm.attach_current_thread_permanently().ok()?;
+        let segment_jbytearray = env.byte_array_from_slice(segment).ok()?;
+        // Safety: segment_jbytearray safely instantiated above.
+        let segment_jobject = unsafe { JObject::from_raw(segment_jbytearray) };
+        let result = env
+            .call_method(
+                self.attestor_obj.as_obj(),
+                "attest",
+                "(I[B)[B",
+                &[JValue::Int(session_id as i32), JValue::Object(segment_jobject)],
+            )
+            .and_then(|signature| signature.l());
+        let _ = env.delete_local_ref(segment_jobject);
+        let signature_jobject = match result {
+            Ok(signature_jobject) => signature_jobject,
+            Err(e) => {
+                error!("UCI JNI: attest of session {} failed: {:?}", session_id, e);
+                let _ = env.exception_clear();
+                return None;
+            }
+        };
+        if signature_jobject.is_null() {
+            return None;
+        }
+        let signature = env.convert_byte_array(*signature_jobject).ok();
+        let _ = env.delete_local_ref(signature_jobject);
+        signature
+    }
+}
+
+/// Set the attestor signing the measurement streams of the attested sessions of all the UWB
+/// devices, or stop attesting them when the attestor is null.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRangingAttestor(
+    env: JNIEnv,
+    obj: JObject,
+    attestor_obj: JObject,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_set_ranging_attestor(env, obj, attestor_obj), function_name!())
+}
+
+fn native_set_ranging_attestor(env: JNIEnv, obj: JObject, attestor_obj: JObject) -> Result<()> {
+    let attestor: Option<Arc<dyn RangingAttestor>> = if attestor_obj.is_null() {
+        None
+    } else {
+        Some(Arc::new(JavaRangingAttestor {
+            vm: unique_jvm::get_static_ref().ok_or(Error::Unknown)?,
+            attestor_obj: env
+                .new_global_ref(attestor_obj)
+                .map_err(|_| Error::ForeignFunctionInterface)?,
+        }))
+    };
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    dispatcher.set_ranging_attestor(attestor)
+}
+
+/// Set how many SESSION_INFO_NTFs of a session are signed together by the attestor, 0 to stop
+/// attesting the session.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRangingAttestation(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    measurements_per_attestation: jint,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(
+        native_set_ranging_attestation(
+            env,
+            obj,
+            session_id as u32,
+            measurements_per_attestation,
+            chip_id,
+        ),
+        function_name!(),
+    )
+}
+
+fn native_set_ranging_attestation(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    measurements_per_attestation: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let measurements_per_attestation =
+        u16::try_from(measurements_per_attestation).map_err(|_| Error::BadParameters)?;
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.set_ranging_attestation(&chip_id_str, session_id, measurements_per_attestation)
+}
+
+fn create_ranging_attestations(
+    session_id: u32,
+    records: Vec<RangingAttestationRecord>,
+    env: JNIEnv,
+) -> Result<jobjectArray> {
+    let ranging_attestation_class =
+        env.find_class(RANGING_ATTESTATION_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let records_jobjectarray = env
+        .new_object_array(records.len() as i32, ranging_attestation_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, record) in records.into_iter().enumerate() {
+        let signature_jbytearray = env
+            .byte_array_from_slice(&record.signature)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        // Safety: signature_jbytearray safely instantiated above.
+        let signature_jobject = unsafe { JObject::from_raw(signature_jbytearray) };
+        let record_jobject = env
+            .new_object(
+                ranging_attestation_class,
+                "(III[B)V",
+                &[
+                    JValue::Int(session_id as i32),
+                    JValue::Int(record.sequence_number as i32),
+                    JValue::Int(record.measurement_count as i32),
+                    JValue::Object(signature_jobject),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(records_jobjectarray, i as i32, record_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(records_jobjectarray)
+}
+
+/// Fetch the attestations of the measurement stream of a session recorded since the previous
+/// call. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeFetchRangingAttestations(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_fetch_ranging_attestations(env, obj, session_id as u32, chip_id),
+        function_name!(),
+    ) {
+        Some(records) => create_ranging_attestations(session_id as u32, records, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_fetch_ranging_attestations(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: u32,
+    chip_id: JString,
+) -> Result<Vec<RangingAttestationRecord>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.fetch_ranging_attestations(&chip_id_str, session_id)
+}
+
+/// Record the UCI packets exchanged with the HAL of a single UWB device from its next opening, for
+/// their replay in the unit tests. Returns true if succeeded.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeStartUciRecording(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_start_uci_recording(env, obj, chip_id), function_name!())
+}
+
+fn native_start_uci_recording(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<()> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.start_uci_recording(&chip_id_str)
+}
+
+/// Stop recording the UCI packets exchanged with the HAL of a single UWB device, and write the
+/// recording to a file. Returns the path of the file, or a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeStopUciRecording(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jstring {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_stop_uci_recording(env, obj, chip_id), function_name!()) {
+        Some(path) => path,
+        None => *JObject::null(),
+    }
+}
+
+fn native_stop_uci_recording(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<jstring> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    let path = dispatcher.stop_uci_recording(&chip_id_str)?;
+    Ok(env.new_string(path).map_err(|_| Error::ForeignFunctionInterface)?.into_raw())
+}
+
+/// Persist the data transfers of a single UWB device in flight, before a shutdown of the service.
+/// Returns true if succeeded.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativePersistDataTransfers(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_persist_data_transfers(env, obj, chip_id), function_name!())
+}
+
+fn native_persist_data_transfers(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<()> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.persist_data_transfers(&chip_id_str)
+}
+
+fn create_aborted_data_transfers(
+    aborted_transfers: Vec<AbortedTransfers>,
+    env: JNIEnv,
+) -> Result<jobjectArray> {
+    let aborted_transfers_class = env
+        .find_class(ABORTED_DATA_TRANSFERS_CLASS)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let aborted_transfers_jobjectarray = env
+        .new_object_array(aborted_transfers.len() as i32, aborted_transfers_class, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, aborted) in aborted_transfers.into_iter().enumerate() {
+        // The short and extended destination addresses are passed as little endian longs.
+        let destinations = aborted
+            .destinations
+            .iter()
+            .map(|destination| {
+                let mut bytes = [0; 8];
+                let len = destination.len().min(8);
+                bytes[..len].copy_from_slice(&destination[..len]);
+                i64::from_le_bytes(bytes)
+            })
+            .collect::<Vec<_>>();
+        let destinations_jlongarray = env
+            .new_long_array(destinations.len() as i32)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_long_array_region(destinations_jlongarray, 0, &destinations)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        // Safety: destinations_jlongarray is safely instantiated above.
+        let destinations_jobject = unsafe { JObject::from_raw(destinations_jlongarray) };
+        let aborted_jobject = env
+            .new_object(
+                aborted_transfers_class,
+                "(III[J)V",
+                &[
+                    JValue::Int(aborted.session_id as i32),
+                    JValue::Int(aborted.transfer_count as i32),
+                    JValue::Int(aborted.remaining_fragments as i32),
+                    JValue::Object(destinations_jobject),
+                ],
+            )
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(aborted_transfers_jobjectarray, i as i32, aborted_jobject)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(aborted_transfers_jobjectarray)
+}
+
+/// Fetch the data transfers of each session of a single UWB device aborted by the last shutdown
+/// of the service. They are reported once. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeFetchAbortedDataTransfers(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_fetch_aborted_data_transfers(env, obj, chip_id),
+        function_name!(),
+    ) {
+        Some(aborted_transfers) => create_aborted_data_transfers(aborted_transfers, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_fetch_aborted_data_transfers(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Vec<AbortedTransfers>> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let chip_id_str =
+        String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
+    dispatcher.take_aborted_data_transfers(&chip_id_str)
+}
 
 /// Update ranging rounds for DT-TAG
 #[no_mangle]
@@ -1368,12 +3626,24 @@ fn native_send_data(
         env.convert_byte_array(address).map_err(|_| Error::ForeignFunctionInterface)?;
     let app_payload_data_bytearray =
         env.convert_byte_array(app_payload_data).map_err(|_| Error::ForeignFunctionInterface)?;
-    uci_manager.send_data_packet(
+    uci_manager.update_data_tx_journal(|journal| {
+        journal.on_send(
+            session_id as u32,
+            &address_bytearray,
+            uci_sequence_number as u16,
+            app_payload_data_bytearray.len(),
+        )
+    });
+    let result = uci_manager.send_data_packet(
         session_id as u32,
         address_bytearray,
         uci_sequence_number as u16,
         app_payload_data_bytearray,
-    )
+    );
+    uci_manager.update_data_tx_journal(|journal| {
+        journal.on_sent(session_id as u32, uci_sequence_number as u16, result.is_ok())
+    });
+    result
 }
 
 /// Get max application data size, that can be sent by the UWBS. Return 0 if failed.
@@ -1511,7 +3781,7 @@ fn native_get_session_token(
 /// Get the class loader object. Has to be called from a JNIEnv where the local java classes are
 /// loaded. Results in a global reference to the class loader object that can be used to look for
 /// classes in other native thread.
-fn get_class_loader_obj(env: &JNIEnv) -> Result<GlobalRef> {
+fn get_class_loader_obj(env: &JNIEnv) -> Result<TrackedGlobalRef> {
     let ranging_data_class =
         env.find_class(UWB_RANGING_DATA_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
     let ranging_data_class_class =
@@ -1528,7 +3798,9 @@ fn get_class_loader_obj(env: &JNIEnv) -> Result<GlobalRef> {
         )
         .map_err(|_| Error::ForeignFunctionInterface)?;
     let class_loader_jobject = class_loader.l().map_err(|_| Error::ForeignFunctionInterface)?;
-    env.new_global_ref(class_loader_jobject).map_err(|_| Error::ForeignFunctionInterface)
+    let class_loader_obj =
+        env.new_global_ref(class_loader_jobject).map_err(|_| Error::ForeignFunctionInterface)?;
+    Ok(TrackedRef::new(class_loader_obj, GlobalRefKind::ClassLoader))
 }
 
 /// Create the dispatcher. Returns pointer to Dispatcher casted as jlong that owns the dispatcher.
@@ -1537,9 +3809,20 @@ pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeDi
     env: JNIEnv,
     obj: JObject,
     chip_ids_jarray: jobjectArray,
+    backpressure_policy: jint,
+    notification_queue_capacity: jint,
 ) -> jlong {
     debug!("{}: enter", function_name!());
-    match option_result_helper(native_dispatcher_new(env, obj, chip_ids_jarray), function_name!()) {
+    match option_result_helper(
+        native_dispatcher_new(
+            env,
+            obj,
+            chip_ids_jarray,
+            backpressure_policy,
+            notification_queue_capacity,
+        ),
+        function_name!(),
+    ) {
         Some(ptr) => ptr as jlong,
         None => *JObject::null() as jlong,
     }
@@ -1549,7 +3832,12 @@ fn native_dispatcher_new(
     env: JNIEnv,
     obj: JObject,
     chip_ids_jarray: jobjectArray,
+    backpressure_policy: jint,
+    notification_queue_capacity: jint,
 ) -> Result<*const Dispatcher> {
+    let backpressure_policy = BackpressurePolicy::try_from(backpressure_policy)?;
+    let notification_queue_capacity =
+        usize::try_from(notification_queue_capacity).map_err(|_| Error::BadParameters)?;
     let chip_ids_len: i32 =
         env.get_array_length(chip_ids_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
     let chip_ids = (0..chip_ids_len)
@@ -1558,11 +3846,14 @@ fn native_dispatcher_new(
         .map_err(|_| Error::ForeignFunctionInterface)?;
     let chip_ids = chip_ids.into_iter().map(String::from).collect::<Vec<String>>();
     let class_loader_obj = get_class_loader_obj(&env)?;
+    let callback_obj = env.new_global_ref(obj).map_err(|_| Error::ForeignFunctionInterface)?;
     Dispatcher::new_dispatcher(
         unique_jvm::get_static_ref().ok_or(Error::Unknown)?,
         class_loader_obj,
-        env.new_global_ref(obj).map_err(|_| Error::ForeignFunctionInterface)?,
+        TrackedRef::new(callback_obj, GlobalRefKind::CallbackObject),
         &chip_ids,
+        backpressure_policy,
+        notification_queue_capacity,
     )?;
     Dispatcher::get_dispatcher_ptr()
 }
@@ -1586,12 +3877,40 @@ fn native_dispatcher_destroy(env: JNIEnv, obj: JObject) -> Result<()> {
         .j()
         .map_err(|_| Error::ForeignFunctionInterface)?;
     if Dispatcher::get_dispatcher_ptr()? as jlong == dispatcher_ptr_long {
+        // The exporter would otherwise keep reporting the metrics of the destroyed dispatcher.
+        metrics_exporter::stop()?;
         Dispatcher::destroy_dispatcher()
     } else {
         Err(Error::BadParameters)
     }
 }
 
+/// Replaces the object receiving the notifications of all the chips. A null object detaches it, the
+/// core and session notifications being retained until an object is attached again.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSwapCallbackObject(
+    env: JNIEnv,
+    _obj: JObject,
+    callback_obj: JObject,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_swap_callback_object(env, callback_obj), function_name!())
+}
+
+fn native_swap_callback_object(env: JNIEnv, callback_obj: JObject) -> Result<()> {
+    let callback_obj = if callback_obj.is_null() {
+        None
+    } else {
+        let callback_obj =
+            env.new_global_ref(callback_obj).map_err(|_| Error::ForeignFunctionInterface)?;
+        Some(TrackedRef::new(callback_obj, GlobalRefKind::CallbackObject))
+    };
+    // The Java object is not locked, as the swap waits for the Java callbacks in flight.
+    let epoch = Dispatcher::swap_callback_obj(callback_obj)?;
+    debug!("UCI JNI: callback object swapped, epoch {}", epoch);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,190 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Redaction of the sensitive app configurations in the UCI packets written to the capture. The
+//! key material, see secret_bytes, is redacted in every log mode, the identifying app
+//! configurations in the Filtered log mode only.
+
+const MESSAGE_TYPE_COMMAND: u8 = 1;
+const MESSAGE_TYPE_RESPONSE: u8 = 2;
+const GID_SESSION_CONFIG: u8 = 0x1;
+const OID_SESSION_SET_APP_CONFIG: u8 = 0x3;
+const OID_SESSION_GET_APP_CONFIG: u8 = 0x4;
+const OID_SESSION_UPDATE_CONTROLLER_MULTICAST_LIST: u8 = 0x7;
+const HEADER_LEN: usize = 4;
+
+/// The app configurations holding key material, whose values are always redacted: STATIC_STS_IV,
+/// SESSION_KEY and SUBSESSION_KEY, see com.android.server.uwb.config.ConfigParam.
+pub(crate) const SECRET_APP_CONFIG_IDS: [u8; 3] = [0x28, 0x45, 0x46];
+/// The app configurations whose values are redacted in the Filtered log mode: VENDOR_ID.
+const IDENTIFYING_APP_CONFIG_IDS: [u8; 1] = [0x27];
+
+/// Actions of SESSION_UPDATE_CONTROLLER_MULTICAST_LIST_CMD adding controlees with their
+/// sub-session keys, and the length of their keys.
+const ADD_CONTROLEE_WITH_SHORT_SUB_SESSION_KEY: u8 = 0x2;
+const ADD_CONTROLEE_WITH_LONG_SUB_SESSION_KEY: u8 = 0x3;
+const SHORT_SUB_SESSION_KEY_LEN: usize = 16;
+const LONG_SUB_SESSION_KEY_LEN: usize = 32;
+/// Short address (2) and sub-session ID (4) of a controlee.
+const CONTROLEE_LEN: usize = 6;
+
+/// Zeroes the sub-session keys of the payload of an encoded
+/// SESSION_UPDATE_CONTROLLER_MULTICAST_LIST_CMD, in place. Returns whether the payload was
+/// modified. A truncated controlee list is redacted up to the first controlee which does not fit.
+fn redact_sub_session_keys(payload: &mut [u8]) -> bool {
+    // Session token (4), action (1), controlee count (1).
+    let (action, controlee_count) = match payload.get(4..6) {
+        Some(&[action, controlee_count]) => (action, controlee_count as usize),
+        _ => return false,
+    };
+    let key_len = match action {
+        ADD_CONTROLEE_WITH_SHORT_SUB_SESSION_KEY => SHORT_SUB_SESSION_KEY_LEN,
+        ADD_CONTROLEE_WITH_LONG_SUB_SESSION_KEY => LONG_SUB_SESSION_KEY_LEN,
+        _ => return false,
+    };
+    let mut redacted = false;
+    let mut offset = 6;
+    for _ in 0..controlee_count {
+        let key_offset = offset + CONTROLEE_LEN;
+        match payload.get_mut(key_offset..key_offset + key_len) {
+            Some(key) => {
+                key.fill(0);
+                redacted = true;
+            }
+            None => break,
+        }
+        offset = key_offset + key_len;
+    }
+    redacted
+}
+
+/// Zeroes the values of the secret TLVs of an encoded SESSION_SET_APP_CONFIG_CMD or
+/// SESSION_GET_APP_CONFIG_RSP, in place, and of the identifying ones when `redact_identifiers`.
+/// The keys of an encoded SESSION_UPDATE_CONTROLLER_MULTICAST_LIST_CMD are zeroed as well. Returns whether the packet was modified. A malformed TLV list is redacted up to
+/// the first TLV which does not fit.
+pub(crate) fn redact_sensitive_app_configs(packet: &mut [u8], redact_identifiers: bool) -> bool {
+    let header = match packet.get(..HEADER_LEN) {
+        Some(header) => header,
+        None => return false,
+    };
+    let message_type = header[0] >> 5;
+    let gid = header[0] & 0x0f;
+    let oid = header[1] & 0x3f;
+    if message_type == MESSAGE_TYPE_COMMAND
+        && gid == GID_SESSION_CONFIG
+        && oid == OID_SESSION_UPDATE_CONTROLLER_MULTICAST_LIST
+    {
+        return redact_sub_session_keys(&mut packet[HEADER_LEN..]);
+    }
+    // SET_APP_CONFIG_CMD: session token (4), TLV count (1). GET_APP_CONFIG_RSP: status (1),
+    // TLV count (1).
+    let tlvs_offset = match (message_type, gid, oid) {
+        (MESSAGE_TYPE_COMMAND, GID_SESSION_CONFIG, OID_SESSION_SET_APP_CONFIG) => HEADER_LEN + 5,
+        (MESSAGE_TYPE_RESPONSE, GID_SESSION_CONFIG, OID_SESSION_GET_APP_CONFIG) => HEADER_LEN + 2,
+        _ => return false,
+    };
+    let tlv_count = match packet.get(tlvs_offset - 1) {
+        Some(&tlv_count) => tlv_count,
+        None => return false,
+    };
+
+    let mut redacted = false;
+    let mut offset = tlvs_offset;
+    for _ in 0..tlv_count {
+        let (id, len) = match packet.get(offset..offset + 2) {
+            Some(&[id, len]) => (id, len as usize),
+            _ => break,
+        };
+        let value = match packet.get_mut(offset + 2..offset + 2 + len) {
+            Some(value) => value,
+            None => break,
+        };
+        if SECRET_APP_CONFIG_IDS.contains(&id)
+            || (redact_identifiers && IDENTIFYING_APP_CONFIG_IDS.contains(&id))
+        {
+            value.fill(0);
+            redacted = true;
+        }
+        offset += 2 + len;
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_set_app_config_cmd() {
+        let mut packet = vec![0x21, 0x03, 0x00, 0x13, 0x01, 0x00, 0x00, 0x00, 0x03];
+        // CHANNEL_NUMBER, STATIC_STS_IV and SESSION_KEY.
+        packet.extend_from_slice(&[0x04, 0x01, 0x09]);
+        packet.extend_from_slice(&[0x28, 0x06, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+        packet.extend_from_slice(&[0x45, 0x02, 0xaa, 0xbb]);
+
+        assert!(redact_sensitive_app_configs(&mut packet, true));
+        assert_eq!(&packet[9..12], &[0x04, 0x01, 0x09]);
+        assert_eq!(&packet[12..20], &[0x28, 0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(&packet[20..], &[0x45, 0x02, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_redact_get_app_config_rsp() {
+        // VENDOR_ID, then a truncated SUBSESSION_KEY.
+        let mut packet =
+            vec![0x41, 0x04, 0x00, 0x09, 0x00, 0x02, 0x27, 0x02, 0x12, 0x34, 0x46, 0x10, 0xff];
+        assert!(redact_sensitive_app_configs(&mut packet, true));
+        assert_eq!(&packet[6..], &[0x27, 0x02, 0x00, 0x00, 0x46, 0x10, 0xff]);
+
+        // Other packets are left untouched.
+        let mut packet = vec![0x21, 0x04, 0x00, 0x07, 0x01, 0x00, 0x00, 0x00, 0x01, 0x27, 0x00];
+        assert!(!redact_sensitive_app_configs(&mut packet, true));
+    }
+
+    #[test]
+    fn test_identifiers_redacted_in_filtered_mode_only() {
+        // VENDOR_ID and SESSION_KEY.
+        let packet = vec![
+            0x21, 0x03, 0x00, 0x0d, 0x01, 0x00, 0x00, 0x00, 0x02, 0x27, 0x02, 0x12, 0x34, 0x45,
+            0x02, 0xaa, 0xbb,
+        ];
+
+        let mut redacted = packet.clone();
+        assert!(redact_sensitive_app_configs(&mut redacted, false));
+        assert_eq!(&redacted[9..], &[0x27, 0x02, 0x12, 0x34, 0x45, 0x02, 0x00, 0x00]);
+
+        let mut redacted = packet;
+        assert!(redact_sensitive_app_configs(&mut redacted, true));
+        assert_eq!(&redacted[9..], &[0x27, 0x02, 0x00, 0x00, 0x45, 0x02, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_redact_controller_multicast_list_cmd() {
+        // Two controlees with a short sub-session key, the second one truncated.
+        let mut packet = vec![0x21, 0x07, 0x00, 0x23, 0x01, 0x00, 0x00, 0x00, 0x02, 0x02];
+        packet.extend_from_slice(&[0x01, 0x02, 0x0a, 0x00, 0x00, 0x00]);
+        packet.extend_from_slice(&[0xaa; 16]);
+        packet.extend_from_slice(&[0x03, 0x04, 0x0b, 0x00, 0x00, 0x00, 0xbb]);
+
+        assert!(redact_sensitive_app_configs(&mut packet, false));
+        assert_eq!(&packet[10..16], &[0x01, 0x02, 0x0a, 0x00, 0x00, 0x00]);
+        assert_eq!(&packet[16..32], &[0; 16]);
+        assert_eq!(packet.last(), Some(&0xbb));
+
+        // The controlees added without a key are left untouched.
+        let mut packet = vec![0x21, 0x07, 0x00, 0x0c, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01];
+        packet.extend_from_slice(&[0x01, 0x02, 0x0a, 0x00, 0x00, 0x00]);
+        assert!(!redact_sensitive_app_configs(&mut packet, true));
+    }
+}
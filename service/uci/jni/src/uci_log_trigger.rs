@@ -0,0 +1,274 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! UCI log triggered by the first error, which captures the context of rare field failures without
+//! always-on logging.
+//!
+//! With the native_error_triggered_uci_log flag, a chip whose UCI log is disabled still passes its
+//! packets to the logger in the Filtered mode, but they are held in an in-memory ring instead of
+//! being written. The first command failure or protocol error flushes the ring to the log file,
+//! then the packets are written for a bounded duration, after which the log is disabled again
+//! until its mode is set anew.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+use uwb_core::error::Result;
+use uwb_core::uci::uci_logger::UciLogger;
+use uwb_uci_packets::{UciControlPacket, UciDataPacket};
+
+/// Number of packets held in the ring.
+pub(crate) const UCI_LOG_RING_CAPACITY: usize = 512;
+/// Duration the packets are written after the error.
+pub(crate) const UCI_LOG_TRIGGERED_DURATION: Duration = Duration::from_secs(120);
+
+const MESSAGE_TYPE_RESPONSE: u8 = 2;
+const STATUS_OK: u8 = 0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum TriggerState {
+    /// The log mode set by Java applies.
+    #[default]
+    Inactive,
+    /// Holding the packets until an error.
+    Armed,
+    /// Writing the packets until `until`.
+    Triggered { until: Instant },
+    /// Triggered once, the log stays disabled.
+    Spent,
+}
+
+/// What the logger does with a packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PacketAction {
+    /// Writes the packet.
+    Write,
+    /// Flushes the ring, then writes the packet.
+    Flush,
+    /// Holds the packet in the ring.
+    Hold,
+    /// Drops the packet.
+    Drop,
+}
+
+/// The error-triggered log of one chip, shared by its logger and its notification manager.
+#[derive(Debug, Default)]
+pub(crate) struct UciLogTrigger {
+    state: TriggerState,
+    /// An error was seen, the log starts with the next packet.
+    error_seen: bool,
+}
+
+impl UciLogTrigger {
+    /// Holds the packets until an error, as the log is disabled.
+    pub fn arm(&mut self) {
+        self.state = TriggerState::Armed;
+        self.error_seen = false;
+    }
+
+    /// Lets the log mode set by Java apply.
+    pub fn disarm(&mut self) {
+        self.state = TriggerState::Inactive;
+        self.error_seen = false;
+    }
+
+    /// Reports a command failure or a protocol error.
+    pub fn on_error(&mut self) {
+        if self.state == TriggerState::Armed {
+            self.error_seen = true;
+        }
+    }
+
+    /// Decides what to do with a packet logged at `now`.
+    pub fn on_packet(&mut self, now: Instant) -> PacketAction {
+        match self.state {
+            TriggerState::Inactive => PacketAction::Write,
+            TriggerState::Armed if self.error_seen => {
+                self.state = TriggerState::Triggered { until: now + UCI_LOG_TRIGGERED_DURATION };
+                PacketAction::Flush
+            }
+            TriggerState::Armed => PacketAction::Hold,
+            TriggerState::Triggered { until } if now < until => PacketAction::Write,
+            TriggerState::Triggered { .. } => {
+                info!("UCI JNI: error-triggered UCI log ended");
+                self.state = TriggerState::Spent;
+                PacketAction::Drop
+            }
+            TriggerState::Spent => PacketAction::Drop,
+        }
+    }
+}
+
+/// Returns whether the encoded control packet is a response with a failure status.
+fn is_failure_response(packet: &[u8]) -> bool {
+    match packet {
+        [header, _, _, _, status, ..] => {
+            header >> 5 == MESSAGE_TYPE_RESPONSE && *status != STATUS_OK
+        }
+        _ => false,
+    }
+}
+
+enum HeldPacket {
+    Control(UciControlPacket),
+    Data(UciDataPacket),
+}
+
+/// UciLogger which writes to `inner` the packets the shared trigger lets through, and holds the
+/// others in a ring until the trigger flushes it.
+pub(crate) struct UciLogTriggerLogger<L: UciLogger> {
+    inner: L,
+    trigger: Arc<Mutex<UciLogTrigger>>,
+    ring: VecDeque<HeldPacket>,
+}
+
+impl<L: UciLogger> UciLogTriggerLogger<L> {
+    /// Constructs the logger. The trigger may be armed at any time through `trigger`.
+    pub fn new(inner: L, trigger: Arc<Mutex<UciLogTrigger>>) -> Self {
+        Self { inner, trigger, ring: VecDeque::with_capacity(UCI_LOG_RING_CAPACITY) }
+    }
+
+    fn on_packet(&mut self, failure: bool) -> PacketAction {
+        let action = match self.trigger.lock() {
+            Ok(mut trigger) => {
+                if failure {
+                    trigger.on_error();
+                }
+                trigger.on_packet(Instant::now())
+            }
+            Err(e) => {
+                error!("UCI JNI: failed to lock UCI log trigger: {:?}", e);
+                PacketAction::Write
+            }
+        };
+        // The packets held while the trigger was armed are not written once it is not.
+        if matches!(action, PacketAction::Write | PacketAction::Drop) {
+            self.ring.clear();
+        }
+        action
+    }
+
+    fn hold(&mut self, packet: HeldPacket) {
+        if self.ring.len() == UCI_LOG_RING_CAPACITY {
+            self.ring.pop_front();
+        }
+        self.ring.push_back(packet);
+    }
+
+    fn flush(&mut self) {
+        info!("UCI JNI: error-triggered UCI log started with {} held packets", self.ring.len());
+        for packet in self.ring.drain(..) {
+            match packet {
+                HeldPacket::Control(packet) => self.inner.log_uci_control_packet(packet),
+                HeldPacket::Data(packet) => self.inner.log_uci_data_packet(&packet),
+            }
+        }
+    }
+}
+
+impl<L: UciLogger> UciLogger for UciLogTriggerLogger<L> {
+    fn log_uci_control_packet(&mut self, packet: UciControlPacket) {
+        match self.on_packet(is_failure_response(&packet.clone().to_vec())) {
+            PacketAction::Write => self.inner.log_uci_control_packet(packet),
+            PacketAction::Flush => {
+                self.flush();
+                self.inner.log_uci_control_packet(packet);
+            }
+            PacketAction::Hold => self.hold(HeldPacket::Control(packet)),
+            PacketAction::Drop => {}
+        }
+    }
+
+    fn log_uci_data_packet(&mut self, packet: &UciDataPacket) {
+        match self.on_packet(false) {
+            PacketAction::Write => self.inner.log_uci_data_packet(packet),
+            PacketAction::Flush => {
+                self.flush();
+                self.inner.log_uci_data_packet(packet);
+            }
+            PacketAction::Hold => self.hold(HeldPacket::Data(packet.clone())),
+            PacketAction::Drop => {}
+        }
+    }
+
+    fn log_hal_open(&mut self, result: Result<()>) {
+        match self.on_packet(false) {
+            PacketAction::Write => self.inner.log_hal_open(result),
+            PacketAction::Flush => {
+                self.flush();
+                self.inner.log_hal_open(result);
+            }
+            PacketAction::Hold | PacketAction::Drop => {}
+        }
+    }
+
+    fn log_hal_close(&mut self, result: Result<()>) {
+        match self.on_packet(false) {
+            PacketAction::Write => self.inner.log_hal_close(result),
+            PacketAction::Flush => {
+                self.flush();
+                self.inner.log_hal_close(result);
+            }
+            PacketAction::Hold | PacketAction::Drop => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inactive() {
+        let mut trigger = UciLogTrigger::default();
+        trigger.on_error();
+        assert_eq!(trigger.on_packet(Instant::now()), PacketAction::Write);
+    }
+
+    #[test]
+    fn test_triggered_by_first_error() {
+        let mut trigger = UciLogTrigger::default();
+        let start = Instant::now();
+        trigger.arm();
+        assert_eq!(trigger.on_packet(start), PacketAction::Hold);
+
+        trigger.on_error();
+        assert_eq!(trigger.on_packet(start), PacketAction::Flush);
+        assert_eq!(trigger.on_packet(start + Duration::from_secs(1)), PacketAction::Write);
+        // Another error does not extend the log.
+        trigger.on_error();
+        let end = start + UCI_LOG_TRIGGERED_DURATION;
+        assert_eq!(trigger.on_packet(end), PacketAction::Drop);
+        trigger.on_error();
+        assert_eq!(trigger.on_packet(end), PacketAction::Drop);
+
+        // Until the log is disabled again.
+        trigger.arm();
+        assert_eq!(trigger.on_packet(end), PacketAction::Hold);
+        trigger.disarm();
+        assert_eq!(trigger.on_packet(end), PacketAction::Write);
+    }
+
+    #[test]
+    fn test_is_failure_response() {
+        // SESSION_INIT_RSP.
+        assert!(!is_failure_response(&[0x41, 0x00, 0x00, 0x01, 0x00]));
+        assert!(is_failure_response(&[0x41, 0x00, 0x00, 0x01, 0x01]));
+        // SESSION_INIT_CMD.
+        assert!(!is_failure_response(&[0x21, 0x00, 0x00, 0x01, 0x01]));
+        assert!(!is_failure_response(&[0x41, 0x00, 0x00, 0x00]));
+    }
+}
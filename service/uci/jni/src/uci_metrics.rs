@@ -0,0 +1,241 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-chip round-trip metrics of the UCI commands, collected from the packets seen by the UCI
+//! logger.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use log::error;
+use uwb_core::error::Result;
+use uwb_core::uci::uci_logger::UciLogger;
+use uwb_uci_packets::{Packet, UciControlPacket, UciDataPacket};
+
+use crate::retry_storm::{RetryStorm, RetryStormDetector};
+
+const MESSAGE_TYPE_COMMAND: u8 = 1;
+const MESSAGE_TYPE_RESPONSE: u8 = 2;
+const HEADER_LEN: usize = 4;
+
+/// Round-trip metrics of the commands of one GID/OID.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct UciCommandStats {
+    /// Number of commands sent, retransmissions excluded.
+    pub command_count: u64,
+    /// Number of commands which got a response.
+    pub response_count: u64,
+    /// Number of retransmissions.
+    pub retry_count: u64,
+    /// Number of attempts which got no response, whether they were retried or not.
+    pub timeout_count: u64,
+    /// Sum of the round-trip times of the commands which got a response, measured from the
+    /// first attempt.
+    pub total_latency_us: u64,
+    pub max_latency_us: u64,
+    /// Number of UCI_STATUS_COMMAND_RETRY storms, see RetryStormDetector.
+    pub storm_count: u64,
+}
+
+/// Matches the UCI commands with their responses. UCI allows a single outstanding command, so a
+/// command sent again before its response is a retransmission, and a different command means the
+/// previous one was given up.
+#[derive(Debug, Default)]
+pub(crate) struct UciMetrics {
+    stats: BTreeMap<(u8, u8), UciCommandStats>,
+    /// The GID/OID of the outstanding command, and when it was first sent.
+    pending: Option<((u8, u8), Instant)>,
+    retry_storms: RetryStormDetector,
+}
+
+impl UciMetrics {
+    /// Records a command of `gid`/`oid` sent at `now`.
+    pub fn on_command(&mut self, gid: u8, oid: u8, now: Instant) {
+        let key = (gid, oid);
+        match self.pending {
+            Some((pending_key, _)) if pending_key == key => {
+                let stats = self.stats.entry(key).or_default();
+                stats.retry_count = stats.retry_count.saturating_add(1);
+                stats.timeout_count = stats.timeout_count.saturating_add(1);
+                if self.retry_storms.on_retry(gid, oid, now) {
+                    error!("UCI JNI: retry storm on GID {:#x} OID {:#x}, throttling", gid, oid);
+                    stats.storm_count = stats.storm_count.saturating_add(1);
+                }
+                return;
+            }
+            Some((pending_key, _)) => {
+                let stats = self.stats.entry(pending_key).or_default();
+                stats.timeout_count = stats.timeout_count.saturating_add(1);
+            }
+            None => {}
+        }
+        let stats = self.stats.entry(key).or_default();
+        stats.command_count = stats.command_count.saturating_add(1);
+        self.pending = Some((key, now));
+    }
+
+    /// Records a response of `gid`/`oid` received at `now`.
+    pub fn on_response(&mut self, gid: u8, oid: u8, now: Instant) {
+        let sent_at = match self.pending {
+            Some((pending_key, sent_at)) if pending_key == (gid, oid) => sent_at,
+            _ => return,
+        };
+        self.pending = None;
+        let latency_us =
+            u64::try_from(now.saturating_duration_since(sent_at).as_micros()).unwrap_or(u64::MAX);
+        let stats = self.stats.entry((gid, oid)).or_default();
+        stats.response_count = stats.response_count.saturating_add(1);
+        stats.total_latency_us = stats.total_latency_us.saturating_add(latency_us);
+        stats.max_latency_us = stats.max_latency_us.max(latency_us);
+    }
+
+    /// Returns whether the commands of `gid`/`oid` are throttled after a retry storm.
+    pub fn is_throttled(&self, gid: u8, oid: u8, now: Instant) -> bool {
+        self.retry_storms.is_throttled(gid, oid, now)
+    }
+
+    /// Takes the retry storms detected since the previous call.
+    pub fn take_unreported_storms(&mut self) -> Vec<RetryStorm> {
+        self.retry_storms.take_unreported()
+    }
+
+    /// Gets the metrics of every GID/OID seen so far, ordered by GID then OID.
+    pub fn snapshot(&self) -> Vec<(u8, u8, UciCommandStats)> {
+        self.stats.iter().map(|(&(gid, oid), stats)| (gid, oid, stats.clone())).collect()
+    }
+}
+
+/// UciLogger which updates the shared metrics with the packets it sees before forwarding them to
+/// `inner`.
+pub(crate) struct UciMetricsLogger<L: UciLogger> {
+    inner: L,
+    metrics: Arc<Mutex<UciMetrics>>,
+}
+
+impl<L: UciLogger> UciMetricsLogger<L> {
+    /// Constructs the logger.
+    pub fn new(inner: L, metrics: Arc<Mutex<UciMetrics>>) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+impl<L: UciLogger> UciLogger for UciMetricsLogger<L> {
+    fn log_uci_control_packet(&mut self, packet: UciControlPacket) {
+        let bytes = packet.clone().to_vec();
+        if let Some(header) = bytes.get(..HEADER_LEN) {
+            let message_type = header[0] >> 5;
+            let gid = header[0] & 0x0f;
+            let oid = header[1] & 0x3f;
+            match self.metrics.lock() {
+                Ok(mut metrics) if message_type == MESSAGE_TYPE_COMMAND => {
+                    metrics.on_command(gid, oid, Instant::now())
+                }
+                Ok(mut metrics) if message_type == MESSAGE_TYPE_RESPONSE => {
+                    metrics.on_response(gid, oid, Instant::now())
+                }
+                Ok(_) => {}
+                Err(e) => error!("UCI JNI: failed to lock UCI metrics: {:?}", e),
+            }
+        }
+        self.inner.log_uci_control_packet(packet);
+    }
+
+    fn log_uci_data_packet(&mut self, packet: &UciDataPacket) {
+        self.inner.log_uci_data_packet(packet);
+    }
+
+    fn log_hal_open(&mut self, result: Result<()>) {
+        self.inner.log_hal_open(result);
+    }
+
+    fn log_hal_close(&mut self, result: Result<()>) {
+        // A command outstanding when the HAL is closed will never get its response.
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.pending = None;
+        }
+        self.inner.log_hal_close(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn stats_of(metrics: &UciMetrics, gid: u8, oid: u8) -> UciCommandStats {
+        metrics.stats.get(&(gid, oid)).cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut metrics = UciMetrics::default();
+        let start = Instant::now();
+        metrics.on_command(0x1, 0x3, start);
+        metrics.on_response(0x1, 0x3, start + Duration::from_micros(300));
+        metrics.on_command(0x1, 0x3, start + Duration::from_millis(1));
+        metrics.on_response(0x1, 0x3, start + Duration::from_micros(1_100));
+
+        let stats = stats_of(&metrics, 0x1, 0x3);
+        assert_eq!(stats.command_count, 2);
+        assert_eq!(stats.response_count, 2);
+        assert_eq!(stats.total_latency_us, 400);
+        assert_eq!(stats.max_latency_us, 300);
+        assert_eq!(stats.retry_count, 0);
+        assert_eq!(stats.timeout_count, 0);
+    }
+
+    #[test]
+    fn test_retry_and_timeout() {
+        let mut metrics = UciMetrics::default();
+        let start = Instant::now();
+        // Retried once, then answered: the latency includes the retry.
+        metrics.on_command(0x2, 0x0, start);
+        metrics.on_command(0x2, 0x0, start + Duration::from_millis(10));
+        metrics.on_response(0x2, 0x0, start + Duration::from_millis(12));
+        // Never answered, given up for the next command.
+        metrics.on_command(0x0, 0x2, start + Duration::from_millis(20));
+        metrics.on_command(0x1, 0x0, start + Duration::from_millis(30));
+        // A response to a command which is not outstanding is ignored.
+        metrics.on_response(0x0, 0x2, start + Duration::from_millis(31));
+
+        let stats = stats_of(&metrics, 0x2, 0x0);
+        assert_eq!(stats.command_count, 1);
+        assert_eq!(stats.retry_count, 1);
+        assert_eq!(stats.timeout_count, 1);
+        assert_eq!(stats.response_count, 1);
+        assert_eq!(stats.max_latency_us, 12_000);
+        let stats = stats_of(&metrics, 0x0, 0x2);
+        assert_eq!(stats.command_count, 1);
+        assert_eq!(stats.timeout_count, 1);
+        assert_eq!(stats.response_count, 0);
+
+        let gids_oids =
+            metrics.snapshot().into_iter().map(|(gid, oid, _)| (gid, oid)).collect::<Vec<_>>();
+        assert_eq!(gids_oids, vec![(0x0, 0x2), (0x1, 0x0), (0x2, 0x0)]);
+    }
+
+    #[test]
+    fn test_retry_storm() {
+        let mut metrics = UciMetrics::default();
+        let start = Instant::now();
+        metrics.on_command(0x2, 0x0, start);
+        for i in 1..=crate::retry_storm::STORM_RETRY_THRESHOLD as u32 {
+            metrics.on_command(0x2, 0x0, start + Duration::from_millis(10) * i);
+        }
+        assert_eq!(stats_of(&metrics, 0x2, 0x0).storm_count, 1);
+        assert!(metrics.is_throttled(0x2, 0x0, start + Duration::from_millis(100)));
+        assert_eq!(metrics.take_unreported_storms().len(), 1);
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Proxy of the HAL of a chip over a local TCP listener, letting a host-side instance of uwb_core
+//! drive the physical chip during development, e.g. through `adb forward`.
+//!
+//! The proxy only runs while the on-device stack is quiesced: it is not started while the
+//! dispatcher exists, and it is stopped before the HAL of the chip is opened again. The client
+//! exchanges raw UCI packets with the HAL, delimited by their UCI headers only. One client is
+//! served at a time, and has exclusive access to the HAL until it disconnects: the other
+//! connections are closed as soon as they are accepted.
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener as StdTcpListener};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use uci_hal_android::uci_hal_android::UciHalAndroid;
+use uwb_core::error::{Error, Result};
+use uwb_core::uci::uci_hal::UciHal;
+
+/// Size of the header of the UCI control and data packets.
+const UCI_PACKET_HEADER_SIZE: usize = 4;
+/// Message type of the UCI data packets, whose payload length is 2 octets long.
+const UCI_MT_DATA: u8 = 0;
+/// Size of the buffer of the reads from the client.
+const READ_BUFFER_SIZE: usize = 1024;
+
+lazy_static! {
+    /// The running proxies, by chip ID.
+    static ref UCI_PROXIES: Mutex<HashMap<String, UciProxy>> = Mutex::new(HashMap::new());
+}
+
+/// Splits the byte stream received from the client into UCI packets.
+#[derive(Debug, Default)]
+pub(crate) struct UciPacketFramer {
+    buffer: Vec<u8>,
+}
+
+impl UciPacketFramer {
+    /// Appends the bytes received, and returns the UCI packets they complete.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(bytes);
+        let mut packets = vec![];
+        while let Some(packet_len) = Self::packet_len(&self.buffer) {
+            if self.buffer.len() < packet_len {
+                break;
+            }
+            let rest = self.buffer.split_off(packet_len);
+            packets.push(std::mem::replace(&mut self.buffer, rest));
+        }
+        packets
+    }
+
+    /// Gets the length of the packet at the beginning of `buffer`, once its header is received.
+    fn packet_len(buffer: &[u8]) -> Option<usize> {
+        let header = buffer.get(..UCI_PACKET_HEADER_SIZE)?;
+        let payload_len = if header[0] >> 5 == UCI_MT_DATA {
+            u16::from_le_bytes([header[2], header[3]]) as usize
+        } else {
+            header[3] as usize
+        };
+        Some(UCI_PACKET_HEADER_SIZE + payload_len)
+    }
+}
+
+/// How a client stopped being served.
+enum ClientEnd {
+    Disconnected,
+    Shutdown,
+}
+
+/// A proxy listening on a local port, which stops when dropped.
+struct UciProxy {
+    shutdown_sender: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<()>>,
+    runtime: Runtime,
+}
+
+impl UciProxy {
+    fn new(chip_id: &str, port: u16) -> Result<Self> {
+        let runtime = RuntimeBuilder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("UwbUciProxy")
+            .enable_all()
+            .build()
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+        // Only reachable from the device itself, the host connects through adb.
+        let std_listener = StdTcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|e| {
+                error!("UCI proxy: failed to listen on port {}: {:?}", port, e);
+                Error::BadParameters
+            })?;
+        let listener = {
+            let _guard = runtime.enter();
+            TcpListener::from_std(std_listener).map_err(|_| Error::ForeignFunctionInterface)?
+        };
+        info!("UCI proxy: chip {} listening on {:?}", chip_id, listener.local_addr());
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+        let chip_id = chip_id.to_owned();
+        let task = runtime.spawn(async move {
+            serve(listener, || UciHalAndroid::new(&chip_id), shutdown_receiver).await
+        });
+        Ok(Self { shutdown_sender: Some(shutdown_sender), task: Some(task), runtime })
+    }
+}
+
+impl Drop for UciProxy {
+    fn drop(&mut self) {
+        // The HAL of the chip is closed before returning, so that the stack can open it again.
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let _ = shutdown_sender.send(());
+        }
+        if let Some(task) = self.task.take() {
+            if let Err(e) = self.runtime.block_on(task) {
+                error!("UCI proxy: the server task failed: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Serves the clients of the listener one after the other, until `shutdown_receiver` fires.
+async fn serve<H: UciHal, F: Fn() -> H>(
+    listener: TcpListener,
+    hal_factory: F,
+    mut shutdown_receiver: oneshot::Receiver<()>,
+) {
+    loop {
+        let stream = tokio::select! {
+            _ = &mut shutdown_receiver => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, address)) => {
+                    info!("UCI proxy: serving {}", address);
+                    stream
+                }
+                Err(e) => {
+                    error!("UCI proxy: failed to accept a client: {:?}", e);
+                    continue;
+                }
+            },
+        };
+        let mut hal = hal_factory();
+        let end = serve_client(&mut hal, stream, &listener, &mut shutdown_receiver).await;
+        if let Err(e) = hal.close().await {
+            error!("UCI proxy: failed to close the HAL: {:?}", e);
+        }
+        if let ClientEnd::Shutdown = end {
+            return;
+        }
+    }
+}
+
+/// Forwards the UCI packets between the client and the HAL until either side closes.
+async fn serve_client<H: UciHal>(
+    hal: &mut H,
+    mut stream: TcpStream,
+    listener: &TcpListener,
+    shutdown_receiver: &mut oneshot::Receiver<()>,
+) -> ClientEnd {
+    let (packet_sender, mut packet_receiver) = mpsc::unbounded_channel();
+    if let Err(e) = hal.open(packet_sender).await {
+        error!("UCI proxy: failed to open the HAL: {:?}", e);
+        return ClientEnd::Disconnected;
+    }
+    let mut framer = UciPacketFramer::default();
+    let mut buffer = [0; READ_BUFFER_SIZE];
+    loop {
+        tokio::select! {
+            _ = &mut *shutdown_receiver => return ClientEnd::Shutdown,
+            read = stream.read(&mut buffer) => match read {
+                Ok(0) => {
+                    info!("UCI proxy: the client disconnected");
+                    return ClientEnd::Disconnected;
+                }
+                Ok(len) => {
+                    for packet in framer.push(&buffer[..len]) {
+                        if let Err(e) = hal.send_packet(packet).await {
+                            error!("UCI proxy: failed to send a packet to the HAL: {:?}", e);
+                            return ClientEnd::Disconnected;
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("UCI proxy: failed to read from the client: {:?}", e);
+                    return ClientEnd::Disconnected;
+                }
+            },
+            packet = packet_receiver.recv() => match packet {
+                Some(packet) => {
+                    if let Err(e) = stream.write_all(&packet).await {
+                        error!("UCI proxy: failed to write to the client: {:?}", e);
+                        return ClientEnd::Disconnected;
+                    }
+                }
+                None => {
+                    error!("UCI proxy: the HAL closed");
+                    return ClientEnd::Disconnected;
+                }
+            },
+            accepted = listener.accept() => {
+                // Dropping the stream closes the connection.
+                if let Ok((_, address)) = accepted {
+                    warn!("UCI proxy: rejecting {}, the HAL is used by another client", address);
+                }
+            },
+        }
+    }
+}
+
+/// Starts proxying the HAL of the chip on the local port.
+pub(crate) fn start(chip_id: &str, port: u16) -> Result<()> {
+    let mut proxies = UCI_PROXIES.lock().map_err(|_| Error::Unknown)?;
+    if proxies.contains_key(chip_id) {
+        error!("UCI proxy: chip {} is already proxied", chip_id);
+        return Err(Error::BadParameters);
+    }
+    proxies.insert(chip_id.to_owned(), UciProxy::new(chip_id, port)?);
+    Ok(())
+}
+
+/// Stops proxying the HAL of the chip, if it is proxied. The HAL is closed once this returns.
+pub(crate) fn stop(chip_id: &str) -> Result<()> {
+    let proxy = UCI_PROXIES.lock().map_err(|_| Error::Unknown)?.remove(chip_id);
+    if proxy.is_some() {
+        info!("UCI proxy: chip {} is no longer proxied", chip_id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framer_control_packets() {
+        let mut framer = UciPacketFramer::default();
+        // CORE_GET_DEVICE_INFO_CMD, then the header of a SESSION_INIT_CMD.
+        assert_eq!(
+            framer.push(&[0x20, 0x02, 0x00, 0x00, 0x21, 0x00]),
+            vec![vec![0x20, 0x02, 0, 0]]
+        );
+        assert!(framer.push(&[0x00, 0x05, 0x01, 0x02]).is_empty());
+        assert_eq!(
+            framer.push(&[0x03, 0x04, 0x00]),
+            vec![vec![0x21, 0x00, 0x00, 0x05, 0x01, 0x02, 0x03, 0x04, 0x00]]
+        );
+    }
+
+    #[test]
+    fn test_framer_data_packets() {
+        let mut framer = UciPacketFramer::default();
+        // The payload length of the data packets is on 2 octets, little endian.
+        let mut packet = vec![0x01, 0x00, 0x04, 0x01];
+        packet.extend_from_slice(&[0xab; 0x104]);
+        let (first, second) = packet.split_at(100);
+        assert!(framer.push(first).is_empty());
+        assert_eq!(framer.push(second), vec![packet.clone()]);
+        assert!(framer.buffer.is_empty());
+    }
+}
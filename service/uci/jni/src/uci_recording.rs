@@ -0,0 +1,454 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording of the UCI packets exchanged with the HAL of a chip, and their replay by a UciHal, to
+//! reproduce in the unit tests the interoperability issues captured on a device.
+//!
+//! A recording starts when the HAL is opened, and ends when it is closed or the recording is
+//! stopped, so that its replay drives the stack through the same initialization. The packets are
+//! recorded as they are passed to the HAL, without redaction: the recordings are only taken on
+//! debuggable builds.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use log::error;
+use tokio::sync::mpsc;
+use uwb_core::error::Result;
+use uwb_core::uci::uci_hal::{UciHal, UciHalPacket};
+
+/// Number of packets after which the recording ends.
+pub(crate) const UCI_RECORDING_CAPACITY: usize = 20_000;
+
+const RECORDING_MAGIC: &[u8; 4] = b"UCIR";
+const RECORDING_VERSION: u8 = 1;
+/// Length of the direction, the elapsed time and the length which prefix each packet.
+const RECORD_HEADER_LEN: usize = 13;
+
+/// Whether a packet was passed to the HAL or received from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PacketDirection {
+    ToHal = 0,
+    FromHal = 1,
+}
+
+/// A packet exchanged with the HAL, `elapsed` after it was opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RecordedPacket {
+    pub direction: PacketDirection,
+    pub elapsed: Duration,
+    pub packet: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+enum RecorderState {
+    #[default]
+    Idle,
+    /// Waiting for the HAL to be opened.
+    Armed,
+    Recording {
+        opened_at: Instant,
+    },
+    /// The HAL was closed, or the capacity reached.
+    Ended,
+}
+
+/// The recording of the packets exchanged with the HAL of one chip.
+#[derive(Debug, Default)]
+pub(crate) struct UciRecorder {
+    state: RecorderState,
+    packets: Vec<RecordedPacket>,
+}
+
+impl UciRecorder {
+    /// Discards the previous recording, and records the packets from the next opening of the HAL.
+    pub fn arm(&mut self) {
+        self.state = RecorderState::Armed;
+        self.packets.clear();
+    }
+
+    /// Records that the HAL was opened at `now`.
+    pub fn on_open(&mut self, now: Instant) {
+        if let RecorderState::Armed = self.state {
+            self.state = RecorderState::Recording { opened_at: now };
+        }
+    }
+
+    /// Records that the HAL was closed.
+    pub fn on_close(&mut self) {
+        if let RecorderState::Recording { .. } = self.state {
+            self.state = RecorderState::Ended;
+        }
+    }
+
+    /// Records a packet exchanged with the HAL at `now`.
+    pub fn record(&mut self, direction: PacketDirection, packet: &[u8], now: Instant) {
+        let opened_at = match self.state {
+            RecorderState::Recording { opened_at } => opened_at,
+            _ => return,
+        };
+        self.packets.push(RecordedPacket {
+            direction,
+            elapsed: now.saturating_duration_since(opened_at),
+            packet: packet.to_vec(),
+        });
+        if self.packets.len() == UCI_RECORDING_CAPACITY {
+            error!("UCI recording: {} packets recorded, ending the recording", self.packets.len());
+            self.state = RecorderState::Ended;
+        }
+    }
+
+    /// Stops recording, and takes the packets recorded.
+    pub fn take(&mut self) -> Vec<RecordedPacket> {
+        self.state = RecorderState::Idle;
+        std::mem::take(&mut self.packets)
+    }
+}
+
+/// Serializes the recording: a magic number and a version, then every packet as its direction,
+/// its elapsed time in microseconds and its length, as little endian integers, followed by its
+/// bytes.
+pub(crate) fn encode_recording(packets: &[RecordedPacket]) -> Vec<u8> {
+    let mut bytes = RECORDING_MAGIC.to_vec();
+    bytes.push(RECORDING_VERSION);
+    for recorded in packets {
+        let elapsed_us = u64::try_from(recorded.elapsed.as_micros()).unwrap_or(u64::MAX);
+        bytes.push(recorded.direction as u8);
+        bytes.extend_from_slice(&elapsed_us.to_le_bytes());
+        bytes.extend_from_slice(&(recorded.packet.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&recorded.packet);
+    }
+    bytes
+}
+
+/// Writes the recording to the file at `path`, replacing it.
+pub(crate) fn write_recording(path: &Path, packets: &[RecordedPacket]) -> io::Result<()> {
+    fs::write(path, encode_recording(packets))
+}
+
+fn lock_recorder(recorder: &Mutex<UciRecorder>) -> Option<MutexGuard<UciRecorder>> {
+    match recorder.lock() {
+        Ok(recorder) => Some(recorder),
+        Err(e) => {
+            error!("UCI JNI: failed to lock UCI recorder: {:?}", e);
+            None
+        }
+    }
+}
+
+/// UciHal which records the packets exchanged with `inner` into the shared recorder.
+pub(crate) struct RecordingUciHal<H: UciHal> {
+    inner: H,
+    recorder: Arc<Mutex<UciRecorder>>,
+}
+
+impl<H: UciHal> RecordingUciHal<H> {
+    /// Constructs the HAL.
+    pub fn new(inner: H, recorder: Arc<Mutex<UciRecorder>>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl<H: UciHal> UciHal for RecordingUciHal<H> {
+    async fn open(&mut self, packet_sender: mpsc::UnboundedSender<UciHalPacket>) -> Result<()> {
+        if let Some(mut recorder) = lock_recorder(&self.recorder) {
+            recorder.on_open(Instant::now());
+        }
+        // The forwarding ends once the HAL drops its sender, when it is closed.
+        let (recorded_sender, mut recorded_receiver) = mpsc::unbounded_channel::<UciHalPacket>();
+        let recorder = self.recorder.clone();
+        tokio::spawn(async move {
+            while let Some(packet) = recorded_receiver.recv().await {
+                if let Some(mut recorder) = lock_recorder(&recorder) {
+                    recorder.record(PacketDirection::FromHal, &packet, Instant::now());
+                }
+                if packet_sender.send(packet).is_err() {
+                    break;
+                }
+            }
+        });
+        self.inner.open(recorded_sender).await
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        let result = self.inner.close().await;
+        if let Some(mut recorder) = lock_recorder(&self.recorder) {
+            recorder.on_close();
+        }
+        result
+    }
+
+    async fn send_packet(&mut self, packet: UciHalPacket) -> Result<()> {
+        if let Some(mut recorder) = lock_recorder(&self.recorder) {
+            recorder.record(PacketDirection::ToHal, &packet, Instant::now());
+        }
+        self.inner.send_packet(packet).await
+    }
+
+    async fn notify_session_initialized(&mut self, session_id: u32) -> Result<()> {
+        self.inner.notify_session_initialized(session_id).await
+    }
+}
+
+/// Replay of the recordings, for the unit tests.
+#[cfg(test)]
+pub(crate) mod replay {
+    use super::*;
+
+    use std::collections::VecDeque;
+    use std::io::ErrorKind;
+
+    use uwb_core::error::Error;
+
+    /// Parses a recording serialized by encode_recording.
+    pub(crate) fn decode_recording(bytes: &[u8]) -> io::Result<Vec<RecordedPacket>> {
+        let invalid = |message| io::Error::new(ErrorKind::InvalidData, message);
+        let mut rest = match bytes.strip_prefix(&RECORDING_MAGIC[..]) {
+            Some([RECORDING_VERSION, rest @ ..]) => rest,
+            _ => return Err(invalid("not a UCI recording")),
+        };
+        let mut packets = vec![];
+        while !rest.is_empty() {
+            if rest.len() < RECORD_HEADER_LEN {
+                return Err(invalid("truncated UCI recording"));
+            }
+            let direction = match rest[0] {
+                0 => PacketDirection::ToHal,
+                1 => PacketDirection::FromHal,
+                _ => return Err(invalid("invalid packet direction")),
+            };
+            let elapsed_us = u64::from_le_bytes(rest[1..9].try_into().unwrap());
+            let len = u32::from_le_bytes(rest[9..13].try_into().unwrap()) as usize;
+            let packet = rest
+                .get(RECORD_HEADER_LEN..RECORD_HEADER_LEN + len)
+                .ok_or_else(|| invalid("truncated UCI recording"))?;
+            packets.push(RecordedPacket {
+                direction,
+                elapsed: Duration::from_micros(elapsed_us),
+                packet: packet.to_vec(),
+            });
+            rest = &rest[RECORD_HEADER_LEN + len..];
+        }
+        Ok(packets)
+    }
+
+    /// Reads the recording written to the file at `path`.
+    pub(crate) fn read_recording(path: &Path) -> io::Result<Vec<RecordedPacket>> {
+        decode_recording(&fs::read(path)?)
+    }
+
+    /// Returns whether two packets have the same message type, GID and OID, or are both data
+    /// packets of the same type. Their payloads may differ, e.g. by the session handles.
+    fn is_same_message(packet: &[u8], other: &[u8]) -> bool {
+        match (packet.get(..2), other.get(..2)) {
+            (Some(header), Some(other_header)) => {
+                header[0] & 0xef == other_header[0] & 0xef
+                    && header[1] & 0x3f == other_header[1] & 0x3f
+            }
+            _ => false,
+        }
+    }
+
+    /// UciHal replaying a recording: the packets received from the HAL are delivered with their
+    /// recorded timing, relative to the packet passed to the HAL which they follow. The packets
+    /// passed to the HAL must come in the recorded order, the replay fails on the first unexpected
+    /// one.
+    pub(crate) struct ReplayUciHal {
+        packets: VecDeque<RecordedPacket>,
+        feeder: Option<mpsc::UnboundedSender<(Instant, UciHalPacket)>>,
+    }
+
+    impl ReplayUciHal {
+        /// Constructs the HAL.
+        pub fn new(packets: Vec<RecordedPacket>) -> Self {
+            Self { packets: packets.into(), feeder: None }
+        }
+
+        /// Schedules the packets received from the HAL up to the next packet passed to it. They
+        /// are delivered relative to `anchor_at`, which the recording reached at `anchor_elapsed`.
+        fn schedule_received_packets(&mut self, anchor_elapsed: Duration, anchor_at: Instant) {
+            let feeder = match self.feeder.as_ref() {
+                Some(feeder) => feeder,
+                None => return,
+            };
+            while let Some(recorded) = self.packets.front() {
+                if recorded.direction == PacketDirection::ToHal {
+                    break;
+                }
+                let due = anchor_at + recorded.elapsed.saturating_sub(anchor_elapsed);
+                let _ = feeder.send((due, self.packets.pop_front().unwrap().packet));
+            }
+        }
+    }
+
+    #[async_trait]
+    impl UciHal for ReplayUciHal {
+        async fn open(&mut self, packet_sender: mpsc::UnboundedSender<UciHalPacket>) -> Result<()> {
+            // A single task delivers the packets, so that they keep the recorded order.
+            let (feeder, mut receiver) = mpsc::unbounded_channel::<(Instant, UciHalPacket)>();
+            tokio::spawn(async move {
+                while let Some((due, packet)) = receiver.recv().await {
+                    tokio::time::sleep_until(tokio::time::Instant::from_std(due)).await;
+                    if packet_sender.send(packet).is_err() {
+                        break;
+                    }
+                }
+            });
+            self.feeder = Some(feeder);
+            self.schedule_received_packets(Duration::ZERO, Instant::now());
+            Ok(())
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            self.feeder = None;
+            Ok(())
+        }
+
+        async fn send_packet(&mut self, packet: UciHalPacket) -> Result<()> {
+            let anchor_elapsed = match self.packets.front() {
+                Some(recorded)
+                    if recorded.direction == PacketDirection::ToHal
+                        && is_same_message(&recorded.packet, &packet) =>
+                {
+                    recorded.elapsed
+                }
+                expected => {
+                    error!(
+                        "UCI replay: unexpected packet {:02x?}, expected {:?}",
+                        packet, expected
+                    );
+                    return Err(Error::Unknown);
+                }
+            };
+            self.packets.pop_front();
+            self.schedule_received_packets(anchor_elapsed, Instant::now());
+            Ok(())
+        }
+
+        async fn notify_session_initialized(&mut self, _session_id: u32) -> Result<()> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::replay::*;
+    use super::*;
+
+    use std::io::ErrorKind;
+
+    const CORE_DEVICE_STATUS_NTF: [u8; 5] = [0x60, 0x01, 0x00, 0x01, 0x01];
+    const CORE_GET_DEVICE_INFO_CMD: [u8; 4] = [0x20, 0x02, 0x00, 0x00];
+    const CORE_GET_DEVICE_INFO_RSP: [u8; 5] = [0x40, 0x02, 0x00, 0x01, 0x00];
+    const SESSION_INIT_CMD: [u8; 9] = [0x21, 0x00, 0x00, 0x05, 0x01, 0x00, 0x00, 0x00, 0x00];
+
+    fn recorded(direction: PacketDirection, elapsed_ms: u64, packet: &[u8]) -> RecordedPacket {
+        RecordedPacket {
+            direction,
+            elapsed: Duration::from_millis(elapsed_ms),
+            packet: packet.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_recorder_starts_on_open() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut recorder = UciRecorder::default();
+        recorder.record(PacketDirection::ToHal, &CORE_GET_DEVICE_INFO_CMD, start);
+        recorder.arm();
+        recorder.record(PacketDirection::ToHal, &CORE_GET_DEVICE_INFO_CMD, start);
+        recorder.on_open(start + ms(1));
+        recorder.record(PacketDirection::FromHal, &CORE_DEVICE_STATUS_NTF, start + ms(3));
+        recorder.on_close();
+        recorder.record(PacketDirection::ToHal, &CORE_GET_DEVICE_INFO_CMD, start + ms(4));
+        // The recording does not resume when the HAL is opened again.
+        recorder.on_open(start + ms(5));
+        recorder.record(PacketDirection::ToHal, &CORE_GET_DEVICE_INFO_CMD, start + ms(6));
+        assert_eq!(
+            recorder.take(),
+            vec![recorded(PacketDirection::FromHal, 2, &CORE_DEVICE_STATUS_NTF)]
+        );
+        assert_eq!(recorder.take(), vec![]);
+    }
+
+    #[test]
+    fn test_recorder_capacity() {
+        let start = Instant::now();
+        let mut recorder = UciRecorder::default();
+        recorder.arm();
+        recorder.on_open(start);
+        for _ in 0..UCI_RECORDING_CAPACITY + 5 {
+            recorder.record(PacketDirection::ToHal, &CORE_GET_DEVICE_INFO_CMD, start);
+        }
+        assert_eq!(recorder.take().len(), UCI_RECORDING_CAPACITY);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("uwb_uci_recording_{}", std::process::id()));
+        let packets = vec![
+            recorded(PacketDirection::ToHal, 1, &CORE_GET_DEVICE_INFO_CMD),
+            recorded(PacketDirection::FromHal, 3, &CORE_GET_DEVICE_INFO_RSP),
+            recorded(PacketDirection::FromHal, 4, &[]),
+        ];
+        write_recording(&path, &packets).unwrap();
+        assert_eq!(read_recording(&path).unwrap(), packets);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_decode_invalid_recording() {
+        assert!(decode_recording(b"UCIX\x01").is_err());
+        assert!(decode_recording(b"UCIR\x02").is_err());
+        assert_eq!(decode_recording(b"UCIR\x01").unwrap(), vec![]);
+        let mut bytes =
+            encode_recording(&[recorded(PacketDirection::ToHal, 1, &CORE_GET_DEVICE_INFO_CMD)]);
+        bytes.pop();
+        assert_eq!(decode_recording(&bytes).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_replay() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let mut hal = ReplayUciHal::new(vec![
+                recorded(PacketDirection::FromHal, 0, &CORE_DEVICE_STATUS_NTF),
+                recorded(PacketDirection::ToHal, 10, &CORE_GET_DEVICE_INFO_CMD),
+                recorded(PacketDirection::FromHal, 12, &CORE_GET_DEVICE_INFO_RSP),
+                recorded(PacketDirection::ToHal, 20, &SESSION_INIT_CMD),
+            ]);
+            let (packet_sender, mut packet_receiver) = mpsc::unbounded_channel();
+            hal.open(packet_sender).await.unwrap();
+            assert_eq!(packet_receiver.recv().await.unwrap(), CORE_DEVICE_STATUS_NTF);
+
+            let sent_at = Instant::now();
+            hal.send_packet(CORE_GET_DEVICE_INFO_CMD.to_vec()).await.unwrap();
+            assert_eq!(packet_receiver.recv().await.unwrap(), CORE_GET_DEVICE_INFO_RSP);
+            assert!(sent_at.elapsed() >= Duration::from_millis(2));
+
+            // The replay fails once the stack diverges from the recording.
+            assert!(hal.send_packet(CORE_GET_DEVICE_INFO_CMD.to_vec()).await.is_err());
+            let mut session_init_cmd = SESSION_INIT_CMD.to_vec();
+            session_init_cmd[4] = 0x02;
+            assert!(hal.send_packet(session_init_cmd).await.is_ok());
+        });
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The OIDs of the vendor commands of the ANDROID GID which are not defined by the UCI
+//! specification, but by the vendor of each chip. They are read from the vendor configuration and
+//! set through nativeSetVendorCommandOid; a command whose OID is not set is not supported by the
+//! chip.
+
+use std::collections::HashMap;
+
+use uwb_core::error::{Error, Result};
+
+/// The ANDROID vendor GID.
+pub(crate) const GID_ANDROID: u8 = 0xc;
+
+/// The largest OID, encoded on 6 bits.
+const MAX_OID: u8 = 0x3f;
+
+/// The vendor commands whose OID is defined by the vendor of the chip. The values are the ones of
+/// the VENDOR_COMMAND_* constants of NativeUwbManager.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum VendorCommand {
+    /// ANDROID_SET_CALIBRATION, see calibration_store.
+    SetCalibration = 0,
+}
+
+impl TryFrom<i32> for VendorCommand {
+    type Error = Error;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(Self::SetCalibration),
+            _ => Err(Error::BadParameters),
+        }
+    }
+}
+
+/// The OIDs of the vendor commands supported by one chip.
+#[derive(Debug, Default)]
+pub(crate) struct VendorCommandOids {
+    oids: HashMap<VendorCommand, u8>,
+}
+
+impl VendorCommandOids {
+    /// Sets the OID of the command on the chip, or marks the command as not supported when it is
+    /// None.
+    pub fn set_oid(&mut self, command: VendorCommand, oid: Option<u8>) -> Result<()> {
+        match oid {
+            Some(oid) if oid > MAX_OID => return Err(Error::BadParameters),
+            Some(oid) => {
+                self.oids.insert(command, oid);
+            }
+            None => {
+                self.oids.remove(&command);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the OID of the command, None if the chip does not support it.
+    pub fn oid(&self, command: VendorCommand) -> Option<u8> {
+        self.oids.get(&command).copied()
+    }
+
+    /// Returns whether the GID and OID of a UCI message are the ones of the command.
+    pub fn is_command(&self, command: VendorCommand, gid: u8, oid: u8) -> bool {
+        gid == GID_ANDROID && self.oid(command) == Some(oid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_oid() {
+        let mut oids = VendorCommandOids::default();
+        assert_eq!(oids.oid(VendorCommand::SetCalibration), None);
+        assert!(!oids.is_command(VendorCommand::SetCalibration, GID_ANDROID, 0x21));
+
+        oids.set_oid(VendorCommand::SetCalibration, Some(0x21)).unwrap();
+        assert_eq!(oids.oid(VendorCommand::SetCalibration), Some(0x21));
+        assert!(oids.is_command(VendorCommand::SetCalibration, GID_ANDROID, 0x21));
+        assert!(!oids.is_command(VendorCommand::SetCalibration, 0xe, 0x21));
+
+        // An OID out of range leaves the previous one.
+        assert!(oids.set_oid(VendorCommand::SetCalibration, Some(0x40)).is_err());
+        assert_eq!(oids.oid(VendorCommand::SetCalibration), Some(0x21));
+
+        oids.set_oid(VendorCommand::SetCalibration, None).unwrap();
+        assert_eq!(oids.oid(VendorCommand::SetCalibration), None);
+    }
+
+    #[test]
+    fn test_vendor_command_from_i32() {
+        assert_eq!(VendorCommand::try_from(0).unwrap(), VendorCommand::SetCalibration);
+        assert!(VendorCommand::try_from(1).is_err());
+        assert!(VendorCommand::try_from(-1).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2024, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Routing of the vendor notifications by GID, so that e.g. the CCC, radar vendor and OEM
+//! diagnostics notifications reach distinct Java callbacks instead of
+//! onVendorUciNotificationReceived.
+
+use std::collections::HashMap;
+
+use uwb_core::error::{Error, Result};
+
+/// Signature of the Java callbacks the vendor notifications are routed to, called with the OID,
+/// the payload and the chip ID.
+pub(crate) const VENDOR_CALLBACK_SIGNATURE: &str = "(I[BLjava/lang/String;)V";
+
+/// The largest GID, encoded on 4 bits.
+const MAX_GID: u32 = 0xf;
+
+/// Returns whether `name` can name a Java method.
+fn is_java_method_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The Java callbacks registered for the vendor notifications of one chip, by GID.
+#[derive(Debug, Default)]
+pub(crate) struct VendorNotificationRouter {
+    java_callbacks: HashMap<u32, String>,
+}
+
+impl VendorNotificationRouter {
+    /// Routes the vendor notifications of the GID to the Java callback `callback_name` of the
+    /// callback object, or back to onVendorUciNotificationReceived when it is None.
+    pub fn set_java_callback(&mut self, gid: u32, callback_name: Option<String>) -> Result<()> {
+        if gid > MAX_GID {
+            return Err(Error::BadParameters);
+        }
+        match callback_name {
+            Some(callback_name) if is_java_method_name(&callback_name) => {
+                self.java_callbacks.insert(gid, callback_name);
+            }
+            Some(_) => return Err(Error::BadParameters),
+            None => {
+                self.java_callbacks.remove(&gid);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the Java callback the vendor notifications of the GID are routed to, None if they go
+    /// to onVendorUciNotificationReceived.
+    pub fn java_callback(&self, gid: u32) -> Option<&str> {
+        self.java_callbacks.get(&gid).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_java_method_name() {
+        assert!(is_java_method_name("onCccVendorNotificationReceived"));
+        assert!(is_java_method_name("_on$Diagnostics2"));
+        assert!(!is_java_method_name(""));
+        assert!(!is_java_method_name("2onVendor"));
+        assert!(!is_java_method_name("onVendor(I)V"));
+        assert!(!is_java_method_name("com/android/Foo"));
+    }
+
+    #[test]
+    fn test_java_callback() {
+        let mut router = VendorNotificationRouter::default();
+        assert_eq!(router.java_callback(0xc), None);
+
+        router.set_java_callback(0xc, Some("onCccVendorNotificationReceived".to_owned())).unwrap();
+        router.set_java_callback(0xe, Some("onOemDiagnosticsReceived".to_owned())).unwrap();
+        assert_eq!(router.java_callback(0xc), Some("onCccVendorNotificationReceived"));
+        assert_eq!(router.java_callback(0xe), Some("onOemDiagnosticsReceived"));
+        assert_eq!(router.java_callback(0xf), None);
+
+        router.set_java_callback(0xc, None).unwrap();
+        assert_eq!(router.java_callback(0xc), None);
+        assert_eq!(router.java_callback(0xe), Some("onOemDiagnosticsReceived"));
+    }
+
+    #[test]
+    fn test_invalid_route() {
+        let mut router = VendorNotificationRouter::default();
+        assert!(router.set_java_callback(0x10, Some("onVendor".to_owned())).is_err());
+        assert!(router.set_java_callback(0xc, Some("onVendor([B)V".to_owned())).is_err());
+        assert_eq!(router.java_callback(0xc), None);
+    }
+}